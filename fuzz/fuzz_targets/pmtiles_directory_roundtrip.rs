@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+	let _ = versatiles_container::fuzzing::roundtrip_pmtiles_directory(data);
+});
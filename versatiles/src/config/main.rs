@@ -13,6 +13,10 @@
 //!   port: 8080
 //!   minimal_recompression: false   # optional
 //!   disable_api: false             # optional
+//!   watch: false                   # optional
+//!   tile_timeout: 10000            # optional, in milliseconds
+//!   index_cache: ./index_cache     # optional
+//!   codec_pool_size: 8             # optional, defaults to the number of CPUs
 //!
 //! # Optional Cross-Origin Resource Sharing (CORS) settings
 //! cors:
@@ -21,6 +25,14 @@
 //!     - "*.example.net"
 //!   max_age_seconds: 86400         # optional
 //!
+//! # Optional per-client request quotas
+//! quota:
+//!   requests_per_minute: 600
+//!   burst: 100                     # optional
+//!   key_header: X-API-Key          # optional
+//!   api_keys: ["secret-key"]       # required with key_header
+//!   ip_header: X-Forwarded-For     # optional
+//!
 //! # Optional extra HTTP response headers
 //! extra_response_headers:
 //!   Cache-Control: "public, max-age=86400, immutable"
@@ -49,7 +61,7 @@
 //! use versatiles::Config;
 //! let cfg = Config::from_string("tiles: [[\"osm\", \"osm.versatiles\"]]").unwrap();
 //! ```
-use super::{CorsConfig, ServerConfig, StaticSourceConfig, TileSourceConfig};
+use super::{CorsConfig, QuotaConfig, ServerConfig, StaticSourceConfig, TileSourceConfig};
 use anyhow::Result;
 use serde::Deserialize;
 use std::{
@@ -83,6 +95,10 @@ pub struct Config {
 	#[serde(default)]
 	pub cors: CorsConfig,
 
+	/// Optional per-client request quotas
+	#[serde(default)]
+	pub quota: QuotaConfig,
+
 	/// Optional extra HTTP response headers to add to every response
 	/// For example, cache control or timing headers
 	#[serde(default)]
@@ -185,12 +201,17 @@ mod tests {
 					ip: Some("127.0.0.1".parse().unwrap()),
 					port: Some(51234),
 					minimal_recompression: Some(true),
-					disable_api: Some(true)
+					disable_api: Some(true),
+					watch: None,
+					tile_timeout: None,
+					index_cache: None,
+					codec_pool_size: None
 				},
 				cors: CorsConfig {
 					allowed_origins: vec!["https://example.org".to_string(), "*.other-example.org".to_string()],
 					max_age_seconds: Some(86400)
 				},
+				quota: QuotaConfig::default(),
 				extra_response_headers: [
 					("Timing-Allow-Origin", "*"),
 					("CDN-Cache-Control", "max-age=604800"),
@@ -226,7 +247,7 @@ mod tests {
 			cfg.unwrap_err().chain().map(|e| e.to_string()).collect::<Vec<_>>(),
 			vec![
 				"parsing config from string (YAML)",
-				"server: unknown field `pi`, expected one of `ip`, `port`, `minimal_recompression`, `disable_api` at line 2 column 3"
+				"server: unknown field `pi`, expected one of `ip`, `port`, `minimal_recompression`, `disable_api`, `watch`, `tile_timeout`, `index_cache`, `codec_pool_size` at line 2 column 3"
 			]
 		);
 	}
@@ -243,11 +264,22 @@ mod tests {
 					port: Some(8080,),
 					minimal_recompression: Some(false,),
 					disable_api: Some(false,),
+					watch: Some(false,),
+					tile_timeout: Some(10000,),
+					index_cache: Some("./index_cache".to_string()),
+					codec_pool_size: Some(8),
 				},
 				cors: CorsConfig {
 					allowed_origins: vec!["https://example.org".to_string(), "*.example.net".to_string()],
 					max_age_seconds: Some(86400),
 				},
+				quota: QuotaConfig {
+					requests_per_minute: Some(600),
+					burst: Some(100),
+					key_header: Some("X-API-Key".to_string()),
+					api_keys: vec!["secret-key".to_string()],
+					ip_header: Some("X-Forwarded-For".to_string()),
+				},
 				extra_response_headers: [
 					("CDN-Cache-Control", "max-age=604800"),
 					("Cache-Control", "public, max-age=86400, immutable"),
@@ -5,6 +5,7 @@
 //! - [`Config`](crate::config::Config): top-level configuration loader and YAML parser
 //! - [`ServerConfig`](crate::config::ServerConfig): network and API settings
 //! - [`Cors`](crate::config::cors::Cors): CORS policy configuration
+//! - [`QuotaConfig`](crate::config::QuotaConfig): per-client request quotas
 //! - [`StaticSourceConfig`](crate::config::StaticSourceConfig): static file sources
 //! - [`TileSourceConfig`](crate::config::TileSourceConfig): tile data sources
 //!
@@ -13,12 +14,14 @@
 
 mod cors;
 mod main;
+mod quota;
 mod server;
 mod static_source;
 mod tile_source;
 
 pub use cors::CorsConfig;
 pub use main::Config;
+pub use quota::QuotaConfig;
 pub use server::ServerConfig;
 pub use static_source::StaticSourceConfig;
 pub use tile_source::TileSourceConfig;
@@ -0,0 +1,66 @@
+//! Per-client request quotas for the VersaTiles server.
+//!
+//! Quotas protect public instances from scrapers: every client may send a limited number
+//! of requests per minute, with short bursts above that rate. Clients that exceed their
+//! quota get `429 Too Many Requests` with a `Retry-After` header.
+//!
+//! Clients are identified by an API key header if configured and the key is one of `api_keys`,
+//! otherwise by IP.
+//!
+//! # Example YAML
+//! ```yaml
+//! quota:
+//!   requests_per_minute: 600
+//!   burst: 100
+//!   key_header: X-API-Key
+//!   api_keys:
+//!     - secret-key
+//!   ip_header: X-Forwarded-For
+//! ```
+use serde::Deserialize;
+use versatiles_derive::ConfigDoc;
+
+/// Request quota configuration.
+///
+/// - `requests_per_minute`: Sustained number of requests per client. Quotas are disabled if not set.
+/// - `burst`: Number of requests a client may send at once before being throttled.
+/// - `key_header`: Header with an API key that identifies the client.
+/// - `api_keys`: Valid API keys. Other keys are ignored, so clients can't get new quotas by changing their key.
+/// - `ip_header`: Header with the client IP, e.g. when running behind a reverse proxy.
+#[derive(Default, Debug, Clone, Deserialize, PartialEq, ConfigDoc)]
+#[serde(deny_unknown_fields)]
+pub struct QuotaConfig {
+	/// Optional number of requests per minute that each client may send
+	/// Defaults to no quota
+	#[serde(default)]
+	#[config_demo("600")]
+	pub requests_per_minute: Option<u32>,
+
+	/// Optional number of requests that a client may send in a burst
+	/// Defaults to `requests_per_minute`
+	#[serde(default)]
+	#[config_demo("100")]
+	pub burst: Option<u32>,
+
+	/// Optional name of a request header with an API key
+	/// If it contains one of `api_keys`, quotas are counted per key instead of per IP
+	#[serde(default)]
+	#[config_demo("X-API-Key")]
+	pub key_header: Option<String>,
+
+	/// List of valid API keys, required if `key_header` is set
+	/// Requests with other keys are counted per IP
+	#[serde(default)]
+	#[config_demo(
+		r#"
+    - "secret-key""#
+	)]
+	pub api_keys: Vec<String>,
+
+	/// Optional name of a request header with the client IP, like `X-Forwarded-For`
+	/// Only use it behind a reverse proxy that sets this header, as clients can fake it.
+	/// Defaults to the IP of the TCP connection
+	#[serde(default)]
+	#[config_demo("X-Forwarded-For")]
+	pub ip_header: Option<String>,
+}
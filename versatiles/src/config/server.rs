@@ -11,6 +11,10 @@
 //!   port: 8080
 //!   minimal_recompression: false
 //!   disable_api: false
+//!   watch: false
+//!   tile_timeout: 10000
+//!   index_cache: ./index_cache
+//!   codec_pool_size: 8
 //! ```
 //!
 //! All fields are optional. Defaults are applied when values are not specified.
@@ -30,6 +34,10 @@ use versatiles_derive::ConfigDoc;
 /// * `port` — Optional port to listen on (default `8080`).
 /// * `minimal_recompression` — If `true`, prefer faster compression over smaller output.
 /// * `disable_api` — If `true`, disable the `/api` endpoints entirely.
+/// * `watch` — If `true`, reload tile sources when their files change on disk.
+/// * `tile_timeout` — Maximum time in milliseconds to read a single tile. Slower tiles are answered with `504`.
+/// * `index_cache` — Directory to cache the indexes of remote containers in, so restarts don't have to download them again.
+/// * `codec_pool_size` — Maximum number of tiles decoded or encoded at the same time on blocking threads (default: number of CPUs).
 #[derive(Debug, Default, Clone, Deserialize, PartialEq, ConfigDoc)]
 #[serde(deny_unknown_fields)]
 pub struct ServerConfig {
@@ -56,6 +64,32 @@ pub struct ServerConfig {
 	#[serde()]
 	#[config_demo("false")]
 	pub disable_api: Option<bool>,
+
+	/// Optional flag to reload tile sources when their files change on disk
+	/// Defaults to false
+	#[serde()]
+	#[config_demo("false")]
+	pub watch: Option<bool>,
+
+	/// Optional maximum time in milliseconds to read a single tile
+	/// Defaults to no limit
+	#[serde()]
+	#[config_demo("10000")]
+	pub tile_timeout: Option<u64>,
+
+	/// Optional directory to cache the indexes of remote tile containers in
+	/// Cached indexes are used until the ETag, Last-Modified or size of a container changes
+	/// Defaults to no cache
+	#[serde()]
+	#[config_demo("./index_cache")]
+	pub index_cache: Option<String>,
+
+	/// Optional maximum number of tiles decoded or encoded at the same time, e.g. when a pipeline renders images
+	/// Decoding and encoding run on blocking threads, so they do not slow down other requests
+	/// Defaults to the number of CPUs
+	#[serde()]
+	#[config_demo("8")]
+	pub codec_pool_size: Option<usize>,
 }
 
 /// Helper methods for merging partial `ServerConfig` values.
@@ -83,4 +117,24 @@ impl ServerConfig {
 			self.disable_api = *disable_api;
 		}
 	}
+	pub fn override_optional_watch(&mut self, watch: &Option<bool>) {
+		if watch.is_some() {
+			self.watch = *watch;
+		}
+	}
+	pub fn override_optional_tile_timeout(&mut self, tile_timeout: &Option<u64>) {
+		if tile_timeout.is_some() {
+			self.tile_timeout = *tile_timeout;
+		}
+	}
+	pub fn override_optional_index_cache(&mut self, index_cache: &Option<String>) {
+		if index_cache.is_some() {
+			self.index_cache = index_cache.clone();
+		}
+	}
+	pub fn override_optional_codec_pool_size(&mut self, codec_pool_size: &Option<usize>) {
+		if codec_pool_size.is_some() {
+			self.codec_pool_size = *codec_pool_size;
+		}
+	}
 }
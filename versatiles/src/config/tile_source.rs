@@ -28,6 +28,7 @@ use versatiles_derive::{ConfigDoc, context};
 /// - `name` — Optional name under which the tiles are exposed (defaults to the
 ///   last part of the file name, e.g. `"osm"` for `"osm.versatiles"`).
 /// - `path` — Local file path or remote URL pointing to the tile source.
+/// - `index` — Optional attribute index used by the `/query` endpoint.
 ///
 /// Relative paths are resolved against the configuration file’s directory
 /// by [`TileSourceConfig::resolve_paths`].
@@ -43,6 +44,10 @@ pub struct TileSourceConfig {
 	/// Can be a local file or remote URL.
 	#[config_demo("osm.versatiles")]
 	pub path: DataLocation,
+
+	/// Optional path to an attribute index built with `versatiles index`
+	/// Enables feature searches via `/query?layer=…&key=…&value=…`
+	pub index: Option<DataLocation>,
 }
 
 impl TileSourceConfig {
@@ -55,7 +60,11 @@ impl TileSourceConfig {
 	/// Returns an error if path resolution fails (e.g., invalid URL format).
 	#[context("resolving tile source paths relative to base path '{}'", base_path)]
 	pub fn resolve_paths(&mut self, base_path: &DataLocation) -> Result<()> {
-		self.path.resolve(base_path)
+		self.path.resolve(base_path)?;
+		if let Some(index) = &mut self.index {
+			index.resolve(base_path)?;
+		}
+		Ok(())
 	}
 }
 
@@ -78,12 +87,15 @@ impl<'de> Deserialize<'de> for TileSourceConfig {
 		struct TileSourceConfigHelper {
 			pub name: Option<String>,
 			pub path: String,
+			#[serde(default)]
+			pub index: Option<String>,
 		}
 
 		let helper = TileSourceConfigHelper::deserialize(deserializer)?;
 		Ok(TileSourceConfig {
 			name: helper.name,
 			path: DataLocation::from(helper.path),
+			index: helper.index.map(DataLocation::from),
 		})
 	}
 }
@@ -94,6 +106,7 @@ impl From<(&str, &str)> for TileSourceConfig {
 		Self {
 			name: Some(name.to_string()),
 			path: DataLocation::from(path),
+			index: None,
 		}
 	}
 }
@@ -0,0 +1,422 @@
+//! Sidecar index that maps feature IDs and attribute values of vector tiles to tile coordinates.
+//!
+//! [`AttributeIndex::build`] reads all tiles of one zoom level and records, for every feature,
+//! in which tiles its ID (key `$id`) and its attribute values occur. The index is stored as a
+//! small text file next to the container (see [`AttributeIndex::sidecar_path`]), so a server can
+//! answer simple searches like "where is the street with `name=Unter den Linden`" without a
+//! separate database.
+//!
+//! The file format is line based: a header `#versatiles-attribute-index <level>` followed by one
+//! line per entry, `layer \t key \t value \t z/x/y,z/x/y,…`. Tabs, newlines and backslashes in
+//! layer names, keys and values are escaped with a backslash.
+//!
+//! ```rust
+//! use versatiles::{AttributeIndex, container::*};
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let registry = ContainerRegistry::default();
+//!     let reader = registry.open_reader("../testdata/berlin.mbtiles").await?;
+//!
+//!     let index = AttributeIndex::build(reader.as_ref(), Some(10), Some(&["name".to_string()])).await?;
+//!     let coords = index.query("place_labels", "name", "Berlin");
+//!     println!("found in {} tiles", coords.len());
+//!     Ok(())
+//! }
+//! ```
+
+use anyhow::{Result, anyhow, bail, ensure};
+use futures::{StreamExt, TryStreamExt, stream};
+use std::{
+	collections::BTreeMap,
+	fmt::Write as _,
+	path::{Path, PathBuf},
+};
+use versatiles_container::{Tile, TilesReaderTrait};
+use versatiles_core::{
+	TileCoord, TileFormat,
+	json::{JsonArray, JsonObject, JsonValue},
+};
+use versatiles_derive::context;
+use versatiles_geometry::{
+	geo::GeoFeature,
+	vector_tile::{TileProjection, VectorTileLayer},
+};
+
+/// Key under which feature IDs are indexed.
+pub const ATTRIBUTE_INDEX_ID_KEY: &str = "$id";
+
+const HEADER: &str = "#versatiles-attribute-index";
+
+/// Maximum number of tiles [`AttributeIndex::query_features`] reads at the same time.
+const QUERY_CONCURRENCY: usize = 8;
+
+/// Maps `(layer, key, value)` to the coordinates of all tiles containing a matching feature.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AttributeIndex {
+	level: u8,
+	entries: BTreeMap<(String, String, String), Vec<TileCoord>>,
+}
+
+impl AttributeIndex {
+	/// Creates an empty index for tiles of zoom `level`.
+	#[must_use]
+	pub fn new(level: u8) -> AttributeIndex {
+		AttributeIndex {
+			level,
+			entries: BTreeMap::new(),
+		}
+	}
+
+	/// Zoom level of the indexed tiles.
+	#[must_use]
+	pub fn level(&self) -> u8 {
+		self.level
+	}
+
+	/// Number of distinct `(layer, key, value)` entries.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Returns `true` if the index has no entries.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Returns the default path of the index file for a container, e.g. `osm.versatiles.index.tsv`.
+	#[must_use]
+	pub fn sidecar_path(container_path: &Path) -> PathBuf {
+		let mut name = container_path.as_os_str().to_owned();
+		name.push(".index.tsv");
+		PathBuf::from(name)
+	}
+
+	/// Builds an index from all tiles of `level` (default: the highest zoom level of `reader`).
+	///
+	/// Feature IDs are always indexed. Attributes are limited to `keys`, or all attributes if `None`.
+	#[context("building attribute index for '{}'", reader.source_name())]
+	pub async fn build(
+		reader: &dyn TilesReaderTrait,
+		level: Option<u8>,
+		keys: Option<&[String]>,
+	) -> Result<AttributeIndex> {
+		let parameters = reader.parameters();
+		ensure!(
+			parameters.tile_format == TileFormat::MVT,
+			"attribute index needs vector tiles, but the tile format is {:?}",
+			parameters.tile_format
+		);
+
+		let level = match level {
+			Some(level) => level,
+			None => parameters
+				.bbox_pyramid
+				.get_level_max()
+				.ok_or_else(|| anyhow!("container has no tiles"))?,
+		};
+		let bbox = *parameters.bbox_pyramid.get_level_bbox(level);
+
+		let keys = keys.map(<[String]>::to_vec);
+		let mut index = AttributeIndex::new(level);
+		let mut error = None;
+		reader
+			.get_tile_stream(bbox)
+			.await?
+			.map_item_parallel(move |tile| Ok(extract_entries(tile, keys.as_deref())))
+			.for_each_sync(|(coord, entries)| match entries {
+				Ok(entries) => {
+					for key in entries {
+						let coords = index.entries.entry(key).or_default();
+						// entries of one tile arrive together, so duplicates are always adjacent
+						if coords.last() != Some(&coord) {
+							coords.push(coord);
+						}
+					}
+				}
+				Err(err) => {
+					error.get_or_insert(err.context(format!("indexing tile {coord:?}")));
+				}
+			})
+			.await;
+
+		if let Some(err) = error {
+			return Err(err);
+		}
+		for coords in index.entries.values_mut() {
+			coords.sort_by_key(TileCoord::get_sort_index);
+		}
+		Ok(index)
+	}
+
+	/// Returns the coordinates of all tiles with a feature in `layer` where `key` has `value`.
+	#[must_use]
+	pub fn query(&self, layer: &str, key: &str, value: &str) -> Vec<TileCoord> {
+		self
+			.entries
+			.get(&(layer.to_string(), key.to_string(), value.to_string()))
+			.cloned()
+			.unwrap_or_default()
+	}
+
+	/// Serializes the index into its text format.
+	#[must_use]
+	pub fn to_text(&self) -> String {
+		let mut text = format!("{HEADER} {}\n", self.level);
+		for ((layer, key, value), coords) in &self.entries {
+			let coords = coords
+				.iter()
+				.map(|c| format!("{}/{}/{}", c.level, c.x, c.y))
+				.collect::<Vec<_>>()
+				.join(",");
+			writeln!(text, "{}\t{}\t{}\t{coords}", escape(layer), escape(key), escape(value)).unwrap();
+		}
+		text
+	}
+
+	/// Parses an index from its text format.
+	#[context("parsing attribute index")]
+	pub fn from_text(text: &str) -> Result<AttributeIndex> {
+		let mut lines = text.lines();
+		let level = lines
+			.next()
+			.and_then(|line| line.strip_prefix(HEADER))
+			.ok_or_else(|| anyhow!("missing header '{HEADER}'"))?
+			.trim()
+			.parse::<u8>()
+			.context("invalid zoom level in header")?;
+
+		let mut index = AttributeIndex::new(level);
+		for (number, line) in lines.enumerate() {
+			if line.is_empty() {
+				continue;
+			}
+			let fields = line.split('\t').collect::<Vec<_>>();
+			ensure!(fields.len() == 4, "line {}: expected 4 fields", number + 2);
+			let coords = fields[3]
+				.split(',')
+				.map(parse_coord)
+				.collect::<Result<Vec<_>>>()
+				.with_context(|| format!("line {}", number + 2))?;
+			index
+				.entries
+				.insert((unescape(fields[0]), unescape(fields[1]), unescape(fields[2])), coords);
+		}
+		Ok(index)
+	}
+
+	/// Writes the index to `path`.
+	#[context("writing attribute index to {:?}", path)]
+	pub fn write_to_path(&self, path: &Path) -> Result<()> {
+		std::fs::write(path, self.to_text())?;
+		Ok(())
+	}
+
+	/// Reads an index from `path`.
+	#[context("reading attribute index from {:?}", path)]
+	pub fn read_from_path(path: &Path) -> Result<AttributeIndex> {
+		AttributeIndex::from_text(&std::fs::read_to_string(path)?)
+	}
+
+	/// Looks up `layer`/`key`/`value` and returns the matching features as a GeoJSON FeatureCollection
+	/// with coordinates in longitude/latitude.
+	///
+	/// At most `max_tiles` tiles are read, up to eight of them at the same time. Features crossing
+	/// tile borders are returned once per tile.
+	#[context("querying features {layer}/{key}={value}")]
+	pub async fn query_features(
+		&self,
+		reader: &dyn TilesReaderTrait,
+		layer: &str,
+		key: &str,
+		value: &str,
+		max_tiles: usize,
+	) -> Result<JsonObject> {
+		let coords = self.query(layer, key, value).into_iter().take(max_tiles);
+		let mut tiles = stream::iter(coords)
+			.map(|coord| async move { reader.get_tile(&coord).await.map(|tile| (coord, tile)) })
+			.buffered(QUERY_CONCURRENCY);
+
+		let mut features = Vec::new();
+		while let Some((coord, tile)) = tiles.try_next().await? {
+			let Some(tile) = tile else {
+				continue;
+			};
+			let vector_tile = tile.into_vector()?;
+			let Some(tile_layer) = vector_tile.find_layer(layer) else {
+				continue;
+			};
+			for mut feature in tile_layer.to_features()? {
+				if feature_matches(&feature, key, value) {
+					TileProjection::new(coord, tile_layer.extent).geometry_to_lon_lat(&mut feature.geometry);
+					features.push(JsonValue::from(feature.to_json(Some(7))));
+				}
+			}
+		}
+
+		let mut collection = JsonObject::new();
+		collection.set("type", JsonValue::from("FeatureCollection"));
+		collection.set("features", JsonValue::Array(JsonArray(features)));
+		Ok(collection)
+	}
+}
+
+fn extract_entries(tile: Tile, keys: Option<&[String]>) -> Result<Vec<(String, String, String)>> {
+	let vector_tile = tile.into_vector()?;
+	let mut entries = Vec::new();
+	for layer in &vector_tile.layers {
+		extract_layer_entries(layer, keys, &mut entries)?;
+	}
+	Ok(entries)
+}
+
+fn extract_layer_entries(
+	layer: &VectorTileLayer,
+	keys: Option<&[String]>,
+	entries: &mut Vec<(String, String, String)>,
+) -> Result<()> {
+	for feature in &layer.features {
+		if let Some(id) = feature.id {
+			entries.push((layer.name.clone(), ATTRIBUTE_INDEX_ID_KEY.to_string(), id.to_string()));
+		}
+		for (key, value) in feature.decode_properties(layer)?.iter() {
+			if keys.is_none_or(|keys| keys.contains(key)) {
+				entries.push((layer.name.clone(), key.clone(), value.to_string()));
+			}
+		}
+	}
+	Ok(())
+}
+
+fn feature_matches(feature: &GeoFeature, key: &str, value: &str) -> bool {
+	if key == ATTRIBUTE_INDEX_ID_KEY {
+		return feature.id.as_ref().is_some_and(|id| id.to_string() == value);
+	}
+	feature.properties.get(key).is_some_and(|v| v.to_string() == value)
+}
+
+fn parse_coord(text: &str) -> Result<TileCoord> {
+	let parts = text.split('/').map(str::parse::<u32>).collect::<Result<Vec<_>, _>>()?;
+	if parts.len() != 3 || parts[0] > 31 {
+		bail!("invalid tile coordinate '{text}'");
+	}
+	TileCoord::new(parts[0] as u8, parts[1], parts[2])
+}
+
+fn escape(text: &str) -> String {
+	text
+		.replace('\\', "\\\\")
+		.replace('\t', "\\t")
+		.replace('\n', "\\n")
+		.replace('\r', "\\r")
+}
+
+fn unescape(text: &str) -> String {
+	let mut result = String::with_capacity(text.len());
+	let mut chars = text.chars();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			result.push(c);
+			continue;
+		}
+		match chars.next() {
+			Some('t') => result.push('\t'),
+			Some('n') => result.push('\n'),
+			Some('r') => result.push('\r'),
+			Some(other) => result.push(other),
+			None => result.push('\\'),
+		}
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_container::MemTilesReader;
+	use versatiles_core::TileCompression;
+	use versatiles_geometry::{
+		geo::{GeoProperties, GeoValue, Geometry},
+		vector_tile::VectorTile,
+	};
+
+	fn poi_tile(names: &[&str]) -> Tile {
+		let features = names
+			.iter()
+			.enumerate()
+			.map(|(i, name)| {
+				let mut feature = GeoFeature::new(Geometry::new_point([2048.0, 2048.0]));
+				feature.set_id(GeoValue::from(i as u64 + 1));
+				feature.set_properties(GeoProperties::from(vec![("name", GeoValue::from(*name))]));
+				feature
+			})
+			.collect();
+		let layer = VectorTileLayer::from_features("poi".to_string(), features, 4096, 1).unwrap();
+		Tile::from_vector(VectorTile::new(vec![layer]), TileFormat::MVT).unwrap()
+	}
+
+	fn reader() -> MemTilesReader {
+		let mut reader = MemTilesReader::new(TileFormat::MVT, TileCompression::Uncompressed);
+		reader
+			.insert(TileCoord::new(1, 0, 0).unwrap(), poi_tile(&["a\tb", "cafe"]))
+			.unwrap();
+		reader
+			.insert(TileCoord::new(1, 1, 1).unwrap(), poi_tile(&["cafe"]))
+			.unwrap();
+		reader
+			.insert(TileCoord::new(0, 0, 0).unwrap(), poi_tile(&["world"]))
+			.unwrap();
+		reader
+	}
+
+	#[tokio::test]
+	async fn build_query_and_roundtrip() -> Result<()> {
+		let reader = reader();
+		let index = AttributeIndex::build(&reader, None, None).await?;
+		assert_eq!(index.level(), 1);
+		assert_eq!(
+			index.query("poi", "name", "cafe"),
+			vec![TileCoord::new(1, 0, 0)?, TileCoord::new(1, 1, 1)?]
+		);
+		assert_eq!(index.query("poi", "$id", "1").len(), 2);
+		assert!(index.query("poi", "name", "world").is_empty());
+
+		let text = index.to_text();
+		assert!(text.starts_with("#versatiles-attribute-index 1\n"));
+		assert!(text.contains("poi\tname\ta\\tb\t1/0/0\n"));
+		assert_eq!(AttributeIndex::from_text(&text)?, index);
+
+		let only_ids = AttributeIndex::build(&reader, Some(0), Some(&[])).await?;
+		assert_eq!(only_ids.len(), 1);
+		assert_eq!(only_ids.query("poi", "$id", "1"), vec![TileCoord::new(0, 0, 0)?]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn query_features_as_geojson() -> Result<()> {
+		let reader = reader();
+		let index = AttributeIndex::build(&reader, Some(1), None).await?;
+		let json = index.query_features(&reader, "poi", "name", "cafe", 10).await?;
+		let text = json.stringify();
+		assert!(
+			text.starts_with(r#"{"features":[{"geometry":{"coordinates":[[-90,66.5132604]],"type":"MultiPoint"},"id":2,"#),
+			"{text}"
+		);
+		assert!(text.ends_with(r#""type":"FeatureCollection"}"#));
+		assert_eq!(text.matches(r#""name":"cafe""#).count(), 2);
+
+		let limited = index.query_features(&reader, "poi", "name", "cafe", 1).await?;
+		assert_eq!(limited.stringify().matches(r#""name":"cafe""#).count(), 1);
+		Ok(())
+	}
+
+	#[test]
+	fn parse_errors() {
+		assert!(AttributeIndex::from_text("").is_err());
+		assert!(AttributeIndex::from_text("#versatiles-attribute-index 1\nfoo").is_err());
+		assert!(AttributeIndex::from_text("#versatiles-attribute-index 1\na\tb\tc\t1/2").is_err());
+		assert_eq!(unescape(&escape("a\\b\tc\nd")), "a\\b\tc\nd");
+	}
+}
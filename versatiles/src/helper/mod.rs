@@ -1,2 +1,6 @@
+mod attribute_index;
 mod registry;
+mod style_sources;
+pub use attribute_index::*;
 pub use registry::*;
+pub use style_sources::*;
@@ -5,7 +5,7 @@ use versatiles_container::{ContainerRegistry, ProcessingConfig, TilesReaderTrait
 /// Creates a `ContainerRegistry` with pre-registered readers for specific file types.
 ///
 /// # Parameters
-/// - `config`: A `ProcessingConfig` instance used to configure the readers.
+/// - `config`: A `ProcessingConfig` instance used to configure the readers and writers.
 ///
 /// # Returns
 /// A `ContainerRegistry` with readers registered for handling certain file extensions.
@@ -23,13 +23,13 @@ use versatiles_container::{ContainerRegistry, ProcessingConfig, TilesReaderTrait
 /// #[tokio::main]
 /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
 ///     let registry: ContainerRegistry = get_registry(ProcessingConfig::default());
-///     let reader = registry.get_reader_from_str("../testdata/berlin.vpl").await?;
+///     let reader = registry.open_reader("../testdata/berlin.vpl").await?;
 ///     // Use the reader here
 ///     Ok(())
 /// }
 /// ```
 pub fn get_registry(config: ProcessingConfig) -> ContainerRegistry {
-	let mut registry = ContainerRegistry::default();
+	let mut registry = ContainerRegistry::new(config.clone());
 
 	// Register a reader for "vpl" files. The closure captures the config and clones it for async usage.
 	let c = config.clone();
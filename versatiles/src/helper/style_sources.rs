@@ -0,0 +1,181 @@
+//! Self-host an existing MapLibre/Mapbox style by serving its tile containers locally.
+//!
+//! [`StyleSources`] reads the `sources` of a style JSON and picks every source whose `url`
+//! points to a tile container, e.g. `"pmtiles://https://example.org/osm.pmtiles"` or
+//! `"./berlin.mbtiles"`. These containers can be added to the server as tile sources, and
+//! [`StyleSources::rewritten_style`] returns the style with their URLs replaced by the
+//! TileJSON endpoints of the server (`/tiles/<name>/tiles.json`).
+//!
+//! Other sources, like TileJSON URLs or `tiles` templates, are left unchanged and listed in
+//! [`StyleSources::skipped_sources`].
+
+use crate::TileSourceConfig;
+use anyhow::{Result, anyhow};
+use std::path::Path;
+use versatiles_container::{ContainerRegistry, DataLocation};
+use versatiles_core::json::{JsonObject, JsonValue};
+use versatiles_derive::context;
+
+/// The tile containers referenced by a style JSON.
+#[derive(Clone, Debug)]
+pub struct StyleSources {
+	style: JsonObject,
+	tile_sources: Vec<TileSourceConfig>,
+	skipped_sources: Vec<String>,
+}
+
+impl StyleSources {
+	/// Parses a style JSON. Relative container paths are resolved against `base`.
+	#[context("parsing style JSON")]
+	pub fn from_string(text: &str, base: &DataLocation, registry: &ContainerRegistry) -> Result<StyleSources> {
+		let style = JsonObject::parse_str(text)?;
+		let mut tile_sources = Vec::new();
+		let mut skipped_sources = Vec::new();
+
+		if let Some(sources) = style.get_object("sources")? {
+			for (name, source) in sources.iter() {
+				let source = source
+					.as_object()
+					.map_err(|_| anyhow!("style source '{name}' must be an object"))?;
+				match container_location(source, base, registry)? {
+					Some(path) => tile_sources.push(TileSourceConfig {
+						name: Some(name.clone()),
+						path,
+						index: None,
+					}),
+					None => skipped_sources.push(name.clone()),
+				}
+			}
+		}
+
+		Ok(StyleSources {
+			style,
+			tile_sources,
+			skipped_sources,
+		})
+	}
+
+	/// Reads a style JSON file. Relative container paths are resolved against its directory.
+	#[context("reading style JSON {path:?}")]
+	pub fn from_path(path: &Path, registry: &ContainerRegistry) -> Result<StyleSources> {
+		let text = std::fs::read_to_string(path)?;
+		let base = DataLocation::from(std::path::absolute(path)?.parent().unwrap_or(Path::new("/")));
+		StyleSources::from_string(&text, &base, registry)
+	}
+
+	/// Tile sources for all containers of the style, named like the style sources.
+	pub fn tile_sources(&self) -> &[TileSourceConfig] {
+		&self.tile_sources
+	}
+
+	/// Names of the style sources that are not backed by a tile container.
+	pub fn skipped_sources(&self) -> &[String] {
+		&self.skipped_sources
+	}
+
+	/// Returns the style with the URLs of all container sources pointing to the server.
+	pub fn rewritten_style(&self) -> JsonObject {
+		let mut style = self.style.clone();
+		if let Some(JsonValue::Object(sources)) = style.0.get_mut("sources") {
+			for tile_source in &self.tile_sources {
+				let name = tile_source.name.as_deref().unwrap_or_default();
+				if let Some(JsonValue::Object(source)) = sources.0.get_mut(name) {
+					source.set("url", format!("/tiles/{name}/tiles.json"));
+				}
+			}
+		}
+		style
+	}
+}
+
+/// Returns the location of the container behind a style source, if its `url` points to one.
+///
+/// Accepts plain paths and URLs as well as protocol prefixes like `pmtiles://` or `mbtiles://`.
+fn container_location(
+	source: &JsonObject,
+	base: &DataLocation,
+	registry: &ContainerRegistry,
+) -> Result<Option<DataLocation>> {
+	let Some(url) = source.get_string("url")? else {
+		return Ok(None);
+	};
+	let url = match url.split_once("://") {
+		Some((scheme, rest)) if registry.supports_reader_extension(scheme) => rest.to_string(),
+		_ => url,
+	};
+
+	let mut location = DataLocation::from(url.as_str());
+	if !location
+		.extension()
+		.is_ok_and(|extension| registry.supports_reader_extension(&extension))
+	{
+		return Ok(None);
+	}
+	location.resolve(base)?;
+	Ok(Some(location))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::get_registry;
+	use versatiles_container::ProcessingConfig;
+
+	const STYLE: &str = r#"{
+		"version": 8,
+		"sources": {
+			"berlin": { "type": "vector", "url": "pmtiles://./berlin.pmtiles" },
+			"remote": { "type": "vector", "url": "https://example.org/data/osm.versatiles" },
+			"tilejson": { "type": "vector", "url": "https://example.org/tiles.json" },
+			"template": { "type": "raster", "tiles": ["https://example.org/{z}/{x}/{y}.png"] }
+		},
+		"layers": []
+	}"#;
+
+	fn parse() -> Result<StyleSources> {
+		let registry = get_registry(ProcessingConfig::default());
+		StyleSources::from_string(STYLE, &DataLocation::from("/data/styles/"), &registry)
+	}
+
+	#[test]
+	fn finds_container_sources() -> Result<()> {
+		let style = parse()?;
+		let sources = style
+			.tile_sources()
+			.iter()
+			.map(|s| (s.name.clone().unwrap(), s.path.to_string().replace('\\', "/")))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			sources,
+			vec![
+				("berlin".to_string(), "/data/styles/berlin.pmtiles".to_string()),
+				(
+					"remote".to_string(),
+					"https://example.org/data/osm.versatiles".to_string()
+				),
+			]
+		);
+		assert_eq!(style.skipped_sources(), ["template", "tilejson"]);
+		Ok(())
+	}
+
+	#[test]
+	fn rewrites_container_urls() -> Result<()> {
+		let style = parse()?.rewritten_style();
+		let sources = style.get_object("sources")?.unwrap();
+		let url = |name: &str| sources.get_object(name).unwrap().unwrap().get_string("url").unwrap();
+		assert_eq!(url("berlin").as_deref(), Some("/tiles/berlin/tiles.json"));
+		assert_eq!(url("remote").as_deref(), Some("/tiles/remote/tiles.json"));
+		assert_eq!(url("tilejson").as_deref(), Some("https://example.org/tiles.json"));
+		assert_eq!(style.get_number("version")?, Some(8.0));
+		Ok(())
+	}
+
+	#[test]
+	fn rejects_invalid_sources() {
+		let registry = get_registry(ProcessingConfig::default());
+		let base = DataLocation::from("/");
+		assert!(StyleSources::from_string(r#"{"sources":{"a":1}}"#, &base, &registry).is_err());
+		assert!(StyleSources::from_string("[]", &base, &registry).is_err());
+	}
+}
@@ -25,7 +25,7 @@
 //! async fn main() {
 //!     let config = ProcessingConfig::default();
 //!     let registry = versatiles::get_registry(config);
-//!     let reader = registry.get_reader_from_str("../testdata/berlin.pmtiles").await.unwrap();
+//!     let reader = registry.open_reader("../testdata/berlin.pmtiles").await.unwrap();
 //!
 //!     // Define the output filename
 //!     let output_path = std::env::temp_dir().join("temp1.versatiles");
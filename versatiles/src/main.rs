@@ -6,6 +6,16 @@
 //! - **Convert**: Convert between different tile containers.
 //! - **Probe**: Show information about a tile container.
 //! - **Serve**: Serve tiles via HTTP.
+//! - **Index**: Build an attribute index for server-side feature queries.
+//! - **Export**: Export raster tiles as georeferenced images (GeoTIFF or PNG with world file).
+//! - **Snapshot**: Render a bbox of raster tiles into one stitched image (PNG, JPEG, WebP or AVIF).
+//! - **Verify**: Check that two tile containers contain the same tiles.
+//! - **OscTiles**: List the tiles affected by OSM change files, for incremental updates.
+//! - **Glyphs**: Convert TTF/OTF fonts into glyph PBFs for MapLibre styles.
+//! - **Sprites**: Render a directory of SVG icons into sprite sheets for MapLibre styles.
+//! - **Bench**: Measure read/write throughput of the container backends.
+//! - **Top**: Convert tiles with an interactive dashboard (requires the `tui` feature).
+//! - **Completions**: Generate shell completions.
 //!
 //! ## Usage
 //! ```sh
@@ -28,7 +38,7 @@
 mod tools;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use log::LevelFilter;
 use std::io::Write;
 
@@ -41,10 +51,20 @@ use std::io::Write;
 	long_about = None, // Disable long description
 	propagate_version = false, // Enable version flag for subcommands
 	disable_help_subcommand = true, // Disable help subcommand
+	arg_required_else_help = true, // Show help if no subcommand is given
 )]
 struct Cli {
 	#[command(subcommand)]
-	command: Commands, // Set subcommands
+	command: Option<Commands>, // Set subcommands
+
+	#[arg(
+		long,
+		exclusive = true,
+		help = "Print all commands and pipeline operations as JSON",
+		long_help = "Print the schemas of all commands, their arguments and all pipeline operations as JSON, e.g. for building user interfaces.",
+		display_order = 100
+	)]
+	help_json: bool,
 
 	#[arg(
 		long,
@@ -72,6 +92,40 @@ struct Cli {
 		display_order = 100,
 	)]
 	verbose: u8,
+
+	#[arg(
+		long,
+		global = true,
+		help = "Print timing spans of pipeline operations and container reads",
+		long_help = "Enable a tracing subscriber that prints a line to stderr whenever a span closes, including how long it was busy and idle.\n\
+			Spans are recorded per pipeline operation and per bbox that is read from a container, which helps to find bottlenecks in complex pipelines.",
+		display_order = 100
+	)]
+	trace: bool,
+
+	#[arg(
+		long,
+		global = true,
+		value_name = "SIZE",
+		value_parser = versatiles_core::parse_memory_size,
+		help = "Memory budget for tile buffers and caches, e.g. \"8G\"",
+		long_help = "Memory budget for large buffers like tile caches, e.g. \"8G\" or \"512M\".\n\
+			When the budget is nearly used up, parallel processing slows down and caches evict entries early.\n\
+			This is not a hard limit on the memory of the process.",
+		display_order = 100
+	)]
+	max_memory: Option<u64>,
+
+	#[arg(
+		long,
+		global = true,
+		help = "Report progress as JSON lines instead of a progress bar",
+		long_help = "Write every progress update as a line of JSON to stderr instead of drawing a progress bar, e.g. for wrapping versatiles in another program.\n\
+			Each line contains `message`, `position`, `length`, `percent` and `finished`.\n\
+			During conversions, `stage`, `stage_index`, `stage_count` and the weighted `total_percent` of all stages are added.",
+		display_order = 100
+	)]
+	progress_json: bool,
 }
 
 /// Define subcommands for the command-line interface
@@ -88,9 +142,40 @@ enum Commands {
 	/// Serve tiles via HTTP
 	Serve(tools::serve::Subcommand),
 
+	/// Build an attribute index of a vector tile container for the "/query" endpoint of the server
+	Index(tools::index::Subcommand),
+
+	/// Export raster tiles as georeferenced images (GeoTIFF, or PNG with a world file) for GIS software
+	Export(tools::export::Subcommand),
+
+	/// Render a bbox at one zoom level into a single image, e.g. as a preview of a pipeline
+	Snapshot(tools::snapshot::Subcommand),
+
+	#[cfg(feature = "tui")]
+	/// Convert tiles while showing live conversion metrics in an interactive dashboard
+	Top(tools::top::Subcommand),
+
+	/// Check that two tile containers contain the same tiles, ignoring their compression
+	Verify(tools::verify::Subcommand),
+
+	/// List the tiles affected by OSM change files (*.osc), to rebuild them with "convert --coordinate-list"
+	OscTiles(tools::osc_tiles::Subcommand),
+
+	/// Convert TTF/OTF fonts into glyph PBFs for MapLibre/Mapbox styles
+	Glyphs(tools::glyphs::Subcommand),
+
+	/// Render a directory of SVG icons into sprite sheets (PNG + JSON, 1x and 2x) for MapLibre/Mapbox styles
+	Sprites(tools::sprites::Subcommand),
+
+	/// Measure read/write throughput of the container backends
+	Bench(tools::bench::Subcommand),
+
 	/// Show detailed help
 	Help(tools::help::Subcommand),
 
+	/// Generate shell completions
+	Completions(tools::completions::Subcommand),
+
 	/// Some unstable developer tools
 	Dev(tools::dev::Subcommand),
 }
@@ -127,16 +212,61 @@ fn main() -> Result<()> {
 		})
 		.init();
 
-	run(cli)
+	// export traces and metrics, if an OTLP endpoint is configured
+	#[cfg(feature = "otel")]
+	let telemetry = tools::telemetry::Telemetry::init(cli.trace)?;
+	#[cfg(not(feature = "otel"))]
+	let telemetry: Option<()> = None;
+
+	if cli.trace && telemetry.is_none() {
+		tracing_subscriber::fmt()
+			.with_max_level(tracing_subscriber::filter::LevelFilter::DEBUG)
+			.with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+			.with_writer(std::io::stderr)
+			.init();
+	}
+
+	versatiles_core::MemoryBudget::global().set_limit(cli.max_memory);
+	versatiles_core::progress::ConversionMetrics::set_progress_json(cli.progress_json);
+
+	let result = run(cli);
+
+	#[cfg(feature = "otel")]
+	if let Some(telemetry) = telemetry {
+		telemetry.shutdown();
+	}
+
+	result
 }
 
 /// Helper function for running subcommands
 fn run(cli: Cli) -> Result<()> {
-	match &cli.command {
+	if cli.help_json {
+		println!("{}", tools::help::help_json()?);
+		return Ok(());
+	}
+
+	let Some(command) = &cli.command else {
+		Cli::command().print_help()?;
+		return Ok(());
+	};
+
+	match command {
+		Commands::Bench(arguments) => tools::bench::run(arguments),
+		Commands::Completions(arguments) => tools::completions::run(arguments),
 		Commands::Convert(arguments) => tools::convert::run(arguments),
+		Commands::Export(arguments) => tools::export::run(arguments),
+		Commands::Glyphs(arguments) => tools::glyphs::run(arguments),
 		Commands::Help(arguments) => tools::help::run(arguments),
+		Commands::Index(arguments) => tools::index::run(arguments),
+		Commands::OscTiles(arguments) => tools::osc_tiles::run(arguments),
 		Commands::Probe(arguments) => tools::probe::run(arguments),
 		Commands::Serve(arguments) => tools::serve::run(arguments),
+		Commands::Snapshot(arguments) => tools::snapshot::run(arguments),
+		Commands::Sprites(arguments) => tools::sprites::run(arguments),
+		#[cfg(feature = "tui")]
+		Commands::Top(arguments) => tools::top::run(arguments),
+		Commands::Verify(arguments) => tools::verify::run(arguments),
 		Commands::Dev(arguments) => tools::dev::run(arguments),
 	}
 }
@@ -2,6 +2,8 @@
 //!
 //! - `serve_tile` serves tiles from a single `TileSource`.
 //! - `serve_static` serves files from a list of `StaticSource`s.
+//! - `serve_query` answers attribute index lookups across all `TileSource`s.
+//! - `serve_healthz` and `serve_readyz` answer liveness and readiness probes.
 //! - `ok_json` is a tiny helper used by the API routes.
 //!
 //! Note: CORS headers are handled exclusively by the `CorsLayer`. Don’t set
@@ -14,12 +16,20 @@ use super::{
 };
 use axum::{
 	body::Body,
-	extract::State,
+	extract::{Query, State},
 	http::{HeaderMap, Uri, header},
 	response::Response,
 };
+use futures::future::join_all;
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+use tracing::Instrument;
+use versatiles_container::TileTimeoutError;
 use versatiles_core::{
 	Blob, TileCompression,
+	json::{JsonObject, JsonValue},
 	utils::{TargetCompression, optimize_compression},
 };
 
@@ -37,6 +47,20 @@ pub struct StaticHandlerState {
 	pub minimal_recompression: bool,
 }
 
+/// State for attribute index queries across all `TileSource`s.
+#[derive(Clone)]
+pub struct QueryHandlerState {
+	pub sources: Vec<TileSource>,
+}
+
+/// State for readiness probes across all `TileSource`s.
+#[derive(Clone)]
+pub struct ReadyHandlerState {
+	pub sources: Vec<TileSource>,
+	/// Maximum time to read the test tile of a source.
+	pub timeout: Duration,
+}
+
 /// Tile handler: pulls data from the bound `TileSource`, negotiates compression,
 /// and emits an HTTP response.
 pub async fn serve_tile(
@@ -55,6 +79,7 @@ pub async fn serve_tile(
 		target.set_fast_compression();
 	}
 
+	let span = tracing::debug_span!("serve_tile", source = %tile_source.prefix, bytes = tracing::field::Empty);
 	let response = tile_source
 		.get_data(
 			&path
@@ -62,10 +87,12 @@ pub async fn serve_tile(
 				.expect("request path should start with source prefix"),
 			&target,
 		)
+		.instrument(span.clone())
 		.await;
 
 	match response {
 		Ok(Some(result)) => {
+			span.record("bytes", result.blob.len());
 			log::debug!("send response for tile request: {path}");
 			ok_data(result, target)
 		}
@@ -73,6 +100,10 @@ pub async fn serve_tile(
 			log::debug!("send 404 for tile request: {path}");
 			error_404()
 		}
+		Err(err) if err.downcast_ref::<TileTimeoutError>().is_some() => {
+			log::warn!("send 504 for tile request: {path}. Reason: {err:#}");
+			error_504()
+		}
 		Err(err) => {
 			log::warn!("send 500 for tile request: {path}. Reason: {err}");
 			error_500()
@@ -112,8 +143,97 @@ pub async fn serve_static(
 	error_404()
 }
 
+/// Query handler: `/query?layer=…&key=…&value=…[&source=…][&format=geojson]`.
+///
+/// `source` is required if more than one tile source has an attribute index.
+pub async fn serve_query(
+	Query(params): Query<HashMap<String, String>>,
+	State(QueryHandlerState { sources }): State<QueryHandlerState>,
+) -> Response<Body> {
+	log::debug!("handle query request: {params:?}");
+
+	let (Some(layer), Some(key), Some(value)) = (params.get("layer"), params.get("key"), params.get("value")) else {
+		return error_400("parameters 'layer', 'key' and 'value' are required");
+	};
+	let geojson = match params.get("format").map(String::as_str) {
+		None | Some("tiles") => false,
+		Some("geojson") => true,
+		Some(_) => return error_400("parameter 'format' must be 'tiles' or 'geojson'"),
+	};
+
+	let mut indexed = sources.iter().filter(|s| s.has_attribute_index());
+	let source = match params.get("source") {
+		Some(id) => indexed.find(|s| &s.id == id),
+		None => match (indexed.next(), indexed.next()) {
+			(Some(source), None) => Some(source),
+			(None, _) => None,
+			(Some(_), Some(_)) => return error_400("parameter 'source' is required"),
+		},
+	};
+	let Some(source) = source else {
+		return error_404();
+	};
+
+	match source.query(layer, key, value, geojson).await {
+		Ok(Some(json)) => ok_json(&json),
+		Ok(None) => error_404(),
+		Err(err) => {
+			log::warn!("send 500 for query request: {params:?}. Reason: {err}");
+			error_500()
+		}
+	}
+}
+
+/// Liveness probe: answers as long as the process is able to handle requests.
+pub async fn serve_healthz() -> Response<Body> {
+	let mut json = JsonObject::new();
+	json.set("status", "ok");
+	json_with(200, &json.stringify())
+}
+
+/// Readiness probe: reads a test tile from every `TileSource` in parallel.
+///
+/// Answers `200` if all sources are ready, otherwise `503`. The JSON body lists
+/// every source with its state, the time it took and the error, if any.
+pub async fn serve_readyz(State(ReadyHandlerState { sources, timeout }): State<ReadyHandlerState>) -> Response<Body> {
+	log::debug!("handle readiness request");
+
+	let checks = join_all(sources.iter().map(|source| async move {
+		let start = Instant::now();
+		let result = source.check_ready(timeout).await;
+		let mut json = JsonObject::new();
+		json.set("id", &source.id);
+		json.set("ready", result.is_ok());
+		json.set("ms", JsonValue::Number(start.elapsed().as_millis() as f64));
+		if let Err(err) = result {
+			log::warn!("tile source '{}' is not ready: {err:#}", source.id);
+			json.set("error", format!("{err:#}"));
+		}
+		json
+	}))
+	.await;
+
+	let ready = checks
+		.iter()
+		.all(|check| check.get("ready") == Some(&JsonValue::Boolean(true)));
+	let mut json = JsonObject::new();
+	json.set("status", if ready { "ready" } else { "not ready" });
+	json.set("sources", checks);
+	json_with(if ready { 200 } else { 503 }, &json.stringify())
+}
+
 // --- small helpers -----------------------------------------------------------
 
+/// JSON response that must not be cached, e.g. for probes.
+fn json_with(status: u16, json: &str) -> Response<Body> {
+	Response::builder()
+		.status(status)
+		.header(header::CONTENT_TYPE, "application/json")
+		.header(header::CACHE_CONTROL, "no-store")
+		.body(Body::from(json.as_bytes().to_vec()))
+		.expect("failed to build JSON response")
+}
+
 fn error_with(status: u16, message: &str) -> Response<Body> {
 	Response::builder()
 		.status(status)
@@ -123,6 +243,10 @@ fn error_with(status: u16, message: &str) -> Response<Body> {
 		.expect("failed to build error response")
 }
 
+fn error_400(message: &str) -> Response<Body> {
+	error_with(400, message)
+}
+
 fn error_404() -> Response<Body> {
 	error_with(404, "Not Found")
 }
@@ -131,6 +255,10 @@ fn error_500() -> Response<Body> {
 	error_with(500, "Internal Server Error")
 }
 
+fn error_504() -> Response<Body> {
+	error_with(504, "Gateway Timeout")
+}
+
 fn ok_data(result: SourceResponse, mut target: TargetCompression) -> Response<Body> {
 	// Binary images are effectively incompressible; avoid recompression.
 	if matches!(
@@ -165,7 +293,7 @@ fn ok_data(result: SourceResponse, mut target: TargetCompression) -> Response<Bo
 	log::trace!("send response with headers: {:?}", response.headers_ref());
 
 	response
-		.body(Body::from(blob.into_vec()))
+		.body(Body::from(blob.into_bytes()))
 		.expect("failed to build OK response")
 }
 
@@ -3,10 +3,12 @@
 mod cors;
 pub mod encoding;
 mod handlers;
+mod quota;
 mod routes;
 mod sources;
 mod tile_server;
 mod utils;
+mod watch;
 
 pub use tile_server::*;
 pub use utils::Url;
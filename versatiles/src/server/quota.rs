@@ -0,0 +1,261 @@
+//! Per-client request quotas.
+//!
+//! Every client gets a token bucket that holds up to `burst` tokens and is refilled with
+//! `requests_per_minute` tokens per minute. Each request takes one token; requests without
+//! a token are answered with `429 Too Many Requests` and a `Retry-After` header that tells
+//! the client how many seconds to wait for the next token.
+//!
+//! Clients are identified by the configured API key header if it contains one of the configured
+//! API keys, by the configured IP header (last entry of e.g. `X-Forwarded-For`, which was added
+//! by the reverse proxy) or by the IP of the TCP connection, in this order.
+//!
+//! When too many clients are tracked, the half that was seen least recently is forgotten.
+
+use crate::QuotaConfig;
+use anyhow::{Result, ensure};
+use axum::{
+	extract::{ConnectInfo, Request, State},
+	http::{HeaderMap, HeaderName, StatusCode, header},
+	middleware::Next,
+	response::{IntoResponse, Response},
+};
+use std::{
+	collections::{HashMap, HashSet},
+	net::SocketAddr,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+/// Above this number of tracked clients, the least recently seen half is forgotten.
+const MAX_CLIENTS: usize = 100_000;
+
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+	tokens: f64,
+	updated: Instant,
+}
+
+/// Token buckets of all clients.
+#[derive(Debug)]
+pub struct QuotaLimiter {
+	/// Refill rate in tokens per second.
+	rate: f64,
+	/// Capacity of each bucket.
+	burst: f64,
+	key_header: Option<HeaderName>,
+	api_keys: HashSet<String>,
+	ip_header: Option<HeaderName>,
+	buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl QuotaLimiter {
+	/// Builds a limiter from the config, or returns `None` if quotas are disabled.
+	pub fn from_config(config: &QuotaConfig) -> Result<Option<QuotaLimiter>> {
+		let Some(requests_per_minute) = config.requests_per_minute else {
+			return Ok(None);
+		};
+		ensure!(
+			requests_per_minute > 0,
+			"quota.requests_per_minute must be greater than 0"
+		);
+		let burst = config.burst.unwrap_or(requests_per_minute);
+		ensure!(burst > 0, "quota.burst must be greater than 0");
+		ensure!(
+			config.key_header.is_none() || !config.api_keys.is_empty(),
+			"quota.key_header requires a list of quota.api_keys"
+		);
+
+		let parse_header = |name: &Option<String>| {
+			name
+				.as_deref()
+				.map(|name| {
+					HeaderName::from_bytes(name.as_bytes()).map_err(|e| anyhow::anyhow!("invalid header name {name:?}: {e}"))
+				})
+				.transpose()
+		};
+
+		Ok(Some(QuotaLimiter {
+			rate: f64::from(requests_per_minute) / 60.0,
+			burst: f64::from(burst),
+			key_header: parse_header(&config.key_header)?,
+			api_keys: config.api_keys.iter().cloned().collect(),
+			ip_header: parse_header(&config.ip_header)?,
+			buckets: Mutex::new(HashMap::new()),
+		}))
+	}
+
+	/// Returns the key that identifies the client of a request.
+	fn client_key(&self, headers: &HeaderMap, addr: Option<SocketAddr>) -> String {
+		if let Some(name) = &self.key_header
+			&& let Some(key) = headers.get(name).and_then(|v| v.to_str().ok())
+			&& self.api_keys.contains(key)
+		{
+			return format!("key:{key}");
+		}
+		if let Some(name) = &self.ip_header
+			&& let Some(ip) = headers
+				.get(name)
+				.and_then(|v| v.to_str().ok())
+				.and_then(|v| v.rsplit(',').next())
+		{
+			return format!("ip:{}", ip.trim());
+		}
+		match addr {
+			Some(addr) => format!("ip:{}", addr.ip()),
+			None => String::from("ip:unknown"),
+		}
+	}
+
+	/// Takes a token from the bucket of `client`.
+	/// Returns how long the client has to wait if the bucket is empty.
+	fn acquire(&self, client: &str, now: Instant) -> Result<(), Duration> {
+		let mut buckets = self.buckets.lock().unwrap();
+		if buckets.len() >= MAX_CLIENTS && !buckets.contains_key(client) {
+			evict_least_recent(&mut buckets, MAX_CLIENTS / 2);
+		}
+
+		let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+			tokens: self.burst,
+			updated: now,
+		});
+		let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+		bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+		bucket.updated = now;
+
+		if bucket.tokens >= 1.0 {
+			bucket.tokens -= 1.0;
+			Ok(())
+		} else {
+			Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+		}
+	}
+}
+
+/// Keeps only the `keep` most recently updated buckets.
+fn evict_least_recent(buckets: &mut HashMap<String, Bucket>, keep: usize) {
+	let mut updated = buckets.values().map(|b| b.updated).collect::<Vec<_>>();
+	if updated.len() <= keep {
+		return;
+	}
+	let index = updated.len() - keep;
+	let (_, threshold, _) = updated.select_nth_unstable(index);
+	let threshold = *threshold;
+	buckets.retain(|_, b| b.updated >= threshold);
+}
+
+/// Axum middleware that answers requests over the quota with `429 Too Many Requests`.
+pub async fn enforce_quota(State(limiter): State<Arc<QuotaLimiter>>, request: Request, next: Next) -> Response {
+	let addr = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0);
+	let client = limiter.client_key(request.headers(), addr);
+
+	match limiter.acquire(&client, Instant::now()) {
+		Ok(()) => next.run(request).await,
+		Err(wait) => {
+			log::debug!("quota exceeded for client {client}");
+			let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+			let mut response = (StatusCode::TOO_MANY_REQUESTS, "Too many requests").into_response();
+			response
+				.headers_mut()
+				.insert(header::RETRY_AFTER, retry_after.to_string().parse().unwrap());
+			response
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use axum::http::HeaderValue;
+
+	fn limiter(requests_per_minute: u32, burst: Option<u32>) -> QuotaLimiter {
+		QuotaLimiter::from_config(&QuotaConfig {
+			requests_per_minute: Some(requests_per_minute),
+			burst,
+			key_header: Some(String::from("X-API-Key")),
+			api_keys: vec![String::from("secret")],
+			ip_header: Some(String::from("X-Forwarded-For")),
+		})
+		.unwrap()
+		.unwrap()
+	}
+
+	#[test]
+	fn disabled_without_rate() -> Result<()> {
+		assert!(QuotaLimiter::from_config(&QuotaConfig::default())?.is_none());
+		let config = QuotaConfig {
+			requests_per_minute: Some(0),
+			..Default::default()
+		};
+		assert!(QuotaLimiter::from_config(&config).is_err());
+		let config = QuotaConfig {
+			requests_per_minute: Some(60),
+			key_header: Some(String::from("X-API-Key")),
+			..Default::default()
+		};
+		assert!(QuotaLimiter::from_config(&config).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn burst_then_refill() {
+		let limiter = limiter(60, Some(3));
+		let start = Instant::now();
+		for _ in 0..3 {
+			assert!(limiter.acquire("a", start).is_ok());
+		}
+		let wait = limiter.acquire("a", start).unwrap_err();
+		assert_eq!(wait.as_secs_f64().round(), 1.0);
+
+		// other clients have their own bucket
+		assert!(limiter.acquire("b", start).is_ok());
+
+		// one token per second
+		assert!(limiter.acquire("a", start + Duration::from_millis(1100)).is_ok());
+		assert!(limiter.acquire("a", start + Duration::from_millis(1200)).is_err());
+
+		// the bucket never holds more than the burst
+		let later = start + Duration::from_secs(3600);
+		for _ in 0..3 {
+			assert!(limiter.acquire("a", later).is_ok());
+		}
+		assert!(limiter.acquire("a", later).is_err());
+	}
+
+	#[test]
+	fn client_key() {
+		let limiter = limiter(60, None);
+		let addr: SocketAddr = "10.0.0.1:1234".parse().unwrap();
+		let mut headers = HeaderMap::new();
+		assert_eq!(limiter.client_key(&headers, Some(addr)), "ip:10.0.0.1");
+		assert_eq!(limiter.client_key(&headers, None), "ip:unknown");
+
+		// the last entry was added by the proxy, earlier ones are chosen by the client
+		headers.insert("X-Forwarded-For", HeaderValue::from_static("6.6.6.6, 1.2.3.4"));
+		assert_eq!(limiter.client_key(&headers, Some(addr)), "ip:1.2.3.4");
+
+		// unknown keys don't get their own bucket
+		headers.insert("X-API-Key", HeaderValue::from_static("random"));
+		assert_eq!(limiter.client_key(&headers, Some(addr)), "ip:1.2.3.4");
+
+		headers.insert("X-API-Key", HeaderValue::from_static("secret"));
+		assert_eq!(limiter.client_key(&headers, Some(addr)), "key:secret");
+	}
+
+	#[test]
+	fn evicts_least_recent_clients() {
+		let start = Instant::now();
+		let mut buckets = (0..10)
+			.map(|i| {
+				let bucket = Bucket {
+					tokens: 0.0,
+					updated: start + Duration::from_secs(i),
+				};
+				(format!("ip:{i}"), bucket)
+			})
+			.collect::<HashMap<_, _>>();
+		evict_least_recent(&mut buckets, 4);
+		let mut keys = buckets.keys().cloned().collect::<Vec<_>>();
+		keys.sort();
+		assert_eq!(keys, ["ip:6", "ip:7", "ip:8", "ip:9"]);
+	}
+}
@@ -4,11 +4,15 @@
 //! lifecycle or CORS logic. It’s intentionally tiny and declarative.
 
 use super::{
-	handlers::{StaticHandlerState, TileHandlerState, ok_json, serve_static, serve_tile},
+	handlers::{
+		QueryHandlerState, ReadyHandlerState, StaticHandlerState, TileHandlerState, ok_json, serve_healthz, serve_query,
+		serve_readyz, serve_static, serve_tile,
+	},
 	sources::{StaticSource, TileSource},
 };
 use anyhow::Result;
 use axum::{Router, routing::get};
+use std::time::Duration;
 use versatiles_derive::context;
 
 /// Attach all tile sources under their prefixes (`/tiles/<id>/{*path}`).
@@ -25,6 +29,15 @@ pub fn add_tile_sources_to_app(mut app: Router, sources: &[TileSource], minimal_
 	app
 }
 
+/// Attach style JSONs at their URLs, e.g. `/styles/osm.json`.
+pub fn add_styles_to_app(mut app: Router, styles: &[(String, String)]) -> Router {
+	for (url, json) in styles.iter() {
+		let json = json.clone();
+		app = app.route(url, get(move || async move { ok_json(&json) }));
+	}
+	app
+}
+
 /// Attach static sources as a catch-all fallback.
 /// Sources are checked in order; the first one returning data wins.
 pub fn add_static_sources_to_app(app: Router, static_sources: &[StaticSource], minimal_recompression: bool) -> Router {
@@ -36,7 +49,7 @@ pub fn add_static_sources_to_app(app: Router, static_sources: &[StaticSource], m
 	app.merge(static_app)
 }
 
-/// Attach small JSON API endpoints (currently `/tiles/index.json`).
+/// Attach small JSON API endpoints (`/tiles/index.json` and, if any source has an attribute index, `/query`).
 #[context("adding API routes to app")]
 pub async fn add_api_to_app(app: Router, sources: &[TileSource]) -> Result<Router> {
 	let mut api_app = Router::new();
@@ -59,9 +72,31 @@ pub async fn add_api_to_app(app: Router, sources: &[TileSource]) -> Result<Route
 		}),
 	);
 
+	if sources.iter().any(TileSource::has_attribute_index) {
+		let state = QueryHandlerState {
+			sources: sources.to_vec(),
+		};
+		api_app = api_app.merge(Router::new().route("/query", get(serve_query)).with_state(state));
+	}
+
 	Ok(app.merge(api_app))
 }
 
+/// Attach probe endpoints: `/status` and `/healthz` for liveness, `/readyz` for readiness.
+/// `/readyz` reads a test tile from every source, each within `timeout`.
+pub fn add_health_to_app(app: Router, sources: &[TileSource], timeout: Duration) -> Router {
+	let state = ReadyHandlerState {
+		sources: sources.to_vec(),
+		timeout,
+	};
+	let health_app = Router::new()
+		.route("/status", get(|| async { "ready!" }))
+		.route("/healthz", get(serve_healthz))
+		.route("/readyz", get(serve_readyz))
+		.with_state(state);
+	app.merge(health_app)
+}
+
 // --- tests -------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
@@ -87,6 +122,59 @@ mod tests {
 		assert_eq!(body, "[]");
 	}
 
+	#[tokio::test]
+	async fn api_query_uses_attribute_index() -> Result<()> {
+		use crate::AttributeIndex;
+		use versatiles_container::{MockTilesReader, MockTilesReaderProfile, TilesReaderTrait};
+
+		let index = AttributeIndex::from_text("#versatiles-attribute-index 3\npoi\tname\tcafe\t3/1/2,3/4/4\n")?;
+		let mut source = TileSource::from(
+			MockTilesReader::new_mock_profile(MockTilesReaderProfile::Pbf)?.boxed(),
+			"osm",
+		)?;
+		source.set_attribute_index(index);
+		let app = add_api_to_app(Router::new(), &[source]).await?;
+
+		let (status, body) = get_body_text(app.clone(), "/query?layer=poi&key=name&value=cafe").await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(body, r#"{"tiles":[[3,1,2],[3,4,4]]}"#);
+
+		let (status, body) = get_body_text(app.clone(), "/query?layer=poi&key=name&value=bar&source=osm").await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(body, r#"{"tiles":[]}"#);
+
+		let (status, _body) = get_body_text(app.clone(), "/query?layer=poi&key=name").await;
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+
+		let (status, _body) = get_body_text(app, "/query?layer=poi&key=name&value=cafe&source=other").await;
+		assert_eq!(status, StatusCode::NOT_FOUND);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn health_endpoints() -> Result<()> {
+		use versatiles_container::{MockTilesReader, MockTilesReaderProfile, TilesReaderTrait};
+
+		let app = add_health_to_app(Router::new(), &[], Duration::from_secs(1));
+		let (status, body) = get_body_text(app.clone(), "/healthz").await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(body, r#"{"status":"ok"}"#);
+		let (status, body) = get_body_text(app, "/readyz").await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(body, r#"{"sources":[],"status":"ready"}"#);
+
+		let source = TileSource::from(
+			MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?.boxed(),
+			"osm",
+		)?;
+		let app = add_health_to_app(Router::new(), &[source], Duration::from_secs(1));
+		let (status, body) = get_body_text(app, "/readyz").await;
+		assert_eq!(status, StatusCode::OK);
+		assert!(body.starts_with(r#"{"sources":[{"id":"osm","ms":"#), "{body}");
+		assert!(body.ends_with(r#","ready":true}],"status":"ready"}"#), "{body}");
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn no_tile_sources_yields_404() {
 		let app = Router::new();
@@ -96,6 +184,19 @@ mod tests {
 		assert_eq!(status, StatusCode::NOT_FOUND);
 	}
 
+	#[tokio::test]
+	async fn styles_are_served_at_their_urls() {
+		let styles = [(String::from("/styles/osm.json"), String::from(r#"{"version":8}"#))];
+		let app = add_styles_to_app(Router::new(), &styles);
+
+		let (status, body) = get_body_text(app.clone(), "/styles/osm.json").await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(body, r#"{"version":8}"#);
+
+		let (status, _body) = get_body_text(app, "/styles/other.json").await;
+		assert_eq!(status, StatusCode::NOT_FOUND);
+	}
+
 	#[tokio::test]
 	async fn no_static_sources_yields_404() {
 		let app = Router::new();
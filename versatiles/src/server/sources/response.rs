@@ -1,5 +1,6 @@
 use versatiles_core::{Blob, TileCompression};
 
+#[derive(Debug)]
 pub struct SourceResponse {
 	pub blob: Blob,
 	pub compression: TileCompression,
@@ -1,9 +1,12 @@
 use super::{super::utils::Url, SourceResponse};
-use anyhow::Result;
-use std::{fmt::Debug, sync::Arc};
+use crate::AttributeIndex;
+use anyhow::{Result, ensure};
+use std::{fmt::Debug, sync::Arc, time::Duration};
 use tokio::sync::Mutex;
-use versatiles_container::TilesReaderTrait;
-use versatiles_core::{Blob, TileCompression, TileCoord, utils::TargetCompression};
+use versatiles_container::{
+	TileTimeoutError, TilesReaderTrait, get_tile_cancellable_with_timeout, get_tile_with_timeout,
+};
+use versatiles_core::{Blob, CancellationToken, TileCompression, TileCoord, utils::TargetCompression};
 use versatiles_derive::context;
 
 // TileSource struct definition
@@ -11,11 +14,16 @@ use versatiles_derive::context;
 pub struct TileSource {
 	pub prefix: Url,
 	pub id: String,
-	reader: Arc<Mutex<Box<dyn TilesReaderTrait>>>,
+	reader: Arc<Mutex<Arc<dyn TilesReaderTrait>>>,
 	pub tile_mime: String,
 	pub compression: TileCompression,
+	attribute_index: Option<Arc<AttributeIndex>>,
+	tile_timeout: Option<Duration>,
 }
 
+/// Maximum number of tiles read to answer a GeoJSON query, so a single request can't keep the reader busy.
+const MAX_QUERY_TILES: usize = 32;
+
 impl TileSource {
 	// Constructor function for creating a TileSource instance
 	#[context("creating tile source: id='{id}'")]
@@ -27,15 +35,106 @@ impl TileSource {
 		Ok(TileSource {
 			prefix: Url::new(format!("/tiles/{id}/")).to_dir(),
 			id: id.to_owned(),
-			reader: Arc::new(Mutex::new(reader)),
+			reader: Arc::new(Mutex::new(Arc::from(reader))),
 			tile_mime,
 			compression,
+			attribute_index: None,
+			tile_timeout: None,
 		})
 	}
 
+	/// Attaches an attribute index, enabling [`TileSource::query`].
+	pub fn set_attribute_index(&mut self, index: AttributeIndex) {
+		self.attribute_index = Some(Arc::new(index));
+	}
+
+	/// Sets the maximum time to read a single tile. (default: no limit)
+	///
+	/// If a tile takes longer, [`TileSource::get_data`] fails with a [`TileTimeoutError`].
+	pub fn set_tile_timeout(&mut self, timeout: Option<Duration>) {
+		self.tile_timeout = timeout;
+	}
+
+	pub fn has_attribute_index(&self) -> bool {
+		self.attribute_index.is_some()
+	}
+
+	/// Returns the current reader. The lock is only held while cloning the `Arc`, so slow requests
+	/// don't block other requests or [`TileSource::replace_reader`].
+	async fn reader(&self) -> Arc<dyn TilesReaderTrait> {
+		self.reader.lock().await.clone()
+	}
+
+	/// Looks up `layer`/`key`/`value` in the attribute index.
+	///
+	/// Returns `{"tiles":[[z,x,y],…]}`, or a GeoJSON FeatureCollection if `geojson` is set.
+	/// Returns `None` if the source has no attribute index.
+	#[context("querying tile source id='{}'", self.id)]
+	pub async fn query(&self, layer: &str, key: &str, value: &str, geojson: bool) -> Result<Option<String>> {
+		let Some(index) = &self.attribute_index else {
+			return Ok(None);
+		};
+
+		if geojson {
+			let reader = self.reader().await;
+			let collection = index
+				.query_features(reader.as_ref(), layer, key, value, MAX_QUERY_TILES)
+				.await?;
+			return Ok(Some(collection.stringify()));
+		}
+
+		let tiles = index
+			.query(layer, key, value)
+			.iter()
+			.map(|c| format!("[{},{},{}]", c.level, c.x, c.y))
+			.collect::<Vec<_>>()
+			.join(",");
+		Ok(Some(format!("{{\"tiles\":[{tiles}]}}")))
+	}
+
+	/// Replaces the reader, e.g. after the container file has changed on disk.
+	///
+	/// Running requests finish with the old reader, so every request sees either the old or the new one.
+	/// Fails if the tile format or compression differs, because they are fixed when the routes are built.
+	#[context("replacing reader of tile source id='{}'", self.id)]
+	pub async fn replace_reader(&self, reader: Box<dyn TilesReaderTrait>) -> Result<()> {
+		let parameters = reader.parameters();
+		ensure!(
+			parameters.tile_format.as_mime_str() == self.tile_mime,
+			"tile format changed from '{}' to '{}'",
+			self.tile_mime,
+			parameters.tile_format.as_mime_str()
+		);
+		ensure!(
+			parameters.tile_compression == self.compression,
+			"tile compression changed from {:?} to {:?}",
+			self.compression,
+			parameters.tile_compression
+		);
+		*self.reader.lock().await = Arc::from(reader);
+		Ok(())
+	}
+
 	pub async fn get_source_name(&self) -> String {
-		let reader = self.reader.lock().await;
-		reader.source_name().to_owned()
+		self.reader().await.source_name().to_owned()
+	}
+
+	/// Checks that the source can serve tiles by reading a test tile within `timeout`.
+	///
+	/// The test tile is the first tile at the lowest zoom level. A missing tile is fine,
+	/// as long as the reader answers without an error.
+	#[context("checking readiness of tile source id='{}'", self.id)]
+	pub async fn check_ready(&self, timeout: Duration) -> Result<()> {
+		let reader = self.reader().await;
+		let pyramid = &reader.parameters().bbox_pyramid;
+		let Some(coord) = pyramid
+			.get_level_min()
+			.and_then(|level| pyramid.get_level_bbox(level).iter_coords().next())
+		else {
+			return Ok(());
+		};
+		get_tile_with_timeout(reader.as_ref(), &coord, Some(timeout)).await?;
+		Ok(())
 	}
 
 	// Retrieve the tile data as an HTTP response
@@ -56,20 +155,25 @@ impl TileSource {
 
 			log::debug!("get tile, prefix: {}, coord: {}", self.prefix, coord.as_json());
 
-			// Get tile data
-			let reader = self.reader.lock().await;
-			let tile = reader.get_tile(&coord).await;
-			drop(reader);
-
-			// If tile data is not found, return a not found response
-			if tile.is_err() {
-				return Ok(None);
-			}
+			// Get tile data. If the client disconnects, this future is dropped and the guard
+			// cancels the token, so readers like pipelines stop generating the tile.
+			let token = CancellationToken::new();
+			let _guard = token.clone().drop_guard();
+			let reader = self.reader().await;
+			let tile = get_tile_cancellable_with_timeout(reader.as_ref(), &coord, self.tile_timeout, token).await;
+
+			// If tile data is not found, return a not found response.
+			// Timeouts are returned as errors, so a hung backend is not mistaken for a missing tile.
+			let tile = match tile {
+				Ok(tile) => tile,
+				Err(err) if err.is::<TileTimeoutError>() => return Err(err),
+				Err(_) => return Ok(None),
+			};
 
 			// If tile data is not found, return a not found response
-			return if let Some(tile) = tile? {
+			return if let Some(tile) = tile {
 				Ok(SourceResponse::new_some(
-					tile.into_blob(self.compression)?,
+					tile.into_blob_pooled(self.compression).await?,
 					self.compression,
 					&self.tile_mime,
 				))
@@ -93,7 +197,7 @@ impl TileSource {
 
 	#[context("building tilejson for tile source id='{}'", self.id)]
 	async fn build_tile_json(&self) -> Result<Blob> {
-		let reader = self.reader.lock().await;
+		let reader = self.reader().await;
 		let mut tilejson = reader.tilejson().clone();
 		tilejson.update_from_reader_parameters(reader.parameters());
 
@@ -121,8 +225,8 @@ mod tests {
 	use crate::get_registry;
 	use anyhow::Result;
 	use rstest::rstest;
-	use versatiles_container::{MockTilesReader, MockTilesReaderProfile, ProcessingConfig};
-	use versatiles_core::TileJSON;
+	use versatiles_container::{HangingReader, MockTilesReader, MockTilesReaderProfile, ProcessingConfig};
+	use versatiles_core::{TileBBoxPyramid, TileFormat, TileJSON, TilesReaderParameters};
 
 	// Test the constructor function for TileSource
 	#[tokio::test]
@@ -151,6 +255,152 @@ mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn replace_reader() -> Result<()> {
+		let reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?;
+		let container = TileSource::from(reader.boxed(), "prefix")?;
+		let clone = container.clone();
+
+		let maxzoom =
+			|blob: Blob| -> Result<Option<f64>> { TileJSON::try_from(&blob)?.as_object().get_number("maxzoom") };
+		assert_eq!(maxzoom(clone.build_tile_json().await?)?, Some(6.0));
+
+		let parameters = TilesReaderParameters::new(
+			TileFormat::PNG,
+			TileCompression::Uncompressed,
+			TileBBoxPyramid::new_full(3),
+		);
+		container
+			.replace_reader(MockTilesReader::new_mock(parameters)?.boxed())
+			.await?;
+		assert_eq!(maxzoom(clone.build_tile_json().await?)?, Some(3.0));
+
+		let reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Pbf)?;
+		assert!(container.replace_reader(reader.boxed()).await.is_err());
+		assert_eq!(maxzoom(clone.build_tile_json().await?)?, Some(3.0));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn tile_timeout() -> Result<()> {
+		let reader = HangingReader(MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?);
+		let mut container = TileSource::from(Box::new(reader), "prefix")?;
+		container.set_tile_timeout(Some(Duration::from_millis(10)));
+
+		let error = container
+			.get_data(
+				&Url::from("3/1/2"),
+				&TargetCompression::from(TileCompression::Uncompressed),
+			)
+			.await
+			.unwrap_err();
+		assert!(error.downcast_ref::<TileTimeoutError>().is_some());
+
+		let error = container.check_ready(Duration::from_millis(10)).await.unwrap_err();
+		assert!(error.downcast_ref::<TileTimeoutError>().is_some());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn pending_tile_does_not_block_source() -> Result<()> {
+		let reader = HangingReader(MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?);
+		let container = TileSource::from(Box::new(reader), "prefix")?;
+		let clone = container.clone();
+		let request = tokio::spawn(async move {
+			let target = TargetCompression::from(TileCompression::Uncompressed);
+			clone.get_data(&Url::from("3/1/2"), &target).await.map(|_| ())
+		});
+		tokio::task::yield_now().await;
+
+		// the pending request must not hold the reader
+		let timeout = Duration::from_secs(5);
+		tokio::time::timeout(timeout, container.build_tile_json()).await??;
+		let reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?;
+		tokio::time::timeout(timeout, container.replace_reader(reader.boxed())).await??;
+
+		request.abort();
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn client_disconnect_cancels_tile() -> Result<()> {
+		/// A reader that keeps the cancellation token of the pending request.
+		#[derive(Debug)]
+		struct RecordingReader(MockTilesReader, Arc<std::sync::Mutex<Option<CancellationToken>>>);
+
+		#[async_trait::async_trait]
+		impl TilesReaderTrait for RecordingReader {
+			fn source_name(&self) -> &str {
+				"recording"
+			}
+			fn container_name(&self) -> &str {
+				"recording"
+			}
+			fn parameters(&self) -> &TilesReaderParameters {
+				self.0.parameters()
+			}
+			fn override_compression(&mut self, _tile_compression: TileCompression) {}
+			fn tilejson(&self) -> &TileJSON {
+				self.0.tilejson()
+			}
+			async fn get_tile(&self, _coord: &TileCoord) -> Result<Option<versatiles_container::Tile>> {
+				std::future::pending().await
+			}
+			async fn get_tile_cancellable(
+				&self,
+				_coord: &TileCoord,
+				token: CancellationToken,
+			) -> Result<Option<versatiles_container::Tile>> {
+				*self.1.lock().unwrap() = Some(token.clone());
+				token.cancelled().await;
+				Ok(None)
+			}
+		}
+
+		let recorded = Arc::new(std::sync::Mutex::new(None));
+		let reader = RecordingReader(
+			MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?,
+			recorded.clone(),
+		);
+		let container = TileSource::from(Box::new(reader), "prefix")?;
+		let request = tokio::spawn(async move {
+			let target = TargetCompression::from(TileCompression::Uncompressed);
+			container.get_data(&Url::from("3/1/2"), &target).await.map(|_| ())
+		});
+
+		let token = loop {
+			if let Some(token) = recorded.lock().unwrap().clone() {
+				break token;
+			}
+			tokio::task::yield_now().await;
+		};
+		assert!(!token.is_cancelled());
+
+		// a disconnecting client drops the request future
+		request.abort();
+		assert!(request.await.unwrap_err().is_cancelled());
+		assert!(token.is_cancelled());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn check_ready() -> Result<()> {
+		let reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?;
+		let container = TileSource::from(reader.boxed(), "prefix")?;
+		container.check_ready(Duration::from_secs(1)).await?;
+
+		let parameters = TilesReaderParameters::new(
+			TileFormat::PNG,
+			TileCompression::Uncompressed,
+			TileBBoxPyramid::new_empty(),
+		);
+		container
+			.replace_reader(MockTilesReader::new_mock(parameters)?.boxed())
+			.await?;
+		container.check_ready(Duration::from_secs(1)).await?;
+		Ok(())
+	}
+
 	// Test the get_data method of the TileSource
 	#[rstest]
 	#[case(
@@ -208,7 +458,7 @@ mod tests {
 		let (exp_mime, exp_bounds, exp_header, exp_minzoom, exp_maxzoom) = expected_tile_json;
 
 		let registry = get_registry(ProcessingConfig::default());
-		let reader = registry.get_reader_from_str(filename).await?;
+		let reader = registry.open_reader(filename).await?;
 		let c = &mut TileSource::from(reader, "prefix")?;
 
 		assert_eq!(
@@ -6,22 +6,29 @@
 //! - `routes` composes handlers into an Axum `Router`.
 //! - `encoding` parses `Accept-Encoding` into our internal compression bitset.
 //! - `cors` builds a `CorsLayer` from user-configurable origin patterns.
+//! - `watch` reloads tile sources whose container files change on disk.
+//! - `quota` limits the number of requests per client.
 //!
 //! `tile_server.rs` owns *lifecycle* concerns only: configuration ingestion,
-//! building the router, applying cross-cutting middlewares (CORS, backpressure,
-//! timeouts, panic catching), listening on a socket, graceful shutdown, and
-//! a tiny `/status` probe for liveness checks.
+//! building the router, applying cross-cutting middlewares (CORS, quotas, backpressure,
+//! timeouts, panic catching), listening on a socket, graceful shutdown,
+//! and probe endpoints (`/status`, `/healthz`, `/readyz`) for liveness and readiness checks.
 
-use super::{cors, routes, sources};
+use super::{cors, quota, routes, sources, watch};
 #[cfg(test)]
 use crate::get_registry;
-use crate::{Config, TileSourceConfig};
-use anyhow::{Result, bail};
+use crate::{AttributeIndex, Config, TileSourceConfig};
+use anyhow::{Result, bail, ensure};
+use axum::Router;
 use axum::error_handling::HandleErrorLayer;
 use axum::http::{StatusCode, header::HeaderName, header::HeaderValue};
 use axum::{BoxError, response::IntoResponse};
-use axum::{Router, routing::get};
-use std::path::Path;
+use std::{
+	net::SocketAddr,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::Duration,
+};
 use tokio::{net::TcpListener, sync::oneshot};
 use tower::{
 	ServiceBuilder, buffer::BufferLayer, limit::ConcurrencyLimitLayer, load_shed::LoadShedLayer, timeout::TimeoutLayer,
@@ -30,7 +37,8 @@ use tower_http::catch_panic::CatchPanicLayer;
 use tower_http::set_header::SetResponseHeaderLayer;
 #[cfg(test)]
 use versatiles_container::ProcessingConfig;
-use versatiles_container::{ContainerRegistry, TilesReaderTrait};
+use versatiles_container::{ContainerRegistry, DataLocation, TileCodecPool, TilesReaderTrait};
+use versatiles_core::json::JsonObject;
 use versatiles_derive::context;
 
 /// Thin orchestration layer for the VersaTiles HTTP server.
@@ -56,6 +64,8 @@ pub struct TileServer {
 	port: u16,
 	tile_sources: Vec<sources::TileSource>,
 	static_sources: Vec<sources::StaticSource>,
+	/// Style JSONs served at fixed URLs, as `(url, json)`.
+	styles: Vec<(String, String)>,
 	/// One-shot channel to signal graceful shutdown to the serving task.
 	exit_signal: Option<oneshot::Sender<()>>,
 	/// Join handle for the serving task; awaited in `stop()` to ensure shutdown completes.
@@ -70,6 +80,16 @@ pub struct TileServer {
 	cors_max_age_seconds: u64,
 	/// Extra response headers as configured.
 	extra_response_headers: Vec<(HeaderName, HeaderValue)>,
+	/// Reload tile sources when their container files change on disk.
+	watch: bool,
+	/// Tile sources added from a config, together with their location, for reloading.
+	source_locations: Vec<(sources::TileSource, DataLocation)>,
+	/// Task polling the files of `source_locations`; aborted in `stop()`.
+	watcher: Option<tokio::task::JoinHandle<()>>,
+	/// Maximum time to read a single tile; slower tiles are answered with `504`.
+	tile_timeout: Option<Duration>,
+	/// Per-client request quotas; clients over their quota get `429`.
+	quota: Option<Arc<quota::QuotaLimiter>>,
 }
 
 impl TileServer {
@@ -80,6 +100,7 @@ impl TileServer {
 			port,
 			tile_sources: Vec::new(),
 			static_sources: Vec::new(),
+			styles: Vec::new(),
 			exit_signal: None,
 			join: None,
 			minimal_recompression,
@@ -88,6 +109,11 @@ impl TileServer {
 			cors_allowed_origins: Vec::new(),
 			cors_max_age_seconds: 3600,
 			extra_response_headers: Vec::new(),
+			watch: false,
+			source_locations: Vec::new(),
+			watcher: None,
+			tile_timeout: None,
+			quota: None,
 		}
 	}
 
@@ -96,7 +122,7 @@ impl TileServer {
 	/// This ingests tile and static sources, applying optional on-the-fly
 	/// transforms (e.g., `flip_y`, `swap_xy`) and compression overrides.
 	#[context("building tile server from config")]
-	pub async fn from_config(config: Config, registry: ContainerRegistry) -> Result<TileServer> {
+	pub async fn from_config(config: Config, mut registry: ContainerRegistry) -> Result<TileServer> {
 		let mut parsed_headers: Vec<(HeaderName, HeaderValue)> = Vec::new();
 		for (k, v) in &config.extra_response_headers {
 			let name =
@@ -106,11 +132,19 @@ impl TileServer {
 			parsed_headers.push((name, value));
 		}
 
+		let tile_timeout = config.server.tile_timeout.map(Duration::from_millis);
+		registry.set_request_timeout(tile_timeout);
+		registry.set_index_cache_dir(config.server.index_cache.as_ref().map(PathBuf::from));
+		if let Some(size) = config.server.codec_pool_size {
+			TileCodecPool::set_size(size);
+		}
+
 		let mut server = TileServer {
 			ip: config.server.ip.unwrap_or("0.0.0.0".into()),
 			port: config.server.port.unwrap_or(8080),
 			tile_sources: Vec::new(),
 			static_sources: Vec::new(),
+			styles: Vec::new(),
 			exit_signal: None,
 			join: None,
 			minimal_recompression: config.server.minimal_recompression.unwrap_or(false),
@@ -119,6 +153,11 @@ impl TileServer {
 			cors_allowed_origins: config.cors.allowed_origins.clone(),
 			cors_max_age_seconds: config.cors.max_age_seconds.unwrap_or(3600),
 			extra_response_headers: parsed_headers,
+			watch: config.server.watch.unwrap_or(false),
+			source_locations: Vec::new(),
+			watcher: None,
+			tile_timeout,
+			quota: quota::QuotaLimiter::from_config(&config.quota)?.map(Arc::new),
 		};
 
 		for tile_config in config.tile_sources.iter() {
@@ -146,8 +185,18 @@ impl TileServer {
 		);
 
 		let reader = self.registry.get_reader(tile_config.path.clone()).await?;
+		self.add_tile_source(&name, reader)?;
+
+		if let Some(index_path) = &tile_config.index {
+			let index = AttributeIndex::read_from_path(index_path.as_path()?)?;
+			log::debug!("add attribute index: name='{name}', entries={}", index.len());
+			self.tile_sources.last_mut().unwrap().set_attribute_index(index);
+		}
+
+		let source = self.tile_sources.last().unwrap().clone();
+		self.source_locations.push((source, tile_config.path.clone()));
 
-		self.add_tile_source(&name, reader)
+		Ok(())
 	}
 
 	/// Register a tile source under `/tiles/<name>/...`.
@@ -157,7 +206,8 @@ impl TileServer {
 	pub fn add_tile_source(&mut self, name: &str, reader: Box<dyn TilesReaderTrait>) -> Result<()> {
 		log::debug!("add source: id='{name}', source={reader:?}");
 
-		let source = sources::TileSource::from(reader, name)?;
+		let mut source = sources::TileSource::from(reader, name)?;
+		source.set_tile_timeout(self.tile_timeout);
 		let url_prefix = &source.prefix;
 
 		for other_tile_source in self.tile_sources.iter() {
@@ -180,6 +230,19 @@ impl TileServer {
 		Ok(())
 	}
 
+	/// Serve a style JSON at `url`, e.g. `/styles/osm.json`.
+	#[context("adding style: url='{url}'")]
+	pub fn add_style(&mut self, url: &str, style: &JsonObject) -> Result<()> {
+		ensure!(url.starts_with('/'), "style url '{url}' must start with '/'");
+		ensure!(
+			self.styles.iter().all(|(other, _)| other != url),
+			"multiple styles with the url '{url}' are defined"
+		);
+		log::debug!("add style: {url}");
+		self.styles.push((url.to_owned(), style.stringify()));
+		Ok(())
+	}
+
 	/// Start listening and serving requests.
 	///
 	/// - Idempotent: if already running, the previous instance is stopped first.
@@ -195,13 +258,24 @@ impl TileServer {
 		log::info!("starting server");
 
 		// Build the router
-		let mut router = Router::new().route("/status", get(|| async { "ready!" }));
+		let mut router = Router::new();
 		router = self.add_tile_sources_to_app(router);
+		router = routes::add_styles_to_app(router, &self.styles);
 		if !self.disable_api {
 			router = self.add_api_to_app(router).await?;
 		}
 		router = self.add_static_sources_to_app(router);
 
+		// Quotas apply to everything except the health probes.
+		if let Some(limiter) = &self.quota {
+			router = router.layer(axum::middleware::from_fn_with_state(
+				limiter.clone(),
+				quota::enforce_quota,
+			));
+		}
+		let probe_timeout = self.tile_timeout.unwrap_or(Duration::from_secs(5));
+		router = routes::add_health_to_app(router, &self.tile_sources, probe_timeout);
+
 		let cors_layer = cors::build_cors_layer(&self.cors_allowed_origins, self.cors_max_age_seconds)?;
 		router = router.layer(ServiceBuilder::new().layer(cors_layer));
 
@@ -254,7 +328,7 @@ impl TileServer {
 
 		// Spawn the server and keep a handle so we can await it on shutdown.
 		let handle = tokio::spawn(async move {
-			if let Err(err) = axum::serve(listener, router.into_make_service())
+			if let Err(err) = axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>())
 				.with_graceful_shutdown(async {
 					rx.await.ok();
 				})
@@ -268,6 +342,14 @@ impl TileServer {
 		self.exit_signal = Some(tx);
 		self.join = Some(handle);
 
+		if self.watch {
+			self.watcher = Some(watch::spawn_watcher(
+				self.source_locations.clone(),
+				self.registry.clone(),
+				std::time::Duration::from_secs(1),
+			));
+		}
+
 		Ok(())
 	}
 
@@ -275,6 +357,10 @@ impl TileServer {
 	///
 	/// Idempotent: if the server is not running, this returns immediately.
 	pub async fn stop(&mut self) {
+		if let Some(watcher) = self.watcher.take() {
+			watcher.abort();
+		}
+
 		// If not running, do nothing (idempotent).
 		if self.exit_signal.is_none() && self.join.is_none() {
 			return;
@@ -589,4 +675,42 @@ mod tests {
 		server.stop().await;
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn quota_returns_429_with_retry_after() -> Result<()> {
+		let mut server = TileServer::new_test(IP, 0, true, false);
+		server.quota = quota::QuotaLimiter::from_config(&crate::QuotaConfig {
+			requests_per_minute: Some(1),
+			burst: Some(2),
+			key_header: Some(String::from("X-API-Key")),
+			api_keys: vec![String::from("a"), String::from("b")],
+			ip_header: None,
+		})?
+		.map(Arc::new);
+
+		server.start().await?;
+		let port = server.port;
+
+		let client = Client::builder().build().unwrap();
+		let get = |path: &str, key: &str| {
+			client
+				.get(format!("http://{IP}:{port}{path}"))
+				.header("X-API-Key", key)
+				.send()
+		};
+
+		assert_eq!(get("/tiles/index.json", "a").await.unwrap().status(), 200);
+		assert_eq!(get("/tiles/index.json", "a").await.unwrap().status(), 200);
+		let resp = get("/tiles/index.json", "a").await.unwrap();
+		assert_eq!(resp.status(), 429);
+		let retry_after: u64 = resp.headers()[header::RETRY_AFTER].to_str()?.parse()?;
+		assert!((1..=60).contains(&retry_after), "{retry_after}");
+
+		// other clients and the liveness probe are not affected
+		assert_eq!(get("/tiles/index.json", "b").await.unwrap().status(), 200);
+		assert_eq!(get("/status", "a").await.unwrap().status(), 200);
+
+		server.stop().await;
+		Ok(())
+	}
 }
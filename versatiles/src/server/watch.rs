@@ -0,0 +1,160 @@
+//! Hot reloading of tile sources whose container files change on disk.
+//!
+//! The watcher polls the modification time, size and inode of every local container
+//! file. A change is only acted upon once the file has been stable for one more
+//! polling interval, so that containers that are still being written are not opened.
+//! The new reader then replaces the old one inside the [`TileSource`], so the server
+//! keeps running and all routes stay the same.
+
+use super::sources::TileSource;
+use std::{
+	fs::Metadata,
+	path::{Path, PathBuf},
+	time::{Duration, SystemTime},
+};
+use tokio::task::JoinHandle;
+use versatiles_container::{ContainerRegistry, DataLocation};
+
+/// The properties of a file that are compared to detect changes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct FileStamp {
+	modified: Option<SystemTime>,
+	len: u64,
+	inode: u64,
+}
+
+impl FileStamp {
+	/// Reads the stamp of `path`, or `None` if the file doesn't exist (e.g. while it is being replaced).
+	fn read(path: &Path) -> Option<FileStamp> {
+		let metadata = std::fs::metadata(path).ok()?;
+		Some(FileStamp {
+			modified: metadata.modified().ok(),
+			len: metadata.len(),
+			inode: inode(&metadata),
+		})
+	}
+}
+
+#[cfg(unix)]
+fn inode(metadata: &Metadata) -> u64 {
+	use std::os::unix::fs::MetadataExt;
+	metadata.ino()
+}
+
+#[cfg(not(unix))]
+fn inode(_metadata: &Metadata) -> u64 {
+	0
+}
+
+/// Detects changes of a single file.
+#[derive(Debug)]
+struct FileWatch {
+	path: PathBuf,
+	/// Stamp of the file that is currently served.
+	current: Option<FileStamp>,
+	/// Stamp of a changed file, waiting to become stable.
+	pending: Option<FileStamp>,
+}
+
+impl FileWatch {
+	fn new(path: PathBuf) -> FileWatch {
+		FileWatch {
+			current: FileStamp::read(&path),
+			pending: None,
+			path,
+		}
+	}
+
+	/// Returns `true` if the file has changed and has not changed again since the last poll.
+	fn poll(&mut self) -> bool {
+		let stamp = FileStamp::read(&self.path);
+		if stamp == self.current {
+			self.pending = None;
+			return false;
+		}
+		if stamp.is_some() && stamp == self.pending {
+			self.current = stamp;
+			self.pending = None;
+			return true;
+		}
+		self.pending = stamp;
+		false
+	}
+}
+
+/// Spawns a task that polls the files of `sources` every `interval` and reloads changed ones.
+///
+/// Sources that are not local files (e.g. URLs) are ignored. Abort the returned handle to stop watching.
+pub fn spawn_watcher(
+	sources: Vec<(TileSource, DataLocation)>,
+	registry: ContainerRegistry,
+	interval: Duration,
+) -> JoinHandle<()> {
+	let mut watches = sources
+		.into_iter()
+		.filter_map(|(source, location)| {
+			let path = location.as_path().ok()?.to_path_buf();
+			log::info!("watching tile source '{}': {path:?}", source.id);
+			Some((source, location, FileWatch::new(path)))
+		})
+		.collect::<Vec<_>>();
+
+	tokio::spawn(async move {
+		loop {
+			tokio::time::sleep(interval).await;
+			for (source, location, watch) in watches.iter_mut() {
+				if watch.poll() {
+					reload(source, location, &registry).await;
+				}
+			}
+		}
+	})
+}
+
+/// Opens the container again and swaps the reader. On errors the old reader is kept.
+async fn reload(source: &TileSource, location: &DataLocation, registry: &ContainerRegistry) {
+	log::info!("reloading tile source '{}' from {location:?}", source.id);
+	let result = match registry.get_reader(location.clone()).await {
+		Ok(reader) => source.replace_reader(reader).await,
+		Err(err) => Err(err),
+	};
+	if let Err(err) = result {
+		log::warn!(
+			"failed to reload tile source '{}', keep serving the old content: {err:?}",
+			source.id
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use assert_fs::TempDir;
+
+	#[test]
+	fn file_watch() -> Result<()> {
+		let dir = TempDir::new()?;
+		let path = dir.path().join("tiles.versatiles");
+		std::fs::write(&path, "old")?;
+
+		let mut watch = FileWatch::new(path.clone());
+		assert!(!watch.poll());
+
+		// a change is reported once the file is stable for one poll
+		std::fs::write(&path, "new content")?;
+		assert!(!watch.poll());
+		assert!(watch.poll());
+		assert!(!watch.poll());
+
+		// a missing file is never reported
+		std::fs::remove_file(&path)?;
+		assert!(!watch.poll());
+		assert!(!watch.poll());
+
+		std::fs::write(&path, "newer content")?;
+		assert!(!watch.poll());
+		assert!(watch.poll());
+		Ok(())
+	}
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use versatiles_container::{BENCHMARK_CONTAINER_TYPES, MockTilesReaderProfile, benchmark_container};
+
+#[derive(clap::Args, Debug)]
+#[command(disable_version_flag = true)]
+pub struct Subcommand {
+	/// container types to benchmark, comma separated
+	#[arg(long, value_delimiter = ',', default_values_t = BENCHMARK_CONTAINER_TYPES.map(String::from))]
+	containers: Vec<String>,
+
+	/// maximum zoom level of the generated fixtures
+	#[arg(long, value_name = "int", default_value_t = 8)]
+	max_zoom: u8,
+
+	/// tile format of the generated fixtures
+	#[arg(long, value_enum, default_value_t = FixtureFormat::Pbf)]
+	tile_format: FixtureFormat,
+
+	/// number of tiles read to measure the latency of single tile requests
+	#[arg(long, value_name = "int", default_value_t = 1000)]
+	samples: usize,
+
+	/// directory for the generated fixtures, defaults to a temporary directory
+	#[arg(long, value_name = "path")]
+	dir: Option<PathBuf>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum FixtureFormat {
+	Json,
+	Png,
+	Pbf,
+}
+
+impl From<FixtureFormat> for MockTilesReaderProfile {
+	fn from(format: FixtureFormat) -> Self {
+		match format {
+			FixtureFormat::Json => MockTilesReaderProfile::Json,
+			FixtureFormat::Png => MockTilesReaderProfile::Png,
+			FixtureFormat::Pbf => MockTilesReaderProfile::Pbf,
+		}
+	}
+}
+
+#[tokio::main]
+pub async fn run(arguments: &Subcommand) -> Result<()> {
+	let (dir, is_temporary) = match &arguments.dir {
+		Some(dir) => (dir.clone(), false),
+		None => (
+			std::env::temp_dir().join(format!("versatiles_bench_{}", std::process::id())),
+			true,
+		),
+	};
+	std::fs::create_dir_all(&dir)?;
+
+	let result = run_benchmarks(arguments, &dir).await;
+
+	if is_temporary && let Err(err) = std::fs::remove_dir_all(&dir) {
+		log::warn!("failed to remove benchmark directory {dir:?}: {err}");
+	}
+	result
+}
+
+async fn run_benchmarks(arguments: &Subcommand, dir: &std::path::Path) -> Result<()> {
+	println!(
+		"{:<12} {:>10} {:>12} {:>14} {:>16}",
+		"container", "tiles", "write", "read latency", "stream tiles/s"
+	);
+	for container_type in &arguments.containers {
+		log::info!("benchmarking {container_type}");
+		let result = benchmark_container(
+			dir,
+			container_type,
+			arguments.tile_format.into(),
+			arguments.max_zoom,
+			arguments.samples,
+		)
+		.await?;
+		println!(
+			"{:<12} {:>10} {:>12} {:>14} {:>16.0}",
+			result.container_type,
+			result.tile_count,
+			format!("{:.1?}", result.write_time),
+			format!("{:.1?}", result.read_latency),
+			result.stream_throughput
+		);
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tests::run_command;
+	use anyhow::Result;
+	use assert_fs::TempDir;
+
+	#[test]
+	fn test_bench() -> Result<()> {
+		let dir = TempDir::new()?;
+		run_command(vec![
+			"versatiles",
+			"bench",
+			"-q",
+			"--containers",
+			"versatiles,directory",
+			"--max-zoom",
+			"2",
+			"--samples",
+			"5",
+			"--dir",
+			dir.path().to_str().unwrap(),
+		])?;
+		assert!(dir.path().join("fixture.versatiles").is_file());
+		assert!(run_command(vec!["versatiles", "bench", "-q", "--containers", "zip"]).is_err());
+		Ok(())
+	}
+}
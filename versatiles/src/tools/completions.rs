@@ -0,0 +1,48 @@
+use anyhow::Result;
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::io::Write;
+
+#[derive(clap::Args, Debug)]
+#[command(arg_required_else_help = true)]
+pub struct Subcommand {
+	/// Shell to generate completions for, e.g. add `source <(versatiles completions bash)` to `~/.bashrc`
+	#[arg(value_enum)]
+	shell: Shell,
+}
+
+pub fn run(command: &Subcommand) -> Result<()> {
+	generate(command.shell, &mut std::io::stdout())
+}
+
+fn generate(shell: Shell, writer: &mut impl Write) -> Result<()> {
+	let mut cli = crate::Cli::command();
+	clap_complete::generate(shell, &mut cli, "versatiles", writer);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::run_command;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case(Shell::Bash, "_versatiles()")]
+	#[case(Shell::Zsh, "#compdef versatiles")]
+	#[case(Shell::Fish, "complete -c versatiles")]
+	fn generates_completions(#[case] shell: Shell, #[case] expected: &str) -> Result<()> {
+		let mut output = Vec::new();
+		generate(shell, &mut output)?;
+		let script = String::from_utf8(output)?;
+		assert!(script.contains(expected), "{script}");
+		assert!(script.contains("convert"));
+		assert!(script.contains("max-memory"));
+		Ok(())
+	}
+
+	#[test]
+	fn rejects_unknown_shell() {
+		assert!(run_command(vec!["versatiles", "completions", "cmd"]).is_err());
+	}
+}
@@ -1,20 +1,31 @@
+use super::{
+	convert_batch::ConvertBatch,
+	convert_job::{ConvertJob, ConvertJobOutput},
+};
 use anyhow::{Result, bail};
 use std::path::PathBuf;
-use versatiles::get_registry;
-use versatiles_container::{ProcessingConfig, TilesConverterParameters, convert_tiles_container};
-use versatiles_core::{GeoBBox, TileBBoxPyramid, TileCompression};
-use versatiles_derive::context;
+use versatiles_container::{PathTemplate, ProcessingConfig, TileErrorPolicy};
+use versatiles_core::{GeoBBox, TileCompression};
 
 #[derive(clap::Args, Debug)]
 #[command(arg_required_else_help = true, disable_version_flag = true)]
 pub struct Subcommand {
-	/// supported container formats: *.versatiles, *.tar, *.pmtiles, *.mbtiles or a directory
-	#[arg()]
-	input_file: String,
-
-	/// supported container formats: *.versatiles, *.tar, *.pmtiles, *.mbtiles or a directory
-	#[arg()]
-	output_file: PathBuf,
+	/// supported container formats: *.versatiles, *.tar, *.pmtiles, *.mbtiles or a directory.
+	/// The file name may contain the wildcards "*" and "?" to convert several files, e.g. 'regions/*.mbtiles'
+	#[arg(required_unless_present = "job")]
+	input_file: Option<String>,
+
+	/// supported container formats: *.versatiles, *.tar, *.pmtiles, *.mbtiles or a directory.
+	/// "{name}" is replaced by the input file name without extension, e.g. 'out/{name}.versatiles'.
+	/// Use "-" to write a tar archive to stdout, e.g. to pipe it into 'ssh' or 'aws s3 cp -',
+	/// or "versatiles:-" to write a *.versatiles container
+	#[arg(required_unless_present = "job")]
+	output_file: Option<PathBuf>,
+
+	/// run a conversion job described in a YAML file, with input, filters and one or more outputs,
+	/// instead of using the other arguments
+	#[arg(long, short, value_name = "FILE", conflicts_with_all = ["input_file", "output_file"], display_order = 0)]
+	job: Option<PathBuf>,
 
 	/// minimum zoom level
 	#[arg(long, value_name = "int", display_order = 1)]
@@ -24,7 +35,7 @@ pub struct Subcommand {
 	#[arg(long, value_name = "int", display_order = 1)]
 	max_zoom: Option<u8>,
 
-	/// use only tiles inside a bounding box
+	/// use only tiles inside a bounding box. If lon_min > lon_max, it crosses the antimeridian
 	#[arg(
 		long,
 		short,
@@ -38,6 +49,16 @@ pub struct Subcommand {
 	#[arg(long, value_name = "int", display_order = 1)]
 	bbox_border: Option<u32>,
 
+	/// use only tiles modified after this Unix timestamp in seconds, e.g. for incremental syncs.
+	/// Tiles without a known modification time are always included
+	#[arg(long, value_name = "SECONDS", display_order = 1)]
+	newer_than: Option<u64>,
+
+	/// use only the tiles listed in a CSV file, either with the columns "z,x,y",
+	/// or with PMTiles tile ids or ranges of them ("first-last") in a single column
+	#[arg(long, value_name = "FILE", display_order = 1)]
+	coordinate_list: Option<PathBuf>,
+
 	/// set new compression
 	#[arg(long, short, value_enum, display_order = 2)]
 	compress: Option<TileCompression>,
@@ -57,70 +78,155 @@ pub struct Subcommand {
 	/// set the output tile format
 	#[arg(long, value_name = "TILE_FORMAT", display_order = 3)]
 	tile_format: Option<versatiles_core::TileFormat>,
-}
 
-#[tokio::main]
-pub async fn run(arguments: &Subcommand) -> Result<()> {
-	log::info!("convert from {:?} to {:?}", arguments.input_file, arguments.output_file);
+	/// path template of the tiles, if the input is a directory, e.g. "{z}/{y}/{x}" or "{z}/{id/3}" for hierarchical tile ids
+	/// [placeholders: {z}, {x}, {y}, {-y}, {quadkey}, {id}, {id/3}]
+	#[arg(long, value_name = "TEMPLATE", display_order = 4)]
+	input_path_template: Option<String>,
+
+	/// path template of the tiles, if the output is a directory (default: "{z}/{x}/{y}")
+	#[arg(long, value_name = "TEMPLATE", display_order = 4)]
+	output_path_template: Option<String>,
+
+	/// what to do with tiles that fail to be read or recompressed: "fail", "skip" or "fallback" (replace with an empty tile)
+	#[arg(long, value_name = "POLICY", default_value = "fail", display_order = 5)]
+	on_error: TileErrorPolicy,
+
+	/// maximum time in milliseconds to read a single tile or to wait for a request to an HTTP input.
+	/// Tiles that take longer are handled by --on-error
+	#[arg(long, value_name = "MILLISECONDS", display_order = 5)]
+	tile_timeout: Option<u64>,
+
+	/// maximum size of a single encoded tile in megabytes, 0 disables the limit (default: 50).
+	/// Larger tiles are handled by --on-error
+	#[arg(long, value_name = "MEGABYTES", display_order = 5)]
+	max_tile_size: Option<u64>,
+
+	/// write failed tiles as newline-delimited JSON (z, x, y, stage, error) to this file
+	#[arg(long, value_name = "FILE", display_order = 5)]
+	error_report: Option<PathBuf>,
+
+	/// write the UTFGrid interaction data of an MBTiles input to this directory as "{z}/{x}/{y}.grid.json",
+	/// because containers can not hold it
+	#[arg(long, value_name = "DIRECTORY", display_order = 5)]
+	grids: Option<PathBuf>,
+
+	/// fail on invalid TileJSON metadata of the input or output, instead of only logging it
+	#[arg(long, display_order = 5)]
+	strict_tilejson: bool,
+
+	/// write directly to the output file, instead of writing to "<output>.tmp" and renaming it when complete,
+	/// e.g. for filesystems where renaming does not replace files atomically
+	#[arg(long, display_order = 6)]
+	direct_write: bool,
+
+	/// flush the output file to disk before renaming it
+	#[arg(long, display_order = 6)]
+	fsync: bool,
+
+	/// number of files converted at the same time, if the input matches several files
+	#[arg(long, value_name = "int", display_order = 7)]
+	parallel: Option<usize>,
+}
 
-	let config = ProcessingConfig::default();
-	let registry = get_registry(config);
-	let mut reader = registry.get_reader_from_str(&arguments.input_file).await?;
+pub fn run(arguments: &Subcommand) -> Result<()> {
+	let job = arguments.to_job()?;
 
-	if arguments.override_input_compression.is_some() {
-		reader.override_compression(arguments.override_input_compression.unwrap());
+	let mut runtime = tokio::runtime::Builder::new_multi_thread();
+	if let Some(threads) = job.threads {
+		runtime.worker_threads(threads);
 	}
+	runtime.enable_all().build()?.block_on(run_batch(job))
+}
+
+async fn run_batch(job: ConvertJob) -> Result<()> {
+	let config = ProcessingConfig::default();
 
-	let parameters = TilesConverterParameters {
-		bbox_pyramid: get_bbox_pyramid(arguments)?,
-		flip_y: arguments.flip_y,
-		swap_xy: arguments.swap_xy,
-		tile_compression: arguments.compress,
-	};
+	// Stop reading on Ctrl-C; writers then finish a valid container with the tiles read so far.
+	// A second Ctrl-C aborts writing, which removes the incomplete output.
+	let token = config.cancellation_token.clone();
+	let abort_token = config.abort_token.clone();
+	tokio::spawn(async move {
+		if tokio::signal::ctrl_c().await.is_ok() {
+			log::warn!(
+				"received Ctrl-C, stopping conversion and flushing tiles read so far (press Ctrl-C again to abort)"
+			);
+			token.cancel();
+		}
+		if tokio::signal::ctrl_c().await.is_ok() {
+			log::error!("received Ctrl-C again, aborting and removing the incomplete output");
+			abort_token.cancel();
+		}
+	});
 
-	convert_tiles_container(reader, parameters, &arguments.output_file, registry).await?;
+	convert(job, config).await?;
 
 	log::info!("finished converting tiles");
 
 	Ok(())
 }
 
-#[context("Failed to get bounding box pyramid")]
-fn get_bbox_pyramid(arguments: &Subcommand) -> Result<Option<TileBBoxPyramid>> {
-	if arguments.min_zoom.is_none() && arguments.max_zoom.is_none() && arguments.bbox.is_none() {
-		return Ok(None);
-	}
-
-	let mut bbox_pyramid = TileBBoxPyramid::new_full(32);
-
-	if let Some(level_min) = arguments.min_zoom {
-		bbox_pyramid.set_level_min(level_min)
-	}
-
-	if let Some(level_max) = arguments.max_zoom {
-		bbox_pyramid.set_level_max(level_max)
-	}
-
-	if let Some(bbox) = &arguments.bbox {
-		log::trace!("parsing bbox argument: {bbox:?}");
-		let values: Vec<f64> = bbox
-			.split(&[' ', ',', ';'])
-			.filter(|s| !s.is_empty())
-			.map(|s| s.parse::<f64>().expect("bbox value is not a number"))
-			.collect();
+/// Runs the conversion `job`, cancellable through `config.cancellation_token` and `config.abort_token`.
+pub async fn convert(job: ConvertJob, config: ProcessingConfig) -> Result<()> {
+	ConvertBatch::new(job)?.run(config).await
+}
 
-		if values.len() != 4 {
-			bail!("bbox must contain exactly 4 numbers, but instead i'v got: {bbox:?}");
+impl Subcommand {
+	/// Returns the job from the `--job` file, or builds a job with a single output from the arguments.
+	pub fn to_job(&self) -> Result<ConvertJob> {
+		if let Some(path) = &self.job {
+			return ConvertJob::from_path(path);
 		}
 
-		bbox_pyramid.intersect_geo_bbox(&GeoBBox::try_from(values)?)?;
-
-		if let Some(b) = arguments.bbox_border {
-			bbox_pyramid.add_border(b, b, b, b);
-		}
+		let (Some(input_file), Some(output_file)) = (&self.input_file, &self.output_file) else {
+			bail!("either --job or an input and an output file are required");
+		};
+
+		let bbox = self
+			.bbox
+			.as_deref()
+			.map(|bbox| bbox.parse::<GeoBBox>().map(|bbox| bbox.as_vec()))
+			.transpose()?;
+
+		Ok(ConvertJob {
+			input: input_file.clone(),
+			override_input_compression: self.override_input_compression,
+			input_path_template: self
+				.input_path_template
+				.as_deref()
+				.map(PathTemplate::parse)
+				.transpose()?,
+			min_zoom: self.min_zoom,
+			max_zoom: self.max_zoom,
+			bbox,
+			bbox_border: self.bbox_border,
+			newer_than: self.newer_than,
+			coordinate_list: self.coordinate_list.clone(),
+			swap_xy: self.swap_xy,
+			flip_y: self.flip_y,
+			compress: self.compress,
+			threads: None,
+			parallel: self.parallel,
+			on_error: self.on_error,
+			tile_timeout: self.tile_timeout,
+			max_tile_size: self.max_tile_size,
+			error_report: self.error_report.clone(),
+			grids: self.grids.clone(),
+			strict_tilejson: self.strict_tilejson,
+			direct_write: self.direct_write,
+			fsync: self.fsync,
+			outputs: vec![ConvertJobOutput {
+				path: output_file.clone(),
+				compress: None,
+				path_template: self
+					.output_path_template
+					.as_deref()
+					.map(PathTemplate::parse)
+					.transpose()?,
+			}],
+			base_path: None,
+		})
 	}
-
-	Ok(Some(bbox_pyramid))
 }
 
 #[cfg(test)]
@@ -174,6 +280,125 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_bbox_across_antimeridian() -> Result<()> {
+		let temp_dir = TempDir::new()?;
+		let input = temp_dir.path().join("input.vpl");
+		std::fs::write(&input, "from_debug format=mvt")?;
+		let output = temp_dir.path().join("fiji");
+		std::fs::create_dir(&output)?;
+
+		run_command(vec![
+			"versatiles",
+			"convert",
+			"--min-zoom=4",
+			"--max-zoom=4",
+			"--bbox=176,-21,-178,-12",
+			input.to_str().unwrap(),
+			output.to_str().unwrap(),
+		])?;
+
+		let mut columns = std::fs::read_dir(output.join("4"))?
+			.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+			.collect::<Vec<_>>();
+		columns.sort();
+		assert_eq!(columns, ["0", "15"]);
+		Ok(())
+	}
+
+	#[test]
+	fn test_batch() -> Result<()> {
+		let temp_dir = TempDir::new()?;
+		let regions = temp_dir.path().join("regions");
+		std::fs::create_dir(&regions)?;
+		std::fs::copy("../testdata/berlin.mbtiles", regions.join("a.mbtiles"))?;
+		std::fs::copy("../testdata/berlin.mbtiles", regions.join("b.mbtiles"))?;
+
+		for parallel in ["1", "2"] {
+			let output = temp_dir.path().join(format!("out{parallel}/{{name}}.versatiles"));
+			std::fs::create_dir(temp_dir.path().join(format!("out{parallel}")))?;
+			run_command(vec![
+				"versatiles",
+				"convert",
+				"--max-zoom=2",
+				&format!("--parallel={parallel}"),
+				regions.join("*.mbtiles").to_str().unwrap(),
+				output.to_str().unwrap(),
+			])?;
+			assert!(temp_dir.path().join(format!("out{parallel}/a.versatiles")).exists());
+			assert!(temp_dir.path().join(format!("out{parallel}/b.versatiles")).exists());
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_job() -> Result<()> {
+		let temp_dir = TempDir::new()?;
+		let job_path = temp_dir.path().join("job.yml");
+		let berlin = std::env::current_dir()?.join("../testdata/berlin.mbtiles");
+		std::fs::write(
+			&job_path,
+			format!(
+				"input: {}\nmax_zoom: 4\nthreads: 2\noutputs:\n  - berlin.versatiles\n  - berlin.tar\n",
+				berlin.display()
+			),
+		)?;
+
+		run_command(vec!["versatiles", "convert", "--job", job_path.to_str().unwrap()])?;
+		assert!(temp_dir.path().join("berlin.versatiles").exists());
+		assert!(temp_dir.path().join("berlin.tar").exists());
+
+		assert!(
+			run_command(vec![
+				"versatiles",
+				"convert",
+				"--job",
+				job_path.to_str().unwrap(),
+				"a.mbtiles"
+			])
+			.is_err()
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_path_templates() -> Result<()> {
+		let temp_dir = TempDir::new()?;
+		let tiles_dir = temp_dir.path().join("tiles");
+		std::fs::create_dir(&tiles_dir)?;
+
+		run_command(vec![
+			"versatiles",
+			"convert",
+			"--max-zoom=3",
+			"--output-path-template={z}/{id/3}",
+			"../testdata/berlin.mbtiles",
+			tiles_dir.to_str().unwrap(),
+		])?;
+		assert!(tiles_dir.join("tiles.json.gz").exists());
+		assert!(tiles_dir.join("3").is_dir());
+
+		run_command(vec![
+			"versatiles",
+			"convert",
+			"--input-path-template={z}/{id/3}",
+			tiles_dir.to_str().unwrap(),
+			temp_dir.path().join("berlin.versatiles").to_str().unwrap(),
+		])?;
+
+		assert!(
+			run_command(vec![
+				"versatiles",
+				"convert",
+				"--output-path-template={z}/{w}",
+				"../testdata/berlin.mbtiles",
+				tiles_dir.to_str().unwrap(),
+			])
+			.is_err()
+		);
+		Ok(())
+	}
+
 	#[test]
 
 	fn test_remote1() -> Result<()> {
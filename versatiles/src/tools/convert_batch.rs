@@ -0,0 +1,278 @@
+//! Batch conversions: one [`ConvertJob`] with a glob input, expanded into one job per matching file.
+//!
+//! ```sh
+//! versatiles convert 'regions/*.mbtiles' 'out/{name}.versatiles'
+//! ```
+//!
+//! The file name of the input may contain the wildcards `*` and `?`. Every output path (and the
+//! `error_report`) must then contain the placeholder `{name}`, which is replaced by the file name of
+//! each match without its extension, e.g. `berlin` for `regions/berlin.mbtiles`.
+//!
+//! By default the files are converted one after another, and the progress bar shows the file
+//! (`[2/5] berlin`) and the progress of the whole batch. With `parallel: N` (`--parallel N`), up to
+//! `N` files are converted at the same time, and an additional progress bar counts the converted files.
+//! The batch stops at the first failed conversion.
+
+use super::convert_job::ConvertJob;
+use anyhow::{Result, ensure};
+use futures::{StreamExt, TryStreamExt, stream};
+use std::path::{Path, PathBuf};
+use versatiles_container::{DataLocation, ProcessingConfig, uri_scheme};
+use versatiles_core::progress::{ProgressStages, get_progress_bar};
+use versatiles_derive::context;
+use wildmatch::WildMatch;
+
+/// Placeholder in output paths that is replaced by the name of the input.
+const NAME_PLACEHOLDER: &str = "{name}";
+
+/// A list of conversion jobs and the number of jobs that run at the same time.
+#[derive(Debug)]
+pub struct ConvertBatch {
+	jobs: Vec<(String, ConvertJob)>,
+	parallel: usize,
+}
+
+impl ConvertBatch {
+	/// Expands the input of `job` into one job per matching file and fills in `{name}` in the outputs.
+	///
+	/// An input without wildcards results in a single job.
+	#[context("expanding the input '{}' of the convert job", job.input)]
+	pub fn new(job: ConvertJob) -> Result<Self> {
+		let parallel = job.parallel.unwrap_or(1);
+
+		let inputs = if is_glob(&job.input) {
+			let pattern = match &job.base_path {
+				Some(base_path) => base_path.join(&job.input),
+				None => PathBuf::from(&job.input),
+			};
+			let inputs = expand_glob(&pattern)?;
+			ensure!(!inputs.is_empty(), "no files match '{}'", job.input);
+			inputs
+				.into_iter()
+				.map(|path| path.to_string_lossy().to_string())
+				.collect()
+		} else {
+			vec![job.input.clone()]
+		};
+
+		if inputs.len() > 1 {
+			for output in &job.outputs {
+				ensure!(
+					output.path.to_string_lossy().contains(NAME_PLACEHOLDER),
+					"output '{}' must contain {NAME_PLACEHOLDER}, because several files match the input",
+					output.path.display()
+				);
+			}
+			if let Some(error_report) = &job.error_report {
+				ensure!(
+					error_report.to_string_lossy().contains(NAME_PLACEHOLDER),
+					"error report '{}' must contain {NAME_PLACEHOLDER}, because several files match the input",
+					error_report.display()
+				);
+			}
+		}
+
+		let mut jobs: Vec<(String, ConvertJob)> = Vec::new();
+		for input in inputs {
+			let name = DataLocation::from(input.as_str()).name()?;
+			ensure!(
+				jobs.iter().all(|(other, _)| *other != name),
+				"several inputs have the name '{name}'"
+			);
+
+			let mut item = job.clone();
+			for output in &mut item.outputs {
+				output.path = replace_name(&output.path, &name);
+			}
+			item.error_report = item.error_report.map(|path| replace_name(&path, &name));
+			item.input = input;
+			jobs.push((name, item));
+		}
+
+		Ok(ConvertBatch { jobs, parallel })
+	}
+
+	/// Runs all jobs, cancellable through `config.cancellation_token`.
+	pub async fn run(&self, config: ProcessingConfig) -> Result<()> {
+		if let [(_, job)] = self.jobs.as_slice() {
+			return job.run(config).await;
+		}
+
+		if self.parallel <= 1 {
+			// every file is a stage, so the progress bars show the file and the progress of the batch
+			let stages: Vec<(&str, u32)> = self.jobs.iter().map(|(name, _)| (name.as_str(), 1)).collect();
+			ProgressStages::define(&stages);
+			let result = self.run_sequential(config).await;
+			ProgressStages::clear();
+			result
+		} else {
+			self.run_parallel(config).await
+		}
+	}
+
+	async fn run_sequential(&self, config: ProcessingConfig) -> Result<()> {
+		for (name, job) in &self.jobs {
+			if config.cancellation_token.is_cancelled() {
+				break;
+			}
+			ProgressStages::begin(name);
+			log::info!("convert {name:?}");
+			job.convert(config.clone()).await?;
+		}
+		Ok(())
+	}
+
+	async fn run_parallel(&self, config: ProcessingConfig) -> Result<()> {
+		let progress = get_progress_bar("converting files", self.jobs.len() as u64);
+		stream::iter(&self.jobs)
+			.map(|(name, job)| {
+				let config = config.clone();
+				let progress = &progress;
+				async move {
+					log::info!("convert {name:?}");
+					job.convert(config).await?;
+					progress.inc(1);
+					Ok::<_, anyhow::Error>(())
+				}
+			})
+			.buffer_unordered(self.parallel)
+			.try_collect::<()>()
+			.await?;
+		progress.finish();
+		Ok(())
+	}
+}
+
+/// Whether `input` is a local path with wildcards. URLs are never expanded, since `?` starts their query.
+fn is_glob(input: &str) -> bool {
+	uri_scheme(input).is_none() && input.contains(['*', '?'])
+}
+
+/// Lists the files and directories whose name matches the file name of `pattern`, sorted by path.
+fn expand_glob(pattern: &Path) -> Result<Vec<PathBuf>> {
+	let dir = pattern
+		.parent()
+		.filter(|dir| !dir.as_os_str().is_empty())
+		.unwrap_or(Path::new("."));
+	ensure!(
+		!is_glob(&dir.to_string_lossy()),
+		"wildcards are only supported in the file name of '{}'",
+		pattern.display()
+	);
+	let file_pattern = WildMatch::new(&pattern.file_name().unwrap_or_default().to_string_lossy());
+
+	let mut paths = std::fs::read_dir(dir)?
+		.map(|entry| Ok(entry?.path()))
+		.collect::<Result<Vec<PathBuf>>>()?;
+	paths.retain(|path| {
+		path
+			.file_name()
+			.is_some_and(|name| file_pattern.matches(&name.to_string_lossy()))
+	});
+	paths.sort();
+	Ok(paths)
+}
+
+fn replace_name(path: &Path, name: &str) -> PathBuf {
+	PathBuf::from(path.to_string_lossy().replace(NAME_PLACEHOLDER, name))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tools::convert_job::ConvertJobOutput;
+	use assert_fs::TempDir;
+
+	fn job(input: &str, outputs: &[&str]) -> ConvertJob {
+		ConvertJob {
+			input: input.to_string(),
+			outputs: outputs
+				.iter()
+				.map(|path| ConvertJobOutput {
+					path: PathBuf::from(path),
+					..Default::default()
+				})
+				.collect(),
+			..Default::default()
+		}
+	}
+
+	fn inputs_and_outputs(batch: &ConvertBatch) -> Vec<(String, String)> {
+		batch
+			.jobs
+			.iter()
+			.map(|(_, job)| {
+				let input = Path::new(&job.input).file_name().unwrap().to_string_lossy().to_string();
+				(input, job.outputs[0].path.to_string_lossy().to_string())
+			})
+			.collect()
+	}
+
+	#[test]
+	fn expand_glob_input() -> Result<()> {
+		let dir = TempDir::new()?;
+		for file in ["b.mbtiles", "a.mbtiles", "ab.mbtiles", "c.pmtiles"] {
+			std::fs::write(dir.path().join(file), b"")?;
+		}
+
+		let mut glob = job("*.mbtiles", &["out/{name}.versatiles"]);
+		glob.base_path = Some(dir.path().to_path_buf());
+		glob.parallel = Some(2);
+		let batch = ConvertBatch::new(glob)?;
+		assert_eq!(batch.parallel, 2);
+		assert_eq!(
+			inputs_and_outputs(&batch),
+			vec![
+				("a.mbtiles".to_string(), "out/a.versatiles".to_string()),
+				("ab.mbtiles".to_string(), "out/ab.versatiles".to_string()),
+				("b.mbtiles".to_string(), "out/b.versatiles".to_string()),
+			]
+		);
+
+		let pattern = dir.path().join("?.mbtiles");
+		let batch = ConvertBatch::new(job(pattern.to_str().unwrap(), &["{name}.tar"]))?;
+		assert_eq!(
+			inputs_and_outputs(&batch),
+			vec![
+				("a.mbtiles".to_string(), "a.tar".to_string()),
+				("b.mbtiles".to_string(), "b.tar".to_string()),
+			]
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn single_inputs() -> Result<()> {
+		let batch = ConvertBatch::new(job("regions/berlin.mbtiles", &["{name}.versatiles"]))?;
+		assert_eq!(
+			inputs_and_outputs(&batch),
+			vec![("berlin.mbtiles".to_string(), "berlin.versatiles".to_string())]
+		);
+
+		// the `?` of a URL is not a wildcard
+		let batch = ConvertBatch::new(job("https://example.org/osm.versatiles?key=1", &["out.versatiles"]))?;
+		assert_eq!(batch.jobs.len(), 1);
+		assert_eq!(batch.jobs[0].1.input, "https://example.org/osm.versatiles?key=1");
+		Ok(())
+	}
+
+	#[test]
+	fn invalid_batches() -> Result<()> {
+		let dir = TempDir::new()?;
+		for file in ["a.mbtiles", "b.mbtiles", "a.pmtiles"] {
+			std::fs::write(dir.path().join(file), b"")?;
+		}
+		let check = |input: &str, output: &str, message: &str| {
+			let mut batch = job(input, &[output]);
+			batch.base_path = Some(dir.path().to_path_buf());
+			let error = format!("{:?}", ConvertBatch::new(batch).unwrap_err());
+			assert!(error.contains(message), "{error}");
+		};
+
+		check("*.mbtiles", "out.versatiles", "must contain {name}");
+		check("*.versatiles", "{name}.versatiles", "no files match");
+		check("a.*", "{name}.versatiles", "several inputs have the name 'a'");
+		check("*/a.mbtiles", "{name}.versatiles", "only supported in the file name");
+		Ok(())
+	}
+}
@@ -0,0 +1,643 @@
+//! Conversion jobs, described in a YAML file and executed with `versatiles convert --job <FILE>`.
+//!
+//! A job bundles everything that is otherwise passed as arguments to `versatiles convert`,
+//! and allows to write several outputs in a single pass over the input:
+//!
+//! ```yaml
+//! input: berlin.mbtiles
+//! min_zoom: 0
+//! max_zoom: 14
+//! bbox: [13.08, 52.33, 13.77, 52.68]
+//! newer_than: 1700000000
+//! coordinate_list: changed_tiles.csv
+//! compress: brotli
+//! threads: 4
+//! on_error: skip
+//! tile_timeout: 10000
+//! max_tile_size: 20
+//! error_report: berlin_errors.ndjson
+//! grids: berlin_grids/
+//! strict_tilejson: true
+//! outputs:
+//!   - berlin.versatiles
+//!   - path: berlin.pmtiles
+//!     compress: gzip
+//!   - path: tiles/
+//!     path_template: "{z}/{y}/{x}"
+//! ```
+//!
+//! With `on_error: skip` or `on_error: fallback`, tiles that fail to be read or recompressed are left out or replaced
+//! by empty tiles, and each failure is written to `error_report` as a line of JSON with `z`, `x`, `y`, `stage` and `error`.
+//!
+//! `tile_timeout` limits the time in milliseconds spent reading a single tile, and every request to an HTTP input.
+//! A tile that is not read in time is handled by `on_error` like any other read error, so a hung server can not
+//! stall the whole conversion.
+//!
+//! `max_tile_size` limits the size of a single encoded tile in megabytes (default: 50, 0 disables the limit).
+//! Larger tiles are handled by `on_error`, so one broken tile can not exhaust the memory of the conversion.
+//!
+//! `newer_than` is a Unix timestamp in seconds. Only tiles modified after it are converted, e.g. to sync the changes of a
+//! directory or a `*.versatiles` file with tile times. Tiles without a known modification time are always converted.
+//!
+//! `coordinate_list` is a CSV file listing the tiles to convert, either as `z,x,y` or as PMTiles tile ids and ranges of
+//! them, see [`TileCoordList`]. It is combined with the other filters, e.g. to rebuild only the tiles affected by an
+//! OSM diff.
+//!
+//! Containers have no place for UTFGrid interaction data. If the input is an MBTiles file with `grids`, they are
+//! written to the `grids` directory as `{z}/{x}/{y}.grid.json`, otherwise a warning reports that they were dropped.
+//!
+//! With `strict_tilejson: true`, invalid TileJSON metadata of the input or the outputs fails the job
+//! instead of only being logged.
+//!
+//! The input may match several files, e.g. `input: regions/*.mbtiles` with outputs like `out/{name}.versatiles`,
+//! see [`ConvertBatch`](super::convert_batch::ConvertBatch). `parallel` sets the number of files converted at the same time.
+//!
+//! Relative paths are resolved against the directory of the job file.
+//! The container format of every output is derived from its extension, directories are written as directory containers.
+//! The output `-` writes a tar archive to stdout, e.g. to pipe it into `ssh` or `aws s3 cp -`,
+//! and `versatiles:-` writes a `.versatiles` container to stdout.
+//! Files are written to `<path>.tmp` and renamed when complete. Set `direct_write: true` to write directly to the
+//! target path, e.g. on filesystems where renaming does not replace files atomically, and `fsync: true` to flush
+//! every output to disk before renaming it.
+
+use anyhow::{Result, bail, ensure};
+use futures::future::try_join_all;
+use serde::{Deserialize, Deserializer, de::Error};
+use std::{
+	fs::{self, File},
+	io::{BufReader, Read},
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::Duration,
+};
+use versatiles::get_registry;
+use versatiles_container::{
+	DEFAULT_MAX_TILE_SIZE, DataLocation, DataSource, MBTilesGrids, PathTemplate, ProcessingConfig, TeeReader,
+	TileErrorLog, TileErrorPolicy, TilesConvertReader, TilesConverterParameters, TilesReaderTrait,
+	convert_tiles_container, stdout_extension,
+};
+use versatiles_core::{GeoBBox, TileBBoxPyramid, TileCompression, TileCoordList, progress::ProgressStages};
+use versatiles_derive::context;
+
+/// Stages of a conversion and their expected share of the total work.
+const STAGES: [(&str, u32); 4] = [("scan", 1), ("convert", 16), ("write directories", 2), ("finalize", 1)];
+
+/// A conversion job: one input, filters and one or more outputs.
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ConvertJob {
+	/// Container, VPL file or URL to read from.
+	pub input: String,
+
+	/// Override the compression of the input, e.g. "gzip".
+	#[serde(default, deserialize_with = "deserialize_compression")]
+	pub override_input_compression: Option<TileCompression>,
+
+	/// Path template of the tiles, if the input is a directory.
+	#[serde(default, deserialize_with = "deserialize_template")]
+	pub input_path_template: Option<PathTemplate>,
+
+	/// Minimum zoom level.
+	#[serde(default)]
+	pub min_zoom: Option<u8>,
+
+	/// Maximum zoom level.
+	#[serde(default)]
+	pub max_zoom: Option<u8>,
+
+	/// Use only tiles inside this bounding box: `[lon_min, lat_min, lon_max, lat_max]`.
+	/// If `lon_min` is greater than `lon_max`, the bounding box crosses the antimeridian.
+	#[serde(default)]
+	pub bbox: Option<Vec<f64>>,
+
+	/// Include additional tiles surrounding the bounding box as a border.
+	#[serde(default)]
+	pub bbox_border: Option<u32>,
+
+	/// Use only tiles modified after this Unix timestamp in seconds.
+	#[serde(default)]
+	pub newer_than: Option<u64>,
+
+	/// Use only the tiles listed in this CSV file, see [`TileCoordList`].
+	#[serde(default)]
+	pub coordinate_list: Option<PathBuf>,
+
+	/// Swap rows and columns, e.g. z/x/y -> z/y/x.
+	#[serde(default)]
+	pub swap_xy: bool,
+
+	/// Flip the input vertically.
+	#[serde(default)]
+	pub flip_y: bool,
+
+	/// New compression of all outputs, e.g. "brotli". Can be overridden per output.
+	#[serde(default, deserialize_with = "deserialize_compression")]
+	pub compress: Option<TileCompression>,
+
+	/// Number of worker threads. (default: number of CPU cores)
+	#[serde(default)]
+	pub threads: Option<usize>,
+
+	/// Number of files converted at the same time, if the input matches several files. (default: 1)
+	#[serde(default)]
+	pub parallel: Option<usize>,
+
+	/// What to do with tiles that fail to be read or recompressed: "fail", "skip" or "fallback". (default: fail)
+	#[serde(default, deserialize_with = "deserialize_policy")]
+	pub on_error: TileErrorPolicy,
+
+	/// Maximum time in milliseconds to read a single tile. (default: no limit)
+	#[serde(default)]
+	pub tile_timeout: Option<u64>,
+
+	/// Maximum size of a single encoded tile in megabytes, 0 disables the limit. (default: 50)
+	#[serde(default)]
+	pub max_tile_size: Option<u64>,
+
+	/// Write failed tiles as newline-delimited JSON to this file.
+	#[serde(default)]
+	pub error_report: Option<PathBuf>,
+
+	/// Write the UTFGrids of an MBTiles input to this directory as `{z}/{x}/{y}.grid.json`.
+	#[serde(default)]
+	pub grids: Option<PathBuf>,
+
+	/// Fail on invalid TileJSON metadata instead of only logging it.
+	#[serde(default)]
+	pub strict_tilejson: bool,
+
+	/// Write directly to the output paths instead of writing to a temporary file and renaming it.
+	#[serde(default)]
+	pub direct_write: bool,
+
+	/// Flush the outputs to disk before renaming them.
+	#[serde(default)]
+	pub fsync: bool,
+
+	/// The containers to write. All outputs are written at the same time, reading the input only once.
+	pub outputs: Vec<ConvertJobOutput>,
+
+	/// Directory that relative input paths are resolved against.
+	#[serde(skip)]
+	pub base_path: Option<PathBuf>,
+}
+
+/// A single output of a [`ConvertJob`]: either a path, or a mapping with `path` and additional settings.
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(from = "OutputEntry")]
+pub struct ConvertJobOutput {
+	/// Path of the output container or directory.
+	pub path: PathBuf,
+	/// Compression of this output, overrides the `compress` of the job.
+	pub compress: Option<TileCompression>,
+	/// Path template of the tiles, if the output is a directory.
+	pub path_template: Option<PathTemplate>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OutputEntry {
+	Path(PathBuf),
+	Full {
+		path: PathBuf,
+		#[serde(default, deserialize_with = "deserialize_compression")]
+		compress: Option<TileCompression>,
+		#[serde(default, deserialize_with = "deserialize_template")]
+		path_template: Option<PathTemplate>,
+	},
+}
+
+impl From<OutputEntry> for ConvertJobOutput {
+	fn from(entry: OutputEntry) -> Self {
+		match entry {
+			OutputEntry::Path(path) => ConvertJobOutput {
+				path,
+				..Default::default()
+			},
+			OutputEntry::Full {
+				path,
+				compress,
+				path_template,
+			} => ConvertJobOutput {
+				path,
+				compress,
+				path_template,
+			},
+		}
+	}
+}
+
+impl ConvertJob {
+	/// Parse a YAML job from any `Read` implementor.
+	#[context("parsing convert job (YAML)")]
+	pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+		let job: ConvertJob = serde_yaml_ng::from_reader(reader)?;
+		job.check()?;
+		Ok(job)
+	}
+
+	/// Read a YAML job file and resolve relative paths against its directory.
+	#[context("reading convert job file '{}'", path.display())]
+	pub fn from_path(path: &Path) -> Result<Self> {
+		let mut job = ConvertJob::from_reader(BufReader::new(File::open(path)?))?;
+		let base_path = std::env::current_dir()?.join(path.parent().unwrap_or(Path::new("")));
+		for output in &mut job.outputs {
+			if stdout_extension(&output.path).is_none() {
+				output.path = base_path.join(&output.path);
+			}
+		}
+		if let Some(error_report) = &mut job.error_report {
+			*error_report = base_path.join(&error_report);
+		}
+		if let Some(grids) = &mut job.grids {
+			*grids = base_path.join(&grids);
+		}
+		if let Some(coordinate_list) = &mut job.coordinate_list {
+			*coordinate_list = base_path.join(&coordinate_list);
+		}
+		job.base_path = Some(base_path);
+		Ok(job)
+	}
+
+	/// Validates everything that can be checked without touching the input or outputs.
+	fn check(&self) -> Result<()> {
+		ensure!(!self.outputs.is_empty(), "a convert job needs at least one output");
+		ensure!(
+			self
+				.outputs
+				.iter()
+				.filter(|output| stdout_extension(&output.path).is_some())
+				.count() <= 1,
+			"only one output can be written to stdout"
+		);
+		if let Some(threads) = self.threads {
+			ensure!(threads > 0, "threads must be greater than zero");
+		}
+		if let Some(parallel) = self.parallel {
+			ensure!(parallel > 0, "parallel must be greater than zero");
+		}
+		self.bbox_pyramid()?;
+		Ok(())
+	}
+
+	/// The maximum size of a single encoded tile in bytes, or `None` if tiles are not limited.
+	#[must_use]
+	pub fn max_tile_size(&self) -> Option<u64> {
+		match self.max_tile_size {
+			None => Some(DEFAULT_MAX_TILE_SIZE),
+			Some(0) => None,
+			Some(megabytes) => Some(megabytes.saturating_mul(1024 * 1024)),
+		}
+	}
+
+	/// The zoom levels and bounding box to convert, or `None` if everything should be converted.
+	///
+	/// If the bounding box crosses the antimeridian, the pyramid also covers the tiles between its parts,
+	/// see [`bbox_parts`](Self::bbox_parts).
+	#[context("Failed to get bounding box pyramid")]
+	pub fn bbox_pyramid(&self) -> Result<Option<TileBBoxPyramid>> {
+		Ok(self.bbox_parts()?.map(|parts| {
+			let mut bbox_pyramid = TileBBoxPyramid::new_empty();
+			for part in &parts {
+				bbox_pyramid.include_bbox_pyramid(part);
+			}
+			bbox_pyramid
+		}))
+	}
+
+	/// The zoom levels and bounding box to convert, split into one pyramid per side of the antimeridian,
+	/// or `None` if everything should be converted.
+	#[context("Failed to get bounding box parts")]
+	pub fn bbox_parts(&self) -> Result<Option<Vec<TileBBoxPyramid>>> {
+		if self.min_zoom.is_none() && self.max_zoom.is_none() && self.bbox.is_none() {
+			return Ok(None);
+		}
+
+		let mut bbox_pyramid = TileBBoxPyramid::new_full(32);
+
+		if let Some(level_min) = self.min_zoom {
+			bbox_pyramid.set_level_min(level_min)
+		}
+
+		if let Some(level_max) = self.max_zoom {
+			bbox_pyramid.set_level_max(level_max)
+		}
+
+		let Some(bbox) = &self.bbox else {
+			return Ok(Some(vec![bbox_pyramid]));
+		};
+		if bbox.len() != 4 {
+			bail!("bbox must contain exactly 4 numbers, but instead i'v got: {bbox:?}");
+		}
+
+		let mut parts = bbox_pyramid.split_geo_bbox(&GeoBBox::new_wrapping(bbox[0], bbox[1], bbox[2], bbox[3])?)?;
+		if let Some(b) = self.bbox_border {
+			for part in &mut parts {
+				part.add_border(b, b, b, b);
+			}
+		}
+
+		Ok(Some(parts))
+	}
+
+	/// The tiles listed in `coordinate_list`, or `None` if there is no list.
+	pub fn tile_list(&self) -> Result<Option<TileCoordList>> {
+		let Some(path) = &self.coordinate_list else {
+			return Ok(None);
+		};
+		let tile_list = TileCoordList::from_csv_path(path)?;
+		log::info!("converting only the {} tiles listed in {path:?}", tile_list.len());
+		Ok(Some(tile_list))
+	}
+
+	/// Converts the input into every output, cancellable through `config.cancellation_token`.
+	///
+	/// Progress is reported in the stages `scan`, `convert`, `write directories` and `finalize`.
+	pub async fn run(&self, config: ProcessingConfig) -> Result<()> {
+		ProgressStages::define(&STAGES);
+		ProgressStages::begin("scan");
+		let result = self.convert(config).await;
+		ProgressStages::clear();
+		result
+	}
+
+	/// Converts the input into every output, without defining progress stages.
+	pub(super) async fn convert(&self, config: ProcessingConfig) -> Result<()> {
+		let mut registry = get_registry(config);
+		registry.set_strict_tilejson(self.strict_tilejson);
+		registry.set_atomic_write(!self.direct_write);
+		registry.set_fsync(self.fsync);
+		let tile_timeout = self.tile_timeout.map(Duration::from_millis);
+		registry.set_request_timeout(tile_timeout);
+		if let Some(template) = &self.input_path_template {
+			registry.set_directory_read_template(template.clone());
+		}
+
+		let tile_list = self.tile_list()?.map(Arc::new);
+
+		let mut source = DataSource::parse(&self.input, &registry)?;
+		if let Some(base_path) = &self.base_path {
+			source.resolve(&DataLocation::from(base_path))?;
+		}
+		if source.extension() == "mbtiles"
+			&& let Ok(path) = source.location().as_path()
+		{
+			self.extract_grids(path, tile_list.as_deref())?;
+		}
+		let mut reader = registry.get_reader(source).await?;
+
+		if let Some(compression) = self.override_input_compression {
+			reader.override_compression(compression);
+		}
+
+		let tile_errors = TileErrorLog::new(self.on_error);
+
+		let bbox_parts = self.bbox_parts()?;
+		let parameters = TilesConverterParameters {
+			bbox_pyramid: self.bbox_pyramid()?,
+			bbox_parts: bbox_parts.filter(|parts| parts.len() > 1),
+			flip_y: self.flip_y,
+			swap_xy: self.swap_xy,
+			tile_compression: None,
+			tile_errors: tile_errors.clone(),
+			tile_timeout,
+			newer_than: self.newer_than,
+			max_tile_size: self.max_tile_size(),
+			tile_list,
+		};
+		let reader = TilesConvertReader::new_from_reader(reader, parameters)?.boxed();
+
+		// all outputs share a single read pass
+		let readers = if self.outputs.len() == 1 {
+			vec![reader]
+		} else {
+			TeeReader::split(reader, self.outputs.len())?
+				.into_iter()
+				.map(TilesReaderTrait::boxed)
+				.collect()
+		};
+
+		try_join_all(self.outputs.iter().zip(readers).map(|(output, reader)| {
+			let mut registry = registry.clone();
+			if let Some(template) = &output.path_template {
+				registry.set_directory_write_template(template.clone());
+			}
+			let parameters = TilesConverterParameters {
+				tile_compression: output.compress.or(self.compress),
+				tile_errors: tile_errors.clone(),
+				max_tile_size: self.max_tile_size(),
+				..Default::default()
+			};
+			log::info!("convert from {:?} to {:?}", self.input, output.path);
+			async move { convert_tiles_container(reader, parameters, &output.path, registry).await }
+		}))
+		.await?;
+
+		if let Some(path) = &self.error_report {
+			tile_errors.write_report(path)?;
+		}
+
+		Ok(())
+	}
+
+	/// Writes the UTFGrids of an MBTiles input to `grids`, or warns that they are not converted.
+	#[context("extracting UTFGrids of '{}'", path.display())]
+	fn extract_grids(&self, path: &Path, tile_list: Option<&TileCoordList>) -> Result<()> {
+		let Some(grids) = MBTilesGrids::open_path(path)? else {
+			return Ok(());
+		};
+		let Some(directory) = &self.grids else {
+			log::warn!(
+				"the {} UTFGrids of {path:?} are not converted, set \"grids\" to extract them",
+				grids.count()?
+			);
+			return Ok(());
+		};
+
+		let bbox_parts = self.bbox_parts()?;
+		let mut count = 0;
+		for (coord, grid) in grids.read_all()? {
+			if bbox_parts
+				.as_ref()
+				.is_some_and(|parts| !parts.iter().any(|part| part.contains_coord(&coord)))
+				|| tile_list.is_some_and(|list| !list.contains(&coord))
+			{
+				continue;
+			}
+			let filename = directory.join(format!("{}/{}/{}.grid.json", coord.level, coord.x, coord.y));
+			fs::create_dir_all(filename.parent().unwrap())?;
+			fs::write(&filename, grid.stringify()).with_context(|| format!("writing {filename:?}"))?;
+			count += 1;
+		}
+		log::info!("extracted {count} UTFGrids to {directory:?}");
+		Ok(())
+	}
+}
+
+fn deserialize_compression<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<TileCompression>, D::Error> {
+	Option::<String>::deserialize(deserializer)?
+		.map(|value| TileCompression::try_from(value.as_str()).map_err(D::Error::custom))
+		.transpose()
+}
+
+fn deserialize_policy<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TileErrorPolicy, D::Error> {
+	String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+}
+
+fn deserialize_template<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<PathTemplate>, D::Error> {
+	Option::<String>::deserialize(deserializer)?
+		.map(|value| PathTemplate::parse(&value).map_err(D::Error::custom))
+		.transpose()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use assert_fs::TempDir;
+	use pretty_assertions::assert_eq;
+
+	#[test]
+	fn parse_job() -> Result<()> {
+		let job = ConvertJob::from_reader(
+			r#"
+input: berlin.mbtiles
+max_zoom: 3
+bbox: [13.0, 52.0, 14.0, 53.0]
+newer_than: 1700000000
+coordinate_list: tiles.csv
+compress: brotli
+on_error: skip
+tile_timeout: 2500
+max_tile_size: 20
+error_report: errors.ndjson
+grids: grids
+strict_tilejson: true
+outputs:
+  - berlin.versatiles
+  - path: tiles
+    compress: gzip
+    path_template: "{z}/{y}/{x}"
+"#
+			.as_bytes(),
+		)?;
+		assert_eq!(
+			job,
+			ConvertJob {
+				input: "berlin.mbtiles".to_string(),
+				max_zoom: Some(3),
+				bbox: Some(vec![13.0, 52.0, 14.0, 53.0]),
+				newer_than: Some(1_700_000_000),
+				coordinate_list: Some(PathBuf::from("tiles.csv")),
+				compress: Some(TileCompression::Brotli),
+				on_error: TileErrorPolicy::Skip,
+				tile_timeout: Some(2500),
+				max_tile_size: Some(20),
+				error_report: Some(PathBuf::from("errors.ndjson")),
+				grids: Some(PathBuf::from("grids")),
+				strict_tilejson: true,
+				outputs: vec![
+					ConvertJobOutput {
+						path: PathBuf::from("berlin.versatiles"),
+						..Default::default()
+					},
+					ConvertJobOutput {
+						path: PathBuf::from("tiles"),
+						compress: Some(TileCompression::Gzip),
+						path_template: Some(PathTemplate::parse("{z}/{y}/{x}")?),
+					},
+				],
+				..Default::default()
+			}
+		);
+		assert_eq!(job.bbox_pyramid()?.unwrap().get_level_max(), Some(3));
+		assert_eq!(job.max_tile_size(), Some(20 * 1024 * 1024));
+		Ok(())
+	}
+
+	#[test]
+	fn parse_invalid_jobs() {
+		for yaml in [
+			"input: a.mbtiles",
+			"input: a.mbtiles\noutputs: []",
+			"input: a.mbtiles\noutputs: [b.versatiles]\ncompress: zip",
+			"input: a.mbtiles\noutputs: [b.versatiles]\nbbox: [1, 2, 3]",
+			"input: a.mbtiles\noutputs: [b.versatiles]\nthreads: 0",
+			"input: a.mbtiles\noutputs: [b.versatiles]\non_error: ignore",
+			"input: a.mbtiles\noutputs: [b.versatiles]\nmaxzoom: 3",
+			"input: a.mbtiles\noutputs: [{path: tiles, path_template: \"{z}\"}]",
+			"input: a.mbtiles\noutputs: [\"-\", \"tar:-\"]",
+		] {
+			assert!(ConvertJob::from_reader(yaml.as_bytes()).is_err(), "{yaml}");
+		}
+	}
+
+	#[tokio::test]
+	async fn run_job() -> Result<()> {
+		let temp_dir = TempDir::new()?;
+		let berlin = std::env::current_dir()?.join("../testdata/berlin.mbtiles");
+		let job_path = temp_dir.path().join("job.yml");
+		std::fs::write(
+			&job_path,
+			format!(
+				"input: {}\nmax_zoom: 5\noutputs:\n  - berlin.versatiles\n  - path: berlin.pmtiles\n    compress: none\n",
+				berlin.display()
+			),
+		)?;
+
+		let job = ConvertJob::from_path(&job_path)?;
+		job.run(ProcessingConfig::default()).await?;
+
+		let registry = get_registry(ProcessingConfig::default());
+		let reader = registry
+			.open_reader(temp_dir.path().join("berlin.versatiles").to_str().unwrap())
+			.await?;
+		assert_eq!(reader.parameters().bbox_pyramid.get_level_max(), Some(5));
+		assert_eq!(reader.parameters().tile_compression, TileCompression::Gzip);
+
+		let reader = registry
+			.open_reader(temp_dir.path().join("berlin.pmtiles").to_str().unwrap())
+			.await?;
+		assert_eq!(reader.parameters().tile_compression, TileCompression::Uncompressed);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn run_job_with_coordinate_list() -> Result<()> {
+		let temp_dir = TempDir::new()?;
+		let berlin = std::env::current_dir()?.join("../testdata/berlin.mbtiles");
+		std::fs::write(
+			temp_dir.path().join("tiles.csv"),
+			"z,x,y
+0,0,0
+5,17,10
+5,0,0
+6,35,21
+",
+		)?;
+		let job_path = temp_dir.path().join("job.yml");
+		std::fs::write(
+			&job_path,
+			format!(
+				"input: {}
+max_zoom: 5
+coordinate_list: tiles.csv
+outputs: [berlin.versatiles]
+",
+				berlin.display()
+			),
+		)?;
+
+		ConvertJob::from_path(&job_path)?
+			.run(ProcessingConfig::default())
+			.await?;
+
+		let reader = get_registry(ProcessingConfig::default())
+			.open_reader(temp_dir.path().join("berlin.versatiles").to_str().unwrap())
+			.await?;
+		let pyramid = &reader.parameters().bbox_pyramid;
+		assert_eq!(pyramid.get_level_min(), Some(0));
+		assert_eq!(pyramid.get_level_max(), Some(5));
+		assert_eq!(pyramid.count_tiles(), 2);
+		Ok(())
+	}
+}
@@ -28,9 +28,7 @@ pub async fn run(args: &ExportOutline) -> Result<()> {
 	let input = &args.input;
 	let output = &args.output;
 
-	let reader = get_registry(ProcessingConfig::default())
-		.get_reader_from_str(input)
-		.await?;
+	let reader = get_registry(ProcessingConfig::default()).open_reader(input).await?;
 
 	let compression = reader.parameters().tile_compression;
 	let bbox_pyramid = reader.parameters().bbox_pyramid.clone();
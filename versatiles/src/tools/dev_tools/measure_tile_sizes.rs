@@ -51,9 +51,7 @@ pub async fn run(args: &MeasureTileSizes) -> Result<()> {
 		output_file.extension().unwrap_or_default()
 	);
 
-	let reader = get_registry(ProcessingConfig::default())
-		.get_reader_from_str(input)
-		.await?;
+	let reader = get_registry(ProcessingConfig::default()).open_reader(input).await?;
 	let bbox = TileBBox::new_full(level)?;
 	let stream = reader.get_tile_stream(bbox).await?;
 
@@ -0,0 +1,224 @@
+use anyhow::{Context, Result, ensure};
+use std::path::{Path, PathBuf};
+use versatiles::get_registry;
+use versatiles_container::ProcessingConfig;
+use versatiles_core::{GeoBBox, TileBBoxPyramid, TileCoord, progress::get_progress_bar};
+use versatiles_image::{DynamicImage, geotiff, png};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum ExportFormat {
+	/// one GeoTIFF per tile
+	Geotiff,
+	/// one PNG per tile, with a world file (.pgw) and a projection file (.prj)
+	Png,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(arg_required_else_help = true, disable_version_flag = true)]
+pub struct Subcommand {
+	/// raster tile container to export from
+	#[arg(required = true)]
+	input_file: String,
+
+	/// directory for the exported images, which are written as "<z>/<x>/<y>.tif" or "<z>/<x>/<y>.png"
+	#[arg(required = true)]
+	output_directory: PathBuf,
+
+	/// minimum zoom level
+	#[arg(long, value_name = "int")]
+	min_zoom: Option<u8>,
+
+	/// maximum zoom level
+	#[arg(long, value_name = "int")]
+	max_zoom: Option<u8>,
+
+	/// export only tiles inside a bounding box
+	#[arg(
+		long,
+		short,
+		value_name = "lon_min,lat_min,lon_max,lat_max",
+		allow_hyphen_values = true
+	)]
+	bbox: Option<String>,
+
+	/// format of the exported images. All images are georeferenced in Web Mercator (EPSG:3857)
+	#[arg(long, short, value_enum, default_value = "geotiff")]
+	format: ExportFormat,
+
+	/// refuse to export more tiles than this, to protect against accidentally exporting whole zoom levels
+	#[arg(long, value_name = "int", default_value_t = 10_000)]
+	max_tiles: u64,
+}
+
+#[tokio::main]
+pub async fn run(arguments: &Subcommand) -> Result<()> {
+	log::info!("export tiles from {:?}", arguments.input_file);
+
+	let reader = get_registry(ProcessingConfig::default())
+		.open_reader(&arguments.input_file)
+		.await?;
+	let parameters = reader.parameters();
+	ensure!(
+		parameters.tile_format.is_raster(),
+		"only raster tiles can be exported as images, but the tiles are {}",
+		parameters.tile_format.as_str()
+	);
+
+	// a bounding box that crosses the antimeridian is exported in two parts
+	let mut pyramids = match &arguments.bbox {
+		Some(bbox) => parameters.bbox_pyramid.split_geo_bbox(&bbox.parse::<GeoBBox>()?)?,
+		None => vec![parameters.bbox_pyramid.clone()],
+	};
+	for pyramid in &mut pyramids {
+		if let Some(min_zoom) = arguments.min_zoom {
+			pyramid.set_level_min(min_zoom);
+		}
+		if let Some(max_zoom) = arguments.max_zoom {
+			pyramid.set_level_max(max_zoom);
+		}
+	}
+
+	let count = pyramids.iter().map(TileBBoxPyramid::count_tiles).sum::<u64>();
+	ensure!(
+		count <= arguments.max_tiles,
+		"refusing to export up to {count} tiles, which is more than --max-tiles {}. Use --bbox, --min-zoom and --max-zoom to select fewer tiles",
+		arguments.max_tiles
+	);
+
+	let progress = get_progress_bar("exporting tiles", count);
+	let mut exported = 0;
+	for bbox in pyramids.iter().flat_map(TileBBoxPyramid::iter_levels) {
+		let mut stream = reader
+			.get_tile_stream(*bbox)
+			.await?
+			.map_item_parallel(|tile| tile.into_image());
+		while let Some((coord, image)) = stream.next().await {
+			write_image(&arguments.output_directory, &coord, &image, arguments.format)?;
+			exported += 1;
+			progress.inc(1);
+		}
+	}
+	progress.finish();
+
+	log::info!("exported {exported} tiles to {:?}", arguments.output_directory);
+	Ok(())
+}
+
+/// Writes the georeferenced image of the tile at `coord` to "<directory>/<z>/<x>/<y>.<extension>".
+fn write_image(directory: &Path, coord: &TileCoord, image: &DynamicImage, format: ExportFormat) -> Result<()> {
+	let directory = directory.join(coord.level.to_string()).join(coord.x.to_string());
+	std::fs::create_dir_all(&directory).with_context(|| format!("Failed to create directory {directory:?}"))?;
+
+	let path = directory.join(coord.y.to_string());
+	let bounds = coord.to_mercator_bbox();
+	let files = match format {
+		ExportFormat::Geotiff => vec![("tif", geotiff::encode(image, bounds)?.into_vec())],
+		ExportFormat::Png => vec![
+			("png", png::encode(image, None)?.into_vec()),
+			(
+				"pgw",
+				geotiff::world_file(image.width(), image.height(), bounds).into_bytes(),
+			),
+			("prj", geotiff::WEB_MERCATOR_PRJ.as_bytes().to_vec()),
+		],
+	};
+	for (extension, content) in files {
+		let filename = path.with_extension(extension);
+		std::fs::write(&filename, content).with_context(|| format!("Failed to write {filename:?}"))?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tests::run_command;
+	use anyhow::Result;
+	use assert_fs::TempDir;
+
+	fn export(format: &str, extra: &[&str]) -> Result<TempDir> {
+		let dir = TempDir::new()?;
+		let input = dir.path().join("input.vpl");
+		std::fs::write(&input, "from_debug format=png")?;
+		let output = dir.path().join("output");
+
+		let mut args = vec![
+			"versatiles",
+			"export",
+			"-q",
+			input.to_str().unwrap(),
+			output.to_str().unwrap(),
+			"--format",
+			format,
+		];
+		args.extend_from_slice(extra);
+		run_command(args)?;
+		Ok(dir)
+	}
+
+	#[test]
+	fn test_export_geotiff() -> Result<()> {
+		let dir = export("geotiff", &["--max-zoom", "1"])?;
+		let output = dir.path().join("output");
+		for file in ["0/0/0.tif", "1/0/0.tif", "1/1/1.tif"] {
+			let data = std::fs::read(output.join(file))?;
+			assert_eq!(&data[0..4], b"II*\0", "{file}");
+		}
+		assert!(!output.join("2").exists());
+		Ok(())
+	}
+
+	#[test]
+	fn test_export_png_with_world_file() -> Result<()> {
+		let dir = export("png", &["--min-zoom", "2", "--max-zoom", "2", "--bbox", "0,0,10,10"])?;
+		let tile = dir.path().join("output/2/2/1");
+		assert!(tile.with_extension("png").exists());
+		assert!(tile.with_extension("prj").exists());
+
+		let world_file = std::fs::read_to_string(tile.with_extension("pgw"))?;
+		let lines = world_file
+			.lines()
+			.map(|l| l.parse::<f64>().unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(lines.len(), 6);
+		assert!(lines[0] > 0.0 && lines[3] < 0.0);
+		assert!((lines[4] - lines[0] / 2.0).abs() < 1e-6, "tile 2/2/1 starts at x = 0");
+		assert_eq!(std::fs::read_dir(dir.path().join("output/2"))?.count(), 1);
+		Ok(())
+	}
+
+	#[test]
+	fn test_export_across_antimeridian() -> Result<()> {
+		let dir = export(
+			"png",
+			&["--min-zoom", "3", "--max-zoom", "3", "--bbox", "176,-21,-178,-12"],
+		)?;
+		let mut columns = std::fs::read_dir(dir.path().join("output/3"))?
+			.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+			.collect::<Vec<_>>();
+		columns.sort();
+		assert_eq!(columns, ["0", "7"]);
+		Ok(())
+	}
+
+	#[test]
+	fn test_export_rejects_too_many_tiles() {
+		let error = export("geotiff", &[]).unwrap_err().to_string();
+		assert!(error.contains("refusing to export"), "{error}");
+	}
+
+	#[test]
+	fn test_export_rejects_vector_tiles() {
+		let dir = TempDir::new().unwrap();
+		let output = dir.path().join("output");
+		let error = run_command(vec![
+			"versatiles",
+			"export",
+			"-q",
+			"../testdata/berlin.mbtiles",
+			output.to_str().unwrap(),
+		])
+		.unwrap_err()
+		.to_string();
+		assert!(error.contains("only raster tiles"), "{error}");
+	}
+}
@@ -0,0 +1,82 @@
+use anyhow::{Context, Result, ensure};
+use std::{fs, path::PathBuf};
+use versatiles_image::glyphs::GlyphRenderer;
+
+#[derive(clap::Args, Debug)]
+#[command(arg_required_else_help = true, disable_version_flag = true)]
+pub struct Subcommand {
+	/// Font files (*.ttf or *.otf).
+	/// The fontstack name is the file name without extension,
+	///    e.g. "fonts/Noto Sans Regular.ttf" becomes "Noto Sans Regular"
+	#[arg(required = true, verbatim_doc_comment)]
+	font_files: Vec<PathBuf>,
+
+	/// Output directory. Every font is written to "$output/$name/$start-$end.pbf".
+	/// Serve the glyphs with e.g. `versatiles serve -s "[/assets/glyphs]$output" ...`
+	///    and set "glyphs" in the style to "/assets/glyphs/{fontstack}/{range}.pbf"
+	#[arg(short, long, value_name = "DIRECTORY", verbatim_doc_comment)]
+	output: PathBuf,
+}
+
+pub fn run(arguments: &Subcommand) -> Result<()> {
+	for path in arguments.font_files.iter() {
+		let name = path
+			.file_stem()
+			.with_context(|| format!("font path {path:?} has no filename"))?
+			.to_string_lossy();
+		let data = fs::read(path).with_context(|| format!("reading font {path:?}"))?;
+		let renderer = GlyphRenderer::from_vec(data, &name)?;
+		write_font(&renderer, &arguments.output)?;
+	}
+	Ok(())
+}
+
+/// Writes all glyph ranges of a font into `$output/$name/`.
+fn write_font(renderer: &GlyphRenderer, output: &std::path::Path) -> Result<()> {
+	let ranges = renderer.range_starts();
+	ensure!(!ranges.is_empty(), "font '{}' contains no glyphs", renderer.name());
+	log::info!("writing {} glyph ranges of font '{}'", ranges.len(), renderer.name());
+
+	let directory = output.join(renderer.name());
+	fs::create_dir_all(&directory).with_context(|| format!("creating directory {directory:?}"))?;
+	for start in ranges {
+		let filename = directory.join(format!("{start}-{}.pbf", start + 255));
+		fs::write(&filename, renderer.render_range(start)?.as_slice())
+			.with_context(|| format!("writing {filename:?}"))?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tests::run_command;
+	use anyhow::Result;
+	use assert_fs::TempDir;
+
+	#[test]
+	fn test_glyphs() -> Result<()> {
+		let dir = TempDir::new()?;
+		run_command(vec![
+			"versatiles",
+			"glyphs",
+			"-q",
+			"-o",
+			dir.path().to_str().unwrap(),
+			"../versatiles_image/src/draw/trim.ttf",
+		])?;
+		assert!(dir.path().join("trim/0-255.pbf").is_file());
+
+		assert!(
+			run_command(vec![
+				"versatiles",
+				"glyphs",
+				"-q",
+				"-o",
+				dir.path().to_str().unwrap(),
+				"missing.ttf"
+			])
+			.is_err()
+		);
+		Ok(())
+	}
+}
@@ -1,5 +1,7 @@
 use anyhow::Result;
+use clap::CommandFactory;
 use versatiles::Config;
+use versatiles_core::json::{JsonObject, JsonValue};
 use versatiles_pipeline::PipelineFactory;
 
 #[derive(clap::Args, Debug)]
@@ -34,6 +36,71 @@ pub fn run(command: &Subcommand) -> Result<()> {
 	Ok(())
 }
 
+/// Returns the schemas of all CLI commands and pipeline operations as JSON, see `--help-json`.
+pub fn help_json() -> Result<String> {
+	let mut cli = crate::Cli::command();
+	cli.build();
+
+	let mut object = JsonObject::new();
+	object.set("cli", command_to_json(&cli));
+	object.set("pipeline", PipelineFactory::new_dummy().help_json()?);
+	Ok(object.stringify_pretty_multi_line(100, 0))
+}
+
+fn command_to_json(command: &clap::Command) -> JsonObject {
+	let mut object = JsonObject::new();
+	object.set("name", command.get_name());
+	object.set_optional("about", &command.get_about().map(ToString::to_string));
+	let arguments = command
+		.get_arguments()
+		.filter(|arg| !arg.is_hide_set())
+		.map(|arg| JsonValue::from(argument_to_json(arg)))
+		.collect::<Vec<_>>();
+	object.set("arguments", arguments);
+	let subcommands = command
+		.get_subcommands()
+		.filter(|subcommand| !subcommand.is_hide_set())
+		.map(|subcommand| JsonValue::from(command_to_json(subcommand)))
+		.collect::<Vec<_>>();
+	if !subcommands.is_empty() {
+		object.set("subcommands", subcommands);
+	}
+	object
+}
+
+fn argument_to_json(arg: &clap::Arg) -> JsonObject {
+	let mut object = JsonObject::new();
+	object.set("id", arg.get_id().as_str());
+	object.set_optional("long", &arg.get_long());
+	object.set_optional("short", &arg.get_short().map(String::from));
+	object.set_optional("help", &arg.get_help().map(ToString::to_string));
+	object.set("positional", arg.is_positional());
+	object.set("required", arg.is_required_set());
+	object.set("global", arg.is_global_set());
+	object.set("takes_value", arg.get_action().takes_values());
+	if let Some(names) = arg.get_value_names() {
+		object.set("value_names", names.iter().map(ToString::to_string).collect::<Vec<_>>());
+	}
+	let possible_values = arg
+		.get_possible_values()
+		.iter()
+		.filter(|value| !value.is_hide_set())
+		.map(|value| value.get_name().to_string())
+		.collect::<Vec<_>>();
+	if !possible_values.is_empty() {
+		object.set("possible_values", possible_values);
+	}
+	let defaults = arg
+		.get_default_values()
+		.iter()
+		.map(|value| value.to_string_lossy().to_string())
+		.collect::<Vec<_>>();
+	if !defaults.is_empty() {
+		object.set("default_values", defaults);
+	}
+	object
+}
+
 fn print_markdown(md: String) {
 	use termimad::{
 		Area, MadSkin,
@@ -73,6 +140,7 @@ fn print_markdown(md: String) {
 mod tests {
 	use crate::tests::run_command;
 	use anyhow::Result;
+	use versatiles_core::json::{JsonObject, JsonValue};
 
 	#[test]
 	fn test_help1() -> Result<()> {
@@ -97,4 +165,38 @@ mod tests {
 		run_command(vec!["versatiles", "help", "--raw", "config"])?;
 		Ok(())
 	}
+
+	#[test]
+	fn test_help_json() -> Result<()> {
+		run_command(vec!["versatiles", "--help-json"])?;
+
+		let json = JsonObject::parse_str(&super::help_json()?)?;
+		let cli = json.get_object("cli")?.unwrap();
+		assert_eq!(cli.get_string("name")?.unwrap(), "versatiles");
+		let subcommands = cli.get_array("subcommands")?.unwrap().as_vec();
+		let convert = subcommands
+			.iter()
+			.map(|c| c.as_object().unwrap())
+			.find(|c| c.get_string("name").unwrap().unwrap() == "convert")
+			.unwrap();
+		let arguments = convert.get_array("arguments")?.unwrap().as_vec();
+		assert!(
+			arguments
+				.iter()
+				.any(|a| a.as_object().unwrap().get_string("long").unwrap().as_deref() == Some("on-error"))
+		);
+
+		let operations = json.get_array("pipeline")?.unwrap().as_vec();
+		let filter = operations
+			.iter()
+			.map(|o| o.as_object().unwrap())
+			.find(|o| o.get_string("name").unwrap().unwrap() == "filter")
+			.unwrap();
+		assert_eq!(filter.get_string("kind")?.unwrap(), "transform");
+		let parameter = filter.get_array("parameters")?.unwrap().as_vec()[0].as_object()?;
+		assert_eq!(parameter.get_string("name")?.unwrap(), "bbox");
+		assert_eq!(parameter.get_string("type")?.unwrap(), "[f64,f64,f64,f64]");
+		assert_eq!(parameter.get("required"), Some(&JsonValue::Boolean(false)));
+		Ok(())
+	}
 }
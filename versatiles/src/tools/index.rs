@@ -0,0 +1,76 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use versatiles::{AttributeIndex, get_registry};
+use versatiles_container::ProcessingConfig;
+
+#[derive(clap::Args, Debug)]
+#[command(arg_required_else_help = true, disable_version_flag = true)]
+pub struct Subcommand {
+	/// vector tile container to index
+	#[arg(required = true)]
+	input_file: String,
+
+	/// index file, defaults to "<input_file>.index.tsv"
+	output_file: Option<PathBuf>,
+
+	/// zoom level of the indexed tiles, defaults to the highest zoom level
+	#[arg(long, value_name = "int")]
+	level: Option<u8>,
+
+	/// attribute keys to index, comma separated, defaults to all attributes (feature IDs are always indexed)
+	#[arg(long, value_delimiter = ',')]
+	keys: Option<Vec<String>>,
+}
+
+#[tokio::main]
+pub async fn run(arguments: &Subcommand) -> Result<()> {
+	log::info!("index {:?}", arguments.input_file);
+
+	let reader = get_registry(ProcessingConfig::default())
+		.open_reader(&arguments.input_file)
+		.await?;
+
+	let index = AttributeIndex::build(reader.as_ref(), arguments.level, arguments.keys.as_deref()).await?;
+
+	let output_file = match &arguments.output_file {
+		Some(path) => path.clone(),
+		None => AttributeIndex::sidecar_path(Path::new(&arguments.input_file)),
+	};
+	index.write_to_path(&output_file)?;
+	log::info!(
+		"wrote {} entries of level {} to {output_file:?}",
+		index.len(),
+		index.level()
+	);
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tests::run_command;
+	use anyhow::Result;
+	use assert_fs::TempDir;
+	use versatiles::AttributeIndex;
+
+	#[test]
+	fn test_index() -> Result<()> {
+		let dir = TempDir::new()?;
+		let output = dir.path().join("berlin.index.tsv");
+		run_command(vec![
+			"versatiles",
+			"index",
+			"-q",
+			"../testdata/berlin.mbtiles",
+			output.to_str().unwrap(),
+			"--level",
+			"10",
+			"--keys",
+			"name",
+		])?;
+		let index = AttributeIndex::read_from_path(&output)?;
+		assert_eq!(index.level(), 10);
+		assert!(!index.is_empty());
+		Ok(())
+	}
+}
@@ -1,8 +1,23 @@
 //! cli tools
 
+pub mod bench;
+pub mod completions;
 pub mod convert;
+mod convert_batch;
+mod convert_job;
 pub mod dev;
 mod dev_tools;
+pub mod export;
+pub mod glyphs;
 pub mod help;
+pub mod index;
+pub mod osc_tiles;
 pub mod probe;
 pub mod serve;
+pub mod snapshot;
+pub mod sprites;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+#[cfg(feature = "tui")]
+pub mod top;
+pub mod verify;
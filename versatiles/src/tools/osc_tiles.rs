@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use std::{
+	fs::{self, File},
+	io::BufWriter,
+	path::PathBuf,
+};
+use versatiles_core::{Blob, utils::decompress_gzip};
+use versatiles_geometry::osm_change::OsmChange;
+
+#[derive(clap::Args, Debug)]
+#[command(arg_required_else_help = true, disable_version_flag = true)]
+pub struct Subcommand {
+	/// OSM change files (*.osc or *.osc.gz), e.g. a sequence of minutely or daily diffs
+	#[arg(required = true)]
+	osc_files: Vec<PathBuf>,
+
+	/// CSV file to write the affected tiles to, as "z,x,y".
+	/// Use it with `versatiles convert --coordinate-list` to rebuild just these tiles
+	#[arg(short, long, value_name = "FILE", verbatim_doc_comment)]
+	output: PathBuf,
+
+	/// minimum zoom level
+	#[arg(long, value_name = "int", default_value_t = 0)]
+	min_zoom: u8,
+
+	/// maximum zoom level
+	#[arg(long, value_name = "int", default_value_t = 14)]
+	max_zoom: u8,
+
+	/// expand the bounding box of every changed element by this many pixels of a 256 pixel tile,
+	/// because features are also drawn into neighbouring tiles
+	#[arg(long, value_name = "PIXELS", default_value_t = 4.0)]
+	buffer: f64,
+}
+
+pub fn run(arguments: &Subcommand) -> Result<()> {
+	let mut change = OsmChange::default();
+	for path in &arguments.osc_files {
+		let mut blob = Blob::from(fs::read(path).with_context(|| format!("reading {path:?}"))?);
+		if path.extension().is_some_and(|extension| extension == "gz") {
+			blob = decompress_gzip(&blob)?;
+		}
+		let xml = std::str::from_utf8(blob.as_slice()).with_context(|| format!("{path:?} is not valid UTF-8"))?;
+		change.add_xml(xml).with_context(|| format!("reading {path:?}"))?;
+	}
+
+	let (nodes, ways, relations) = change.count_elements();
+	log::info!("read {nodes} nodes, {ways} ways and {relations} relations");
+	let unresolved = change.count_unresolved();
+	if unresolved > 0 {
+		log::warn!("{unresolved} ways and relations are skipped, because none of their nodes are in the change files");
+	}
+
+	let tiles = change.affected_tiles(arguments.min_zoom, arguments.max_zoom, arguments.buffer / 256.0)?;
+	let file = File::create(&arguments.output).with_context(|| format!("creating {:?}", arguments.output))?;
+	tiles.write_csv(BufWriter::new(file))?;
+	log::info!("wrote {} affected tiles to {:?}", tiles.len(), arguments.output);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tests::run_command;
+	use anyhow::Result;
+	use assert_fs::TempDir;
+	use std::fs;
+	use versatiles_core::{Blob, TileCoord, TileCoordList, utils::compress_gzip};
+
+	fn osc(id: u32, lon: f64) -> String {
+		format!(r#"<osmChange version="0.6"><modify><node id="{id}" lat="52.52" lon="{lon}"/></modify></osmChange>"#)
+	}
+
+	#[test]
+	fn test_osc_tiles() -> Result<()> {
+		let dir = TempDir::new()?;
+		let osc_path = dir.path().join("changes.osc");
+		fs::write(&osc_path, osc(1, 13.4))?;
+		let osc_gz_path = dir.path().join("changes.osc.gz");
+		fs::write(&osc_gz_path, compress_gzip(&Blob::from(osc(2, -70.0)))?.as_slice())?;
+		let output = dir.path().join("tiles.csv");
+
+		run_command(vec![
+			"versatiles",
+			"osc-tiles",
+			"--max-zoom=14",
+			osc_path.to_str().unwrap(),
+			osc_gz_path.to_str().unwrap(),
+			"-o",
+			output.to_str().unwrap(),
+		])?;
+
+		let tiles = TileCoordList::from_csv_path(&output)?;
+		assert!(tiles.contains(&TileCoord::new(14, 8801, 5373)?));
+		assert!(tiles.contains(&TileCoord::new(14, 5006, 5373)?));
+		assert_eq!(tiles.bbox_pyramid().get_level_max(), Some(14));
+
+		assert!(
+			run_command(vec![
+				"versatiles",
+				"osc-tiles",
+				"-o",
+				output.to_str().unwrap(),
+				"missing.osc"
+			])
+			.is_err()
+		);
+		Ok(())
+	}
+}
@@ -1,7 +1,10 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use versatiles::get_registry;
-use versatiles_container::ProcessingConfig;
-use versatiles_core::ProbeDepth;
+use versatiles_container::{DataLocation, ProcessingConfig, VersaTilesReader};
+use versatiles_core::{
+	ProbeDepth,
+	io::{DataReaderFile, DataReaderHttp},
+};
 
 #[derive(clap::Args, Debug)]
 #[command(arg_required_else_help = true, disable_version_flag = true)]
@@ -14,17 +17,26 @@ pub struct Subcommand {
 	/// deep scan (depending on the container implementation)
 	///   -d: scans container
 	///  -dd: scans all tiles
-	/// -ddd: scans all tile contents
+	/// -ddd: samples tile contents, e.g. the bytes per layer of vector tiles
 	#[arg(long, short, action = clap::ArgAction::Count, verbatim_doc_comment)]
 	deep: u8,
+
+	/// print the binary layout of a *.versatiles container as annotated JSON:
+	/// all header fields and index entries with their byte offsets
+	#[arg(long, conflicts_with = "deep", verbatim_doc_comment)]
+	layout: bool,
 }
 
 #[tokio::main]
 pub async fn run(arguments: &Subcommand) -> Result<()> {
 	log::info!("probe {:?}", arguments.filename);
 
+	if arguments.layout {
+		return print_layout(&arguments.filename).await;
+	}
+
 	let mut reader = get_registry(ProcessingConfig::default())
-		.get_reader_from_str(&arguments.filename)
+		.open_reader(&arguments.filename)
 		.await?;
 
 	let level = match arguments.deep {
@@ -40,6 +52,18 @@ pub async fn run(arguments: &Subcommand) -> Result<()> {
 	Ok(())
 }
 
+async fn print_layout(filename: &str) -> Result<()> {
+	let mut location = DataLocation::from(filename);
+	location.resolve(&DataLocation::cwd()?)?;
+	let reader = match location {
+		DataLocation::Url(url) => VersaTilesReader::open_reader(DataReaderHttp::from_url(url)?).await?,
+		DataLocation::Path(path) => VersaTilesReader::open_reader(DataReaderFile::open(&path)?).await?,
+		DataLocation::Blob(_) => bail!("--layout needs a file or URL"),
+	};
+	println!("{}", reader.layout().await?.stringify_pretty_multi_line(100, 0));
+	Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 	use crate::tests::run_command;
@@ -51,6 +75,17 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_layout() -> Result<()> {
+		let temp_dir = assert_fs::TempDir::new()?;
+		let filename = temp_dir.path().join("berlin.versatiles");
+		let filename = filename.to_str().unwrap();
+		run_command(vec!["versatiles", "convert", "../testdata/berlin.mbtiles", filename])?;
+		run_command(vec!["versatiles", "probe", "--layout", filename])?;
+		assert!(run_command(vec!["versatiles", "probe", "--layout", "-d", filename]).is_err());
+		Ok(())
+	}
+
 	#[test]
 	fn test_remote() -> Result<()> {
 		run_command(vec![
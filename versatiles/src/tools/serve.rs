@@ -1,8 +1,14 @@
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, anyhow, ensure};
 use regex::Regex;
-use std::{mem::swap, path::PathBuf, str::FromStr};
+use std::{
+	io::{BufWriter, Read, Write},
+	mem::swap,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+use tempfile::NamedTempFile;
 use tokio::time::{Duration, sleep};
-use versatiles::{Config, StaticSourceConfig, TileSourceConfig, get_registry, server::TileServer};
+use versatiles::{Config, StaticSourceConfig, StyleSources, TileSourceConfig, get_registry, server::TileServer};
 use versatiles_container::{DataLocation, ProcessingConfig};
 
 #[derive(clap::Args, Debug)]
@@ -16,6 +22,9 @@ pub struct Subcommand {
 	///    e.g. ".../ukraine.versatiles" will be served at url "/tiles/ukraine/..."
 	/// You can also configure a different id for each file using:
 	///    "[id]file", "file[id]" or "file#id"
+	/// Use "-" to read a container from stdin, e.g. "curl ... | versatiles serve -".
+	///    The format is detected automatically or can be set with a prefix, e.g. "pmtiles:-".
+	///    The container is stored in a temporary file while the server runs.
 	#[arg(verbatim_doc_comment)]
 	pub tile_sources: Vec<String>,
 
@@ -39,6 +48,12 @@ pub struct Subcommand {
 	#[arg(short = 's', long = "static", verbatim_doc_comment, display_order = 1)]
 	pub static_content: Vec<String>,
 
+	/// Self-host a MapLibre/Mapbox style JSON.
+	/// All sources of the style that point to tile containers (e.g. "pmtiles://..." or "osm.versatiles")
+	/// are served under "/tiles/$source/" and the style, rewritten to use them, is served at "/styles/$filename".
+	#[arg(long, value_name = "FILE", verbatim_doc_comment, display_order = 1)]
+	pub style: Vec<PathBuf>,
+
 	/// Shutdown server automatically after x milliseconds.
 	#[arg(long, display_order = 4)]
 	pub auto_shutdown: Option<u64>,
@@ -50,6 +65,26 @@ pub struct Subcommand {
 	/// disable API
 	#[arg(long, display_order = 4)]
 	pub disable_api: Option<bool>,
+
+	/// Reload tile containers when their files are modified or replaced on disk.
+	/// Only local files are watched. Changes are applied once a file hasn't changed for a second.
+	#[arg(long, display_order = 2)]
+	pub watch: bool,
+
+	/// Maximum time in milliseconds to read a single tile, e.g. from a remote container or a pipeline.
+	/// Slower tiles are answered with "504 Gateway Timeout".
+	#[arg(long, value_name = "MILLISECONDS", display_order = 2)]
+	pub tile_timeout: Option<u64>,
+
+	/// Cache the indexes of remote tile containers in this directory, so restarts don't have to download them again.
+	/// A cached index is used until the ETag, Last-Modified or size of its container changes.
+	#[arg(long, value_name = "DIR", verbatim_doc_comment, display_order = 2)]
+	pub index_cache: Option<String>,
+
+	/// Maximum number of tiles decoded or encoded at the same time, e.g. when a pipeline renders images.
+	/// Decoding and encoding run on blocking threads, so they do not slow down other requests. Defaults to the number of CPUs.
+	#[arg(long, value_name = "COUNT", verbatim_doc_comment, display_order = 2)]
+	pub codec_pool_size: Option<usize>,
 }
 
 #[tokio::main]
@@ -67,6 +102,14 @@ pub async fn run(arguments: &Subcommand) -> Result<()> {
 		.server
 		.override_optional_minimal_recompression(&arguments.minimal_recompression);
 	config.server.override_optional_disable_api(&arguments.disable_api);
+	if arguments.watch {
+		config.server.override_optional_watch(&Some(true));
+	}
+	config.server.override_optional_tile_timeout(&arguments.tile_timeout);
+	config.server.override_optional_index_cache(&arguments.index_cache);
+	config
+		.server
+		.override_optional_codec_pool_size(&arguments.codec_pool_size);
 
 	let tile_patterns: Vec<Regex> = [
 		r"^\[(?P<name>[^\]]+?)\](?P<url>.*)$",
@@ -78,6 +121,7 @@ pub async fn run(arguments: &Subcommand) -> Result<()> {
 	.map(|pat| Regex::new(pat).unwrap())
 	.collect();
 
+	let mut stdin_container: Option<StdinContainer> = None;
 	let mut tile_sources = arguments
 		.tile_sources
 		.iter()
@@ -89,13 +133,27 @@ pub async fn run(arguments: &Subcommand) -> Result<()> {
 				.captures(argument)
 				.ok_or_else(|| anyhow!("Failed to parse tile source argument: {}", argument))?;
 
-			let path = DataLocation::from_str(capture.name("url").unwrap().as_str())?;
-			let name: String = match capture.name("name") {
-				None => path.name()?,
-				Some(m) => m.as_str().to_string(),
+			let url = capture.name("url").unwrap().as_str();
+			let (path, default_name) = if let Some(extension) = parse_stdin(url) {
+				ensure!(stdin_container.is_none(), "stdin can only be used once");
+				let container = StdinContainer::from_reader(std::io::stdin().lock(), extension.as_deref())?;
+				let path = DataLocation::from(container.path().to_path_buf());
+				stdin_container = Some(container);
+				(path, Some("stdin"))
+			} else {
+				(DataLocation::from_str(url)?, None)
+			};
+			let name: String = match (capture.name("name"), default_name) {
+				(Some(m), _) => m.as_str().to_string(),
+				(None, Some(name)) => name.to_string(),
+				(None, None) => path.name()?,
 			};
 
-			Ok(TileSourceConfig { name: Some(name), path })
+			Ok(TileSourceConfig {
+				name: Some(name),
+				path,
+				index: None,
+			})
 		})
 		.collect::<Result<Vec<TileSourceConfig>>>()?;
 	swap(&mut config.tile_sources, &mut tile_sources);
@@ -134,7 +192,26 @@ pub async fn run(arguments: &Subcommand) -> Result<()> {
 	config.static_sources.extend(static_sources);
 
 	let registry = get_registry(ProcessingConfig::default());
+
+	let mut styles = Vec::new();
+	for path in arguments.style.iter() {
+		let style = StyleSources::from_path(path, &registry)?;
+		for name in style.skipped_sources() {
+			log::warn!("style {path:?}: source '{name}' is not a tile container and is left unchanged");
+		}
+		config.tile_sources.extend(style.tile_sources().iter().cloned());
+		let filename = path
+			.file_name()
+			.context("style path has no filename")?
+			.to_string_lossy();
+		styles.push((format!("/styles/{filename}"), style.rewritten_style()));
+	}
+
 	let mut server: TileServer = TileServer::from_config(config, registry).await?;
+	for (url, style) in styles.iter() {
+		log::info!("add style: {url}");
+		server.add_style(url, style)?;
+	}
 
 	let mut list = server.get_url_mapping().await;
 	list.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
@@ -147,18 +224,140 @@ pub async fn run(arguments: &Subcommand) -> Result<()> {
 	if let Some(milliseconds) = arguments.auto_shutdown {
 		sleep(Duration::from_millis(milliseconds)).await
 	} else {
-		loop {
-			sleep(Duration::from_secs(60)).await
-		}
+		// Wait for Ctrl+C, so a container read from stdin is removed on exit.
+		tokio::signal::ctrl_c().await?;
 	}
 
+	server.stop().await;
+	drop(stdin_container);
+
 	Ok(())
 }
 
+/// Returns `Some(extension)` if `url` means stdin: `-` or with a format prefix like `pmtiles:-`.
+fn parse_stdin(url: &str) -> Option<Option<String>> {
+	if url == "-" {
+		return Some(None);
+	}
+	let (prefix, rest) = url.split_once(':')?;
+	(rest == "-" && !prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_alphanumeric()))
+		.then(|| Some(prefix.to_lowercase()))
+}
+
+/// Detects the container format from the first bytes of a file.
+fn detect_format(head: &[u8]) -> Option<&'static str> {
+	if head.starts_with(b"versatiles_v02") || head.starts_with(b"versatiles_s02") {
+		Some("versatiles")
+	} else if head.starts_with(b"PMTiles") {
+		Some("pmtiles")
+	} else if head.starts_with(b"SQLite format 3\0") {
+		Some("mbtiles")
+	} else if head.starts_with(b"PK\x03\x04") {
+		Some("zip")
+	} else if head.starts_with(&[0x1f, 0x8b])
+		|| head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd])
+		|| head.get(257..262) == Some(&b"ustar"[..])
+	{
+		// uncompressed, gzip or zstd compressed tar archive
+		Some("tar")
+	} else {
+		None
+	}
+}
+
+/// A container read from stdin, stored in a temporary file that is removed on drop.
+struct StdinContainer {
+	file: NamedTempFile,
+}
+
+impl StdinContainer {
+	/// Copies `input` into a temporary file.
+	/// Without `extension`, the container format is detected from the first bytes.
+	fn from_reader(mut input: impl Read, extension: Option<&str>) -> Result<StdinContainer> {
+		let mut head = Vec::new();
+		(&mut input).take(512).read_to_end(&mut head)?;
+		ensure!(!head.is_empty(), "no data on stdin");
+		let extension = match extension {
+			Some(extension) => extension.to_string(),
+			None => detect_format(&head)
+				.context("unknown container format on stdin, set it with a prefix, e.g. 'pmtiles:-'")?
+				.to_string(),
+		};
+
+		// The file gets a random name and is created exclusively, so an existing file or symlink in a shared
+		// temporary directory can't be used to redirect the write. It is removed on drop, even if copying fails.
+		let container = StdinContainer {
+			file: tempfile::Builder::new()
+				.prefix("versatiles_stdin_")
+				.suffix(&format!(".{extension}"))
+				.tempfile()?,
+		};
+		let mut file = BufWriter::new(container.file.as_file());
+		file.write_all(&head)?;
+		let size = head.len() as u64 + std::io::copy(&mut input, &mut file).context("Failed to read from stdin")?;
+		file.flush()?;
+		drop(file);
+		log::info!("read {size} bytes of {extension} container from stdin");
+
+		Ok(container)
+	}
+
+	/// Path of the temporary file.
+	fn path(&self) -> &Path {
+		self.file.path()
+	}
+}
+
 #[cfg(test)]
 mod tests {
+	use super::*;
 	use crate::tests::run_command;
 	use anyhow::Result;
+	use std::io::Cursor;
+
+	#[test]
+	fn test_parse_stdin() {
+		assert_eq!(parse_stdin("-"), Some(None));
+		assert_eq!(parse_stdin("PMTiles:-"), Some(Some(String::from("pmtiles"))));
+		assert_eq!(parse_stdin("tiles.pmtiles"), None);
+		assert_eq!(parse_stdin("https://example.org/-"), None);
+		assert_eq!(parse_stdin(":-"), None);
+	}
+
+	#[test]
+	fn test_detect_format() {
+		let mut tar = vec![0; 512];
+		tar[257..262].copy_from_slice(b"ustar");
+		assert_eq!(detect_format(&tar), Some("tar"));
+		assert_eq!(detect_format(&[0x1f, 0x8b, 8, 0]), Some("tar"));
+		assert_eq!(detect_format(b"versatiles_v02..."), Some("versatiles"));
+		assert_eq!(detect_format(b"versatiles_s02..."), Some("versatiles"));
+		assert_eq!(detect_format(b"PK\x03\x04..."), Some("zip"));
+		assert_eq!(detect_format(b"{\"tilejson\":\"3.0.0\"}"), None);
+	}
+
+	#[test]
+	fn test_stdin_container() -> Result<()> {
+		for (filename, extension) in [
+			("../testdata/berlin.mbtiles", "mbtiles"),
+			("../testdata/berlin.pmtiles", "pmtiles"),
+		] {
+			let data = std::fs::read(filename)?;
+			let container = StdinContainer::from_reader(Cursor::new(&data), None)?;
+			let path = container.path().to_path_buf();
+			assert_eq!(path.extension().unwrap(), extension);
+			assert_eq!(std::fs::read(&path)?, data);
+			drop(container);
+			assert!(!path.exists());
+		}
+
+		let container = StdinContainer::from_reader(Cursor::new(b"data"), Some("tar"))?;
+		assert_eq!(container.path().extension().unwrap(), "tar");
+
+		assert!(StdinContainer::from_reader(Cursor::new(b"unknown"), None).is_err());
+		assert!(StdinContainer::from_reader(Cursor::new(b""), Some("tar")).is_err());
+		Ok(())
+	}
 
 	#[test]
 	fn test_local() -> Result<()> {
@@ -176,6 +375,49 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_watch() -> Result<()> {
+		run_command(vec![
+			"versatiles",
+			"serve",
+			"-i",
+			"127.0.0.1",
+			"-p",
+			"65003",
+			"--auto-shutdown",
+			"500",
+			"--watch",
+			"../testdata/berlin.mbtiles[test]",
+		])?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_style() -> Result<()> {
+		let dir = assert_fs::TempDir::new()?;
+		let style = dir.path().join("style.json");
+		std::fs::write(
+			&style,
+			format!(
+				r#"{{"version":8,"sources":{{"berlin":{{"type":"vector","url":"pmtiles://{}"}}}},"layers":[]}}"#,
+				std::fs::canonicalize("../testdata/berlin.pmtiles")?.display()
+			),
+		)?;
+		run_command(vec![
+			"versatiles",
+			"serve",
+			"-i",
+			"127.0.0.1",
+			"-p",
+			"65004",
+			"--auto-shutdown",
+			"500",
+			"--style",
+			style.to_str().unwrap(),
+		])?;
+		Ok(())
+	}
+
 	#[test]
 	fn test_remote() -> Result<()> {
 		run_command(vec![
@@ -0,0 +1,243 @@
+use anyhow::{Context, Result, bail, ensure};
+use std::path::PathBuf;
+use versatiles::get_registry;
+use versatiles_container::ProcessingConfig;
+use versatiles_core::{GeoBBox, TileBBox, TileCoord, TileFormat};
+use versatiles_geometry::{geo::Coordinates, vector_tile::TileProjection};
+use versatiles_image::{DynamicImage, DynamicImageTraitOperation, Rgb, Rgba, mosaic};
+
+/// color of the tile borders drawn by `--grid`
+const GRID_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+#[derive(clap::Args, Debug)]
+#[command(arg_required_else_help = true, disable_version_flag = true)]
+pub struct Subcommand {
+	/// raster tile container or pipeline (.vpl) to render
+	#[arg(required = true)]
+	input_file: String,
+
+	/// image file to write. The format is detected from the extension: .png, .jpg, .webp or .avif
+	#[arg(required = true)]
+	output_file: PathBuf,
+
+	/// area to render
+	#[arg(
+		long,
+		short,
+		required = true,
+		value_name = "lon_min,lat_min,lon_max,lat_max",
+		allow_hyphen_values = true
+	)]
+	bbox: String,
+
+	/// zoom level of the tiles to render
+	#[arg(long, short, value_name = "int")]
+	zoom: u8,
+
+	/// draw the tile borders on top of the image
+	#[arg(long)]
+	grid: bool,
+
+	/// quality of lossy output formats (0-100)
+	#[arg(long, value_name = "int")]
+	quality: Option<u8>,
+
+	/// refuse to render more tiles than this, to protect against accidentally rendering huge images
+	#[arg(long, value_name = "int", default_value_t = 1_000)]
+	max_tiles: u64,
+}
+
+#[tokio::main]
+pub async fn run(arguments: &Subcommand) -> Result<()> {
+	log::info!("render snapshot of {:?}", arguments.input_file);
+
+	let mut filename = arguments.output_file.to_string_lossy().to_string();
+	let format = TileFormat::from_filename(&mut filename)
+		.filter(TileFormat::is_raster)
+		.with_context(|| {
+			format!(
+				"output file {:?} must end with .png, .jpg, .webp or .avif",
+				arguments.output_file
+			)
+		})?;
+
+	let bbox: GeoBBox = arguments.bbox.parse()?;
+	ensure!(
+		!bbox.crosses_antimeridian(),
+		"snapshots of bounding boxes that cross the antimeridian are not supported"
+	);
+	let tile_bbox = TileBBox::from_geo(arguments.zoom, &bbox)?;
+	ensure!(
+		tile_bbox.count_tiles() <= arguments.max_tiles,
+		"refusing to render {} tiles, which is more than --max-tiles {}. Use a smaller --bbox or --zoom",
+		tile_bbox.count_tiles(),
+		arguments.max_tiles
+	);
+
+	let reader = get_registry(ProcessingConfig::default())
+		.open_reader(&arguments.input_file)
+		.await?;
+	let parameters = reader.parameters();
+	ensure!(
+		parameters.tile_format.is_raster(),
+		"only raster tiles can be rendered, but the tiles are {}",
+		parameters.tile_format.as_str()
+	);
+
+	let mut stream = reader
+		.get_tile_stream(tile_bbox)
+		.await?
+		.map_item_parallel(|tile| tile.into_image());
+	let mut tiles = Vec::new();
+	while let Some(entry) = stream.next().await {
+		tiles.push(entry);
+	}
+
+	let Some((_, first)) = tiles.first() else {
+		bail!("there are no tiles in bbox {bbox:?} at zoom {}", arguments.zoom);
+	};
+	let tile_size = first.width();
+
+	let count = tiles.len();
+	let image = render(tiles, &bbox, arguments.zoom, tile_size, arguments.grid)?;
+	let image = if format == TileFormat::JPG {
+		image.into_flattened(Rgb([255, 255, 255]))?
+	} else {
+		image
+	};
+
+	let blob = versatiles_image::encode(&image, format, arguments.quality, None)?;
+	std::fs::write(&arguments.output_file, blob.as_slice())
+		.with_context(|| format!("Failed to write {:?}", arguments.output_file))?;
+
+	log::info!(
+		"rendered {}x{} pixels from {} tiles to {:?}",
+		image.width(),
+		image.height(),
+		count,
+		arguments.output_file
+	);
+	Ok(())
+}
+
+/// Stitches the tiles into one image that covers exactly `bbox`. Missing tiles stay transparent.
+fn render(
+	tiles: Vec<(TileCoord, DynamicImage)>,
+	bbox: &GeoBBox,
+	zoom: u8,
+	tile_size: u32,
+	grid: bool,
+) -> Result<DynamicImage> {
+	let tile_bbox = TileBBox::from_geo(zoom, bbox)?;
+	let mosaic = mosaic::stitch(&tile_bbox, tiles, tile_size, Rgba([0, 0, 0, 0]))?;
+
+	// crop the mosaic from whole tiles to the pixels of the bbox, in global pixel coordinates at `zoom`
+	let projection = TileProjection::new(TileCoord::new(zoom, 0, 0)?, tile_size);
+	let top_left = projection.from_lon_lat_clamped(&Coordinates::new(bbox.x_min, bbox.y_max));
+	let bottom_right = projection.from_lon_lat_clamped(&Coordinates::new(bbox.x_max, bbox.y_min));
+	let (x0, y0) = (top_left.x(), top_left.y());
+	let (x1, y1) = (bottom_right.x(), bottom_right.y());
+	let left = f64::from(tile_bbox.x_min()? * tile_size);
+	let top = f64::from(tile_bbox.y_min()? * tile_size);
+	let crop_x = (x0.floor() - left).clamp(0.0, f64::from(mosaic.width() - 1)) as u32;
+	let crop_y = (y0.floor() - top).clamp(0.0, f64::from(mosaic.height() - 1)) as u32;
+	let width = ((x1.ceil() - left) as u32).clamp(crop_x + 1, mosaic.width()) - crop_x;
+	let height = ((y1.ceil() - top) as u32).clamp(crop_y + 1, mosaic.height()) - crop_y;
+	let mut image = mosaic.crop_imm(crop_x, crop_y, width, height).to_rgba8();
+
+	if grid {
+		for (x, y, pixel) in image.enumerate_pixels_mut() {
+			if (crop_x + x).is_multiple_of(tile_size) || (crop_y + y).is_multiple_of(tile_size) {
+				*pixel = GRID_COLOR;
+			}
+		}
+	}
+
+	Ok(DynamicImage::ImageRgba8(image))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::run_command;
+	use assert_fs::TempDir;
+	use versatiles_core::Blob;
+	use versatiles_image::ImageBuffer;
+
+	fn snapshot(output: &str, extra: &[&str]) -> Result<(TempDir, PathBuf)> {
+		let dir = TempDir::new()?;
+		let input = dir.path().join("input.vpl");
+		std::fs::write(&input, "from_debug format=png")?;
+		let output = dir.path().join(output);
+
+		let mut args = vec![
+			"versatiles",
+			"snapshot",
+			"-q",
+			input.to_str().unwrap(),
+			output.to_str().unwrap(),
+		];
+		args.extend_from_slice(extra);
+		run_command(args)?;
+		Ok((dir, output))
+	}
+
+	#[test]
+	fn test_snapshot_world() -> Result<()> {
+		let (_dir, output) = snapshot("world.png", &["--bbox", "-180,-85.0511,180,85.0511", "--zoom", "1"])?;
+		let image = versatiles_image::decode(&Blob::from(std::fs::read(output)?), TileFormat::PNG)?;
+		assert_eq!(image.width(), image.height());
+		assert_eq!(image.width() % 2, 0);
+		Ok(())
+	}
+
+	#[test]
+	fn test_snapshot_grid() -> Result<()> {
+		let (_dir, output) = snapshot(
+			"grid.png",
+			&["--bbox", "-180,-85.0511,180,85.0511", "--zoom", "1", "--grid"],
+		)?;
+		let image = versatiles_image::decode(&Blob::from(std::fs::read(output)?), TileFormat::PNG)?.to_rgba8();
+		let (width, height) = (image.width(), image.height());
+		assert_eq!(image.get_pixel(width / 2, height / 4), &GRID_COLOR);
+		assert_eq!(image.get_pixel(width / 4, height / 2), &GRID_COLOR);
+		assert_ne!(image.get_pixel(width / 2 + 1, height / 4 + 1), &GRID_COLOR);
+		Ok(())
+	}
+
+	#[test]
+	fn test_snapshot_jpeg() -> Result<()> {
+		let (_dir, output) = snapshot("map.jpg", &["--bbox", "0,0,10,10", "--zoom", "3", "--quality", "80"])?;
+		let image = versatiles_image::decode(&Blob::from(std::fs::read(output)?), TileFormat::JPG)?;
+		assert!(image.width() > 0 && image.height() > 0);
+		Ok(())
+	}
+
+	#[test]
+	fn test_snapshot_rejects_unknown_extension() {
+		let error = snapshot("map.txt", &["--bbox", "0,0,10,10", "--zoom", "2"])
+			.unwrap_err()
+			.to_string();
+		assert!(error.contains("must end with"), "{error}");
+	}
+
+	#[test]
+	fn test_snapshot_rejects_too_many_tiles() {
+		let error = snapshot("map.png", &["--bbox", "-180,-85,180,85", "--zoom", "8"])
+			.unwrap_err()
+			.to_string();
+		assert!(error.contains("refusing to render"), "{error}");
+	}
+
+	#[test]
+	fn test_render_crops_to_bbox() -> Result<()> {
+		let tile = DynamicImage::ImageRgba8(ImageBuffer::from_pixel(256, 256, Rgba([0, 0, 255, 255])));
+		let tiles = vec![(TileCoord::new(1, 1, 0)?, tile)];
+		let bbox = GeoBBox::new(0.0, 0.0, 90.0, 66.51326)?;
+		let image = render(tiles, &bbox, 1, 256, false)?.to_rgba8();
+		assert_eq!(image.width(), 128);
+		assert!((128..=129).contains(&image.height()));
+		assert_eq!(image.get_pixel(0, image.height() - 1).0, [0, 0, 255, 255]);
+		Ok(())
+	}
+}
@@ -0,0 +1,88 @@
+use anyhow::{Context, Result, ensure};
+use std::{fs, path::PathBuf};
+use versatiles_image::{format::png, sprites::SpriteBuilder};
+
+#[derive(clap::Args, Debug)]
+#[command(arg_required_else_help = true, disable_version_flag = true)]
+pub struct Subcommand {
+	/// Directory of SVG icons. The icon names are the file names without extension.
+	#[arg(required = true)]
+	svg_directory: PathBuf,
+
+	/// Output path prefix, e.g. "sprites/basic".
+	/// Writes "$output.png", "$output.json", "$output@2x.png" and "$output@2x.json".
+	/// Serve them with e.g. `versatiles serve -s "[/assets/sprites]sprites" ...`
+	///    and set "sprite" in the style to "/assets/sprites/basic"
+	#[arg(short, long, value_name = "PREFIX", verbatim_doc_comment)]
+	output: PathBuf,
+}
+
+pub fn run(arguments: &Subcommand) -> Result<()> {
+	let directory = &arguments.svg_directory;
+	let mut builder = SpriteBuilder::default();
+	for entry in fs::read_dir(directory).with_context(|| format!("reading directory {directory:?}"))? {
+		let path = entry?.path();
+		if !path.extension().is_some_and(|e| e.eq_ignore_ascii_case("svg")) {
+			continue;
+		}
+		let name = path.file_stem().unwrap().to_string_lossy();
+		builder.add_svg(&name, &fs::read(&path).with_context(|| format!("reading {path:?}"))?)?;
+	}
+	ensure!(!builder.is_empty(), "no SVG files found in {directory:?}");
+	log::info!("rendering sprite of {} icons", builder.len());
+
+	if let Some(parent) = arguments.output.parent() {
+		fs::create_dir_all(parent).with_context(|| format!("creating directory {parent:?}"))?;
+	}
+	for (ratio, suffix) in [(1, ""), (2, "@2x")] {
+		let (image, index) = builder.render(ratio)?;
+		let prefix = format!("{}{suffix}", arguments.output.display());
+		fs::write(format!("{prefix}.png"), png::encode(&image, None)?.as_slice())
+			.with_context(|| format!("writing {prefix}.png"))?;
+		fs::write(format!("{prefix}.json"), index.stringify()).with_context(|| format!("writing {prefix}.json"))?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tests::run_command;
+	use anyhow::Result;
+	use assert_fs::TempDir;
+	use versatiles_core::json::JsonObject;
+
+	#[test]
+	fn test_sprites() -> Result<()> {
+		let dir = TempDir::new()?;
+		let icons = dir.path().join("icons");
+		std::fs::create_dir(&icons)?;
+		for (name, size) in [("a", 8), ("b", 12)] {
+			std::fs::write(
+				icons.join(format!("{name}.svg")),
+				format!(
+					r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}"><rect width="{size}" height="{size}"/></svg>"#
+				),
+			)?;
+		}
+		std::fs::write(icons.join("readme.txt"), "not an icon")?;
+
+		let output = dir.path().join("sprites/basic");
+		run_command(vec![
+			"versatiles",
+			"sprites",
+			"-q",
+			"-o",
+			output.to_str().unwrap(),
+			icons.to_str().unwrap(),
+		])?;
+
+		for filename in ["basic.png", "basic.json", "basic@2x.png", "basic@2x.json"] {
+			assert!(dir.path().join("sprites").join(filename).is_file(), "{filename}");
+		}
+		let index = JsonObject::parse_str(&std::fs::read_to_string(dir.path().join("sprites/basic@2x.json"))?)?;
+		let icon = index.get_object("b")?.unwrap();
+		assert_eq!(icon.get_number("width")?, Some(24.0));
+		assert_eq!(icon.get_number("pixelRatio")?, Some(2.0));
+		Ok(())
+	}
+}
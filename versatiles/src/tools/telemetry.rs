@@ -0,0 +1,312 @@
+//! OpenTelemetry export of traces and metrics (requires the `otel` feature).
+//!
+//! Export is enabled by the standard OTLP environment variables, e.g.:
+//!
+//! ```sh
+//! OTEL_EXPORTER_OTLP_ENDPOINT=http://collector:4318 versatiles convert planet.pmtiles planet.versatiles
+//! ```
+//!
+//! All other standard variables are supported as well, e.g. `OTEL_SERVICE_NAME` (default: "versatiles"),
+//! `OTEL_RESOURCE_ATTRIBUTES`, `OTEL_EXPORTER_OTLP_HEADERS`, `OTEL_METRIC_EXPORT_INTERVAL` or `OTEL_SDK_DISABLED`.
+//! Data is sent as protobuf over HTTP.
+//!
+//! Exported are:
+//! - **traces**: the spans of pipeline operations, container reads and tile requests of the server
+//! - `versatiles.tiles.processed` and `versatiles.tiles.total`: tiles per zoom level (attribute `zoom`)
+//! - `versatiles.bytes.written`, `versatiles.workers.busy` and `versatiles.cache.hits`: see [`ConversionMetrics`]
+//!
+//! The conversion metrics are those of the whole process, i.e. the sums over all conversions, see [`ConversionMetrics::process`].
+//! - `versatiles.codec_pool.size`, `versatiles.codec_pool.queued`, `versatiles.codec_pool.running`,
+//!   `versatiles.codec_pool.completed` and `versatiles.codec_pool.busy`: see [`TileCodecPool`]
+//! - `versatiles.operation.duration`: a histogram of the durations of all spans (attribute `operation`)
+//! - `versatiles.operation.bytes`: bytes handled by spans that record a `bytes` field, e.g. served tiles
+
+use anyhow::Result;
+use opentelemetry::{
+	KeyValue,
+	metrics::{Counter, Histogram, Meter, MeterProvider as _},
+	trace::TracerProvider as _,
+};
+use opentelemetry_otlp::{MetricExporter, SpanExporter};
+use opentelemetry_sdk::{Resource, metrics::SdkMeterProvider, trace::SdkTracerProvider};
+use std::{env, time::Instant};
+use tracing::{
+	Subscriber,
+	field::{Field, Visit},
+	span::{Attributes, Id, Record},
+};
+use tracing_subscriber::{
+	Layer,
+	filter::LevelFilter,
+	layer::{Context, SubscriberExt as _},
+	registry::LookupSpan,
+	util::SubscriberInitExt as _,
+};
+use versatiles_container::TileCodecPool;
+use versatiles_core::progress::ConversionMetrics;
+
+/// Environment variables that configure an OTLP endpoint.
+const ENDPOINT_VARIABLES: [&str; 3] = [
+	"OTEL_EXPORTER_OTLP_ENDPOINT",
+	"OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+	"OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+];
+
+/// Running exporters. Call [`Telemetry::shutdown`] before exiting, to send the remaining data.
+pub struct Telemetry {
+	tracer_provider: SdkTracerProvider,
+	meter_provider: SdkMeterProvider,
+}
+
+impl Telemetry {
+	/// Starts the exporters and installs the tracing subscriber, if an OTLP endpoint is configured.
+	///
+	/// With `print_spans` (`--trace`), closed spans are also printed to stderr.
+	/// Returns `None` if no endpoint is configured or `OTEL_SDK_DISABLED=true`.
+	pub fn init(print_spans: bool) -> Result<Option<Telemetry>> {
+		if !is_enabled(|name| env::var(name).ok()) {
+			return Ok(None);
+		}
+
+		let mut resource = Resource::builder();
+		if env::var("OTEL_SERVICE_NAME").is_err() {
+			resource = resource.with_service_name("versatiles");
+		}
+		let resource = resource.build();
+
+		let tracer_provider = SdkTracerProvider::builder()
+			.with_batch_exporter(SpanExporter::builder().with_http().build()?)
+			.with_resource(resource.clone())
+			.build();
+		let meter_provider = SdkMeterProvider::builder()
+			.with_periodic_exporter(MetricExporter::builder().with_http().build()?)
+			.with_resource(resource)
+			.build();
+
+		let meter = meter_provider.meter("versatiles");
+		register_conversion_metrics(&meter);
+		register_codec_pool_metrics(&meter);
+
+		let print_layer = print_spans.then(|| {
+			tracing_subscriber::fmt::layer()
+				.with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+				.with_writer(std::io::stderr)
+		});
+		tracing_subscriber::registry()
+			.with(LevelFilter::DEBUG)
+			.with(tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("versatiles")))
+			.with(OperationLayer::new(&meter))
+			.with(print_layer)
+			.try_init()?;
+
+		log::debug!("exporting traces and metrics via OTLP");
+		Ok(Some(Telemetry {
+			tracer_provider,
+			meter_provider,
+		}))
+	}
+
+	/// Flushes and stops the exporters.
+	pub fn shutdown(self) {
+		if let Err(err) = self.tracer_provider.shutdown() {
+			log::warn!("failed to export traces: {err}");
+		}
+		if let Err(err) = self.meter_provider.shutdown() {
+			log::warn!("failed to export metrics: {err}");
+		}
+	}
+}
+
+/// Whether the environment configures an OTLP endpoint and does not disable the SDK.
+fn is_enabled(var: impl Fn(&str) -> Option<String>) -> bool {
+	if var("OTEL_SDK_DISABLED").is_some_and(|value| value.trim().eq_ignore_ascii_case("true")) {
+		return false;
+	}
+	ENDPOINT_VARIABLES
+		.iter()
+		.any(|name| var(name).is_some_and(|value| !value.trim().is_empty()))
+}
+
+/// Registers instruments that report the process-wide [`ConversionMetrics`] on every export.
+fn register_conversion_metrics(meter: &Meter) {
+	meter
+		.u64_observable_counter("versatiles.tiles.processed")
+		.with_description("Tiles processed per zoom level")
+		.with_unit("{tile}")
+		.with_callback(|observer| {
+			for level in ConversionMetrics::process().snapshot().levels {
+				observer.observe(level.done, &[KeyValue::new("zoom", i64::from(level.level))]);
+			}
+		})
+		.build();
+	meter
+		.u64_observable_gauge("versatiles.tiles.total")
+		.with_description("Tiles to process per zoom level")
+		.with_unit("{tile}")
+		.with_callback(|observer| {
+			for level in ConversionMetrics::process().snapshot().levels {
+				observer.observe(level.total, &[KeyValue::new("zoom", i64::from(level.level))]);
+			}
+		})
+		.build();
+	meter
+		.u64_observable_counter("versatiles.bytes.written")
+		.with_description("Bytes written to the outputs")
+		.with_unit("By")
+		.with_callback(|observer| observer.observe(ConversionMetrics::process().snapshot().bytes_written, &[]))
+		.build();
+	meter
+		.u64_observable_gauge("versatiles.workers.busy")
+		.with_description("Workers currently processing a tile")
+		.with_unit("{worker}")
+		.with_callback(|observer| observer.observe(ConversionMetrics::process().snapshot().workers_busy, &[]))
+		.build();
+	meter
+		.u64_observable_counter("versatiles.cache.hits")
+		.with_description("Tiles served from a cache instead of being processed again")
+		.with_unit("{tile}")
+		.with_callback(|observer| observer.observe(ConversionMetrics::process().snapshot().cache_hits, &[]))
+		.build();
+}
+
+/// Registers instruments that report the size and counters of the [`TileCodecPool`] on every export.
+fn register_codec_pool_metrics(meter: &Meter) {
+	meter
+		.u64_observable_gauge("versatiles.codec_pool.size")
+		.with_description("Maximum number of tiles decoded or encoded at the same time")
+		.with_unit("{job}")
+		.with_callback(|observer| observer.observe(TileCodecPool::stats().size as u64, &[]))
+		.build();
+	meter
+		.u64_observable_gauge("versatiles.codec_pool.queued")
+		.with_description("Tiles waiting to be decoded or encoded")
+		.with_unit("{job}")
+		.with_callback(|observer| observer.observe(TileCodecPool::stats().queued, &[]))
+		.build();
+	meter
+		.u64_observable_gauge("versatiles.codec_pool.running")
+		.with_description("Tiles being decoded or encoded")
+		.with_unit("{job}")
+		.with_callback(|observer| observer.observe(TileCodecPool::stats().running, &[]))
+		.build();
+	meter
+		.u64_observable_counter("versatiles.codec_pool.completed")
+		.with_description("Tiles decoded or encoded")
+		.with_unit("{job}")
+		.with_callback(|observer| observer.observe(TileCodecPool::stats().completed, &[]))
+		.build();
+	meter
+		.f64_observable_counter("versatiles.codec_pool.busy")
+		.with_description("Time spent decoding and encoding tiles")
+		.with_unit("s")
+		.with_callback(|observer| observer.observe(TileCodecPool::stats().busy.as_secs_f64(), &[]))
+		.build();
+}
+
+/// Start time of a span and the bytes it recorded.
+struct SpanTiming {
+	start: Instant,
+	bytes: u64,
+}
+
+/// Records the `bytes` field of a span.
+struct BytesVisitor<'a>(&'a mut u64);
+
+impl Visit for BytesVisitor<'_> {
+	fn record_u64(&mut self, field: &Field, value: u64) {
+		if field.name() == "bytes" {
+			*self.0 += value;
+		}
+	}
+
+	fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+/// Tracing layer that turns closed spans into duration and byte metrics per operation.
+struct OperationLayer {
+	duration: Histogram<f64>,
+	bytes: Counter<u64>,
+}
+
+impl OperationLayer {
+	fn new(meter: &Meter) -> Self {
+		OperationLayer {
+			duration: meter
+				.f64_histogram("versatiles.operation.duration")
+				.with_description("Duration of pipeline operations, container reads and tile requests")
+				.with_unit("s")
+				.build(),
+			bytes: meter
+				.u64_counter("versatiles.operation.bytes")
+				.with_description("Bytes handled by operations, e.g. served tiles")
+				.with_unit("By")
+				.build(),
+		}
+	}
+}
+
+impl<S> Layer<S> for OperationLayer
+where
+	S: Subscriber + for<'a> LookupSpan<'a>,
+{
+	fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+		if let Some(span) = ctx.span(id) {
+			let mut timing = SpanTiming {
+				start: Instant::now(),
+				bytes: 0,
+			};
+			attrs.record(&mut BytesVisitor(&mut timing.bytes));
+			span.extensions_mut().insert(timing);
+		}
+	}
+
+	fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+		if let Some(span) = ctx.span(id)
+			&& let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>()
+		{
+			values.record(&mut BytesVisitor(&mut timing.bytes));
+		}
+	}
+
+	fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+		let Some(span) = ctx.span(&id) else {
+			return;
+		};
+		let extensions = span.extensions();
+		let Some(timing) = extensions.get::<SpanTiming>() else {
+			return;
+		};
+		let attributes = [KeyValue::new("operation", span.name())];
+		self.duration.record(timing.start.elapsed().as_secs_f64(), &attributes);
+		if timing.bytes > 0 {
+			self.bytes.add(timing.bytes, &attributes);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashMap;
+
+	#[test]
+	fn enabled_by_environment() {
+		let check = |vars: &[(&str, &str)]| {
+			let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+			is_enabled(|name| vars.get(name).cloned())
+		};
+
+		assert!(!check(&[]));
+		assert!(!check(&[("OTEL_SERVICE_NAME", "tiles")]));
+		assert!(!check(&[("OTEL_EXPORTER_OTLP_ENDPOINT", " ")]));
+		assert!(check(&[("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318")]));
+		assert!(check(&[(
+			"OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+			"http://localhost:4318"
+		)]));
+		assert!(!check(&[
+			("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318"),
+			("OTEL_SDK_DISABLED", "TRUE")
+		]));
+	}
+}
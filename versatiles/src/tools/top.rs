@@ -0,0 +1,297 @@
+//! `versatiles top`: runs a conversion and shows its live state in an interactive terminal dashboard.
+//!
+//! Instead of a single progress bar, the dashboard shows the conversion stage, per-zoom progress,
+//! throughput, worker utilization, cache hits and an ETA. The values come from the
+//! [`ConversionMetrics`](versatiles_core::progress::ConversionMetrics) of the conversion's `ProcessingConfig`.
+
+use super::convert;
+use anyhow::Result;
+use log::LevelFilter;
+use ratatui::{
+	DefaultTerminal, Frame,
+	crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+	layout::{Constraint, Layout},
+	style::{Color, Style},
+	text::Line,
+	widgets::{Block, Gauge, Paragraph},
+};
+use std::{collections::VecDeque, time::Duration};
+use versatiles_container::ProcessingConfig;
+use versatiles_core::progress::{ConversionMetrics, MetricsSnapshot};
+
+/// Interval between two dashboard updates.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Number of snapshots used to compute throughput, i.e. a window of 5 seconds.
+const RATE_WINDOW: usize = 20;
+
+#[derive(clap::Args, Debug)]
+#[command(arg_required_else_help = true, disable_version_flag = true)]
+// the flattened convert arguments already use the group name "Subcommand"
+#[group(skip)]
+pub struct Subcommand {
+	#[command(flatten)]
+	convert: convert::Subcommand,
+}
+
+#[tokio::main]
+pub async fn run(arguments: &Subcommand) -> Result<()> {
+	let config = ProcessingConfig::default();
+
+	// log output and the progress bar would corrupt the dashboard
+	let log_level = log::max_level();
+	log::set_max_level(LevelFilter::Off);
+	ConversionMetrics::set_progress_bar_hidden(true);
+
+	let mut terminal = ratatui::init();
+	let result = run_dashboard(&mut terminal, arguments, config).await;
+	ratatui::restore();
+
+	ConversionMetrics::set_progress_bar_hidden(false);
+	log::set_max_level(log_level);
+
+	result?;
+	log::info!("finished converting tiles");
+	Ok(())
+}
+
+async fn run_dashboard(terminal: &mut DefaultTerminal, arguments: &Subcommand, config: ProcessingConfig) -> Result<()> {
+	let token = config.cancellation_token.clone();
+	let abort_token = config.abort_token.clone();
+	let mut receiver = config.metrics.subscribe(REFRESH_INTERVAL);
+	let conversion = convert::convert(arguments.convert.to_job()?, config);
+	tokio::pin!(conversion);
+
+	let mut history = VecDeque::with_capacity(RATE_WINDOW);
+
+	loop {
+		tokio::select! {
+			result = &mut conversion => return result,
+			changed = receiver.changed() => {
+				if changed.is_err() {
+					// the sampler is gone, keep converting without updates
+					return conversion.await;
+				}
+			}
+		}
+
+		if history.len() == RATE_WINDOW {
+			history.pop_front();
+		}
+		history.push_back(receiver.borrow_and_update().clone());
+		let footer = footer(token.is_cancelled(), abort_token.is_cancelled());
+		terminal.draw(|frame| render(frame, &history, footer))?;
+
+		while event::poll(Duration::ZERO)? {
+			let Event::Key(key) = event::read()? else {
+				continue;
+			};
+			if key.kind != KeyEventKind::Press {
+				continue;
+			}
+			let ctrl_c = key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c');
+			if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c {
+				if token.is_cancelled() {
+					// writing fails and the incomplete output is removed, so keep waiting for the conversion
+					abort_token.cancel();
+				} else {
+					// stop reading; writers then finish a valid container with the tiles read so far
+					token.cancel();
+				}
+			}
+		}
+	}
+}
+
+fn render(frame: &mut Frame, history: &VecDeque<MetricsSnapshot>, footer: &str) {
+	let (Some(first), Some(last)) = (history.front(), history.back()) else {
+		return;
+	};
+
+	let [summary_area, levels_area, footer_area] =
+		Layout::vertical([Constraint::Length(7), Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+
+	let (tiles_per_sec, bytes_per_sec) = last.rates_since(first);
+	let done = last.tiles_done();
+	let total = last.tiles_total();
+	let eta = if tiles_per_sec > 0.0 {
+		format_duration(Duration::from_secs_f64(
+			total.saturating_sub(done) as f64 / tiles_per_sec,
+		))
+	} else {
+		String::from("-")
+	};
+
+	let stage = match last.stages.current_stage() {
+		Some(stage) => format!(
+			"{}/{} {}, total {:.1}%",
+			last.stages.current.unwrap_or_default() + 1,
+			last.stages.stages.len(),
+			stage.name,
+			last.stages.fraction() * 100.0
+		),
+		None => String::from("-"),
+	};
+
+	let summary = vec![
+		Line::from(format!("stage:      {stage}")),
+		Line::from(format!("tiles:      {done} / {total} ({:.1}%)", percent(done, total))),
+		Line::from(format!(
+			"throughput: {:.0} tiles/s, {}/s",
+			tiles_per_sec,
+			format_bytes(bytes_per_sec)
+		)),
+		Line::from(format!(
+			"workers:    {} / {} busy, cache hits: {}",
+			last.workers_busy, last.workers_total, last.cache_hits
+		)),
+		Line::from(format!(
+			"written:    {}, ETA: {eta}",
+			format_bytes(last.bytes_written as f64)
+		)),
+	];
+	frame.render_widget(
+		Paragraph::new(summary).block(Block::bordered().title(" versatiles top ")),
+		summary_area,
+	);
+
+	let levels_block = Block::bordered().title(" zoom levels ");
+	let inner = levels_block.inner(levels_area);
+	frame.render_widget(levels_block, levels_area);
+	let rows = Layout::vertical(vec![Constraint::Length(1); last.levels.len()]).split(inner);
+	for (level, row) in last.levels.iter().zip(rows.iter()) {
+		let ratio = if level.total > 0 {
+			(level.done as f64 / level.total as f64).min(1.0)
+		} else {
+			0.0
+		};
+		let gauge = Gauge::default()
+			.gauge_style(Style::default().fg(Color::Green))
+			.ratio(ratio)
+			.label(format!("z{:<2} {} / {}", level.level, level.done, level.total));
+		frame.render_widget(gauge, *row);
+	}
+
+	frame.render_widget(Paragraph::new(footer), footer_area);
+}
+
+fn footer(cancelled: bool, aborted: bool) -> &'static str {
+	if aborted {
+		"aborting, removing incomplete output …"
+	} else if cancelled {
+		"stopping, flushing tiles read so far … press q again to abort"
+	} else {
+		"press q to stop the conversion"
+	}
+}
+
+fn percent(done: u64, total: u64) -> f64 {
+	if total == 0 {
+		0.0
+	} else {
+		done as f64 * 100.0 / total as f64
+	}
+}
+
+fn format_bytes(bytes: f64) -> String {
+	const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+	let mut value = bytes;
+	let mut unit = 0;
+	while value >= 1000.0 && unit < UNITS.len() - 1 {
+		value /= 1000.0;
+		unit += 1;
+	}
+	format!("{value:.1} {}", UNITS[unit])
+}
+
+fn format_duration(duration: Duration) -> String {
+	let secs = duration.as_secs();
+	format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ratatui::{Terminal, backend::TestBackend};
+	use std::time::Instant;
+	use versatiles_core::progress::{LevelProgress, StageProgress, StagesSnapshot, TileTranscodeStats};
+
+	#[test]
+	fn formatting() {
+		assert_eq!(format_bytes(999.0), "999.0 B");
+		assert_eq!(format_bytes(1_500_000.0), "1.5 MB");
+		assert_eq!(format_duration(Duration::from_secs(3725)), "1:02:05");
+		assert_eq!(percent(1, 4), 25.0);
+		assert_eq!(percent(1, 0), 0.0);
+	}
+
+	#[test]
+	fn render_dashboard() -> Result<()> {
+		let start = Instant::now();
+		let snapshot = |done: u64, secs: u64| MetricsSnapshot {
+			time: start + Duration::from_secs(secs),
+			levels: vec![
+				LevelProgress {
+					level: 3,
+					done: 64,
+					total: 64,
+				},
+				LevelProgress {
+					level: 4,
+					done,
+					total: 256,
+				},
+			],
+			bytes_written: done * 1000,
+			workers_busy: 2,
+			workers_total: 8,
+			cache_hits: 5,
+			transcode: TileTranscodeStats::default(),
+			stages: StagesSnapshot {
+				stages: vec![
+					StageProgress {
+						name: String::from("scan"),
+						weight: 1,
+						done: 0,
+						total: 0,
+						finished: true,
+					},
+					StageProgress {
+						name: String::from("convert"),
+						weight: 3,
+						done,
+						total: 256,
+						finished: false,
+					},
+				],
+				current: Some(1),
+			},
+		};
+		let history = VecDeque::from([snapshot(0, 0), snapshot(128, 2)]);
+
+		let mut terminal = Terminal::new(TestBackend::new(80, 15))?;
+		terminal.draw(|frame| render(frame, &history, footer(false, false)))?;
+
+		let text = terminal
+			.backend()
+			.buffer()
+			.content()
+			.iter()
+			.map(|cell| cell.symbol())
+			.collect::<String>();
+		assert!(text.contains("stage:      2/2 convert, total 62.5%"), "{text}");
+		assert!(text.contains("192 / 320"), "{text}");
+		assert!(text.contains("64 tiles/s"), "{text}");
+		assert!(text.contains("2 / 8 busy, cache hits: 5"), "{text}");
+		assert!(text.contains("z4  128 / 256"), "{text}");
+		assert!(text.contains("ETA: 0:00:02"), "{text}");
+		assert!(text.contains("press q to stop the conversion"), "{text}");
+		Ok(())
+	}
+
+	#[test]
+	fn footer_follows_cancellation() {
+		assert!(footer(true, false).starts_with("stopping"));
+		assert!(footer(true, true).starts_with("aborting"));
+	}
+}
@@ -0,0 +1,197 @@
+use anyhow::{Result, bail, ensure};
+use std::hash::{DefaultHasher, Hasher};
+use versatiles::get_registry;
+use versatiles_container::{ProcessingConfig, Tile, TilesReaderTrait};
+use versatiles_core::{TileBBox, TileBBoxMap, TileCompression, TileCoord, Traversal};
+
+#[derive(clap::Args, Debug)]
+#[command(arg_required_else_help = true, disable_version_flag = true)]
+pub struct Subcommand {
+	/// first tile container
+	#[arg(required = true)]
+	file_a: String,
+
+	/// second tile container
+	#[arg(required = true)]
+	file_b: String,
+
+	/// maximum number of mismatching tile coordinates to print
+	#[arg(long, value_name = "int", default_value_t = 20)]
+	max_report: usize,
+}
+
+#[derive(Debug, PartialEq)]
+enum Mismatch {
+	Different,
+	OnlyInA,
+	OnlyInB,
+}
+
+#[derive(Debug, Default)]
+struct Summary {
+	identical: u64,
+	mismatches: Vec<(TileCoord, Mismatch)>,
+}
+
+#[tokio::main]
+pub async fn run(arguments: &Subcommand) -> Result<()> {
+	log::info!("verify {:?} against {:?}", arguments.file_a, arguments.file_b);
+
+	let registry = get_registry(ProcessingConfig::default());
+	let reader_a = registry.open_reader(&arguments.file_a).await?;
+	let reader_b = registry.open_reader(&arguments.file_b).await?;
+
+	let summary = compare_readers(reader_a.as_ref(), reader_b.as_ref()).await?;
+
+	let mut mismatches = summary.mismatches;
+	mismatches.sort_by_key(|(coord, _)| coord.get_sort_index());
+	for (coord, mismatch) in mismatches.iter().take(arguments.max_report) {
+		let description = match mismatch {
+			Mismatch::Different => "tile content differs",
+			Mismatch::OnlyInA => "tile only in first container",
+			Mismatch::OnlyInB => "tile only in second container",
+		};
+		println!("{}/{}/{}: {description}", coord.level, coord.x, coord.y);
+	}
+	if mismatches.len() > arguments.max_report {
+		println!("… and {} more", mismatches.len() - arguments.max_report);
+	}
+
+	let count = |kind: Mismatch| mismatches.iter().filter(|(_, m)| *m == kind).count();
+	println!(
+		"identical: {}, different: {}, only in first: {}, only in second: {}",
+		summary.identical,
+		count(Mismatch::Different),
+		count(Mismatch::OnlyInA),
+		count(Mismatch::OnlyInB)
+	);
+
+	if !mismatches.is_empty() {
+		bail!("containers differ in {} tiles", mismatches.len());
+	}
+	Ok(())
+}
+
+/// Maximum width and height of the blocks that are compared at once.
+const BLOCK_SIZE: u32 = 256;
+
+/// Compares the uncompressed tiles of both readers, block by block.
+///
+/// The blocks follow the traversals of both readers, so only the checksums of one block are held in memory.
+async fn compare_readers(reader_a: &dyn TilesReaderTrait, reader_b: &dyn TilesReaderTrait) -> Result<Summary> {
+	let parameters_a = reader_a.parameters();
+	let parameters_b = reader_b.parameters();
+	ensure!(
+		parameters_a.tile_format == parameters_b.tile_format,
+		"cannot compare tiles of format {:?} with tiles of format {:?}",
+		parameters_a.tile_format,
+		parameters_b.tile_format
+	);
+
+	let mut pyramid = parameters_a.bbox_pyramid.clone();
+	pyramid.include_bbox_pyramid(&parameters_b.bbox_pyramid);
+
+	let traversal = Traversal::intersect_all([
+		(format!("'{}'", reader_a.source_name()), reader_a.traversal()),
+		(format!("'{}'", reader_b.source_name()), reader_b.traversal()),
+	])?;
+	let min_size = traversal.min_size()?;
+	let max_size = traversal.max_size()?.min(BLOCK_SIZE).max(min_size);
+	let traversal = Traversal::new(*traversal.order(), min_size, max_size)?;
+
+	let mut summary = Summary::default();
+	for bbox in traversal.traverse_pyramid(&pyramid)? {
+		log::debug!("comparing block {bbox:?}");
+
+		let mut checksums_a = get_checksums(reader_a, bbox).await?;
+		for (coord, checksum_b) in get_checksums(reader_b, bbox).await? {
+			let Some(checksum_b) = checksum_b else {
+				continue;
+			};
+			match checksums_a.get_mut(&coord)?.take() {
+				Some(checksum_a) if checksum_a == checksum_b => summary.identical += 1,
+				Some(_) => summary.mismatches.push((coord, Mismatch::Different)),
+				None => summary.mismatches.push((coord, Mismatch::OnlyInB)),
+			}
+		}
+		summary.mismatches.extend(
+			checksums_a
+				.into_iter()
+				.filter_map(|(coord, checksum)| checksum.map(|_| (coord, Mismatch::OnlyInA))),
+		);
+	}
+	Ok(summary)
+}
+
+/// Reads all tiles within `bbox` and returns a checksum of every uncompressed tile.
+async fn get_checksums(reader: &dyn TilesReaderTrait, bbox: TileBBox) -> Result<TileBBoxMap<Option<u64>>> {
+	let mut checksums = TileBBoxMap::new_default(bbox);
+	let mut error = None;
+	reader
+		.get_tile_stream(bbox)
+		.await?
+		.map_item_parallel(|tile| Ok(checksum(tile)))
+		.for_each_sync(|(coord, result)| {
+			let result = result.and_then(|checksum| {
+				*checksums.get_mut(&coord)? = Some(checksum);
+				Ok(())
+			});
+			if let Err(err) = result {
+				error.get_or_insert(err.context(format!("reading tile {coord:?} of '{}'", reader.source_name())));
+			}
+		})
+		.await;
+	match error {
+		Some(err) => Err(err),
+		None => Ok(checksums),
+	}
+}
+
+fn checksum(tile: Tile) -> Result<u64> {
+	let blob = tile.into_blob(TileCompression::Uncompressed)?;
+	let mut hasher = DefaultHasher::new();
+	hasher.write(blob.as_slice());
+	Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tests::run_command;
+	use anyhow::Result;
+	use assert_fs::TempDir;
+
+	#[test]
+	fn test_verify() -> Result<()> {
+		let dir = TempDir::new()?;
+		let brotli = dir.path().join("berlin.versatiles");
+		let brotli = brotli.to_str().unwrap();
+		let cropped = dir.path().join("cropped.versatiles");
+		let cropped = cropped.to_str().unwrap();
+
+		run_command(vec![
+			"versatiles",
+			"convert",
+			"-q",
+			"--compress",
+			"brotli",
+			"../testdata/berlin.mbtiles",
+			brotli,
+		])?;
+		run_command(vec!["versatiles", "verify", "-q", "../testdata/berlin.mbtiles", brotli])?;
+
+		run_command(vec![
+			"versatiles",
+			"convert",
+			"-q",
+			"--max-zoom",
+			"10",
+			"../testdata/berlin.mbtiles",
+			cropped,
+		])?;
+		let error = run_command(vec!["versatiles", "verify", "-q", brotli, cropped])
+			.unwrap_err()
+			.to_string();
+		assert!(error.starts_with("containers differ in "), "{error}");
+		Ok(())
+	}
+}
@@ -11,12 +11,12 @@ fn command() -> Result<(), Box<dyn std::error::Error>> {
 		.failure()
 		.code(2)
 		.stdout(str::is_empty())
-		.stderr(str::contains(format!("Usage: {BINARY_NAME} [OPTIONS] <COMMAND>")));
+		.stderr(str::contains(format!("Usage: {BINARY_NAME} [OPTIONS] [COMMAND]")));
 	Ok(())
 }
 
 #[rstest]
-#[case("convert", "[OPTIONS] <INPUT_FILE> <OUTPUT_FILE>")]
+#[case("convert", "[OPTIONS] [INPUT_FILE] [OUTPUT_FILE]")]
 #[case("dev export-outline", "[OPTIONS] <INPUT_FILE> <OUTPUT_FILE>")]
 #[case("dev measure-tile-sizes", "[OPTIONS] <INPUT_FILE> <OUTPUT_FILE> [LEVEL] [SCALE]")]
 #[case("dev print-tilejson", "[OPTIONS] <INPUT_FILE>")]
@@ -53,7 +53,7 @@ fn convert_pmtiles_to_mbtiles_with_bbox_and_border() {
 	assert!(output.exists(), "output file was not created: {:?}", output);
 	assert_eq!(
 		get_metadata(&output),
-		"{author:OpenStreetMap contributors, Geofabrik GmbH,bounds:[13.07373,52.321911,13.776855,52.683043],description:Tile config for simple vector tiles schema,license:Open Database License 1.0,maxzoom:14,minzoom:0,name:Tilemaker to Geofabrik Vector Tiles schema,tilejson:3.0.0,type:baselayer,vector_layers:[{fields:{name:String,number:String},id:addresses,maxzoom:14,minzoom:14},{fields:{kind:String},id:aerialways,maxzoom:14,minzoom:12},{fields:{admin_level:Number,maritime:Boolean},id:boundaries,maxzoom:14,minzoom:0},{fields:{admin_level:String,name:String,name_de:String,name_en:String,way_area:Number},id:boundary_labels,maxzoom:14,minzoom:2},{fields:{dummy:Number},id:buildings,maxzoom:14,minzoom:14},{fields:{kind:String},id:land,maxzoom:14,minzoom:7},{fields:{},id:ocean,maxzoom:14,minzoom:8},{fields:{kind:String,name:String,name_de:String,name_en:String,population:Number},id:place_labels,maxzoom:14,minzoom:3},{fields:{kind:String,name:String,name_de:String,name_en:String},id:public_transport,maxzoom:14,minzoom:11},{fields:{kind:String},id:sites,maxzoom:14,minzoom:14},{fields:{kind:String,name:String,name_de:String,name_en:String,ref:String,ref_cols:Number,ref_rows:Number,tunnel:Boolean},id:street_labels,maxzoom:14,minzoom:10},{fields:{kind:String,name:String,name_de:String,name_en:String,ref:String},id:street_labels_points,maxzoom:14,minzoom:12},{fields:{bridge:Boolean,kind:String,rail:Boolean,service:String,surface:String,tunnel:Boolean},id:street_polygons,maxzoom:14,minzoom:14},{fields:{bicycle:String,bridge:Boolean,horse:String,kind:String,link:Boolean,rail:Boolean,service:String,surface:String,tracktype:String,tunnel:Boolean},id:streets,maxzoom:14,minzoom:14},{fields:{kind:String,name:String,name_de:String,name_en:String},id:streets_polygons_labels,maxzoom:14,minzoom:14},{fields:{kind:String},id:water_lines,maxzoom:14,minzoom:4},{fields:{kind:String,name:String,name_de:String,name_en:String},id:water_lines_labels,maxzoom:14,minzoom:4},{fields:{kind:String},id:water_polygons,maxzoom:14,minzoom:4},{fields:{kind:String,name:String,name_de:String,name_en:String},id:water_polygons_labels,maxzoom:14,minzoom:14}],version:3.0}"
+		"{author:OpenStreetMap contributors, Geofabrik GmbH,bounds:[13.07373,52.321911,13.776855,52.683043],center:[13.425293,52.502477,2],description:Tile config for simple vector tiles schema,license:Open Database License 1.0,maxzoom:14,minzoom:0,name:Tilemaker to Geofabrik Vector Tiles schema,tilejson:3.0.0,type:baselayer,vector_layers:[{fields:{name:String,number:String},id:addresses,maxzoom:14,minzoom:14},{fields:{kind:String},id:aerialways,maxzoom:14,minzoom:12},{fields:{admin_level:Number,maritime:Boolean},id:boundaries,maxzoom:14,minzoom:0},{fields:{admin_level:String,name:String,name_de:String,name_en:String,way_area:Number},id:boundary_labels,maxzoom:14,minzoom:2},{fields:{dummy:Number},id:buildings,maxzoom:14,minzoom:14},{fields:{kind:String},id:land,maxzoom:14,minzoom:7},{fields:{},id:ocean,maxzoom:14,minzoom:8},{fields:{kind:String,name:String,name_de:String,name_en:String,population:Number},id:place_labels,maxzoom:14,minzoom:3},{fields:{kind:String,name:String,name_de:String,name_en:String},id:public_transport,maxzoom:14,minzoom:11},{fields:{kind:String},id:sites,maxzoom:14,minzoom:14},{fields:{kind:String,name:String,name_de:String,name_en:String,ref:String,ref_cols:Number,ref_rows:Number,tunnel:Boolean},id:street_labels,maxzoom:14,minzoom:10},{fields:{kind:String,name:String,name_de:String,name_en:String,ref:String},id:street_labels_points,maxzoom:14,minzoom:12},{fields:{bridge:Boolean,kind:String,rail:Boolean,service:String,surface:String,tunnel:Boolean},id:street_polygons,maxzoom:14,minzoom:14},{fields:{bicycle:String,bridge:Boolean,horse:String,kind:String,link:Boolean,rail:Boolean,service:String,surface:String,tracktype:String,tunnel:Boolean},id:streets,maxzoom:14,minzoom:14},{fields:{kind:String,name:String,name_de:String,name_en:String},id:streets_polygons_labels,maxzoom:14,minzoom:14},{fields:{kind:String},id:water_lines,maxzoom:14,minzoom:4},{fields:{kind:String,name:String,name_de:String,name_en:String},id:water_lines_labels,maxzoom:14,minzoom:4},{fields:{kind:String},id:water_polygons,maxzoom:14,minzoom:4},{fields:{kind:String,name:String,name_de:String,name_en:String},id:water_polygons_labels,maxzoom:14,minzoom:14}],version:3.0}"
 	);
 
 	drop(temp_dir); // clean up
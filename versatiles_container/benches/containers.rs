@@ -0,0 +1,53 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use tokio::runtime::Runtime;
+use versatiles_container::{
+	BENCHMARK_CONTAINER_TYPES, ContainerRegistry, DataLocation, MockTilesReaderProfile, TilesReaderTrait,
+	measure_stream_throughput, sample_coords, write_benchmark_fixture,
+};
+
+const MAX_ZOOM_LEVEL: u8 = 6;
+const SAMPLES: usize = 256;
+
+fn benchmark_containers(c: &mut Criterion) {
+	let runtime = Runtime::new().unwrap();
+	let dir = tempfile::tempdir().unwrap();
+
+	let mut group = c.benchmark_group("containers");
+	for container_type in BENCHMARK_CONTAINER_TYPES {
+		let reader: Box<dyn TilesReaderTrait> = runtime.block_on(async {
+			let path = write_benchmark_fixture(dir.path(), container_type, MockTilesReaderProfile::Pbf, MAX_ZOOM_LEVEL)
+				.await
+				.unwrap();
+			ContainerRegistry::default()
+				.get_reader(DataLocation::from(path.as_path()))
+				.await
+				.unwrap()
+		});
+		let coords = sample_coords(&reader.parameters().bbox_pyramid, SAMPLES);
+
+		group.throughput(Throughput::Elements(coords.len() as u64));
+		group.bench_with_input(BenchmarkId::new("get_tile", container_type), &coords, |b, coords| {
+			b.iter(|| {
+				runtime.block_on(async {
+					for coord in coords {
+						black_box(reader.get_tile(coord).await.unwrap());
+					}
+				})
+			});
+		});
+
+		group.throughput(Throughput::Elements(reader.parameters().bbox_pyramid.count_tiles()));
+		group.bench_function(BenchmarkId::new("stream", container_type), |b| {
+			b.iter(|| runtime.block_on(measure_stream_throughput(reader.as_ref())).unwrap());
+		});
+	}
+	group.finish();
+}
+
+criterion_group!(
+	name = benches;
+	config = Criterion::default().significance_level(0.1).sample_size(20);
+	targets = benchmark_containers
+);
+criterion_main!(benches);
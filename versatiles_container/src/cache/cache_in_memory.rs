@@ -9,7 +9,9 @@
 //! environments where caching to disk would be unnecessarily slow.
 //!
 //! All operations are synchronous and thread-safe only through external synchronization.
+//! The estimated size of all stored values is reserved in the global [`MemoryBudget`].
 use anyhow::Result;
+use versatiles_core::{MemoryBudget, MemoryReservation};
 use versatiles_derive::context;
 
 use super::traits::{Cache, CacheKey, CacheValue};
@@ -24,6 +26,7 @@ use std::{collections::HashMap, fmt::Debug, marker::PhantomData};
 /// is `InMemory`.
 pub struct InMemoryCache<K: CacheKey, V: CacheValue> {
 	data: HashMap<String, Vec<V>>,
+	reservation: MemoryReservation<'static>,
 	_marker_k: PhantomData<K>,
 }
 
@@ -36,9 +39,21 @@ impl<K: CacheKey, V: CacheValue> InMemoryCache<K, V> {
 	pub fn new() -> Self {
 		Self {
 			data: HashMap::new(),
+			reservation: MemoryBudget::global().reserve(0),
 			_marker_k: PhantomData,
 		}
 	}
+
+	/// Adds the estimated size of `values` to the reservation, or subtracts it if `add` is `false`.
+	fn account(&mut self, values: &[V], add: bool) {
+		let size: usize = values.iter().map(CacheValue::memory_size).sum();
+		let reserved = self.reservation.bytes() as usize;
+		self.reservation.resize(if add {
+			reserved + size
+		} else {
+			reserved.saturating_sub(size)
+		});
+	}
 }
 
 /// Implementation of the generic [`Cache`](crate::cache::traits::cache::Cache) trait
@@ -58,17 +73,25 @@ impl<K: CacheKey, V: CacheValue> Cache<K, V> for InMemoryCache<K, V> {
 
 	#[context("removing entry for key '{}'", key.to_cache_key())]
 	fn remove(&mut self, key: &K) -> Result<Option<Vec<V>>> {
-		Ok(self.data.remove(&key.to_cache_key()))
+		let values = self.data.remove(&key.to_cache_key());
+		if let Some(values) = &values {
+			self.account(values, false);
+		}
+		Ok(values)
 	}
 
 	#[context("inserting values for key '{}'",  key.to_cache_key())]
 	fn insert(&mut self, key: &K, values: Vec<V>) -> Result<()> {
-		self.data.insert(key.to_cache_key(), values);
+		self.account(&values, true);
+		if let Some(old) = self.data.insert(key.to_cache_key(), values) {
+			self.account(&old, false);
+		}
 		Ok(())
 	}
 
 	#[context("appending values for key '{}'",  key.to_cache_key())]
 	fn append(&mut self, key: &K, values: Vec<V>) -> Result<()> {
+		self.account(&values, true);
 		self.data.entry(key.to_cache_key()).or_default().extend(values);
 		Ok(())
 	}
@@ -78,6 +101,7 @@ impl<K: CacheKey, V: CacheValue> Cache<K, V> for InMemoryCache<K, V> {
 	/// Since this cache is memory-based, cleanup simply empties the internal map.
 	fn clean_up(&mut self) {
 		self.data.clear();
+		self.reservation.resize(0);
 	}
 }
 
@@ -170,4 +194,25 @@ mod tests {
 		assert_eq!(cache.get_clone(&k)?, Some(v(&["a", "b", "c"])));
 		Ok(())
 	}
+
+	#[test]
+	fn reserves_memory_of_values() -> Result<()> {
+		let mut cache: InMemoryCache<String, String> = InMemoryCache::new();
+		let size = |s: &str| size_of::<String>() + s.len();
+		let k = "k".to_string();
+
+		cache.insert(&k, v(&["abc"]))?;
+		assert_eq!(cache.reservation.bytes() as usize, size("abc"));
+		cache.append(&k, v(&["de"]))?;
+		assert_eq!(cache.reservation.bytes() as usize, size("abc") + size("de"));
+		cache.insert(&k, v(&["f"]))?;
+		assert_eq!(cache.reservation.bytes() as usize, size("f"));
+		cache.remove(&k)?;
+		assert_eq!(cache.reservation.bytes(), 0);
+
+		cache.insert(&k, v(&["xyz"]))?;
+		cache.clean_up();
+		assert_eq!(cache.reservation.bytes(), 0);
+		Ok(())
+	}
 }
@@ -54,6 +54,14 @@ pub trait CacheValue: Clone {
 	/// # Errors
 	/// Returns an error if reading or decoding fails.
 	fn read_from_cache(reader: &mut Cursor<&[u8]>) -> Result<Self>;
+
+	/// Estimates the number of bytes this value occupies in memory, used for the
+	/// [`MemoryBudget`](versatiles_core::MemoryBudget) of in-memory caches.
+	///
+	/// Defaults to the stack size of the type; types owning heap data should add it.
+	fn memory_size(&self) -> usize {
+		size_of_val(self)
+	}
 }
 
 /// Implements binary serialization for `u8`.
@@ -84,6 +92,19 @@ impl CacheValue for u32 {
 	}
 }
 
+/// Implements binary serialization for `u64` using little-endian encoding.
+impl CacheValue for u64 {
+	fn write_to_cache(&self, writer: &mut Vec<u8>) -> Result<()> {
+		writer.write_u64::<LE>(*self)?;
+		Ok(())
+	}
+
+	fn read_from_cache(reader: &mut Cursor<&[u8]>) -> Result<Self> {
+		let value = reader.read_u64::<LE>()?;
+		Ok(value)
+	}
+}
+
 /// Implements binary serialization for UTF-8 `String`s.
 ///
 /// Format:
@@ -105,6 +126,10 @@ impl CacheValue for String {
 		reader.read_exact(&mut bytes)?;
 		String::from_utf8(bytes).map_err(|e| anyhow!(e))
 	}
+
+	fn memory_size(&self) -> usize {
+		size_of::<Self>() + self.len()
+	}
 }
 
 /// Implements serialization for homogeneous vectors.
@@ -129,6 +154,10 @@ impl<T: CacheValue> CacheValue for Vec<T> {
 		}
 		Ok(vec)
 	}
+
+	fn memory_size(&self) -> usize {
+		size_of::<Self>() + self.iter().map(CacheValue::memory_size).sum::<usize>()
+	}
 }
 
 /// Implements serialization for pairs `(A, B)`, storing elements sequentially.
@@ -143,6 +172,10 @@ impl<A: CacheValue, B: CacheValue> CacheValue for (A, B) {
 		let b = B::read_from_cache(reader)?;
 		Ok((a, b))
 	}
+
+	fn memory_size(&self) -> usize {
+		self.0.memory_size() + self.1.memory_size()
+	}
 }
 
 /// Implements serialization for [`TileCoord`](versatiles_core::TileCoord).
@@ -185,6 +218,10 @@ impl CacheValue for Blob {
 		reader.read_exact(&mut bytes)?;
 		Ok(Blob::from(bytes))
 	}
+
+	fn memory_size(&self) -> usize {
+		size_of::<Self>() + self.len() as usize
+	}
 }
 
 /// Implements serialization for [`TileFormat`](versatiles_core::TileFormat)
@@ -244,6 +281,10 @@ impl<V: CacheValue> CacheValue for Option<V> {
 			bail!("Invalid flag value: {flag}")
 		}
 	}
+
+	fn memory_size(&self) -> usize {
+		self.as_ref().map_or(size_of::<Self>(), CacheValue::memory_size)
+	}
 }
 
 /// Implements serialization for [`DynamicImage`](versatiles_image::DynamicImage).
@@ -295,6 +336,10 @@ impl CacheValue for DynamicImage {
 			_ => bail!("Unsupported channel count: {channel_count}"),
 		})
 	}
+
+	fn memory_size(&self) -> usize {
+		size_of::<Self>() + self.as_bytes().len()
+	}
 }
 
 #[cfg(test)]
@@ -467,4 +512,16 @@ mod tests {
 		let img = make_image_dynamic(kind);
 		roundtrip::<DynamicImage>(img);
 	}
+
+	#[test]
+	fn memory_size_counts_heap_data() {
+		let blob = Blob::from(vec![0u8; 1000]);
+		assert_eq!(blob.memory_size(), size_of::<Blob>() + 1000);
+		assert_eq!(Some(blob.clone()).memory_size(), blob.memory_size());
+		assert_eq!(
+			vec![(TileCoord::new(1, 0, 0).unwrap(), blob.clone())].memory_size(),
+			size_of::<Vec<(TileCoord, Blob)>>() + size_of::<TileCoord>() + blob.memory_size()
+		);
+		assert_eq!(make_image_dynamic("rgba").memory_size(), size_of::<DynamicImage>() + 16);
+	}
 }
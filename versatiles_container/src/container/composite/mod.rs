@@ -0,0 +1,9 @@
+//! Combine multiple containers into one
+//!
+//! This module provides `CompositeReader`, which overlays several tile readers with a priority order
+//! and exposes them as a single `TilesReaderTrait`. For every coordinate the tile of the first reader
+//! that has one is returned, so simple fallback setups (e.g. updates over a base map) need no pipeline.
+
+mod reader;
+
+pub use reader::CompositeReader;
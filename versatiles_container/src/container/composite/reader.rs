@@ -0,0 +1,296 @@
+//! This module provides a reader that overlays multiple tile readers.
+//!
+//! Readers are queried in the given order; the first reader that returns a tile for a coordinate wins.
+//! All readers must provide the same tile format. Tiles are returned in the compression of the first reader.
+//!
+//! ## Usage
+//! ```rust
+//! use versatiles_container::*;
+//! use versatiles_core::*;
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let registry = ContainerRegistry::default();
+//!     let updates = registry.open_reader("../testdata/berlin.pmtiles").await?;
+//!     let base = registry.open_reader("../testdata/berlin.mbtiles").await?;
+//!
+//!     let reader = CompositeReader::new(vec![updates, base])?;
+//!     let tile = reader.get_tile(&TileCoord::new(0, 0, 0)?).await?;
+//!     assert!(tile.is_some());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Tile, TilesReaderTrait};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use futures::{StreamExt, TryStreamExt, stream};
+use std::fmt::Debug;
+use versatiles_core::*;
+use versatiles_derive::context;
+
+/// A reader that returns, for every coordinate, the tile of the first reader that provides one.
+pub struct CompositeReader {
+	name: String,
+	parameters: TilesReaderParameters,
+	readers: Vec<Box<dyn TilesReaderTrait>>,
+	tilejson: TileJSON,
+	traversal: Traversal,
+}
+
+impl CompositeReader {
+	/// Creates a composite of `readers`, ordered from highest to lowest priority.
+	///
+	/// # Errors
+	/// Returns an error if `readers` is empty or the readers have different tile formats.
+	#[context("creating composite reader")]
+	pub fn new(readers: Vec<Box<dyn TilesReaderTrait>>) -> Result<CompositeReader> {
+		ensure!(!readers.is_empty(), "composite reader needs at least one reader");
+
+		let first = readers[0].parameters();
+		let tile_format = first.tile_format;
+		let tile_compression = first.tile_compression;
+
+		let mut pyramid = TileBBoxPyramid::new_empty();
+		let mut tilejson = TileJSON::default();
+
+		// merge in reverse order, so that metadata of readers with higher priority wins
+		for reader in readers.iter().rev() {
+			let parameters = reader.parameters();
+			ensure!(
+				parameters.tile_format == tile_format,
+				"all readers must have the same tile format, but '{}' has {:?} instead of {:?}",
+				reader.source_name(),
+				parameters.tile_format,
+				tile_format
+			);
+			pyramid.include_bbox_pyramid(&parameters.bbox_pyramid);
+			tilejson.merge(reader.tilejson())?;
+		}
+
+		let traversal = Traversal::intersect_all(
+			readers
+				.iter()
+				.map(|reader| (format!("'{}'", reader.source_name()), reader.traversal())),
+		)?;
+
+		let parameters = TilesReaderParameters::new(tile_format, tile_compression, pyramid);
+		tilejson.update_from_reader_parameters(&parameters);
+
+		let name = readers
+			.iter()
+			.map(|reader| reader.source_name())
+			.collect::<Vec<_>>()
+			.join(",");
+
+		Ok(CompositeReader {
+			name,
+			parameters,
+			readers,
+			tilejson,
+			traversal,
+		})
+	}
+}
+
+#[async_trait]
+impl TilesReaderTrait for CompositeReader {
+	fn source_name(&self) -> &str {
+		&self.name
+	}
+
+	fn container_name(&self) -> &str {
+		"composite"
+	}
+
+	fn parameters(&self) -> &TilesReaderParameters {
+		&self.parameters
+	}
+
+	fn override_compression(&mut self, tile_compression: TileCompression) {
+		self.parameters.tile_compression = tile_compression;
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		&self.tilejson
+	}
+
+	fn traversal(&self) -> &Traversal {
+		&self.traversal
+	}
+
+	#[context("getting tile {:?} from composite reader", coord)]
+	async fn get_tile(&self, coord: &TileCoord) -> Result<Option<Tile>> {
+		for reader in &self.readers {
+			if !reader.parameters().bbox_pyramid.contains_coord(coord) {
+				continue;
+			}
+			if let Some(mut tile) = reader.get_tile(coord).await? {
+				tile.change_compression(self.parameters.tile_compression)?;
+				return Ok(Some(tile));
+			}
+		}
+		Ok(None)
+	}
+
+	#[context("prefetching {:?} in composite reader", bbox)]
+	async fn prefetch(&self, bbox: &TileBBox) -> Result<()> {
+		for reader in &self.readers {
+			let mut bbox = *bbox;
+			bbox.intersect_with_pyramid(&reader.parameters().bbox_pyramid);
+			if !bbox.is_empty() {
+				reader.prefetch(&bbox).await?;
+			}
+		}
+		Ok(())
+	}
+
+	#[context("getting tile stream for {:?} from composite reader", bbox)]
+	async fn get_tile_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		let sub_bboxes: Vec<TileBBox> = bbox.iter_bbox_grid(32).collect();
+		let blocks = stream::iter(sub_bboxes)
+			.map(|bbox| self.read_block(bbox))
+			.buffer_unordered(num_cpus::get())
+			.try_collect::<Vec<_>>()
+			.await?;
+
+		Ok(TileStream::from_vec(blocks.into_iter().flatten().collect()))
+	}
+}
+
+impl CompositeReader {
+	/// Reads all tiles of `bbox`, taking each tile from the first reader that provides it.
+	///
+	/// Fails if a reader fails or a tile cannot be recompressed, just like [`TilesReaderTrait::get_tile`].
+	#[context("reading block {:?} from composite reader", bbox)]
+	async fn read_block(&self, bbox: TileBBox) -> Result<Vec<(TileCoord, Tile)>> {
+		let mut tiles = TileBBoxMap::<Option<Tile>>::new_default(bbox);
+
+		for reader in &self.readers {
+			let mut bbox_left = TileBBox::new_empty(bbox.level)?;
+			for (coord, slot) in tiles.iter() {
+				if slot.is_none() {
+					bbox_left.include_coord(&coord)?;
+				}
+			}
+			bbox_left.intersect_with_pyramid(&reader.parameters().bbox_pyramid);
+			if bbox_left.is_empty() {
+				continue;
+			}
+
+			reader
+				.get_tile_stream(bbox_left)
+				.await?
+				.for_each_sync(|(coord, tile)| {
+					let entry = tiles.get_mut(&coord).unwrap();
+					if entry.is_none() {
+						*entry = Some(tile);
+					}
+				})
+				.await;
+		}
+
+		let compression = self.parameters.tile_compression;
+		tiles
+			.into_iter()
+			.filter_map(|(coord, item)| item.map(|tile| (coord, tile)))
+			.map(|(coord, mut tile)| {
+				tile.change_compression(compression)?;
+				Ok((coord, tile))
+			})
+			.collect()
+	}
+}
+
+impl Debug for CompositeReader {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("CompositeReader")
+			.field("name", &self.name)
+			.field("parameters", &self.parameters)
+			.finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::MemTilesReader;
+	use TileCompression::*;
+
+	fn mem_reader(label: &str, bbox: TileBBox, compression: TileCompression) -> Box<dyn TilesReaderTrait> {
+		let mut reader = MemTilesReader::new(TileFormat::JSON, compression);
+		for coord in bbox.iter_coords() {
+			let blob = utils::compress(Blob::from(label), compression).unwrap();
+			reader
+				.insert(coord, Tile::from_blob(blob, compression, TileFormat::JSON))
+				.unwrap();
+		}
+		Box::new(reader)
+	}
+
+	async fn get_label(reader: &CompositeReader, coord: TileCoord) -> Option<String> {
+		let tile = reader.get_tile(&coord).await.unwrap()?;
+		Some(tile.into_blob(Uncompressed).unwrap().as_str().to_string())
+	}
+
+	#[tokio::test]
+	async fn first_reader_wins() -> Result<()> {
+		let reader = CompositeReader::new(vec![
+			mem_reader("update", TileBBox::from_min_and_max(2, 1, 1, 2, 2)?, Gzip),
+			mem_reader("base", TileBBox::new_full(2)?, Uncompressed),
+		])?;
+
+		assert_eq!(reader.parameters().tile_compression, Gzip);
+		assert_eq!(reader.parameters().bbox_pyramid.count_tiles(), 16);
+
+		assert_eq!(get_label(&reader, TileCoord::new(2, 1, 1)?).await.unwrap(), "update");
+		assert_eq!(get_label(&reader, TileCoord::new(2, 0, 0)?).await.unwrap(), "base");
+		assert_eq!(get_label(&reader, TileCoord::new(1, 0, 0)?).await, None);
+
+		let mut labels = reader
+			.get_tile_stream(TileBBox::new_full(2)?)
+			.await?
+			.to_vec()
+			.await
+			.into_iter()
+			.map(|(coord, tile)| {
+				assert_eq!(tile.compression(), Gzip);
+				(
+					coord.get_sort_index(),
+					tile.into_blob(Uncompressed).unwrap().as_str().to_string(),
+				)
+			})
+			.collect::<Vec<_>>();
+		labels.sort();
+		assert_eq!(labels.len(), 16);
+		assert_eq!(labels.iter().filter(|(_, label)| label == "update").count(), 4);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn reader_errors_are_propagated() -> Result<()> {
+		let mut broken = MemTilesReader::new(TileFormat::JSON, Gzip);
+		broken.insert(
+			TileCoord::new(1, 0, 0)?,
+			Tile::from_blob(Blob::from("not gzip"), Gzip, TileFormat::JSON),
+		)?;
+		let mut reader = CompositeReader::new(vec![
+			Box::new(broken),
+			mem_reader("base", TileBBox::new_full(1)?, Brotli),
+		])?;
+		reader.override_compression(Brotli);
+
+		assert!(reader.get_tile(&TileCoord::new(1, 0, 0)?).await.is_err());
+		assert!(reader.get_tile_stream(TileBBox::new_full(1)?).await.is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn rejects_invalid_readers() {
+		assert!(CompositeReader::new(vec![]).is_err());
+
+		let png = Box::new(MemTilesReader::new(TileFormat::PNG, Uncompressed));
+		let json = Box::new(MemTilesReader::new(TileFormat::JSON, Uncompressed));
+		assert!(CompositeReader::new(vec![png, json]).is_err());
+	}
+}
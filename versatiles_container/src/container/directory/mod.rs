@@ -5,9 +5,18 @@
 //! The main components of this module are:
 //! - `DirectoryTilesReader`: Reads tiles from a directory structure.
 //! - `DirectoryTilesWriter`: Writes tiles to a directory structure.
+//! - `PathTemplate`: Describes alternative file naming schemes, e.g. `{z}/{y}/{x}` or hierarchical tile ids.
+//!
+//! `PathTemplate` is always available, the reader and writer require the `directory` feature.
 
+mod path_template;
+#[cfg(feature = "directory")]
 mod reader;
+#[cfg(feature = "directory")]
 mod writer;
 
+pub use path_template::PathTemplate;
+#[cfg(feature = "directory")]
 pub use reader::DirectoryTilesReader;
+#[cfg(feature = "directory")]
 pub use writer::DirectoryTilesWriter;
@@ -0,0 +1,371 @@
+//! Path templates describe how tile files are named inside a directory container.
+//!
+//! A template is the path of a tile file relative to the directory, **without** the file
+//! extensions. The extensions for format and compression (e.g. `.pbf.br`) are always appended.
+//! Placeholders in curly braces are replaced by the tile coordinate:
+//!
+//! | Placeholder | Value                                                                  |
+//! |-------------|------------------------------------------------------------------------|
+//! | `{z}`       | zoom level                                                             |
+//! | `{x}`       | column                                                                 |
+//! | `{y}`       | row, counted from the top (XYZ scheme)                                 |
+//! | `{-y}`      | row, counted from the bottom (TMS scheme)                              |
+//! | `{quadkey}` | Bing Maps quadkey, also encodes the zoom level                         |
+//! | `{id}`      | tile id within the zoom level: `y * 2^z + x`                           |
+//! | `{id/3}`    | tile id, zero-padded to a multiple of 3 digits and split into folders of 3 digits |
+//!
+//! `{id/3}` mirrors the hierarchical tile naming of routing engines like Valhalla, e.g.
+//! `{z}/{id/3}` names the tile `12/2200/1345` as `12/005/511/320`. Note that the ids are based on the
+//! WebMercator tile grid, so only the naming scheme is compatible, not the tile grid itself.
+//!
+//! The default template is `{z}/{x}/{y}`.
+//!
+//! ```
+//! use versatiles_container::PathTemplate;
+//! use versatiles_core::TileCoord;
+//!
+//! let template = PathTemplate::parse("tiles/{z}/{y}/{x}").unwrap();
+//! let coord = TileCoord::new(3, 2, 1).unwrap();
+//! assert_eq!(template.format(&coord), "tiles/3/1/2");
+//! assert_eq!(template.parse_path("tiles/3/1/2"), Some(coord));
+//! ```
+
+use anyhow::{Result, bail, ensure};
+use regex::Regex;
+use std::{fmt, str::FromStr};
+use versatiles_core::TileCoord;
+use versatiles_derive::context;
+
+/// One element of a parsed [`PathTemplate`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Placeholder {
+	Z,
+	X,
+	Y,
+	YFlipped,
+	Quadkey,
+	Id,
+	IdSplit,
+}
+
+impl Placeholder {
+	fn from_name(name: &str) -> Option<Placeholder> {
+		Some(match name {
+			"z" => Placeholder::Z,
+			"x" => Placeholder::X,
+			"y" => Placeholder::Y,
+			"-y" => Placeholder::YFlipped,
+			"quadkey" => Placeholder::Quadkey,
+			"id" => Placeholder::Id,
+			"id/3" => Placeholder::IdSplit,
+			_ => return None,
+		})
+	}
+
+	/// Regex capturing the value of this placeholder in a named group.
+	fn pattern(self) -> &'static str {
+		match self {
+			Placeholder::Z => r"(?P<z>\d+)",
+			Placeholder::X => r"(?P<x>\d+)",
+			Placeholder::Y => r"(?P<y>\d+)",
+			Placeholder::YFlipped => r"(?P<ty>\d+)",
+			Placeholder::Quadkey => r"(?P<quadkey>[0-3]*)",
+			Placeholder::Id => r"(?P<id>\d+)",
+			Placeholder::IdSplit => r"(?P<ids>\d{3}(?:/\d{3})*)",
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+	Literal(String),
+	Placeholder(Placeholder),
+}
+
+/// A parsed path template for naming tile files, see the [module documentation](self).
+#[derive(Clone)]
+pub struct PathTemplate {
+	template: String,
+	parts: Vec<Part>,
+	regex: Regex,
+}
+
+impl PathTemplate {
+	/// Parses a template like `{z}/{x}/{y}`.
+	///
+	/// Fails on unknown or repeated placeholders, and if the template doesn't determine the tile coordinate.
+	#[context("parsing path template '{template}'")]
+	pub fn parse(template: &str) -> Result<PathTemplate> {
+		ensure!(!template.starts_with('/'), "template must be a relative path");
+
+		let mut parts = Vec::new();
+		let mut rest = template;
+		while !rest.is_empty() {
+			if let Some(inner) = rest.strip_prefix('{') {
+				let Some(end) = inner.find('}') else {
+					bail!("missing '}}'")
+				};
+				let name = &inner[..end];
+				let Some(placeholder) = Placeholder::from_name(name) else {
+					bail!("unknown placeholder '{{{name}}}'")
+				};
+				ensure!(
+					!parts.contains(&Part::Placeholder(placeholder)),
+					"placeholder '{{{name}}}' is used twice"
+				);
+				parts.push(Part::Placeholder(placeholder));
+				rest = &inner[end + 1..];
+			} else {
+				let end = rest.find('{').unwrap_or(rest.len());
+				let literal = &rest[..end];
+				ensure!(!literal.contains('}'), "unexpected '}}'");
+				parts.push(Part::Literal(literal.to_string()));
+				rest = &rest[end..];
+			}
+		}
+
+		let has = |p: Placeholder| parts.contains(&Part::Placeholder(p));
+		ensure!(
+			has(Placeholder::Z) || has(Placeholder::Quadkey),
+			"template must contain '{{z}}' or '{{quadkey}}'"
+		);
+		ensure!(
+			has(Placeholder::Quadkey)
+				|| has(Placeholder::Id)
+				|| has(Placeholder::IdSplit)
+				|| (has(Placeholder::X) && (has(Placeholder::Y) || has(Placeholder::YFlipped))),
+			"template must contain '{{x}}' and '{{y}}', '{{-y}}', '{{id}}', '{{id/3}}' or '{{quadkey}}'"
+		);
+
+		let pattern = parts
+			.iter()
+			.map(|part| match part {
+				Part::Literal(literal) => regex::escape(literal),
+				Part::Placeholder(placeholder) => placeholder.pattern().to_string(),
+			})
+			.collect::<String>();
+
+		Ok(PathTemplate {
+			template: template.to_string(),
+			regex: Regex::new(&format!("^{pattern}$"))?,
+			parts,
+		})
+	}
+
+	/// Returns the path of the tile `coord`, without file extensions.
+	pub fn format(&self, coord: &TileCoord) -> String {
+		let mut path = String::new();
+		for part in &self.parts {
+			match part {
+				Part::Literal(literal) => path.push_str(literal),
+				Part::Placeholder(placeholder) => path.push_str(&match placeholder {
+					Placeholder::Z => coord.level.to_string(),
+					Placeholder::X => coord.x.to_string(),
+					Placeholder::Y => coord.y.to_string(),
+					Placeholder::YFlipped => ((1u32 << coord.level) - 1 - coord.y).to_string(),
+					Placeholder::Quadkey => quadkey(coord),
+					Placeholder::Id => tile_id(coord).to_string(),
+					Placeholder::IdSplit => {
+						let digits = id_digits(coord.level);
+						let id = format!("{:0digits$}", tile_id(coord));
+						id.as_bytes()
+							.chunks(3)
+							.map(|chunk| std::str::from_utf8(chunk).unwrap())
+							.collect::<Vec<_>>()
+							.join("/")
+					}
+				}),
+			}
+		}
+		path
+	}
+
+	/// Parses the path of a tile file, without file extensions, into a tile coordinate.
+	///
+	/// Returns `None` if the path doesn't match the template or describes an invalid coordinate.
+	pub fn parse_path(&self, path: &str) -> Option<TileCoord> {
+		let captures = self.regex.captures(path)?;
+		let number = |name: &str| captures.name(name).map(|m| m.as_str().parse::<u64>().ok());
+
+		let quadkey = captures.name("quadkey").map(|m| m.as_str());
+		let level = match (number("z"), quadkey) {
+			(Some(z), _) => u8::try_from(z?).ok()?,
+			(None, Some(quadkey)) => u8::try_from(quadkey.len()).ok()?,
+			(None, None) => return None,
+		};
+		if level > 30 {
+			return None;
+		}
+		let size = 1u64 << level;
+
+		let id = match (number("id"), captures.name("ids")) {
+			(Some(id), _) => Some(id?),
+			(None, Some(ids)) => {
+				let digits = ids.as_str().replace('/', "");
+				if digits.len() != id_digits(level) {
+					return None;
+				}
+				Some(digits.parse::<u64>().ok()?)
+			}
+			(None, None) => None,
+		};
+
+		let (x, y) = if let Some(x) = number("x") {
+			let y = match (number("y"), number("ty")) {
+				(Some(y), _) => y?,
+				(None, Some(ty)) => size.checked_sub(1)?.checked_sub(ty?)?,
+				(None, None) => return None,
+			};
+			(x?, y)
+		} else if let Some(id) = id {
+			(id % size, id / size)
+		} else {
+			let coord = quadkey_to_coord(quadkey?)?;
+			(u64::from(coord.x), u64::from(coord.y))
+		};
+		if x >= size || y >= size {
+			return None;
+		}
+
+		let coord = TileCoord::new(level, u32::try_from(x).ok()?, u32::try_from(y).ok()?).ok()?;
+		// all given values must describe the same tile
+		let consistent = self.format(&coord) == path;
+		consistent.then_some(coord)
+	}
+
+	/// Returns the template string.
+	pub fn as_str(&self) -> &str {
+		&self.template
+	}
+}
+
+/// Row-major tile id within the zoom level.
+fn tile_id(coord: &TileCoord) -> u64 {
+	(u64::from(coord.y) << coord.level) + u64::from(coord.x)
+}
+
+/// Number of digits of the largest tile id of `level`, rounded up to a multiple of 3.
+fn id_digits(level: u8) -> usize {
+	let max_id = (1u64 << (2 * u32::from(level))) - 1;
+	max_id.to_string().len().div_ceil(3) * 3
+}
+
+fn quadkey(coord: &TileCoord) -> String {
+	(1..=coord.level)
+		.rev()
+		.map(|i| {
+			let mask = 1 << (i - 1);
+			let digit = u8::from(coord.x & mask != 0) + 2 * u8::from(coord.y & mask != 0);
+			char::from(b'0' + digit)
+		})
+		.collect()
+}
+
+fn quadkey_to_coord(quadkey: &str) -> Option<TileCoord> {
+	let (mut x, mut y) = (0u32, 0u32);
+	for c in quadkey.chars() {
+		let digit = c.to_digit(4)?;
+		x = (x << 1) | (digit & 1);
+		y = (y << 1) | (digit >> 1);
+	}
+	TileCoord::new(u8::try_from(quadkey.len()).ok()?, x, y).ok()
+}
+
+impl Default for PathTemplate {
+	fn default() -> Self {
+		PathTemplate::parse("{z}/{x}/{y}").unwrap()
+	}
+}
+
+impl FromStr for PathTemplate {
+	type Err = anyhow::Error;
+
+	fn from_str(template: &str) -> Result<Self> {
+		PathTemplate::parse(template)
+	}
+}
+
+impl PartialEq for PathTemplate {
+	fn eq(&self, other: &Self) -> bool {
+		self.template == other.template
+	}
+}
+
+impl fmt::Debug for PathTemplate {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "PathTemplate({:?})", self.template)
+	}
+}
+
+impl fmt::Display for PathTemplate {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.template)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case("{z}/{x}/{y}", "3/2/1")]
+	#[case("{z}/{y}/{x}", "3/1/2")]
+	#[case("{z}/{x}/{-y}", "3/2/6")]
+	#[case("tiles_{z}-{x}-{y}", "tiles_3-2-1")]
+	#[case("{quadkey}", "012")]
+	#[case("{z}/{id}", "3/10")]
+	#[case("{z}/{id/3}", "3/010")]
+	fn format_and_parse(#[case] template: &str, #[case] path: &str) -> Result<()> {
+		let template = PathTemplate::parse(template)?;
+		let coord = TileCoord::new(3, 2, 1)?;
+		assert_eq!(template.format(&coord), path);
+		assert_eq!(template.parse_path(path), Some(coord));
+		Ok(())
+	}
+
+	#[test]
+	fn id_split() -> Result<()> {
+		let template = PathTemplate::parse("{z}/{id/3}")?;
+		assert_eq!(template.format(&TileCoord::new(0, 0, 0)?), "0/000");
+		assert_eq!(template.format(&TileCoord::new(12, 2200, 1345)?), "12/005/511/320");
+		assert_eq!(
+			template.parse_path("12/005/511/320"),
+			Some(TileCoord::new(12, 2200, 1345)?)
+		);
+		// wrong number of digits for the level
+		assert_eq!(template.parse_path("12/511/320"), None);
+		Ok(())
+	}
+
+	#[rstest]
+	#[case("3/2/1/0")]
+	#[case("3/2")]
+	#[case("3/8/1")]
+	#[case("a/2/1")]
+	#[case("meta")]
+	fn parse_invalid_paths(#[case] path: &str) {
+		assert_eq!(PathTemplate::default().parse_path(path), None);
+	}
+
+	#[test]
+	fn parse_inconsistent_paths() -> Result<()> {
+		// the quadkey of 3/2/1 is "012"
+		let template = PathTemplate::parse("{z}/{quadkey}")?;
+		assert!(template.parse_path("3/012").is_some());
+		assert!(template.parse_path("2/012").is_none());
+		Ok(())
+	}
+
+	#[rstest]
+	#[case("/{z}/{x}/{y}")]
+	#[case("{z}/{x}/{y")]
+	#[case("{z}/{x}/{y}}")]
+	#[case("{z}/{x}/{w}")]
+	#[case("{z}/{x}/{x}")]
+	#[case("{x}/{y}")]
+	#[case("{z}/{x}")]
+	fn parse_invalid_templates(#[case] template: &str) {
+		assert!(PathTemplate::parse(template).is_err());
+	}
+}
@@ -17,10 +17,15 @@
 //! | `/tiles/4/2/1.pbf.br` | Brotli compressed PBF tile  |
 //! | `/tiles/meta.json`  | Metadata file                |
 //!
+//! Other naming schemes, e.g. `<z>/<y>/<x>` or hierarchical tile ids, can be read with
+//! [`DirectoryTilesReader::open_path_with_template`] and a [`PathTemplate`].
+//!
 //! All tiles must share the same **format** and **compression**. If multiple formats or compressions are detected, an error is returned.
 //!
 //! Bounds, minimum zoom, and maximum zoom are inferred from the discovered tiles and merged with any metadata files found.
 //!
+//! The modification time of a tile file is used as the modification time of the tile, see [`Tile::mtime`].
+//!
 //! ## Usage
 //! ```no_run
 //! use versatiles_container::*;
@@ -40,15 +45,16 @@
 //! ## Errors
 //! Errors are returned if the directory is not absolute, does not exist, is not a directory, contains no tiles, or if tiles have inconsistent formats or compressions.
 
+use super::PathTemplate;
 use crate::{Tile, TilesReaderTrait};
 use anyhow::{Result, bail, ensure};
 use async_trait::async_trait;
-use itertools::Itertools;
 use std::{
-	collections::HashMap,
+	collections::{HashMap, HashSet},
 	fmt::Debug,
 	fs,
 	path::{Path, PathBuf},
+	time::UNIX_EPOCH,
 };
 use versatiles_core::{utils::*, *};
 use versatiles_derive::context;
@@ -94,6 +100,14 @@ impl DirectoryTilesReader {
 	where
 		Self: Sized,
 	{
+		Self::open_path_with_template(dir, &PathTemplate::default())
+	}
+
+	/// Opens a directory whose tile files are named according to `template`, e.g. `{z}/{y}/{x}` or `{z}/{id/3}`.
+	///
+	/// See [`PathTemplate`] for the supported placeholders. Otherwise it behaves like [`DirectoryTilesReader::open_path`].
+	#[context("opening tiles directory {:?} with path template '{}'", dir, template)]
+	pub fn open_path_with_template(dir: &Path, template: &PathTemplate) -> Result<DirectoryTilesReader> {
 		log::trace!("read {dir:?}");
 
 		ensure!(dir.is_absolute(), "path {dir:?} must be absolute");
@@ -106,95 +120,69 @@ impl DirectoryTilesReader {
 		let mut container_comp: Option<TileCompression> = None;
 		let mut bbox_pyramid = TileBBoxPyramid::new_empty();
 
-		for result1 in fs::read_dir(dir)? {
-			// z level
-			if result1.is_err() {
+		for entry in fs::read_dir(dir)?.flatten() {
+			let Ok(name) = entry.file_name().into_string() else {
+				continue;
+			};
+			match name.as_str() {
+				"meta.json" | "tiles.json" | "metadata.json" => {
+					tilejson.merge(&TileJSON::try_from_blob_or_default(&Self::read(&entry.path())?))?;
+				}
+				"meta.json.gz" | "tiles.json.gz" | "metadata.json.gz" => {
+					tilejson.merge(&TileJSON::try_from_blob_or_default(&decompress(
+						Self::read(&entry.path())?,
+						TileCompression::Gzip,
+					)?))?;
+				}
+				"meta.json.br" | "tiles.json.br" | "metadata.json.br" => {
+					tilejson.merge(&TileJSON::try_from_blob_or_default(&decompress(
+						Self::read(&entry.path())?,
+						TileCompression::Brotli,
+					)?))?;
+				}
+				&_ => {}
+			};
+		}
+
+		let mut files = Vec::new();
+		Self::collect_files(dir, &mut files, &mut HashSet::new())?;
+		files.sort_unstable();
+
+		for path in files {
+			let Some(relative) = path.strip_prefix(dir)?.to_str() else {
 				continue;
+			};
+			let mut filename = relative.replace('\\', "/");
+			let file_comp = TileCompression::from_filename(&mut filename);
+			let Some(file_form) = TileFormat::from_filename(&mut filename) else {
+				continue;
+			};
+			let Some(coord) = template.parse_path(&filename) else {
+				continue;
+			};
+
+			if let Some(form) = container_form {
+				if form != file_form {
+					let mut r = [form, file_form];
+					r.sort();
+					bail!("found multiple tile formats: {:?}", r);
+				}
+			} else {
+				container_form = Some(file_form);
 			}
-			let entry1 = result1?;
-			let name1 = entry1.file_name().into_string().unwrap();
-			let numeric1 = name1.parse::<u8>();
-			if numeric1.is_ok() {
-				let level = numeric1?;
-
-				for result2 in fs::read_dir(entry1.path())? {
-					// x level
-					if result2.is_err() {
-						continue;
-					}
-					let entry2 = result2?;
-					let name2 = entry2.file_name().into_string().unwrap();
-					let numeric2 = name2.parse::<u32>();
-					if numeric2.is_err() {
-						continue;
-					}
-					let x = numeric2?;
-
-					let files = fs::read_dir(entry2.path())?.map(|f| f.unwrap());
-					let files = files.sorted_unstable_by(|a, b| a.file_name().partial_cmp(&b.file_name()).unwrap());
-
-					for entry3 in files {
-						// y level
-						let mut filename = entry3.file_name().into_string().unwrap();
-						let file_comp = TileCompression::from_filename(&mut filename);
-						let this_form = TileFormat::from_filename(&mut filename);
-
-						if this_form.is_none() {
-							continue;
-						}
-						let file_form = this_form.unwrap();
-
-						let numeric3 = filename.parse::<u32>();
-						if numeric3.is_err() {
-							continue;
-						}
-						let y = numeric3?;
-
-						if let Some(form) = container_form {
-							if form != file_form {
-								let mut r = [form, file_form];
-								r.sort();
-								bail!("found multiple tile formats: {:?}", r);
-							}
-						} else {
-							container_form = Some(file_form);
-						}
-
-						if let Some(comp) = container_comp {
-							if comp != file_comp {
-								let mut r = [comp, file_comp];
-								r.sort();
-								bail!("found multiple tile compressions: {:?}", r);
-							}
-						} else {
-							container_comp = Some(file_comp);
-						}
-
-						let coord = TileCoord::new(level, x, y)?;
-						bbox_pyramid.include_coord(&coord);
-						tile_map.insert(coord, entry3.path());
-					}
+
+			if let Some(comp) = container_comp {
+				if comp != file_comp {
+					let mut r = [comp, file_comp];
+					r.sort();
+					bail!("found multiple tile compressions: {:?}", r);
 				}
 			} else {
-				match name1.as_str() {
-					"meta.json" | "tiles.json" | "metadata.json" => {
-						tilejson.merge(&TileJSON::try_from_blob_or_default(&Self::read(&entry1.path())?))?;
-					}
-					"meta.json.gz" | "tiles.json.gz" | "metadata.json.gz" => {
-						tilejson.merge(&TileJSON::try_from_blob_or_default(&decompress(
-							Self::read(&entry1.path())?,
-							TileCompression::Gzip,
-						)?))?;
-					}
-					"meta.json.br" | "tiles.json.br" | "metadata.json.br" => {
-						tilejson.merge(&TileJSON::try_from_blob_or_default(&decompress(
-							Self::read(&entry1.path())?,
-							TileCompression::Brotli,
-						)?))?;
-					}
-					&_ => {}
-				};
+				container_comp = Some(file_comp);
 			}
+
+			bbox_pyramid.include_coord(&coord);
+			tile_map.insert(coord, path);
 		}
 
 		if tile_map.is_empty() {
@@ -214,11 +202,35 @@ impl DirectoryTilesReader {
 		})
 	}
 
+	/// Recursively collects the paths of all files below `dir`.
+	///
+	/// Symlinked directories are followed, but every directory is visited only once, so symlink loops end.
+	fn collect_files(dir: &Path, files: &mut Vec<PathBuf>, visited: &mut HashSet<PathBuf>) -> Result<()> {
+		if !visited.insert(dir.canonicalize()?) {
+			return Ok(());
+		}
+		for entry in fs::read_dir(dir)?.flatten() {
+			let path = entry.path();
+			if path.is_dir() {
+				Self::collect_files(&path, files, visited)?;
+			} else {
+				files.push(path);
+			}
+		}
+		Ok(())
+	}
+
 	/// Reads a file into a `Blob`.
 	#[context("reading file '{}'", path.display())]
 	fn read(path: &Path) -> Result<Blob> {
 		Ok(Blob::from(fs::read(path)?))
 	}
+
+	/// Returns the modification time of a file in seconds since the Unix epoch, if the file system provides it.
+	fn modified(path: &Path) -> Option<u64> {
+		let modified = fs::metadata(path).ok()?.modified().ok()?;
+		Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+	}
 }
 
 /// Implements the `TilesReaderTrait` for `DirectoryTilesReader`.
@@ -250,16 +262,18 @@ impl TilesReaderTrait for DirectoryTilesReader {
 
 		if let Some(path) = self.tile_map.get(coord) {
 			Self::read(path).map(|blob| {
-				Some(Tile::from_blob(
-					blob,
-					self.parameters.tile_compression,
-					self.parameters.tile_format,
-				))
+				let mut tile = Tile::from_blob(blob, self.parameters.tile_compression, self.parameters.tile_format);
+				tile.set_mtime(Self::modified(path));
+				Some(tile)
 			})
 		} else {
 			Ok(None)
 		}
 	}
+
+	async fn get_tile_mtime(&self, coord: &TileCoord) -> Result<Option<u64>> {
+		Ok(self.tile_map.get(coord).and_then(|path| Self::modified(path)))
+	}
 	fn source_name(&self) -> &str {
 		self.dir.to_str().unwrap()
 	}
@@ -303,8 +317,36 @@ mod tests {
 			tile_data.as_blob(reader.parameters().tile_compression)?,
 			&Blob::from("test tile data")
 		);
+		assert!(tile_data.mtime().is_some_and(|mtime| mtime > 1_600_000_000));
+		assert_eq!(
+			reader.get_tile_mtime(&TileCoord::new(3, 2, 1)?).await?,
+			tile_data.mtime()
+		);
 
 		assert!(reader.get_tile(&TileCoord::new(2, 2, 1)?).await?.is_none());
+		assert_eq!(reader.get_tile_mtime(&TileCoord::new(2, 2, 1)?).await?, None);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn open_path_with_template() -> Result<()> {
+		let dir = TempDir::new()?;
+		dir.child("tiles/3/010.pbf.br").write_str("tile 3/2/1")?;
+		dir.child("tiles/3/063.pbf.br").write_str("tile 3/7/7")?;
+		dir.child("tiles/3/000/010.pbf.br").write_str("ignored")?;
+		dir.child("3/2/1.pbf.br").write_str("ignored")?;
+
+		let template = PathTemplate::parse("tiles/{z}/{id/3}")?;
+		let reader = DirectoryTilesReader::open_path_with_template(&dir, &template)?;
+		assert_eq!(reader.parameters().tile_format, TileFormat::MVT);
+		assert_eq!(reader.parameters().tile_compression, TileCompression::Brotli);
+		assert_eq!(reader.tile_map.len(), 2);
+
+		let tile = reader.get_tile(&TileCoord::new(3, 2, 1)?).await?.unwrap();
+		assert_eq!(tile.into_blob(TileCompression::Brotli)?, Blob::from("tile 3/2/1"));
+		let tile = reader.get_tile(&TileCoord::new(3, 7, 7)?).await?.unwrap();
+		assert_eq!(tile.into_blob(TileCompression::Brotli)?, Blob::from("tile 3/7/7"));
 
 		Ok(())
 	}
@@ -385,6 +427,18 @@ mod tests {
 		Ok(())
 	}
 
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn symlink_loop() -> Result<()> {
+		let dir = TempDir::new()?;
+		dir.child("3/2/1.png").write_str("tile at 3/2/1")?;
+		std::os::unix::fs::symlink("..", dir.path().join("3/2/loop"))?;
+
+		let reader = DirectoryTilesReader::open_path(&dir)?;
+		assert_eq!(reader.tile_map.len(), 1);
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn incorrect_format_and_compression_handling() -> Result<()> {
 		let dir = TempDir::new().unwrap();
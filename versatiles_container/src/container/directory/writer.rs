@@ -19,6 +19,9 @@
 //! - Tiles at `<z>/<x>/<y>.<ext>[.<br|gz>]` (e.g., `2/3/1.pbf.gz`, `7/21/42.png`).
 //! - TileJSON at `tiles.json[.<br|gz>]`.
 //!
+//! Other naming schemes can be written with [`DirectoryTilesWriter::write_to_path_with_template`]
+//! and a [`PathTemplate`].
+//!
 //! ### Example
 //! ```rust,no_run
 //! use versatiles_container::*;
@@ -41,6 +44,7 @@
 //! ### Errors
 //! Returns errors if the destination path is not absolute, if file I/O fails, or if compression/encoding fails.
 
+use super::PathTemplate;
 use crate::{ProcessingConfig, TilesReaderTrait, TilesReaderTraverseExt, TilesWriterTrait};
 use anyhow::{Result, bail, ensure};
 use async_trait::async_trait;
@@ -69,21 +73,16 @@ impl DirectoryTilesWriter {
 		fs::write(&path, blob.as_slice())?;
 		Ok(())
 	}
-}
 
-#[async_trait]
-impl TilesWriterTrait for DirectoryTilesWriter {
-	/// Write all tiles and metadata from `reader` into the absolute directory `path`.
-	///
-	/// * Validates that `path` is absolute.
-	/// * Encodes tiles using `reader.parameters().tile_format` and `reader.parameters().tile_compression`.
-	/// * Writes `tiles.json[.<compression>]` containing the reader's TileJSON, compressed to the same transport layer.
-	/// * Creates the `{z}/{x}/{y}` directory structure on demand.
-	///
-	/// # Errors
-	/// Returns an error for non-absolute paths, I/O failures, or encoding/compression errors.
-	#[context("writing tiles to directory '{}'", path.display())]
-	async fn write_to_path(reader: &mut dyn TilesReaderTrait, path: &Path, config: ProcessingConfig) -> Result<()> {
+	/// Like [`TilesWriterTrait::write_to_path`], but names the tile files according to `template`,
+	/// e.g. `{z}/{y}/{x}` or `{z}/{id/3}`. See [`PathTemplate`] for the supported placeholders.
+	#[context("writing tiles to directory '{}' with path template '{}'", path.display(), template)]
+	pub async fn write_to_path_with_template(
+		reader: &mut dyn TilesReaderTrait,
+		path: &Path,
+		template: &PathTemplate,
+		config: ProcessingConfig,
+	) -> Result<()> {
 		ensure!(path.is_absolute(), "path {path:?} must be absolute");
 
 		log::trace!("convert_from");
@@ -107,13 +106,16 @@ impl TilesWriterTrait for DirectoryTilesWriter {
 					let extension_format = extension_format.clone();
 					let extension_compression = extension_compression.clone();
 					let path = path.to_path_buf();
+					let template = template.clone();
 					Box::pin(async move {
 						while let Some(entry) = stream.next().await {
 							let (coord, tile) = entry;
 
 							let filename = format!(
-								"{}/{}/{}{}{}",
-								coord.level, coord.x, coord.y, extension_format, extension_compression
+								"{}{}{}",
+								template.format(&coord),
+								extension_format,
+								extension_compression
 							);
 
 							// Write blob to file
@@ -128,6 +130,23 @@ impl TilesWriterTrait for DirectoryTilesWriter {
 
 		Ok(())
 	}
+}
+
+#[async_trait]
+impl TilesWriterTrait for DirectoryTilesWriter {
+	/// Write all tiles and metadata from `reader` into the absolute directory `path`, using the `{z}/{x}/{y}` layout.
+	///
+	/// * Validates that `path` is absolute.
+	/// * Encodes tiles using `reader.parameters().tile_format` and `reader.parameters().tile_compression`.
+	/// * Writes `tiles.json[.<compression>]` containing the reader's TileJSON, compressed to the same transport layer.
+	/// * Creates the `{z}/{x}/{y}` directory structure on demand.
+	///
+	/// # Errors
+	/// Returns an error for non-absolute paths, I/O failures, or encoding/compression errors.
+	#[context("writing tiles to directory '{}'", path.display())]
+	async fn write_to_path(reader: &mut dyn TilesReaderTrait, path: &Path, config: ProcessingConfig) -> Result<()> {
+		Self::write_to_path_with_template(reader, path, &PathTemplate::default(), config).await
+	}
 
 	/// Writes the tile data from the given `TilesReader` to the specified `DataWriterTrait`.
 	///
@@ -181,6 +200,33 @@ mod tests {
 		assert_eq!(load("0/0/0.pbf.gz").as_slice(), MOCK_BYTES_PBF);
 		assert_eq!(load("2/3/3.pbf.gz").as_slice(), MOCK_BYTES_PBF);
 
+		Ok(())
+	}
+	#[tokio::test]
+	async fn test_write_with_template() -> Result<()> {
+		let temp_dir = assert_fs::TempDir::new()?;
+		let temp_path = temp_dir.path();
+
+		let mut mock_reader = MockTilesReader::new_mock(TilesReaderParameters::new(
+			TileFormat::PNG,
+			TileCompression::Uncompressed,
+			TileBBoxPyramid::new_full(3),
+		))?;
+
+		let template = PathTemplate::parse("{z}/{id/3}")?;
+		DirectoryTilesWriter::write_to_path_with_template(
+			&mut mock_reader,
+			temp_path,
+			&template,
+			ProcessingConfig::default(),
+		)
+		.await?;
+
+		assert!(temp_path.join("tiles.json").exists());
+		assert!(temp_path.join("0/000.png").exists());
+		assert!(temp_path.join("3/010.png").exists());
+		assert!(!temp_path.join("3/2/1.png").exists());
+
 		Ok(())
 	}
 }
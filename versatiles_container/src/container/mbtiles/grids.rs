@@ -0,0 +1,195 @@
+//! Read UTFGrid interaction data from an MBTiles (SQLite) database.
+//!
+//! The [MBTiles 1.3 specification](https://github.com/mapbox/mbtiles-spec/blob/master/1.3/spec.md#grids)
+//! allows interaction data next to the tiles: the `grids` table (or view) holds zlib-compressed UTFGrid JSON
+//! with `grid` and `keys`, and the `grid_data` table holds the JSON data of every key.
+//! Tile containers have no place for grids, so [`MBTilesGrids`] reads them as complete UTFGrid documents
+//! (`{"grid": [...], "keys": [...], "data": {...}}`), e.g. to write them as `{z}/{x}/{y}.grid.json` files.
+//!
+//! ```rust,no_run
+//! use versatiles_container::MBTilesGrids;
+//! use std::path::Path;
+//!
+//! let path = Path::new("/absolute/path/to/interactive.mbtiles");
+//! if let Some(grids) = MBTilesGrids::open_path(path).unwrap() {
+//!     for (coord, grid) in grids.read_all().unwrap() {
+//!         println!("{coord:?}: {}", grid.stringify());
+//!     }
+//! }
+//! ```
+
+use anyhow::{Context, Result, ensure};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use r2d2_sqlite::rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::{collections::HashMap, io::Read, path::Path};
+use versatiles_core::{
+	TileCoord,
+	json::{JsonObject, parse_json_str},
+};
+use versatiles_derive::context;
+
+/// UTFGrid interaction data of an MBTiles database.
+pub struct MBTilesGrids {
+	connection: Connection,
+	has_grid_data: bool,
+}
+
+impl MBTilesGrids {
+	/// Opens the MBTiles database at `path`, or returns `None` if it contains no `grids` table or view.
+	///
+	/// # Errors
+	/// Returns an error if the file does not exist or is not an SQLite database.
+	#[context("opening UTFGrids of MBTiles at '{}'", path.display())]
+	pub fn open_path(path: &Path) -> Result<Option<MBTilesGrids>> {
+		ensure!(path.exists(), "file {path:?} does not exist");
+		let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+		if !has_table(&connection, "grids")? {
+			return Ok(None);
+		}
+		let has_grid_data = has_table(&connection, "grid_data")?;
+		Ok(Some(MBTilesGrids {
+			connection,
+			has_grid_data,
+		}))
+	}
+
+	/// Returns the number of grids.
+	///
+	/// # Errors
+	/// Returns an error if the query fails.
+	pub fn count(&self) -> Result<u64> {
+		Ok(self
+			.connection
+			.query_row("SELECT COUNT(*) FROM grids", [], |row| row.get(0))?)
+	}
+
+	/// Reads all grids as UTFGrid documents, including the data of their keys.
+	///
+	/// # Errors
+	/// Returns an error if a query fails or a grid is not valid compressed JSON.
+	#[context("reading UTFGrids")]
+	pub fn read_all(&self) -> Result<Vec<(TileCoord, JsonObject)>> {
+		let mut data = self.read_grid_data()?;
+
+		let mut stmt = self
+			.connection
+			.prepare("SELECT zoom_level, tile_column, tile_row, grid FROM grids")?;
+		let rows = stmt.query_map([], |row| {
+			Ok((
+				row.get::<_, u8>(0)?,
+				row.get::<_, u32>(1)?,
+				row.get::<_, u32>(2)?,
+				row.get::<_, Vec<u8>>(3)?,
+			))
+		})?;
+
+		let mut grids = Vec::new();
+		for row in rows {
+			let (level, x, y, blob) = row?;
+			let mut grid = JsonObject::parse_str(&decompress_grid(&blob)?)
+				.with_context(|| format!("parsing grid of tile {level}/{x}/{y}"))?;
+			if let Some(keys) = data.remove(&(level, x, y)) {
+				grid.set("data", keys);
+			}
+			let mut coord = TileCoord::new(level, x, y)?;
+			coord.flip_y();
+			grids.push((coord, grid));
+		}
+		Ok(grids)
+	}
+
+	/// Reads the `grid_data` table, grouped by tile in TMS layout.
+	fn read_grid_data(&self) -> Result<HashMap<(u8, u32, u32), JsonObject>> {
+		let mut data: HashMap<(u8, u32, u32), JsonObject> = HashMap::new();
+		if !self.has_grid_data {
+			return Ok(data);
+		}
+
+		let mut stmt = self
+			.connection
+			.prepare("SELECT zoom_level, tile_column, tile_row, key_name, key_json FROM grid_data")?;
+		let rows = stmt.query_map([], |row| {
+			Ok((
+				(row.get::<_, u8>(0)?, row.get::<_, u32>(1)?, row.get::<_, u32>(2)?),
+				row.get::<_, String>(3)?,
+				row.get::<_, String>(4)?,
+			))
+		})?;
+		for row in rows {
+			let (tile, key, json) = row?;
+			let value = parse_json_str(&json).with_context(|| format!("parsing data of key '{key}'"))?;
+			data.entry(tile).or_default().set(&key, value);
+		}
+		Ok(data)
+	}
+}
+
+/// Returns `true` if the database has a table or view called `name`.
+fn has_table(connection: &Connection, name: &str) -> Result<bool> {
+	Ok(connection
+		.query_row(
+			"SELECT 1 FROM sqlite_master WHERE type IN ('table', 'view') AND name = ?",
+			[name],
+			|_| Ok(()),
+		)
+		.optional()?
+		.is_some())
+}
+
+/// Grids are compressed with zlib, but some tools write gzip instead.
+fn decompress_grid(blob: &[u8]) -> Result<String> {
+	let mut json = String::new();
+	if blob.starts_with(&[0x1f, 0x8b]) {
+		GzDecoder::new(blob).read_to_string(&mut json)
+	} else {
+		ZlibDecoder::new(blob).read_to_string(&mut json)
+	}
+	.context("decompressing grid")?;
+	Ok(json)
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+	use flate2::{Compression, write::ZlibEncoder};
+	use std::io::Write;
+
+	/// Adds the `grids` and `grid_data` tables with a single grid at tile 1/0/0 (XYZ) to an MBTiles file.
+	pub fn add_grids(path: &Path) -> Result<()> {
+		let connection = Connection::open(path)?;
+		connection.execute_batch(
+			"CREATE TABLE grids (zoom_level integer, tile_column integer, tile_row integer, grid blob);
+			CREATE TABLE grid_data (zoom_level integer, tile_column integer, tile_row integer, key_name text, key_json text);
+			INSERT INTO grid_data VALUES (1, 0, 1, '42', '{\"name\":\"Berlin\"}');",
+		)?;
+		let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+		encoder.write_all(br#"{"grid":["  ","!!"],"keys":["","42"]}"#)?;
+		connection.execute("INSERT INTO grids VALUES (1, 0, 1, ?)", [encoder.finish()?])?;
+		Ok(())
+	}
+
+	#[test]
+	fn read_grids() -> Result<()> {
+		let dir = assert_fs::TempDir::new()?;
+		let path = dir.path().join("grids.mbtiles");
+		add_grids(&path)?;
+
+		let grids = MBTilesGrids::open_path(&path)?.unwrap();
+		assert_eq!(grids.count()?, 1);
+		let grids = grids.read_all()?;
+		assert_eq!(grids.len(), 1);
+		assert_eq!(grids[0].0, TileCoord::new(1, 0, 0)?);
+		assert_eq!(
+			grids[0].1.stringify(),
+			r#"{"data":{"42":{"name":"Berlin"}},"grid":["  ","!!"],"keys":["","42"]}"#
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn no_grids() -> Result<()> {
+		let path = std::env::current_dir()?.join("../testdata/berlin.mbtiles");
+		assert!(MBTilesGrids::open_path(&path)?.is_none());
+		Ok(())
+	}
+}
@@ -5,9 +5,12 @@
 //! The main components of this module are:
 //! - `MBTilesReader`: Reads tiles from an MBTiles SQLite database.
 //! - `MBTilesWriter`: Writes tiles to an MBTiles SQLite database.
+//! - `MBTilesGrids`: Reads UTFGrid interaction data from an MBTiles SQLite database.
 
+mod grids;
 mod reader;
 mod writer;
 
+pub use grids::MBTilesGrids;
 pub use reader::MBTilesReader;
 pub use writer::MBTilesWriter;
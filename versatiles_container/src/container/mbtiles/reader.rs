@@ -13,6 +13,11 @@
 //! `vector_layers`) and merges them into an internal [`TileJSON`](versatiles_core::TileJSON).
 //! The bounding-box pyramid is inferred from the `tiles` table to augment/validate metadata.
 //!
+//! ## Concurrency
+//! The database is opened read-only with a pool of SQLite connections, and tiles are read on
+//! Tokio's blocking thread pool, so concurrent requests (e.g. from the server) are served in parallel.
+//! Use [`MBTilesReader::open_path_with_pool_size`] to configure the number of connections.
+//!
 //! ## Requirements
 //! - The MBTiles file **must be an absolute path** when opening with [`open_path`].
 //! - The database must include a `format` entry in `metadata` so that format & compression
@@ -52,7 +57,7 @@ use crate::{Tile, TilesReaderTrait};
 use anyhow::{Result, anyhow, ensure};
 use async_trait::async_trait;
 use r2d2::Pool;
-use r2d2_sqlite::SqliteConnectionManager;
+use r2d2_sqlite::{SqliteConnectionManager, rusqlite::OpenFlags};
 use std::path::Path;
 use versatiles_core::{TileCompression::*, TileFormat::*, json::parse_json_str, progress::get_progress_bar, types::*};
 use versatiles_derive::context;
@@ -77,16 +82,29 @@ impl MBTilesReader {
 	/// Validates existence and absoluteness of `path`, then initializes a connection pool
 	/// and loads metadata/parameters.
 	///
+	/// Uses one SQLite connection per CPU core, but at least 4.
+	///
 	/// # Errors
 	/// Returns an error if the file does not exist, the path is not absolute, or SQLite cannot be opened.
-	#[context("opening MBTiles at '{}'", path.display())]
 	pub fn open_path(path: &Path) -> Result<MBTilesReader> {
-		log::debug!("open {path:?}");
+		MBTilesReader::open_path_with_pool_size(path, num_cpus::get().max(4) as u32)
+	}
+
+	/// Open an MBTiles database from an **absolute** filesystem path,
+	/// using at most `pool_size` SQLite connections for concurrent reads.
+	///
+	/// # Errors
+	/// Returns an error if the file does not exist, the path is not absolute, `pool_size` is 0,
+	/// or SQLite cannot be opened.
+	#[context("opening MBTiles at '{}'", path.display())]
+	pub fn open_path_with_pool_size(path: &Path, pool_size: u32) -> Result<MBTilesReader> {
+		log::debug!("open {path:?} with {pool_size} connections");
 
 		ensure!(path.exists(), "file {path:?} does not exist");
 		ensure!(path.is_absolute(), "path {path:?} must be absolute");
+		ensure!(pool_size > 0, "pool size must be at least 1");
 
-		MBTilesReader::load_from_sqlite(path)
+		MBTilesReader::load_from_sqlite(path, pool_size)
 	}
 
 	/// Internal loader that establishes the SQLite pool, sets default parameters,
@@ -95,11 +113,12 @@ impl MBTilesReader {
 	/// # Errors
 	/// Returns an error if the connection cannot be established or metadata fails to load.
 	#[context("loading SQLite '{}'", path.display())]
-	fn load_from_sqlite(path: &Path) -> Result<MBTilesReader> {
+	fn load_from_sqlite(path: &Path, pool_size: u32) -> Result<MBTilesReader> {
 		log::debug!("load_from_sqlite {path:?}");
 
-		let manager = SqliteConnectionManager::file(path);
-		let pool = Pool::builder().max_size(10).build(manager)?;
+		let manager = SqliteConnectionManager::file(path)
+			.with_flags(OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX);
+		let pool = Pool::builder().max_size(pool_size).build(manager)?;
 		let parameters = TilesReaderParameters::new(MVT, Uncompressed, TileBBoxPyramid::new_empty());
 
 		let mut reader = MBTilesReader {
@@ -172,6 +191,7 @@ impl MBTilesReader {
 						.collect::<Result<Vec<f64>, _>>()?;
 					self.tilejson.limit_bbox(GeoBBox::try_from(bounds)?);
 				}
+				"center" => self.tilejson.center = Some(value.parse::<GeoCenter>()?),
 				"name" | "attribution" | "author" | "description" | "license" | "type" | "version" => {
 					self.tilejson.set_string(key, value)?
 				}
@@ -184,6 +204,8 @@ impl MBTilesReader {
 						.with_context(|| anyhow!("expected 'vector_layers'"))?;
 					self.tilejson.set_vector_layers(vector_layers)?;
 				}
+				// keep non-standard metadata, e.g. `generator` or `tippecanoe_decisions`
+				_ if !TileJSON::is_standard_key(key) => self.tilejson.set_string(key, value)?,
 				_ => {}
 			}
 		}
@@ -324,28 +346,34 @@ impl TilesReaderTrait for MBTilesReader {
 	/// Coordinates are converted to TMS row indexing internally (via `y' = 2^z - 1 - y`).
 	/// Returns `Ok(None)` when the tile is not present.
 	///
+	/// The query runs on Tokio's blocking thread pool with its own pooled connection,
+	/// so concurrent calls do not block each other or the async runtime.
+	///
 	/// # Errors
-	/// Returns an error if the query fails.
+	/// Returns an error if no connection is available or the query fails.
 	#[context("fetching tile {:?} from '{}'", coord, self.name)]
 	async fn get_tile(&self, coord: &TileCoord) -> Result<Option<Tile>> {
 		log::trace!("read tile from coord {coord:?}");
 
-		let conn = self.pool.get()?;
-		let mut stmt =
-			conn.prepare("SELECT tile_data FROM tiles WHERE tile_column = ? AND tile_row = ? AND zoom_level = ?")?;
-
+		let pool = self.pool.clone();
 		let max_index = 2u32.pow(coord.level as u32) - 1;
-		if let Ok(vec) = stmt.query_row([coord.x, max_index - coord.y, coord.level as u32], |row| {
-			row.get::<_, Vec<u8>>(0)
-		}) {
-			Ok(Some(Tile::from_blob(
+		let params = [coord.x, max_index - coord.y, coord.level as u32];
+
+		let data = tokio::task::spawn_blocking(move || -> Result<Option<Vec<u8>>> {
+			let conn = pool.get()?;
+			let mut stmt = conn
+				.prepare_cached("SELECT tile_data FROM tiles WHERE tile_column = ? AND tile_row = ? AND zoom_level = ?")?;
+			Ok(stmt.query_row(params, |row| row.get::<_, Vec<u8>>(0)).ok())
+		})
+		.await??;
+
+		Ok(data.map(|vec| {
+			Tile::from_blob(
 				Blob::from(vec),
 				self.parameters.tile_compression,
 				self.parameters.tile_format,
-			)))
-		} else {
-			Ok(None)
-		}
+			)
+		}))
 	}
 
 	/// Stream tiles within a single-zoom bounding box.
@@ -447,7 +475,7 @@ pub mod tests {
 		assert!(reader.source_name().ends_with("../testdata/berlin.mbtiles"));
 		assert_eq!(
 			reader.tilejson().as_string(),
-			"{\"author\":\"OpenStreetMap contributors, Geofabrik GmbH\",\"bounds\":[13.08283,52.33446,13.762245,52.6783],\"description\":\"Tile config for simple vector tiles schema\",\"license\":\"Open Database License 1.0\",\"maxzoom\":14,\"minzoom\":0,\"name\":\"Tilemaker to Geofabrik Vector Tiles schema\",\"tilejson\":\"3.0.0\",\"type\":\"baselayer\",\"vector_layers\":[{\"fields\":{\"name\":\"String\",\"number\":\"String\"},\"id\":\"addresses\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"kind\":\"String\"},\"id\":\"aerialways\",\"maxzoom\":14,\"minzoom\":12},{\"fields\":{\"admin_level\":\"Number\",\"maritime\":\"Boolean\"},\"id\":\"boundaries\",\"maxzoom\":14,\"minzoom\":0},{\"fields\":{\"admin_level\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\",\"way_area\":\"Number\"},\"id\":\"boundary_labels\",\"maxzoom\":14,\"minzoom\":2},{\"fields\":{\"dummy\":\"Number\"},\"id\":\"buildings\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"kind\":\"String\"},\"id\":\"land\",\"maxzoom\":14,\"minzoom\":7},{\"fields\":{},\"id\":\"ocean\",\"maxzoom\":14,\"minzoom\":8},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\",\"population\":\"Number\"},\"id\":\"place_labels\",\"maxzoom\":14,\"minzoom\":3},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\"},\"id\":\"public_transport\",\"maxzoom\":14,\"minzoom\":11},{\"fields\":{\"kind\":\"String\"},\"id\":\"sites\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\",\"ref\":\"String\",\"ref_cols\":\"Number\",\"ref_rows\":\"Number\",\"tunnel\":\"Boolean\"},\"id\":\"street_labels\",\"maxzoom\":14,\"minzoom\":10},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\",\"ref\":\"String\"},\"id\":\"street_labels_points\",\"maxzoom\":14,\"minzoom\":12},{\"fields\":{\"bridge\":\"Boolean\",\"kind\":\"String\",\"rail\":\"Boolean\",\"service\":\"String\",\"surface\":\"String\",\"tunnel\":\"Boolean\"},\"id\":\"street_polygons\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"bicycle\":\"String\",\"bridge\":\"Boolean\",\"horse\":\"String\",\"kind\":\"String\",\"link\":\"Boolean\",\"rail\":\"Boolean\",\"service\":\"String\",\"surface\":\"String\",\"tracktype\":\"String\",\"tunnel\":\"Boolean\"},\"id\":\"streets\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\"},\"id\":\"streets_polygons_labels\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"kind\":\"String\"},\"id\":\"water_lines\",\"maxzoom\":14,\"minzoom\":4},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\"},\"id\":\"water_lines_labels\",\"maxzoom\":14,\"minzoom\":4},{\"fields\":{\"kind\":\"String\"},\"id\":\"water_polygons\",\"maxzoom\":14,\"minzoom\":4},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\"},\"id\":\"water_polygons_labels\",\"maxzoom\":14,\"minzoom\":14}],\"version\":\"3.0\"}"
+			"{\"author\":\"OpenStreetMap contributors, Geofabrik GmbH\",\"bounds\":[13.08283,52.33446,13.762245,52.6783],\"center\":[13.422538,52.50638,7],\"description\":\"Tile config for simple vector tiles schema\",\"license\":\"Open Database License 1.0\",\"maxzoom\":14,\"minzoom\":0,\"name\":\"Tilemaker to Geofabrik Vector Tiles schema\",\"tilejson\":\"3.0.0\",\"type\":\"baselayer\",\"vector_layers\":[{\"fields\":{\"name\":\"String\",\"number\":\"String\"},\"id\":\"addresses\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"kind\":\"String\"},\"id\":\"aerialways\",\"maxzoom\":14,\"minzoom\":12},{\"fields\":{\"admin_level\":\"Number\",\"maritime\":\"Boolean\"},\"id\":\"boundaries\",\"maxzoom\":14,\"minzoom\":0},{\"fields\":{\"admin_level\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\",\"way_area\":\"Number\"},\"id\":\"boundary_labels\",\"maxzoom\":14,\"minzoom\":2},{\"fields\":{\"dummy\":\"Number\"},\"id\":\"buildings\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"kind\":\"String\"},\"id\":\"land\",\"maxzoom\":14,\"minzoom\":7},{\"fields\":{},\"id\":\"ocean\",\"maxzoom\":14,\"minzoom\":8},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\",\"population\":\"Number\"},\"id\":\"place_labels\",\"maxzoom\":14,\"minzoom\":3},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\"},\"id\":\"public_transport\",\"maxzoom\":14,\"minzoom\":11},{\"fields\":{\"kind\":\"String\"},\"id\":\"sites\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\",\"ref\":\"String\",\"ref_cols\":\"Number\",\"ref_rows\":\"Number\",\"tunnel\":\"Boolean\"},\"id\":\"street_labels\",\"maxzoom\":14,\"minzoom\":10},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\",\"ref\":\"String\"},\"id\":\"street_labels_points\",\"maxzoom\":14,\"minzoom\":12},{\"fields\":{\"bridge\":\"Boolean\",\"kind\":\"String\",\"rail\":\"Boolean\",\"service\":\"String\",\"surface\":\"String\",\"tunnel\":\"Boolean\"},\"id\":\"street_polygons\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"bicycle\":\"String\",\"bridge\":\"Boolean\",\"horse\":\"String\",\"kind\":\"String\",\"link\":\"Boolean\",\"rail\":\"Boolean\",\"service\":\"String\",\"surface\":\"String\",\"tracktype\":\"String\",\"tunnel\":\"Boolean\"},\"id\":\"streets\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\"},\"id\":\"streets_polygons_labels\",\"maxzoom\":14,\"minzoom\":14},{\"fields\":{\"kind\":\"String\"},\"id\":\"water_lines\",\"maxzoom\":14,\"minzoom\":4},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\"},\"id\":\"water_lines_labels\",\"maxzoom\":14,\"minzoom\":4},{\"fields\":{\"kind\":\"String\"},\"id\":\"water_polygons\",\"maxzoom\":14,\"minzoom\":4},{\"fields\":{\"kind\":\"String\",\"name\":\"String\",\"name_de\":\"String\",\"name_en\":\"String\"},\"id\":\"water_polygons_labels\",\"maxzoom\":14,\"minzoom\":14}],\"version\":\"3.0\"}"
 		);
 		assert_eq!(
 			format!("{:?}", reader.parameters()),
@@ -473,6 +501,32 @@ pub mod tests {
 		Ok(())
 	}
 
+	#[tokio::test(flavor = "multi_thread")]
+	async fn concurrent_reads() -> Result<()> {
+		let reader = MBTilesReader::open_path_with_pool_size(&PATH, 2)?;
+
+		let coords = TileBBox::from_min_and_max(14, 8787, 5361, 8818, 5387)?
+			.iter_coords()
+			.take(64)
+			.collect::<Vec<_>>();
+		let tiles = futures::future::try_join_all(coords.iter().map(|coord| reader.get_tile(coord))).await?;
+		assert_eq!(tiles.len(), 64);
+		assert!(tiles.iter().any(Option::is_some));
+
+		// results match sequential reads
+		for (coord, tile) in coords.iter().zip(tiles) {
+			let expected = reader.get_tile(coord).await?;
+			assert_eq!(
+				tile.map(|t| t.into_blob(Gzip).unwrap()),
+				expected.map(|t| t.into_blob(Gzip).unwrap())
+			);
+		}
+
+		assert!(MBTilesReader::open_path_with_pool_size(&PATH, 0).is_err());
+
+		Ok(())
+	}
+
 	// Test tile fetching
 	#[cfg(feature = "cli")]
 	#[tokio::test]
@@ -495,6 +549,18 @@ pub mod tests {
 			"tiles:\n  deep tiles probing is not implemented for this container format\n"
 		);
 
+		let mut printer = PrettyPrint::new();
+		reader
+			.probe_tile_contents(&printer.get_category("tile contents").await)
+			.await?;
+		let output = printer.as_string().await;
+		assert!(
+			output.starts_with("tile contents:\n  level 0: 1 sampled tiles, "),
+			"{output}"
+		);
+		assert!(output.contains("\n  level 14: 253 sampled tiles, "), "{output}");
+		assert!(output.contains("\n    buildings: 36.9% (11521899 bytes)\n"), "{output}");
+
 		Ok(())
 	}
 }
@@ -49,7 +49,11 @@ use futures::lock::Mutex;
 use r2d2::Pool;
 use r2d2_sqlite::{SqliteConnectionManager, rusqlite::params};
 use std::{fs::remove_file, path::Path, sync::Arc};
-use versatiles_core::{io::DataWriterTrait, json::JsonObject, *};
+use versatiles_core::{
+	io::DataWriterTrait,
+	json::{JsonObject, JsonValue},
+	*,
+};
 use versatiles_derive::context;
 
 /// Writer for MBTiles (SQLite) containers.
@@ -168,14 +172,14 @@ impl TilesWriterTrait for MBTilesWriter {
 		writer.set_metadata("version", "3.0")?;
 		let pyramid = &reader.parameters().bbox_pyramid;
 		let bbox = pyramid.get_geo_bbox().unwrap();
-		let center = pyramid.get_geo_center().unwrap();
+		let center = reader.tilejson().center.or_else(|| pyramid.get_geo_center()).unwrap();
 		let zoom_min = pyramid.get_level_min().unwrap();
 		let zoom_max = pyramid.get_level_max().unwrap();
 		writer.set_metadata(
 			"bounds",
 			&format!("{},{},{},{}", bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max),
 		)?;
-		writer.set_metadata("center", &format!("{},{},{}", center.0, center.1, center.2))?;
+		writer.set_metadata("center", &center.to_string())?;
 		writer.set_metadata("minzoom", &zoom_min.to_string())?;
 		writer.set_metadata("maxzoom", &zoom_max.to_string())?;
 
@@ -193,6 +197,17 @@ impl TilesWriterTrait for MBTilesWriter {
 			}
 		}
 
+		// keep non-standard metadata, e.g. `generator` or `tippecanoe_decisions`
+		for (key, value) in tilejson.iter_extras() {
+			if key == "format" || key == "json" {
+				continue;
+			}
+			match value {
+				JsonValue::String(s) => writer.set_metadata(&key, &s)?,
+				other => writer.set_metadata(&key, &other.stringify())?,
+			}
+		}
+
 		let writer_mutex = Arc::new(Mutex::new(writer));
 		let tile_compression = reader.parameters().tile_compression;
 
@@ -0,0 +1,14 @@
+//! Keep tiles in memory
+//!
+//! This module provides an in-memory tile container, backed by a `HashMap` keyed by [`TileCoord`](versatiles_core::TileCoord).
+//! It is useful for tests, ephemeral pipelines and as an intermediate target when converting between containers.
+//!
+//! The main components of this module are:
+//! - `MemTilesReader`: Stores tiles in memory and serves them via `TilesReaderTrait`.
+//! - `MemTilesWriter`: Reads all tiles of another container into a `MemTilesReader`, and implements `TilesWriterTrait`.
+
+mod reader;
+mod writer;
+
+pub use reader::MemTilesReader;
+pub use writer::MemTilesWriter;
@@ -0,0 +1,231 @@
+//! This module provides an in-memory tile container that implements `TilesReaderTrait`.
+//!
+//! Tiles can be inserted one by one with [`MemTilesReader::insert`] or copied from another
+//! container with [`MemTilesWriter::write`](crate::MemTilesWriter::write). The bounding box
+//! pyramid and the TileJSON bounds are updated automatically.
+//!
+//! ## Usage
+//! ```rust
+//! use versatiles_container::*;
+//! use versatiles_core::*;
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let mut reader = MemTilesReader::new(TileFormat::JSON, TileCompression::Uncompressed);
+//!     let coord = TileCoord::new(3, 1, 2)?;
+//!     reader.insert(coord, Tile::from_blob(Blob::from("{}"), TileCompression::Uncompressed, TileFormat::JSON))?;
+//!
+//!     assert_eq!(reader.len(), 1);
+//!     assert!(reader.get_tile(&coord).await?.is_some());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Tile, TilesReaderTrait};
+use anyhow::{Result, anyhow, ensure};
+use async_trait::async_trait;
+use std::{collections::HashMap, fmt::Debug, path::Path};
+use versatiles_core::*;
+use versatiles_derive::context;
+
+/// A tile container that keeps all tiles in a `HashMap` in memory.
+pub struct MemTilesReader {
+	parameters: TilesReaderParameters,
+	tilejson: TileJSON,
+	tiles: HashMap<TileCoord, Tile>,
+}
+
+impl MemTilesReader {
+	/// Creates an empty in-memory container for tiles of the given format.
+	///
+	/// `tile_compression` is the compression of the tiles returned by `get_tile`.
+	pub fn new(tile_format: TileFormat, tile_compression: TileCompression) -> MemTilesReader {
+		let parameters = TilesReaderParameters::new(tile_format, tile_compression, TileBBoxPyramid::new_empty());
+		let mut tilejson = TileJSON::default();
+		tilejson.update_from_reader_parameters(&parameters);
+		MemTilesReader {
+			parameters,
+			tilejson,
+			tiles: HashMap::new(),
+		}
+	}
+
+	/// Takes the container that [`MemTilesWriter`](crate::MemTilesWriter) stored under `path`.
+	///
+	/// The container is removed from the store, so it can only be taken once.
+	#[context("taking memory container '{}'", path.display())]
+	pub fn take_path(path: &Path) -> Result<MemTilesReader> {
+		super::writer::take_stored(path).ok_or_else(|| anyhow!("no memory container stored under this path"))
+	}
+
+	/// Inserts a tile, replacing any existing tile at `coord`.
+	///
+	/// The tile must have the format of this container, but may use any compression.
+	#[context("inserting tile {:?} into memory container", coord)]
+	pub fn insert(&mut self, coord: TileCoord, tile: Tile) -> Result<()> {
+		ensure!(
+			tile.format() == self.parameters.tile_format,
+			"tile format {:?} does not match container format {:?}",
+			tile.format(),
+			self.parameters.tile_format
+		);
+		self.parameters.bbox_pyramid.include_coord(&coord);
+		self.tilejson.update_from_reader_parameters(&self.parameters);
+		self.tiles.insert(coord, tile);
+		Ok(())
+	}
+
+	/// Removes the tile at `coord` and returns it, if present.
+	///
+	/// The bounding box pyramid is not shrunk.
+	pub fn remove(&mut self, coord: &TileCoord) -> Option<Tile> {
+		self.tiles.remove(coord)
+	}
+
+	/// Replaces the TileJSON metadata, keeping bounds and zoom levels in sync with the stored tiles.
+	pub fn set_tilejson(&mut self, tilejson: TileJSON) {
+		self.tilejson = tilejson;
+		self.tilejson.update_from_reader_parameters(&self.parameters);
+	}
+
+	/// Returns the number of stored tiles.
+	pub fn len(&self) -> usize {
+		self.tiles.len()
+	}
+
+	/// Returns `true` if no tiles are stored.
+	pub fn is_empty(&self) -> bool {
+		self.tiles.is_empty()
+	}
+
+	/// Returns an iterator over all stored coordinates, in arbitrary order.
+	pub fn coords(&self) -> impl Iterator<Item = &TileCoord> {
+		self.tiles.keys()
+	}
+
+	/// Consumes the container and returns all tiles, in arbitrary order.
+	pub fn into_tiles(self) -> Vec<(TileCoord, Tile)> {
+		self.tiles.into_iter().collect()
+	}
+
+	fn prepare_tile(&self, tile: &Tile) -> Result<Tile> {
+		let mut tile = tile.clone();
+		tile.change_compression(self.parameters.tile_compression)?;
+		Ok(tile)
+	}
+}
+
+#[async_trait]
+impl TilesReaderTrait for MemTilesReader {
+	fn source_name(&self) -> &str {
+		"memory"
+	}
+
+	fn container_name(&self) -> &str {
+		"memory"
+	}
+
+	fn parameters(&self) -> &TilesReaderParameters {
+		&self.parameters
+	}
+
+	fn override_compression(&mut self, tile_compression: TileCompression) {
+		self.parameters.tile_compression = tile_compression;
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		&self.tilejson
+	}
+
+	#[context("getting tile {:?} from memory container", coord)]
+	async fn get_tile(&self, coord: &TileCoord) -> Result<Option<Tile>> {
+		self.tiles.get(coord).map(|tile| self.prepare_tile(tile)).transpose()
+	}
+
+	#[context("getting tile stream for {:?} from memory container", bbox)]
+	async fn get_tile_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		let mut tiles = self
+			.tiles
+			.iter()
+			.filter(|(coord, _)| bbox.contains(coord))
+			.map(|(coord, tile)| Ok((*coord, self.prepare_tile(tile)?)))
+			.collect::<Result<Vec<_>>>()?;
+		tiles.sort_by_key(|(coord, _)| coord.get_sort_index());
+		Ok(TileStream::from_vec(tiles))
+	}
+}
+
+impl Debug for MemTilesReader {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("MemTilesReader")
+			.field("parameters", &self.parameters)
+			.field("tiles", &self.tiles.len())
+			.finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use TileCompression::*;
+
+	fn json_tile(coord: &TileCoord, compression: TileCompression) -> Tile {
+		let blob = utils::compress(Blob::from(coord.as_json()), compression).unwrap();
+		Tile::from_blob(blob, compression, TileFormat::JSON)
+	}
+
+	#[tokio::test]
+	async fn insert_and_get() -> Result<()> {
+		let mut reader = MemTilesReader::new(TileFormat::JSON, Uncompressed);
+		assert!(reader.is_empty());
+
+		let c1 = TileCoord::new(2, 1, 3)?;
+		let c2 = TileCoord::new(4, 5, 6)?;
+		reader.insert(c1, json_tile(&c1, Uncompressed))?;
+		reader.insert(c2, json_tile(&c2, Gzip))?;
+		assert_eq!(reader.len(), 2);
+		assert_eq!(reader.parameters().bbox_pyramid.get_level_min(), Some(2));
+		assert_eq!(reader.parameters().bbox_pyramid.get_level_max(), Some(4));
+
+		// tiles are returned in the container compression
+		let tile = reader.get_tile(&c2).await?.unwrap();
+		assert_eq!(tile.compression(), Uncompressed);
+		assert_eq!(tile.into_blob(Uncompressed)?.as_str(), "{x:5,y:6,z:4}");
+
+		reader.override_compression(Brotli);
+		assert_eq!(reader.get_tile(&c1).await?.unwrap().compression(), Brotli);
+
+		assert!(reader.get_tile(&TileCoord::new(2, 0, 0)?).await?.is_none());
+		assert!(reader.remove(&c1).is_some());
+		assert!(reader.get_tile(&c1).await?.is_none());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn rejects_wrong_format() -> Result<()> {
+		let mut reader = MemTilesReader::new(TileFormat::PNG, Uncompressed);
+		let coord = TileCoord::new(0, 0, 0)?;
+		assert!(reader.insert(coord, json_tile(&coord, Uncompressed)).is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn tile_stream() -> Result<()> {
+		let mut reader = MemTilesReader::new(TileFormat::JSON, Uncompressed);
+		for coord in TileBBox::new_full(2)?.iter_coords() {
+			reader.insert(coord, json_tile(&coord, Uncompressed))?;
+		}
+
+		let bbox = TileBBox::from_min_and_max(2, 1, 1, 2, 3)?;
+		let coords = reader
+			.get_tile_stream(bbox)
+			.await?
+			.to_vec()
+			.await
+			.into_iter()
+			.map(|(coord, _)| (coord.x, coord.y))
+			.collect::<Vec<_>>();
+		assert_eq!(coords, [(1, 1), (2, 1), (1, 2), (2, 2), (1, 3), (2, 3)]);
+		Ok(())
+	}
+}
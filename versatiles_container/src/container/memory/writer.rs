@@ -0,0 +1,200 @@
+//! This module provides functionality for copying all tiles of a container into memory.
+//!
+//! [`MemTilesWriter::write`] returns the copied tiles directly as a [`MemTilesReader`].
+//! To convert an in-memory container into any other format, pass the `MemTilesReader` to
+//! [`ContainerRegistry::write_to_path`](crate::ContainerRegistry::write_to_path).
+//!
+//! `MemTilesWriter` also implements [`TilesWriterTrait`], so it can be used wherever a writer is
+//! generic over the output format. `write_to_path` keeps the copied container in a process-wide
+//! store under the given path, from where [`MemTilesReader::take_path`] moves it out again.
+//! A stored container stays in memory until it is taken or dropped with [`MemTilesWriter::remove_path`],
+//! otherwise it lives until the process ends.
+//! An in-memory container has no byte representation, so `write_to_writer` fails.
+//!
+//! ## Usage
+//! ```rust
+//! use versatiles_container::*;
+//! use versatiles_core::*;
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let registry = ContainerRegistry::default();
+//!     let mut reader = registry.open_reader("../testdata/berlin.mbtiles").await?;
+//!
+//!     // copy all tiles into memory …
+//!     let memory = MemTilesWriter::write(reader.as_mut(), ProcessingConfig::default()).await?;
+//!
+//!     // … and write them into another container
+//!     let output = std::env::temp_dir().join("memory_example.versatiles");
+//!     registry.write_to_path(Box::new(memory), &output).await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{MemTilesReader, ProcessingConfig, TilesReaderTrait, TilesReaderTraverseExt, TilesWriterTrait};
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use std::{
+	collections::HashMap,
+	path::{Path, PathBuf},
+	sync::{Arc, LazyLock, Mutex},
+};
+use versatiles_core::{Traversal, io::DataWriterTrait};
+use versatiles_derive::context;
+
+/// Containers written with [`TilesWriterTrait::write_to_path`], keyed by their path.
+///
+/// Entries are only removed by [`take_stored`] and [`MemTilesWriter::remove_path`].
+static STORE: LazyLock<Mutex<HashMap<PathBuf, MemTilesReader>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Removes the container stored under `path` by [`MemTilesWriter`] and returns it.
+pub(crate) fn take_stored(path: &Path) -> Option<MemTilesReader> {
+	STORE.lock().unwrap().remove(path)
+}
+
+/// Copies tiles from any container into a [`MemTilesReader`].
+pub struct MemTilesWriter {}
+
+impl MemTilesWriter {
+	/// Reads all tiles and the TileJSON from `reader` and stores them in a new [`MemTilesReader`].
+	///
+	/// Tiles are kept in the compression of the source.
+	#[context("writing tiles from '{}' into memory", reader.source_name())]
+	pub async fn write(reader: &mut dyn TilesReaderTrait, config: ProcessingConfig) -> Result<MemTilesReader> {
+		let parameters = reader.parameters().clone();
+		let collected = Arc::new(Mutex::new(Vec::new()));
+
+		let collected_clone = collected.clone();
+		reader
+			.traverse_all_tiles(
+				&Traversal::ANY,
+				move |_bbox, stream| {
+					let collected = collected_clone.clone();
+					Box::pin(async move {
+						let tiles = stream.to_vec().await;
+						collected.lock().unwrap().extend(tiles);
+						Ok(())
+					})
+				},
+				config,
+			)
+			.await?;
+
+		let mut memory = MemTilesReader::new(parameters.tile_format, parameters.tile_compression);
+		for (coord, tile) in std::mem::take(&mut *collected.lock().unwrap()) {
+			memory.insert(coord, tile)?;
+		}
+		memory.set_tilejson(reader.tilejson().clone());
+
+		Ok(memory)
+	}
+
+	/// Drops the container that [`TilesWriterTrait::write_to_path`] stored under `path`.
+	///
+	/// Returns `false` if no container was stored there.
+	pub fn remove_path(path: &Path) -> bool {
+		STORE.lock().unwrap().remove(path).is_some()
+	}
+}
+
+#[async_trait]
+impl TilesWriterTrait for MemTilesWriter {
+	/// Copies all tiles into memory and stores them under `path`, replacing any container stored there.
+	///
+	/// Nothing is written to the filesystem. Use [`MemTilesReader::take_path`] to get the container back,
+	/// or [`MemTilesWriter::remove_path`] to free it. Until then it stays in memory.
+	#[context("writing tiles into memory under '{}'", path.display())]
+	async fn write_to_path(reader: &mut dyn TilesReaderTrait, path: &Path, config: ProcessingConfig) -> Result<()> {
+		let memory = MemTilesWriter::write(reader, config).await?;
+		STORE.lock().unwrap().insert(path.to_path_buf(), memory);
+		Ok(())
+	}
+
+	/// Always fails, because an in-memory container has no byte representation.
+	async fn write_to_writer(
+		_reader: &mut dyn TilesReaderTrait,
+		_writer: &mut dyn DataWriterTrait,
+		_config: ProcessingConfig,
+	) -> Result<()> {
+		bail!("in-memory containers can not be serialized to a byte sink, use 'write_to_path' instead")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ContainerRegistry, DataLocation, MockTilesReader, MockTilesReaderProfile};
+	use assert_fs::TempDir;
+
+	#[tokio::test]
+	async fn write_from_mock() -> Result<()> {
+		let mut reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?;
+		let memory = MemTilesWriter::write(&mut reader, ProcessingConfig::default()).await?;
+
+		let count = reader.parameters().bbox_pyramid.count_tiles();
+		assert_eq!(memory.len() as u64, count);
+		assert_eq!(memory.parameters().bbox_pyramid.count_tiles(), count);
+		assert_eq!(memory.parameters().tile_format, reader.parameters().tile_format);
+		assert!(memory.tilejson().as_string().contains("\"type\":\"dummy\""));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn roundtrip_through_versatiles() -> Result<()> {
+		let mut reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Pbf)?;
+		let memory = MemTilesWriter::write(&mut reader, ProcessingConfig::default()).await?;
+		let count = memory.len();
+
+		let dir = TempDir::new()?;
+		let path = dir.path().join("memory.versatiles");
+		let registry = ContainerRegistry::default();
+		registry.write_to_path(Box::new(memory), &path).await?;
+
+		let mut reader = registry.get_reader(DataLocation::from(path.as_path())).await?;
+		let memory = MemTilesWriter::write(reader.as_mut(), ProcessingConfig::default()).await?;
+		assert_eq!(memory.len(), count);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn write_to_path_stores_container() -> Result<()> {
+		async fn write_generic<W: TilesWriterTrait>(reader: &mut dyn TilesReaderTrait, path: &Path) -> Result<()> {
+			W::write_to_path(reader, path, ProcessingConfig::default()).await
+		}
+
+		let mut reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Pbf)?;
+		let path = Path::new("memory_writer_test/write_to_path");
+		write_generic::<MemTilesWriter>(&mut reader, path).await?;
+		assert!(!path.exists());
+
+		let memory = MemTilesReader::take_path(path)?;
+		assert_eq!(memory.len() as u64, reader.parameters().bbox_pyramid.count_tiles());
+
+		// the container is moved out of the store
+		assert!(MemTilesReader::take_path(path).is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn remove_path_frees_container() -> Result<()> {
+		let mut reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Pbf)?;
+		let path = Path::new("memory_writer_test/remove_path");
+		MemTilesWriter::write_to_path(&mut reader, path, ProcessingConfig::default()).await?;
+
+		assert!(MemTilesWriter::remove_path(path));
+		assert!(!MemTilesWriter::remove_path(path));
+		assert!(MemTilesReader::take_path(path).is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn write_to_writer_fails() -> Result<()> {
+		let mut reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Pbf)?;
+		let mut writer = versatiles_core::io::DataWriterBlob::new()?;
+		let error = MemTilesWriter::write_to_writer(&mut reader, &mut writer, ProcessingConfig::default())
+			.await
+			.unwrap_err();
+		assert!(error.to_string().contains("byte sink"));
+		Ok(())
+	}
+}
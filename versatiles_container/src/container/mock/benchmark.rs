@@ -0,0 +1,186 @@
+//! Read/write throughput measurements for container backends
+//!
+//! Writes a deterministic fixture (see [`write_mock_container`]) for each container type and measures
+//! how long writing takes, the mean latency of single [`get_tile`](crate::TilesReaderTrait::get_tile)
+//! calls, and the throughput of sequential tile streams. Used by the criterion benchmarks and the
+//! `versatiles bench` command, so results are comparable across releases.
+//!
+//! ```rust
+//! use versatiles_container::*;
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let dir = tempfile::tempdir()?;
+//!     let result = benchmark_container(dir.path(), "versatiles", MockTilesReaderProfile::Pbf, 4, 16).await?;
+//!     assert_eq!(result.tile_count, 341);
+//!     println!("{result}");
+//!     Ok(())
+//! }
+//! ```
+
+use super::{MockTilesReaderProfile, write_mock_container};
+use crate::{ContainerRegistry, DataLocation, TilesReaderTrait};
+use anyhow::{Result, ensure};
+use std::{
+	fmt::Display,
+	path::{Path, PathBuf},
+	time::{Duration, Instant},
+};
+use versatiles_core::{TileBBoxPyramid, TileCoord};
+use versatiles_derive::context;
+
+/// Container types that can be benchmarked. `directory` writes one file per tile.
+pub const BENCHMARK_CONTAINER_TYPES: [&str; 5] = ["versatiles", "pmtiles", "mbtiles", "tar", "directory"];
+
+/// Results of benchmarking a single container type.
+#[derive(Clone, Debug)]
+pub struct ContainerBenchmark {
+	/// Container type, e.g. `"pmtiles"`.
+	pub container_type: String,
+	/// Number of tiles in the fixture.
+	pub tile_count: u64,
+	/// Time needed to write the fixture.
+	pub write_time: Duration,
+	/// Mean duration of a single `get_tile` call.
+	pub read_latency: Duration,
+	/// Tiles per second when streaming all tiles level by level.
+	pub stream_throughput: f64,
+}
+
+impl Display for ContainerBenchmark {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{}: {} tiles, write {:.1?}, read latency {:.1?}, stream {:.0} tiles/s",
+			self.container_type, self.tile_count, self.write_time, self.read_latency, self.stream_throughput
+		)
+	}
+}
+
+/// Returns the path of the fixture for `container_type` inside `dir`.
+#[must_use]
+pub fn benchmark_fixture_path(dir: &Path, container_type: &str) -> PathBuf {
+	if container_type == "directory" {
+		dir.join("fixture")
+	} else {
+		dir.join(format!("fixture.{container_type}"))
+	}
+}
+
+/// Writes the fixture for `container_type` into `dir` and returns its path.
+#[context("writing benchmark fixture '{}' into {:?}", container_type, dir)]
+pub async fn write_benchmark_fixture(
+	dir: &Path,
+	container_type: &str,
+	profile: MockTilesReaderProfile,
+	max_zoom_level: u8,
+) -> Result<PathBuf> {
+	ensure!(
+		BENCHMARK_CONTAINER_TYPES.contains(&container_type),
+		"unknown container type '{container_type}', expected one of {BENCHMARK_CONTAINER_TYPES:?}"
+	);
+	let path = benchmark_fixture_path(dir, container_type);
+	if container_type == "directory" {
+		std::fs::create_dir_all(&path)?;
+	}
+	write_mock_container(&path, profile, max_zoom_level).await?;
+	Ok(path)
+}
+
+/// Returns up to `count` coordinates, evenly spread over all tiles of `bbox_pyramid`.
+///
+/// The selection only depends on the pyramid, so repeated runs read the same tiles.
+#[must_use]
+pub fn sample_coords(bbox_pyramid: &TileBBoxPyramid, count: usize) -> Vec<TileCoord> {
+	let step = (bbox_pyramid.count_tiles() as usize / count.max(1)).max(1);
+	bbox_pyramid
+		.iter_levels()
+		.flat_map(|bbox| bbox.iter_coords())
+		.step_by(step)
+		.take(count)
+		.collect()
+}
+
+/// Reads each of `coords` with `get_tile` and returns the mean duration of a call.
+#[context("measuring read latency of '{}'", reader.source_name())]
+pub async fn measure_read_latency(reader: &dyn TilesReaderTrait, coords: &[TileCoord]) -> Result<Duration> {
+	ensure!(!coords.is_empty(), "no coordinates to read");
+	let start = Instant::now();
+	for coord in coords {
+		ensure!(reader.get_tile(coord).await?.is_some(), "tile {coord:?} is missing");
+	}
+	Ok(start.elapsed() / coords.len() as u32)
+}
+
+/// Streams all tiles level by level and returns the number of tiles and the time it took.
+#[context("measuring stream throughput of '{}'", reader.source_name())]
+pub async fn measure_stream_throughput(reader: &dyn TilesReaderTrait) -> Result<(u64, Duration)> {
+	let start = Instant::now();
+	let mut count = 0;
+	for bbox in reader.parameters().bbox_pyramid.iter_levels() {
+		count += reader.get_tile_stream(*bbox).await?.drain_and_count().await;
+	}
+	Ok((count, start.elapsed()))
+}
+
+/// Writes a fixture for `container_type` into `dir` and measures write time, read latency
+/// (over `samples` tiles) and stream throughput.
+#[context("benchmarking container '{}'", container_type)]
+pub async fn benchmark_container(
+	dir: &Path,
+	container_type: &str,
+	profile: MockTilesReaderProfile,
+	max_zoom_level: u8,
+	samples: usize,
+) -> Result<ContainerBenchmark> {
+	let start = Instant::now();
+	let path = write_benchmark_fixture(dir, container_type, profile, max_zoom_level).await?;
+	let write_time = start.elapsed();
+
+	let reader = ContainerRegistry::default()
+		.get_reader(DataLocation::from(path.as_path()))
+		.await?;
+	let coords = sample_coords(&reader.parameters().bbox_pyramid, samples);
+	let read_latency = measure_read_latency(reader.as_ref(), &coords).await?;
+	let (tile_count, stream_time) = measure_stream_throughput(reader.as_ref()).await?;
+
+	Ok(ContainerBenchmark {
+		container_type: container_type.to_string(),
+		tile_count,
+		write_time,
+		read_latency,
+		stream_throughput: tile_count as f64 / stream_time.as_secs_f64().max(f64::EPSILON),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use assert_fs::TempDir;
+
+	#[test]
+	fn sample_coords_are_spread() {
+		let pyramid = TileBBoxPyramid::new_full(3);
+		let coords = sample_coords(&pyramid, 10);
+		assert_eq!(coords.len(), 10);
+		assert_eq!(coords[0], TileCoord::new(0, 0, 0).unwrap());
+		assert_eq!(coords[9].level, 3);
+		assert_eq!(sample_coords(&pyramid, 1000).len(), 85);
+	}
+
+	#[tokio::test]
+	async fn benchmark_all_container_types() -> Result<()> {
+		let dir = TempDir::new()?;
+		for container_type in BENCHMARK_CONTAINER_TYPES {
+			let result = benchmark_container(dir.path(), container_type, MockTilesReaderProfile::Png, 3, 8).await?;
+			assert_eq!(result.tile_count, 85, "for '{container_type}'");
+			assert!(result.stream_throughput > 0.0);
+		}
+		assert!(
+			write_benchmark_fixture(dir.path(), "zip", MockTilesReaderProfile::Png, 1)
+				.await
+				.is_err()
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,117 @@
+//! Deterministic test fixtures
+//!
+//! Writes small synthetic tile containers to disk, so downstream crates can write integration tests
+//! without shipping binary fixtures. The container type is derived from the file extension
+//! (e.g. `*.versatiles`, `*.pmtiles`, `*.mbtiles`, `*.tar` or a directory).
+//!
+//! The tiles are generated by [`MockTilesReader`], so every call with the same arguments
+//! produces a container with identical tiles and metadata.
+//!
+//! ```rust
+//! use versatiles_container::*;
+//! use versatiles_core::*;
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let path = std::env::temp_dir().join("fixture_example.pmtiles");
+//!     write_mock_container(&path, MockTilesReaderProfile::Pbf, 3).await?;
+//!
+//!     let reader = ContainerRegistry::default().get_reader(DataLocation::from(path.as_path())).await?;
+//!     assert_eq!(reader.parameters().tile_format, TileFormat::MVT);
+//!     Ok(())
+//! }
+//! ```
+
+use super::{MockTilesReader, MockTilesReaderProfile};
+use crate::ContainerRegistry;
+use anyhow::Result;
+use std::path::Path;
+use versatiles_core::{TileBBoxPyramid, TileCompression, TileFormat, TilesReaderParameters};
+use versatiles_derive::context;
+
+/// Writes a mock container with the tile format and compression of `profile`,
+/// containing all tiles from zoom level 0 up to `max_zoom_level`.
+#[context("writing mock container {:?} with profile {:?}", path, profile)]
+pub async fn write_mock_container(path: &Path, profile: MockTilesReaderProfile, max_zoom_level: u8) -> Result<()> {
+	let (tile_format, tile_compression) = match profile {
+		MockTilesReaderProfile::Json => (TileFormat::JSON, TileCompression::Uncompressed),
+		MockTilesReaderProfile::Png => (TileFormat::PNG, TileCompression::Uncompressed),
+		MockTilesReaderProfile::Pbf => (TileFormat::MVT, TileCompression::Gzip),
+	};
+	write_mock_container_with_parameters(
+		path,
+		TilesReaderParameters::new(tile_format, tile_compression, TileBBoxPyramid::new_full(max_zoom_level)),
+	)
+	.await
+}
+
+/// Writes a mock container using custom reader `parameters`, e.g. to restrict the bounding box pyramid.
+#[context("writing mock container {:?}", path)]
+pub async fn write_mock_container_with_parameters(path: &Path, parameters: TilesReaderParameters) -> Result<()> {
+	let reader = MockTilesReader::new_mock(parameters)?;
+	ContainerRegistry::default().write_to_path(Box::new(reader), path).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::DataLocation;
+	use assert_fs::TempDir;
+	use versatiles_core::TileCoord;
+
+	async fn read_all(path: &Path) -> Result<Vec<(TileCoord, Vec<u8>)>> {
+		let reader = ContainerRegistry::default()
+			.get_reader(DataLocation::from(path))
+			.await?;
+		let bbox_pyramid = reader.parameters().bbox_pyramid.clone();
+		let mut tiles = Vec::new();
+		for bbox in bbox_pyramid.iter_levels() {
+			for coord in bbox.iter_coords() {
+				if let Some(tile) = reader.get_tile(&coord).await? {
+					tiles.push((coord, tile.into_blob(TileCompression::Uncompressed)?.into_vec()));
+				}
+			}
+		}
+		Ok(tiles)
+	}
+
+	#[tokio::test]
+	async fn fixtures_are_deterministic() -> Result<()> {
+		let dir = TempDir::new()?;
+
+		for ext in ["versatiles", "pmtiles"] {
+			let path1 = dir.path().join(format!("a.{ext}"));
+			let path2 = dir.path().join(format!("b.{ext}"));
+			write_mock_container(&path1, MockTilesReaderProfile::Pbf, 2).await?;
+			write_mock_container(&path2, MockTilesReaderProfile::Pbf, 2).await?;
+
+			let tiles = read_all(&path1).await?;
+			assert_eq!(tiles.len(), 21, "for '{ext}'");
+			assert_eq!(tiles, read_all(&path2).await?, "for '{ext}'");
+		}
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn fixture_with_parameters() -> Result<()> {
+		let dir = TempDir::new()?;
+		let path = dir.path().join("fixture.versatiles");
+
+		let mut bbox_pyramid = TileBBoxPyramid::new_full(3);
+		bbox_pyramid.set_level_min(3);
+		write_mock_container_with_parameters(
+			&path,
+			TilesReaderParameters::new(TileFormat::PNG, TileCompression::Uncompressed, bbox_pyramid),
+		)
+		.await?;
+
+		let reader = ContainerRegistry::default()
+			.get_reader(DataLocation::from(path.as_path()))
+			.await?;
+		assert_eq!(reader.parameters().tile_format, TileFormat::PNG);
+		assert_eq!(reader.parameters().bbox_pyramid.get_level_min(), Some(3));
+		assert_eq!(read_all(&path).await?.len(), 64);
+		Ok(())
+	}
+}
@@ -5,12 +5,18 @@
 //! ## Submodules
 //! - `reader`: Contains mock implementations of tile readers.
 //! - `writer`: Contains mock implementations of tile writers.
+//! - `fixture`: Writes small deterministic tile containers to disk.
+//! - `benchmark`: Measures read/write throughput of container backends using these fixtures.
 //!
 //! ## Usage
 //! These mocks can be used to simulate tile reading and writing operations in tests, allowing you to verify the behavior of your code without relying on actual tile data or I/O operations.
 
+mod benchmark;
+mod fixture;
 mod reader;
 mod writer;
 
+pub use benchmark::*;
+pub use fixture::*;
 pub use reader::*;
 pub use writer::*;
@@ -29,7 +29,7 @@ use versatiles_core::{utils::compress, *};
 use versatiles_derive::context;
 
 /// Enum representing different mock profiles for tile data.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MockTilesReaderProfile {
 	/// Mock profile for JSON format.
 	Json,
@@ -2,34 +2,62 @@
 //!
 //! ## Supported tile container formats
 //!
-//! | Format         | Read | Write | Feature   |
-//! |----------------|:----:|:-----:|-----------|
-//! | `*.versatiles` | ✅   | ✅     | `default` |
-//! | `*.mbtiles`    | ✅   | ✅     | `full`    |
-//! | `*.pmtiles`    | ✅   | ✅     | `full`    |
-//! | `*.tar`        | ✅   | ✅     | `full`    |
-//! | directory      | ✅   | ✅     | `default` |
-//! | pipeline       | ✅   | ❌     | `full`    |
+//! | Format         | Read | Write | Feature      |
+//! |----------------|:----:|:-----:|--------------|
+//! | `*.versatiles` | ✅   | ✅     | `versatiles` |
+//! | `*.mbtiles`    | ✅   | ✅     | `mbtiles`    |
+//! | `*.pmtiles`    | ✅   | ✅     | `pmtiles`    |
+//! | `*.tar`        | ✅   | ✅     | `tar`        |
+//! | `*.zip`        | ✅   | ✅     | `zip`        |
+//! | directory      | ✅   | ✅     | `directory`  |
+//! | in-memory      | ✅   | ✅     | -            |
+//! | composite      | ✅   | ❌     | -            |
+//! | tee            | ✅   | ❌     | -            |
 //!
 //! This module provides a unified interface for reading and writing various tile container formats.
-//! Depending on the enabled features, it supports different formats with corresponding read and write capabilities.
+//! Each file backend is behind a cargo feature of the same name. All of them are enabled by default;
+//! disable the default features to build only the backends you need, e.g.
+//! `versatiles_container = { default-features = false, features = ["versatiles"] }`.
+//! The [`ContainerRegistry`](crate::ContainerRegistry) only knows the enabled backends.
 
+mod composite;
+pub use composite::*;
+
+#[cfg(feature = "mbtiles")]
 mod mbtiles;
+#[cfg(feature = "mbtiles")]
 pub use mbtiles::*;
 
-#[cfg(any(test, feature = "test"))]
+mod memory;
+pub use memory::*;
+
+#[cfg(any(test, feature = "mock"))]
 mod mock;
-#[cfg(any(test, feature = "test"))]
+#[cfg(any(test, feature = "mock"))]
 pub use mock::*;
 
+#[cfg(feature = "pmtiles")]
 mod pmtiles;
+#[cfg(feature = "pmtiles")]
 pub use pmtiles::*;
 
+#[cfg(feature = "tar")]
 mod tar;
+#[cfg(feature = "tar")]
 pub use tar::*;
 
+mod tee;
+pub use tee::*;
+
 mod directory;
 pub use directory::*;
 
+#[cfg(feature = "versatiles")]
 mod versatiles;
+#[cfg(feature = "versatiles")]
 pub use versatiles::*;
+
+#[cfg(feature = "zip")]
+mod zip;
+#[cfg(feature = "zip")]
+pub use zip::*;
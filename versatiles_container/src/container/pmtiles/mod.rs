@@ -20,3 +20,6 @@ mod writer;
 
 pub use reader::PMTilesReader;
 pub use writer::PMTilesWriter;
+
+#[cfg(feature = "arbitrary")]
+pub(crate) use types::{EntriesV3, HeaderV3};
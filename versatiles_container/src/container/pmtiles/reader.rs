@@ -12,6 +12,16 @@
 //! - `parameters`: [`TilesReaderParameters`] with `tile_format`, `tile_compression`, and a
 //!   computed **bbox pyramid** inferred from the directory tree
 //!
+//! ## Remote containers
+//! Opening reads the first 16 KiB with a single request, which includes the header, the root
+//! directory and usually the metadata, so remote containers open with as few round trips as possible.
+//!
+//! All leaf directories are then read with one more request, because the bbox pyramid is computed
+//! from them. So there are no leaf directories left to prefetch from a remote source, and prefetching
+//! based on access patterns would not save a single request. Each leaf directory is decompressed and
+//! parsed on first use and kept in a cache of 100 MB, so frequently used directories stay ready.
+//! [`TilesReaderTrait::prefetch`] parses the leaf directories of an upcoming bbox ahead of time.
+//!
 //! ## Requirements
 //! - Use an **absolute** filesystem path when opening via [`open_path`].
 //! - The container must be a valid PMTiles v3 file with readable header, directories, and data.
@@ -45,8 +55,8 @@
 //! Returns errors when the path is not absolute, the file cannot be read, the
 //! PMTiles header/directories cannot be parsed or decompressed, or a requested tile is missing.
 
-use super::types::{EntriesV3, HeaderV3};
-use crate::{Tile, TilesReaderTrait};
+use super::types::{EntriesV3, EntryV3, HeaderV3};
+use crate::{ContainerError, ReaderLimits, Tile, TilesReaderTrait};
 use anyhow::{Result, bail};
 use async_trait::async_trait;
 use futures::lock::Mutex;
@@ -56,7 +66,7 @@ use versatiles_core::utils::PrettyPrint;
 use versatiles_core::{
 	io::*,
 	progress::get_progress_bar,
-	utils::{HilbertIndex, decompress},
+	utils::{HilbertIndex, decompress_limited},
 	*,
 };
 use versatiles_derive::context;
@@ -86,6 +96,8 @@ pub struct PMTilesReader {
 	pub root_bytes_uncompressed: Blob,
 	/// Parsed entries of the root directory (shared across queries).
 	pub root_entries: Arc<EntriesV3>,
+	/// Limits for directory sizes and nesting, checked while parsing.
+	pub limits: ReaderLimits,
 }
 
 impl PMTilesReader {
@@ -108,37 +120,53 @@ impl PMTilesReader {
 	///
 	/// # Errors
 	/// Returns an error if reading or decompression fails, or if the header/dirs are invalid.
+	pub async fn open_reader(data_reader: DataReader) -> Result<PMTilesReader> {
+		PMTilesReader::open_reader_with_limits(data_reader, ReaderLimits::default()).await
+	}
+
+	/// Open a PMTiles container like [`PMTilesReader::open_reader`], but with custom [`ReaderLimits`].
+	///
+	/// Use stricter limits when serving files from untrusted sources.
+	///
+	/// # Errors
+	/// Returns a [`ContainerError`] if the file exceeds the `limits` or is inconsistent,
+	/// or any other error if reading or decompression fails.
 	#[context("opening PMTiles from reader")]
-	pub async fn open_reader(data_reader: DataReader) -> Result<PMTilesReader>
-	where
-		Self: Sized,
-	{
+	pub async fn open_reader_with_limits(data_reader: DataReader, limits: ReaderLimits) -> Result<PMTilesReader> {
 		log::debug!("Opening PMTilesReader for {}", data_reader.get_name());
 
-		let header = HeaderV3::deserialize(&data_reader.read_range(&ByteRange::new(0, HeaderV3::len())).await?)?;
+		let initial_bytes = read_initial_bytes(&data_reader, &limits).await?;
+		log::trace!("Initial bytes length: {}", initial_bytes.len());
+
+		let header = HeaderV3::deserialize(&initial_bytes.read_range(&ByteRange::new(0, HeaderV3::len()))?)?;
 		log::trace!("Header: {:?}", header);
 
 		let internal_compression = header.internal_compression.as_value()?;
 		log::trace!("Internal compression: {:?}", internal_compression);
 
-		let meta = data_reader.read_range(&header.metadata).await?;
-		let meta = decompress(meta, internal_compression)?;
+		for range in [&header.metadata, &header.root_dir, &header.leaf_dirs] {
+			limits.check_range(range)?;
+		}
+		let max_size = limits.max_decompressed_size;
+
+		let meta = read_range(&data_reader, &initial_bytes, &header.metadata).await?;
+		let meta = decompress_limited(meta, internal_compression, max_size)?;
 		let tilejson = TileJSON::try_from_blob_or_default(&meta);
 		log::trace!("TileJSON: {:?}", tilejson);
 
-		let root_bytes = data_reader.read_range(&header.root_dir).await?;
+		let root_bytes = read_range(&data_reader, &initial_bytes, &header.root_dir).await?;
 		log::trace!("Root directory bytes length: {}", root_bytes.len());
 
-		let root_bytes_uncompressed = decompress(root_bytes, internal_compression)?;
+		let root_bytes_uncompressed = decompress_limited(root_bytes, internal_compression, max_size)?;
 		log::trace!(
 			"Root directory bytes uncompressed length: {}",
 			root_bytes_uncompressed.len()
 		);
 
-		let leaves_bytes = data_reader.read_range(&header.leaf_dirs).await?;
+		let leaves_bytes = read_range(&data_reader, &initial_bytes, &header.leaf_dirs).await?;
 		log::trace!("Leaf directories bytes length: {}", leaves_bytes.len());
 
-		let bbox_pyramid = calc_bbox_pyramid(&root_bytes_uncompressed, &leaves_bytes, internal_compression)?;
+		let bbox_pyramid = calc_bbox_pyramid(&root_bytes_uncompressed, &leaves_bytes, internal_compression, &limits)?;
 		log::trace!("Bounding box pyramid: {:?}", bbox_pyramid);
 
 		let parameters = TilesReaderParameters::new(
@@ -148,7 +176,7 @@ impl PMTilesReader {
 		);
 		log::trace!("Reader parameters: {:?}", parameters);
 
-		let root_entries = Arc::new(EntriesV3::from_blob(&root_bytes_uncompressed)?);
+		let root_entries = Arc::new(EntriesV3::from_blob_limited(&root_bytes_uncompressed, &limits)?);
 
 		Ok(PMTilesReader {
 			data_reader,
@@ -160,56 +188,180 @@ impl PMTilesReader {
 			parameters,
 			root_bytes_uncompressed,
 			root_entries,
+			limits,
 		})
 	}
 
 	/// Decode and return the root directory entries (`EntriesV3`).
 	#[context("reading PMTiles root entries")]
 	pub fn get_tile_entries(&self) -> Result<EntriesV3> {
-		EntriesV3::from_blob(&self.root_bytes_uncompressed)
+		EntriesV3::from_blob_limited(&self.root_bytes_uncompressed, &self.limits)
+	}
+
+	/// Find the entry of the run that contains `tile_id`, following up to
+	/// `max_directory_depth` levels of leaf directories.
+	///
+	/// Returns `Ok(None)` if the tile does not exist.
+	async fn find_tile_entry(&self, tile_id: u64) -> Result<Option<EntryV3>> {
+		// Start with the root directory entries
+		let mut entries = self.root_entries.clone();
+
+		for _depth in 0..self.limits.max_directory_depth {
+			// Find the entry corresponding to the requested tile ID
+			let Some(entry) = entries.find_tile(tile_id) else {
+				return Ok(None);
+			};
+			if entry.range.length == 0 {
+				return Ok(None);
+			}
+			// A run of tiles points to tile data, everything else to a leaf directory
+			if entry.run_length > 0 {
+				return Ok(Some(entry));
+			}
+			entries = self.get_leaf_entries(entry.range).await?;
+		}
+
+		// If the tile data is not found after traversing all levels, return an error
+		bail!(ContainerError::DirectoryTooDeep {
+			max_depth: self.limits.max_directory_depth
+		})
+	}
+
+	/// Return the parsed entries of the leaf directory at `range`.
+	///
+	/// Uses the cache to avoid redundant decompression and parsing.
+	async fn get_leaf_entries(&self, range: ByteRange) -> Result<Arc<EntriesV3>> {
+		let mut cache = self.leaves_cache.lock().await;
+		cache.get_or_set(&range, || {
+			self.limits.check_range(&range)?;
+			let blob = self.leaves_bytes.read_range(&range)?;
+			let blob = decompress_limited(blob, self.internal_compression, self.limits.max_decompressed_size)?;
+			Ok(Arc::new(EntriesV3::from_blob_limited(&blob, &self.limits)?))
+		})
 	}
 }
 
+/// Number of bytes fetched by the first request.
+///
+/// The PMTiles spec guarantees that header and root directory fit into the first 16 KiB,
+/// so a single request is enough to open most containers, which is important for remote files.
+const INITIAL_FETCH_SIZE: u64 = 16 * 1024;
+
+/// Reads the first [`INITIAL_FETCH_SIZE`] bytes, or only the header if the container is smaller.
+async fn read_initial_bytes(data_reader: &DataReader, limits: &ReaderLimits) -> Result<Blob> {
+	let size = INITIAL_FETCH_SIZE.min(limits.max_read_size).max(HeaderV3::len());
+	match data_reader.read_range(&ByteRange::new(0, size)).await {
+		Ok(blob) => Ok(blob),
+		Err(error) => {
+			log::debug!("reading the first {size} bytes failed, reading only the header: {error:#}");
+			data_reader.read_range(&ByteRange::new(0, HeaderV3::len())).await
+		}
+	}
+}
+
+/// Reads `range` from `initial_bytes` if it is included, otherwise from `data_reader`.
+async fn read_range(data_reader: &DataReader, initial_bytes: &Blob, range: &ByteRange) -> Result<Blob> {
+	if range.offset + range.length <= initial_bytes.len() {
+		initial_bytes.read_range(range)
+	} else {
+		data_reader.read_range(range).await
+	}
+}
+
+/// Includes the run of `run_length` tiles starting at Hilbert index `tile_id` in `bbox_pyramid`.
+///
+/// Runs can be billions of tiles long, so they are not expanded tile by tile. Instead, the run is
+/// split into blocks of `4^k` tiles that start at a multiple of `4^k` within their zoom level.
+/// Each of these blocks covers an aligned square of `2^k × 2^k` tiles, so a run needs only a few blocks.
+fn include_tile_run(bbox_pyramid: &mut TileBBoxPyramid, tile_id: u64, run_length: u64) -> Result<()> {
+	let end = tile_id
+		.checked_add(run_length)
+		.ok_or_else(|| ContainerError::Malformed(format!("tile id of run at {tile_id} overflows")))?;
+
+	let mut id = tile_id;
+	while id < end {
+		let coord = TileCoord::from_hilbert_index(id)?;
+		let level = u32::from(coord.level);
+		// Hilbert index of the first tile at this zoom level, and of the first tile of the next level
+		let level_start = (4u64.pow(level) - 1) / 3;
+		let level_end = level_start + 4u64.pow(level);
+		let block_end = end.min(level_end);
+
+		let index = id - level_start;
+		let mut k = 0;
+		while k < level && index.is_multiple_of(4u64.pow(k + 1)) && id + 4u64.pow(k + 1) <= block_end {
+			k += 1;
+		}
+
+		let size = 1u32 << k;
+		bbox_pyramid.include_bbox(&TileBBox::from_min_and_size(
+			coord.level,
+			coord.x & !(size - 1),
+			coord.y & !(size - 1),
+			size,
+			size,
+		)?);
+		id += 4u64.pow(k);
+	}
+	Ok(())
+}
+
 /// Build the per‑zoom bounding box pyramid by traversing PMTiles directory entries.
 ///
 /// Walks the root and leaf directory blobs, following entry ranges. For `run_length`
-/// entries, expands the run into individual tiles via Hilbert indices; for directory
+/// entries, includes the tiles of the run via [`include_tile_run`]; for directory
 /// entries, decompresses and recurses. Returns the accumulated [`TileBBoxPyramid`].
 ///
 /// ### Parameters
 /// - `root_bytes_uncompressed`: uncompressed root directory bytes.
 /// - `leaves_bytes`: concatenated (compressed) leaf directory bytes as a single blob.
 /// - `compression`: compression algorithm used for directory blobs.
+/// - `limits`: maximum nesting depth, decompressed size, and number of entries. The entry limit
+///   applies to all visited directories together, so leaf directories that are referenced
+///   over and over again can't keep the reader busy.
 ///
 /// ### Errors
-/// Returns an error when directory blobs cannot be parsed or decompressed.
+/// Returns an error when directory blobs cannot be parsed or decompressed, or exceed the `limits`.
 #[context("building bbox pyramid from PMTiles directories")]
 fn calc_bbox_pyramid(
 	root_bytes_uncompressed: &Blob,
 	leaves_bytes: &Blob,
 	compression: TileCompression,
+	limits: &ReaderLimits,
 ) -> Result<TileBBoxPyramid> {
 	let mut bbox_pyramid = TileBBoxPyramid::new_empty();
+	let mut visited_entries = 0;
 
 	parse_directories(
 		&mut bbox_pyramid,
 		root_bytes_uncompressed,
 		leaves_bytes,
 		compression,
-		true,
+		limits,
+		0,
+		&mut visited_entries,
 	)?;
 
-	#[context("parsing PMTiles directory (root={})", root)]
+	#[context("parsing PMTiles directory (depth={})", depth)]
 	fn parse_directories(
 		bbox_pyramid: &mut TileBBoxPyramid,
 		dir: &Blob,
 		leaves_bytes: &Blob,
 		compression: TileCompression,
-		root: bool,
+		limits: &ReaderLimits,
+		depth: u8,
+		visited_entries: &mut u64,
 	) -> Result<u64> {
 		log::trace!("parse_directories");
 
-		let entries = EntriesV3::from_blob(dir)?;
+		// also stops leaf directories that reference themselves
+		limits.check_depth(depth)?;
+		let root = depth == 0;
+
+		let entries = EntriesV3::from_blob_limited(dir, limits)?;
+		*visited_entries += entries.len() as u64;
+		limits.check_entries(*visited_entries)?;
+
 		let entries = entries.iter().collect::<Vec<_>>();
 		let progress = if root {
 			Some(get_progress_bar("Parsing PMTiles directories", entries.len() as u64))
@@ -225,16 +377,21 @@ fn calc_bbox_pyramid(
 
 			if entry.range.length > 0 {
 				if entry.run_length > 0 {
-					for i in 0..entry.run_length as u64 {
-						let coord = TileCoord::from_hilbert_index(i + entry.tile_id)?;
-						bbox_pyramid.include_coord(&coord);
-					}
+					include_tile_run(bbox_pyramid, entry.tile_id, u64::from(entry.run_length))?;
 					total_entries += entry.run_length as u64;
 				} else {
-					let range = entry.range;
-					let mut blob = leaves_bytes.read_range(&range)?;
-					blob = decompress(blob, compression)?;
-					total_entries += parse_directories(bbox_pyramid, &blob, leaves_bytes, compression, false)?;
+					limits.check_range(&entry.range)?;
+					let blob = leaves_bytes.read_range(&entry.range)?;
+					let blob = decompress_limited(blob, compression, limits.max_decompressed_size)?;
+					total_entries += parse_directories(
+						bbox_pyramid,
+						&blob,
+						leaves_bytes,
+						compression,
+						limits,
+						depth + 1,
+						visited_entries,
+					)?;
 				}
 			}
 		}
@@ -292,54 +449,41 @@ impl TilesReaderTrait for PMTilesReader {
 
 		// Convert the tile coordinates into a unique tile ID
 		let tile_id: u64 = coord.get_hilbert_index()?;
-		// Start with the root directory entries
-		let mut entries = self.root_entries.clone();
-
-		// Iterate through the directory depth (up to 3 levels)
-		for _depth in 0..3 {
-			// Find the entry corresponding to the requested tile ID
-			let entry = entries.find_tile(tile_id);
+		let Some(entry) = self.find_tile_entry(tile_id).await? else {
+			return Ok(None);
+		};
 
-			// If the entry is not found, return None
-			let entry = if let Some(entry) = entry {
-				entry
-			} else {
-				return Ok(None);
-			};
+		self.limits.check_range(&entry.range)?;
+		let offset = entry
+			.range
+			.offset
+			.checked_add(self.header.tile_data.offset)
+			.ok_or_else(|| ContainerError::Malformed(format!("offset of tile {tile_id} overflows")))?;
+		Ok(Some(Tile::from_blob(
+			self
+				.data_reader
+				.read_range(&ByteRange::new(offset, entry.range.length))
+				.await?,
+			self.parameters.tile_compression,
+			self.parameters.tile_format,
+		)))
+	}
 
-			// Check if the entry has a valid range
-			if entry.range.length > 0 {
-				// If the entry represents a run of tiles, directly fetch the tile data
-				if entry.run_length > 0 {
-					return Ok(Some(Tile::from_blob(
-						self
-							.data_reader
-							.read_range(&entry.range.get_shifted_forward(self.header.tile_data.offset))
-							.await?,
-						self.parameters.tile_compression,
-						self.parameters.tile_format,
-					)));
-				} else {
-					// Otherwise, fetch the directory bytes for the next level
-					let range = entry.range;
-					let mut cache = self.leaves_cache.lock().await;
-					// Use the cache to avoid redundant decompression and reading
-					entries = cache.get_or_set(&range, || {
-						let mut blob = self.leaves_bytes.read_range(&range)?;
-						// Decompress the directory bytes
-						blob = decompress(blob, self.internal_compression)?;
-						let entries = EntriesV3::from_blob(&blob)?;
-						Ok(Arc::new(entries))
-					})?;
-				}
-			} else {
-				// If the range is invalid, return None
-				return Ok(None);
-			}
+	/// Decompresses and caches the leaf directories of all tiles within `bbox`,
+	/// so that a following `get_tile_stream` only has to read tile data.
+	#[context("prefetching leaf directories for bbox {:?}", bbox)]
+	async fn prefetch(&self, bbox: &TileBBox) -> Result<()> {
+		if self.leaves_bytes.is_empty() {
+			return Ok(());
 		}
-
-		// If the tile data is not found after traversing all levels, return an error
-		bail!("not found")
+		let tile_ids = bbox
+			.iter_coords()
+			.map(|coord| coord.get_hilbert_index())
+			.collect::<Result<Vec<u64>>>()?;
+		for tile_id in tile_ids {
+			self.find_tile_entry(tile_id).await?;
+		}
+		Ok(())
 	}
 
 	// deep probe of container meta
@@ -358,13 +502,162 @@ impl TilesReaderTrait for PMTilesReader {
 mod tests {
 	use super::*;
 	use lazy_static::lazy_static;
-	use std::{env::current_dir, path::PathBuf};
+	use std::{
+		env::current_dir,
+		path::PathBuf,
+		sync::atomic::{AtomicUsize, Ordering},
+	};
 	use versatiles_core::assert_wildcard;
 
 	lazy_static! {
 		static ref PATH: PathBuf = current_dir().unwrap().join("../testdata/berlin.pmtiles");
 	}
 
+	#[tokio::test]
+	async fn reader_limits() -> Result<()> {
+		let open = |limits: ReaderLimits| async move {
+			PMTilesReader::open_reader_with_limits(DataReaderFile::open(&PATH).unwrap(), limits)
+				.await
+				.unwrap_err()
+		};
+
+		let error = open(ReaderLimits {
+			max_directory_entries: 100,
+			..ReaderLimits::default()
+		})
+		.await;
+		assert!(matches!(
+			error.downcast_ref::<ContainerError>(),
+			Some(ContainerError::TooManyEntries { max: 100, .. })
+		));
+
+		let error = open(ReaderLimits {
+			max_read_size: 1000,
+			..ReaderLimits::default()
+		})
+		.await;
+		assert!(matches!(
+			error.downcast_ref::<ContainerError>(),
+			Some(ContainerError::RangeTooLarge { max: 1000, .. })
+		));
+
+		let error = open(ReaderLimits {
+			max_decompressed_size: 100,
+			..ReaderLimits::default()
+		})
+		.await;
+		assert!(error.downcast_ref::<utils::DecompressionLimitExceeded>().is_some());
+		Ok(())
+	}
+
+	/// Counts the requests to the wrapped reader.
+	#[derive(Debug)]
+	struct CountingReader {
+		inner: DataReader,
+		requests: Arc<AtomicUsize>,
+	}
+
+	#[async_trait]
+	impl DataReaderTrait for CountingReader {
+		async fn read_range(&self, range: &ByteRange) -> Result<Blob> {
+			self.requests.fetch_add(1, Ordering::SeqCst);
+			self.inner.read_range(range).await
+		}
+		async fn read_all(&self) -> Result<Blob> {
+			self.requests.fetch_add(1, Ordering::SeqCst);
+			self.inner.read_all().await
+		}
+		fn get_name(&self) -> &str {
+			self.inner.get_name()
+		}
+	}
+
+	#[test]
+	fn include_tile_run_matches_single_tiles() -> Result<()> {
+		for (tile_id, run_length) in [(0, 1), (0, 100), (5, 16), (7, 3), (21, 64), (22, 300), (1000, 5000)] {
+			let mut expected = TileBBoxPyramid::new_empty();
+			for id in tile_id..tile_id + run_length {
+				expected.include_coord(&TileCoord::from_hilbert_index(id)?);
+			}
+			let mut pyramid = TileBBoxPyramid::new_empty();
+			include_tile_run(&mut pyramid, tile_id, run_length)?;
+			assert_eq!(pyramid, expected, "run of {run_length} at {tile_id}");
+		}
+
+		// a huge run must not be expanded tile by tile
+		let mut pyramid = TileBBoxPyramid::new_empty();
+		include_tile_run(&mut pyramid, 0, u64::from(u32::MAX))?;
+		assert_eq!(pyramid.get_level_max(), Some(16));
+		assert!(include_tile_run(&mut pyramid, u64::MAX - 1, 10).is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn opens_with_a_single_request() -> Result<()> {
+		let requests = Arc::new(AtomicUsize::new(0));
+		let data_reader = CountingReader {
+			inner: DataReaderFile::open(&PATH)?,
+			requests: requests.clone(),
+		};
+		let reader = PMTilesReader::open_reader(Box::new(data_reader)).await?;
+		assert_eq!(requests.load(Ordering::SeqCst), 1);
+		assert_eq!(reader.root_entries.len(), 878);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn reads_only_header_of_small_containers() -> Result<()> {
+		let data_reader: DataReader = Box::new(DataReaderBlob::from(vec![7u8; 1000]));
+		let limits = ReaderLimits::default();
+		assert_eq!(read_initial_bytes(&data_reader, &limits).await?.len(), HeaderV3::len());
+
+		let data_reader: DataReader = Box::new(DataReaderBlob::from(vec![7u8; 20_000]));
+		let blob = read_initial_bytes(&data_reader, &limits).await?;
+		assert_eq!(blob.len(), INITIAL_FETCH_SIZE);
+
+		// ranges beyond the initial bytes are read from the data reader
+		let range = ByteRange::new(16_000, 1_000);
+		assert_eq!(read_range(&data_reader, &blob, &range).await?.len(), 1_000);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn prefetch_caches_leaf_directories() -> Result<()> {
+		// enough different tiles that the writer needs leaf directories
+		let mut source = crate::MemTilesReader::new(TileFormat::JSON, TileCompression::Uncompressed);
+		for coord in TileBBox::new_full(7)?.iter_coords() {
+			let blob = Blob::from(format!("{coord:?}"));
+			source.insert(
+				coord,
+				Tile::from_blob(blob, TileCompression::Uncompressed, TileFormat::JSON),
+			)?;
+		}
+		let dir = assert_fs::TempDir::new()?;
+		let path = dir.path().join("leaves.pmtiles");
+		crate::ContainerRegistry::default()
+			.write_to_path(Box::new(source), &path)
+			.await?;
+
+		let reader = PMTilesReader::open_path(&path).await?;
+		assert!(!reader.leaves_bytes.is_empty());
+		let coord = TileCoord::new(7, 100, 30)?;
+		let leaf = reader.root_entries.find_tile(coord.get_hilbert_index()?).unwrap();
+		assert_eq!(leaf.run_length, 0);
+		assert!(reader.leaves_cache.lock().await.get(&leaf.range).is_none());
+
+		reader
+			.prefetch(&TileBBox::from_min_and_max(7, 96, 28, 103, 35)?)
+			.await?;
+		assert!(reader.leaves_cache.lock().await.get(&leaf.range).is_some());
+
+		let tile = reader.get_tile(&coord).await?.unwrap();
+		assert_eq!(
+			tile.into_blob(TileCompression::Uncompressed)?.as_str(),
+			format!("{coord:?}")
+		);
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn reader() -> Result<()> {
 		let reader = PMTilesReader::open_path(&PATH).await?;
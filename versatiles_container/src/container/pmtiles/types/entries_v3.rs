@@ -1,5 +1,6 @@
 use super::{Directory, EntryV3};
-use anyhow::{Result, bail};
+use crate::{ContainerError, ReaderLimits};
+use anyhow::{Result, anyhow, bail};
 use std::{
 	cmp::Ordering,
 	io::Write,
@@ -29,28 +30,52 @@ impl EntriesV3 {
 	/// # Errors
 	/// Returns an error if the `Blob` format is incorrect or the data cannot be parsed.
 	///
-	/// # Panics
-	/// Panics if the number of entries exceeds 10 billion, which is considered an error.
+	/// Also returns an error if tile IDs or byte ranges overflow `u64`, so malformed
+	/// directories never cause a panic.
 	pub fn from_blob(data: &Blob) -> Result<Self> {
+		Self::from_blob_limited(data, &ReaderLimits::default())
+	}
+
+	/// Deserializes a `Blob` like [`EntriesV3::from_blob`], but with custom [`ReaderLimits`].
+	///
+	/// # Errors
+	/// Returns [`ContainerError::TooManyEntries`] if the directory exceeds `limits.max_directory_entries`,
+	/// and [`ContainerError::Malformed`] if the directory is inconsistent.
+	pub fn from_blob_limited(data: &Blob, limits: &ReaderLimits) -> Result<Self> {
+		let malformed = |message: String| anyhow!(ContainerError::Malformed(message));
 		let mut entries: Vec<EntryV3> = Vec::new();
 		let mut reader = ValueReaderSlice::new_le(data.as_slice());
 
-		let num_entries = reader.read_varint()? as usize;
+		let num_entries = reader.read_varint()?;
 
 		if num_entries > 10_000_000_000 {
 			bail!("there is something wrong: PMTiles with more then 10 billion tiles?")
 		}
+		limits.check_entries(num_entries)?;
+
+		// every entry needs at least one byte for each of its four varints
+		if num_entries > data.len() / 4 {
+			return Err(malformed(format!(
+				"directory claims {num_entries} entries, but contains only {} bytes",
+				data.len()
+			)));
+		}
+		let num_entries = num_entries as usize;
 
 		let mut last_id: u64 = 0;
 
 		for _ in 0..num_entries {
 			let diff = reader.read_varint()?;
-			last_id += diff;
+			last_id = last_id
+				.checked_add(diff)
+				.ok_or_else(|| malformed(format!("tile id overflows after {last_id}")))?;
 			entries.push(EntryV3::new(last_id, ByteRange::empty(), 0));
 		}
 
 		for entry in entries.iter_mut() {
-			entry.run_length = reader.read_varint()? as u32;
+			let run_length = reader.read_varint()?;
+			entry.run_length = u32::try_from(run_length)
+				.map_err(|_| malformed(format!("run length {run_length} does not fit into u32")))?;
 		}
 
 		for entry in entries.iter_mut() {
@@ -59,10 +84,20 @@ impl EntriesV3 {
 
 		for i in 0..num_entries {
 			let tmp = reader.read_varint()?;
-			if i > 0 && tmp == 0 {
-				entries[i].range.offset = entries[i - 1].range.offset + entries[i - 1].range.length;
+			entries[i].range.offset = if tmp > 0 {
+				tmp - 1
 			} else {
-				entries[i].range.offset = tmp - 1
+				if i == 0 {
+					return Err(malformed("the offset of the first entry must not be 0".to_string()));
+				}
+				let previous = entries[i - 1].range;
+				previous
+					.offset
+					.checked_add(previous.length)
+					.ok_or_else(|| malformed(format!("offset of entry {i} overflows")))?
+			};
+			if entries[i].range.offset.checked_add(entries[i].range.length).is_none() {
+				return Err(malformed(format!("byte range of entry {i} overflows")));
 			}
 		}
 
@@ -221,6 +256,33 @@ impl From<&Blob> for EntriesV3 {
 	}
 }
 
+/// Generates valid directories: tile IDs are strictly increasing, byte ranges never overflow,
+/// and about half of the entries directly follow their predecessor, like tiles written in order.
+#[cfg(any(test, feature = "arbitrary"))]
+impl<'a> arbitrary::Arbitrary<'a> for EntriesV3 {
+	fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+		let mut entries: Vec<EntryV3> = Vec::new();
+		let mut tile_id: u64 = 0;
+		for _ in 0..u.arbitrary_len::<EntryV3>()? {
+			let delta = u.int_in_range(u64::from(!entries.is_empty())..=u64::from(u32::MAX))?;
+			let Some(id) = tile_id.checked_add(delta) else { break };
+			tile_id = id;
+
+			let following = entries
+				.last()
+				.and_then(|e| e.range.offset.checked_add(e.range.length))
+				.filter(|offset| *offset < u64::MAX);
+			let offset = match following {
+				Some(offset) if u.arbitrary()? => offset,
+				_ => u.int_in_range(0..=u64::MAX - 1)?,
+			};
+			let range = ByteRange::new(offset, u.int_in_range(0..=u64::MAX - offset)?);
+			entries.push(EntryV3::new(tile_id, range, u.arbitrary()?));
+		}
+		Ok(EntriesV3 { entries })
+	}
+}
+
 /// A slice of `EntryV3`, supporting partial views into `EntriesV3`.
 pub struct EntriesSliceV3<'a> {
 	entries: &'a [EntryV3],
@@ -258,7 +320,8 @@ impl EntriesSliceV3<'_> {
 	/// Serializes the entries slice into a `Blob`.
 	///
 	/// # Errors
-	/// Returns an error if any part of the serialization process fails.
+	/// Returns an error if the entries are not sorted by tile ID, if an offset is `u64::MAX`,
+	/// or if any other part of the serialization process fails.
 	pub fn serialize_entries(&self) -> Result<Blob> {
 		let mut writer = ValueWriterBlob::new_le();
 		let entries = self.entries;
@@ -270,7 +333,10 @@ impl EntriesSliceV3<'_> {
 		// Serialize TileID deltas
 		let mut last_id: u64 = 0;
 		for entry in entries {
-			let delta = entry.tile_id - last_id;
+			let delta = entry
+				.tile_id
+				.checked_sub(last_id)
+				.ok_or_else(|| anyhow!("entries must be sorted by tile id"))?;
 			writer.write_varint(delta)?;
 			last_id = entry.tile_id;
 		}
@@ -287,10 +353,16 @@ impl EntriesSliceV3<'_> {
 
 		// Serialize Offsets
 		for i in 0..entries.len() {
-			let offset = if i > 0 && entries[i].range.offset == entries[i - 1].range.offset + entries[i - 1].range.length {
+			let previous_end = (i > 0).then(|| entries[i - 1].range.offset.checked_add(entries[i - 1].range.length));
+			let offset = if previous_end == Some(Some(entries[i].range.offset)) {
 				0
 			} else {
-				entries[i].range.offset + 1 // add 1 to not conflict with 0
+				// add 1 to not conflict with 0
+				entries[i]
+					.range
+					.offset
+					.checked_add(1)
+					.ok_or_else(|| anyhow!("offset of entry {i} is too large"))?
 			};
 			writer.write_varint(offset)?;
 		}
@@ -302,6 +374,8 @@ impl EntriesSliceV3<'_> {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use arbitrary::Unstructured;
+	use proptest::prelude::*;
 
 	// Helper function to create sample entries
 	fn create_entries() -> EntriesV3 {
@@ -397,6 +471,31 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn test_from_blob_limited() -> Result<()> {
+		let blob = create_filled_entries(10).as_slice().serialize_entries()?;
+		let limits = ReaderLimits {
+			max_directory_entries: 9,
+			..ReaderLimits::default()
+		};
+		assert_eq!(
+			EntriesV3::from_blob_limited(&blob, &limits)
+				.unwrap_err()
+				.downcast_ref::<ContainerError>(),
+			Some(&ContainerError::TooManyEntries { count: 10, max: 9 })
+		);
+
+		// the first offset must not reference a previous entry
+		let blob = Blob::from(vec![1, 0, 0, 0, 0]);
+		assert!(matches!(
+			EntriesV3::from_blob(&blob)
+				.unwrap_err()
+				.downcast_ref::<ContainerError>(),
+			Some(ContainerError::Malformed(_))
+		));
+		Ok(())
+	}
+
 	/// Tests the as_directory function for correct directory structure creation
 	#[test]
 	fn test_as_directory_structure() -> Result<()> {
@@ -414,4 +513,22 @@ mod tests {
 
 		Ok(())
 	}
+
+	proptest! {
+		#[test]
+		fn prop_serialization_roundtrip(bytes in prop::collection::vec(any::<u8>(), 0..4096)) {
+			let entries: EntriesV3 = Unstructured::new(&bytes).arbitrary().unwrap();
+			let blob = entries.as_slice().serialize_entries().unwrap();
+			prop_assert_eq!(EntriesV3::from_blob(&blob).unwrap(), entries);
+		}
+
+		#[test]
+		fn prop_from_blob_does_not_panic(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+			if let Ok(entries) = EntriesV3::from_blob(&Blob::from(bytes)) {
+				for entry in entries.iter() {
+					prop_assert!(entry.range.offset.checked_add(entry.range.length).is_some());
+				}
+			}
+		}
+	}
 }
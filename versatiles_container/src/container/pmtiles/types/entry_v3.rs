@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use versatiles_core::ByteRange;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 pub struct EntryV3 {
 	pub tile_id: u64,
 	pub range: ByteRange,
@@ -1,11 +1,13 @@
 use super::{PMTilesCompression, PMTilesType};
-use anyhow::{Result, ensure};
+use crate::ContainerError;
+use anyhow::{Result, bail, ensure};
 use versatiles_core::{
 	Blob, ByteRange, TilesReaderParameters,
 	io::{ValueReader, ValueReaderSlice, ValueWriter, ValueWriterBlob},
 };
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 pub struct HeaderV3 {
 	pub root_dir: ByteRange,
 	pub metadata: ByteRange,
@@ -133,6 +135,14 @@ impl HeaderV3 {
 			center_lat_e7: reader.read_i32()?,
 		};
 
+		for range in [header.root_dir, header.metadata, header.leaf_dirs, header.tile_data] {
+			if range.offset.checked_add(range.length).is_none() {
+				bail!(ContainerError::Malformed(format!(
+					"pmtiles byte range {range} overflows"
+				)));
+			}
+		}
+
 		Ok(header)
 	}
 
@@ -144,6 +154,8 @@ impl HeaderV3 {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use arbitrary::Unstructured;
+	use proptest::prelude::*;
 
 	#[test]
 	fn header_serialization_deserialization() {
@@ -175,4 +187,21 @@ mod tests {
 
 		assert_eq!(header, deserialized_header);
 	}
+
+	proptest! {
+		#[test]
+		fn prop_serialization_roundtrip(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+			if let Ok(header) = Unstructured::new(&bytes).arbitrary::<HeaderV3>() {
+				let blob = header.serialize().unwrap();
+				prop_assert_eq!(blob.len(), HeaderV3::len());
+				prop_assert_eq!(HeaderV3::deserialize(&blob).unwrap(), header);
+			}
+		}
+
+		#[test]
+		fn prop_deserialize_does_not_panic(mut bytes in prop::collection::vec(any::<u8>(), 127)) {
+			bytes[0..8].copy_from_slice(b"PMTiles\x03");
+			let _ = HeaderV3::deserialize(&Blob::from(bytes));
+		}
+	}
 }
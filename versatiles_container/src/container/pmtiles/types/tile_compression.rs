@@ -2,6 +2,7 @@ use anyhow::{Result, bail};
 use versatiles_core::TileCompression::{self, *};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 pub enum PMTilesCompression {
 	Unknown = 0x0,
 	None = 0x1,
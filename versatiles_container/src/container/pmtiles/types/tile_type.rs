@@ -3,6 +3,7 @@ use versatiles_core::TileFormat;
 
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 pub enum PMTilesType {
 	UNKNOWN = 0x0,
 	MVT = 0x1,
@@ -45,6 +45,7 @@ use futures::lock::Mutex;
 use std::sync::Arc;
 use versatiles_core::{
 	io::DataWriterTrait,
+	progress::ProgressStages,
 	traversal::*,
 	types::*,
 	utils::{HilbertIndex, compress},
@@ -111,7 +112,7 @@ impl TilesWriterTrait for PMTilesWriter {
 						tiles.sort_by_key(|(coord, _)| coord.get_hilbert_index().unwrap());
 						for (coord, mut tile) in tiles {
 							let id = coord.get_hilbert_index()?;
-							let range = writer.append(tile.as_blob(tile_compression)?)?;
+							let range = writer.append_with(&mut |w| tile.write_blob_to(tile_compression, w))?;
 							entries.push(EntryV3::new(id, range.get_shifted_backward(tile_data_start), 1));
 						}
 						Ok(())
@@ -121,6 +122,7 @@ impl TilesWriterTrait for PMTilesWriter {
 			)
 			.await?;
 
+		ProgressStages::begin("write directories");
 		let mut entries = entries_mutex.lock().await;
 		let mut writer = writer_mutex.lock().await;
 
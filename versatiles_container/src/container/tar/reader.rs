@@ -1,8 +1,21 @@
 //! Read tiles and metadata from a `.tar` archive.
 //!
-//! The `TarTilesReader` scans a tarball for tiles arranged in a `{z}/{x}/{y}.<format>[.<compression>]`
-//! layout and optional TileJSON metadata files (`meta.json`, `tiles.json`, `metadata.json`)
-//! including their compressed variants (`.gz`, `.br`). Non-regular entries are ignored.
+//! The `TarTilesReader` scans a tarball for tiles and optional TileJSON metadata files
+//! (`meta.json`, `tiles.json`, `metadata.json`) including their compressed variants (`.gz`, `.br`).
+//! Non-regular entries are ignored.
+//!
+//! ## Archive compression
+//! Archives compressed as a whole (`.tar.gz`/`.tgz`, `.tar.zst`/`.tzst`) are detected by their magic
+//! bytes and decompressed while streaming through the archive. Because compressed archives don't
+//! allow random access, all tiles are kept in memory, so this is only suitable for small archives.
+//! The tiles may take at most [`ReaderLimits::max_decompressed_size`] bytes, see
+//! [`TarTilesReader::open_path_with_limits`].
+//!
+//! ## Tile layouts
+//! The layout is auto-detected from the first tile entry and must be the same for all tiles:
+//! - `{z}/{x}/{y}.<format>[.<compression>]`, optionally nested below further directories
+//!   (e.g. `tiles/{z}/{x}/{y}.png`)
+//! - `{z}-{x}-{y}.<format>[.<compression>]` or `{z}_{x}_{y}.<format>[.<compression>]`
 //!
 //! ## Detected properties
 //! - **Tile format** is inferred from the innermost filename extension (e.g., `.png`, `.webp`, `.pbf`, `.mvt`, `.bin`).
@@ -29,26 +42,123 @@
 //!
 //! ## Errors
 //! Returns errors when the tar cannot be opened or read, when no tiles are found,
-//! or when mixed formats/compressions are detected.
+//! or when mixed formats/compressions/layouts are detected.
 
-use crate::{Tile, TilesReaderTrait};
-use anyhow::{Result, anyhow, ensure};
+use crate::{ReaderLimits, Tile, TilesReaderTrait};
+use anyhow::{Result, anyhow, bail, ensure};
 use async_trait::async_trait;
-use std::{collections::HashMap, fmt::Debug, io::Read, path::Path};
+use std::{
+	collections::HashMap,
+	fmt::Debug,
+	fs::File,
+	io::{BufReader, Read},
+	path::Path,
+};
 use tar::{Archive, EntryType};
-use versatiles_core::{io::*, utils::decompress, *};
+use versatiles_core::{
+	io::*,
+	utils::{DecompressionLimitExceeded, decompress},
+	*,
+};
 use versatiles_derive::context;
 
+/// Compression of the whole archive, detected by magic bytes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ArchiveCompression {
+	None,
+	Gzip,
+	Zstd,
+}
+
+impl ArchiveCompression {
+	fn detect(magic: &[u8]) -> ArchiveCompression {
+		if magic.starts_with(&[0x1f, 0x8b]) {
+			ArchiveCompression::Gzip
+		} else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+			ArchiveCompression::Zstd
+		} else {
+			ArchiveCompression::None
+		}
+	}
+}
+
+/// How tile coordinates are encoded in the entry paths.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TileLayout {
+	/// `{z}/{x}/{y}.ext`, optionally below further directories
+	Nested,
+	/// `{z}-{x}-{y}.ext` or `{z}_{x}_{y}.ext`, with the given separator
+	Flat(char),
+}
+
+/// A tile entry path split into its parts.
+#[derive(Debug, PartialEq)]
+struct TilePath {
+	layout: TileLayout,
+	coord: (u8, u32, u32),
+	format: TileFormat,
+	compression: TileCompression,
+}
+
+impl TilePath {
+	/// Parses `path` (with `/` separators) as a tile path. Returns `None` if it doesn't look like a tile.
+	fn parse(path: &str) -> Option<TilePath> {
+		let parts = path.split('/').collect::<Vec<_>>();
+		let mut filename = String::from(*parts.last()?);
+		let compression = TileCompression::from_filename(&mut filename);
+		let format = TileFormat::from_filename(&mut filename)?;
+
+		let numbers = |values: &[&str]| -> Option<(u8, u32, u32)> {
+			Some((
+				values[0].parse().ok()?,
+				values[1].parse().ok()?,
+				values[2].parse().ok()?,
+			))
+		};
+
+		for separator in ['-', '_'] {
+			let values = filename.split(separator).collect::<Vec<_>>();
+			if values.len() == 3 {
+				let coord = numbers(&values)?;
+				return Some(TilePath {
+					layout: TileLayout::Flat(separator),
+					coord,
+					format,
+					compression,
+				});
+			}
+		}
+
+		if parts.len() >= 3 {
+			let coord = numbers(&[parts[parts.len() - 3], parts[parts.len() - 2], &filename])?;
+			return Some(TilePath {
+				layout: TileLayout::Nested,
+				coord,
+				format,
+				compression,
+			});
+		}
+		None
+	}
+}
+
+/// Where the tile data lives.
+enum TileStorage {
+	/// Byte ranges of the tiles inside an uncompressed archive.
+	File(Box<DataReaderFile>, HashMap<TileCoord, ByteRange>),
+	/// Tiles of a compressed archive, decompressed into memory while opening.
+	Memory(HashMap<TileCoord, Blob>),
+}
+
 /// Reader for tiles stored inside a tar archive.
 ///
 /// Merges TileJSON from recognized metadata files, builds a map from `{z,x,y}` to
-/// byte ranges within the archive, infers uniform format/compression, and exposes
-/// tiles via [`TilesReaderTrait`].
+/// byte ranges within the archive (or to the tile data itself for compressed archives),
+/// infers uniform format/compression, and exposes tiles via [`TilesReaderTrait`].
 pub struct TarTilesReader {
 	tilejson: TileJSON,
 	name: String,
-	reader: Box<DataReaderFile>,
-	tile_map: HashMap<TileCoord, ByteRange>,
+	storage: TileStorage,
 	parameters: TilesReaderParameters,
 }
 
@@ -56,137 +166,196 @@ impl TarTilesReader {
 	/// Open a tar archive and build an index of tiles and metadata.
 	///
 	/// Scans regular entries in the archive, recognizing:
-	/// - tiles at `{z}/{x}/{y}.<format>[.<compression>]`
+	/// - tiles in one of the supported layouts (see module docs)
 	/// - metadata files: `meta.json`, `tiles.json`, `metadata.json` (optionally `.gz`/`.br`)
 	///
 	/// Determines a uniform tile **format** and **compression**, and computes a bbox pyramid
-	/// from discovered coordinates.
+	/// from discovered coordinates. Gzip and Zstandard compressed archives are supported.
 	///
 	/// # Errors
 	/// Returns an error if the file cannot be opened, if **no tiles** are found, or if mixed
-	/// formats/compressions are encountered.
+	/// formats/compressions/layouts are encountered.
 	#[context("opening tar from path '{}'", path.display())]
 	pub fn open_path(path: &Path) -> Result<TarTilesReader> {
-		let mut reader = DataReaderFile::open(path)?;
-		let mut archive = Archive::new(&mut reader);
-
-		let mut tilejson = TileJSON::default();
-		let mut tile_map = HashMap::new();
-		let mut tile_format: Option<TileFormat> = None;
-		let mut tile_compression: Option<TileCompression> = None;
-		let mut bbox_pyramid = TileBBoxPyramid::new_empty();
-
-		for entry in archive.entries()? {
-			let mut entry = entry?;
-			let header = entry.header();
-			if header.entry_type() != EntryType::Regular {
-				continue;
-			}
-
-			let path = entry.path()?.clone();
-			let mut path_tmp: Vec<&str> = path.iter().map(|s| s.to_str().unwrap()).collect();
+		TarTilesReader::open_path_with_limits(path, ReaderLimits::default())
+	}
 
-			if path_tmp[0] == "." {
-				path_tmp.remove(0);
+	/// Open a tar archive like [`TarTilesReader::open_path`], but with custom [`ReaderLimits`].
+	///
+	/// The tiles of a compressed archive are read into memory, so their total size must not
+	/// exceed `limits.max_decompressed_size`.
+	///
+	/// # Errors
+	/// Returns a [`DecompressionLimitExceeded`] error if the tiles of a compressed archive are too large,
+	/// or any error of [`TarTilesReader::open_path`].
+	#[context("opening tar from path '{}'", path.display())]
+	pub fn open_path_with_limits(path: &Path, limits: ReaderLimits) -> Result<TarTilesReader> {
+		let mut magic = Vec::with_capacity(4);
+		File::open(path)?.take(4).read_to_end(&mut magic)?;
+
+		let (tilejson, parameters, storage) = match ArchiveCompression::detect(&magic) {
+			ArchiveCompression::None => {
+				let mut reader = DataReaderFile::open(path)?;
+				let mut tile_map = HashMap::new();
+				let (tilejson, parameters) = scan_archive(&mut Archive::new(&mut reader), |coord, entry| {
+					let offset = entry.raw_file_position();
+					let length = entry.size();
+					tile_map.insert(coord, ByteRange { offset, length });
+					Ok(())
+				})?;
+				(tilejson, parameters, TileStorage::File(reader, tile_map))
 			}
+			compression => {
+				log::debug!("reading {compression:?} compressed tar into memory");
+				let file = BufReader::new(File::open(path)?);
+				let decoder: Box<dyn Read> = match compression {
+					ArchiveCompression::Gzip => Box::new(flate2::read::GzDecoder::new(file)),
+					ArchiveCompression::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(file)?),
+					ArchiveCompression::None => unreachable!(),
+				};
+				let mut tiles = HashMap::new();
+				let mut total_size = 0u64;
+				let (tilejson, parameters) = scan_archive(&mut Archive::new(decoder), |coord, entry| {
+					total_size = total_size.saturating_add(entry.size());
+					if total_size > limits.max_decompressed_size {
+						return Err(
+							anyhow::Error::new(DecompressionLimitExceeded {
+								limit: limits.max_decompressed_size,
+							})
+							.context(format!(
+								"the tiles of the {compression:?} compressed tar don't fit into memory, use an uncompressed tar instead"
+							)),
+						);
+					}
+					let mut data = Vec::with_capacity(entry.size() as usize);
+					entry.read_to_end(&mut data)?;
+					tiles.insert(coord, Blob::from(data));
+					Ok(())
+				})?;
+				(tilejson, parameters, TileStorage::Memory(tiles))
+			}
+		};
 
-			let path_tmp_string = path_tmp.join("/");
-			drop(path);
-			let path_vec: Vec<&str> = path_tmp_string.split('/').collect();
-
-			if path_vec.len() == 3 {
-				let level = path_vec[0].parse::<u8>()?;
-				let x = path_vec[1].parse::<u32>()?;
-
-				let mut filename: String = String::from(path_vec[2]);
-				let this_compression = TileCompression::from_filename(&mut filename);
-				let this_format = TileFormat::from_filename(&mut filename);
+		Ok(TarTilesReader {
+			tilejson,
+			name: path.to_str().unwrap().to_string(),
+			parameters,
+			storage,
+		})
+	}
+}
 
-				if this_format.is_none() {
-					continue;
-				}
-				let this_format = this_format.unwrap();
+/// Walks through all entries of `archive`, calls `on_tile` for every tile entry and returns the
+/// merged TileJSON and the reader parameters.
+fn scan_archive<R: Read>(
+	archive: &mut Archive<R>,
+	mut on_tile: impl FnMut(TileCoord, &mut tar::Entry<'_, R>) -> Result<()>,
+) -> Result<(TileJSON, TilesReaderParameters)> {
+	let mut tilejson = TileJSON::default();
+	let mut tile_layout: Option<TileLayout> = None;
+	let mut tile_format: Option<TileFormat> = None;
+	let mut tile_compression: Option<TileCompression> = None;
+	let mut bbox_pyramid = TileBBoxPyramid::new_empty();
+	let mut tile_count = 0usize;
+
+	for entry in archive.entries()? {
+		let mut entry = entry?;
+		let header = entry.header();
+		if header.entry_type() != EntryType::Regular {
+			continue;
+		}
 
-				let y = filename.parse::<u32>()?;
+		let mut path_parts: Vec<String> = entry.path()?.iter().map(|s| s.to_str().unwrap().to_string()).collect();
+		if path_parts[0] == "." {
+			path_parts.remove(0);
+		}
+		let path_string = path_parts.join("/");
 
-				if let Some(f) = &tile_format {
-					ensure!(
-						f == &this_format,
-						"mixed tile formats in tar, found both {f:?} and {this_format:?}"
-					);
-				} else {
-					tile_format = Some(this_format);
+		if let Some(tile_path) = TilePath::parse(&path_string) {
+			match tile_layout {
+				None => {
+					log::debug!("detected tile layout {:?} from '{path_string}'", tile_path.layout);
+					tile_layout = Some(tile_path.layout);
 				}
-
-				if let Some(c) = &tile_compression {
-					ensure!(
-						c == &this_compression,
-						"mixed tile compressions in tar, found both {c:?} and {this_compression:?}"
+				Some(layout) if layout != tile_path.layout => {
+					bail!(
+						"mixed tile layouts in tar, found both {layout:?} and {:?} ('{path_string}')",
+						tile_path.layout
 					);
-				} else {
-					tile_compression = Some(this_compression);
 				}
+				Some(_) => {}
+			}
 
-				let offset = entry.raw_file_position();
-				let length = entry.size();
-
-				let coord = TileCoord::new(level, x, y)?;
-				bbox_pyramid.include_coord(&coord);
-				tile_map.insert(coord, ByteRange { offset, length });
-				continue;
+			if let Some(f) = &tile_format {
+				ensure!(
+					f == &tile_path.format,
+					"mixed tile formats in tar, found both {f:?} and {:?}",
+					tile_path.format
+				);
+			} else {
+				tile_format = Some(tile_path.format);
 			}
 
-			let mut read_to_end = || {
-				let mut blob: Vec<u8> = Vec::new();
-				entry.read_to_end(&mut blob).unwrap();
-				Blob::from(blob)
-			};
-
-			if path_vec.len() == 1 {
-				match path_vec[0] {
-					"meta.json" | "tiles.json" | "metadata.json" => {
-						tilejson.merge(&TileJSON::try_from_blob_or_default(&read_to_end()))?;
-						continue;
-					}
-					"meta.json.gz" | "tiles.json.gz" | "metadata.json.gz" => {
-						tilejson.merge(&TileJSON::try_from_blob_or_default(&decompress(
-							read_to_end(),
-							TileCompression::Gzip,
-						)?))?;
-						continue;
-					}
-					"meta.json.br" | "tiles.json.br" | "metadata.json.br" => {
-						tilejson.merge(&TileJSON::try_from_blob_or_default(&decompress(
-							read_to_end(),
-							TileCompression::Brotli,
-						)?))?;
-						continue;
-					}
-					&_ => {}
-				};
+			if let Some(c) = &tile_compression {
+				ensure!(
+					c == &tile_path.compression,
+					"mixed tile compressions in tar, found both {c:?} and {:?}",
+					tile_path.compression
+				);
+			} else {
+				tile_compression = Some(tile_path.compression);
 			}
 
-			log::warn!("unknown file in tar: {path_tmp_string:?}");
+			let (level, x, y) = tile_path.coord;
+			let coord = TileCoord::new(level, x, y)?;
+			bbox_pyramid.include_coord(&coord);
+			on_tile(coord, &mut entry)?;
+			tile_count += 1;
+			continue;
 		}
 
-		if tile_map.is_empty() {
-			return Err(anyhow!("no tiles found in tar"));
-		}
+		let mut read_to_end = || -> Result<Blob> {
+			let mut blob: Vec<u8> = Vec::new();
+			entry.read_to_end(&mut blob)?;
+			Ok(Blob::from(blob))
+		};
 
-		let parameters = TilesReaderParameters::new(
-			tile_format.ok_or(anyhow!("unknown tile format, can't detect format"))?,
-			tile_compression.ok_or(anyhow!("unknown tile compression, can't detect compression"))?,
-			bbox_pyramid.clone(),
-		);
+		let filename = path_parts.last().map(String::as_str).unwrap_or_default();
+		match filename {
+			"meta.json" | "tiles.json" | "metadata.json" => {
+				tilejson.merge(&TileJSON::try_from_blob_or_default(&read_to_end()?))?;
+				continue;
+			}
+			"meta.json.gz" | "tiles.json.gz" | "metadata.json.gz" => {
+				tilejson.merge(&TileJSON::try_from_blob_or_default(&decompress(
+					read_to_end()?,
+					TileCompression::Gzip,
+				)?))?;
+				continue;
+			}
+			"meta.json.br" | "tiles.json.br" | "metadata.json.br" => {
+				tilejson.merge(&TileJSON::try_from_blob_or_default(&decompress(
+					read_to_end()?,
+					TileCompression::Brotli,
+				)?))?;
+				continue;
+			}
+			&_ => {}
+		};
 
-		Ok(TarTilesReader {
-			tilejson,
-			name: path.to_str().unwrap().to_string(),
-			parameters,
-			reader,
-			tile_map,
-		})
+		log::warn!("unknown file in tar: {path_string:?}");
 	}
+
+	if tile_count == 0 {
+		return Err(anyhow!("no tiles found in tar"));
+	}
+
+	let parameters = TilesReaderParameters::new(
+		tile_format.ok_or(anyhow!("unknown tile format, can't detect format"))?,
+		tile_compression.ok_or(anyhow!("unknown tile compression, can't detect compression"))?,
+		bbox_pyramid,
+	);
+	Ok((tilejson, parameters))
 }
 
 #[async_trait]
@@ -217,7 +386,8 @@ impl TilesReaderTrait for TarTilesReader {
 	/// Fetch a single tile by XYZ coordinate.
 	///
 	/// Looks up the coordinate in the prebuilt index and reads the corresponding byte range
-	/// from the underlying `DataReaderFile`. Returns `Ok(None)` if the tile is absent.
+	/// from the underlying `DataReaderFile` (or clones the in-memory tile of a compressed archive).
+	/// Returns `Ok(None)` if the tile is absent.
 	///
 	/// # Errors
 	/// Propagates I/O errors while reading the tar entry.
@@ -225,18 +395,22 @@ impl TilesReaderTrait for TarTilesReader {
 	async fn get_tile(&self, coord: &TileCoord) -> Result<Option<Tile>> {
 		log::trace!("get_tile {:?}", coord);
 
-		let range = self.tile_map.get(coord);
-
-		if let Some(range) = range {
-			let blob = self.reader.read_range(range).await?;
-			Ok(Some(Tile::from_blob(
-				blob,
-				self.parameters.tile_compression,
-				self.parameters.tile_format,
-			)))
-		} else {
-			Ok(None)
-		}
+		let blob = match &self.storage {
+			TileStorage::File(reader, tile_map) => match tile_map.get(coord) {
+				Some(range) => reader.read_range(range).await?,
+				None => return Ok(None),
+			},
+			TileStorage::Memory(tiles) => match tiles.get(coord) {
+				Some(blob) => blob.clone(),
+				None => return Ok(None),
+			},
+		};
+
+		Ok(Some(Tile::from_blob(
+			blob,
+			self.parameters.tile_compression,
+			self.parameters.tile_format,
+		)))
 	}
 
 	/// Returns the name of the tar archive.
@@ -383,4 +557,105 @@ pub mod tests {
 		);
 		Ok(())
 	}
+
+	fn build_tar(paths: &[&str]) -> Result<Vec<u8>> {
+		let mut builder = tar::Builder::new(Vec::new());
+		for path in paths {
+			let mut header = tar::Header::new_gnu();
+			header.set_size(3);
+			header.set_cksum();
+			builder.append_data(&mut header, path, [1, 2, 3].as_ref())?;
+		}
+		Ok(builder.into_inner()?)
+	}
+
+	async fn check_archive(name: &str, data: &[u8], coord: TileCoord) -> Result<()> {
+		let filename = assert_fs::NamedTempFile::new(name)?;
+		std::fs::write(&filename, data)?;
+
+		let reader = TarTilesReader::open_path(&filename)?;
+		assert_eq!(reader.parameters().tile_format, TileFormat::PNG);
+		assert_eq!(reader.parameters().bbox_pyramid.count_tiles(), 2);
+		let mut tile = reader.get_tile(&coord).await?.unwrap();
+		assert_eq!(tile.as_blob(TileCompression::Uncompressed)?.as_slice(), [1, 2, 3]);
+		assert!(reader.get_tile(&TileCoord::new(0, 0, 0)?).await?.is_none());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn compressed_archives() -> Result<()> {
+		use std::io::Write;
+		let tar = build_tar(&["3/1/2.png", "3/1/3.png"])?;
+
+		let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+		encoder.write_all(&tar)?;
+		check_archive("tiles.tar.gz", &encoder.finish()?, TileCoord::new(3, 1, 2)?).await?;
+
+		let zstd = zstd::stream::encode_all(tar.as_slice(), 3)?;
+		check_archive("tiles.tar.zst", &zstd, TileCoord::new(3, 1, 3)?).await?;
+		Ok(())
+	}
+
+	#[test]
+	fn compressed_archive_limit() -> Result<()> {
+		let tar = build_tar(&["3/1/2.png", "3/1/3.png"])?;
+		let filename = assert_fs::NamedTempFile::new("tiles.tar.zst")?;
+		std::fs::write(&filename, zstd::stream::encode_all(tar.as_slice(), 3)?)?;
+
+		let open = |max_decompressed_size| {
+			TarTilesReader::open_path_with_limits(
+				&filename,
+				ReaderLimits {
+					max_decompressed_size,
+					..ReaderLimits::default()
+				},
+			)
+		};
+		assert!(open(6).is_ok());
+
+		let error = open(5).unwrap_err();
+		assert_eq!(
+			error.downcast_ref::<DecompressionLimitExceeded>(),
+			Some(&DecompressionLimitExceeded { limit: 5 })
+		);
+		assert!(format!("{error:#}").contains("don't fit into memory"), "{error:#}");
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn tile_layouts() -> Result<()> {
+		let tar = build_tar(&["tiles/3/1/2.png", "tiles/3/1/3.png", "tiles/meta.json"])?;
+		check_archive("prefixed.tar", &tar, TileCoord::new(3, 1, 2)?).await?;
+
+		let tar = build_tar(&["3-1-2.png", "3-1-3.png"])?;
+		check_archive("dashes.tar", &tar, TileCoord::new(3, 1, 3)?).await?;
+
+		let tar = build_tar(&["out/3_1_2.png", "out/3_1_3.png"])?;
+		check_archive("underscores.tar", &tar, TileCoord::new(3, 1, 2)?).await?;
+
+		let filename = assert_fs::NamedTempFile::new("mixed.tar")?;
+		std::fs::write(&filename, build_tar(&["3/1/2.png", "3-1-3.png"])?)?;
+		assert!(
+			TarTilesReader::open_path(&filename)
+				.unwrap_err()
+				.chain()
+				.last()
+				.unwrap()
+				.to_string()
+				.starts_with("mixed tile layouts in tar")
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn parse_tile_path() {
+		let parse = |path: &str| TilePath::parse(path).map(|p| (p.layout, p.coord));
+		assert_eq!(parse("3/1/2.png"), Some((TileLayout::Nested, (3, 1, 2))));
+		assert_eq!(parse("a/b/3/1/2.pbf.gz"), Some((TileLayout::Nested, (3, 1, 2))));
+		assert_eq!(parse("3-1-2.png"), Some((TileLayout::Flat('-'), (3, 1, 2))));
+		assert_eq!(parse("x/3_1_2.png.br"), Some((TileLayout::Flat('_'), (3, 1, 2))));
+		assert_eq!(parse("1/2.png"), None);
+		assert_eq!(parse("meta.json"), None);
+		assert_eq!(parse("3/1/2.txt"), None);
+	}
 }
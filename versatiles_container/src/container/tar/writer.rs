@@ -10,22 +10,25 @@
 //! - Uses the **same** tile `format` and `compression` for all files (as reported by the reader).
 //! - Writes TileJSON first, then streams all tiles from the reader (order is not significant).
 //! - The output path can be relative or absolute; parent directories must exist or be creatable.
+//! - The archive is only appended to, so it can also be streamed into a non-seekable [`DataWriterTrait`],
+//!   e.g. stdout.
 //!
 //! ## Errors
 //! Returns errors if the archive file cannot be created, or if encoding/compression of
 //! tiles/TileJSON fails while streaming from the reader.
 
 use crate::{ProcessingConfig, TilesReaderTrait, TilesReaderTraverseExt, TilesWriterTrait};
-use anyhow::{Result, bail};
+use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use futures::lock::Mutex;
 use std::{
 	fs::File,
+	io::{self, Write},
 	path::{Path, PathBuf},
 	sync::Arc,
 };
 use tar::{Builder, Header};
-use versatiles_core::{Traversal, io::DataWriterTrait, utils::compress};
+use versatiles_core::{Blob, Traversal, io::DataWriterTrait, utils::compress};
 use versatiles_derive::context;
 
 /// Writer for tiles packaged inside a tar archive.
@@ -50,72 +53,95 @@ impl TilesWriterTrait for TarTilesWriter {
 	/// serialization or compression fails.
 	#[context("writing tar to path '{}'", path.display())]
 	async fn write_to_path(reader: &mut dyn TilesReaderTrait, path: &Path, config: ProcessingConfig) -> Result<()> {
-		let file = File::create(path)?;
-		let mut builder = Builder::new(file);
-
-		let parameters = reader.parameters();
-		let tile_format = &parameters.tile_format.clone();
-		let tile_compression = reader.parameters().tile_compression;
-
-		let extension_format = tile_format.as_extension();
-		let extension_compression = tile_compression.as_extension();
-
-		let meta_data = compress(reader.tilejson().into(), tile_compression)?;
-		let filename = format!("tiles.json{extension_compression}");
-		let mut header = Header::new_gnu();
-		header.set_size(meta_data.len() as u64);
-		header.set_mode(0o644);
-		builder.append_data(&mut header, Path::new(&filename), meta_data.as_slice())?;
-
-		let builder_mutex = Arc::new(Mutex::new(builder));
-
-		reader
-			.traverse_all_tiles(
-				&Traversal::ANY,
-				|_bbox, mut stream| {
-					let builder_mutex = Arc::clone(&builder_mutex);
-					Box::pin(async move {
-						let mut builder = builder_mutex.lock().await;
-						while let Some((coord, tile)) = stream.next().await {
-							let filename = format!(
-								"./{}/{}/{}{}{}",
-								coord.level, coord.x, coord.y, extension_format, extension_compression
-							);
-							let path = PathBuf::from(&filename);
-
-							let blob = tile.into_blob(tile_compression)?;
-
-							// Build header
-							let mut header = Header::new_gnu();
-							header.set_size(blob.len());
-							header.set_mode(0o644);
-
-							// Write blob to file
-							builder.append_data(&mut header, path, blob.as_slice())?;
-						}
-						Ok(())
-					})
-				},
-				config,
-			)
-			.await?;
-
-		builder_mutex.lock().await.finish()?;
-
+		write_tar(reader, File::create(path)?, config).await?;
 		Ok(())
 	}
 
-	/// Not implemented: streaming a tar archive to an abstract `DataWriterTrait`.
+	/// Stream the tar archive into `writer`, e.g. a [`DataWriterStream`](versatiles_core::io::DataWriterStream)
+	/// on stdout.
+	///
+	/// The archive is only appended to, so the writer does not need to be seekable.
+	/// The writer is flushed at the end.
 	///
 	/// # Errors
-	/// Always returns `not implemented`.
+	/// Returns an error if writing fails, or if any tile/metadata serialization or compression fails.
 	#[context("writing tar to DataWriter")]
 	async fn write_to_writer(
-		_reader: &mut dyn TilesReaderTrait,
-		_writer: &mut dyn DataWriterTrait,
-		_config: ProcessingConfig,
+		reader: &mut dyn TilesReaderTrait,
+		writer: &mut dyn DataWriterTrait,
+		config: ProcessingConfig,
 	) -> Result<()> {
-		bail!("not implemented")
+		let sink = write_tar(reader, DataWriterSink(writer), config).await?;
+		sink.0.flush()
+	}
+}
+
+/// Writes the TileJSON and all tiles of `reader` as a tar archive into `sink`, and returns the sink.
+async fn write_tar<W: Write + Send>(reader: &mut dyn TilesReaderTrait, sink: W, config: ProcessingConfig) -> Result<W> {
+	let mut builder = Builder::new(sink);
+
+	let parameters = reader.parameters();
+	let tile_format = &parameters.tile_format.clone();
+	let tile_compression = reader.parameters().tile_compression;
+
+	let extension_format = tile_format.as_extension();
+	let extension_compression = tile_compression.as_extension();
+
+	let meta_data = compress(reader.tilejson().into(), tile_compression)?;
+	let filename = format!("tiles.json{extension_compression}");
+	let mut header = Header::new_gnu();
+	header.set_size(meta_data.len() as u64);
+	header.set_mode(0o644);
+	builder.append_data(&mut header, Path::new(&filename), meta_data.as_slice())?;
+
+	let builder_mutex = Arc::new(Mutex::new(builder));
+
+	reader
+		.traverse_all_tiles(
+			&Traversal::ANY,
+			|_bbox, mut stream| {
+				let builder_mutex = Arc::clone(&builder_mutex);
+				Box::pin(async move {
+					let mut builder = builder_mutex.lock().await;
+					while let Some((coord, tile)) = stream.next().await {
+						let filename = format!(
+							"./{}/{}/{}{}{}",
+							coord.level, coord.x, coord.y, extension_format, extension_compression
+						);
+						let path = PathBuf::from(&filename);
+
+						let blob = tile.into_blob(tile_compression)?;
+
+						// Build header
+						let mut header = Header::new_gnu();
+						header.set_size(blob.len());
+						header.set_mode(0o644);
+
+						// Write blob to file
+						builder.append_data(&mut header, path, blob.as_slice())?;
+					}
+					Ok(())
+				})
+			},
+			config,
+		)
+		.await?;
+
+	let builder = Arc::into_inner(builder_mutex).ok_or_else(|| anyhow!("tar builder is still in use"))?;
+	Ok(builder.into_inner().into_inner()?)
+}
+
+/// Adapts a [`DataWriterTrait`] to [`Write`], so the tar builder can append to it.
+struct DataWriterSink<'a>(&'a mut dyn DataWriterTrait);
+
+impl Write for DataWriterSink<'_> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.append(&Blob::from(buf)).map_err(io::Error::other)?;
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.0.flush().map_err(io::Error::other)
 	}
 }
 
@@ -124,7 +150,7 @@ mod tests {
 	use super::*;
 	use crate::{MockTilesReader, MockTilesWriter, TarTilesReader};
 	use assert_fs::NamedTempFile;
-	use versatiles_core::*;
+	use versatiles_core::{io::DataWriterStream, *};
 
 	#[tokio::test]
 	async fn read_write() -> Result<()> {
@@ -267,4 +293,24 @@ mod tests {
 
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn write_to_stream() -> Result<()> {
+		let mut mock_reader = MockTilesReader::new_mock(TilesReaderParameters {
+			bbox_pyramid: TileBBoxPyramid::new_full(2),
+			tile_compression: TileCompression::Gzip,
+			tile_format: TileFormat::MVT,
+		})?;
+
+		let mut writer = DataWriterStream::new(Vec::new());
+		TarTilesWriter::write_to_writer(&mut mock_reader, &mut writer, ProcessingConfig::default()).await?;
+		let data = writer.into_inner()?;
+
+		let temp_path = NamedTempFile::new("test_stream.tar")?;
+		std::fs::write(&temp_path, &data)?;
+		let reader = TarTilesReader::open_path(&temp_path)?;
+		assert_eq!(reader.parameters().bbox_pyramid.count_tiles(), 21);
+
+		Ok(())
+	}
 }
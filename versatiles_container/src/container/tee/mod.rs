@@ -0,0 +1,9 @@
+//! Share one reader between several consumers
+//!
+//! This module provides `TeeReader`, which splits a tile reader into several readers that all return the same
+//! tiles, while every tile is read from the source only once. This allows e.g. writing several containers in a
+//! single pass, sharing the expensive reading and processing of the tiles.
+
+mod reader;
+
+pub use reader::TeeReader;
@@ -0,0 +1,465 @@
+//! This module provides a reader that shares one source reader between several consumers.
+//!
+//! [`TeeReader::split`] wraps a reader and returns several `TeeReader`s. The source is read in blocks of up to
+//! 64×64 tiles. A block is read once, when the first consumer needs it, and kept in memory until every consumer
+//! has read all of its tiles. Consumers may use different traversal orders and bbox sizes.
+//!
+//! To limit memory, at most [`TeeReader::MAX_BLOCKS`] blocks are kept at once: a consumer that needs a new block
+//! waits until the others have caught up. If all other consumers are waiting too, the limit is exceeded instead,
+//! so consumers that read in incompatible orders cannot deadlock.
+//!
+//! Dropping a `TeeReader` releases all blocks that are only kept for it.
+//!
+//! ## Usage
+//! ```rust
+//! use versatiles_container::*;
+//! use versatiles_core::*;
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let registry = ContainerRegistry::default();
+//!     let reader = registry.open_reader("../testdata/berlin.mbtiles").await?;
+//!
+//!     let mut readers = TeeReader::split(reader, 2)?.into_iter();
+//!     let temp_dir = assert_fs::TempDir::new()?;
+//!     let path1 = temp_dir.join("tee1.versatiles");
+//!     let path2 = temp_dir.join("tee2.pmtiles");
+//!     futures::try_join!(
+//!         registry.write_to_path(readers.next().unwrap().boxed(), &path1),
+//!         registry.write_to_path(readers.next().unwrap().boxed(), &path2),
+//!     )?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Tile, TilesReaderTrait};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use std::{
+	collections::HashMap,
+	fmt::Debug,
+	sync::{Arc, Mutex},
+};
+use tokio::sync::{Notify, OnceCell};
+use versatiles_core::*;
+use versatiles_derive::context;
+
+/// The maximum width and height of the blocks that are read from the source.
+const BLOCK_SIZE: u32 = 64;
+
+type BlockTiles = HashMap<TileCoord, Tile>;
+
+/// A block of tiles that has been read (or is being read) from the source.
+struct Block {
+	tiles: Arc<OnceCell<BlockTiles>>,
+	/// For every consumer, the number of tiles of this block it has not read yet.
+	remaining: Vec<u64>,
+}
+
+/// The state of a consumer.
+#[derive(Clone, Copy, Default)]
+struct Consumer {
+	dropped: bool,
+	/// Number of `get_tile_stream` calls that are currently reading.
+	running: usize,
+	/// Number of `get_tile_stream` calls that are waiting for a free block.
+	waiting: usize,
+}
+
+impl Consumer {
+	fn is_blocked(&self) -> bool {
+		self.dropped || (self.running == 0 && self.waiting > 0)
+	}
+}
+
+struct State {
+	blocks: HashMap<TileBBox, Block>,
+	consumers: Vec<Consumer>,
+}
+
+struct Shared {
+	reader: Box<dyn TilesReaderTrait>,
+	block_size: u32,
+	state: Mutex<State>,
+	/// Notified whenever a block is released or a consumer is blocked.
+	changed: Notify,
+}
+
+impl Shared {
+	/// Returns the tiles of block `key`, reading it from the source if necessary.
+	async fn get_block(&self, key: TileBBox, index: usize) -> Result<Arc<OnceCell<BlockTiles>>> {
+		loop {
+			let notified = {
+				let mut state = self.state.lock().unwrap();
+				if let Some(block) = state.blocks.get(&key) {
+					return Ok(block.tiles.clone());
+				}
+
+				let others_blocked = state
+					.consumers
+					.iter()
+					.enumerate()
+					.all(|(i, consumer)| i == index || consumer.is_blocked());
+				if state.blocks.len() < TeeReader::MAX_BLOCKS || others_blocked {
+					let count = key.count_tiles();
+					let remaining = state
+						.consumers
+						.iter()
+						.map(|consumer| if consumer.dropped { 0 } else { count })
+						.collect();
+					let tiles = Arc::new(OnceCell::new());
+					state.blocks.insert(
+						key,
+						Block {
+							tiles: tiles.clone(),
+							remaining,
+						},
+					);
+					return Ok(tiles);
+				}
+
+				let consumer = &mut state.consumers[index];
+				consumer.running -= 1;
+				consumer.waiting += 1;
+				let notified = self.changed.notified();
+				if consumer.is_blocked() {
+					self.changed.notify_waiters();
+				}
+				notified
+			};
+
+			notified.await;
+
+			let mut state = self.state.lock().unwrap();
+			let consumer = &mut state.consumers[index];
+			consumer.waiting -= 1;
+			consumer.running += 1;
+		}
+	}
+
+	/// Marks `count` tiles of block `key` as read by consumer `index` and frees the block once everybody read it.
+	fn release(&self, key: &TileBBox, index: usize, count: u64) {
+		let mut state = self.state.lock().unwrap();
+		let Some(block) = state.blocks.get_mut(key) else {
+			return;
+		};
+		block.remaining[index] = block.remaining[index].saturating_sub(count);
+		if block.remaining.iter().all(|r| *r == 0) {
+			state.blocks.remove(key);
+			self.changed.notify_waiters();
+		}
+	}
+
+	async fn read_block(&self, key: TileBBox) -> Result<BlockTiles> {
+		Ok(self
+			.reader
+			.get_tile_stream(key)
+			.await?
+			.to_vec()
+			.await
+			.into_iter()
+			.collect())
+	}
+}
+
+/// One of several readers that share a single source reader, see the [module documentation](self).
+pub struct TeeReader {
+	shared: Arc<Shared>,
+	index: usize,
+	parameters: TilesReaderParameters,
+}
+
+impl TeeReader {
+	/// The maximum number of blocks that are kept in memory, unless all consumers would have to wait.
+	pub const MAX_BLOCKS: usize = 64;
+
+	/// Splits `reader` into `count` readers that return the same tiles, while reading every tile only once.
+	///
+	/// # Errors
+	/// Returns an error if `count` is zero.
+	#[context("splitting reader into {count} readers")]
+	pub fn split(reader: Box<dyn TilesReaderTrait>, count: usize) -> Result<Vec<TeeReader>> {
+		ensure!(count > 0, "tee needs at least one consumer");
+
+		let block_size = reader.traversal().max_size().unwrap_or(BLOCK_SIZE).min(BLOCK_SIZE);
+		let parameters = reader.parameters().clone();
+		let shared = Arc::new(Shared {
+			reader,
+			block_size,
+			state: Mutex::new(State {
+				blocks: HashMap::new(),
+				consumers: vec![Consumer::default(); count],
+			}),
+			changed: Notify::new(),
+		});
+
+		Ok((0..count)
+			.map(|index| TeeReader {
+				shared: shared.clone(),
+				index,
+				parameters: parameters.clone(),
+			})
+			.collect())
+	}
+
+	fn source(&self) -> &dyn TilesReaderTrait {
+		self.shared.reader.as_ref()
+	}
+
+	async fn read_part(&self, part: TileBBox) -> Result<Vec<(TileCoord, Tile)>> {
+		// blocks of low zoom levels may be bigger than the whole level
+		let mut key = if part.max_count() >= self.shared.block_size {
+			part.rounded(self.shared.block_size)
+		} else {
+			TileBBox::new_full(part.level)?
+		};
+		key.intersect_with_pyramid(&self.parameters.bbox_pyramid);
+
+		let cell = self.shared.get_block(key, self.index).await?;
+		let tiles = cell.get_or_try_init(|| self.shared.read_block(key)).await;
+		let tiles = match tiles {
+			Ok(tiles) => part
+				.iter_coords()
+				.filter_map(|coord| Some((coord, tiles.get(&coord)?.clone())))
+				.collect::<Vec<_>>(),
+			Err(err) => {
+				self.shared.release(&key, self.index, part.count_tiles());
+				return Err(err);
+			}
+		};
+		self.shared.release(&key, self.index, part.count_tiles());
+
+		let compression = self.parameters.tile_compression;
+		tiles
+			.into_iter()
+			.map(|(coord, mut tile)| {
+				tile.change_compression(compression)?;
+				Ok((coord, tile))
+			})
+			.collect()
+	}
+}
+
+impl Drop for TeeReader {
+	fn drop(&mut self) {
+		let mut state = self.shared.state.lock().unwrap();
+		state.consumers[self.index].dropped = true;
+		let index = self.index;
+		state.blocks.retain(|_, block| {
+			block.remaining[index] = 0;
+			block.remaining.iter().any(|r| *r > 0)
+		});
+		self.shared.changed.notify_waiters();
+	}
+}
+
+impl Debug for TeeReader {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("TeeReader")
+			.field("index", &self.index)
+			.field("parameters", &self.parameters)
+			.field("reader", &self.shared.reader)
+			.finish()
+	}
+}
+
+#[async_trait]
+impl TilesReaderTrait for TeeReader {
+	fn source_name(&self) -> &str {
+		self.source().source_name()
+	}
+
+	fn container_name(&self) -> &str {
+		self.source().container_name()
+	}
+
+	fn parameters(&self) -> &TilesReaderParameters {
+		&self.parameters
+	}
+
+	fn override_compression(&mut self, tile_compression: TileCompression) {
+		self.parameters.tile_compression = tile_compression;
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		self.source().tilejson()
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.source().traversal()
+	}
+
+	/// Single tiles are read directly from the source, without sharing.
+	#[context("getting tile {:?} from tee reader", coord)]
+	async fn get_tile(&self, coord: &TileCoord) -> Result<Option<Tile>> {
+		let Some(mut tile) = self.source().get_tile(coord).await? else {
+			return Ok(None);
+		};
+		tile.change_compression(self.parameters.tile_compression)?;
+		Ok(Some(tile))
+	}
+
+	#[context("getting tile stream for {:?} from tee reader", bbox)]
+	async fn get_tile_stream(&self, mut bbox: TileBBox) -> Result<TileStream<Tile>> {
+		bbox.intersect_with_pyramid(&self.parameters.bbox_pyramid);
+
+		self.shared.state.lock().unwrap().consumers[self.index].running += 1;
+		let mut result = Vec::new();
+		let mut error = None;
+		let parts: Vec<TileBBox> = bbox.iter_bbox_grid(self.shared.block_size).collect();
+		for part in parts {
+			match self.read_part(part).await {
+				Ok(tiles) => result.extend(tiles),
+				Err(err) => {
+					error = Some(err);
+					break;
+				}
+			}
+		}
+		{
+			let mut state = self.shared.state.lock().unwrap();
+			let consumer = &mut state.consumers[self.index];
+			consumer.running -= 1;
+			if consumer.is_blocked() {
+				self.shared.changed.notify_waiters();
+			}
+		}
+
+		match error {
+			Some(err) => Err(err),
+			None => Ok(TileStream::from_vec(result)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{MockTilesReader, ProcessingConfig, TilesReaderTraverseExt};
+	use futures::future::BoxFuture;
+	use std::sync::atomic::{AtomicU64, Ordering};
+
+	/// Counts how many tiles are read from the wrapped reader.
+	#[derive(Debug)]
+	struct CountingReader {
+		reader: MockTilesReader,
+		count: Arc<AtomicU64>,
+	}
+
+	#[async_trait]
+	impl TilesReaderTrait for CountingReader {
+		fn source_name(&self) -> &str {
+			self.reader.source_name()
+		}
+		fn container_name(&self) -> &str {
+			self.reader.container_name()
+		}
+		fn parameters(&self) -> &TilesReaderParameters {
+			self.reader.parameters()
+		}
+		fn override_compression(&mut self, tile_compression: TileCompression) {
+			self.reader.override_compression(tile_compression);
+		}
+		fn tilejson(&self) -> &TileJSON {
+			self.reader.tilejson()
+		}
+		async fn get_tile(&self, coord: &TileCoord) -> Result<Option<Tile>> {
+			self.count.fetch_add(1, Ordering::Relaxed);
+			self.reader.get_tile(coord).await
+		}
+	}
+
+	fn new_readers(count: usize, level_max: u8) -> Result<(Vec<TeeReader>, Arc<AtomicU64>)> {
+		let counter = Arc::new(AtomicU64::new(0));
+		let reader = CountingReader {
+			reader: MockTilesReader::new_mock(TilesReaderParameters::new(
+				TileFormat::MVT,
+				TileCompression::Gzip,
+				TileBBoxPyramid::new_full(level_max),
+			))?,
+			count: counter.clone(),
+		};
+		Ok((TeeReader::split(reader.boxed(), count)?, counter))
+	}
+
+	async fn count_tiles(reader: &TeeReader, traversal: &Traversal) -> Result<u64> {
+		let count = Arc::new(AtomicU64::new(0));
+		reader
+			.traverse_all_tiles(
+				traversal,
+				|_bbox, stream| -> BoxFuture<'_, Result<()>> {
+					let count = count.clone();
+					Box::pin(async move {
+						let n = stream.drain_and_count().await;
+						count.fetch_add(n, Ordering::Relaxed);
+						Ok(())
+					})
+				},
+				ProcessingConfig::default(),
+			)
+			.await?;
+		Ok(count.load(Ordering::Relaxed))
+	}
+
+	#[tokio::test]
+	async fn reads_every_tile_once() -> Result<()> {
+		let (readers, counter) = new_readers(3, 7)?;
+		let total = TileBBoxPyramid::new_full(7).count_tiles();
+
+		let traversals = [
+			Traversal::ANY,
+			Traversal::new_any_size(256, 256)?,
+			Traversal::new(TraversalOrder::PMTiles, 1, 64)?,
+		];
+		let counts = futures::future::try_join_all(
+			readers
+				.iter()
+				.zip(traversals.iter())
+				.map(|(reader, traversal)| count_tiles(reader, traversal)),
+		)
+		.await?;
+
+		assert_eq!(counts, vec![total; 3]);
+		assert_eq!(counter.load(Ordering::Relaxed), total);
+		assert!(readers[0].shared.state.lock().unwrap().blocks.is_empty());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn dropped_reader_releases_blocks() -> Result<()> {
+		let (mut readers, _counter) = new_readers(2, 3)?;
+		let second = readers.pop().unwrap();
+		assert_eq!(count_tiles(&readers[0], &Traversal::ANY).await?, 85);
+		assert!(!readers[0].shared.state.lock().unwrap().blocks.is_empty());
+
+		drop(second);
+		assert!(readers[0].shared.state.lock().unwrap().blocks.is_empty());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn override_compression() -> Result<()> {
+		let (mut readers, _counter) = new_readers(2, 1)?;
+		readers[1].override_compression(TileCompression::Uncompressed);
+
+		let bbox = TileBBox::new_full(1)?;
+		let tiles0 = readers[0].get_tile_stream(bbox).await?.to_vec().await;
+		let tiles1 = readers[1].get_tile_stream(bbox).await?.to_vec().await;
+		assert_eq!(tiles0.len(), 4);
+		assert_eq!(tiles0[0].1.compression(), TileCompression::Gzip);
+		assert_eq!(tiles1[0].1.compression(), TileCompression::Uncompressed);
+		Ok(())
+	}
+
+	#[test]
+	fn split_needs_consumers() -> Result<()> {
+		let (_readers, _counter) = new_readers(1, 1)?;
+		let reader = MockTilesReader::new_mock(TilesReaderParameters::new(
+			TileFormat::MVT,
+			TileCompression::Gzip,
+			TileBBoxPyramid::new_full(1),
+		))?;
+		assert!(TeeReader::split(reader.boxed(), 0).is_err());
+		Ok(())
+	}
+}
@@ -44,6 +44,8 @@
 //! ```
 
 mod types;
+#[cfg(feature = "arbitrary")]
+pub(crate) use types::{BlockIndex, FileHeader, TileIndex};
 
 mod reader;
 pub use reader::VersaTilesReader;
@@ -8,11 +8,18 @@
 //! a Brotli-compressed tile index (byte ranges), followed by a contiguous region of
 //! tile blobs. This reader lazily caches decoded tile indices for fast random access.
 //!
+//! If the file contains a **time index**, the modification times of the tiles are read
+//! lazily per block as well and attached to the tiles, see [`Tile::mtime`].
+//!
+//! Files written as a stream, see [`VersaTilesWriter::write_to_stream`](crate::VersaTilesWriter::write_to_stream),
+//! have their final header at the end. To open them, the [`DataReader`] must know its size.
+//!
 //! ## Extracted artifacts
 //! - `tilejson`: parsed TileJSON from the `meta_range` (if present)
 //! - `parameters`: [`TilesReaderParameters`] with `tile_format`, `tile_compression`, and a
 //!   **bbox pyramid** computed from the block index
 //! - `block_index`: lightweight structure describing all block ranges
+//! - `time_index`: optional byte ranges of the tile modification times per block
 //!
 //! ## Usage
 //! ```rust,no_run
@@ -53,15 +60,18 @@
 //! Returns errors when the file cannot be read or decompressed, when metadata/index parsing fails,
 //! or when a requested tile is missing.
 
-use super::types::{BlockDefinition, BlockIndex, FileHeader, TileIndex};
-use crate::{Tile, TilesReaderTrait};
+use super::types::{
+	BLOCK_INDEX_LENGTH, BlockDefinition, BlockIndex, FileHeader, TIME_INDEX_LENGTH, TileIndex, TileTimes, TimeIndex,
+	layout::{schema, section},
+};
+use crate::{ReaderLimits, Tile, TilesReaderTrait};
 use anyhow::Result;
 use async_trait::async_trait;
-use futures::{lock::Mutex, stream::StreamExt};
+use futures::{TryStreamExt, lock::Mutex, stream::StreamExt};
 use std::{fmt::Debug, ops::Shr, path::Path, sync::Arc};
 #[cfg(feature = "cli")]
 use versatiles_core::utils::PrettyPrint;
-use versatiles_core::{io::*, utils::decompress, *};
+use versatiles_core::{io::*, json::JsonObject, utils::decompress_limited, *};
 use versatiles_derive::context;
 
 /// Reader for `.versatiles` containers.
@@ -75,7 +85,10 @@ pub struct VersaTilesReader {
 	parameters: TilesReaderParameters,
 	reader: DataReader,
 	tile_index_cache: Mutex<LimitedCache<TileCoord, Arc<TileIndex>>>,
+	tile_times_cache: Mutex<LimitedCache<TileCoord, Arc<TileTimes>>>,
 	tilejson: TileJSON,
+	time_index: TimeIndex,
+	limits: ReaderLimits,
 }
 
 impl VersaTilesReader {
@@ -99,30 +112,57 @@ impl VersaTilesReader {
 	///
 	/// # Errors
 	/// Returns an error if header/metadata/index reads or decompressions fail.
+	pub async fn open_reader(reader: DataReader) -> Result<VersaTilesReader> {
+		VersaTilesReader::open_reader_with_limits(reader, ReaderLimits::default()).await
+	}
+
+	/// Open a `.versatiles` container like [`VersaTilesReader::open_reader`], but with custom [`ReaderLimits`].
+	///
+	/// Use stricter limits when serving files from untrusted sources.
+	///
+	/// # Errors
+	/// Returns a [`ContainerError`](crate::ContainerError) if the file exceeds the `limits` or is inconsistent,
+	/// or any other error if reads or decompressions fail.
 	#[context("Failed to open versatiles reader")]
-	pub async fn open_reader(mut reader: DataReader) -> Result<VersaTilesReader> {
+	pub async fn open_reader_with_limits(mut reader: DataReader, limits: ReaderLimits) -> Result<VersaTilesReader> {
 		let header = FileHeader::from_reader(&mut reader)
 			.await
 			.context("Failed reading the header")?;
+		limits.check_range(&header.meta_range)?;
+		limits.check_range(&header.blocks_range)?;
+		limits.check_range(&header.times_range)?;
 
 		let tilejson = if header.meta_range.length > 0 {
 			let blob = reader
 				.read_range(&header.meta_range)
 				.await
 				.context("Failed reading the meta data")?;
-			let blob = decompress(blob, header.compression).context("Failed decompressing the meta data")?;
+			let blob = decompress_limited(blob, header.compression, limits.max_decompressed_size)
+				.context("Failed decompressing the meta data")?;
 			TileJSON::try_from_blob_or_default(&blob)
 		} else {
 			TileJSON::default()
 		};
 
-		let block_index = BlockIndex::from_brotli_blob(
-			reader
-				.read_range(&header.blocks_range)
+		let blob = reader
+			.read_range(&header.blocks_range)
+			.await
+			.context("Failed reading the block index")?;
+		let blob = decompress_limited(blob, TileCompression::Brotli, limits.max_decompressed_size)
+			.context("Failed decompressing the block index")?;
+		let block_index = BlockIndex::from_blob(blob)?;
+		limits.check_entries(block_index.len() as u64)?;
+
+		let time_index = if header.times_range.length > 0 {
+			let blob = reader
+				.read_range(&header.times_range)
 				.await
-				.context("Failed reading the block index")?,
-		)
-		.context("Failed decompressing the block index")?;
+				.context("Failed reading the time index")?;
+			TimeIndex::from_brotli_blob(blob, limits.max_decompressed_size)?
+		} else {
+			TimeIndex::new_empty()
+		};
+		limits.check_entries(time_index.len() as u64)?;
 
 		let bbox_pyramid = block_index.get_bbox_pyramid();
 		let parameters = TilesReaderParameters::new(header.tile_format, header.compression, bbox_pyramid);
@@ -133,10 +173,92 @@ impl VersaTilesReader {
 			parameters,
 			reader,
 			tile_index_cache: Mutex::new(LimitedCache::with_maximum_size(100_000_000)),
+			tile_times_cache: Mutex::new(LimitedCache::with_maximum_size(100_000_000)),
 			tilejson,
+			time_index,
+			limits,
 		})
 	}
 
+	/// Describes the binary layout of the file as JSON, e.g. for `versatiles probe --layout`.
+	///
+	/// Lists every field of the header and its extension, and the sections with metadata, block index
+	/// and time index. Index entries are listed in the order in which they are stored, each with its
+	/// `offset` in the decompressed index. Useful for debugging and to check other implementations
+	/// against the [specification](https://github.com/versatiles-org/versatiles-spec).
+	///
+	/// # Errors
+	/// Returns an error if an index cannot be read or decompressed.
+	#[context("Failed to describe the layout of the versatiles file")]
+	pub async fn layout(&self) -> Result<JsonObject> {
+		let header = &self.header;
+		let mut json = JsonObject::new();
+		json.set("header", header.layout()?);
+		if let Some(extension) = header.extension_layout() {
+			json.set("header_extension", extension);
+		}
+
+		if header.meta_range.length > 0 {
+			let mut meta = section(&header.meta_range);
+			meta.set("compression", header.compression.as_str());
+			json.set("meta", meta);
+		}
+
+		if header.blocks_range.length > 0 {
+			let blob = self.read_index(&header.blocks_range).await?;
+			let mut block_index = section(&header.blocks_range);
+			block_index.set("compression", "brotli");
+			block_index.set("decompressed_length", blob.len());
+			block_index.set("entry_length", BLOCK_INDEX_LENGTH);
+			block_index.set("entry_fields", schema(&BlockDefinition::LAYOUT));
+			block_index.set("tile_index_compression", "brotli");
+			let entries = BlockIndex::definitions_from_blob(&blob)?
+				.iter()
+				.enumerate()
+				.map(|(i, block)| {
+					let mut entry = block.layout()?;
+					entry.set("offset", i as u64 * BLOCK_INDEX_LENGTH);
+					Ok(entry)
+				})
+				.collect::<Result<Vec<_>>>()?;
+			block_index.set("entries", entries);
+			json.set("block_index", block_index);
+		}
+
+		if header.times_range.length > 0 {
+			let blob = self.read_index(&header.times_range).await?;
+			let mut time_index = section(&header.times_range);
+			time_index.set("compression", "brotli");
+			time_index.set("decompressed_length", blob.len());
+			time_index.set("entry_length", TIME_INDEX_LENGTH);
+			time_index.set("entry_fields", schema(&TimeIndex::LAYOUT));
+			let entries = TimeIndex::entries_from_blob(blob)?
+				.iter()
+				.enumerate()
+				.map(|(i, (coord, range))| {
+					let mut entry = JsonObject::new();
+					entry.set("offset", i as u64 * TIME_INDEX_LENGTH);
+					entry.set("level", coord.level);
+					entry.set("x", coord.x);
+					entry.set("y", coord.y);
+					entry.set("times_offset", range.offset);
+					entry.set("times_length", range.length);
+					entry
+				})
+				.collect::<Vec<_>>();
+			time_index.set("entries", entries);
+			json.set("time_index", time_index);
+		}
+
+		Ok(json)
+	}
+
+	/// Reads and decompresses a Brotli compressed index.
+	async fn read_index(&self, range: &ByteRange) -> Result<Blob> {
+		let blob = self.reader.read_range(range).await?;
+		decompress_limited(blob, TileCompression::Brotli, self.limits.max_decompressed_size)
+	}
+
 	/// Load (and cache) the tile index for a block.
 	///
 	/// Reads the block's index blob, decompresses it, adjusts offsets to the tiles segment,
@@ -153,16 +275,52 @@ impl VersaTilesReader {
 		Ok(if let Some(value) = cache.get(block_coord) {
 			value
 		} else {
+			self.limits.check_range(block.get_index_range())?;
 			let blob = self.reader.read_range(block.get_index_range()).await?;
-			let mut tile_index = TileIndex::from_brotli_blob(blob)?;
-			tile_index.add_offset(block.get_tiles_range().offset);
-
-			assert_eq!(tile_index.len(), block.count_tiles() as usize);
+			let mut tile_index = TileIndex::from_brotli_blob_with_count(blob, block.count_tiles())?;
+			tile_index.add_offset(block.get_tiles_range().offset)?;
 
 			cache.add(*block_coord, Arc::new(tile_index))
 		})
 	}
 
+	/// Returns the modification times of the tiles in `block`, or `None` if the block has no known times.
+	#[context("Failed to get tile times for block {block:?}")]
+	async fn get_block_tile_times(&self, block: &BlockDefinition) -> Result<Option<Arc<TileTimes>>> {
+		let block_coord = block.get_coord();
+		let Some(range) = self.time_index.get_block(block_coord) else {
+			return Ok(None);
+		};
+
+		let mut cache = self.tile_times_cache.lock().await;
+
+		Ok(Some(if let Some(value) = cache.get(block_coord) {
+			value
+		} else {
+			self.limits.check_range(range)?;
+			let blob = self.reader.read_range(range).await?;
+			let tile_times = TileTimes::from_brotli_blob_with_count(blob, block.count_tiles())?;
+
+			cache.add(*block_coord, Arc::new(tile_times))
+		}))
+	}
+
+	/// Returns the block containing `coord` and the index of the tile within it, if the block exists.
+	fn find_tile(&self, coord: &TileCoord) -> Result<Option<(BlockDefinition, usize)>> {
+		let block_coord = TileCoord::new(coord.level, coord.x.shr(8), coord.y.shr(8))?;
+		let Some(block) = self.block_index.get_block(&block_coord) else {
+			return Ok(None);
+		};
+
+		let bbox = block.get_global_bbox();
+		if !bbox.contains(coord) {
+			log::trace!("tile {coord:?} outside block definition");
+			return Ok(None);
+		}
+
+		Ok(Some((block.clone(), bbox.index_of(coord)? as usize)))
+	}
+
 	/// Sum of all block index byte lengths.
 	fn get_index_size(&self) -> u64 {
 		self.block_index.iter().map(|b| b.get_index_range().length).sum()
@@ -177,7 +335,10 @@ impl VersaTilesReader {
 	///
 	/// Coalesces nearby ranges into at most ~64 MiB chunks (with a small gap tolerance)
 	/// to minimize I/O calls during streaming.
-	async fn get_chunks(&self, bbox: TileBBox) -> Vec<Chunk> {
+	///
+	/// # Errors
+	/// Returns an error if the tile index of a block can not be read.
+	async fn get_chunks(&self, bbox: TileBBox) -> Result<Vec<Chunk>> {
 		const MAX_CHUNK_SIZE: u64 = 64 * 1024 * 1024;
 		const MAX_CHUNK_GAP: u64 = 32 * 1024;
 
@@ -188,7 +349,7 @@ impl VersaTilesReader {
 				// Get the block using the block coordinate
 				let block_option = self.block_index.get_block(&block_coord);
 				if block_option.is_none() {
-					return Vec::new();
+					return Ok::<_, anyhow::Error>(Vec::new());
 				}
 
 				// Get the block
@@ -208,19 +369,23 @@ impl VersaTilesReader {
 				assert_eq!(bbox.level, tiles_bbox_used.level);
 
 				// Get the tile index of this block
-				let tile_index: Arc<TileIndex> = self.get_block_tile_index(&block).await.unwrap();
+				let tile_index: Arc<TileIndex> = self.get_block_tile_index(&block).await?;
 				log::trace!("tile_index.len() {}", tile_index.len());
+				let tile_times: Option<Arc<TileTimes>> = self.get_block_tile_times(&block).await?;
 
 				// let tile_range: &ByteRange = tile_index.get(tile_id);
-				let mut tile_ranges: Vec<(TileCoord, ByteRange)> = tile_index
+				let mut tile_ranges: Vec<(TileCoord, ByteRange, Option<u64>)> = tile_index
 					.iter()
 					.enumerate()
-					.map(|(index, range)| (tiles_bbox_block.coord_at_index(index as u64).unwrap(), *range))
-					.filter(|(coord, range)| tiles_bbox_used.contains(coord) && (range.length > 0))
+					.map(|(index, range)| {
+						let mtime = tile_times.as_ref().and_then(|times| times.get(index));
+						(tiles_bbox_block.coord_at_index(index as u64).unwrap(), *range, mtime)
+					})
+					.filter(|(coord, range, _)| tiles_bbox_used.contains(coord) && (range.length > 0))
 					.collect();
 
 				if tile_ranges.is_empty() {
-					return Vec::new();
+					return Ok(Vec::new());
 				}
 
 				tile_ranges.sort_by_key(|e| e.1.offset);
@@ -250,14 +415,13 @@ impl VersaTilesReader {
 					chunks.push(chunk);
 				}
 
-				chunks
+				Ok(chunks)
 			}
 		});
 
-		let chunks: Vec<Vec<Chunk>> = stream.collect().await;
+		let chunks: Vec<Vec<Chunk>> = stream.try_collect().await?;
 
-		let chunks: Vec<Chunk> = chunks.into_iter().flatten().collect();
-		chunks
+		Ok(chunks.into_iter().flatten().collect())
 	}
 }
 
@@ -268,7 +432,7 @@ unsafe impl Sync for VersaTilesReader {}
 // from a single large read. `range` tracks the combined byte span in the container.
 #[derive(Debug)]
 struct Chunk {
-	tiles: Vec<(TileCoord, ByteRange)>,
+	tiles: Vec<(TileCoord, ByteRange, Option<u64>)>,
 	range: ByteRange,
 }
 
@@ -279,7 +443,7 @@ impl Chunk {
 			range: ByteRange::new(start, 0),
 		}
 	}
-	fn push(&mut self, entry: (TileCoord, ByteRange)) {
+	fn push(&mut self, entry: (TileCoord, ByteRange, Option<u64>)) {
 		self.tiles.push(entry);
 		if entry.1.offset < self.range.offset {
 			panic!()
@@ -323,28 +487,10 @@ impl TilesReaderTrait for VersaTilesReader {
 	/// Returns `Ok(None)` for empty ranges or missing blocks.
 	#[context("fetching tile {:?} from '{}'", coord, self.reader.get_name())]
 	async fn get_tile(&self, coord: &TileCoord) -> Result<Option<Tile>> {
-		// Calculate block coordinate
-		let block_coord = TileCoord::new(coord.level, coord.x.shr(8), coord.y.shr(8))?;
-
-		// Get the block using the block coordinate
-		let block = self.block_index.get_block(&block_coord);
-
-		if block.is_none() {
-			return Ok(None);
-		}
-		let block = block.unwrap().clone();
-
-		// Get the block and its bounding box
-		let bbox = block.get_global_bbox();
-
-		// Check if the tile is within the block definition
-		if !bbox.contains(coord) {
-			log::trace!("tile {coord:?} outside block definition");
+		// Find the block and the tile ID within the block
+		let Some((block, tile_id)) = self.find_tile(coord)? else {
 			return Ok(None);
-		}
-
-		// Get the tile ID
-		let tile_id = bbox.index_of(coord).unwrap() as usize;
+		};
 
 		// Retrieve the tile index from cache or read from the reader
 		let tile_index: Arc<TileIndex> = self.get_block_tile_index(&block).await?;
@@ -356,35 +502,75 @@ impl TilesReaderTrait for VersaTilesReader {
 		}
 
 		// Read the tile data from the reader
+		self.limits.check_range(&tile_range)?;
 		let blob = self.reader.read_range(&tile_range).await?;
-		Ok(Some(Tile::from_blob(
-			blob,
-			self.parameters.tile_compression,
-			self.parameters.tile_format,
-		)))
+		let mut tile = Tile::from_blob(blob, self.parameters.tile_compression, self.parameters.tile_format);
+		if let Some(tile_times) = self.get_block_tile_times(&block).await? {
+			tile.set_mtime(tile_times.get(tile_id));
+		}
+		Ok(Some(tile))
+	}
+
+	/// Reads only the tile times of the block, without reading the tile itself.
+	#[context("fetching modification time of tile {:?} from '{}'", coord, self.reader.get_name())]
+	async fn get_tile_mtime(&self, coord: &TileCoord) -> Result<Option<u64>> {
+		let Some((block, tile_id)) = self.find_tile(coord)? else {
+			return Ok(None);
+		};
+		Ok(self
+			.get_block_tile_times(&block)
+			.await?
+			.and_then(|tile_times| tile_times.get(tile_id)))
+	}
+
+	/// Loads and caches the tile indices of all blocks overlapping `bbox`,
+	/// so that a following `get_tile_stream` only has to read tile data.
+	#[context("prefetching block indices for bbox {:?}", bbox)]
+	async fn prefetch(&self, bbox: &TileBBox) -> Result<()> {
+		let blocks: Vec<_> = bbox
+			.scaled_down(256)
+			.iter_coords()
+			.filter_map(|block_coord| self.block_index.get_block(&block_coord).cloned())
+			.collect();
+		for block in blocks {
+			self.get_block_tile_index(&block).await?;
+		}
+		Ok(())
 	}
 
 	#[context("streaming tiles for bbox {:?}", bbox)]
 	async fn get_tile_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
 		log::debug!("get_tile_stream {:?}", bbox);
-		let chunks = self.get_chunks(bbox).await;
+		let chunks = self.get_chunks(bbox).await?;
 		Ok(TileStream::from_stream(
 			futures::stream::iter(chunks)
 				.then(move |chunk| async move {
-					let big_blob = self.reader.read_range(&chunk.range).await.unwrap();
+					let big_blob = match self.reader.read_range(&chunk.range).await {
+						Ok(blob) => blob,
+						Err(err) => {
+							log::error!(
+								"skipping {} tiles, reading {:?} failed: {err:?}",
+								chunk.len(),
+								chunk.range
+							);
+							return futures::stream::iter(Vec::new());
+						}
+					};
 
 					let entries: Vec<(TileCoord, Tile)> = chunk
 						.tiles
 						.into_iter()
-						.map(|(coord, range)| {
+						.map(|(coord, range, mtime)| {
 							assert!(bbox.contains(&coord), "outer_bbox {bbox:?} does not contain {coord:?}");
 
 							let start = range.offset - chunk.range.offset;
 							let end = start + range.length;
 							let tile_range = (start as usize)..(end as usize);
 
-							let blob = Blob::from(big_blob.get_range(tile_range));
-							let tile = Tile::from_blob(blob, self.parameters.tile_compression, self.parameters.tile_format);
+							let blob = big_blob.slice(tile_range);
+							let mut tile =
+								Tile::from_blob(blob, self.parameters.tile_compression, self.parameters.tile_format);
+							tile.set_mtime(mtime);
 
 							(coord, tile)
 						})
@@ -415,6 +601,11 @@ impl TilesReaderTrait for VersaTilesReader {
 		print
 			.add_key_value("sum of block tiles sizes", &self.get_tiles_size())
 			.await;
+		if !self.time_index.is_empty() {
+			print
+				.add_key_value("blocks with tile times", &self.time_index.len())
+				.await;
+		}
 
 		Ok(())
 	}
@@ -463,7 +654,7 @@ impl TilesReaderTrait for VersaTilesReader {
 					y: coord.y,
 					z: coord.level,
 				});
-				biggest_tiles.sort_by(|a, b| b.size.cmp(&a.size));
+				biggest_tiles.sort_by_key(|b| std::cmp::Reverse(b.size));
 				while biggest_tiles.len() > 10 {
 					biggest_tiles.pop();
 				}
@@ -507,9 +698,15 @@ impl PartialEq for VersaTilesReader {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::{MOCK_BYTES_PBF, MockTilesReader, ProcessingConfig, TilesWriterTrait, VersaTilesWriter, make_test_file};
+	use crate::{
+		MOCK_BYTES_PBF, MemTilesReader, MockTilesReader, ProcessingConfig, TilesWriterTrait, VersaTilesWriter,
+		make_test_file,
+	};
 	use assert_fs::NamedTempFile;
-	use versatiles_core::{assert_wildcard, io::DataWriterBlob};
+	use versatiles_core::{
+		assert_wildcard,
+		io::{DataReaderBlob, DataWriterBlob, DataWriterStream},
+	};
 
 	// Helper to quickly create a test reader and bbox
 	async fn mk_reader() -> Result<(NamedTempFile, VersaTilesReader)> {
@@ -518,6 +715,25 @@ mod tests {
 		Ok((temp_file, reader))
 	}
 
+	#[tokio::test]
+	async fn prefetch_caches_block_indices() -> Result<()> {
+		let (_, reader) = mk_reader().await?;
+		let block_coord = TileCoord::new(4, 0, 0)?;
+		assert!(reader.tile_index_cache.lock().await.get(&block_coord).is_none());
+
+		reader.prefetch(&TileBBox::from_min_and_max(4, 2, 3, 5, 6)?).await?;
+		assert!(reader.tile_index_cache.lock().await.get(&block_coord).is_some());
+		assert!(
+			reader
+				.tile_index_cache
+				.lock()
+				.await
+				.get(&TileCoord::new(3, 0, 0)?)
+				.is_none()
+		);
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn reader() -> Result<()> {
 		let (_, reader) = mk_reader().await?;
@@ -616,6 +832,165 @@ mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn tile_times_roundtrip() -> Result<()> {
+		let mut source = MemTilesReader::new(TileFormat::JSON, TileCompression::Uncompressed);
+		for (x, mtime) in [(0, Some(1_700_000_000)), (1, None), (300, Some(1_800_000_000))] {
+			let mut tile = Tile::from_blob(Blob::from("{}"), TileCompression::Uncompressed, TileFormat::JSON);
+			tile.set_mtime(mtime);
+			source.insert(TileCoord::new(9, x, 5)?, tile)?;
+		}
+
+		let mut data_writer = DataWriterBlob::new()?;
+		VersaTilesWriter::write_to_writer(&mut source, &mut data_writer, ProcessingConfig::default()).await?;
+		let reader = VersaTilesReader::open_reader(Box::new(data_writer.to_reader())).await?;
+		assert_eq!(reader.time_index.len(), 2);
+
+		for (x, mtime) in [
+			(0, Some(1_700_000_000)),
+			(1, None),
+			(2, None),
+			(300, Some(1_800_000_000)),
+		] {
+			assert_eq!(reader.get_tile_mtime(&TileCoord::new(9, x, 5)?).await?, mtime);
+		}
+
+		let tile = reader.get_tile(&TileCoord::new(9, 300, 5)?).await?.unwrap();
+		assert_eq!(tile.mtime(), Some(1_800_000_000));
+
+		let bbox = TileBBox::from_min_and_max(9, 0, 5, 1, 5)?;
+		let mut mtimes: Vec<(u32, Option<u64>)> = reader
+			.get_tile_stream(bbox)
+			.await?
+			.to_vec()
+			.await
+			.into_iter()
+			.map(|(coord, tile)| (coord.x, tile.mtime()))
+			.collect();
+		mtimes.sort();
+		assert_eq!(mtimes, vec![(0, Some(1_700_000_000)), (1, None)]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn no_tile_times_without_mtimes() -> Result<()> {
+		let (_, reader) = mk_reader().await?;
+		assert_eq!(reader.header.times_range, ByteRange::empty());
+		assert!(reader.time_index.is_empty());
+		assert_eq!(reader.get_tile_mtime(&TileCoord::new(4, 1, 1)?).await?, None);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn layout() -> Result<()> {
+		let (temp_file, reader) = mk_reader().await?;
+		let file_size = std::fs::metadata(temp_file.path())?.len();
+		let layout = reader.layout().await?;
+		let number = |json: &JsonObject, key: &str| json.get_number(key).unwrap().unwrap() as u64;
+
+		// header fields are contiguous and fill the header
+		let header = layout.get_object("header")?.unwrap();
+		let mut offset = 0;
+		for field in header.get_array("fields")?.unwrap().as_vec() {
+			let field = field.as_object()?;
+			assert_eq!(number(field, "offset"), offset);
+			offset += number(field, "length");
+		}
+		assert_eq!(offset, 66);
+		assert_eq!(number(header, "length"), 66);
+		assert!(layout.get("header_extension").is_some());
+		assert!(layout.get("time_index").is_none());
+
+		// every block is described, and its tiles and tile index lie within the file
+		let block_index = layout.get_object("block_index")?.unwrap();
+		assert_eq!(number(block_index, "offset") + number(block_index, "length"), file_size);
+		let entries = block_index.get_array("entries")?.unwrap().as_vec();
+		assert_eq!(entries.len(), reader.block_index.len());
+		for (i, entry) in entries.iter().enumerate() {
+			let entry = entry.as_object()?;
+			assert_eq!(number(entry, "offset"), i as u64 * 33);
+			let index_offset = number(entry, "tiles_offset") + number(entry, "tiles_length");
+			assert_eq!(number(entry, "index_offset"), index_offset);
+			assert!(index_offset + number(entry, "index_length") <= file_size);
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn layout_with_time_index() -> Result<()> {
+		let mut source = MemTilesReader::new(TileFormat::JSON, TileCompression::Uncompressed);
+		let mut tile = Tile::from_blob(Blob::from("{}"), TileCompression::Uncompressed, TileFormat::JSON);
+		tile.set_mtime(Some(1_700_000_000));
+		source.insert(TileCoord::new(9, 300, 5)?, tile)?;
+
+		let mut data_writer = DataWriterBlob::new()?;
+		VersaTilesWriter::write_to_writer(&mut source, &mut data_writer, ProcessingConfig::default()).await?;
+		let reader = VersaTilesReader::open_reader(Box::new(data_writer.to_reader())).await?;
+		let layout = reader.layout().await?;
+
+		let time_index = layout.get_object("time_index")?.unwrap();
+		let entries = time_index.get_array("entries")?.unwrap().as_vec();
+		assert_eq!(entries.len(), reader.time_index.len());
+		let entry = entries[0].as_object()?;
+		assert_eq!(entry.get_number("level")?, Some(9.0));
+		assert_eq!(entry.get_number("offset")?, Some(0.0));
+		assert!(entry.get_number("times_length")? > Some(0.0));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn read_streamed_file() -> Result<()> {
+		let mut source = MockTilesReader::new_mock(TilesReaderParameters::new(
+			TileFormat::MVT,
+			TileCompression::Gzip,
+			TileBBoxPyramid::new_full(4),
+		))?;
+
+		let mut data_writer = DataWriterStream::new(Vec::new());
+		VersaTilesWriter::write_to_stream(&mut source, &mut data_writer, ProcessingConfig::default()).await?;
+		let data = Blob::from(data_writer.into_inner()?);
+		assert_eq!(&data.as_slice()[0..14], b"versatiles_s02");
+
+		let reader = VersaTilesReader::open_reader(Box::new(DataReaderBlob::from(data))).await?;
+		assert!(reader.header.streamed);
+		assert_eq!(reader.parameters().bbox_pyramid.count_tiles(), 341);
+		assert_eq!(
+			reader.tilejson().as_string(),
+			"{\"tilejson\":\"3.0.0\",\"type\":\"dummy\"}"
+		);
+		let blob = reader
+			.get_tile(&TileCoord::new(4, 15, 1)?)
+			.await?
+			.unwrap()
+			.into_blob(TileCompression::Uncompressed)?;
+		assert_eq!(blob.as_slice(), MOCK_BYTES_PBF);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn corrupt_tile_index_is_an_error() -> Result<()> {
+		let mut source = MockTilesReader::new_mock(TilesReaderParameters::new(
+			TileFormat::MVT,
+			TileCompression::Gzip,
+			TileBBoxPyramid::new_full(2),
+		))?;
+
+		let mut data_writer = DataWriterBlob::new()?;
+		VersaTilesWriter::write_to_stream(&mut source, &mut data_writer, ProcessingConfig::default()).await?;
+		let mut data = data_writer.into_blob().into_vec();
+
+		// overwrite the tile index of the level 2 block with garbage
+		let reader = VersaTilesReader::open_reader(Box::new(DataReaderBlob::from(data.clone()))).await?;
+		let block = reader.block_index.iter().find(|b| b.get_coord().level == 2).unwrap();
+		let range = *block.get_index_range();
+		data[range.as_range_usize()].fill(0xFF);
+
+		let reader = VersaTilesReader::open_reader(Box::new(DataReaderBlob::from(data))).await?;
+		assert!(reader.get_tile_stream(TileBBox::new_full(2)?).await.is_err());
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn read_your_own_dog_food() -> Result<()> {
 		let mut reader1 = MockTilesReader::new_mock(TilesReaderParameters::new(
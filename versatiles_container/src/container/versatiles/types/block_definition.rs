@@ -4,9 +4,10 @@
 //!
 //! The `BlockDefinition` struct contains metadata about the tile block, including its coordinates, bounding box, and byte ranges for tiles and index data.
 
-use anyhow::{Result, ensure};
+use crate::ContainerError;
+use anyhow::{Result, bail, ensure};
 use std::{fmt, ops::Div};
-use versatiles_core::{io::*, *};
+use versatiles_core::{io::*, json::JsonObject, *};
 use versatiles_derive::context;
 
 /// A struct representing a block of tiles within a larger tile set.
@@ -80,19 +81,29 @@ impl BlockDefinition {
 		let tiles_length = reader.read_u64()?;
 		let index_length = reader.read_u32()? as u64;
 
+		let index_offset = offset.checked_add(tiles_length);
+		if index_offset.and_then(|o| o.checked_add(index_length)).is_none() {
+			bail!(ContainerError::Malformed(format!(
+				"byte ranges of block ({offset}, {tiles_length}, {index_length}) overflow"
+			)));
+		}
+
 		let tiles_range = ByteRange::new(offset, tiles_length);
-		let index_range = ByteRange::new(offset + tiles_length, index_length);
-
-		let global_bbox = TileBBox::from_min_and_max(
-			level,
-			x_min + x * 256,
-			y_min + y * 256,
-			x_max + x * 256,
-			y_max + y * 256,
-		)?;
+		let index_range = ByteRange::new(index_offset.unwrap(), index_length);
+
+		let offset = TileCoord::new(level, x, y)?;
+		let shift = |v: u32, min: u32| v.checked_mul(256).and_then(|v| v.checked_add(min));
+		let (Some(x0), Some(y0), Some(x1), Some(y1)) =
+			(shift(x, x_min), shift(y, y_min), shift(x, x_max), shift(y, y_max))
+		else {
+			bail!(ContainerError::Malformed(format!(
+				"block {offset:?} is outside of the valid tile range"
+			)));
+		};
+		let global_bbox = TileBBox::from_min_and_max(level, x0, y0, x1, y1)?;
 
 		Ok(Self {
-			offset: TileCoord::new(level, x, y)?,
+			offset,
 			global_bbox,
 			tiles_coverage: tiles_bbox,
 			tiles_range,
@@ -155,6 +166,39 @@ impl BlockDefinition {
 		Ok(writer.into_blob())
 	}
 
+	/// Names, offsets and lengths of the fields of a block definition in the block index.
+	pub const LAYOUT: [(&'static str, u64, u64); 10] = [
+		("level", 0, 1),
+		("x", 1, 4),
+		("y", 5, 4),
+		("x_min", 9, 1),
+		("y_min", 10, 1),
+		("x_max", 11, 1),
+		("y_max", 12, 1),
+		("tiles_offset", 13, 8),
+		("tiles_length", 21, 8),
+		("index_length", 29, 4),
+	];
+
+	/// Returns the values of the fields in [`BlockDefinition::LAYOUT`] as JSON,
+	/// plus the derived `index_offset`, where the Brotli compressed tile index of the block starts.
+	#[context("Failed to describe BlockDefinition layout")]
+	pub fn layout(&self) -> Result<JsonObject> {
+		let mut json = JsonObject::new();
+		json.set("level", self.offset.level);
+		json.set("x", self.offset.x);
+		json.set("y", self.offset.y);
+		json.set("x_min", self.tiles_coverage.x_min()?);
+		json.set("y_min", self.tiles_coverage.y_min()?);
+		json.set("x_max", self.tiles_coverage.x_max()?);
+		json.set("y_max", self.tiles_coverage.y_max()?);
+		json.set("tiles_offset", self.tiles_range.offset);
+		json.set("tiles_length", self.tiles_range.length);
+		json.set("index_length", self.index_range.length);
+		json.set("index_offset", self.index_range.offset);
+		Ok(json)
+	}
+
 	/// Returns the sort index for the block.
 	///
 	/// # Returns
@@ -10,7 +10,8 @@ use std::{collections::HashMap, ops::Div};
 use versatiles_core::{io::*, utils::*, *};
 use versatiles_derive::context;
 
-const BLOCK_INDEX_LENGTH: u64 = 33;
+/// Length of a single block definition in the decompressed block index.
+pub const BLOCK_INDEX_LENGTH: u64 = 33;
 
 /// A struct representing an index of blocks within a tile set.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -36,6 +37,20 @@ impl BlockIndex {
 	/// Returns an error if the binary data cannot be parsed correctly.
 	#[context("Failed to create BlockIndex from blob")]
 	pub fn from_blob(buf: Blob) -> Result<Self> {
+		let mut block_index = Self::new_empty();
+		for block in Self::definitions_from_blob(&buf)? {
+			block_index.add_block(block);
+		}
+
+		Ok(block_index)
+	}
+
+	/// Parses the block definitions of a decompressed block index, in the order in which they are stored.
+	///
+	/// # Errors
+	/// Returns an error if the binary data cannot be parsed correctly.
+	#[context("Failed to read block definitions from blob")]
+	pub fn definitions_from_blob(buf: &Blob) -> Result<Vec<BlockDefinition>> {
 		let count = buf.len().div(BLOCK_INDEX_LENGTH);
 		ensure!(
 			count * BLOCK_INDEX_LENGTH == buf.len(),
@@ -43,13 +58,12 @@ impl BlockIndex {
 			BLOCK_INDEX_LENGTH
 		);
 
-		let mut block_index = Self::new_empty();
-		for i in 0..count {
-			let range = &ByteRange::new(i * BLOCK_INDEX_LENGTH, BLOCK_INDEX_LENGTH);
-			block_index.add_block(BlockDefinition::from_blob(&buf.read_range(range)?)?);
-		}
-
-		Ok(block_index)
+		(0..count)
+			.map(|i| {
+				let range = &ByteRange::new(i * BLOCK_INDEX_LENGTH, BLOCK_INDEX_LENGTH);
+				BlockDefinition::from_blob(&buf.read_range(range)?)
+			})
+			.collect()
 	}
 
 	/// Creates a `BlockIndex` from a Brotli compressed binary blob.
@@ -87,6 +101,8 @@ impl BlockIndex {
 
 	/// Converts the `BlockIndex` to a binary blob.
 	///
+	/// Blocks are sorted by their coordinates, so the same index always results in the same bytes.
+	///
 	/// # Returns
 	/// A binary blob representing the `BlockIndex`.
 	///
@@ -94,8 +110,11 @@ impl BlockIndex {
 	/// Returns an error if the conversion fails.
 	#[context("Failed to create BlockIndex from blob")]
 	pub fn as_blob(&self) -> Result<Blob> {
+		let mut blocks = self.lookup.values().collect::<Vec<_>>();
+		blocks.sort_by_key(|block| block.get_sort_index());
+
 		let mut writer = ValueWriterBlob::new_be();
-		for (_coord, block) in self.lookup.iter() {
+		for block in blocks {
 			writer.write_blob(&block.as_blob()?)?;
 		}
 
@@ -155,4 +174,30 @@ mod tests {
 		assert_eq!(index1, index2);
 		Ok(())
 	}
+
+	#[test]
+	fn blob_is_sorted() -> Result<()> {
+		let bboxes = [
+			TileBBox::from_min_and_max(9, 256, 0, 300, 10)?,
+			TileBBox::from_min_and_max(3, 1, 2, 3, 4)?,
+			TileBBox::from_min_and_max(9, 0, 0, 10, 10)?,
+		];
+		let mut index1 = BlockIndex::new_empty();
+		let mut index2 = BlockIndex::new_empty();
+		for bbox in &bboxes {
+			index1.add_block(BlockDefinition::new(bbox)?);
+		}
+		for bbox in bboxes.iter().rev() {
+			index2.add_block(BlockDefinition::new(bbox)?);
+		}
+		assert_eq!(index1.as_blob()?, index2.as_blob()?);
+
+		let blocks = BlockIndex::definitions_from_blob(&index1.as_blob()?)?;
+		let coords = blocks
+			.iter()
+			.map(|b| (b.get_z(), b.get_coord().x, b.get_coord().y))
+			.collect::<Vec<_>>();
+		assert_eq!(coords, [(3, 0, 0), (9, 0, 0), (9, 1, 0)]);
+		Ok(())
+	}
 }
@@ -1,4 +1,4 @@
-use super::{BlockDefinition, TileIndex};
+use super::{BlockDefinition, TileIndex, TileTimes};
 use anyhow::Result;
 use std::collections::HashMap;
 use versatiles_core::{Blob, ByteRange, TileBBox, TileCoord, io::DataWriterTrait};
@@ -9,6 +9,7 @@ pub struct BlockWriter<'a> {
 	writer: &'a mut dyn DataWriterTrait,
 	initial_offset: u64,
 	tile_index: TileIndex,
+	tile_times: TileTimes,
 	tile_hash_lookup: HashMap<Vec<u8>, ByteRange>,
 }
 
@@ -17,6 +18,7 @@ impl<'a> BlockWriter<'a> {
 		let bbox = *block_definition.get_global_bbox();
 		let initial_offset = writer.get_position().unwrap();
 		let tile_index = TileIndex::new_empty(bbox.count_tiles() as usize);
+		let tile_times = TileTimes::new_empty(bbox.count_tiles() as usize);
 		let tile_hash_lookup: HashMap<Vec<u8>, ByteRange> = HashMap::new();
 
 		Self {
@@ -24,14 +26,16 @@ impl<'a> BlockWriter<'a> {
 			writer,
 			initial_offset,
 			tile_index,
+			tile_times,
 			tile_hash_lookup,
 		}
 	}
 
-	/// Write a single tile to the writer.
+	/// Write a single tile and its optional modification time to the writer.
 	#[context("writing tile at {coord:?}")]
-	pub fn write_tile(&mut self, coord: TileCoord, blob: Blob) -> Result<()> {
+	pub fn write_tile(&mut self, coord: TileCoord, blob: Blob, mtime: Option<u64>) -> Result<()> {
 		let index = self.bbox.index_of(&coord)? as usize;
+		self.tile_times.set(index, mtime);
 
 		let mut save_hash = false;
 		if blob.len() < 1000 {
@@ -54,13 +58,22 @@ impl<'a> BlockWriter<'a> {
 		Ok(())
 	}
 
+	/// Write the tile index and, if any tile has a modification time, the tile times.
+	///
+	/// Returns the byte ranges of the tiles, the tile index and the optional tile times.
 	#[context("finalizing block writer")]
-	pub fn finalize(self) -> Result<(ByteRange, ByteRange)> {
+	pub fn finalize(self) -> Result<(ByteRange, ByteRange, Option<ByteRange>)> {
 		// Get the final writer position
 		let offset1 = self.writer.get_position()?;
 		let tile_range = ByteRange::new(self.initial_offset, offset1 - self.initial_offset);
 		let index_range = self.writer.append(&self.tile_index.as_brotli_blob()?)?;
 
-		Ok((tile_range, index_range))
+		let times_range = if self.tile_times.is_empty() {
+			None
+		} else {
+			Some(self.writer.append(&self.tile_times.as_brotli_blob()?)?)
+		};
+
+		Ok((tile_range, index_range, times_range))
 	}
 }
@@ -3,12 +3,28 @@
 //! This module defines the `FileHeader` struct, which represents the header of a versatiles file.
 //!
 //! The `FileHeader` struct contains metadata about the file, including its tile format, compression, zoom range, bounding box, and byte ranges for metadata and blocks.
-
-use anyhow::{Result, bail, ensure};
-use versatiles_core::{io::*, *};
+//!
+//! The header can be followed by an optional extension: the magic word `vt_times` and the byte range of the `TimeIndex`.
+//! Readers only look for it if the first section starts behind it, so files without extension stay valid.
+//!
+//! Files written as a stream can not update the header when they are complete. Their leading header starts with the
+//! magic word `versatiles_s02` instead of `versatiles_v02`, so readers that don't know about streamed files reject
+//! them instead of reading an empty container. It has no extension, and only its metadata range is valid.
+//! The final header, with `versatiles_v02` and the `vt_times` extension, is appended as a 90 byte trailer to the end
+//! of the file.
+
+use super::layout::{field, section};
+use crate::ContainerError;
+use anyhow::{Result, anyhow, bail, ensure};
+use versatiles_core::{io::*, json::JsonObject, *};
 use versatiles_derive::context;
 
 const HEADER_LENGTH: u64 = 66;
+const EXTENSION_LENGTH: u64 = 24;
+const MAGIC: &str = "versatiles_v02";
+const STREAMED_MAGIC: &str = "versatiles_s02";
+const EXTENSION_MAGIC: &[u8; 8] = b"vt_times";
+const TRAILER_LENGTH: u64 = HEADER_LENGTH + EXTENSION_LENGTH;
 const BBOX_SCALE: f64 = 10000000.0;
 
 /// A struct representing the header of a versatiles file.
@@ -20,6 +36,9 @@ pub struct FileHeader {
 	pub compression: TileCompression,
 	pub meta_range: ByteRange,
 	pub blocks_range: ByteRange,
+	pub times_range: ByteRange,
+	/// Whether the file was written as a stream, so the final header is a trailer at the end of the file.
+	pub streamed: bool,
 }
 
 impl FileHeader {
@@ -54,6 +73,8 @@ impl FileHeader {
 			compression,
 			meta_range: ByteRange::empty(),
 			blocks_range: ByteRange::empty(),
+			times_range: ByteRange::empty(),
+			streamed: false,
 		})
 	}
 
@@ -68,7 +89,64 @@ impl FileHeader {
 	pub async fn from_reader(reader: &mut DataReader) -> Result<FileHeader> {
 		let range = ByteRange::new(0, HEADER_LENGTH);
 		let blob = reader.read_range(&range).await?;
-		FileHeader::from_blob(&blob)
+		let mut header = FileHeader::from_blob(&blob)?;
+
+		if header.streamed {
+			return FileHeader::read_trailer(reader, &header).await;
+		}
+
+		if header.has_extension() {
+			let range = ByteRange::new(HEADER_LENGTH, EXTENSION_LENGTH);
+			header.read_extension(&reader.read_range(&range).await?)?;
+		}
+
+		Ok(header)
+	}
+
+	/// Reads the final header from the end of a file that was written as a stream.
+	#[context("Failed to read FileHeader trailer")]
+	async fn read_trailer(reader: &mut DataReader, header: &FileHeader) -> Result<FileHeader> {
+		let size = reader.get_size().await?.ok_or_else(|| {
+			anyhow!(
+				"'{}' was written as a stream and has its header at the end, but its size is unknown",
+				reader.get_name()
+			)
+		})?;
+		if size < HEADER_LENGTH + TRAILER_LENGTH {
+			bail!(ContainerError::Malformed(format!(
+				"file of {size} bytes is too short for a header trailer"
+			)));
+		}
+
+		let blob = reader
+			.read_range(&ByteRange::new(size - TRAILER_LENGTH, TRAILER_LENGTH))
+			.await?;
+		let mut trailer = FileHeader::from_blob(&blob.slice(0..HEADER_LENGTH as usize))?;
+		trailer.read_extension(&blob.slice(HEADER_LENGTH as usize..TRAILER_LENGTH as usize))?;
+
+		let matches = trailer.tile_format == header.tile_format
+			&& trailer.compression == header.compression
+			&& trailer.zoom_range == header.zoom_range
+			&& trailer.bbox == header.bbox
+			&& trailer.meta_range == header.meta_range;
+		if !matches || trailer.streamed {
+			bail!(ContainerError::Malformed(String::from(
+				"the header trailer does not match the header"
+			)));
+		}
+
+		trailer.streamed = true;
+		Ok(trailer)
+	}
+
+	/// Returns `true` if the sections start late enough to leave room for the header extension.
+	pub fn has_extension(&self) -> bool {
+		[self.meta_range, self.blocks_range]
+			.iter()
+			.filter(|range| range.length > 0)
+			.map(|range| range.offset)
+			.min()
+			.is_some_and(|offset| offset >= HEADER_LENGTH + EXTENSION_LENGTH)
 	}
 
 	/// Converts the `FileHeader` to a binary blob.
@@ -81,7 +159,7 @@ impl FileHeader {
 		use TileFormat::*;
 
 		let mut writer = ValueWriterBlob::new_be();
-		writer.write_slice(b"versatiles_v02")?;
+		writer.write_slice(self.magic().as_bytes())?;
 
 		// tile type
 		writer.write_u8(match self.tile_format {
@@ -127,6 +205,93 @@ impl FileHeader {
 		Ok(writer.into_blob())
 	}
 
+	/// The magic word at the start of the header.
+	fn magic(&self) -> &'static str {
+		if self.streamed { STREAMED_MAGIC } else { MAGIC }
+	}
+
+	/// Converts the header extension with the byte range of the `TimeIndex` to a binary blob.
+	///
+	/// The writer appends it directly after the header.
+	#[context("Failed to create FileHeader extension blob")]
+	pub fn extension_to_blob(&self) -> Result<Blob> {
+		let mut writer = ValueWriterBlob::new_be();
+		writer.write_slice(EXTENSION_MAGIC)?;
+		writer.write_range(&self.times_range)?;
+		Ok(writer.into_blob())
+	}
+
+	/// Describes the binary layout of the header: every field with its offset, length and value.
+	#[context("Failed to describe FileHeader layout")]
+	pub fn layout(&self) -> Result<JsonObject> {
+		let blob = self.to_blob()?;
+		let bytes = blob.as_slice();
+
+		let mut tile_format = field("tile_format", 14, 1, bytes[14]);
+		tile_format.set("meaning", self.tile_format.as_str());
+		let mut compression = field("compression", 15, 1, bytes[15]);
+		compression.set("meaning", self.compression.as_str());
+
+		let mut fields = vec![
+			field("magic", 0, 14, self.magic()),
+			tile_format,
+			compression,
+			field("zoom_min", 16, 1, self.zoom_range[0]),
+			field("zoom_max", 17, 1, self.zoom_range[1]),
+		];
+		for (i, name) in ["bbox_west", "bbox_south", "bbox_east", "bbox_north"]
+			.iter()
+			.enumerate()
+		{
+			let mut json = field(name, 18 + 4 * i as u64, 4, self.bbox[i]);
+			json.set("meaning", f64::from(self.bbox[i]) / BBOX_SCALE);
+			fields.push(json);
+		}
+		fields.push(field("meta_offset", 34, 8, self.meta_range.offset));
+		fields.push(field("meta_length", 42, 8, self.meta_range.length));
+		fields.push(field("blocks_offset", 50, 8, self.blocks_range.offset));
+		fields.push(field("blocks_length", 58, 8, self.blocks_range.length));
+
+		let mut json = section(&ByteRange::new(0, HEADER_LENGTH));
+		json.set("fields", fields);
+		Ok(json)
+	}
+
+	/// Describes the binary layout of the header extension, if the file has one.
+	pub fn extension_layout(&self) -> Option<JsonObject> {
+		if !self.has_extension() {
+			return None;
+		}
+		let mut json = section(&ByteRange::new(HEADER_LENGTH, EXTENSION_LENGTH));
+		json.set(
+			"fields",
+			vec![
+				field("magic", HEADER_LENGTH, 8, "vt_times"),
+				field("times_offset", HEADER_LENGTH + 8, 8, self.times_range.offset),
+				field("times_length", HEADER_LENGTH + 16, 8, self.times_range.length),
+			],
+		);
+		Some(json)
+	}
+
+	/// Reads the header extension. Unknown extensions are ignored.
+	#[context("Failed to read FileHeader extension")]
+	fn read_extension(&mut self, blob: &Blob) -> Result<()> {
+		if blob.len() != EXTENSION_LENGTH {
+			return Ok(());
+		}
+		if &blob.as_slice()[0..8] != EXTENSION_MAGIC {
+			return Ok(());
+		}
+		let mut reader = ValueReaderSlice::new_be(&blob.as_slice()[8..]);
+		let times_range = reader.read_range()?;
+		if times_range.offset.checked_add(times_range.length).is_none() {
+			bail!(ContainerError::Malformed(format!("byte range {times_range} overflows")));
+		}
+		self.times_range = times_range;
+		Ok(())
+	}
+
 	/// Creates a `FileHeader` from a binary blob.
 	///
 	/// # Arguments
@@ -135,7 +300,7 @@ impl FileHeader {
 	/// # Errors
 	/// Returns an error if the binary data cannot be parsed correctly.
 	#[context("Failed to create FileHeader from blob")]
-	fn from_blob(blob: &Blob) -> Result<FileHeader> {
+	pub(crate) fn from_blob(blob: &Blob) -> Result<FileHeader> {
 		use TileCompression::*;
 		use TileFormat::*;
 
@@ -145,8 +310,10 @@ impl FileHeader {
 
 		let mut reader = ValueReaderSlice::new_be(blob.as_slice());
 		let magic_word = reader.read_string(14)?;
-		if &magic_word != "versatiles_v02" {
-			bail!("'{blob:?}' is not a valid versatiles header. A header should start with 'versatiles_v02'");
+		let streamed = match magic_word.as_str() {
+			MAGIC => false,
+			STREAMED_MAGIC => true,
+			_ => bail!("'{blob:?}' is not a valid versatiles header. A header should start with '{MAGIC}'"),
 		};
 
 		let tile_format = match reader.read_u8()? {
@@ -183,6 +350,11 @@ impl FileHeader {
 
 		let meta_range = reader.read_range()?;
 		let blocks_range = reader.read_range()?;
+		for range in [meta_range, blocks_range] {
+			if range.offset.checked_add(range.length).is_none() {
+				bail!(ContainerError::Malformed(format!("byte range {range} overflows")));
+			}
+		}
 
 		Ok(FileHeader {
 			zoom_range,
@@ -191,6 +363,8 @@ impl FileHeader {
 			compression,
 			meta_range,
 			blocks_range,
+			times_range: ByteRange::empty(),
+			streamed,
 		})
 	}
 }
@@ -287,6 +461,93 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn layout() -> Result<()> {
+		let bbox = GeoBBox::new(-180.0, -85.0, 180.0, 85.0)?;
+		let mut header = FileHeader::new(TileFormat::MVT, Gzip, [3, 8], &bbox)?;
+		header.meta_range = ByteRange::new(66, 34);
+		header.blocks_range = ByteRange::new(100, 20);
+		assert!(header.extension_layout().is_none());
+		header.meta_range = ByteRange::new(90, 10);
+		header.times_range = ByteRange::new(120, 30);
+
+		assert_eq!(
+			header.layout()?.stringify(),
+			r#"{"fields":[{"length":14,"name":"magic","offset":0,"value":"versatiles_v02"},{"length":1,"meaning":"mvt","name":"tile_format","offset":14,"value":32},{"length":1,"meaning":"gzip","name":"compression","offset":15,"value":1},{"length":1,"name":"zoom_min","offset":16,"value":3},{"length":1,"name":"zoom_max","offset":17,"value":8},{"length":4,"meaning":-180,"name":"bbox_west","offset":18,"value":-1800000000},{"length":4,"meaning":-85,"name":"bbox_south","offset":22,"value":-850000000},{"length":4,"meaning":180,"name":"bbox_east","offset":26,"value":1800000000},{"length":4,"meaning":85,"name":"bbox_north","offset":30,"value":850000000},{"length":8,"name":"meta_offset","offset":34,"value":90},{"length":8,"name":"meta_length","offset":42,"value":10},{"length":8,"name":"blocks_offset","offset":50,"value":100},{"length":8,"name":"blocks_length","offset":58,"value":20}],"length":66,"offset":0}"#
+		);
+		assert_eq!(
+			header.extension_layout().unwrap().stringify(),
+			r#"{"fields":[{"length":8,"name":"magic","offset":66,"value":"vt_times"},{"length":8,"name":"times_offset","offset":74,"value":120},{"length":8,"name":"times_length","offset":82,"value":30}],"length":24,"offset":66}"#
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn extension_roundtrip() -> Result<()> {
+		let bbox = GeoBBox::new(0.0, 0.0, 0.0, 0.0)?;
+		let mut header = FileHeader::new(TileFormat::MVT, Gzip, [0, 0], &bbox)?;
+		header.meta_range = ByteRange::new(HEADER_LENGTH + EXTENSION_LENGTH, 10);
+		header.times_range = ByteRange::new(200, 30);
+
+		let mut blob = header.to_blob()?.into_vec();
+		blob.extend_from_slice(header.extension_to_blob()?.as_slice());
+		blob.resize(300, 0);
+		let mut reader: DataReader = Box::new(DataReaderBlob::from(Blob::from(blob)));
+		assert_eq!(FileHeader::from_reader(&mut reader).await?, header);
+
+		// without extension, the meta data starts directly behind the header
+		header.meta_range = ByteRange::new(HEADER_LENGTH, 10);
+		let mut blob = header.to_blob()?.into_vec();
+		blob.extend_from_slice(b"vt_times and more meta data");
+		let mut reader: DataReader = Box::new(DataReaderBlob::from(Blob::from(blob)));
+		assert_eq!(
+			FileHeader::from_reader(&mut reader).await?.times_range,
+			ByteRange::empty()
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn trailer_roundtrip() -> Result<()> {
+		let bbox = GeoBBox::new(0.0, 0.0, 0.0, 0.0)?;
+		let mut header = FileHeader::new(TileFormat::MVT, Gzip, [0, 0], &bbox)?;
+		header.meta_range = ByteRange::new(HEADER_LENGTH, 10);
+		header.streamed = true;
+
+		let to_blob = |header: &FileHeader| -> Result<Vec<u8>> {
+			let mut blob = header.to_blob()?.into_vec();
+			blob.extend_from_slice(header.extension_to_blob()?.as_slice());
+			Ok(blob)
+		};
+
+		let mut blob = header.to_blob()?.into_vec();
+		assert_eq!(&blob[0..14], b"versatiles_s02");
+		blob.resize(300, 0);
+		let mut final_header = FileHeader::new(TileFormat::MVT, Gzip, [0, 0], &bbox)?;
+		final_header.meta_range = header.meta_range;
+		final_header.blocks_range = ByteRange::new(200, 20);
+		final_header.times_range = ByteRange::new(220, 30);
+		blob.extend(to_blob(&final_header)?);
+
+		let mut reader: DataReader = Box::new(DataReaderBlob::from(Blob::from(blob.clone())));
+		let read = FileHeader::from_reader(&mut reader).await?;
+		assert!(read.streamed);
+		assert_eq!(read.blocks_range, final_header.blocks_range);
+		assert_eq!(read.times_range, final_header.times_range);
+
+		// the trailer must describe the same file
+		final_header.zoom_range = [0, 1];
+		blob.truncate(300);
+		blob.extend(to_blob(&final_header)?);
+		let mut reader: DataReader = Box::new(DataReaderBlob::from(Blob::from(blob)));
+		assert!(FileHeader::from_reader(&mut reader).await.is_err());
+
+		// a file without trailer is too short
+		let mut reader: DataReader = Box::new(DataReaderBlob::from(header.to_blob()?));
+		assert!(FileHeader::from_reader(&mut reader).await.is_err());
+		Ok(())
+	}
+
 	#[test]
 	fn new_file_header_with_invalid_params() {
 		let tf = TileFormat::PNG;
@@ -356,14 +617,15 @@ mod tests {
 
 	#[test]
 	fn invalid_magic_word() {
-		let mut invalid_blob = Blob::from(vec![0; HEADER_LENGTH as usize]);
-		invalid_blob.as_mut_slice()[0..14].copy_from_slice(b"invalid_header");
+		let mut buffer = vec![0; HEADER_LENGTH as usize];
+		buffer[0..14].copy_from_slice(b"invalid_header");
+		let invalid_blob = Blob::from(buffer);
 		assert!(FileHeader::from_blob(&invalid_blob).is_err());
 	}
 
 	#[test]
 	fn unknown_tile_format() {
-		let mut invalid_blob = FileHeader::new(
+		let mut buffer = FileHeader::new(
 			TileFormat::PNG,
 			Gzip,
 			[0, 0],
@@ -371,8 +633,10 @@ mod tests {
 		)
 		.unwrap()
 		.to_blob()
-		.unwrap();
-		invalid_blob.as_mut_slice()[14] = 0xFF; // Set an unknown tile format value
+		.unwrap()
+		.into_vec();
+		buffer[14] = 0xFF; // Set an unknown tile format value
+		let invalid_blob = Blob::from(buffer);
 
 		let result = catch_unwind(|| {
 			FileHeader::from_blob(&invalid_blob).unwrap();
@@ -383,7 +647,7 @@ mod tests {
 
 	#[test]
 	fn unknown_compression() {
-		let mut invalid_blob = FileHeader::new(
+		let mut buffer = FileHeader::new(
 			TileFormat::PNG,
 			Gzip,
 			[0, 0],
@@ -391,8 +655,10 @@ mod tests {
 		)
 		.unwrap()
 		.to_blob()
-		.unwrap();
-		invalid_blob.as_mut_slice()[15] = 0xFF; // Set an unknown compression value
+		.unwrap()
+		.into_vec();
+		buffer[15] = 0xFF; // Set an unknown compression value
+		let invalid_blob = Blob::from(buffer);
 
 		let result = catch_unwind(|| {
 			FileHeader::from_blob(&invalid_blob).unwrap();
@@ -0,0 +1,38 @@
+//! Helpers to describe the binary layout of a versatiles file as JSON, see `VersaTilesReader::layout`.
+//!
+//! Every field is described by its `name`, its byte `offset` and `length`, and its `value`.
+//! Offsets are absolute for the header, and relative to the start of the entry for index entries.
+
+use versatiles_core::{ByteRange, json::*};
+
+/// Describes a field of `length` bytes at `offset`.
+pub fn field(name: &str, offset: u64, length: u64, value: impl Into<JsonValue>) -> JsonObject {
+	let mut json = JsonObject::new();
+	json.set("name", name);
+	json.set("offset", offset);
+	json.set("length", length);
+	json.set("value", value.into());
+	json
+}
+
+/// Describes the fields of an index entry without values: `(name, offset, length)`.
+pub fn schema(fields: &[(&str, u64, u64)]) -> Vec<JsonObject> {
+	fields
+		.iter()
+		.map(|(name, offset, length)| {
+			let mut json = JsonObject::new();
+			json.set("name", *name);
+			json.set("offset", *offset);
+			json.set("length", *length);
+			json
+		})
+		.collect()
+}
+
+/// Describes a section of the file.
+pub fn section(range: &ByteRange) -> JsonObject {
+	let mut json = JsonObject::new();
+	json.set("offset", range.offset);
+	json.set("length", range.length);
+	json
+}
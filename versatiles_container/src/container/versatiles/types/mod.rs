@@ -12,12 +12,16 @@
 //! - `BlockIndex`: Manages a collection of `BlockDefinition`s, allowing for efficient lookups and conversions.
 //! - `FileHeader`: Represents the header of a `versatiles` file, containing metadata about the tile format, compression, and ranges.
 //! - `TileIndex`: Manages the byte ranges of individual tiles within the container, allowing for efficient access and modifications.
+//! - `TileTimes`: Stores the optional modification times of the tiles in a block.
+//! - `TimeIndex`: Maps blocks to their `TileTimes`, referenced by the optional header extension.
+//!
+//! The `layout` helpers describe the binary layout of these types as JSON.
 
 mod block_definition;
 pub use block_definition::BlockDefinition;
 
 mod block_index;
-pub use block_index::BlockIndex;
+pub use block_index::{BLOCK_INDEX_LENGTH, BlockIndex};
 
 mod block_writer;
 pub use block_writer::BlockWriter;
@@ -25,5 +29,13 @@ pub use block_writer::BlockWriter;
 mod file_header;
 pub use file_header::FileHeader;
 
+pub mod layout;
+
 mod tile_index;
 pub use tile_index::TileIndex;
+
+mod tile_times;
+pub use tile_times::TileTimes;
+
+mod time_index;
+pub use time_index::{TIME_INDEX_LENGTH, TimeIndex};
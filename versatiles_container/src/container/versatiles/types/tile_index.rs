@@ -4,7 +4,8 @@
 //!
 //! The `TileIndex` struct is used to manage the byte ranges of tiles within a versatiles file. It provides methods to create, manipulate, and convert the index to and from binary blobs.
 
-use anyhow::{Result, ensure};
+use crate::ContainerError;
+use anyhow::{Result, bail, ensure};
 use std::ops::Div;
 use versatiles_core::{io::*, utils::*, *};
 use versatiles_derive::context;
@@ -48,7 +49,13 @@ impl TileIndex {
 		let mut index = Vec::new();
 		let mut reader = ValueReaderBlob::new_be(blob);
 		for _ in 0..count {
-			index.push(ByteRange::new(reader.read_u64()?, reader.read_u32()? as u64));
+			let range = ByteRange::new(reader.read_u64()?, reader.read_u32()? as u64);
+			if range.offset.checked_add(range.length).is_none() {
+				bail!(ContainerError::Malformed(format!(
+					"Tile index is defective: byte range {range} overflows"
+				)));
+			}
+			index.push(range);
 		}
 
 		Ok(Self { index })
@@ -66,6 +73,27 @@ impl TileIndex {
 		Self::from_blob(decompress_brotli(&buf)?)
 	}
 
+	/// Creates a `TileIndex` from a Brotli compressed binary blob that must contain exactly `count` byte ranges.
+	///
+	/// Decompression stops early if the data would be larger than expected, so a crafted blob
+	/// can't expand to an arbitrary size.
+	///
+	/// # Errors
+	/// Returns [`ContainerError::Malformed`] if the index doesn't contain `count` byte ranges,
+	/// or an error if the data cannot be decompressed or parsed correctly.
+	#[context("Failed to create TileIndex with {count} entries from Brotli blob")]
+	pub fn from_brotli_blob_with_count(buf: Blob, count: u64) -> Result<Self> {
+		let blob = decompress_limited(buf, TileCompression::Brotli, count * TILE_INDEX_LENGTH)?;
+		let index = Self::from_blob(blob)?;
+		if index.len() as u64 != count {
+			bail!(ContainerError::Malformed(format!(
+				"tile index contains {} entries, but the block has {count} tiles",
+				index.len()
+			)));
+		}
+		Ok(index)
+	}
+
 	/// Sets the byte range for a specific index.
 	///
 	/// # Arguments
@@ -124,8 +152,18 @@ impl TileIndex {
 	///
 	/// # Arguments
 	/// * `offset` - The offset to add to each byte range.
-	pub fn add_offset(&mut self, offset: u64) {
-		self.index.iter_mut().for_each(|r| r.offset += offset);
+	///
+	/// # Errors
+	/// Returns [`ContainerError::Malformed`] if a byte range would end beyond `u64::MAX`.
+	pub fn add_offset(&mut self, offset: u64) -> Result<()> {
+		for range in self.index.iter_mut() {
+			range.offset = range
+				.offset
+				.checked_add(offset)
+				.filter(|start| start.checked_add(range.length).is_some())
+				.ok_or_else(|| ContainerError::Malformed(format!("byte range {range} overflows after adding {offset}")))?;
+		}
+		Ok(())
 	}
 }
 
@@ -145,7 +183,8 @@ mod tests {
 			assert_eq!(index.get(i as usize), &ByteRange::new(i * i, i));
 		}
 
-		index.add_offset(18);
+		index.add_offset(18).unwrap();
+		assert!(index.add_offset(u64::MAX).is_err());
 
 		for (index, range) in index.iter().enumerate() {
 			let i = index as u64;
@@ -162,6 +201,20 @@ mod tests {
 		let index2 = TileIndex::from_brotli_blob(index1.as_brotli_blob()?)?;
 		assert_eq!(index1, index2);
 
+		let index3 = TileIndex::from_brotli_blob_with_count(index1.as_brotli_blob()?, 100)?;
+		assert_eq!(index1, index3);
+
+		// the blob contains more entries than expected
+		let error = TileIndex::from_brotli_blob_with_count(index1.as_brotli_blob()?, 99).unwrap_err();
+		assert!(error.downcast_ref::<DecompressionLimitExceeded>().is_some());
+
+		// the blob contains fewer entries than expected
+		let error = TileIndex::from_brotli_blob_with_count(index1.as_brotli_blob()?, 101).unwrap_err();
+		assert!(matches!(
+			error.downcast_ref::<ContainerError>(),
+			Some(ContainerError::Malformed(_))
+		));
+
 		Ok(())
 	}
 }
@@ -0,0 +1,98 @@
+//! This module defines the `TileTimes` struct, which stores the modification times of the tiles in a block.
+//!
+//! `TileTimes` has the same order as the `TileIndex` of a block. Every entry is the modification time of a tile in seconds since the Unix epoch, `0` means unknown.
+
+use crate::ContainerError;
+use anyhow::{Result, bail};
+use versatiles_core::{io::*, utils::*, *};
+use versatiles_derive::context;
+
+const TILE_TIME_LENGTH: u64 = 8;
+
+/// A struct representing the modification times of the tiles in a block.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TileTimes {
+	times: Vec<u64>,
+}
+
+impl TileTimes {
+	/// Creates a new `TileTimes` with `count` unknown times.
+	pub fn new_empty(count: usize) -> Self {
+		Self { times: vec![0; count] }
+	}
+
+	/// Creates `TileTimes` from a Brotli compressed binary blob that must contain exactly `count` times.
+	///
+	/// # Errors
+	/// Returns [`ContainerError::Malformed`] if the blob doesn't contain `count` times,
+	/// or an error if the data cannot be decompressed.
+	#[context("Failed to create TileTimes with {count} entries from Brotli blob")]
+	pub fn from_brotli_blob_with_count(buf: Blob, count: u64) -> Result<Self> {
+		let blob = decompress_limited(buf, TileCompression::Brotli, count * TILE_TIME_LENGTH)?;
+		if blob.len() != count * TILE_TIME_LENGTH {
+			bail!(ContainerError::Malformed(format!(
+				"tile times contain {} bytes, but the block has {count} tiles",
+				blob.len()
+			)));
+		}
+
+		let mut reader = ValueReaderBlob::new_be(blob);
+		let times = (0..count).map(|_| reader.read_u64()).collect::<Result<Vec<u64>>>()?;
+		Ok(Self { times })
+	}
+
+	/// Sets the modification time of the tile at `index`. `None` is stored as unknown.
+	pub fn set(&mut self, index: usize, mtime: Option<u64>) {
+		self.times[index] = mtime.unwrap_or(0);
+	}
+
+	/// Returns the modification time of the tile at `index`, or `None` if it is unknown.
+	pub fn get(&self, index: usize) -> Option<u64> {
+		self.times.get(index).copied().filter(|time| *time > 0)
+	}
+
+	/// Returns `true` if no time is known, so the block doesn't need a time section.
+	pub fn is_empty(&self) -> bool {
+		self.times.iter().all(|time| *time == 0)
+	}
+
+	/// Converts the `TileTimes` to a Brotli compressed binary blob.
+	#[context("Failed to create TileTimes Brotli blob")]
+	pub fn as_brotli_blob(&self) -> Result<Blob> {
+		let mut writer = ValueWriterBlob::new_be();
+		for time in &self.times {
+			writer.write_u64(*time)?;
+		}
+		compress_brotli_fast(&writer.into_blob())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn roundtrip() -> Result<()> {
+		let mut times = TileTimes::new_empty(4);
+		assert!(times.is_empty());
+		times.set(1, Some(1_700_000_000));
+		times.set(3, None);
+		assert!(!times.is_empty());
+
+		let times2 = TileTimes::from_brotli_blob_with_count(times.as_brotli_blob()?, 4)?;
+		assert_eq!(times, times2);
+		assert_eq!(times2.get(0), None);
+		assert_eq!(times2.get(1), Some(1_700_000_000));
+		assert_eq!(times2.get(3), None);
+		assert_eq!(times2.get(4), None);
+		Ok(())
+	}
+
+	#[test]
+	fn wrong_count() -> Result<()> {
+		let blob = TileTimes::new_empty(4).as_brotli_blob()?;
+		assert!(TileTimes::from_brotli_blob_with_count(blob.clone(), 3).is_err());
+		assert!(TileTimes::from_brotli_blob_with_count(blob, 5).is_err());
+		Ok(())
+	}
+}
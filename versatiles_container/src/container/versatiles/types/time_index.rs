@@ -0,0 +1,146 @@
+//! This module defines the `TimeIndex` struct, the optional index of tile modification times in a versatiles file.
+//!
+//! The `TimeIndex` maps the coordinates of a block to the byte range of its `TileTimes`. Blocks without known times are not listed.
+
+use crate::ContainerError;
+use anyhow::{Result, bail, ensure};
+use std::{collections::HashMap, ops::Div};
+use versatiles_core::{io::*, utils::*, *};
+use versatiles_derive::context;
+
+/// Length of a single entry in the decompressed time index.
+pub const TIME_INDEX_LENGTH: u64 = 25;
+
+/// A struct representing the index of tile modification times within a versatiles file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimeIndex {
+	lookup: HashMap<TileCoord, ByteRange>,
+}
+
+impl TimeIndex {
+	/// Creates a new empty `TimeIndex`.
+	pub fn new_empty() -> Self {
+		Self::default()
+	}
+
+	/// Creates a `TimeIndex` from a Brotli compressed binary blob.
+	///
+	/// # Errors
+	/// Returns an error if the binary data cannot be decompressed or parsed correctly.
+	#[context("Failed to create TimeIndex from Brotli blob")]
+	pub fn from_brotli_blob(buf: Blob, max_size: u64) -> Result<Self> {
+		let blob = decompress_limited(buf, TileCompression::Brotli, max_size)?;
+		let mut time_index = Self::new_empty();
+		for (coord, range) in Self::entries_from_blob(blob)? {
+			time_index.add_block(coord, range);
+		}
+
+		Ok(time_index)
+	}
+
+	/// Parses the entries of a decompressed time index, in the order in which they are stored.
+	///
+	/// # Errors
+	/// Returns an error if the binary data cannot be parsed correctly.
+	#[context("Failed to read TimeIndex entries from blob")]
+	pub fn entries_from_blob(blob: Blob) -> Result<Vec<(TileCoord, ByteRange)>> {
+		let count = blob.len().div(TIME_INDEX_LENGTH);
+		ensure!(
+			count * TIME_INDEX_LENGTH == blob.len(),
+			"Time index is defective, because buffer length is not a multiple of {}",
+			TIME_INDEX_LENGTH
+		);
+
+		let mut entries = Vec::with_capacity(count as usize);
+		let mut reader = ValueReaderBlob::new_be(blob);
+		for _ in 0..count {
+			let coord = TileCoord::new(reader.read_u8()?, reader.read_u32()?, reader.read_u32()?)?;
+			let range = reader.read_range()?;
+			if range.offset.checked_add(range.length).is_none() {
+				bail!(ContainerError::Malformed(format!(
+					"Time index is defective: byte range {range} overflows"
+				)));
+			}
+			entries.push((coord, range));
+		}
+
+		Ok(entries)
+	}
+
+	/// Names, offsets and lengths of the fields of an entry in the time index.
+	pub const LAYOUT: [(&'static str, u64, u64); 5] = [
+		("level", 0, 1),
+		("x", 1, 4),
+		("y", 5, 4),
+		("times_offset", 9, 8),
+		("times_length", 17, 8),
+	];
+
+	/// Adds the byte range of the `TileTimes` of the block at `block_coord`.
+	pub fn add_block(&mut self, block_coord: TileCoord, range: ByteRange) {
+		self.lookup.insert(block_coord, range);
+	}
+
+	/// Returns the byte range of the `TileTimes` of the block at `block_coord`, if the block has known times.
+	pub fn get_block(&self, block_coord: &TileCoord) -> Option<&ByteRange> {
+		self.lookup.get(block_coord)
+	}
+
+	/// Returns the number of blocks in the index.
+	pub fn len(&self) -> usize {
+		self.lookup.len()
+	}
+
+	/// Returns `true` if no block has known times.
+	pub fn is_empty(&self) -> bool {
+		self.lookup.is_empty()
+	}
+
+	/// Converts the `TimeIndex` to a Brotli compressed binary blob.
+	///
+	/// Entries are sorted by their coordinates, so the same index always results in the same bytes.
+	#[context("Failed to create TimeIndex Brotli blob")]
+	pub fn as_brotli_blob(&self) -> Result<Blob> {
+		let mut entries = self.lookup.iter().collect::<Vec<_>>();
+		entries.sort_by_key(|(coord, _)| coord.get_sort_index());
+
+		let mut writer = ValueWriterBlob::new_be();
+		for (coord, range) in entries {
+			writer.write_u8(coord.level)?;
+			writer.write_u32(coord.x)?;
+			writer.write_u32(coord.y)?;
+			writer.write_range(range)?;
+		}
+		compress_brotli_fast(&writer.into_blob())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn roundtrip() -> Result<()> {
+		let mut index = TimeIndex::new_empty();
+		assert!(index.is_empty());
+		index.add_block(TileCoord::new(3, 0, 0)?, ByteRange::new(100, 20));
+		index.add_block(TileCoord::new(12, 15, 9)?, ByteRange::new(200, 30));
+
+		let index2 = TimeIndex::from_brotli_blob(index.as_brotli_blob()?, 1000)?;
+		assert_eq!(index, index2);
+		assert_eq!(index2.len(), 2);
+		assert_eq!(
+			index2.get_block(&TileCoord::new(12, 15, 9)?),
+			Some(&ByteRange::new(200, 30))
+		);
+		assert_eq!(index2.get_block(&TileCoord::new(12, 0, 0)?), None);
+		Ok(())
+	}
+
+	#[test]
+	fn defective_blob() -> Result<()> {
+		let blob = compress_brotli_fast(&Blob::from(vec![0u8; 24]))?;
+		assert!(TimeIndex::from_brotli_blob(blob, 1000).is_err());
+		Ok(())
+	}
+}
@@ -7,15 +7,30 @@
 //!
 //! ## File layout
 //! ```notest
-//! [ FileHeader | meta_blob | block_index_blob | blocks... ]
+//! [ FileHeader | header extension | meta_blob | blocks... | block_index_blob | time_index_blob ]
 //! ```
 //! Each block contains:
-//! - a Brotli-compressed **tile index** (mapping tile IDs to byte ranges)
 //! - a contiguous sequence of tile blobs in the reader’s `tile_format` and `tile_compression`
+//! - a Brotli-compressed **tile index** (mapping tile IDs to byte ranges)
+//! - optionally, Brotli-compressed **tile times** (the modification times of the tiles, see [`Tile::mtime`](crate::Tile::mtime))
+//!
+//! The header extension points to the **time index**, which lists the tile times of all blocks.
+//! It is empty if the source doesn't provide modification times.
+//!
+//! ## Streaming
+//! [`VersaTilesWriter::write_to_stream`] writes into non-seekable writers, e.g. pipes or uploads to object stores.
+//! The header can not be updated at the end, so the block index and time index are kept in memory until all
+//! blocks are written, and the final header is appended as a trailer:
+//! ```notest
+//! [ FileHeader ("versatiles_s02") | meta_blob | blocks... | block_index_blob | time_index_blob | FileHeader | header extension ]
+//! ```
+//! The leading header has its own magic word, so older readers fail instead of reading an empty container.
+//! Readers need to know the size of such a file to find the trailer.
 //!
 //! ## Behavior
 //! - All tiles are grouped in 256×256 blocks (`Traversal::new_any_size(256, 256)`).
-//! - The header is written twice: once before, and once after writing metadata and blocks.
+//! - The header is written twice: once before, and once after writing metadata and blocks
+//!   (when streaming, the second one is appended as a trailer).
 //! - Metadata (`TileJSON`) and block indices are compressed using Brotli for storage efficiency.
 //! - The writer supports both raster and vector tile formats.
 //!
@@ -43,7 +58,7 @@
 //! Returns errors if writing fails, compression fails, or if metadata or bounding box
 //! information is invalid.
 
-use super::types::{BlockDefinition, BlockIndex, FileHeader};
+use super::types::{BlockDefinition, BlockIndex, FileHeader, TimeIndex};
 use crate::{
 	ProcessingConfig, TilesReaderTrait, TilesReaderTraverseExt, TilesWriterTrait,
 	container::versatiles::types::BlockWriter,
@@ -52,7 +67,7 @@ use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use futures::lock::Mutex;
 use std::sync::Arc;
-use versatiles_core::{Traversal, io::DataWriterTrait, types::*, utils::compress};
+use versatiles_core::{Traversal, io::DataWriterTrait, progress::ProgressStages, types::*, utils::compress};
 use versatiles_derive::context;
 
 /// Writer for `.versatiles` containers.
@@ -81,6 +96,36 @@ impl TilesWriterTrait for VersaTilesWriter {
 		reader: &mut dyn TilesReaderTrait,
 		writer: &mut dyn DataWriterTrait,
 		config: ProcessingConfig,
+	) -> Result<()> {
+		Self::write(reader, writer, config, false).await
+	}
+}
+
+impl VersaTilesWriter {
+	/// Convert tiles from a [`TilesReaderTrait`] and write them to a non-seekable [`DataWriterTrait`], e.g. stdout.
+	///
+	/// Like [`write_to_writer`](TilesWriterTrait::write_to_writer), but the writer is only appended to:
+	/// the final header is written as a trailer at the end, see the [module documentation](self).
+	/// The writer is flushed at the end.
+	///
+	/// # Errors
+	/// Returns an error if writing, compression, or bounding box validation fails.
+	#[context("writing VersaTiles to stream")]
+	pub async fn write_to_stream(
+		reader: &mut dyn TilesReaderTrait,
+		writer: &mut dyn DataWriterTrait,
+		config: ProcessingConfig,
+	) -> Result<()> {
+		Self::write(reader, writer, config, true).await?;
+		writer.flush()
+	}
+
+	/// Writes the container. If `streamed`, the final header is appended instead of overwriting the first one.
+	async fn write(
+		reader: &mut dyn TilesReaderTrait,
+		writer: &mut dyn DataWriterTrait,
+		config: ProcessingConfig,
+		streamed: bool,
 	) -> Result<()> {
 		// Finalize the configuration
 		let parameters = reader.parameters();
@@ -103,60 +148,74 @@ impl TilesWriterTrait for VersaTilesWriter {
 			&bbox_pyramid.get_geo_bbox().ok_or(anyhow!("invalid geo bounding box"))?,
 		)?;
 
-		// Convert the header to a blob and write it
-		let blob: Blob = header.to_blob()?;
+		// The metadata follows the header, so its range is known in advance,
+		// and is valid in the first header even if it is never updated.
+		header.streamed = streamed;
+		let meta = Self::meta_blob(reader, tile_compression)?;
+		header.meta_range = ByteRange::new(Self::header_blob(&header)?.len(), meta.len());
+
+		// Convert the header and its extension to a blob and write it
+		let blob: Blob = Self::header_blob(&header)?;
 		log::trace!("write header");
 		writer.append(&blob)?;
 
 		log::trace!("write meta");
-		header.meta_range = Self::write_meta(reader, writer, tile_compression).await?;
+		writer.append(&meta)?;
 
 		log::trace!("write blocks");
-		header.blocks_range = Self::write_blocks(reader, writer, tile_compression, config).await?;
+		(header.blocks_range, header.times_range) = Self::write_blocks(reader, writer, tile_compression, config).await?;
 
-		log::trace!("update header");
-		let blob: Blob = header.to_blob()?;
-		writer.write_start(&blob)?;
+		header.streamed = false;
+		let blob: Blob = Self::header_blob(&header)?;
+		if streamed {
+			log::trace!("append header trailer");
+			writer.append(&blob)?;
+		} else {
+			log::trace!("update header");
+			writer.write_start(&blob)?;
+		}
 
 		Ok(())
 	}
-}
 
-impl VersaTilesWriter {
-	/// Write the TileJSON metadata as a Brotli-compressed blob to the writer.
-	///
-	/// Returns the byte range where the metadata was written.
-	#[context("Failed to write metadata")]
-	async fn write_meta(
-		reader: &dyn TilesReaderTrait,
-		writer: &mut dyn DataWriterTrait,
-		compression: TileCompression,
-	) -> Result<ByteRange> {
-		let meta: Blob = reader.tilejson().into();
-		let compressed = compress(meta, compression)?;
+	/// Concatenate the header and its extension. The leading header of a streamed file has no extension.
+	fn header_blob(header: &FileHeader) -> Result<Blob> {
+		if header.streamed {
+			return header.to_blob();
+		}
+		let mut blob = header.to_blob()?.into_vec();
+		blob.extend_from_slice(header.extension_to_blob()?.as_slice());
+		Ok(Blob::from(blob))
+	}
 
-		writer.append(&compressed)
+	/// Compress the TileJSON metadata with the tile compression.
+	#[context("Failed to compress metadata")]
+	fn meta_blob(reader: &dyn TilesReaderTrait, compression: TileCompression) -> Result<Blob> {
+		let meta: Blob = reader.tilejson().into();
+		compress(meta, compression)
 	}
 
 	/// Write all tile blocks and their Brotli-compressed indices.
 	///
 	/// Traverses the reader in 256×256 blocks, writes tiles into each block, and appends
-	/// the resulting block index at the end of the file.
+	/// the resulting block index and, if any tile has a modification time, the time index
+	/// at the end of the file.
 	///
-	/// Returns the byte range covering the block index blob.
+	/// Returns the byte ranges of the block index blob and the time index blob.
 	#[context("Failed to write blocks")]
 	async fn write_blocks(
 		reader: &mut dyn TilesReaderTrait,
 		writer: &mut dyn DataWriterTrait,
 		tile_compression: TileCompression,
 		config: ProcessingConfig,
-	) -> Result<ByteRange> {
+	) -> Result<(ByteRange, ByteRange)> {
 		if reader.parameters().bbox_pyramid.is_empty() {
-			return Ok(ByteRange::empty());
+			return Ok((ByteRange::empty(), ByteRange::empty()));
 		}
 
-		// Create the block index
+		// Create the block index and the time index
 		let block_index_mutex = Arc::new(Mutex::new(BlockIndex::new_empty()));
+		let time_index_mutex = Arc::new(Mutex::new(TimeIndex::new_empty()));
 		let writer_mutex = Arc::new(Mutex::new(writer));
 
 		// Initialize blocks and populate them
@@ -166,6 +225,7 @@ impl VersaTilesWriter {
 				|bbox, stream| {
 					let writer_mutex = Arc::clone(&writer_mutex);
 					let block_index_mutex = Arc::clone(&block_index_mutex);
+					let time_index_mutex = Arc::clone(&time_index_mutex);
 
 					Box::pin(async move {
 						// Log the start of the block
@@ -177,8 +237,9 @@ impl VersaTilesWriter {
 						let mut block_writer = BlockWriter::new(&block, &mut **writer);
 						stream
 							.for_each_sync(|(coord, tile)| {
+								let mtime = tile.mtime();
 								block_writer
-									.write_tile(coord, tile.into_blob(tile_compression).unwrap())
+									.write_tile(coord, tile.into_blob(tile_compression).unwrap(), mtime)
 									.unwrap();
 							})
 							.await;
@@ -186,7 +247,7 @@ impl VersaTilesWriter {
 						// Finish the block
 						log::trace!("finish block {block:?}");
 
-						let (tiles_range, index_range) = block_writer.finalize()?;
+						let (tiles_range, index_range, times_range) = block_writer.finalize()?;
 
 						if tiles_range.length + index_range.length == 0 {
 							// Block is empty, continue with the next block
@@ -196,6 +257,9 @@ impl VersaTilesWriter {
 						// Update the block with the tile and index range and add it to the block index
 						block.set_tiles_range(tiles_range);
 						block.set_index_range(index_range);
+						if let Some(times_range) = times_range {
+							time_index_mutex.lock().await.add_block(*block.get_coord(), times_range);
+						}
 						block_index_mutex.lock().await.add_block(block);
 
 						Ok(())
@@ -206,11 +270,17 @@ impl VersaTilesWriter {
 			.await?;
 
 		// write the block index
-		let range = writer_mutex
-			.lock()
-			.await
-			.append(&block_index_mutex.lock().await.as_brotli_blob()?)?;
+		ProgressStages::begin("write directories");
+		let mut writer = writer_mutex.lock().await;
+		let blocks_range = writer.append(&block_index_mutex.lock().await.as_brotli_blob()?)?;
+
+		let time_index = time_index_mutex.lock().await;
+		let times_range = if time_index.is_empty() {
+			ByteRange::empty()
+		} else {
+			writer.append(&time_index.as_brotli_blob()?)?
+		};
 
-		Ok(range)
+		Ok((blocks_range, times_range))
 	}
 }
@@ -0,0 +1,42 @@
+//! This module provides functionality for handling tiles stored in ZIP archives.
+//!
+//! ZIP archives are a common way to ship offline tile packages. Tiles are stored as
+//! `{z}/{x}/{y}.<format>[.<compression>]` entries, next to an optional `tiles.json`.
+//!
+//! ## Overview
+//! The module exposes the following items:
+//! - `ZipTilesReader`: For reading tiles from a ZIP archive.
+//! - `ZipTilesWriter`: For writing tiles to a ZIP archive.
+//! - `ZipCompressionMethod`: Whether the writer stores or deflates the entries.
+//!
+//! ## Usage Example
+//!
+//! ```no_run
+//! use versatiles_container::*;
+//! use versatiles_core::*;
+//! use std::path::Path;
+//! use anyhow::Result;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     // Reading from a ZIP archive
+//!     let mut reader = ZipTilesReader::open_path(Path::new("path/to/your/tiles.zip"))?;
+//!     let tile = reader.get_tile(&TileCoord::new(1, 2, 3)?).await?;
+//!
+//!     // Writing to a ZIP archive, deflating every entry
+//!     ZipTilesWriter::write_to_path_with_method(
+//!         &mut reader,
+//!         Path::new("path/to/output.zip"),
+//!         ProcessingConfig::default(),
+//!         ZipCompressionMethod::Deflate,
+//!     ).await?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+mod reader;
+mod writer;
+
+pub use reader::ZipTilesReader;
+pub use writer::{ZipCompressionMethod, ZipTilesWriter};
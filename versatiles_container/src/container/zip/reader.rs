@@ -0,0 +1,407 @@
+//! Read tiles and metadata from a `.zip` archive.
+//!
+//! The `ZipTilesReader` indexes the central directory of a ZIP archive once and afterwards
+//! reads single tiles via byte ranges, so even large archives can be served without unpacking.
+//!
+//! ## Layout
+//! - Tiles are expected as `{z}/{x}/{y}.<format>[.<compression>]` entries.
+//! - TileJSON metadata is read from `meta.json`, `tiles.json` or `metadata.json`
+//!   (optionally `.gz`/`.br` compressed).
+//! - Entries may be stored or deflated. Other ZIP compression methods are rejected.
+//! - Deflated entries are inflated to at most the size declared in the central directory, and no entry
+//!   may be larger than [`ReaderLimits::max_decompressed_size`], see [`ZipTilesReader::open_path_with_limits`].
+//!
+//! All tiles must share the same **format** and **compression**; mixing them returns an error.
+//!
+//! ## Usage
+//! ```rust,no_run
+//! use versatiles_container::*;
+//! use versatiles_core::*;
+//! use std::path::Path;
+//! # async fn demo() -> anyhow::Result<()> {
+//! let reader = ZipTilesReader::open_path(Path::new("/absolute/path/to/tiles.zip"))?;
+//! if let Some(mut tile) = reader.get_tile(&TileCoord::new(3, 6, 2)?).await? {
+//!     let _blob = tile.as_blob(reader.parameters().tile_compression)?;
+//! }
+//! # Ok(()) }
+//! ```
+
+use crate::{ReaderLimits, Tile, TilesReaderTrait};
+use anyhow::{Result, anyhow, bail, ensure};
+use async_trait::async_trait;
+use std::{
+	collections::HashMap,
+	fmt::Debug,
+	fs::File,
+	io::{BufReader, Read},
+	path::Path,
+};
+use versatiles_core::{
+	io::*,
+	utils::{DecompressionLimitExceeded, decompress_limited},
+	*,
+};
+use versatiles_derive::context;
+use zip::{CompressionMethod, ZipArchive};
+
+/// Location of a tile inside the archive.
+#[derive(Clone, Debug)]
+struct ZipEntry {
+	/// Byte range of the (possibly deflated) entry data.
+	range: ByteRange,
+	/// `true` if the entry data is deflated and must be inflated after reading.
+	deflated: bool,
+	/// Uncompressed size of the entry, as declared in the central directory.
+	size: u64,
+}
+
+/// Reader for tiles stored inside a ZIP archive.
+///
+/// Merges TileJSON from recognized metadata files, builds a map from `{z,x,y}` to
+/// byte ranges within the archive, infers uniform format/compression, and exposes
+/// tiles via [`TilesReaderTrait`].
+pub struct ZipTilesReader {
+	tilejson: TileJSON,
+	name: String,
+	reader: Box<DataReaderFile>,
+	tile_map: HashMap<TileCoord, ZipEntry>,
+	parameters: TilesReaderParameters,
+}
+
+impl ZipTilesReader {
+	/// Open a ZIP archive and build an index of tiles and metadata.
+	///
+	/// # Errors
+	/// Returns an error if the file is not a valid ZIP archive, if **no tiles** are found,
+	/// if mixed formats/compressions are encountered, or if an entry uses an unsupported
+	/// compression method.
+	#[context("opening zip from path '{}'", path.display())]
+	pub fn open_path(path: &Path) -> Result<ZipTilesReader> {
+		ZipTilesReader::open_path_with_limits(path, ReaderLimits::default())
+	}
+
+	/// Open a ZIP archive like [`ZipTilesReader::open_path`], but with custom [`ReaderLimits`].
+	///
+	/// # Errors
+	/// Returns a [`DecompressionLimitExceeded`] error if an entry is larger than `limits.max_decompressed_size`,
+	/// or any error of [`ZipTilesReader::open_path`].
+	#[context("opening zip from path '{}'", path.display())]
+	pub fn open_path_with_limits(path: &Path, limits: ReaderLimits) -> Result<ZipTilesReader> {
+		let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+
+		let mut tilejson = TileJSON::default();
+		let mut tile_map = HashMap::new();
+		let mut tile_format: Option<TileFormat> = None;
+		let mut tile_compression: Option<TileCompression> = None;
+		let mut bbox_pyramid = TileBBoxPyramid::new_empty();
+
+		for index in 0..archive.len() {
+			let mut file = archive.by_index_raw(index)?;
+			if !file.is_file() {
+				continue;
+			}
+
+			let name = file.name().trim_start_matches("./").to_string();
+			let deflated = match file.compression() {
+				CompressionMethod::Stored => false,
+				CompressionMethod::Deflated => true,
+				method => bail!("unsupported compression method {method:?} of entry '{name}' in zip"),
+			};
+
+			let size = file.size();
+			if size > limits.max_decompressed_size {
+				return Err(
+					anyhow::Error::new(DecompressionLimitExceeded {
+						limit: limits.max_decompressed_size,
+					})
+					.context(format!("entry '{name}' in zip has {size} bytes")),
+				);
+			}
+
+			let path_vec: Vec<&str> = name.split('/').collect();
+
+			if path_vec.len() == 3 {
+				let level = path_vec[0].parse::<u8>()?;
+				let x = path_vec[1].parse::<u32>()?;
+
+				let mut filename: String = String::from(path_vec[2]);
+				let this_compression = TileCompression::from_filename(&mut filename);
+				let Some(this_format) = TileFormat::from_filename(&mut filename) else {
+					log::warn!("unknown file in zip: {name:?}");
+					continue;
+				};
+
+				let y = filename.parse::<u32>()?;
+
+				if let Some(f) = &tile_format {
+					ensure!(
+						f == &this_format,
+						"mixed tile formats in zip, found both {f:?} and {this_format:?}"
+					);
+				} else {
+					tile_format = Some(this_format);
+				}
+
+				if let Some(c) = &tile_compression {
+					ensure!(
+						c == &this_compression,
+						"mixed tile compressions in zip, found both {c:?} and {this_compression:?}"
+					);
+				} else {
+					tile_compression = Some(this_compression);
+				}
+
+				let range = ByteRange::new(file.data_start(), file.compressed_size());
+
+				let coord = TileCoord::new(level, x, y)?;
+				bbox_pyramid.include_coord(&coord);
+				tile_map.insert(coord, ZipEntry { range, deflated, size });
+				continue;
+			}
+
+			let mut read_to_end = || -> Result<Blob> {
+				let mut blob: Vec<u8> = Vec::new();
+				file.read_to_end(&mut blob)?;
+				let blob = Blob::from(blob);
+				if deflated { inflate(&blob, size) } else { Ok(blob) }
+			};
+
+			match name.as_str() {
+				"meta.json" | "tiles.json" | "metadata.json" => {
+					tilejson.merge(&TileJSON::try_from_blob_or_default(&read_to_end()?))?;
+					continue;
+				}
+				"meta.json.gz" | "tiles.json.gz" | "metadata.json.gz" => {
+					tilejson.merge(&TileJSON::try_from_blob_or_default(&decompress_limited(
+						read_to_end()?,
+						TileCompression::Gzip,
+						limits.max_decompressed_size,
+					)?))?;
+					continue;
+				}
+				"meta.json.br" | "tiles.json.br" | "metadata.json.br" => {
+					tilejson.merge(&TileJSON::try_from_blob_or_default(&decompress_limited(
+						read_to_end()?,
+						TileCompression::Brotli,
+						limits.max_decompressed_size,
+					)?))?;
+					continue;
+				}
+				&_ => {}
+			};
+
+			log::warn!("unknown file in zip: {name:?}");
+		}
+
+		if tile_map.is_empty() {
+			return Err(anyhow!("no tiles found in zip"));
+		}
+
+		let parameters = TilesReaderParameters::new(
+			tile_format.ok_or(anyhow!("unknown tile format, can't detect format"))?,
+			tile_compression.ok_or(anyhow!("unknown tile compression, can't detect compression"))?,
+			bbox_pyramid,
+		);
+
+		Ok(ZipTilesReader {
+			tilejson,
+			name: path.to_string_lossy().to_string(),
+			parameters,
+			reader: DataReaderFile::open(path)?,
+			tile_map,
+		})
+	}
+}
+
+/// Inflates raw deflate data, as used by ZIP entries with compression method 8.
+///
+/// Fails if the data expands to more than the declared `size`.
+fn inflate(blob: &Blob, size: u64) -> Result<Blob> {
+	let mut data = Vec::new();
+	flate2::read::DeflateDecoder::new(blob.as_slice())
+		.take(size.saturating_add(1))
+		.read_to_end(&mut data)?;
+	if data.len() as u64 > size {
+		bail!(DecompressionLimitExceeded { limit: size });
+	}
+	Ok(Blob::from(data))
+}
+
+#[async_trait]
+impl TilesReaderTrait for ZipTilesReader {
+	/// Returns the container name.
+	fn container_name(&self) -> &str {
+		"zip"
+	}
+
+	/// Returns the parameters of the tiles reader.
+	fn parameters(&self) -> &TilesReaderParameters {
+		&self.parameters
+	}
+
+	/// Overrides the tile compression method.
+	fn override_compression(&mut self, tile_compression: TileCompression) {
+		self.parameters.tile_compression = tile_compression;
+	}
+
+	/// Return the parsed TileJSON metadata for this archive.
+	fn tilejson(&self) -> &TileJSON {
+		&self.tilejson
+	}
+
+	/// Fetch a single tile by XYZ coordinate.
+	///
+	/// Reads the entry data via its byte range and inflates it if the entry is deflated.
+	/// Returns `Ok(None)` if the tile is absent.
+	#[context("getting tile {:?}", coord)]
+	async fn get_tile(&self, coord: &TileCoord) -> Result<Option<Tile>> {
+		log::trace!("get_tile {:?}", coord);
+
+		let Some(entry) = self.tile_map.get(coord) else {
+			return Ok(None);
+		};
+
+		let mut blob = self.reader.read_range(&entry.range).await?;
+		if entry.deflated {
+			blob = inflate(&blob, entry.size)?;
+		}
+
+		Ok(Some(Tile::from_blob(
+			blob,
+			self.parameters.tile_compression,
+			self.parameters.tile_format,
+		)))
+	}
+
+	/// Returns the name of the zip archive.
+	fn source_name(&self) -> &str {
+		&self.name
+	}
+}
+
+impl Debug for ZipTilesReader {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ZipTilesReader")
+			.field("parameters", &self.parameters())
+			.finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{MOCK_BYTES_PBF, MockTilesWriter, make_test_file};
+	use std::io::Write;
+	use zip::{ZipWriter, write::SimpleFileOptions};
+
+	fn write_zip(entries: &[(&str, CompressionMethod, &[u8])]) -> Result<assert_fs::NamedTempFile> {
+		let file = assert_fs::NamedTempFile::new("test.zip")?;
+		let mut writer = ZipWriter::new(File::create(&file)?);
+		for (name, method, data) in entries {
+			writer.start_file(*name, SimpleFileOptions::default().compression_method(*method))?;
+			writer.write_all(data)?;
+		}
+		writer.finish()?;
+		Ok(file)
+	}
+
+	#[tokio::test]
+	async fn reader() -> Result<()> {
+		let temp_file = make_test_file(TileFormat::MVT, TileCompression::Gzip, 3, "zip").await?;
+		let mut reader = ZipTilesReader::open_path(&temp_file)?;
+
+		assert_eq!(reader.container_name(), "zip");
+		assert!(reader.source_name().ends_with(temp_file.to_str().unwrap()));
+		assert_eq!(
+			reader.tilejson().as_string(),
+			"{\"tilejson\":\"3.0.0\",\"type\":\"dummy\"}"
+		);
+		assert_eq!(reader.parameters().tile_compression, TileCompression::Gzip);
+		assert_eq!(reader.parameters().tile_format, TileFormat::MVT);
+		assert_eq!(reader.parameters().bbox_pyramid.count_tiles(), 85);
+
+		let blob = reader
+			.get_tile(&TileCoord::new(3, 6, 2)?)
+			.await?
+			.unwrap()
+			.into_blob(TileCompression::Uncompressed)?;
+		assert_eq!(blob.as_slice(), MOCK_BYTES_PBF);
+
+		MockTilesWriter::write(&mut reader).await?;
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn stored_and_deflated_entries() -> Result<()> {
+		let data = b"a tile that is long enough to be deflated, a tile that is long enough";
+		let file = write_zip(&[
+			("tiles.json", CompressionMethod::Deflated, br#"{"name":"zipped"}"#),
+			("1/0/0.bin", CompressionMethod::Stored, data),
+			("1/1/0.bin", CompressionMethod::Deflated, data),
+			("readme.txt", CompressionMethod::Stored, b"hello"),
+		])?;
+
+		let reader = ZipTilesReader::open_path(&file)?;
+		assert_eq!(reader.tilejson().as_string(), r#"{"name":"zipped","tilejson":"3.0.0"}"#);
+		assert_eq!(reader.parameters().bbox_pyramid.count_tiles(), 2);
+		for x in 0..2 {
+			let mut tile = reader.get_tile(&TileCoord::new(1, x, 0)?).await?.unwrap();
+			assert_eq!(tile.as_blob(TileCompression::Uncompressed)?.as_slice(), data);
+		}
+		assert!(reader.get_tile(&TileCoord::new(1, 0, 1)?).await?.is_none());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn errors() -> Result<()> {
+		let error = |file: &Path| {
+			ZipTilesReader::open_path(file)
+				.unwrap_err()
+				.chain()
+				.last()
+				.unwrap()
+				.to_string()
+		};
+
+		let file = write_zip(&[("tiles.json", CompressionMethod::Stored, b"{}")])?;
+		assert_eq!(error(&file), "no tiles found in zip");
+
+		let file = write_zip(&[
+			("0/0/0.png", CompressionMethod::Stored, b"1"),
+			("1/0/0.jpg", CompressionMethod::Stored, b"2"),
+		])?;
+		assert_eq!(error(&file), "mixed tile formats in zip, found both PNG and JPG");
+		Ok(())
+	}
+
+	#[test]
+	fn entry_size_limit() -> Result<()> {
+		let file = write_zip(&[("0/0/0.png", CompressionMethod::Deflated, &[0; 1000])])?;
+		let limits = ReaderLimits {
+			max_decompressed_size: 999,
+			..ReaderLimits::default()
+		};
+		let error = ZipTilesReader::open_path_with_limits(&file, limits).unwrap_err();
+		assert_eq!(
+			error.downcast_ref::<DecompressionLimitExceeded>(),
+			Some(&DecompressionLimitExceeded { limit: 999 })
+		);
+		assert!(ZipTilesReader::open_path(&file).is_ok());
+		Ok(())
+	}
+
+	#[test]
+	fn inflate_stops_at_declared_size() -> Result<()> {
+		let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+		encoder.write_all(&[0; 1000])?;
+		let blob = Blob::from(encoder.finish()?);
+
+		assert_eq!(inflate(&blob, 1000)?.len(), 1000);
+		let error = inflate(&blob, 10).unwrap_err();
+		assert_eq!(
+			error.downcast_ref::<DecompressionLimitExceeded>(),
+			Some(&DecompressionLimitExceeded { limit: 10 })
+		);
+		Ok(())
+	}
+}
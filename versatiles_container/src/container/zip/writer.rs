@@ -0,0 +1,208 @@
+//! Write tiles and metadata into a `.zip` archive.
+//!
+//! The `ZipTilesWriter` emits a tile pyramid into a ZIP archive using the
+//! `{z}/{x}/{y}.<format>[.<compression>]` layout and writes TileJSON as `tiles.json[.<compression>]`.
+//! The transport **compression** (`.br`/`.gz` or none) follows the source reader’s
+//! [`TilesReaderParameters::tile_compression`](versatiles_core::TilesReaderParameters).
+//!
+//! ## Entry compression
+//! Independent of the tile compression, every ZIP entry is either stored or deflated
+//! (see [`ZipCompressionMethod`]). Storing is the default, because tiles are usually
+//! compressed already and stored entries can be read without inflating them.
+//!
+//! ## Errors
+//! Returns errors if the archive file cannot be created, or if encoding/compression of
+//! tiles/TileJSON fails while streaming from the reader.
+
+use crate::{ProcessingConfig, TilesReaderTrait, TilesReaderTraverseExt, TilesWriterTrait};
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use futures::lock::Mutex;
+use std::{fs::File, io::Write, path::Path, sync::Arc};
+use versatiles_core::{Traversal, io::DataWriterTrait, utils::compress};
+use versatiles_derive::context;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+/// How the entries of a ZIP archive are compressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ZipCompressionMethod {
+	/// Store entries without compression.
+	#[default]
+	Store,
+	/// Compress entries with deflate.
+	Deflate,
+}
+
+impl From<ZipCompressionMethod> for CompressionMethod {
+	fn from(method: ZipCompressionMethod) -> Self {
+		match method {
+			ZipCompressionMethod::Store => CompressionMethod::Stored,
+			ZipCompressionMethod::Deflate => CompressionMethod::Deflated,
+		}
+	}
+}
+
+/// Writer for tiles packaged inside a ZIP archive.
+///
+/// Serializes TileJSON as `tiles.json[.<br|gz>]` and each tile as `{z}/{x}/{y}.<ext>[.<br|gz>]`,
+/// using the reader’s reported `tile_format` and `tile_compression`.
+pub struct ZipTilesWriter {}
+
+impl ZipTilesWriter {
+	/// Write all tiles and TileJSON from `reader` into a ZIP archive at `path`,
+	/// compressing each entry with `method`.
+	///
+	/// # Errors
+	/// Returns an error if the output file cannot be created, or if any tile/metadata
+	/// serialization or compression fails.
+	#[context("writing zip to path '{}'", path.display())]
+	pub async fn write_to_path_with_method(
+		reader: &mut dyn TilesReaderTrait,
+		path: &Path,
+		config: ProcessingConfig,
+		method: ZipCompressionMethod,
+	) -> Result<()> {
+		let mut writer = ZipWriter::new(File::create(path)?);
+		let options = SimpleFileOptions::default()
+			.compression_method(method.into())
+			.unix_permissions(0o644);
+
+		let tile_compression = reader.parameters().tile_compression;
+		let extension_format = reader.parameters().tile_format.as_extension();
+		let extension_compression = tile_compression.as_extension();
+
+		let meta_data = compress(reader.tilejson().into(), tile_compression)?;
+		writer.start_file(format!("tiles.json{extension_compression}"), options)?;
+		writer.write_all(meta_data.as_slice())?;
+
+		let writer_mutex = Arc::new(Mutex::new(writer));
+
+		reader
+			.traverse_all_tiles(
+				&Traversal::ANY,
+				|_bbox, mut stream| {
+					let writer_mutex = Arc::clone(&writer_mutex);
+					Box::pin(async move {
+						let mut writer = writer_mutex.lock().await;
+						while let Some((coord, tile)) = stream.next().await {
+							let filename = format!(
+								"{}/{}/{}{}{}",
+								coord.level, coord.x, coord.y, extension_format, extension_compression
+							);
+							let blob = tile.into_blob(tile_compression)?;
+							writer.start_file(filename, options)?;
+							writer.write_all(blob.as_slice())?;
+						}
+						Ok(())
+					})
+				},
+				config,
+			)
+			.await?;
+
+		let writer = Arc::try_unwrap(writer_mutex)
+			.map_err(|_| anyhow::anyhow!("zip writer is still in use"))?
+			.into_inner();
+		writer.finish()?;
+
+		Ok(())
+	}
+}
+
+#[async_trait]
+impl TilesWriterTrait for ZipTilesWriter {
+	/// Write all tiles and TileJSON from `reader` into a ZIP archive at `path`, storing
+	/// the entries without additional compression.
+	///
+	/// # Errors
+	/// Returns an error if the output file cannot be created, or if any tile/metadata
+	/// serialization or compression fails.
+	async fn write_to_path(reader: &mut dyn TilesReaderTrait, path: &Path, config: ProcessingConfig) -> Result<()> {
+		Self::write_to_path_with_method(reader, path, config, ZipCompressionMethod::Store).await
+	}
+
+	/// Not implemented: writing a ZIP archive requires a seekable file.
+	///
+	/// # Errors
+	/// Always returns `not implemented`.
+	#[context("writing zip to DataWriter")]
+	async fn write_to_writer(
+		_reader: &mut dyn TilesReaderTrait,
+		_writer: &mut dyn DataWriterTrait,
+		_config: ProcessingConfig,
+	) -> Result<()> {
+		bail!("not implemented")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{MockTilesReader, MockTilesWriter, ZipTilesReader};
+	use assert_fs::NamedTempFile;
+	use versatiles_core::*;
+	use zip::ZipArchive;
+
+	fn mock_reader(tile_format: TileFormat, tile_compression: TileCompression) -> Result<MockTilesReader> {
+		MockTilesReader::new_mock(TilesReaderParameters {
+			bbox_pyramid: TileBBoxPyramid::new_full(3),
+			tile_compression,
+			tile_format,
+		})
+	}
+
+	#[tokio::test]
+	async fn read_write() -> Result<()> {
+		for method in [ZipCompressionMethod::Store, ZipCompressionMethod::Deflate] {
+			let mut mock_reader = mock_reader(TileFormat::MVT, TileCompression::Gzip)?;
+			let temp_path = NamedTempFile::new("test_output.zip")?;
+			ZipTilesWriter::write_to_path_with_method(&mut mock_reader, &temp_path, ProcessingConfig::default(), method)
+				.await?;
+
+			let mut archive = ZipArchive::new(File::open(&temp_path)?)?;
+			assert_eq!(archive.len(), 86);
+			assert_eq!(archive.by_name("3/1/2.pbf.gz")?.compression(), method.into());
+
+			let mut reader = ZipTilesReader::open_path(&temp_path)?;
+			assert_eq!(reader.parameters().bbox_pyramid.count_tiles(), 85);
+			assert_eq!(
+				reader.tilejson().as_string(),
+				"{\"tilejson\":\"3.0.0\",\"type\":\"dummy\"}"
+			);
+			MockTilesWriter::write(&mut reader).await?;
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_filenames() -> Result<()> {
+		let mut bbox_pyramid = TileBBoxPyramid::new_empty();
+		bbox_pyramid.include_coord(&TileCoord::new(3, 1, 2)?);
+		let mut mock_reader = MockTilesReader::new_mock(TilesReaderParameters {
+			bbox_pyramid,
+			tile_compression: TileCompression::Uncompressed,
+			tile_format: TileFormat::PNG,
+		})?;
+
+		let temp_path = NamedTempFile::new("test_filenames.zip")?;
+		ZipTilesWriter::write_to_path(&mut mock_reader, &temp_path, ProcessingConfig::default()).await?;
+
+		let archive = ZipArchive::new(File::open(&temp_path)?)?;
+		let mut filenames = archive.file_names().collect::<Vec<_>>();
+		filenames.sort();
+		assert_eq!(filenames, vec!["3/1/2.png", "tiles.json"]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_invalid_path() -> Result<()> {
+		let mut mock_reader = mock_reader(TileFormat::PNG, TileCompression::Uncompressed)?;
+		let invalid_path = Path::new("/invalid/path/output.zip");
+		assert!(
+			ZipTilesWriter::write_to_path(&mut mock_reader, invalid_path, ProcessingConfig::default())
+				.await
+				.is_err()
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,81 @@
+//! Entry points for fuzzing the container parsers.
+//!
+//! The targets in the `fuzz/` directory feed arbitrary bytes into these functions. Each function
+//! parses the input and returns an error if it is malformed. If parsing succeeds, it asserts that
+//! the result can be serialized and parsed again without changing. So every panic reported by a
+//! fuzzer is a bug: either in the parser or in the serializer.
+//!
+//! This module is only available with the `arbitrary` feature.
+
+use crate::{BlockIndex, EntriesV3, FileHeader, HeaderV3, TileIndex};
+use anyhow::Result;
+use arbitrary::Unstructured;
+use versatiles_core::Blob;
+
+/// Parses a PMTiles v3 header (127 bytes) and checks the serialization roundtrip.
+pub fn parse_pmtiles_header(data: &[u8]) -> Result<()> {
+	let header = HeaderV3::deserialize(&Blob::from(data))?;
+	assert_eq!(HeaderV3::deserialize(&header.serialize()?)?, header);
+	Ok(())
+}
+
+/// Parses an uncompressed PMTiles directory, looks up some tiles and checks the serialization roundtrip.
+pub fn parse_pmtiles_directory(data: &[u8]) -> Result<()> {
+	let entries = EntriesV3::from_blob(&Blob::from(data))?;
+	for entry in entries.iter() {
+		entries.find_tile(entry.tile_id);
+		entries.find_tile(entry.tile_id.saturating_add(u64::from(entry.run_length)));
+	}
+	assert_eq!(EntriesV3::from_blob(&entries.as_slice().serialize_entries()?)?, entries);
+	Ok(())
+}
+
+/// Builds a valid PMTiles directory from arbitrary bytes and checks the serialization roundtrip.
+pub fn roundtrip_pmtiles_directory(data: &[u8]) -> Result<()> {
+	let entries: EntriesV3 = Unstructured::new(data).arbitrary()?;
+	assert_eq!(EntriesV3::from_blob(&entries.as_slice().serialize_entries()?)?, entries);
+	Ok(())
+}
+
+/// Parses a `*.versatiles` file header and checks the serialization roundtrip.
+pub fn parse_versatiles_header(data: &[u8]) -> Result<()> {
+	let header = FileHeader::from_blob(&Blob::from(data))?;
+	assert_eq!(FileHeader::from_blob(&header.to_blob()?)?, header);
+	Ok(())
+}
+
+/// Parses an uncompressed `*.versatiles` block index and checks the serialization roundtrip.
+pub fn parse_versatiles_block_index(data: &[u8]) -> Result<()> {
+	let index = BlockIndex::from_blob(Blob::from(data))?;
+	assert_eq!(BlockIndex::from_blob(index.as_blob()?)?, index);
+	Ok(())
+}
+
+/// Parses an uncompressed `*.versatiles` tile index and checks the serialization roundtrip.
+pub fn parse_versatiles_tile_index(data: &[u8]) -> Result<()> {
+	let index = TileIndex::from_blob(Blob::from(data))?;
+	assert_eq!(index.as_blob()?.as_slice(), data);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proptest::prelude::*;
+
+	proptest! {
+		#[test]
+		fn parsers_do_not_panic(bytes in prop::collection::vec(any::<u8>(), 0..1024)) {
+			let _ = parse_pmtiles_header(&bytes);
+			let _ = parse_pmtiles_directory(&bytes);
+			let _ = parse_versatiles_header(&bytes);
+			let _ = parse_versatiles_block_index(&bytes);
+			let _ = parse_versatiles_tile_index(&bytes);
+		}
+
+		#[test]
+		fn valid_directories_roundtrip(bytes in prop::collection::vec(any::<u8>(), 0..4096)) {
+			roundtrip_pmtiles_directory(&bytes).unwrap();
+		}
+	}
+}
@@ -19,7 +19,7 @@
 //! async fn main() -> anyhow::Result<()> {
 //!     // Open a source container via the registry
 //!     let registry = ContainerRegistry::default();
-//!     let reader = registry.get_reader_from_str("../testdata/berlin.mbtiles").await?;
+//!     let reader = registry.open_reader("../testdata/berlin.mbtiles").await?;
 //!
 //!     // Optionally adapt the reader: limit to a bbox pyramid, keep compression as-is
 //!     let params = TilesConverterParameters {
@@ -36,8 +36,12 @@
 //! ```
 //!
 //! # Features
+//! - `directory`, `mbtiles`, `pmtiles`, `tar`, `versatiles`, `zip` (default): the container backends,
+//!   see [the list of formats](crate#supported-tile-container-formats). Embedded users can disable
+//!   the default features and only enable e.g. `versatiles` to get a small binary.
 //! - `cli`: enables human‑readable probing of containers and tiles.
 //! - `test`: helpers for integration tests in downstream crates.
+//! - `arbitrary`: `Arbitrary` implementations and the [`fuzzing`] entry points for the container parsers.
 //!
 //! ## See also
 //! - [`ContainerRegistry`]: register custom reader/writer implementations at runtime
@@ -52,6 +56,9 @@ mod container;
 /// Re‑exports the container registry and common open/write helpers.
 pub use container::*;
 
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
+
 mod types;
 /// Re‑exports reader/writer traits, converters, and auxiliary types.
 pub use types::*;
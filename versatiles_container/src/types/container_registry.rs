@@ -1,6 +1,8 @@
 //! `ContainerRegistry` provides functionalities to read and write tile containers from various sources such as local files, directories, and URLs.
 //!
 //! It supports multiple container formats and allows registering custom readers and writers for different file extensions.
+//! Containers can also be opened by URI, e.g. `https://example.org/tiles.pmtiles`, `s3://bucket/tiles.versatiles`
+//! or `dir://./tiles/`, see [`ContainerRegistry::open_reader`].
 //!
 //! # Example Usage
 //!
@@ -14,7 +16,7 @@
 //!     let registry = ContainerRegistry::default();
 //!
 //!     // Read from a local file
-//!     let reader = registry.get_reader_from_str("../testdata/berlin.mbtiles").await.unwrap();
+//!     let reader = registry.open_reader("../testdata/berlin.mbtiles").await.unwrap();
 //!
 //!     // Define the output filename
 //!     let output_path = std::env::temp_dir().join("temp3.versatiles");
@@ -30,6 +32,7 @@ use crate::{types::data_location::DataLocation, *};
 use anyhow::{Result, anyhow, bail};
 #[cfg(test)]
 use assert_fs::NamedTempFile;
+use reqwest::Url;
 use std::{
 	collections::HashMap,
 	env,
@@ -37,19 +40,28 @@ use std::{
 	path::{Path, PathBuf},
 	pin::Pin,
 	sync::Arc,
+	time::Duration,
 };
-use versatiles_core::io::{DataReader, DataReaderBlob, DataReaderHttp};
 #[cfg(test)]
 use versatiles_core::{TileCompression, TileFormat};
+use versatiles_core::{
+	io::{DataReader, DataReaderBlob, DataReaderCached, DataReaderHttp, DataWriterStream, DataWriterTrait},
+	progress::{ConversionMetrics, ProgressStages},
+	utils::fnv1a64,
+};
 use versatiles_derive::context;
 
 /// Signature for async opener functions used by the registry.
 type ReadFuture = Pin<Box<dyn Future<Output = Result<Box<dyn TilesReaderTrait>>> + Send>>;
 type ReadData = Box<dyn Fn(DataReader) -> ReadFuture + Send + Sync + 'static>;
 type ReadFile = Box<dyn Fn(PathBuf) -> ReadFuture + Send + Sync + 'static>;
+type ReadUri = Box<dyn Fn(String, ContainerRegistry) -> ReadFuture + Send + Sync + 'static>;
 type WriteFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
 type WriteFile =
 	Box<dyn Fn(Box<dyn TilesReaderTrait>, PathBuf, ProcessingConfig) -> WriteFuture + Send + Sync + 'static>;
+type WriteData = Box<
+	dyn Fn(Box<dyn TilesReaderTrait>, Box<dyn DataWriterTrait>, ProcessingConfig) -> WriteFuture + Send + Sync + 'static,
+>;
 
 /// Registry mapping file extensions to async tile container readers and writers.
 ///
@@ -59,12 +71,26 @@ type WriteFile =
 /// - PMTiles
 /// - VersaTiles
 /// - Directory-based containers
+///
+/// Only the formats whose cargo feature is enabled are registered.
+///
+/// Sources can be addressed by URI schemes (`file`, `http`, `https`, `s3` and `dir`), see [`Self::open_reader`].
 #[derive(Clone)]
 pub struct ContainerRegistry {
 	data_readers: HashMap<String, Arc<ReadData>>,
 	file_readers: HashMap<String, Arc<ReadFile>>,
+	uri_readers: HashMap<String, Arc<ReadUri>>,
 	file_writers: HashMap<String, Arc<WriteFile>>,
+	data_writers: HashMap<String, Arc<WriteData>>,
 	writer_config: ProcessingConfig,
+	directory_read_template: PathTemplate,
+	directory_write_template: PathTemplate,
+	strict_tilejson: bool,
+	atomic_write: bool,
+	fsync: bool,
+	request_timeout: Option<Duration>,
+	s3_endpoint: Option<Url>,
+	index_cache_dir: Option<PathBuf>,
 }
 
 impl ContainerRegistry {
@@ -72,48 +98,204 @@ impl ContainerRegistry {
 	///
 	/// Registers built-in readers and writers for supported container formats.
 	pub fn new(writer_config: ProcessingConfig) -> Self {
+		#[allow(unused_mut)] // without any container feature, nothing is registered
 		let mut reg = Self {
 			data_readers: HashMap::new(),
 			file_readers: HashMap::new(),
+			uri_readers: HashMap::new(),
 			file_writers: HashMap::new(),
+			data_writers: HashMap::new(),
 			writer_config,
+			directory_read_template: PathTemplate::default(),
+			directory_write_template: PathTemplate::default(),
+			strict_tilejson: false,
+			atomic_write: true,
+			fsync: false,
+			request_timeout: None,
+			s3_endpoint: None,
+			index_cache_dir: None,
 		};
 
-		// MBTiles
-		reg.register_reader_file("mbtiles", |p| async move { Ok(MBTilesReader::open_path(&p)?.boxed()) });
-		reg.register_writer_file("mbtiles", |mut r, p, c| async move {
+		reg.register_uri_schemes();
+
+		#[cfg(feature = "mbtiles")]
+		reg.register_mbtiles();
+		#[cfg(feature = "tar")]
+		reg.register_tar();
+		#[cfg(feature = "pmtiles")]
+		reg.register_pmtiles();
+		#[cfg(feature = "zip")]
+		reg.register_zip();
+		#[cfg(feature = "versatiles")]
+		reg.register_versatiles();
+
+		reg
+	}
+
+	fn register_uri_schemes(&mut self) {
+		for scheme in ["file", "http", "https"] {
+			self.register_reader_uri(scheme, |uri, registry| async move {
+				registry.get_reader(DataLocation::from(uri.as_str())).await
+			});
+		}
+		self.register_reader_uri("s3", |uri, registry| async move {
+			let url = s3_url(&uri, registry.s3_endpoint.as_ref())?;
+			registry.get_reader(DataLocation::Url(url)).await
+		});
+		#[cfg(feature = "directory")]
+		self.register_reader_uri("dir", |uri, registry| async move {
+			let mut location = DataLocation::Path(PathBuf::from(strip_scheme(&uri)));
+			location.resolve(&DataLocation::cwd()?)?;
+			anyhow::ensure!(location.as_path()?.is_dir(), "'{uri}' is not a directory");
+			registry.get_reader(location).await
+		});
+	}
+
+	#[cfg(feature = "mbtiles")]
+	fn register_mbtiles(&mut self) {
+		self.register_reader_file("mbtiles", |p| async move { Ok(MBTilesReader::open_path(&p)?.boxed()) });
+		self.register_writer_file("mbtiles", |mut r, p, c| async move {
 			MBTilesWriter::write_to_path(r.as_mut(), &p, c).await
 		});
+	}
 
-		// TAR
-		reg.register_reader_file("tar", |p| async move { Ok(TarTilesReader::open_path(&p)?.boxed()) });
-		reg.register_writer_file("tar", |mut r, p, c| async move {
+	#[cfg(feature = "tar")]
+	fn register_tar(&mut self) {
+		self.register_reader_file("tar", |p| async move { Ok(TarTilesReader::open_path(&p)?.boxed()) });
+		for extension in ["tgz", "gz", "tzst", "zst"] {
+			self.register_reader_file(extension, |p| async move { Ok(TarTilesReader::open_path(&p)?.boxed()) });
+		}
+		self.register_writer_file("tar", |mut r, p, c| async move {
 			TarTilesWriter::write_to_path(r.as_mut(), &p, c).await
 		});
-		// PMTiles
-		reg.register_reader_file(
+		self.register_writer_data("tar", |mut r, mut w, c| async move {
+			TarTilesWriter::write_to_writer(r.as_mut(), w.as_mut(), c).await
+		});
+	}
+
+	#[cfg(feature = "pmtiles")]
+	fn register_pmtiles(&mut self) {
+		self.register_reader_file(
 			"pmtiles",
 			|p| async move { Ok(PMTilesReader::open_path(&p).await?.boxed()) },
 		);
-		reg.register_reader_data("pmtiles", |p| async move {
+		self.register_reader_data("pmtiles", |p| async move {
 			Ok(PMTilesReader::open_reader(p).await?.boxed())
 		});
-		reg.register_writer_file("pmtiles", |mut r, p, c| async move {
+		self.register_writer_file("pmtiles", |mut r, p, c| async move {
 			PMTilesWriter::write_to_path(r.as_mut(), &p, c).await
 		});
+	}
+
+	#[cfg(feature = "zip")]
+	fn register_zip(&mut self) {
+		self.register_reader_file("zip", |p| async move { Ok(ZipTilesReader::open_path(&p)?.boxed()) });
+		self.register_writer_file("zip", |mut r, p, c| async move {
+			ZipTilesWriter::write_to_path(r.as_mut(), &p, c).await
+		});
+	}
 
-		// VersaTiles
-		reg.register_reader_file("versatiles", |p| async move {
+	#[cfg(feature = "versatiles")]
+	fn register_versatiles(&mut self) {
+		self.register_reader_file("versatiles", |p| async move {
 			Ok(VersaTilesReader::open_path(&p).await?.boxed())
 		});
-		reg.register_reader_data("versatiles", |p| async move {
+		self.register_reader_data("versatiles", |p| async move {
 			Ok(VersaTilesReader::open_reader(p).await?.boxed())
 		});
-		reg.register_writer_file("versatiles", |mut r, p, c| async move {
+		self.register_writer_file("versatiles", |mut r, p, c| async move {
 			VersaTilesWriter::write_to_path(r.as_mut(), &p, c).await
 		});
+		self.register_writer_data("versatiles", |mut r, mut w, c| async move {
+			VersaTilesWriter::write_to_stream(r.as_mut(), w.as_mut(), c).await
+		});
+	}
 
-		reg
+	/// Sets the [`PathTemplate`] used to find the tiles when reading directory containers. (default: `{z}/{x}/{y}`)
+	pub fn set_directory_read_template(&mut self, template: PathTemplate) {
+		self.directory_read_template = template;
+	}
+
+	/// Sets the [`PathTemplate`] used to name the tiles when writing directory containers. (default: `{z}/{x}/{y}`)
+	pub fn set_directory_write_template(&mut self, template: PathTemplate) {
+		self.directory_write_template = template;
+	}
+
+	/// Sets whether invalid `TileJSON` metadata fails reading and writing containers. (default: `false`)
+	///
+	/// The metadata is validated with [`TileJSON::validate`](versatiles_core::TileJSON::validate) when a container is
+	/// opened and before it is written. If not strict, problems are only logged.
+	pub fn set_strict_tilejson(&mut self, strict: bool) {
+		self.strict_tilejson = strict;
+	}
+
+	/// Sets whether file containers are written atomically. (default: `true`)
+	///
+	/// If enabled, writers write to `<path>.tmp` in the same directory, which is renamed to `<path>` on success,
+	/// so an interrupted conversion never leaves a half-written file at the target path.
+	/// Disable it for filesystems where renaming does not replace files atomically.
+	pub fn set_atomic_write(&mut self, atomic_write: bool) {
+		self.atomic_write = atomic_write;
+	}
+
+	/// Sets whether written file containers are flushed to disk with `fsync` before they are renamed. (default: `false`)
+	pub fn set_fsync(&mut self, fsync: bool) {
+		self.fsync = fsync;
+	}
+
+	/// Sets the maximum duration of a single request to an HTTP source. (default: no limit)
+	///
+	/// A request that takes longer fails, so a hung server can not stall reading.
+	/// Use [`TilesConverterParameters::tile_timeout`] to limit the time spent on a tile of any reader.
+	pub fn set_request_timeout(&mut self, timeout: Option<Duration>) {
+		self.request_timeout = timeout;
+	}
+
+	/// Sets the endpoint used for `s3://bucket/key` URIs. (default: Amazon S3)
+	///
+	/// By default, `s3://bucket/key` is read from `https://bucket.s3.amazonaws.com/key`.
+	/// With an endpoint, e.g. of a MinIO server, it is read from `<endpoint>/bucket/key`.
+	/// Objects are read with unauthenticated HTTP requests, so they must be public.
+	pub fn set_s3_endpoint(&mut self, endpoint: Option<Url>) {
+		self.s3_endpoint = endpoint;
+	}
+
+	/// Sets a directory to cache the indexes of HTTP sources in. (default: no cache)
+	///
+	/// Everything read while opening a remote container, e.g. its header and the leaf directories of PMTiles
+	/// or the block index of a `.versatiles` file, is stored in a cache file, so opening it again, e.g. after
+	/// a server restart, needs no requests besides a `HEAD` request.
+	/// A cache file is only used as long as the ETag, `Last-Modified` and content length of the source are unchanged.
+	/// Sources that report none of them are not cached.
+	pub fn set_index_cache_dir(&mut self, dir: Option<PathBuf>) {
+		self.index_cache_dir = dir;
+	}
+
+	/// Returns the configuration passed to the readers and writers.
+	pub fn config(&self) -> &ProcessingConfig {
+		&self.writer_config
+	}
+
+	/// Sets the metrics that the readers and writers count into, e.g. those of a single output of a conversion.
+	pub fn set_metrics(&mut self, metrics: ConversionMetrics) {
+		self.writer_config.metrics = metrics;
+	}
+
+	/// Register an async reader for a URI scheme, e.g. `s3` for `s3://bucket/key`.
+	///
+	/// # Arguments
+	/// * `scheme` - The URI scheme to associate with the reader.
+	/// * `read_uri` - Async function that takes the full URI and the registry, and returns a boxed `TilesReaderTrait`.
+	///   It usually translates the URI into a [`DataLocation`] and calls [`Self::get_reader`].
+	pub fn register_reader_uri<F, Fut>(&mut self, scheme: &str, read_uri: F)
+	where
+		F: Fn(String, ContainerRegistry) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<Box<dyn TilesReaderTrait>>> + Send + 'static,
+	{
+		self.uri_readers.insert(
+			scheme.to_ascii_lowercase(),
+			Arc::new(Box::new(move |u, r| Box::pin(read_uri(u, r)))),
+		);
 	}
 
 	/// Register an async file-based reader for a given file extension.
@@ -165,13 +347,52 @@ impl ContainerRegistry {
 		);
 	}
 
-	pub async fn get_reader_from_str(&self, data_source: &str) -> Result<Box<dyn TilesReaderTrait>> {
-		self.get_reader(DataSource::parse(data_source, self)?).await
+	/// Register an async writer for a given file extension, that writes into a `DataWriterTrait`.
+	///
+	/// Used to write containers into streams, e.g. stdout, see [`Self::write_to_writer`].
+	///
+	/// # Arguments
+	/// * `ext` - The file extension to associate with the writer.
+	/// * `write_data` - Async function that takes a boxed `TilesReaderTrait`, a boxed `DataWriterTrait`, and a
+	///   `ProcessingConfig`, and writes the tiles into the data writer.
+	pub fn register_writer_data<F, Fut>(&mut self, ext: &str, write_data: F)
+	where
+		F: Fn(Box<dyn TilesReaderTrait>, Box<dyn DataWriterTrait>, ProcessingConfig) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<()>> + Send + 'static,
+	{
+		self.data_writers.insert(
+			sanitize_extension(ext),
+			Arc::new(Box::new(move |r, w, c| Box::pin(write_data(r, w, c)))),
+		);
+	}
+
+	/// Opens a tile container reader for a URI or a path.
+	///
+	/// This is the common entry point to open sources, used by the CLI, the server and pipelines.
+	/// The reader is chosen by:
+	/// - the URI scheme, e.g. `https://example.org/tiles.pmtiles`, `s3://bucket/tiles.versatiles`,
+	///   `dir://./tiles/` or `file:///data/tiles.mbtiles`, see [`Self::register_reader_uri`],
+	/// - a driver prefix, e.g. `mbtiles:data.bin` or `vpl:-` to read from stdin,
+	/// - otherwise the file extension, e.g. `tiles.mbtiles`. Directories are read as directory containers.
+	///
+	/// # Errors
+	/// Returns an error if the scheme or extension is unknown, or if the container can not be opened.
+	#[context("Failed to open reader for '{uri}'")]
+	pub async fn open_reader(&self, uri: &str) -> Result<Box<dyn TilesReaderTrait>> {
+		if let Some(scheme) = uri_scheme(uri) {
+			let read_uri = self
+				.uri_readers
+				.get(&scheme)
+				.ok_or_else(|| anyhow!("URI scheme '{scheme}' unknown"))?;
+			return read_uri(uri.to_string(), self.clone()).await;
+		}
+		self.get_reader(DataSource::parse(uri, self)?).await
 	}
 
 	/// Get a tile container reader for a given filename or URL.
 	///
 	/// Resolves the path or URL, determines the file extension, and uses the appropriate registered reader.
+	/// The `TileJSON` of the reader is validated, see [`Self::set_strict_tilejson`].
 	///
 	/// # Arguments
 	/// * `url_path` - The file path or URL to read from.
@@ -187,16 +408,32 @@ impl ContainerRegistry {
 		data_source.resolve(&DataLocation::cwd()?)?;
 		let extension = sanitize_extension(data_source.extension());
 
-		match data_source.into_location() {
+		let reader = match data_source.into_location() {
 			DataLocation::Url(url) => {
-				let reader = DataReaderHttp::from_url(url.clone())
-					.with_context(|| format!("Failed to create HTTP data reader for URL '{url}'"))?;
+				if !matches!(url.scheme(), "http" | "https")
+					&& let Some(read_uri) = self.uri_readers.get(url.scheme())
+				{
+					return read_uri(url.to_string(), self.clone()).await;
+				}
 
-				self
+				let reader = DataReaderHttp::from_url_with_timeout(url.clone(), self.request_timeout)
+					.with_context(|| format!("Failed to create HTTP data reader for URL '{url}'"))?;
+				let read_data = self
 					.data_readers
 					.get(&extension)
-					.ok_or_else(|| anyhow!("file extension '{extension}' unknown"))?(reader)
-				.await
+					.ok_or_else(|| anyhow!("file extension '{extension}' unknown"))?;
+
+				match &self.index_cache_dir {
+					Some(dir) => {
+						let (reader, handle) = DataReaderCached::open(reader, &dir.join(index_cache_name(&url))).await?;
+						let reader = read_data(Box::new(reader)).await?;
+						if let Err(error) = handle.persist() {
+							log::warn!("can not cache index of '{url}': {error:#}");
+						}
+						reader
+					}
+					None => read_data(reader).await?,
+				}
 			}
 			DataLocation::Path(path) => {
 				if !path.exists() {
@@ -204,16 +441,14 @@ impl ContainerRegistry {
 				}
 
 				if path.is_dir() {
-					return Ok(DirectoryTilesReader::open_path(&path)
-						.with_context(|| format!("Failed opening {path:?} as directory"))?
-						.boxed());
+					self.get_directory_reader(&path)?
+				} else {
+					self
+						.file_readers
+						.get(&extension)
+						.ok_or_else(|| anyhow!("file extension '{extension}' unknown"))?(path.to_path_buf())
+					.await?
 				}
-
-				self
-					.file_readers
-					.get(&extension)
-					.ok_or_else(|| anyhow!("file extension '{extension}' unknown"))?(path.to_path_buf())
-				.await
 			}
 			DataLocation::Blob(blob) => {
 				let reader = Box::new(DataReaderBlob::from(blob));
@@ -221,15 +456,33 @@ impl ContainerRegistry {
 					.data_readers
 					.get(&extension)
 					.ok_or_else(|| anyhow!("file extension '{extension}' unknown"))?(reader)
-				.await
+				.await?
 			}
-		}
+		};
+
+		reader
+			.tilejson()
+			.validate()
+			.check(&format!("'{}'", reader.source_name()), self.strict_tilejson)?;
+
+		Ok(reader)
 	}
 
 	/// Write tiles from a reader to the specified output path.
 	///
 	/// If the path is a directory, writes using the directory writer; otherwise, uses the appropriate file writer based on extension.
 	///
+	/// File containers are written to a temporary file that is renamed on success, see [`Self::set_atomic_write`].
+	/// If a file writer fails, the incomplete output file is removed, so no corrupt container is left behind.
+	/// If the writer's cancellation token is cancelled, the writer finishes a valid container that only
+	/// contains the tiles read before the cancellation. If its abort token is cancelled, writing fails
+	/// immediately and the incomplete output file is removed as well.
+	///
+	/// The `TileJSON` of the reader is validated before writing, see [`Self::set_strict_tilejson`].
+	///
+	/// The path `-` writes a tar archive to stdout. A driver prefix selects another format, e.g. `versatiles:-`,
+	/// see [`Self::write_to_writer`].
+	///
 	/// # Arguments
 	/// * `reader` - A boxed tile container reader providing tiles to write.
 	/// * `path` - The output path to write tiles to.
@@ -238,9 +491,20 @@ impl ContainerRegistry {
 	/// Result indicating success or failure.
 	#[context("writing tiles to path '{path:?}'")]
 	pub async fn write_to_path(&self, mut reader: Box<dyn TilesReaderTrait>, path: &Path) -> Result<()> {
+		if let Some(extension) = stdout_extension(path) {
+			let mut writer = DataWriterStream::stdout();
+			writer.set_metrics(self.writer_config.metrics.clone());
+			return self.write_to_writer(reader, &extension, Box::new(writer)).await;
+		}
+
 		let path = env::current_dir()?.join(path);
+		reader
+			.tilejson()
+			.validate()
+			.check(&format!("output {path:?}"), self.strict_tilejson)?;
+
 		if path.is_dir() {
-			return DirectoryTilesWriter::write_to_path(reader.as_mut(), &path, self.writer_config.clone()).await;
+			return self.abortable(self.write_to_directory(reader.as_mut(), &path)).await;
 		}
 
 		let extension = path
@@ -253,21 +517,195 @@ impl ContainerRegistry {
 			.file_writers
 			.get(&extension)
 			.ok_or_else(|| anyhow!("Error when reading: file extension '{extension}' unknown"))?;
-		writer(reader, path.to_path_buf(), self.writer_config.clone()).await?;
+
+		let write_path = if self.atomic_write {
+			let mut name = path.file_name().unwrap_or_default().to_os_string();
+			name.push(".tmp");
+			let write_path = path.with_file_name(name);
+			if write_path.is_file() {
+				log::warn!("removing stale temporary file {write_path:?}");
+				std::fs::remove_file(&write_path)?;
+			}
+			write_path
+		} else {
+			path.clone()
+		};
+
+		let result = self
+			.abortable(writer(reader, write_path.clone(), self.writer_config.clone()))
+			.await;
+
+		if result.is_err() && write_path.is_file() {
+			log::warn!("removing incomplete output file {write_path:?}");
+			if let Err(err) = std::fs::remove_file(&write_path) {
+				log::error!("failed to remove incomplete output file {write_path:?}: {err}");
+			}
+		}
+		result?;
+
+		ProgressStages::begin("finalize");
+		if self.fsync {
+			std::fs::File::open(&write_path)?
+				.sync_all()
+				.with_context(|| format!("syncing {write_path:?}"))?;
+		}
+
+		if write_path != path {
+			std::fs::rename(&write_path, &path).with_context(|| format!("renaming {write_path:?} to {path:?}"))?;
+			#[cfg(unix)]
+			if self.fsync
+				&& let Some(dir) = path.parent()
+			{
+				// persist the rename itself
+				std::fs::File::open(dir)?.sync_all()?;
+			}
+		}
+
+		if self.writer_config.cancellation_token.is_cancelled() {
+			log::warn!("writing was cancelled, {path:?} only contains the tiles read so far");
+		}
+
+		Ok(())
+	}
+
+	/// Write tiles from a reader into a data writer, e.g. a [`DataWriterStream`] on stdout.
+	///
+	/// The container format is selected by `extension`, e.g. `tar`. Only formats with a registered data writer
+	/// are supported, see [`Self::register_writer_data`]. The data writer does not need to be seekable.
+	/// The `TileJSON` of the reader is validated before writing, see [`Self::set_strict_tilejson`].
+	///
+	/// # Arguments
+	/// * `reader` - A boxed tile container reader providing tiles to write.
+	/// * `extension` - The file extension of the container format to write.
+	/// * `writer` - The data writer to write the container into.
+	#[context("writing tiles as '{extension}' into a data writer")]
+	pub async fn write_to_writer(
+		&self,
+		reader: Box<dyn TilesReaderTrait>,
+		extension: &str,
+		writer: Box<dyn DataWriterTrait>,
+	) -> Result<()> {
+		let extension = sanitize_extension(extension);
+		reader
+			.tilejson()
+			.validate()
+			.check(&format!("output '{extension}' stream"), self.strict_tilejson)?;
+
+		let write_data = self
+			.data_writers
+			.get(&extension)
+			.ok_or_else(|| anyhow!("writing '{extension}' containers into a stream is not supported"))?;
+		self
+			.abortable(write_data(reader, writer, self.writer_config.clone()))
+			.await?;
+
+		if self.writer_config.cancellation_token.is_cancelled() {
+			log::warn!("writing was cancelled, the '{extension}' stream only contains the tiles read so far");
+		}
 
 		Ok(())
 	}
 
+	/// Runs `write`, but fails as soon as the `abort_token` of the [`ProcessingConfig`] is cancelled.
+	///
+	/// The writer is dropped in that case, so callers can remove what it has written so far.
+	async fn abortable(&self, write: impl Future<Output = Result<()>>) -> Result<()> {
+		let abort_token = &self.writer_config.abort_token;
+		tokio::select! {
+			result = write => result,
+			() = abort_token.cancelled() => bail!("writing was aborted"),
+		}
+	}
+
 	pub fn supports_reader_extension(&self, ext: &str) -> bool {
 		let ext = sanitize_extension(ext);
 		self.data_readers.contains_key(&ext) || self.file_readers.contains_key(&ext)
 	}
+
+	#[cfg(feature = "directory")]
+	#[context("Failed opening {path:?} as directory")]
+	fn get_directory_reader(&self, path: &Path) -> Result<Box<dyn TilesReaderTrait>> {
+		Ok(DirectoryTilesReader::open_path_with_template(path, &self.directory_read_template)?.boxed())
+	}
+
+	#[cfg(not(feature = "directory"))]
+	fn get_directory_reader(&self, path: &Path) -> Result<Box<dyn TilesReaderTrait>> {
+		bail!("can not read directory {path:?}, because the feature 'directory' is disabled")
+	}
+
+	#[cfg(feature = "directory")]
+	async fn write_to_directory(&self, reader: &mut dyn TilesReaderTrait, path: &Path) -> Result<()> {
+		DirectoryTilesWriter::write_to_path_with_template(
+			reader,
+			path,
+			&self.directory_write_template,
+			self.writer_config.clone(),
+		)
+		.await
+	}
+
+	#[cfg(not(feature = "directory"))]
+	async fn write_to_directory(&self, _reader: &mut dyn TilesReaderTrait, path: &Path) -> Result<()> {
+		bail!("can not write directory {path:?}, because the feature 'directory' is disabled")
+	}
 }
 
 fn sanitize_extension(ext: &str) -> String {
 	ext.to_ascii_lowercase().trim_matches('.').to_string()
 }
 
+/// Returns the container format, if `path` is stdout: `tar` for `-`, or the driver prefix, e.g. `versatiles:-`.
+#[must_use]
+pub fn stdout_extension(path: &Path) -> Option<String> {
+	let path = path.to_str()?;
+	if path == "-" {
+		return Some(String::from("tar"));
+	}
+	let prefix = path.strip_suffix(":-")?;
+	(!prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_alphanumeric())).then(|| sanitize_extension(prefix))
+}
+
+/// Returns the name of the index cache file of a URL: a readable part of the URL and a hash of the whole URL.
+///
+/// The hash must be stable across Rust versions, otherwise caches would not be found after an upgrade.
+fn index_cache_name(url: &Url) -> String {
+	let name: String = format!("{}{}", url.host_str().unwrap_or_default(), url.path())
+		.chars()
+		.map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '_' })
+		.take(100)
+		.collect();
+	format!("{name}.{:016x}.cache", fnv1a64(url.as_str().as_bytes()))
+}
+
+/// Returns the lower-case scheme of a URI like `s3://bucket/key`, or `None` for plain paths and driver prefixes.
+#[must_use]
+pub fn uri_scheme(uri: &str) -> Option<String> {
+	let (scheme, _) = uri.split_once("://")?;
+	let mut chars = scheme.chars();
+	// a single letter is a Windows drive, e.g. `C://data`
+	let valid = scheme.len() > 1
+		&& chars.next()?.is_ascii_alphabetic()
+		&& chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+	valid.then(|| scheme.to_ascii_lowercase())
+}
+
+fn strip_scheme(uri: &str) -> &str {
+	uri.split_once("://").map_or(uri, |(_, rest)| rest)
+}
+
+/// Translates `s3://bucket/key` into the HTTPS URL of the object.
+fn s3_url(uri: &str, endpoint: Option<&Url>) -> Result<Url> {
+	let (bucket, key) = strip_scheme(uri)
+		.split_once('/')
+		.filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+		.ok_or_else(|| anyhow!("S3 URI '{uri}' must have the form 's3://bucket/key'"))?;
+	let url = match endpoint {
+		Some(endpoint) => format!("{}/{bucket}/{key}", endpoint.as_str().trim_end_matches('/')),
+		None => format!("https://{bucket}.s3.amazonaws.com/{key}"),
+	};
+	Ok(Url::parse(&url)?)
+}
+
 impl Default for ContainerRegistry {
 	fn default() -> Self {
 		Self::new(ProcessingConfig::default())
@@ -295,6 +733,7 @@ pub async fn make_test_file(
 	let container_file = match extension {
 		"tar" => NamedTempFile::new("temp.tar"),
 		"versatiles" => NamedTempFile::new("temp.versatiles"),
+		"zip" => NamedTempFile::new("temp.zip"),
 		_ => panic!("make_test_file: extension {extension} not found"),
 	}?;
 
@@ -310,7 +749,7 @@ pub mod tests {
 	use super::*;
 	use assert_fs::TempDir;
 	use std::time::Instant;
-	use versatiles_core::{TileBBoxPyramid, TilesReaderParameters};
+	use versatiles_core::{TileBBox, TileBBoxPyramid, TilesReaderParameters};
 
 	/// Test writers and readers for various formats.
 	#[test]
@@ -320,6 +759,7 @@ pub mod tests {
 			Directory,
 			Tar,
 			Versatiles,
+			Zip,
 		}
 
 		#[tokio::main]
@@ -349,19 +789,25 @@ pub mod tests {
 				Container::Directory => path,
 				Container::Tar => path.join("temp.tar"),
 				Container::Versatiles => path.join("temp.versatiles"),
+				Container::Zip => path.join("temp.zip"),
 			};
 
 			let registry = ContainerRegistry::new(ProcessingConfig::default());
 			registry.write_to_path(Box::new(reader1), &path).await?;
 
 			// get test container reader using the default registry (back-compat)
-			let mut reader2 = registry.get_reader_from_str(path.to_str().unwrap()).await?;
+			let mut reader2 = registry.open_reader(path.to_str().unwrap()).await?;
 			MockTilesWriter::write(reader2.as_mut()).await?;
 
 			Ok(())
 		}
 
-		let containers = vec![Container::Directory, Container::Tar, Container::Versatiles];
+		let containers = vec![
+			Container::Directory,
+			Container::Tar,
+			Container::Versatiles,
+			Container::Zip,
+		];
 
 		for container in containers {
 			test_writer_and_reader(&container, TileFormat::PNG, TileCompression::Uncompressed)?;
@@ -371,4 +817,298 @@ pub mod tests {
 
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn failed_write_removes_incomplete_file() -> Result<()> {
+		let mut registry = ContainerRegistry::default();
+		registry.register_writer_file("fail", |_r, p, _c| async move {
+			std::fs::write(&p, b"incomplete")?;
+			bail!("writer failed")
+		});
+
+		let dir = TempDir::new()?;
+		let path = dir.path().join("temp.fail");
+		let reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?;
+		let error = registry.write_to_path(reader.boxed(), &path).await.unwrap_err();
+		assert!(format!("{error:#}").contains("writer failed"), "{error:#}");
+		assert!(dir.path().exists());
+		assert!(!path.exists());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn strict_tilejson_rejects_invalid_metadata() -> Result<()> {
+		// vector tiles without 'vector_layers' violate TileJSON 3.0.0
+		let make_reader = || MemTilesReader::new(TileFormat::MVT, TileCompression::Uncompressed).boxed();
+		let dir = TempDir::new()?;
+		let path = dir.path().join("temp.ok");
+
+		let mut registry = ContainerRegistry::default();
+		registry.register_writer_file("ok", |_r, p, _c| async move { Ok(std::fs::write(&p, b"ok")?) });
+		registry.set_strict_tilejson(true);
+		let error = registry.write_to_path(make_reader(), &path).await.unwrap_err();
+		assert!(
+			format!("{error:#}").contains("vector tilesets must have 'vector_layers'"),
+			"{error:#}"
+		);
+		assert!(!path.exists());
+
+		registry.set_strict_tilejson(false);
+		registry.write_to_path(make_reader(), &path).await?;
+		assert!(path.exists());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn atomic_write() -> Result<()> {
+		let dir = TempDir::new()?;
+		let mut registry = ContainerRegistry::default();
+		registry.register_writer_file("ok", |_r, p, _c| async move {
+			assert!(p.ends_with("temp.ok.tmp"));
+			Ok(std::fs::write(&p, b"complete")?)
+		});
+		registry.register_writer_file("fail", |_r, p, _c| async move {
+			assert!(p.ends_with("temp.fail.tmp"));
+			std::fs::write(&p, b"incomplete")?;
+			bail!("writer failed")
+		});
+		let make_reader = || {
+			MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)
+				.unwrap()
+				.boxed()
+		};
+
+		// a failed write keeps the previous file
+		let path = dir.path().join("temp.fail");
+		std::fs::write(&path, b"previous")?;
+		assert!(registry.write_to_path(make_reader(), &path).await.is_err());
+		assert_eq!(std::fs::read(&path)?, b"previous");
+		assert!(!dir.path().join("temp.fail.tmp").exists());
+
+		// a successful write replaces the previous file
+		let path = dir.path().join("temp.ok");
+		std::fs::write(&path, b"previous")?;
+		registry.set_fsync(true);
+		registry.write_to_path(make_reader(), &path).await?;
+		assert_eq!(std::fs::read(&path)?, b"complete");
+		assert!(!dir.path().join("temp.ok.tmp").exists());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn direct_write() -> Result<()> {
+		let dir = TempDir::new()?;
+		let path = dir.path().join("temp.test");
+
+		let mut registry = ContainerRegistry::default();
+		registry.set_atomic_write(false);
+		registry.register_writer_file("test", |_r, p, _c| async move {
+			assert!(p.ends_with("temp.test"));
+			Ok(std::fs::write(&p, b"complete")?)
+		});
+		let reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?;
+		registry.write_to_path(reader.boxed(), &path).await?;
+		assert_eq!(std::fs::read(&path)?, b"complete");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn cancelled_write_produces_valid_container() -> Result<()> {
+		let config = ProcessingConfig::default();
+		config.cancellation_token.cancel();
+		let registry = ContainerRegistry::new(config);
+
+		let dir = TempDir::new()?;
+		let path = dir.path().join("temp.versatiles");
+		let reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?;
+		registry.write_to_path(reader.boxed(), &path).await?;
+
+		let reader = registry.open_reader(path.to_str().unwrap()).await?;
+		let count = reader
+			.get_tile_stream(TileBBox::new_full(3)?)
+			.await?
+			.drain_and_count()
+			.await;
+		assert_eq!(count, 0);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn aborted_write_removes_incomplete_file() -> Result<()> {
+		let config = ProcessingConfig::default();
+		config.abort_token.cancel_after(Duration::from_millis(50));
+		let mut registry = ContainerRegistry::new(config);
+		registry.register_writer_file("hang", |_r, p, _c| async move {
+			std::fs::write(&p, b"incomplete")?;
+			std::future::pending::<()>().await;
+			Ok(())
+		});
+
+		let dir = TempDir::new()?;
+		let path = dir.path().join("temp.hang");
+		let reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?;
+		let error = registry.write_to_path(reader.boxed(), &path).await.unwrap_err();
+		assert!(format!("{error:?}").contains("writing was aborted"), "{error:?}");
+		assert!(!path.exists());
+		assert!(!dir.path().join("temp.hang.tmp").exists());
+
+		Ok(())
+	}
+
+	/// A `Write` whose data stays accessible after it is moved into a `DataWriterStream`.
+	#[derive(Clone, Default)]
+	struct SharedBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+	impl std::io::Write for SharedBuffer {
+		fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+			self.0.lock().unwrap().extend_from_slice(buf);
+			Ok(buf.len())
+		}
+
+		fn flush(&mut self) -> std::io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[cfg(feature = "tar")]
+	#[tokio::test]
+	async fn write_to_stream() -> Result<()> {
+		let registry = ContainerRegistry::default();
+		let make_reader = || {
+			MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)
+				.unwrap()
+				.boxed()
+		};
+
+		let buffer = SharedBuffer::default();
+		let writer = Box::new(DataWriterStream::new(buffer.clone()));
+		registry.write_to_writer(make_reader(), "tar", writer).await?;
+
+		let file = NamedTempFile::new("stream.tar")?;
+		std::fs::write(&file, buffer.0.lock().unwrap().as_slice())?;
+		let reader = TarTilesReader::open_path(file.path())?;
+		assert_eq!(reader.parameters().tile_format, TileFormat::PNG);
+
+		let writer = Box::new(DataWriterStream::new(buffer.clone()));
+		let error = registry
+			.write_to_writer(make_reader(), "mbtiles", writer)
+			.await
+			.unwrap_err();
+		assert!(
+			format!("{error:#}").contains("writing 'mbtiles' containers into a stream is not supported"),
+			"{error:#}"
+		);
+
+		Ok(())
+	}
+
+	#[rstest::rstest]
+	#[case("-", Some("tar"))]
+	#[case("versatiles:-", Some("versatiles"))]
+	#[case("TAR:-", Some("tar"))]
+	#[case(":-", None)]
+	#[case("./-", None)]
+	#[case("tiles.tar", None)]
+	fn stdout_extensions(#[case] path: &str, #[case] extension: Option<&str>) {
+		assert_eq!(stdout_extension(Path::new(path)).as_deref(), extension);
+	}
+
+	#[rstest::rstest]
+	#[case("s3://bucket/tiles.versatiles", Some("s3"))]
+	#[case("HTTPS://example.org/tiles.pmtiles", Some("https"))]
+	#[case("dir://./tiles/", Some("dir"))]
+	#[case("file:///data/tiles.mbtiles", Some("file"))]
+	#[case("C://data/tiles.mbtiles", None)]
+	#[case("mbtiles:data.bin", None)]
+	#[case("./data/tiles.mbtiles", None)]
+	#[case("1x://data", None)]
+	fn uri_schemes(#[case] uri: &str, #[case] scheme: Option<&str>) {
+		assert_eq!(uri_scheme(uri).as_deref(), scheme);
+	}
+
+	#[test]
+	fn s3_urls() -> Result<()> {
+		assert_eq!(
+			s3_url("s3://bucket/a/tiles.versatiles", None)?.as_str(),
+			"https://bucket.s3.amazonaws.com/a/tiles.versatiles"
+		);
+		let endpoint = Url::parse("http://localhost:9000/")?;
+		assert_eq!(
+			s3_url("s3://bucket/tiles.pmtiles", Some(&endpoint))?.as_str(),
+			"http://localhost:9000/bucket/tiles.pmtiles"
+		);
+		assert!(s3_url("s3://bucket", None).is_err());
+		assert!(s3_url("s3:///tiles.pmtiles", None).is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn open_reader_by_uri() -> Result<()> {
+		let dir = TempDir::new()?;
+		let tiles_dir = dir.path().join("tiles");
+		std::fs::create_dir(&tiles_dir)?;
+		let registry = ContainerRegistry::default();
+		let reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?;
+		registry.write_to_path(reader.boxed(), &tiles_dir).await?;
+		let reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?;
+		let file = dir.path().join("tiles.versatiles");
+		registry.write_to_path(reader.boxed(), &file).await?;
+
+		let reader = registry.open_reader(&format!("file://{}", file.display())).await?;
+		assert_eq!(reader.container_name(), "versatiles");
+
+		let reader = registry.open_reader(&format!("dir://{}", tiles_dir.display())).await?;
+		assert_eq!(reader.container_name(), "directory");
+
+		let error = registry
+			.open_reader(&format!("dir://{}", file.display()))
+			.await
+			.unwrap_err();
+		assert!(format!("{error:?}").contains("is not a directory"));
+
+		let error = registry
+			.open_reader("ftp://example.org/tiles.versatiles")
+			.await
+			.unwrap_err();
+		assert!(format!("{error:?}").contains("URI scheme 'ftp' unknown"));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn register_reader_uri() -> Result<()> {
+		let mut registry = ContainerRegistry::default();
+		registry.register_reader_uri("mock", |uri, _registry| async move {
+			assert_eq!(uri, "mock://png");
+			Ok(MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?.boxed())
+		});
+
+		let reader = registry.open_reader("mock://png").await?;
+		assert_eq!(reader.container_name(), "dummy_container");
+
+		// URLs with a registered scheme are also resolved by `get_reader`
+		let reader = registry.get_reader(DataLocation::from("mock://png")).await?;
+		assert_eq!(reader.container_name(), "dummy_container");
+
+		Ok(())
+	}
+
+	#[test]
+	fn index_cache_names() -> Result<()> {
+		let name = index_cache_name(&Url::parse("https://example.org/tiles/osm.pmtiles")?);
+		// the name must not change, e.g. with a new Rust version
+		assert_eq!(name, "example.org_tiles_osm.pmtiles.275a840c6ed84722.cache");
+
+		// URLs that differ only in special characters or the query get different files
+		let other = index_cache_name(&Url::parse("https://example.org/tiles_osm.pmtiles")?);
+		assert_ne!(name, other);
+		let other = index_cache_name(&Url::parse("https://example.org/tiles/osm.pmtiles?v=2")?);
+		assert_ne!(name, other);
+		Ok(())
+	}
 }
@@ -1,7 +1,8 @@
 //! Converts tile data between formats, compressions, and coordinate conventions.
 //!
 //! This module provides:
-//! - [`TilesConverterParameters`]: declarative knobs (bbox filter, compression override, `flip_y`, `swap_xy`)
+//! - [`TilesConverterParameters`]: declarative knobs (bbox filter, compression override, `flip_y`, `swap_xy`, error policy,
+//!   tile timeout, modification time filter, tile size limit, coordinate list)
 //! - [`TilesConvertReader`]: an adapter that applies those conversions while reading
 //! - [`convert_tiles_container`]: a convenience function to convert and write to a target path using a [`ContainerRegistry`]
 //!
@@ -9,6 +10,32 @@
 //! - `flip_y`: inverts Y within the zoom level (useful to switch between TMS and XYZ-like schemes)
 //! - `swap_xy`: swaps X and Y (occasionally useful for sources with unconventional axis ordering)
 //!
+//! ## Broken tiles
+//! Tiles that fail to be read or recompressed abort the conversion by default. Set `tile_errors` to a
+//! [`TileErrorLog`] with another [`TileErrorPolicy`](crate::TileErrorPolicy) to skip them or replace them
+//! with empty tiles instead, and write the collected errors as a report afterwards.
+//!
+//! Set `tile_timeout` to limit the time spent reading a single tile, e.g. from a network backend.
+//! A tile that is not read in time fails with a [`TileTimeoutError`](crate::TileTimeoutError) and is
+//! handled by the error policy like any other read error. With a timeout, tiles are read one by one
+//! instead of as a stream, so that one hung tile can not stall the others.
+//!
+//! Tiles larger than `max_tile_size` after encoding fail with a [`TileTooLargeError`](crate::TileTooLargeError),
+//! which names the coordinate and the source, and are handled by the error policy as well. The limit defaults to
+//! [`DEFAULT_MAX_TILE_SIZE`](crate::DEFAULT_MAX_TILE_SIZE).
+//!
+//! ## Incremental conversions
+//! Set `newer_than` to a Unix timestamp to skip all tiles that were not modified after it, see
+//! [`Tile::mtime`]. Tiles without a known modification time are always kept, because they might have changed.
+//!
+//! ## Antimeridian
+//! A [`TileBBoxPyramid`] can't describe a bounding box that crosses the antimeridian, only one that also covers
+//! the tiles between its two parts. Set `bbox_parts` to the pyramids of both parts, see
+//! [`TileBBoxPyramid::split_geo_bbox`], to read only the tiles of the parts and limit the TileJSON bounds accordingly.
+//!
+//! Set `tile_list` to a [`TileCoordList`] to convert only the listed tiles, e.g. the tiles affected by an update of
+//! the source data. The coordinates refer to the output, i.e. after `flip_y` and `swap_xy`.
+//!
 //! ## Example
 //! ```rust
 //! use versatiles_container::*;
@@ -21,7 +48,7 @@
 //!     let registry = ContainerRegistry::default();
 //!
 //!     // Open the source
-//!     let reader = registry.get_reader_from_str("../testdata/berlin.mbtiles").await?;
+//!     let reader = registry.open_reader("../testdata/berlin.mbtiles").await?;
 //!
 //!     // Limit to a bbox pyramid and keep source compression;
 //!     // you could also set `tile_compression: Some(TileCompression::Brotli)` to re-encode.
@@ -39,14 +66,21 @@
 //! }
 //! ```
 
-use crate::{ContainerRegistry, Tile, TilesReaderTrait};
+use crate::{
+	ContainerRegistry, DEFAULT_MAX_TILE_SIZE, Tile, TileErrorLog, TileErrorPolicy, TilesReaderTrait, check_tile_size,
+	get_tile_with_timeout,
+};
 use anyhow::Result;
 use async_trait::async_trait;
-use std::path::Path;
+use futures::{StreamExt, future, stream};
+use std::{path::Path, sync::Arc, time::Duration};
 use versatiles_core::{
-	TileBBox, TileBBoxPyramid, TileCompression, TileCoord, TileJSON, TileStream, TilesReaderParameters, Traversal,
+	GeoBBox, TileBBox, TileBBoxPyramid, TileCompression, TileCoord, TileCoordList, TileFormat, TileJSON, TileStream,
+	TileType, TilesReaderParameters, Traversal, progress::ConversionMetrics,
 };
 use versatiles_derive::context;
+use versatiles_geometry::vector_tile::VectorTile;
+use versatiles_image::DynamicImage;
 
 /// Parameters that control how tiles are transformed during reading/conversion.
 ///
@@ -60,6 +94,9 @@ pub struct TilesConverterParameters {
 	/// Optional spatial/zoom restriction. When set, only tiles inside the given
 	/// [`TileBBoxPyramid`] are read/streamed. Existing bounds are intersected with this.
 	pub bbox_pyramid: Option<TileBBoxPyramid>,
+	/// Optional pyramids of the parts of a bounding box that crosses the antimeridian. When set, only tiles
+	/// inside one of them are read/streamed, instead of all tiles between them.
+	pub bbox_parts: Option<Vec<TileBBoxPyramid>>,
 	/// Optional compression override. When set, tile payloads are re-encoded to this
 	/// [`TileCompression`] (e.g., Gzip → Brotli). If `None`, the source compression is kept.
 	pub tile_compression: Option<TileCompression>,
@@ -67,16 +104,33 @@ pub struct TilesConverterParameters {
 	pub flip_y: bool,
 	/// If `true`, swap X and Y coordinates.
 	pub swap_xy: bool,
+	/// Policy for tiles that fail to be read or recompressed. Collects the errors of skipped tiles.
+	pub tile_errors: TileErrorLog,
+	/// Optional deadline for reading a single tile. Tiles that take longer fail and are handled by `tile_errors`.
+	pub tile_timeout: Option<Duration>,
+	/// Optional Unix timestamp in seconds. When set, tiles with a modification time at or before it are skipped.
+	pub newer_than: Option<u64>,
+	/// Optional maximum size of a single encoded tile in bytes. Larger tiles fail and are handled by `tile_errors`.
+	pub max_tile_size: Option<u64>,
+	/// Optional list of tiles. When set, only the listed tiles are read/streamed.
+	pub tile_list: Option<Arc<TileCoordList>>,
 }
 
 impl Default for TilesConverterParameters {
-	/// Returns parameters that perform no geometric change and keep source compression.
+	/// Returns parameters that perform no geometric change, keep source compression
+	/// and limit tiles to [`DEFAULT_MAX_TILE_SIZE`].
 	fn default() -> Self {
 		TilesConverterParameters {
 			bbox_pyramid: None,
+			bbox_parts: None,
 			tile_compression: None,
 			flip_y: false,
 			swap_xy: false,
+			tile_errors: TileErrorLog::default(),
+			tile_timeout: None,
+			newer_than: None,
+			max_tile_size: Some(DEFAULT_MAX_TILE_SIZE),
+			tile_list: None,
 		}
 	}
 }
@@ -84,6 +138,8 @@ impl Default for TilesConverterParameters {
 /// Converts tiles from the given reader and writes them to `path` via the provided [`ContainerRegistry`].
 ///
 /// The conversion is applied by wrapping `reader` in a [`TilesConvertReader`] configured by `cp`.
+/// It counts into a child of the registry's [`ConversionMetrics`], so the transcoded tiles logged at the end
+/// are those of this output, even if other conversions run at the same time.
 ///
 /// ### Arguments
 /// - `reader`: Source container reader.
@@ -102,10 +158,27 @@ pub async fn convert_tiles_container(
 	reader: Box<dyn TilesReaderTrait>,
 	cp: TilesConverterParameters,
 	path: &Path,
-	registry: ContainerRegistry,
+	mut registry: ContainerRegistry,
 ) -> Result<()> {
-	let converter = TilesConvertReader::new_from_reader(reader, cp)?;
-	registry.write_to_path(Box::new(converter), path).await
+	let tile_errors = cp.tile_errors.clone();
+	let metrics = registry.config().metrics.child();
+	registry.set_metrics(metrics.clone());
+	let mut converter = TilesConvertReader::new_from_reader(reader, cp)?;
+	converter.set_metrics(metrics.clone());
+	registry.write_to_path(Box::new(converter), path).await?;
+
+	let stats = metrics.snapshot().transcode;
+	if !stats.is_empty() {
+		log::info!("tiles {stats}");
+	}
+	if !tile_errors.is_empty() {
+		log::warn!(
+			"{} tiles failed and were handled with error policy '{}'",
+			tile_errors.len(),
+			tile_errors.policy()
+		);
+	}
+	Ok(())
 }
 
 /// Reader adapter that applies coordinate transforms, bbox filtering, and optional
@@ -122,6 +195,8 @@ pub struct TilesConvertReader {
 	container_name: String,
 	name: String,
 	tilejson: TileJSON,
+	fallback_tile: Option<Tile>,
+	metrics: ConversionMetrics,
 }
 
 impl TilesConvertReader {
@@ -135,7 +210,7 @@ impl TilesConvertReader {
 	#[context("Creating converter reader from existing reader")]
 	pub fn new_from_reader(
 		reader: Box<dyn TilesReaderTrait>,
-		cp: TilesConverterParameters,
+		mut cp: TilesConverterParameters,
 	) -> Result<TilesConvertReader> {
 		let container_name = format!("converter({})", reader.container_name());
 		let name = format!("converter({})", reader.source_name());
@@ -154,12 +229,38 @@ impl TilesConvertReader {
 			new_rp.bbox_pyramid.intersect(bbox_pyramid);
 		}
 
+		if let Some(tile_list) = &cp.tile_list {
+			new_rp.bbox_pyramid.intersect(tile_list.bbox_pyramid());
+		}
+
+		let mut bounds = None;
+		if let Some(parts) = &mut cp.bbox_parts {
+			let mut bbox_pyramid = TileBBoxPyramid::new_empty();
+			for part in parts.iter_mut() {
+				part.intersect(&new_rp.bbox_pyramid);
+				bbox_pyramid.include_bbox_pyramid(part);
+			}
+			new_rp.bbox_pyramid = bbox_pyramid;
+			bounds = parts_bounds(parts)?;
+		}
+
 		if let Some(tile_compression) = cp.tile_compression {
 			new_rp.tile_compression = tile_compression;
 		}
 
 		let mut tilejson = reader.tilejson().clone();
 		tilejson.update_from_reader_parameters(&new_rp);
+		if let Some(bounds) = bounds {
+			// the pyramid spans the whole width, so limit the bounds to the parts
+			tilejson.limit_bbox(bounds);
+		}
+
+		let fallback_tile = if cp.tile_errors.policy() == TileErrorPolicy::Fallback {
+			let tile_size = tilejson.tile_size.map_or(256, |size| u32::from(size.size()));
+			empty_tile(new_rp.tile_format, new_rp.tile_compression, tile_size)
+		} else {
+			None
+		};
 
 		Ok(TilesConvertReader {
 			reader,
@@ -168,8 +269,93 @@ impl TilesConvertReader {
 			container_name,
 			name,
 			tilejson,
+			fallback_tile,
+			metrics: ConversionMetrics::new(),
 		})
 	}
+
+	/// Sets the metrics that count the recompressed tiles, e.g. those of the running conversion.
+	pub fn set_metrics(&mut self, metrics: ConversionMetrics) {
+		self.metrics = metrics;
+	}
+}
+
+/// Returns the bounding box that crosses the antimeridian and covers both parts in the order of
+/// [`GeoBBox::split_antimeridian`], or `None` if not both parts contain tiles.
+fn parts_bounds(parts: &[TileBBoxPyramid]) -> Result<Option<GeoBBox>> {
+	let [first, second] = parts else {
+		return Ok(None);
+	};
+	let (Some(first), Some(second)) = (first.get_geo_bbox(), second.get_geo_bbox()) else {
+		return Ok(None);
+	};
+	Ok(Some(GeoBBox::new_wrapping(
+		first.x_min,
+		first.y_min.min(second.y_min),
+		second.x_max,
+		first.y_max.max(second.y_max),
+	)?))
+}
+
+/// Returns an encoded empty tile, used to replace broken tiles, or `None` if `format` has no empty representation.
+fn empty_tile(format: TileFormat, compression: TileCompression, tile_size: u32) -> Option<Tile> {
+	let tile = match format.to_type() {
+		TileType::Vector => Tile::from_vector(VectorTile::default(), format),
+		TileType::Raster => Tile::from_image(DynamicImage::new_rgba8(tile_size, tile_size), format),
+		TileType::Unknown => return None,
+	};
+	let mut tile = tile.ok()?;
+	tile.as_blob(compression).ok()?;
+	Some(tile)
+}
+
+/// Whether `tile` passes the `newer_than` filter. Tiles without a known modification time always pass.
+fn is_newer(tile: &Tile, newer_than: Option<u64>) -> bool {
+	match (newer_than, tile.mtime()) {
+		(Some(newer_than), Some(mtime)) => mtime > newer_than,
+		_ => true,
+	}
+}
+
+/// Recompresses `tile`, counts it in `metrics` and applies the error policy if that fails.
+fn encode_tile(
+	coord: &TileCoord,
+	mut tile: Tile,
+	compression: TileCompression,
+	tile_errors: &TileErrorLog,
+	fallback_tile: Option<&Tile>,
+	metrics: &ConversionMetrics,
+) -> Result<Option<Tile>> {
+	if tile.has_blob() {
+		metrics.count_compression(tile.compression() == compression);
+	}
+	let result = tile
+		.change_compression(compression)
+		.and_then(|()| tile.as_blob(compression).map(|_| ()));
+	Ok(match tile_errors.handle(coord, "encode", result)? {
+		Some(()) => Some(tile),
+		None => fallback_tile.cloned(),
+	})
+}
+
+/// Checks the encoded size of `tile` and applies the error policy if it exceeds `max_tile_size`.
+fn limit_tile_size(
+	coord: &TileCoord,
+	mut tile: Tile,
+	compression: TileCompression,
+	max_tile_size: Option<u64>,
+	source: &str,
+	tile_errors: &TileErrorLog,
+	fallback_tile: Option<&Tile>,
+) -> Result<Option<Tile>> {
+	let Some(max_tile_size) = max_tile_size else {
+		return Ok(Some(tile));
+	};
+	let result = check_tile_size(&mut tile, coord, compression, max_tile_size, source);
+	Ok(match tile_errors.handle(coord, "size", result)? {
+		Some(()) => Some(tile),
+		None => fallback_tile.cloned(),
+	})
 }
 
 #[async_trait]
@@ -199,6 +385,18 @@ impl TilesReaderTrait for TilesConvertReader {
 	}
 
 	async fn get_tile(&self, coord: &TileCoord) -> Result<Option<Tile>> {
+		if let Some(tile_list) = &self.converter_parameters.tile_list
+			&& !tile_list.contains(coord)
+		{
+			return Ok(None);
+		}
+
+		if let Some(parts) = &self.converter_parameters.bbox_parts
+			&& !parts.iter().any(|part| part.contains_coord(coord))
+		{
+			return Ok(None);
+		}
+
 		let mut coord = *coord;
 
 		if self.converter_parameters.flip_y {
@@ -209,18 +407,85 @@ impl TilesReaderTrait for TilesConvertReader {
 			coord.swap_xy();
 		}
 
-		let tile = self.reader.get_tile(&coord).await?;
+		let tile_errors = &self.converter_parameters.tile_errors;
+		let result = get_tile_with_timeout(self.reader.as_ref(), &coord, self.converter_parameters.tile_timeout).await;
+		let tile = match tile_errors.handle(&coord, "read", result)? {
+			Some(Some(tile)) => tile,
+			Some(None) => return Ok(None),
+			None => return Ok(self.fallback_tile.clone()),
+		};
 
-		let mut tile = if let Some(tile) = tile { tile } else { return Ok(None) };
+		if !is_newer(&tile, self.converter_parameters.newer_than) {
+			return Ok(None);
+		}
 
-		if let Some(compression) = self.converter_parameters.tile_compression {
-			tile.change_compression(compression)?;
+		let tile = match self.converter_parameters.tile_compression {
+			Some(compression) => encode_tile(
+				&coord,
+				tile,
+				compression,
+				tile_errors,
+				self.fallback_tile.as_ref(),
+				&self.metrics,
+			)?,
+			None => Some(tile),
+		};
+		let Some(tile) = tile else {
+			return Ok(None);
+		};
+
+		limit_tile_size(
+			&coord,
+			tile,
+			self.reader_parameters.tile_compression,
+			self.converter_parameters.max_tile_size,
+			self.reader.source_name(),
+			tile_errors,
+			self.fallback_tile.as_ref(),
+		)
+	}
+
+	async fn prefetch(&self, bbox: &TileBBox) -> Result<()> {
+		let mut bbox = *bbox;
+		if self.converter_parameters.swap_xy {
+			bbox.swap_xy();
+		}
+		if self.converter_parameters.flip_y {
+			bbox.flip_y();
 		}
+		self.reader.prefetch(&bbox).await
+	}
+
+	async fn get_tile_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		let Some(parts) = &self.converter_parameters.bbox_parts else {
+			return self.get_part_stream(bbox).await;
+		};
 
-		Ok(Some(tile))
+		// read the parts separately, so that the tiles between them are not read
+		let mut streams = Vec::new();
+		for part in parts {
+			let mut part_bbox = bbox;
+			part_bbox.intersect_with_pyramid(part);
+			if !part_bbox.is_empty() {
+				streams.push(self.get_part_stream(part_bbox).await?);
+			}
+		}
+		Ok(TileStream::from_streams(stream::iter(streams).map(future::ready)))
 	}
+}
+
+impl TilesConvertReader {
+	/// Streams the tiles of `bbox`, which must not include tiles between the `bbox_parts`.
+	async fn get_part_stream(&self, mut bbox: TileBBox) -> Result<TileStream<'_, Tile>> {
+		if self.converter_parameters.tile_timeout.is_some() {
+			// read tile by tile, so that every tile gets its own deadline
+			let coords: Vec<TileCoord> = bbox.iter_coords().collect();
+			return Ok(TileStream::from_coord_vec_async_parallel(
+				coords,
+				move |coord| async move { self.get_tile(&coord).await },
+			));
+		}
 
-	async fn get_tile_stream(&self, mut bbox: TileBBox) -> Result<TileStream<Tile>> {
 		if self.converter_parameters.swap_xy {
 			bbox.swap_xy();
 		}
@@ -230,6 +495,16 @@ impl TilesReaderTrait for TilesConvertReader {
 
 		let mut stream = self.reader.get_tile_stream(bbox).await?;
 
+		let newer_than = self.converter_parameters.newer_than;
+		if newer_than.is_some() {
+			stream = TileStream::from_stream(
+				stream
+					.inner
+					.filter(move |(_, tile)| std::future::ready(is_newer(tile, newer_than)))
+					.boxed(),
+			);
+		}
+
 		let flip_y = self.converter_parameters.flip_y;
 		let swap_xy = self.converter_parameters.swap_xy;
 
@@ -245,11 +520,46 @@ impl TilesReaderTrait for TilesConvertReader {
 			});
 		}
 
-		if let Some(tile_compression) = self.converter_parameters.tile_compression {
-			stream = stream.map_item_parallel(move |mut tile| {
-				tile.change_compression(tile_compression)?;
-				Ok(tile)
-			});
+		if let Some(tile_list) = &self.converter_parameters.tile_list {
+			let tile_list = tile_list.clone();
+			stream = stream.filter_coord(move |coord| std::future::ready(tile_list.contains(&coord)));
+		}
+
+		let tile_compression = self.converter_parameters.tile_compression;
+		let max_tile_size = self.converter_parameters.max_tile_size;
+		if tile_compression.is_some() || max_tile_size.is_some() {
+			let compression = self.reader_parameters.tile_compression;
+			let source = self.reader.source_name().to_string();
+			let tile_errors = self.converter_parameters.tile_errors.clone();
+			let fallback_tile = self.fallback_tile.clone();
+			let metrics = self.metrics.clone();
+			// pair every tile with its coordinate, so that errors can be reported per tile
+			stream = TileStream::from_stream(stream.inner.map(|(coord, tile)| (coord, (coord, tile))).boxed())
+				.filter_map_item_parallel(move |(coord, tile)| {
+					let tile = match tile_compression {
+						Some(compression) => encode_tile(
+							&coord,
+							tile,
+							compression,
+							&tile_errors,
+							fallback_tile.as_ref(),
+							&metrics,
+						)?,
+						None => Some(tile),
+					};
+					let Some(tile) = tile else {
+						return Ok(None);
+					};
+					limit_tile_size(
+						&coord,
+						tile,
+						compression,
+						max_tile_size,
+						&source,
+						&tile_errors,
+						fallback_tile.as_ref(),
+					)
+				});
 		}
 
 		Ok(stream)
@@ -261,9 +571,10 @@ impl TilesReaderTrait for TilesConvertReader {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::{MockTilesReader, VersaTilesReader};
+	use crate::{MemTilesReader, MockTilesReader, VersaTilesReader};
 	use assert_fs::NamedTempFile;
 	use versatiles_core::{
+		Blob,
 		TileCompression::*,
 		TileFormat::{self, *},
 	};
@@ -293,9 +604,15 @@ mod tests {
 
 			let cp = TilesConverterParameters {
 				bbox_pyramid: Some(pyramid_convert),
+				bbox_parts: None,
 				flip_y,
 				swap_xy,
 				tile_compression: None,
+				tile_errors: TileErrorLog::default(),
+				tile_timeout: None,
+				newer_than: None,
+				max_tile_size: None,
+				tile_list: None,
 			};
 			convert_tiles_container(reader.boxed(), cp, &temp_file, ContainerRegistry::default()).await?;
 
@@ -331,13 +648,54 @@ mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn bbox_parts_across_antimeridian() -> Result<()> {
+		let reader_parameters = TilesReaderParameters::new(JSON, Uncompressed, TileBBoxPyramid::new_full(4));
+		let reader = MockTilesReader::new_mock(reader_parameters)?;
+
+		let geo_bbox = GeoBBox::new_wrapping(170.0, -20.0, -170.0, -10.0)?;
+		let cp = TilesConverterParameters {
+			bbox_pyramid: Some(TileBBoxPyramid::new_full(4)),
+			bbox_parts: Some(TileBBoxPyramid::new_full(4).split_geo_bbox(&geo_bbox)?),
+			..Default::default()
+		};
+		let tcr = TilesConvertReader::new_from_reader(reader.boxed(), cp)?;
+		assert_eq!(
+			tcr.parameters().bbox_pyramid.get_level_bbox(4).as_array()?,
+			[0, 8, 15, 8]
+		);
+		let bounds = tcr.tilejson().bounds.unwrap();
+		assert!(bounds.crosses_antimeridian(), "{bounds:?}");
+
+		let mut coords = tcr
+			.get_tile_stream(TileBBox::new_full(4)?)
+			.await?
+			.to_vec()
+			.await
+			.into_iter()
+			.map(|(coord, _)| (coord.x, coord.y))
+			.collect::<Vec<_>>();
+		coords.sort();
+		assert_eq!(coords, [(0, 8), (15, 8)]);
+
+		assert!(tcr.get_tile(&TileCoord::new(4, 8, 8)?).await?.is_none());
+		assert!(tcr.get_tile(&TileCoord::new(4, 0, 8)?).await?.is_some());
+		Ok(())
+	}
+
 	#[test]
 	fn test_tiles_converter_parameters_new() {
 		let cp = TilesConverterParameters {
 			bbox_pyramid: Some(TileBBoxPyramid::new_full(1)),
+			bbox_parts: None,
 			flip_y: true,
 			swap_xy: true,
 			tile_compression: None,
+			tile_errors: TileErrorLog::default(),
+			tile_timeout: None,
+			newer_than: None,
+			max_tile_size: None,
+			tile_list: None,
 		};
 
 		assert!(cp.bbox_pyramid.is_some());
@@ -428,4 +786,224 @@ mod tests {
 
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn broken_tiles_follow_error_policy() -> Result<()> {
+		async fn convert(policy: TileErrorPolicy) -> Result<(Vec<(TileCoord, Tile)>, TileErrorLog)> {
+			let mut reader = MemTilesReader::new(MVT, Gzip);
+			reader.insert(TileCoord::new(1, 0, 0)?, Tile::from_vector(VectorTile::default(), MVT)?)?;
+			reader.insert(
+				TileCoord::new(1, 1, 0)?,
+				Tile::from_blob(Blob::from("not gzip"), Gzip, MVT),
+			)?;
+
+			let tile_errors = TileErrorLog::new(policy);
+			let cp = TilesConverterParameters {
+				tile_compression: Some(Brotli),
+				tile_errors: tile_errors.clone(),
+				..Default::default()
+			};
+			let tcr = TilesConvertReader::new_from_reader(reader.boxed(), cp)?;
+			let tiles = tcr.get_tile_stream(TileBBox::new_full(1)?).await?.to_vec().await;
+			Ok((tiles, tile_errors))
+		}
+
+		let (tiles, tile_errors) = convert(TileErrorPolicy::Skip).await?;
+		assert_eq!(tiles.len(), 1);
+		assert_eq!(tiles[0].0, TileCoord::new(1, 0, 0)?);
+		let errors = tile_errors.errors();
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0].coord, TileCoord::new(1, 1, 0)?);
+		assert_eq!(errors[0].stage, "encode");
+
+		let (mut tiles, tile_errors) = convert(TileErrorPolicy::Fallback).await?;
+		assert_eq!(tiles.len(), 2);
+		assert_eq!(tile_errors.len(), 1);
+		tiles.sort_by_key(|(coord, _)| coord.x);
+		assert_eq!(tiles[1].1.compression(), Brotli);
+		assert_eq!(tiles[1].1.clone().into_vector()?, VectorTile::default());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn hung_tiles_follow_error_policy() -> Result<()> {
+		use crate::{HangingReader, MockTilesReaderProfile, TileTimeoutError};
+		use std::time::Duration;
+
+		let reader = HangingReader(MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?);
+		let tile_errors = TileErrorLog::new(TileErrorPolicy::Skip);
+		let cp = TilesConverterParameters {
+			tile_errors: tile_errors.clone(),
+			tile_timeout: Some(Duration::from_millis(10)),
+			..Default::default()
+		};
+		let tcr = TilesConvertReader::new_from_reader(Box::new(reader), cp)?;
+
+		let coord = TileCoord::new(3, 1, 2)?;
+		assert!(tcr.get_tile(&coord).await?.is_none());
+		let bbox = TileBBox::from_min_and_max(3, 1, 2, 2, 3)?;
+		assert_eq!(tcr.get_tile_stream(bbox).await?.to_vec().await.len(), 0);
+
+		let errors = tile_errors.errors();
+		assert_eq!(errors.len(), 5);
+		assert_eq!(errors[0].stage, "read");
+		assert_eq!(
+			errors[0].message,
+			TileTimeoutError {
+				coord,
+				timeout: Duration::from_millis(10)
+			}
+			.to_string()
+		);
+
+		// the default policy fails
+		let reader = HangingReader(MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?);
+		let cp = TilesConverterParameters {
+			tile_timeout: Some(Duration::from_millis(10)),
+			..Default::default()
+		};
+		let tcr = TilesConvertReader::new_from_reader(Box::new(reader), cp)?;
+		assert!(tcr.get_tile(&coord).await.is_err());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn large_tiles_follow_error_policy() -> Result<()> {
+		use crate::TileTooLargeError;
+
+		let mut source = MemTilesReader::new(JSON, Uncompressed);
+		for (x, size) in [(0, 10), (1, 1000)] {
+			let tile = Tile::from_blob(Blob::from(vec![b' '; size]), Uncompressed, JSON);
+			source.insert(TileCoord::new(3, x, 0)?, tile)?;
+		}
+		let tile_errors = TileErrorLog::new(TileErrorPolicy::Skip);
+		let cp = TilesConverterParameters {
+			tile_errors: tile_errors.clone(),
+			max_tile_size: Some(100),
+			..Default::default()
+		};
+		let tcr = TilesConvertReader::new_from_reader(source.boxed(), cp)?;
+
+		assert!(tcr.get_tile(&TileCoord::new(3, 0, 0)?).await?.is_some());
+		assert!(tcr.get_tile(&TileCoord::new(3, 1, 0)?).await?.is_none());
+		let bbox = TileBBox::from_min_and_max(3, 0, 0, 1, 0)?;
+		let tiles = tcr.get_tile_stream(bbox).await?.to_vec().await;
+		assert_eq!(tiles.len(), 1);
+		assert_eq!(tiles[0].0, TileCoord::new(3, 0, 0)?);
+
+		let errors = tile_errors.errors();
+		assert_eq!(errors.len(), 2);
+		assert_eq!(errors[0].stage, "size");
+		assert_eq!(
+			errors[0].message,
+			TileTooLargeError {
+				coord: TileCoord::new(3, 1, 0)?,
+				source: tcr.reader.source_name().to_string(),
+				size: 1000,
+				max_size: 100,
+			}
+			.to_string()
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn newer_than_filters_tiles() -> Result<()> {
+		let mut source = MemTilesReader::new(JSON, Uncompressed);
+		for (x, mtime) in [(0, Some(100)), (1, Some(200)), (2, None)] {
+			let mut tile = Tile::from_blob(Blob::from("{}"), Uncompressed, JSON);
+			tile.set_mtime(mtime);
+			source.insert(TileCoord::new(3, x, 0)?, tile)?;
+		}
+		let cp = TilesConverterParameters {
+			newer_than: Some(100),
+			tile_compression: Some(Gzip),
+			..Default::default()
+		};
+		let tcr = TilesConvertReader::new_from_reader(source.boxed(), cp)?;
+
+		assert!(tcr.get_tile(&TileCoord::new(3, 0, 0)?).await?.is_none());
+		assert!(tcr.get_tile(&TileCoord::new(3, 1, 0)?).await?.is_some());
+		assert!(tcr.get_tile(&TileCoord::new(3, 2, 0)?).await?.is_some());
+
+		let bbox = TileBBox::from_min_and_max(3, 0, 0, 2, 0)?;
+		let mut xs: Vec<u32> = tcr
+			.get_tile_stream(bbox)
+			.await?
+			.to_vec()
+			.await
+			.into_iter()
+			.map(|(coord, tile)| {
+				assert_eq!(tile.compression(), Gzip);
+				coord.x
+			})
+			.collect();
+		xs.sort();
+		assert_eq!(xs, vec![1, 2]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn tile_list_filters_tiles() -> Result<()> {
+		let tile_list = TileCoordList::from_iter([TileCoord::new(3, 1, 0)?, TileCoord::new(3, 2, 6)?]);
+		let cp = TilesConverterParameters {
+			flip_y: true,
+			tile_list: Some(Arc::new(tile_list)),
+			..Default::default()
+		};
+		let tcr = TilesConvertReader::new_from_reader(get_mock_reader(MVT, Uncompressed).boxed(), cp)?;
+		let pyramid = &tcr.parameters().bbox_pyramid;
+		assert_eq!(pyramid.get_level_min(), Some(3));
+		assert_eq!(pyramid.get_level_max(), Some(3));
+		assert_eq!(pyramid.get_level_bbox(3).as_array()?, [1, 0, 2, 6]);
+
+		assert!(tcr.get_tile(&TileCoord::new(3, 1, 0)?).await?.is_some());
+		assert!(tcr.get_tile(&TileCoord::new(3, 1, 1)?).await?.is_none());
+
+		let mut coords: Vec<(u32, u32)> = tcr
+			.get_tile_stream(TileBBox::new_full(3)?)
+			.await?
+			.to_vec()
+			.await
+			.into_iter()
+			.map(|(coord, _)| (coord.x, coord.y))
+			.collect();
+		coords.sort();
+		assert_eq!(coords, [(1, 0), (2, 6)]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn concurrent_conversions_count_separately() -> Result<()> {
+		async fn convert(compression: TileCompression) -> Result<ConversionMetrics> {
+			let config = crate::ProcessingConfig::default();
+			let metrics = config.metrics.clone();
+			let cp = TilesConverterParameters {
+				bbox_pyramid: Some(TileBBoxPyramid::new_full(2)),
+				tile_compression: Some(compression),
+				..Default::default()
+			};
+			let temp_file = NamedTempFile::new("test.versatiles")?;
+			let reader = get_mock_reader(MVT, Gzip).boxed();
+			convert_tiles_container(reader, cp, &temp_file, ContainerRegistry::new(config)).await?;
+			Ok(metrics)
+		}
+
+		let (gzip, brotli) = futures::try_join!(convert(Gzip), convert(Brotli))?;
+
+		// 1 + 4 + 16 tiles each
+		let gzip = gzip.snapshot();
+		assert_eq!(gzip.tiles_done(), 21);
+		assert_eq!(gzip.transcode.compression_passed, 21);
+		assert_eq!(gzip.transcode.compression_converted, 0);
+
+		let brotli = brotli.snapshot();
+		assert_eq!(brotli.tiles_done(), 21);
+		assert_eq!(brotli.transcode.compression_passed, 0);
+		assert_eq!(brotli.transcode.compression_converted, 21);
+		assert!(brotli.bytes_written > 0);
+		Ok(())
+	}
 }
@@ -251,6 +251,10 @@ impl From<String> for DataLocation {
 
 impl From<&str> for DataLocation {
 	fn from(s: &str) -> Self {
+		// `file:///absolute/path` or `file://./relative/path`
+		if let Some(path) = s.strip_prefix("file://") {
+			return DataLocation::Path(PathBuf::from(path));
+		}
 		if let Ok(url) = reqwest::Url::parse(s)
 			&& url.has_host()
 		{
@@ -350,6 +354,14 @@ mod tests {
 		Ok(())
 	}
 
+	#[rstest]
+	#[case("file:///data/tiles.mbtiles", "/data/tiles.mbtiles")]
+	#[case("file://./tiles.mbtiles", "./tiles.mbtiles")]
+	fn file_uri_becomes_path(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+		assert_eq!(DataLocation::from(input).as_path()?, Path::new(expected));
+		Ok(())
+	}
+
 	#[rstest]
 	#[case("../a/b", "../x/y.z", "../a/x/y.z")]
 	#[case("../a/b", "./x/y.z", "../a/b/x/y.z")]
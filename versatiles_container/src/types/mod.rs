@@ -3,8 +3,14 @@ mod converter;
 mod data_location;
 mod data_source;
 mod processing_config;
+mod reader_limits;
 mod tile;
+mod tile_codec_pool;
 mod tile_content;
+mod tile_encode_cache;
+mod tile_errors;
+mod tile_size_limit;
+mod tile_timeout;
 mod tiles_reader;
 mod writer;
 
@@ -13,7 +19,13 @@ pub use converter::*;
 pub use data_location::*;
 pub use data_source::*;
 pub use processing_config::*;
+pub use reader_limits::*;
 pub use tile::*;
+pub use tile_codec_pool::*;
 pub use tile_content::*;
+pub use tile_encode_cache::*;
+pub use tile_errors::*;
+pub use tile_size_limit::*;
+pub use tile_timeout::*;
 pub use tiles_reader::*;
 pub use writer::*;
@@ -3,17 +3,21 @@
 //! It encapsulates configuration that affects how data is read, processed, and cached.
 //! The most important field is `cache_type`, which controls whether an in-memory cache
 //! or another cache backend is used by various data readers and writers.
+//! The `cancellation_token` allows long-running conversions to be stopped cooperatively,
+//! the `abort_token` makes them fail without leaving incomplete files behind,
+//! and `metrics` collects the progress and counters of a single conversion.
 //!
 //! The configuration is usually cloned or wrapped in an [`Arc`](std::sync::Arc)
 //! to share it safely between async tasks and threads.
 
 use crate::CacheType;
 use std::sync::Arc;
+use versatiles_core::{CancellationToken, progress::ConversionMetrics};
 
 /// Configuration parameters controlling data processing behavior.
 ///
-/// Currently only the cache backend is configurable, but this struct is designed
-/// to be extended with more runtime parameters (e.g., parallelism limits,
+/// Currently the cache backend, the cancellation and abort tokens and the metrics are configurable, but this struct
+/// is designed to be extended with more runtime parameters (e.g., parallelism limits,
 /// I/O buffer sizes, or tile transformation options).
 ///
 /// Typical usage:
@@ -26,6 +30,18 @@ use std::sync::Arc;
 pub struct ProcessingConfig {
 	/// The type of cache backend to use for tile data.
 	pub cache_type: CacheType,
+	/// Token that stops tile streams when cancelled, e.g. on Ctrl-C.
+	///
+	/// Writers receive truncated streams and finish with the tiles read so far.
+	pub cancellation_token: CancellationToken,
+	/// Token that aborts writing, e.g. on a second Ctrl-C.
+	///
+	/// Unlike `cancellation_token`, writing fails and incomplete output files are removed.
+	pub abort_token: CancellationToken,
+	/// Metrics of the conversion, e.g. tiles per zoom level, bytes written and transcoded tiles.
+	///
+	/// Clones share the counters, so all readers and writers of a conversion count into the same metrics.
+	pub metrics: ConversionMetrics,
 }
 
 impl ProcessingConfig {
@@ -42,11 +58,14 @@ impl ProcessingConfig {
 
 /// Provides a reasonable default configuration.
 ///
-/// Uses an in-memory cache backend by default.
+/// Uses an in-memory cache backend, fresh, uncancelled tokens and empty metrics by default.
 impl Default for ProcessingConfig {
 	fn default() -> Self {
 		Self {
 			cache_type: CacheType::new_memory(),
+			cancellation_token: CancellationToken::new(),
+			abort_token: CancellationToken::new(),
+			metrics: ConversionMetrics::new(),
 		}
 	}
 }
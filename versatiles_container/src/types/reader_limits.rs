@@ -0,0 +1,158 @@
+//! Resource limits for readers of untrusted container files.
+//!
+//! A crafted `*.pmtiles` or `*.versatiles` file can declare huge directories, deeply nested
+//! leaf directories or compressed data that expands to gigabytes. [`ReaderLimits`] caps all of
+//! these, so such a file results in an error instead of exhausting memory or looping forever.
+//! Violations are reported as [`ContainerError`], which can be recovered from an
+//! [`anyhow::Error`] with `downcast_ref`.
+//!
+//! ```
+//! use versatiles_container::{ContainerError, ReaderLimits};
+//!
+//! let limits = ReaderLimits {
+//!     max_decompressed_size: 16 * 1024 * 1024,
+//!     ..ReaderLimits::default()
+//! };
+//!
+//! let error = anyhow::Error::new(ContainerError::TooManyEntries { count: 20, max: 10 });
+//! assert!(matches!(
+//!     error.downcast_ref::<ContainerError>(),
+//!     Some(ContainerError::TooManyEntries { .. })
+//! ));
+//! ```
+
+use std::fmt;
+use versatiles_core::ByteRange;
+
+/// Limits that readers enforce while parsing container files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReaderLimits {
+	/// Maximum nesting depth of directories, counting the root directory.
+	pub max_directory_depth: u8,
+	/// Maximum number of entries in a single directory or index.
+	pub max_directory_entries: u64,
+	/// Maximum number of bytes read at once, e.g. for metadata, directories, indexes or single tiles.
+	pub max_read_size: u64,
+	/// Maximum size of decompressed metadata, directories or indexes in bytes.
+	pub max_decompressed_size: u64,
+}
+
+/// Defaults that accept every regular planet-scale file.
+impl Default for ReaderLimits {
+	fn default() -> Self {
+		Self {
+			max_directory_depth: 3,
+			max_directory_entries: 100_000_000,
+			max_read_size: 1024 * 1024 * 1024,
+			max_decompressed_size: 1024 * 1024 * 1024,
+		}
+	}
+}
+
+/// Errors for container files that are malformed or exceed the [`ReaderLimits`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContainerError {
+	/// Directories are nested deeper than allowed.
+	DirectoryTooDeep { max_depth: u8 },
+	/// A directory or index has more entries than allowed.
+	TooManyEntries { count: u64, max: u64 },
+	/// A byte range that has to be read at once is larger than allowed.
+	RangeTooLarge { range: ByteRange, max: u64 },
+	/// The file is inconsistent, e.g. an offset overflows or an index has the wrong length.
+	Malformed(String),
+}
+
+impl fmt::Display for ContainerError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ContainerError::DirectoryTooDeep { max_depth } => {
+				write!(f, "directories are nested deeper than {max_depth} levels")
+			}
+			ContainerError::TooManyEntries { count, max } => {
+				write!(f, "directory has {count} entries, but only {max} are allowed")
+			}
+			ContainerError::RangeTooLarge { range, max } => {
+				write!(f, "byte range {range} is larger than the limit of {max} bytes")
+			}
+			ContainerError::Malformed(message) => write!(f, "malformed container: {message}"),
+		}
+	}
+}
+
+impl std::error::Error for ContainerError {}
+
+impl ReaderLimits {
+	/// Checks that `range` can be read at once.
+	///
+	/// # Errors
+	/// Returns [`ContainerError::RangeTooLarge`] if the range exceeds `max_read_size`.
+	pub fn check_range(&self, range: &ByteRange) -> Result<(), ContainerError> {
+		if range.length > self.max_read_size {
+			return Err(ContainerError::RangeTooLarge {
+				range: *range,
+				max: self.max_read_size,
+			});
+		}
+		Ok(())
+	}
+
+	/// Checks the number of entries of a directory or index.
+	///
+	/// # Errors
+	/// Returns [`ContainerError::TooManyEntries`] if `count` exceeds `max_directory_entries`.
+	pub fn check_entries(&self, count: u64) -> Result<(), ContainerError> {
+		if count > self.max_directory_entries {
+			return Err(ContainerError::TooManyEntries {
+				count,
+				max: self.max_directory_entries,
+			});
+		}
+		Ok(())
+	}
+
+	/// Checks the nesting `depth` of a directory, where the root directory has depth 0.
+	///
+	/// # Errors
+	/// Returns [`ContainerError::DirectoryTooDeep`] if `depth` reaches `max_directory_depth`.
+	pub fn check_depth(&self, depth: u8) -> Result<(), ContainerError> {
+		if depth >= self.max_directory_depth {
+			return Err(ContainerError::DirectoryTooDeep {
+				max_depth: self.max_directory_depth,
+			});
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn checks() {
+		let limits = ReaderLimits {
+			max_directory_depth: 2,
+			max_directory_entries: 10,
+			max_read_size: 100,
+			max_decompressed_size: 1000,
+		};
+
+		assert!(limits.check_depth(1).is_ok());
+		assert_eq!(
+			limits.check_depth(2),
+			Err(ContainerError::DirectoryTooDeep { max_depth: 2 })
+		);
+
+		assert!(limits.check_entries(10).is_ok());
+		assert_eq!(
+			limits.check_entries(11),
+			Err(ContainerError::TooManyEntries { count: 11, max: 10 })
+		);
+
+		assert!(limits.check_range(&ByteRange::new(u64::MAX - 100, 100)).is_ok());
+		assert_eq!(
+			limits.check_range(&ByteRange::new(0, 101)).unwrap_err().to_string(),
+			"byte range [0..=100] is larger than the limit of 100 bytes"
+		);
+	}
+}
@@ -7,7 +7,8 @@
 //!
 //! The type keeps track of the tile **format** (e.g. `PNG`, `MVT`) and the transport
 //! **compression** (e.g. `Gzip`, `Uncompressed`). Quality and speed hints are stored
-//! for formats that support them and are applied when (re-)encoding.
+//! for formats that support them and are applied when (re-)encoding. An optional
+//! modification time (**mtime**) is passed through unchanged, e.g. for incremental syncs.
 //!
 //! All expensive conversions are performed lazily and are wrapped with contextual error
 //! messages via the `#[context(...)]` attribute from `versatiles_derive`.
@@ -15,18 +16,23 @@
 //! Typical usage:
 //! - Build from an image/vector and retrieve a blob ready to send over the wire.
 //! - Build from a blob received from storage, then inspect or mutate the decoded content.
+//! - Raster images are always decoded and encoded on the [`TileCodecPool`](crate::TileCodecPool);
+//!   in async code, use `into_blob_pooled` to also keep the async worker threads free.
 
 use anyhow::{Result, anyhow, ensure};
-use std::{fmt::Debug, io::Cursor};
+use std::{
+	fmt::Debug,
+	io::{Cursor, Write},
+};
 use versatiles_core::{
 	Blob, TileCompression, TileFormat,
-	utils::{decompress_ref, recompress},
+	utils::{decompress_ref, recompress, recompress_to_writer},
 };
 use versatiles_derive::context;
 use versatiles_geometry::vector_tile::VectorTile;
 use versatiles_image::DynamicImage;
 
-use crate::{CacheValue, TileContent};
+use crate::{CacheValue, TileCodecPool, TileContent};
 
 /// A lazy tile container that can hold either an encoded blob or decoded content.
 ///
@@ -62,6 +68,7 @@ pub struct Tile {
 	compression: TileCompression,
 	format_quality: Option<u8>,
 	format_speed: Option<u8>,
+	mtime: Option<u64>,
 }
 
 /// Constructors and lazy accessors for `Tile`.
@@ -81,6 +88,7 @@ impl Tile {
 			compression,
 			format_quality: None,
 			format_speed: None,
+			mtime: None,
 		}
 	}
 
@@ -92,6 +100,7 @@ impl Tile {
 			compression: TileCompression::Uncompressed,
 			format_quality: None,
 			format_speed: None,
+			mtime: None,
 		}
 	}
 
@@ -124,26 +133,11 @@ impl Tile {
 		Ok(())
 	}
 
-	#[context("decompressing blob ({:?})", self.compression)]
-	fn decompress_blob(&mut self) -> Result<()> {
-		assert!(self.blob.is_some());
-		if self.compression != TileCompression::Uncompressed {
-			self.blob = Some(decompress_ref(self.blob.as_ref().unwrap(), self.compression)?);
-			self.compression = TileCompression::Uncompressed;
-		}
-		Ok(())
-	}
-
 	#[cfg(test)]
 	pub(super) fn __recompress_blob_for_test(&mut self, compression: TileCompression) {
 		self.recompress_blob(compression).unwrap();
 	}
 
-	#[cfg(test)]
-	pub(super) fn __decompress_blob_for_test(&mut self) {
-		self.decompress_blob().unwrap();
-	}
-
 	fn delete_blob(&mut self) {
 		self.blob = None;
 		self.compression = TileCompression::Uncompressed;
@@ -167,8 +161,14 @@ impl Tile {
 	fn materialize_content(&mut self) -> Result<()> {
 		if self.content.is_none() {
 			ensure!(self.blob.is_some(), "Cannot materialize content without blob");
-			self.decompress_blob()?;
-			self.content = Some(TileContent::from_blob(self.blob.as_ref().unwrap(), self.format)?);
+			// Decode from a decompressed copy, so the original blob can still be written
+			// without re-compression as long as the content is not mutated.
+			let blob = self.blob.as_ref().unwrap();
+			self.content = Some(if self.compression == TileCompression::Uncompressed {
+				TileContent::from_blob(blob, self.format)?
+			} else {
+				TileContent::from_blob(&decompress_ref(blob, self.compression)?, self.format)?
+			});
 		}
 		Ok(())
 	}
@@ -187,6 +187,17 @@ impl Tile {
 		self.blob.as_ref().ok_or(anyhow!("blob should be present"))
 	}
 
+	#[context("writing blob (target_compression={:?})", compression)]
+	/// Write the encoded blob with the requested compression into `output`.
+	///
+	/// Like [`as_blob`](Self::as_blob), but the (re-)compressed data is streamed into `output`
+	/// instead of being stored in the tile, so large tiles are not held in memory twice.
+	pub fn write_blob_to(&mut self, compression: TileCompression, output: &mut dyn Write) -> Result<()> {
+		self.materialize_blob()?;
+		let blob = self.blob.as_ref().ok_or(anyhow!("blob should be present"))?;
+		recompress_to_writer(blob.as_slice(), self.compression, compression, output)
+	}
+
 	#[context("accessing tile content")]
 	fn as_content(&mut self) -> Result<&TileContent> {
 		self.materialize_content()?;
@@ -219,6 +230,17 @@ impl Tile {
 		Ok(self.blob.unwrap())
 	}
 
+	/// Like [`into_blob`](Self::into_blob), but encodes and (re-)compresses on the [`TileCodecPool`],
+	/// so that async worker threads are not blocked.
+	///
+	/// A blob that already has the requested compression is returned directly.
+	pub async fn into_blob_pooled(self, compression: TileCompression) -> Result<Blob> {
+		if self.blob.is_some() && self.compression == compression {
+			return self.into_blob(compression);
+		}
+		TileCodecPool::run(move || self.into_blob(compression)).await?
+	}
+
 	#[context("converting tile into content")]
 	fn into_content(mut self) -> Result<TileContent> {
 		self.materialize_content()?;
@@ -272,6 +294,27 @@ impl Tile {
 	pub fn compression(&self) -> TileCompression {
 		self.compression
 	}
+	/// Return the modification time in seconds since the Unix epoch, if the source knows it.
+	pub fn mtime(&self) -> Option<u64> {
+		self.mtime
+	}
+	/// Set the modification time in seconds since the Unix epoch.
+	///
+	/// The time is kept when the content, format or compression change.
+	pub fn set_mtime(&mut self, mtime: Option<u64>) {
+		self.mtime = mtime;
+	}
+
+	/// Whether the tile already has `format` and the hints would not change, i.e. whether
+	/// [`Tile::change_format`] passes it through untouched.
+	///
+	/// Callers use this to count format changes in their
+	/// [`ConversionMetrics`](versatiles_core::progress::ConversionMetrics).
+	pub fn has_format(&self, format: TileFormat, quality: Option<u8>, speed: Option<u8>) -> bool {
+		let same_quality = quality.is_none() || quality == self.format_quality;
+		let same_speed = speed.is_none() || speed == self.format_speed;
+		format == self.format && same_quality && same_speed
+	}
 
 	#[context("changing format: {:?} -> {:?} (q={:?}, s={:?})", self.format, format, quality, speed)]
 	/// Change the tile's **format** (e.g., `PNG` → `WEBP`) while preserving the content type.
@@ -280,9 +323,17 @@ impl Tile {
 	/// `quality`/`speed` hints are updated if provided (passed as `Some`).
 	/// Passing `None` keeps the previous hint value.
 	///
+	/// If the tile already has `format` and the hints would not change, the tile is passed
+	/// through untouched, without decoding or re-encoding it.
+	///
 	/// The `format` must have the same type (raster vs. vector) as the current format.
 	pub fn change_format(&mut self, format: TileFormat, quality: Option<u8>, speed: Option<u8>) -> Result<()> {
 		assert_eq!(format.to_type(), self.format.to_type());
+
+		if self.has_format(format, quality, speed) {
+			return Ok(());
+		}
+
 		self.materialize_content()?;
 		self.delete_blob();
 		self.compression = TileCompression::Uncompressed;
@@ -320,6 +371,22 @@ impl Tile {
 	pub fn has_content(&self) -> bool {
 		self.content.is_some()
 	}
+
+	/// Whether the tile has already been decoded, i.e. accessing its image or vector data is free.
+	///
+	/// Same as [`Tile::has_content`].
+	pub fn is_decoded(&self) -> bool {
+		self.has_content()
+	}
+
+	/// Decode the blob now instead of on first access, e.g. to move the work to a worker thread.
+	///
+	/// The blob is kept, so an unmodified tile can still be written without re-encoding.
+	/// Does nothing if the tile is already decoded.
+	#[context("decoding tile (format={:?})", self.format)]
+	pub fn ensure_decoded(&mut self) -> Result<()> {
+		self.materialize_content()
+	}
 }
 
 impl Debug for Tile {
@@ -342,6 +409,7 @@ impl CacheValue for Tile {
 		self.compression.write_to_cache(writer)?;
 		self.format_quality.write_to_cache(writer)?;
 		self.format_speed.write_to_cache(writer)?;
+		self.mtime.write_to_cache(writer)?;
 		Ok(())
 	}
 
@@ -353,6 +421,7 @@ impl CacheValue for Tile {
 		let compression = TileCompression::read_from_cache(reader)?;
 		let format_quality = Option::<u8>::read_from_cache(reader)?;
 		let format_speed = Option::<u8>::read_from_cache(reader)?;
+		let mtime = Option::<u64>::read_from_cache(reader)?;
 		Ok(Tile {
 			blob,
 			content,
@@ -360,8 +429,15 @@ impl CacheValue for Tile {
 			compression,
 			format_quality,
 			format_speed,
+			mtime,
 		})
 	}
+
+	fn memory_size(&self) -> usize {
+		size_of::<Self>()
+			+ self.blob.as_ref().map_or(0, |blob| blob.len() as usize)
+			+ self.content.as_ref().map_or(0, |content| content.memory_size())
+	}
 }
 
 #[cfg(test)]
@@ -404,20 +480,6 @@ mod tests {
 		Ok(())
 	}
 
-	#[test]
-	fn decompress_blob_noop_when_uncompressed() -> Result<()> {
-		let mut tile = Tile::from_image(tiny_rgb_image(), PNG)?;
-		let before = tile.as_blob(Uncompressed)?.clone();
-		assert_eq!(tile.compression(), Uncompressed);
-
-		// call the private method via test wrapper; nothing should change
-		tile.__decompress_blob_for_test();
-		assert_eq!(tile.compression(), Uncompressed);
-		let after = tile.as_blob(Uncompressed)?.clone();
-		assert_eq!(before, after);
-		Ok(())
-	}
-
 	#[test]
 	fn from_image_then_materialize_blob() -> Result<()> {
 		let img = tiny_rgb_image();
@@ -436,6 +498,34 @@ mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn into_blob_pooled_matches_into_blob() -> Result<()> {
+		let expected = Tile::from_image(tiny_rgb_image(), PNG)?.into_blob(Gzip)?;
+
+		let before = TileCodecPool::stats();
+		let blob = Tile::from_image(tiny_rgb_image(), PNG)?.into_blob_pooled(Gzip).await?;
+		assert_eq!(blob, expected);
+		assert!(TileCodecPool::stats().completed > before.completed);
+
+		// blobs with the right compression are returned without using the pool
+		let tile = Tile::from_blob(expected.clone(), Gzip, PNG);
+		assert_eq!(tile.into_blob_pooled(Gzip).await?, expected);
+		Ok(())
+	}
+
+	#[test]
+	fn raster_decode_and_encode_use_the_pool() -> Result<()> {
+		let blob = Tile::from_image(tiny_rgb_image(), PNG)?.into_blob(Uncompressed)?;
+
+		let before = TileCodecPool::stats();
+		let mut tile = Tile::from_blob(blob, Uncompressed, PNG);
+		tile.as_image_mut()?;
+		tile.as_blob(Uncompressed)?;
+		// other tests may use the pool in parallel
+		assert!(TileCodecPool::stats().completed - before.completed >= 2);
+		Ok(())
+	}
+
 	#[test]
 	fn as_content_mut_deletes_blob() -> Result<()> {
 		let mut tile = Tile::from_image(tiny_rgb_image(), PNG)?;
@@ -470,6 +560,27 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn change_format_passes_through_same_format() -> Result<()> {
+		let blob = Tile::from_image(tiny_rgb_image(), PNG)?.into_blob(Gzip)?;
+		let mut tile = Tile::from_blob(blob.clone(), Gzip, PNG);
+
+		assert!(tile.has_format(PNG, None, None));
+		assert!(!tile.has_format(PNG, Some(50), None));
+		assert!(!tile.has_format(WEBP, None, None));
+		tile.change_format(PNG, None, None)?;
+		assert!(tile.has_blob());
+		assert!(!tile.has_content());
+		assert_eq!(tile.compression(), Gzip);
+		assert_eq!(tile.as_blob(Gzip)?, &blob);
+
+		// a new quality hint forces re-encoding
+		tile.change_format(PNG, Some(50), None)?;
+		assert!(!tile.has_blob());
+		assert!(tile.has_content());
+		Ok(())
+	}
+
 	#[test]
 	fn cachevalue_roundtrip_preserves_fields() -> Result<()> {
 		let img = tiny_rgb_image();
@@ -518,6 +629,26 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn decoding_keeps_compressed_blob_until_mutation() -> Result<()> {
+		let blob = Tile::from_image(tiny_rgb_image(), PNG)?.into_blob(Gzip)?;
+		let mut tile = Tile::from_blob(blob.clone(), Gzip, PNG);
+		assert!(!tile.is_decoded());
+
+		tile.ensure_decoded()?;
+		assert!(tile.is_decoded());
+		assert_eq!(tile.compression(), Gzip);
+		assert_eq!(tile.as_image()?.dimensions(), (2, 2));
+		// reading does not touch the encoded bytes
+		assert_eq!(tile.as_blob(Gzip)?, &blob);
+
+		tile.as_image_mut()?.put_pixel(0, 0, [1, 2, 3, 255].into());
+		assert!(tile.is_decoded());
+		assert!(!tile.has_blob());
+		assert_ne!(tile.as_blob(Gzip)?, &blob);
+		Ok(())
+	}
+
 	#[test]
 	fn as_blob_is_deterministic_when_unchanged() -> Result<()> {
 		let mut tile = Tile::from_image(tiny_rgb_image(), PNG)?;
@@ -527,6 +658,20 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn write_blob_to_matches_as_blob() -> Result<()> {
+		let mut tile = Tile::from_image(tiny_rgb_image(), PNG)?;
+		let mut output = Vec::new();
+		tile.write_blob_to(Gzip, &mut output)?;
+		// the tile itself keeps its uncompressed blob
+		assert_eq!(tile.compression(), Uncompressed);
+		assert_eq!(
+			decompress_ref(&Blob::from(output), Gzip)?,
+			tile.as_blob(Uncompressed)?.clone()
+		);
+		Ok(())
+	}
+
 	#[test]
 	fn cache_roundtrip_with_blob_only() -> Result<()> {
 		// Create a blob-only tile using from_blob
@@ -684,6 +829,22 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn mtime_survives_changes_and_cache() -> Result<()> {
+		let mut tile = Tile::from_image(tiny_rgb_image(), PNG)?;
+		assert_eq!(tile.mtime(), None);
+		tile.set_mtime(Some(1_700_000_000));
+		tile.change_format(WEBP, None, None)?;
+		tile.change_compression(Gzip)?;
+		assert_eq!(tile.mtime(), Some(1_700_000_000));
+
+		let mut buf = Vec::new();
+		tile.write_to_cache(&mut buf)?;
+		let decoded = Tile::read_from_cache(&mut Cursor::new(buf.as_slice()))?;
+		assert_eq!(decoded.mtime(), Some(1_700_000_000));
+		Ok(())
+	}
+
 	#[test]
 	fn debug_shows_core_fields_for_raster_content_only() -> Result<()> {
 		let tile = Tile::from_image(tiny_rgb_image(), PNG)?;
@@ -0,0 +1,275 @@
+//! A bounded pool of blocking threads for encoding and decoding tiles.
+//!
+//! Encoding an image as PNG, WebP or AVIF can take many milliseconds. Running it directly on an
+//! async worker thread blocks every other request handled by that thread, so under load a server
+//! would stop answering even cheap requests. [`TileCodecPool::run`] moves such work onto tokio's
+//! blocking threads (`tokio::task::spawn_blocking`) and limits how many jobs run at the same time.
+//! Further jobs wait in a queue without occupying a thread.
+//!
+//! Code that already runs on a blocking thread, e.g. a pipeline operation in
+//! `TileStream::map_item_parallel`, uses [`TileCodecPool::run_blocking`] instead. [`TileContent`](crate::TileContent)
+//! does so for every raster decode and encode, so all image work shares the same limit and counters.
+//! Jobs started inside another job run immediately and do not need a second slot.
+//!
+//! The size defaults to the number of CPUs and can be changed with [`TileCodecPool::set_size`].
+//! Jobs that are already running finish under the old limit.
+//!
+//! The pool keeps process-wide counters, see [`TileCodecPool::stats`].
+//!
+//! ```rust
+//! use versatiles_container::*;
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     TileCodecPool::set_size(2);
+//!     let sum = TileCodecPool::run(|| (1..=100u32).sum::<u32>()).await?;
+//!     assert_eq!(sum, 5050);
+//!
+//!     let stats = TileCodecPool::stats();
+//!     assert_eq!(stats.size, 2);
+//!     assert!(stats.completed >= 1);
+//!     Ok(())
+//! }
+//! ```
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use std::{
+	cell::Cell,
+	fmt::Display,
+	sync::{
+		Arc, RwLock,
+		atomic::{AtomicU64, Ordering},
+	},
+	time::{Duration, Instant},
+};
+use tokio::sync::Semaphore;
+
+lazy_static! {
+	static ref POOL: RwLock<(usize, Arc<Semaphore>)> = {
+		let size = num_cpus::get().max(1);
+		RwLock::new((size, Arc::new(Semaphore::new(size))))
+	};
+}
+
+static QUEUED: AtomicU64 = AtomicU64::new(0);
+static RUNNING: AtomicU64 = AtomicU64::new(0);
+static COMPLETED: AtomicU64 = AtomicU64::new(0);
+static BUSY_MICROS: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+	/// Whether the current thread is running a job of the pool.
+	static IN_JOB: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs CPU-heavy tile encoding and decoding on a limited number of blocking threads.
+pub struct TileCodecPool;
+
+impl TileCodecPool {
+	/// Sets the maximum number of jobs running at the same time. `0` is treated as `1`.
+	pub fn set_size(size: usize) {
+		let size = size.max(1);
+		let mut pool = POOL.write().unwrap();
+		if pool.0 != size {
+			*pool = (size, Arc::new(Semaphore::new(size)));
+		}
+	}
+
+	/// Returns the maximum number of jobs running at the same time.
+	#[must_use]
+	pub fn size() -> usize {
+		POOL.read().unwrap().0
+	}
+
+	/// Runs `job` on a blocking thread as soon as the pool has capacity and returns its result.
+	///
+	/// If the returned future is dropped while `job` is running, `job` still runs to completion.
+	pub async fn run<T, F>(job: F) -> Result<T>
+	where
+		T: Send + 'static,
+		F: FnOnce() -> T + Send + 'static,
+	{
+		let semaphore = POOL.read().unwrap().1.clone();
+
+		let queued = Gauge::new(&QUEUED);
+		let permit = semaphore.acquire_owned().await?;
+		drop(queued);
+
+		let result = tokio::task::spawn_blocking(move || {
+			let result = execute(job);
+			drop(permit);
+			result
+		})
+		.await?;
+		Ok(result)
+	}
+
+	/// Runs `job` on the current thread as soon as the pool has capacity and returns its result.
+	///
+	/// Blocks the thread while waiting, so call it only from blocking threads or synchronous code.
+	/// Inside another job of the pool, `job` runs immediately.
+	pub fn run_blocking<T>(job: impl FnOnce() -> T) -> Result<T> {
+		if IN_JOB.get() {
+			return Ok(job());
+		}
+		let semaphore = POOL.read().unwrap().1.clone();
+
+		let queued = Gauge::new(&QUEUED);
+		// inside a tokio task, an exhausted cooperative budget would leave the wake-up to the runtime we are blocking
+		let permit = futures::executor::block_on(tokio::task::unconstrained(semaphore.acquire_owned()))?;
+		drop(queued);
+
+		let result = execute(job);
+		drop(permit);
+		Ok(result)
+	}
+
+	/// Returns the current size and counters of the pool.
+	#[must_use]
+	pub fn stats() -> TileCodecPoolStats {
+		TileCodecPoolStats {
+			size: Self::size(),
+			queued: QUEUED.load(Ordering::Relaxed),
+			running: RUNNING.load(Ordering::Relaxed),
+			completed: COMPLETED.load(Ordering::Relaxed),
+			busy: Duration::from_micros(BUSY_MICROS.load(Ordering::Relaxed)),
+		}
+	}
+}
+
+/// Size and counters of the [`TileCodecPool`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TileCodecPoolStats {
+	/// Maximum number of jobs running at the same time.
+	pub size: usize,
+	/// Jobs waiting for a free slot.
+	pub queued: u64,
+	/// Jobs running right now.
+	pub running: u64,
+	/// Jobs finished since the process started.
+	pub completed: u64,
+	/// Total time spent running jobs since the process started.
+	pub busy: Duration,
+}
+
+impl Display for TileCodecPoolStats {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"codec pool: size {}, {} queued, {} running, {} completed, {:.3}s busy",
+			self.size,
+			self.queued,
+			self.running,
+			self.completed,
+			self.busy.as_secs_f64()
+		)
+	}
+}
+
+/// Runs `job` as a job of the pool and counts it.
+fn execute<T>(job: impl FnOnce() -> T) -> T {
+	let running = Gauge::new(&RUNNING);
+	IN_JOB.set(true);
+	let start = Instant::now();
+	let result = job();
+	BUSY_MICROS.fetch_add(start.elapsed().as_micros() as u64, Ordering::Relaxed);
+	COMPLETED.fetch_add(1, Ordering::Relaxed);
+	IN_JOB.set(false);
+	drop(running);
+	result
+}
+
+/// Increments a counter while it is alive, so that cancelled jobs are not counted forever.
+struct Gauge(&'static AtomicU64);
+
+impl Gauge {
+	fn new(counter: &'static AtomicU64) -> Gauge {
+		counter.fetch_add(1, Ordering::Relaxed);
+		Gauge(counter)
+	}
+}
+
+impl Drop for Gauge {
+	fn drop(&mut self) {
+		self.0.fetch_sub(1, Ordering::Relaxed);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::AtomicUsize;
+
+	#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+	async fn limits_concurrent_jobs() -> Result<()> {
+		// the pool is global, so this is the only test that changes its size
+		TileCodecPool::set_size(2);
+		assert_eq!(TileCodecPool::size(), 2);
+
+		static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+		static MAX_ACTIVE: AtomicUsize = AtomicUsize::new(0);
+		let before = TileCodecPool::stats();
+
+		let jobs = (0..8).map(|i| {
+			TileCodecPool::run(move || {
+				let active = ACTIVE.fetch_add(1, Ordering::SeqCst) + 1;
+				MAX_ACTIVE.fetch_max(active, Ordering::SeqCst);
+				std::thread::sleep(Duration::from_millis(20));
+				ACTIVE.fetch_sub(1, Ordering::SeqCst);
+				i * 2
+			})
+		});
+		let results = futures::future::try_join_all(jobs).await?;
+
+		assert_eq!(results, vec![0, 2, 4, 6, 8, 10, 12, 14]);
+		// other tests may use the pool in parallel, so only an upper bound can be checked
+		assert!(MAX_ACTIVE.load(Ordering::SeqCst) <= 2);
+
+		let stats = TileCodecPool::stats();
+		assert!(stats.completed - before.completed >= 8);
+		assert!(stats.busy - before.busy >= Duration::from_millis(160));
+		Ok(())
+	}
+
+	#[test]
+	fn nested_jobs_run_immediately() -> Result<()> {
+		let before = TileCodecPool::stats();
+		let result = TileCodecPool::run_blocking(|| TileCodecPool::run_blocking(|| 21 * 2))??;
+		assert_eq!(result, 42);
+		assert!(TileCodecPool::stats().completed - before.completed >= 1);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn blocking_jobs_inside_a_task() -> Result<()> {
+		// more jobs than tokio's cooperative budget allows per task
+		for i in 0..1000 {
+			assert_eq!(TileCodecPool::run_blocking(|| i)?, i);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn display() {
+		let stats = TileCodecPoolStats {
+			size: 4,
+			queued: 3,
+			running: 2,
+			completed: 1,
+			busy: Duration::from_millis(1500),
+		};
+		assert_eq!(
+			stats.to_string(),
+			"codec pool: size 4, 3 queued, 2 running, 1 completed, 1.500s busy"
+		);
+	}
+
+	#[test]
+	fn gauge_decrements_on_drop() {
+		static COUNTER: AtomicU64 = AtomicU64::new(0);
+		let gauge = Gauge::new(&COUNTER);
+		assert_eq!(COUNTER.load(Ordering::Relaxed), 1);
+		drop(gauge);
+		assert_eq!(COUNTER.load(Ordering::Relaxed), 0);
+	}
+}
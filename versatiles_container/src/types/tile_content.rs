@@ -7,8 +7,9 @@
 //! This enum is used internally by higher-level types (like `Tile`) to lazily convert
 //! between an encoded blob and a decoded content representation. Expensive conversions
 //! are wrapped with contextual error messages via the `#[context(...)]` attribute.
+//! Raster images are decoded and encoded on the [`TileCodecPool`](crate::TileCodecPool).
 
-use crate::CacheValue;
+use crate::{CacheValue, TileCodecPool};
 use anyhow::{Result, bail};
 use byteorder::{ReadBytesExt, WriteBytesExt};
 use std::io::Cursor;
@@ -47,7 +48,7 @@ impl TileContent {
 	#[context("converting tile to blob: format={:?}, q={:?}, s={:?}", format, quality, speed)]
 	pub fn to_blob(&self, format: TileFormat, quality: Option<u8>, speed: Option<u8>) -> Result<Blob> {
 		match self {
-			TileContent::Raster(image) => image.to_blob(format, quality, speed),
+			TileContent::Raster(image) => TileCodecPool::run_blocking(|| image.to_blob(format, quality, speed))?,
 			TileContent::Vector(vector) => vector.to_blob(),
 		}
 	}
@@ -60,7 +61,7 @@ impl TileContent {
 	pub fn from_blob(blob: &Blob, format: TileFormat) -> Result<Self> {
 		Ok(match format.to_type() {
 			TileType::Raster => {
-				let image = DynamicImage::from_blob(blob, format)?;
+				let image = TileCodecPool::run_blocking(|| DynamicImage::from_blob(blob, format))??;
 				TileContent::Raster(image)
 			}
 			TileType::Vector => {
@@ -171,4 +172,12 @@ impl CacheValue for TileContent {
 			_ => bail!("Unknown TileContent type identifier: {content_type}"),
 		}
 	}
+
+	/// Counts the pixel data of raster content. Vector content is not estimated.
+	fn memory_size(&self) -> usize {
+		match self {
+			TileContent::Raster(image) => image.memory_size(),
+			TileContent::Vector(_) => size_of::<Self>(),
+		}
+	}
 }
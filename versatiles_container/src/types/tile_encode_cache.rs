@@ -0,0 +1,212 @@
+//! Cache for encoded tiles, keyed by a hash of the tile content.
+//!
+//! Many tile sets contain lots of identical tiles, e.g. empty ocean or land tiles. When such tiles
+//! are converted to another format, each of them would be decoded and encoded again.
+//! [`TileEncodeCache::change_format`] hashes the input tile and reuses the encoded result of an
+//! identical tile that was converted before, so each unique input is encoded only once.
+//!
+//! The key consists of a hash of the input (blob bytes, or image pixels for decoded raster tiles),
+//! the input format and compression, and the target format, quality and speed.
+//! Decoded vector tiles are not cached.
+//!
+//! Format changes and cache hits are counted in the [`ConversionMetrics`] given to [`TileEncodeCache::new`].
+//!
+//! ```rust
+//! use versatiles_container::*;
+//! use versatiles_core::*;
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let cache = TileEncodeCache::new(1024, progress::ConversionMetrics::new());
+//! let image = versatiles_image::DynamicImage::new_rgb8(4, 4);
+//!
+//! for _ in 0..3 {
+//!     let mut tile = Tile::from_image(image.clone(), TileFormat::PNG)?;
+//!     cache.change_format(&mut tile, TileFormat::WEBP, Some(80), None)?;
+//!     assert_eq!(tile.format(), TileFormat::WEBP);
+//! }
+//! assert_eq!(cache.hit_count(), 2);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::Tile;
+use anyhow::Result;
+use std::{
+	hash::{DefaultHasher, Hash, Hasher},
+	mem::size_of,
+	sync::{
+		Mutex,
+		atomic::{AtomicU64, Ordering},
+	},
+};
+use versatiles_core::{Blob, LimitedCache, TileCompression, TileFormat, progress::ConversionMetrics};
+use versatiles_derive::context;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct EncodeKey {
+	content_hash: u64,
+	format: u8,
+	quality: Option<u8>,
+	speed: Option<u8>,
+}
+
+/// Thread-safe cache of encoded tile blobs, keyed by input content and encoding parameters.
+pub struct TileEncodeCache {
+	cache: Mutex<LimitedCache<EncodeKey, Blob>>,
+	hits: AtomicU64,
+	metrics: ConversionMetrics,
+}
+
+impl TileEncodeCache {
+	/// Creates a cache that keeps up to `max_entries` encoded tiles and counts into `metrics`.
+	#[must_use]
+	pub fn new(max_entries: usize, metrics: ConversionMetrics) -> TileEncodeCache {
+		let entry_size = size_of::<EncodeKey>() + size_of::<Blob>();
+		TileEncodeCache {
+			cache: Mutex::new(LimitedCache::with_maximum_size(max_entries.max(1) * entry_size)),
+			hits: AtomicU64::new(0),
+			metrics,
+		}
+	}
+
+	/// Like [`Tile::change_format`], but reuses the result if an identical tile was encoded before.
+	///
+	/// On a cache miss the tile is encoded immediately, so the result can be stored.
+	/// After the call the tile holds an uncompressed blob in `format`.
+	#[context("changing tile format with encode cache: {:?} -> {:?}", tile.format(), format)]
+	pub fn change_format(
+		&self,
+		tile: &mut Tile,
+		format: TileFormat,
+		quality: Option<u8>,
+		speed: Option<u8>,
+	) -> Result<()> {
+		self.metrics.count_format(tile.has_format(format, quality, speed));
+		if format == tile.format() && quality.is_none() && speed.is_none() {
+			// passed through without encoding, nothing to cache
+			return tile.change_format(format, quality, speed);
+		}
+
+		let Some(content_hash) = content_hash(tile)? else {
+			return tile.change_format(format, quality, speed);
+		};
+
+		let key = EncodeKey {
+			content_hash,
+			format: format as u8,
+			quality,
+			speed,
+		};
+
+		if let Some(blob) = self.cache.lock().unwrap().get(&key) {
+			self.hits.fetch_add(1, Ordering::Relaxed);
+			self.metrics.add_cache_hit();
+			*tile = Tile::from_blob(blob, TileCompression::Uncompressed, format);
+			return Ok(());
+		}
+
+		tile.change_format(format, quality, speed)?;
+		let blob = tile.as_blob(TileCompression::Uncompressed)?.clone();
+		self.cache.lock().unwrap().add(key, blob);
+		Ok(())
+	}
+
+	/// Number of tiles that were served from the cache.
+	#[must_use]
+	pub fn hit_count(&self) -> u64 {
+		self.hits.load(Ordering::Relaxed)
+	}
+}
+
+impl std::fmt::Debug for TileEncodeCache {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("TileEncodeCache")
+			.field("hits", &self.hit_count())
+			.finish()
+	}
+}
+
+/// Hashes the input of an encoding step. Returns `None` for decoded vector tiles.
+fn content_hash(tile: &mut Tile) -> Result<Option<u64>> {
+	let mut hasher = DefaultHasher::new();
+	(tile.format() as u8).hash(&mut hasher);
+
+	if tile.has_blob() {
+		let compression = tile.compression();
+		(compression as u8).hash(&mut hasher);
+		tile.as_blob(compression)?.as_slice().hash(&mut hasher);
+	} else if tile.format().to_type().is_raster() {
+		let image = tile.as_image()?;
+		(image.width(), image.height()).hash(&mut hasher);
+		let color = image.color();
+		(color.bytes_per_pixel(), color.has_alpha()).hash(&mut hasher);
+		image.as_bytes().hash(&mut hasher);
+	} else {
+		return Ok(None);
+	}
+
+	Ok(Some(hasher.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_image::DynamicImage;
+
+	fn png_tile(value: u8) -> Tile {
+		let image = DynamicImage::ImageRgb8(versatiles_image::ImageBuffer::from_pixel(
+			8,
+			8,
+			versatiles_image::Rgb([value, value, value]),
+		));
+		Tile::from_image(image, TileFormat::PNG).unwrap()
+	}
+
+	#[test]
+	fn identical_tiles_are_encoded_once() -> Result<()> {
+		let cache = TileEncodeCache::new(16, ConversionMetrics::new());
+
+		let mut first = png_tile(10);
+		cache.change_format(&mut first, TileFormat::WEBP, Some(90), None)?;
+		assert_eq!(cache.hit_count(), 0);
+
+		let mut second = png_tile(10);
+		cache.change_format(&mut second, TileFormat::WEBP, Some(90), None)?;
+		assert_eq!(cache.hit_count(), 1);
+		assert_eq!(second.format(), TileFormat::WEBP);
+		assert_eq!(
+			first.as_blob(TileCompression::Uncompressed)?,
+			second.as_blob(TileCompression::Uncompressed)?
+		);
+
+		// different content, quality or input representation are separate entries
+		cache.change_format(&mut png_tile(11), TileFormat::WEBP, Some(90), None)?;
+		cache.change_format(&mut png_tile(10), TileFormat::WEBP, Some(80), None)?;
+		let blob = png_tile(10).into_blob(TileCompression::Gzip)?;
+		let mut from_blob = Tile::from_blob(blob, TileCompression::Gzip, TileFormat::PNG);
+		cache.change_format(&mut from_blob, TileFormat::WEBP, Some(90), None)?;
+		assert_eq!(cache.hit_count(), 1);
+		Ok(())
+	}
+
+	#[test]
+	fn blob_tiles_are_cached() -> Result<()> {
+		let metrics = ConversionMetrics::new();
+		let cache = TileEncodeCache::new(16, metrics.clone());
+		let blob = png_tile(200).into_blob(TileCompression::Uncompressed)?;
+
+		for i in 0..3 {
+			let mut tile = Tile::from_blob(blob.clone(), TileCompression::Uncompressed, TileFormat::PNG);
+			cache.change_format(&mut tile, TileFormat::JPG, Some(70), None)?;
+			assert_eq!(tile.format(), TileFormat::JPG);
+			// only the first tile had to be decoded
+			assert_eq!(tile.has_content(), i == 0);
+		}
+		assert_eq!(cache.hit_count(), 2);
+
+		let snapshot = metrics.snapshot();
+		assert_eq!(snapshot.cache_hits, 2);
+		assert_eq!(snapshot.transcode.format_converted, 3);
+		Ok(())
+	}
+}
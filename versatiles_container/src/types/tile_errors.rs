@@ -0,0 +1,240 @@
+//! Handling of tiles that fail to process during a conversion.
+//!
+//! By default a single broken tile aborts the whole conversion. For large jobs it is often better to
+//! finish the conversion and investigate the broken tiles afterwards. [`TileErrorPolicy`] selects what
+//! happens to a failing tile, and [`TileErrorLog`] collects every failure, so that it can be written as
+//! a machine-readable report at the end:
+//!
+//! ```rust
+//! use versatiles_container::*;
+//! use versatiles_core::TileCoord;
+//!
+//! let log = TileErrorLog::new(TileErrorPolicy::Skip);
+//! let coord = TileCoord::new(3, 1, 2).unwrap();
+//!
+//! let result: anyhow::Result<u8> = Err(anyhow::anyhow!("broken gzip stream"));
+//! assert_eq!(log.handle(&coord, "encode", result).unwrap(), None);
+//!
+//! assert_eq!(log.len(), 1);
+//! assert_eq!(
+//!     log.to_ndjson(),
+//!     "{\"error\":\"broken gzip stream\",\"stage\":\"encode\",\"x\":1,\"y\":2,\"z\":3}\n"
+//! );
+//! ```
+
+use anyhow::{Context, Result, bail};
+use std::{
+	fmt,
+	path::Path,
+	str::FromStr,
+	sync::{Arc, Mutex},
+};
+use versatiles_core::{TileCoord, json::JsonObject};
+
+/// What to do with a tile that fails to process.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TileErrorPolicy {
+	/// Abort the conversion. This is the default.
+	#[default]
+	Fail,
+	/// Record the error and leave the tile out.
+	Skip,
+	/// Record the error and write an empty tile instead, if the tile format allows it. Otherwise leave the tile out.
+	Fallback,
+}
+
+impl FromStr for TileErrorPolicy {
+	type Err = anyhow::Error;
+
+	fn from_str(value: &str) -> Result<Self> {
+		Ok(match value.to_lowercase().trim() {
+			"fail" => TileErrorPolicy::Fail,
+			"skip" => TileErrorPolicy::Skip,
+			"fallback" => TileErrorPolicy::Fallback,
+			_ => bail!("unknown error policy {value:?}, expected one of: fail, skip, fallback"),
+		})
+	}
+}
+
+impl fmt::Display for TileErrorPolicy {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			TileErrorPolicy::Fail => "fail",
+			TileErrorPolicy::Skip => "skip",
+			TileErrorPolicy::Fallback => "fallback",
+		})
+	}
+}
+
+/// A single tile that failed to process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileError {
+	/// Coordinate of the tile.
+	pub coord: TileCoord,
+	/// Processing step that failed, e.g. `"read"` or `"encode"`.
+	pub stage: String,
+	/// The error, including all its causes.
+	pub message: String,
+}
+
+/// Applies a [`TileErrorPolicy`] and collects all tile errors.
+///
+/// Clones share the same list of errors, so a log can be handed to parallel workers.
+#[derive(Clone, Debug, Default)]
+pub struct TileErrorLog {
+	policy: TileErrorPolicy,
+	errors: Arc<Mutex<Vec<TileError>>>,
+}
+
+impl TileErrorLog {
+	/// Creates an empty log with the given policy.
+	#[must_use]
+	pub fn new(policy: TileErrorPolicy) -> Self {
+		Self {
+			policy,
+			errors: Arc::default(),
+		}
+	}
+
+	/// Returns the policy of this log.
+	#[must_use]
+	pub fn policy(&self) -> TileErrorPolicy {
+		self.policy
+	}
+
+	/// Records a failed tile and logs it as a warning.
+	pub fn record(&self, coord: &TileCoord, stage: &str, error: &anyhow::Error) {
+		let message = format!("{error:#}");
+		log::warn!("{stage} of tile {coord:?} failed: {message}");
+		self.errors.lock().unwrap().push(TileError {
+			coord: *coord,
+			stage: stage.to_string(),
+			message,
+		});
+	}
+
+	/// Applies the policy to the `result` of processing stage `stage` of the tile at `coord`.
+	///
+	/// Returns `Ok(Some(value))` on success and `Ok(None)` if the error was recorded and the tile should be
+	/// skipped or replaced.
+	///
+	/// # Errors
+	/// With [`TileErrorPolicy::Fail`] the error is returned with the coordinate and stage as context.
+	pub fn handle<T>(&self, coord: &TileCoord, stage: &str, result: Result<T>) -> Result<Option<T>> {
+		match result {
+			Ok(value) => Ok(Some(value)),
+			Err(error) if self.policy == TileErrorPolicy::Fail => {
+				Err(error.context(format!("{stage} of tile {coord:?} failed")))
+			}
+			Err(error) => {
+				self.record(coord, stage, &error);
+				Ok(None)
+			}
+		}
+	}
+
+	/// Returns the number of recorded errors.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.errors.lock().unwrap().len()
+	}
+
+	/// Returns `true` if no error was recorded.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns all recorded errors, sorted by coordinate.
+	#[must_use]
+	pub fn errors(&self) -> Vec<TileError> {
+		let mut errors = self.errors.lock().unwrap().clone();
+		errors.sort_by_key(|e| (e.coord.level, e.coord.y, e.coord.x));
+		errors
+	}
+
+	/// Returns the report as newline-delimited JSON, one object with `z`, `x`, `y`, `stage` and `error` per line.
+	#[must_use]
+	pub fn to_ndjson(&self) -> String {
+		self
+			.errors()
+			.iter()
+			.map(|e| {
+				let mut object = JsonObject::new();
+				object.set("z", e.coord.level);
+				object.set("x", e.coord.x);
+				object.set("y", e.coord.y);
+				object.set("stage", e.stage.as_str());
+				object.set("error", e.message.as_str());
+				object.stringify() + "\n"
+			})
+			.collect()
+	}
+
+	/// Writes the report as newline-delimited JSON to `path`.
+	///
+	/// # Errors
+	/// Returns an error if the file can not be written.
+	pub fn write_report(&self, path: &Path) -> Result<()> {
+		std::fs::write(path, self.to_ndjson())
+			.with_context(|| format!("Failed to write tile error report to {path:?}"))?;
+		log::info!("wrote {} tile errors to {path:?}", self.len());
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::anyhow;
+
+	fn coord(z: u8, x: u32, y: u32) -> TileCoord {
+		TileCoord::new(z, x, y).unwrap()
+	}
+
+	#[test]
+	fn parse_policy() {
+		assert_eq!("fail".parse::<TileErrorPolicy>().unwrap(), TileErrorPolicy::Fail);
+		assert_eq!("Skip".parse::<TileErrorPolicy>().unwrap(), TileErrorPolicy::Skip);
+		assert_eq!(
+			"fallback".parse::<TileErrorPolicy>().unwrap(),
+			TileErrorPolicy::Fallback
+		);
+		assert!("ignore".parse::<TileErrorPolicy>().is_err());
+		assert_eq!(TileErrorPolicy::Fallback.to_string(), "fallback");
+	}
+
+	#[test]
+	fn fail_returns_error() {
+		let log = TileErrorLog::default();
+		let error = log
+			.handle::<()>(&coord(1, 0, 1), "read", Err(anyhow!("boom")))
+			.unwrap_err();
+		assert_eq!(format!("{error:#}"), "read of tile TileCoord(1, [0, 1]) failed: boom");
+		assert!(log.is_empty());
+		assert_eq!(log.handle(&coord(1, 0, 1), "read", Ok(5)).unwrap(), Some(5));
+	}
+
+	#[test]
+	fn skip_records_errors() {
+		let log = TileErrorLog::new(TileErrorPolicy::Skip);
+		let shared = log.clone();
+		assert_eq!(
+			shared
+				.handle::<()>(&coord(2, 3, 1), "encode", Err(anyhow!("b")))
+				.unwrap(),
+			None
+		);
+		assert_eq!(
+			shared.handle::<()>(&coord(2, 1, 0), "read", Err(anyhow!("a"))).unwrap(),
+			None
+		);
+
+		assert_eq!(log.len(), 2);
+		assert_eq!(log.errors()[0].coord, coord(2, 1, 0));
+		assert_eq!(
+			log.to_ndjson(),
+			"{\"error\":\"a\",\"stage\":\"read\",\"x\":1,\"y\":0,\"z\":2}\n{\"error\":\"b\",\"stage\":\"encode\",\"x\":3,\"y\":1,\"z\":2}\n"
+		);
+	}
+}
@@ -0,0 +1,106 @@
+//! Size limit for single tiles.
+//!
+//! A broken source or a pipeline operation gone wrong can produce absurdly large tiles, e.g. a vector tile
+//! at a low zoom level that contains every feature of a planet. [`check_tile_size`] rejects such tiles with a
+//! [`TileTooLargeError`] that names the coordinate and the source, so one bad tile does not balloon the memory
+//! of a whole conversion. The error is handled like any other tile error, e.g. by a [`TileErrorLog`](crate::TileErrorLog).
+//!
+//! ```rust
+//! use versatiles_container::*;
+//! use versatiles_core::{Blob, TileCompression, TileCoord, TileFormat};
+//!
+//! let mut tile = Tile::from_blob(Blob::from(vec![0; 100]), TileCompression::Uncompressed, TileFormat::MVT);
+//! let coord = TileCoord::new(3, 1, 2).unwrap();
+//! assert!(check_tile_size(&mut tile, &coord, TileCompression::Uncompressed, 100, "source").is_ok());
+//! assert!(check_tile_size(&mut tile, &coord, TileCompression::Uncompressed, 99, "source").is_err());
+//! ```
+
+use crate::Tile;
+use anyhow::Result;
+use std::fmt;
+use versatiles_core::{TileCompression, TileCoord};
+
+/// Default maximum size of a single encoded tile: 50 MB.
+pub const DEFAULT_MAX_TILE_SIZE: u64 = 50 * 1024 * 1024;
+
+/// An encoded tile is larger than the configured limit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileTooLargeError {
+	/// Coordinate of the tile.
+	pub coord: TileCoord,
+	/// Name of the source or operation that produced the tile.
+	pub source: String,
+	/// Size of the encoded tile in bytes.
+	pub size: u64,
+	/// The limit that was exceeded, in bytes.
+	pub max_size: u64,
+}
+
+impl fmt::Display for TileTooLargeError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"tile {:?} from '{}' has {} bytes, but only {} bytes are allowed",
+			self.coord, self.source, self.size, self.max_size
+		)
+	}
+}
+
+impl std::error::Error for TileTooLargeError {}
+
+/// Checks that `tile`, encoded with `compression`, is not larger than `max_size` bytes.
+///
+/// The blob is encoded if necessary and kept in the tile, so writers do not encode it again.
+///
+/// # Errors
+/// Returns a [`TileTooLargeError`] if the tile is too large, or the error of encoding the tile.
+pub fn check_tile_size(
+	tile: &mut Tile,
+	coord: &TileCoord,
+	compression: TileCompression,
+	max_size: u64,
+	source: &str,
+) -> Result<()> {
+	let size = tile.as_blob(compression)?.len();
+	if size > max_size {
+		return Err(
+			TileTooLargeError {
+				coord: *coord,
+				source: source.to_string(),
+				size,
+				max_size,
+			}
+			.into(),
+		);
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_core::{Blob, TileFormat};
+
+	#[test]
+	fn rejects_large_tiles() -> Result<()> {
+		let coord = TileCoord::new(3, 1, 2)?;
+		let mut tile = Tile::from_blob(
+			Blob::from(vec![0; 1000]),
+			TileCompression::Uncompressed,
+			TileFormat::MVT,
+		);
+		assert!(check_tile_size(&mut tile, &coord, TileCompression::Uncompressed, 1000, "test").is_ok());
+
+		let error = check_tile_size(&mut tile, &coord, TileCompression::Uncompressed, 999, "test").unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"tile TileCoord(3, [1, 2]) from 'test' has 1000 bytes, but only 999 bytes are allowed"
+		);
+		assert_eq!(error.downcast_ref::<TileTooLargeError>().unwrap().size, 1000);
+
+		// the limit applies to the encoded size
+		assert!(check_tile_size(&mut tile, &coord, TileCompression::Gzip, 999, "test").is_ok());
+		assert_eq!(tile.compression(), TileCompression::Gzip);
+		Ok(())
+	}
+}
@@ -0,0 +1,154 @@
+//! Deadlines for reading single tiles.
+//!
+//! A reader backed by a network source, e.g. an HTTP container or a pipeline that fetches remote data,
+//! can hang on a single request. [`get_tile_with_timeout`] bounds the time spent on one tile, so that a
+//! hung backend produces a [`TileTimeoutError`] instead of stalling a whole conversion or server.
+//! The error is handled like any other read error, e.g. by a [`TileErrorLog`](crate::TileErrorLog).
+//! [`get_tile_cancellable_with_timeout`] additionally stops reading once a [`CancellationToken`] is cancelled.
+//!
+//! ```rust
+//! use versatiles_container::*;
+//! use versatiles_core::TileCoord;
+//! use std::time::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let reader = MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?;
+//!     let coord = TileCoord::new(3, 1, 2)?;
+//!     let tile = get_tile_with_timeout(&reader, &coord, Some(Duration::from_secs(5))).await?;
+//!     assert!(tile.is_some());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Tile, TilesReaderTrait};
+use anyhow::Result;
+use std::{fmt, time::Duration};
+use versatiles_core::{CancellationToken, TileCoord};
+
+/// Reading a tile took longer than the configured timeout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileTimeoutError {
+	/// Coordinate of the tile.
+	pub coord: TileCoord,
+	/// The timeout that was exceeded.
+	pub timeout: Duration,
+}
+
+impl fmt::Display for TileTimeoutError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "reading tile {:?} timed out after {:?}", self.coord, self.timeout)
+	}
+}
+
+impl std::error::Error for TileTimeoutError {}
+
+/// Reads the tile at `coord` from `reader`, failing with a [`TileTimeoutError`] once `timeout` has elapsed.
+///
+/// Without a timeout, this is the same as [`TilesReaderTrait::get_tile`].
+///
+/// # Errors
+/// Returns the error of the reader, or a [`TileTimeoutError`] if the tile was not read in time.
+pub async fn get_tile_with_timeout<R>(reader: &R, coord: &TileCoord, timeout: Option<Duration>) -> Result<Option<Tile>>
+where
+	R: TilesReaderTrait + ?Sized,
+{
+	with_timeout(coord, timeout, reader.get_tile(coord)).await
+}
+
+/// Like [`get_tile_with_timeout`], but reads with [`TilesReaderTrait::get_tile_cancellable`], so the reader
+/// stops its work and returns `Ok(None)` once `token` is cancelled.
+///
+/// # Errors
+/// Returns the error of the reader, or a [`TileTimeoutError`] if the tile was not read in time.
+pub async fn get_tile_cancellable_with_timeout<R>(
+	reader: &R,
+	coord: &TileCoord,
+	timeout: Option<Duration>,
+	token: CancellationToken,
+) -> Result<Option<Tile>>
+where
+	R: TilesReaderTrait + ?Sized,
+{
+	with_timeout(coord, timeout, reader.get_tile_cancellable(coord, token)).await
+}
+
+async fn with_timeout(
+	coord: &TileCoord,
+	timeout: Option<Duration>,
+	read: impl Future<Output = Result<Option<Tile>>>,
+) -> Result<Option<Tile>> {
+	let Some(timeout) = timeout else {
+		return read.await;
+	};
+	match tokio::time::timeout(timeout, read).await {
+		Ok(result) => result,
+		Err(_) => Err(TileTimeoutError { coord: *coord, timeout }.into()),
+	}
+}
+
+/// A reader whose tiles never arrive, for testing timeouts and cancellation.
+#[cfg(any(test, feature = "mock"))]
+#[derive(Debug)]
+pub struct HangingReader(pub crate::MockTilesReader);
+
+#[cfg(any(test, feature = "mock"))]
+#[async_trait::async_trait]
+impl TilesReaderTrait for HangingReader {
+	fn source_name(&self) -> &str {
+		"hanging"
+	}
+	fn container_name(&self) -> &str {
+		"hanging"
+	}
+	fn parameters(&self) -> &versatiles_core::TilesReaderParameters {
+		self.0.parameters()
+	}
+	fn override_compression(&mut self, _tile_compression: versatiles_core::TileCompression) {}
+	fn tilejson(&self) -> &versatiles_core::TileJSON {
+		self.0.tilejson()
+	}
+	async fn get_tile(&self, _coord: &TileCoord) -> Result<Option<Tile>> {
+		std::future::pending().await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{MockTilesReader, MockTilesReaderProfile};
+
+	#[tokio::test]
+	async fn hanging_reader_times_out() -> Result<()> {
+		let reader = HangingReader(MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?);
+		let coord = TileCoord::new(3, 1, 2)?;
+
+		let error = get_tile_with_timeout(&reader, &coord, Some(Duration::from_millis(10)))
+			.await
+			.unwrap_err();
+		assert_eq!(
+			error.downcast_ref::<TileTimeoutError>(),
+			Some(&TileTimeoutError {
+				coord,
+				timeout: Duration::from_millis(10)
+			})
+		);
+		assert_eq!(
+			error.to_string(),
+			"reading tile TileCoord(3, [1, 2]) timed out after 10ms"
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn hanging_reader_is_cancelled() -> Result<()> {
+		let reader = HangingReader(MockTilesReader::new_mock_profile(MockTilesReaderProfile::Png)?);
+		let coord = TileCoord::new(3, 1, 2)?;
+		let token = CancellationToken::new();
+		token.cancel();
+
+		let tile = get_tile_cancellable_with_timeout(&reader, &coord, Some(Duration::from_secs(10)), token).await?;
+		assert!(tile.is_none());
+		Ok(())
+	}
+}
@@ -3,7 +3,7 @@
 //! This module defines the object‑safe [`TilesReaderTrait`], which exposes:
 //! - Lightweight metadata access (`source_name`, `container_name`, [`tilejson`])
 //! - Runtime parameters and formats via [`parameters`]
-//! - Random access to individual tiles (`get_tile`)
+//! - Random access to individual tiles (`get_tile`) and their modification times (`get_tile_mtime`)
 //! - Async streaming over regions via [`get_tile_stream`]
 //! - An optional CLI probing interface (behind the `cli` feature)
 //!
@@ -18,7 +18,7 @@
 //! # use versatiles_core::*;
 //! # async fn demo() -> anyhow::Result<()> {
 //! let registry = ContainerRegistry::default();
-//! let reader = registry.get_reader_from_str("../testdata/berlin.mbtiles").await?;
+//! let reader = registry.open_reader("../testdata/berlin.mbtiles").await?;
 //! let bbox = TileBBox::from_min_and_max(1, 0, 0, 1, 1)?;
 //! let mut stream = reader.get_tile_stream(bbox).await?;
 //! // drain tiles
@@ -29,17 +29,22 @@
 //! ```
 
 use crate::{CacheMap, ProcessingConfig, Tile};
+#[cfg(feature = "cli")]
+use anyhow::Context;
 use anyhow::Result;
 use async_trait::async_trait;
 use futures::{StreamExt, future::BoxFuture, stream};
 use std::{fmt::Debug, sync::Arc};
 use tokio::sync::Mutex;
-#[cfg(feature = "cli")]
-use versatiles_core::{ProbeDepth, utils::PrettyPrint};
+use tracing::Instrument;
 use versatiles_core::{
-	TileBBox, TileCompression, TileCoord, TileJSON, TileStream, TilesReaderParameters, Traversal,
-	TraversalTranslationStep, progress::get_progress_bar, translate_traversals,
+	CancellationToken, TileBBox, TileCompression, TileCoord, TileJSON, TileStream, TilesReaderParameters, Traversal,
+	TraversalTranslationStep,
+	progress::{MAX_LEVELS, ProgressStages, get_progress_bar},
+	translate_traversals,
 };
+#[cfg(feature = "cli")]
+use versatiles_core::{ProbeDepth, utils::PrettyPrint};
 
 /// Object‑safe interface for reading tiles from a container.
 ///
@@ -85,6 +90,37 @@ pub trait TilesReaderTrait: Debug + Send + Sync + Unpin {
 	/// The tile's compression/format follow the current [`TilesReaderTrait::parameters`].
 	async fn get_tile(&self, coord: &TileCoord) -> Result<Option<Tile>>;
 
+	/// Fetches a single tile at `coord` like [`TilesReaderTrait::get_tile`], but gives up once `token` is cancelled.
+	///
+	/// Returns `Ok(None)` if the token is cancelled first. The default implementation only stops waiting
+	/// for `get_tile`; readers that generate tiles, like pipelines, should override it to stop that work as well.
+	async fn get_tile_cancellable(&self, coord: &TileCoord, token: CancellationToken) -> Result<Option<Tile>> {
+		tokio::select! {
+			tile = self.get_tile(coord) => tile,
+			() = token.cancelled() => Ok(None),
+		}
+	}
+
+	/// Returns the modification time of the tile at `coord` in seconds since the Unix epoch.
+	///
+	/// Returns `Ok(None)` if the tile does not exist or the container does not store modification times.
+	/// The default implementation reads the whole tile; readers with a separate time index should override it.
+	async fn get_tile_mtime(&self, coord: &TileCoord) -> Result<Option<u64>> {
+		Ok(self.get_tile(coord).await?.and_then(|tile| tile.mtime()))
+	}
+
+	/// Hints that the tiles within `bbox` will be requested soon.
+	///
+	/// Readers with high-latency backends can use this to load directories or index blocks in advance,
+	/// so that a following [`TilesReaderTrait::get_tile_stream`] is served faster. The returned future
+	/// performs the loading, so callers usually run it concurrently with other work.
+	/// Prefetching is only an optimization: callers should log errors instead of aborting.
+	///
+	/// The default implementation does nothing.
+	async fn prefetch(&self, _bbox: &TileBBox) -> Result<()> {
+		Ok(())
+	}
+
 	/// Asynchronously streams all tiles within `bbox` as `(TileCoord, Tile)` pairs.
 	///
 	/// Implemented with internal synchronization to allow concurrent pulls from the stream.
@@ -189,13 +225,19 @@ pub trait TilesReaderTrait: Debug + Send + Sync + Unpin {
 		Ok(())
 	}
 
-	/// Writes sample tile content diagnostics or a placeholder warning if not implemented.
+	/// Writes sample tile content diagnostics.
+	///
+	/// For vector tiles, a sample of tiles at the center of every zoom level is decoded and the share of
+	/// every layer in the uncompressed tile size is reported, e.g. to find the layers worth optimizing.
 	#[cfg(feature = "cli")]
 	async fn probe_tile_contents(&mut self, print: &PrettyPrint) -> Result<()> {
-		print
-			.add_warning("deep tile contents probing is not implemented for this container format")
-			.await;
-		Ok(())
+		if !self.parameters().tile_format.is_vector() {
+			print
+				.add_warning("deep tile contents probing is only implemented for vector tiles")
+				.await;
+			return Ok(());
+		}
+		probe_layer_sizes(self, print).await
 	}
 
 	/// Converts `self` into a boxed trait object for dynamic dispatch and composition.
@@ -207,6 +249,73 @@ pub trait TilesReaderTrait: Debug + Send + Sync + Unpin {
 	}
 }
 
+/// Number of tiles per side of the square at the center of every zoom level, that is sampled to analyze tile contents.
+#[cfg(feature = "cli")]
+const CONTENT_SAMPLE_SIZE: u32 = 16;
+
+/// The share of a layer in the size of the sampled tiles.
+#[cfg(feature = "cli")]
+struct LayerShare {
+	size: u64,
+	total: u64,
+}
+
+#[cfg(feature = "cli")]
+impl Debug for LayerShare {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let percent = 100.0 * self.size as f64 / self.total.max(1) as f64;
+		write!(f, "{percent:.1}% ({} bytes)", self.size)
+	}
+}
+
+/// Reports for every zoom level, how many bytes each layer of the sampled vector tiles accounts for.
+#[cfg(feature = "cli")]
+async fn probe_layer_sizes<R: TilesReaderTrait + ?Sized>(reader: &R, print: &PrettyPrint) -> Result<()> {
+	use std::collections::HashMap;
+	use versatiles_geometry::vector_tile::VectorTile;
+
+	let bbox_pyramid = reader.parameters().bbox_pyramid.clone();
+	for bbox in bbox_pyramid.iter_levels() {
+		// a square at the center of the level, where tiles are usually more interesting than at the edges
+		let width = bbox.width().min(CONTENT_SAMPLE_SIZE);
+		let height = bbox.height().min(CONTENT_SAMPLE_SIZE);
+		let x_min = bbox.x_min()? + (bbox.width() - width) / 2;
+		let y_min = bbox.y_min()? + (bbox.height() - height) / 2;
+		let sample = TileBBox::from_min_and_max(bbox.level, x_min, y_min, x_min + width - 1, y_min + height - 1)?;
+
+		let mut tile_count: u64 = 0;
+		let mut layer_sizes: HashMap<String, u64> = HashMap::new();
+		let mut stream = reader.get_tile_stream(sample).await?;
+		while let Some((coord, tile)) = stream.next().await {
+			let blob = tile.into_blob(TileCompression::Uncompressed)?;
+			for (name, size) in VectorTile::layer_sizes(&blob).with_context(|| format!("analyzing tile {coord:?}"))? {
+				*layer_sizes.entry(name).or_default() += size;
+			}
+			tile_count += 1;
+		}
+		if tile_count == 0 {
+			continue;
+		}
+
+		let total: u64 = layer_sizes.values().sum();
+		let mut layers: Vec<(String, u64)> = layer_sizes.into_iter().collect();
+		layers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+		let list = print
+			.clone()
+			.get_list(&format!(
+				"level {}: {tile_count} sampled tiles, {} bytes per tile",
+				bbox.level,
+				total / tile_count
+			))
+			.await;
+		for (name, size) in layers {
+			list.add_key_value(&name, &LayerShare { size, total }).await;
+		}
+	}
+	Ok(())
+}
+
 /// Extension trait providing traversal with higher‑rank trait bounds (HRTBs) while
 /// keeping [`TilesReaderTrait`] object‑safe.
 ///
@@ -220,7 +329,10 @@ pub trait TilesReaderTraverseExt: TilesReaderTrait {
 	/// * `callback` — async function to consume each bbox + stream.
 	/// * `config` — processing configuration (also used to size caches).
 	///
-	/// Progress is reported via a progress bar; caching is used to support `Push/Pop` phases.
+	/// Progress is reported via a progress bar in the stage `convert`, see [`ProgressStages`], and counted in `config.metrics`;
+	/// caching is used to support `Push/Pop` phases.
+	/// Once `config.cancellation_token` is cancelled, all streams end early, so the callback
+	/// only receives the tiles that have been read so far.
 	fn traverse_all_tiles<'s, 'a, C>(
 		&'s self,
 		traversal_write: &'s Traversal,
@@ -239,6 +351,7 @@ pub trait TilesReaderTraverseExt: TilesReaderTrait {
 
 			let mut tn_read = 0;
 			let mut tn_write = 0;
+			let mut level_totals = [0u64; MAX_LEVELS];
 
 			for step in &traversal_steps {
 				match step {
@@ -247,78 +360,127 @@ pub trait TilesReaderTraverseExt: TilesReaderTrait {
 					}
 					Pop(_, bbox_out) => {
 						tn_write += bbox_out.count_tiles();
+						level_totals[bbox_out.level as usize] += bbox_out.count_tiles();
 					}
 					Stream(bboxes_in, bbox_out) => {
 						tn_read += bboxes_in.iter().map(TileBBox::count_tiles).sum::<u64>();
 						tn_write += bbox_out.count_tiles();
+						level_totals[bbox_out.level as usize] += bbox_out.count_tiles();
 					}
 				}
 			}
+			for (level, count) in level_totals.into_iter().enumerate() {
+				config.metrics.set_level_total(level as u8, count);
+			}
+			ProgressStages::begin("convert");
 			let progress = get_progress_bar("converting tiles", u64::midpoint(tn_read, tn_write));
 
 			let mut ti_read = 0;
 			let mut ti_write = 0;
 
 			let cache = Arc::new(Mutex::new(CacheMap::<usize, (TileCoord, Tile)>::new(&config)));
-			for step in traversal_steps {
-				match step {
-					Push(bboxes, index) => {
-						log::trace!("Cache {bboxes:?} at index {index}");
-						stream::iter(bboxes.clone())
-							.map(|bbox| {
+			let prefetch_bboxes = next_read_bboxes(&traversal_steps);
+			for (step, next_bboxes) in traversal_steps.into_iter().zip(prefetch_bboxes) {
+				let step_span = tracing::debug_span!("traversal_step", step = ?step);
+				// hint the reader about the next read step, while the current step is processed
+				let prefetch = async {
+					for bbox in &next_bboxes {
+						if let Err(err) = self.prefetch(bbox).await {
+							log::warn!("prefetching {bbox:?} failed: {err:?}");
+						}
+					}
+				};
+
+				let process = async {
+					match step {
+						Push(bboxes, index) => {
+							log::trace!("Cache {bboxes:?} at index {index}");
+							stream::iter(bboxes.clone())
+								.map(|bbox| {
+									let progress = progress.clone();
+									let c = cache.clone();
+									let token = config.cancellation_token.clone();
+									let span =
+										tracing::debug_span!("read_bbox", container = self.container_name(), bbox = ?bbox);
+									async move {
+										let vec = self
+											.get_tile_stream(bbox)
+											.instrument(span.clone())
+											.await?
+											.in_span(span)
+											.take_until_cancelled(token)
+											.inspect(move || progress.inc(1))
+											.to_vec()
+											.await;
+
+										let mut cache = c.lock().await;
+										cache.append(&index, vec)?;
+
+										Ok::<_, anyhow::Error>(())
+									}
+								})
+								.buffer_unordered(num_cpus::get() / 4)
+								.collect::<Vec<_>>()
+								.await
+								.into_iter()
+								.collect::<Result<Vec<_>>>()?;
+							ti_read += bboxes.iter().map(TileBBox::count_tiles).sum::<u64>();
+						}
+						Pop(index, bbox) => {
+							log::trace!("Uncache {bbox:?} at index {index}");
+							let vec = cache.lock().await.remove(&index)?.unwrap();
+							let progress = progress.clone();
+							let metrics = config.metrics.clone();
+							let stream = TileStream::from_vec(vec).inspect(move || {
+								progress.inc(1);
+								metrics.add_tiles(bbox.level, 1);
+							});
+							callback(bbox, stream).await?;
+							ti_write += bbox.count_tiles();
+						}
+						Stream(bboxes, bbox) => {
+							log::trace!("Stream {bbox:?}");
+							let progress = progress.clone();
+							let token = config.cancellation_token.clone();
+							let metrics = config.metrics.clone();
+							let streams = stream::iter(bboxes.clone()).map(move |bbox| {
 								let progress = progress.clone();
-								let c = cache.clone();
+								let token = token.clone();
+								let metrics = metrics.clone();
+								let span = tracing::debug_span!("read_bbox", container = self.container_name(), bbox = ?bbox);
 								async move {
-									let vec = self
+									self
 										.get_tile_stream(bbox)
-										.await?
-										.inspect(move || progress.inc(1))
-										.to_vec()
-										.await;
-
-									let mut cache = c.lock().await;
-									cache.append(&index, vec)?;
-
-									Ok::<_, anyhow::Error>(())
+										.instrument(span.clone())
+										.await
+										.unwrap()
+										.in_span(span)
+										.take_until_cancelled(token)
+										.inspect(move || {
+											progress.inc(2);
+											metrics.add_tiles(bbox.level, 1);
+										})
 								}
-							})
-							.buffer_unordered(num_cpus::get() / 4)
-							.collect::<Vec<_>>()
-							.await
-							.into_iter()
-							.collect::<Result<Vec<_>>>()?;
-						ti_read += bboxes.iter().map(TileBBox::count_tiles).sum::<u64>();
+							});
+							callback(bbox, TileStream::from_streams(streams)).await?;
+							ti_read += bboxes.iter().map(TileBBox::count_tiles).sum::<u64>();
+							ti_write += bbox.count_tiles();
+						}
 					}
-					Pop(index, bbox) => {
-						log::trace!("Uncache {bbox:?} at index {index}");
-						let vec = cache.lock().await.remove(&index)?.unwrap();
-						let progress = progress.clone();
-						let stream = TileStream::from_vec(vec).inspect(move || progress.inc(1));
-						callback(bbox, stream).await?;
-						ti_write += bbox.count_tiles();
-					}
-					Stream(bboxes, bbox) => {
-						log::trace!("Stream {bbox:?}");
-						let progress = progress.clone();
-						let streams = stream::iter(bboxes.clone()).map(move |bbox| {
-							let progress = progress.clone();
-							async move {
-								self
-									.get_tile_stream(bbox)
-									.await
-									.unwrap()
-									.inspect(move || progress.inc(2))
-							}
-						});
-						callback(bbox, TileStream::from_streams(streams)).await?;
-						ti_read += bboxes.iter().map(TileBBox::count_tiles).sum::<u64>();
-						ti_write += bbox.count_tiles();
-					}
-				}
+					Ok::<_, anyhow::Error>(())
+				};
+
+				let (result, ()) = futures::join!(process.instrument(step_span), prefetch);
+				result?;
 				progress.set_position(u64::midpoint(ti_read, ti_write));
 			}
 
 			progress.finish();
+
+			if config.cancellation_token.is_cancelled() {
+				log::warn!("traversal was cancelled, only tiles read so far have been passed on");
+			}
+
 			Ok(())
 		}
 	}
@@ -326,6 +488,21 @@ pub trait TilesReaderTraverseExt: TilesReaderTrait {
 
 impl<T: TilesReaderTrait + ?Sized> TilesReaderTraverseExt for T {}
 
+/// Returns, for every step, the bboxes read by the next `Push` or `Stream` step.
+fn next_read_bboxes(steps: &[TraversalTranslationStep]) -> Vec<Vec<TileBBox>> {
+	use TraversalTranslationStep::*;
+
+	let mut result = vec![Vec::new(); steps.len()];
+	let mut next = Vec::new();
+	for (index, step) in steps.iter().enumerate().rev() {
+		result[index] = next.clone();
+		if let Push(bboxes, _) | Stream(bboxes, _) = step {
+			next = bboxes.clone();
+		}
+	}
+	result
+}
+
 /// Tests cover trait defaults, parameter plumbing, streaming behavior, and the CLI probe stubs.
 #[cfg(test)]
 mod tests {
@@ -439,6 +616,47 @@ mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_get_tile_cancellable() -> Result<()> {
+		let coord = TileCoord::new(3, 1, 2)?;
+		let token = CancellationToken::new();
+		let reader = TestReader::new_dummy();
+		assert!(reader.get_tile_cancellable(&coord, token.clone()).await?.is_some());
+
+		let reader = crate::HangingReader(crate::MockTilesReader::new_mock_profile(
+			crate::MockTilesReaderProfile::Png,
+		)?);
+		token.cancel_after(std::time::Duration::from_millis(10));
+		assert!(reader.get_tile_cancellable(&coord, token).await?.is_none());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_traverse_all_tiles_cancelled() -> Result<()> {
+		let reader = TestReader::new_dummy();
+		let config = ProcessingConfig::default();
+		config.cancellation_token.cancel();
+
+		let count = Arc::new(Mutex::new(0u64));
+		let count_clone = count.clone();
+		reader
+			.traverse_all_tiles(
+				&Traversal::ANY,
+				move |_bbox, stream| {
+					let count = count_clone.clone();
+					Box::pin(async move {
+						*count.lock().await += stream.drain_and_count().await;
+						Ok(())
+					})
+				},
+				config,
+			)
+			.await?;
+
+		assert_eq!(*count.lock().await, 0);
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_probe_tile_contents() -> Result<()> {
 		#[cfg(feature = "cli")]
@@ -446,10 +664,15 @@ mod tests {
 			use versatiles_core::utils::PrettyPrint;
 
 			let mut reader = TestReader::new_dummy();
+			reader.parameters.tile_format = TileFormat::PNG;
 			let mut print = PrettyPrint::new();
 			reader
 				.probe_tile_contents(&print.get_category("tile contents").await)
 				.await?;
+			assert_eq!(
+				print.as_string().await,
+				"tile contents:\n  deep tile contents probing is only implemented for vector tiles\n"
+			);
 		}
 		Ok(())
 	}
@@ -485,6 +708,8 @@ mod tests {
 	#[tokio::test]
 	async fn test_probe_all_levels() -> Result<()> {
 		let mut reader = TestReader::new_dummy();
+		// the dummy tiles are not valid vector tiles
+		reader.parameters.tile_format = TileFormat::PNG;
 		reader.probe(ProbeDepth::Container).await?;
 		reader.probe(ProbeDepth::Tiles).await?;
 		reader.probe(ProbeDepth::TileContents).await?;
@@ -499,4 +724,21 @@ mod tests {
 		assert_eq!(boxed.source_name(), "dummy");
 		assert_eq!(boxed.container_name(), "test container name");
 	}
+
+	#[tokio::test]
+	async fn test_prefetch_default_is_noop() -> Result<()> {
+		let reader = TestReader::new_dummy();
+		reader.prefetch(&TileBBox::new_full(2)?).await?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_next_read_bboxes() -> Result<()> {
+		use TraversalTranslationStep::*;
+		let b1 = TileBBox::new_full(1)?;
+		let b2 = TileBBox::new_full(2)?;
+		let steps = vec![Push(vec![b1], 0), Pop(0, b1), Stream(vec![b2], b2), Pop(1, b2)];
+		assert_eq!(next_read_bboxes(&steps), vec![vec![b2], vec![b2], vec![], vec![]]);
+		Ok(())
+	}
 }
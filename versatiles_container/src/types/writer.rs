@@ -21,7 +21,7 @@
 //! #[tokio::main]
 //! async fn main() -> anyhow::Result<()> {
 //!     let registry = ContainerRegistry::default();
-//!     let reader = registry.get_reader_from_str("../testdata/berlin.mbtiles").await?;
+//!     let reader = registry.open_reader("../testdata/berlin.mbtiles").await?;
 //!     let output_path = std::env::temp_dir().join("example.versatiles");
 //!
 //!     // The registry automatically dispatches to the correct writer
@@ -46,14 +46,16 @@ use versatiles_core::io::*;
 pub trait TilesWriterTrait: Send {
 	/// Writes all tile data from `reader` into the file or directory at `path`.
 	///
-	/// The default implementation wraps `path` in a [`DataWriterFile`] and calls
-	/// [`TilesWriterTrait::write_to_writer`]. Implementations may override this for more efficient
-	/// file handling.
+	/// The default implementation wraps `path` in a [`DataWriterFile`], that counts the written bytes in
+	/// `config.metrics`, and calls [`TilesWriterTrait::write_to_writer`]. Implementations may override this
+	/// for more efficient file handling.
 	///
 	/// # Errors
 	/// Returns an error if the file cannot be created or the writing operation fails.
 	async fn write_to_path(reader: &mut dyn TilesReaderTrait, path: &Path, config: ProcessingConfig) -> Result<()> {
-		Self::write_to_writer(reader, &mut DataWriterFile::from_path(path)?, config).await
+		let mut writer = DataWriterFile::from_path(path)?;
+		writer.set_metrics(config.metrics.clone());
+		Self::write_to_writer(reader, &mut writer, config).await
 	}
 
 	/// Writes tile data from `reader` to the provided [`DataWriterTrait`] sink.
@@ -0,0 +1,51 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use versatiles_core::{
+	Blob,
+	utils::{BrotliCodec, Codec, available_gzip_codecs},
+};
+
+const DATA_SIZE: usize = 256 * 1024;
+
+/// Generates compressible data, similar to vector tiles with repeated keys and values.
+fn tile_like_data() -> Blob {
+	let mut data = Vec::with_capacity(DATA_SIZE);
+	let mut i: u64 = 0;
+	while data.len() < DATA_SIZE {
+		i += 1;
+		data.extend_from_slice(format!("name:Street {},highway:residential,id:{};", i % 977, i * 7919).as_bytes());
+	}
+	Blob::from(data)
+}
+
+fn benchmark_codecs(c: &mut Criterion) {
+	let data = tile_like_data();
+
+	let mut codecs: Vec<(String, &dyn Codec)> = available_gzip_codecs()
+		.into_iter()
+		.map(|codec| (format!("gzip {}", codec.name()), codec))
+		.collect();
+	codecs.push(("brotli best".to_string(), &BrotliCodec::BEST));
+	codecs.push(("brotli fast".to_string(), &BrotliCodec::FAST));
+
+	let mut group = c.benchmark_group("compression");
+	group.throughput(Throughput::Bytes(data.len()));
+	for (name, codec) in codecs {
+		let compressed = codec.compress(&data).unwrap();
+
+		group.bench_with_input(BenchmarkId::new("compress", &name), &data, |b, data| {
+			b.iter(|| codec.compress(black_box(data)).unwrap());
+		});
+		group.bench_with_input(BenchmarkId::new("decompress", &name), &compressed, |b, compressed| {
+			b.iter(|| codec.decompress(black_box(compressed)).unwrap());
+		});
+	}
+	group.finish();
+}
+
+criterion_group!(
+	name = benches;
+	config = Criterion::default().significance_level(0.1).sample_size(20);
+	targets = benchmark_codecs
+);
+criterion_main!(benches);
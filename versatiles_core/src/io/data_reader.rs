@@ -94,4 +94,24 @@ pub trait DataReaderTrait: Debug + Send + Sync {
 	///
 	/// * A string slice representing the name of the data source.
 	fn get_name(&self) -> &str;
+
+	/// Identifies the current version of the data, e.g. by ETag and content length.
+	///
+	/// Used to invalidate cached data, see [`DataReaderCached`](super::DataReaderCached).
+	///
+	/// # Returns
+	///
+	/// * A Result containing the fingerprint, or `None` if the source can not tell whether it has changed.
+	async fn fingerprint(&self) -> Result<Option<String>> {
+		Ok(None)
+	}
+
+	/// Gets the size of the data source in bytes, e.g. to read data at its end.
+	///
+	/// # Returns
+	///
+	/// * A Result containing the size, or `None` if the source does not know its size.
+	async fn get_size(&self) -> Result<Option<u64>> {
+		Ok(None)
+	}
 }
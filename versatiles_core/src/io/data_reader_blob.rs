@@ -3,7 +3,7 @@
 //! # Overview
 //!
 //! The `DataReaderBlob` struct allows for reading data stored in an in-memory
-//! vector of bytes (`Vec<u8>`). It implements the `DataReaderTrait` to provide
+//! [`Blob`]. Ranges are returned as slices of the same buffer, without copying. It implements the `DataReaderTrait` to provide
 //! asynchronous reading capabilities and the standard library's `Read` trait for
 //! synchronous reading.
 //!
@@ -43,20 +43,20 @@ use versatiles_derive::context;
 /// A struct that provides reading capabilities from an in-memory blob of data.
 #[derive(Debug)]
 pub struct DataReaderBlob {
-	blob: Cursor<Vec<u8>>,
+	blob: Cursor<Blob>,
 }
 
 impl DataReaderBlob {
 	/// Returns the length of the data in the reader.
 	#[must_use]
 	pub fn len(&self) -> usize {
-		self.blob.get_ref().len()
+		self.blob.get_ref().len() as usize
 	}
 
 	/// Checks if the reader is empty.
 	#[must_use]
 	pub fn is_empty(&self) -> bool {
-		self.blob.get_ref().len() == 0
+		self.blob.get_ref().is_empty()
 	}
 }
 
@@ -73,15 +73,15 @@ impl DataReaderTrait for DataReaderBlob {
 	/// * A Result containing a Blob with the read data or an error.
 	#[context("while reading range {range:?} from DataReaderBlob")]
 	async fn read_range(&self, range: &ByteRange) -> Result<Blob> {
-		let start = range.offset as usize;
-		let end = (range.offset + range.length) as usize;
+		let start = range.offset;
+		let end = range.offset + range.length;
 		let blob = self.blob.get_ref();
 		ensure!(
 			end <= blob.len(),
 			"end of range ({start}..{end}) is outside blob ({})",
 			blob.len()
 		);
-		Ok(Blob::from(&blob[start..end]))
+		Ok(blob.slice(start as usize..end as usize))
 	}
 
 	/// Reads all the data from the reader.
@@ -91,7 +91,7 @@ impl DataReaderTrait for DataReaderBlob {
 	/// * A Result containing a Blob with all the data or an error.
 	#[context("while reading all data from DataReaderBlob")]
 	async fn read_all(&self) -> Result<Blob> {
-		Ok(Blob::from(self.blob.get_ref()))
+		Ok(self.blob.get_ref().clone())
 	}
 
 	/// Gets the name of the data source.
@@ -102,6 +102,15 @@ impl DataReaderTrait for DataReaderBlob {
 	fn get_name(&self) -> &'static str {
 		"memory"
 	}
+
+	/// Gets the length of the data in bytes.
+	///
+	/// # Returns
+	///
+	/// * A Result containing the length.
+	async fn get_size(&self) -> Result<Option<u64>> {
+		Ok(Some(self.blob.get_ref().len()))
+	}
 }
 
 impl Read for DataReaderBlob {
@@ -161,7 +170,7 @@ impl From<Blob> for DataReaderBlob {
 	/// * A new `DataReaderBlob`.
 	fn from(value: Blob) -> Self {
 		DataReaderBlob {
-			blob: Cursor::new(value.into_vec()),
+			blob: Cursor::new(value),
 		}
 	}
 }
@@ -178,7 +187,7 @@ impl From<Vec<u8>> for DataReaderBlob {
 	/// * A new `DataReaderBlob`.
 	fn from(value: Vec<u8>) -> Self {
 		DataReaderBlob {
-			blob: Cursor::new(value),
+			blob: Cursor::new(Blob::from(value)),
 		}
 	}
 }
@@ -194,6 +203,7 @@ mod tests {
 		let data_reader = DataReaderBlob::from(blob.clone());
 
 		assert_eq!(data_reader.get_name(), "memory");
+		assert_eq!(data_reader.get_size().await?, Some(8));
 
 		assert_eq!(data_reader.read_range(&ByteRange::new(0, 8)).await?, blob);
 
@@ -0,0 +1,319 @@
+//! This module provides a data reader that persists ranges in a local cache file.
+//!
+//! # Overview
+//!
+//! Opening a container reads its header and its directories or indexes, e.g. the leaf directories of
+//! PMTiles or the block index of a `.versatiles` file. For remote containers these reads can take a while.
+//! `DataReaderCached` wraps another data reader and records all ranges read until [`DataReaderCacheHandle::persist`]
+//! is called, usually right after the container has been opened. The recorded ranges are written to a cache file,
+//! so the next time the container is opened, e.g. after a server restart, they are read from disk instead.
+//!
+//! The cache file stores the [fingerprint](DataReaderTrait::fingerprint) of the source, e.g. its ETag and
+//! content length. If the fingerprint has changed or is unknown, the cache file is ignored and rewritten.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use versatiles_core::{io::*, ByteRange};
+//! use anyhow::Result;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     let source = std::env::current_dir()?.parent().unwrap().join("LICENSE");
+//!     let temp_dir = assert_fs::TempDir::new()?;
+//!     let cache = temp_dir.path().join("license.cache");
+//!
+//!     let (reader, handle) = DataReaderCached::open(DataReaderFile::open(&source)?, &cache).await?;
+//!     assert_eq!(reader.read_range(&ByteRange::new(4, 7)).await?.as_str(), "License");
+//!     handle.persist()?;
+//!     assert!(cache.exists());
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use super::{DataReader, DataReaderTrait, ValueReader, ValueReaderSlice, ValueWriter, ValueWriterBlob};
+use crate::{Blob, ByteRange};
+use anyhow::{Result, bail, ensure};
+use async_trait::async_trait;
+use std::{
+	collections::HashMap,
+	fmt::Debug,
+	fs,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex},
+};
+use versatiles_derive::context;
+
+/// Identifies cache files, followed by the format version.
+const MAGIC: &[u8] = b"versatiles:range_cache:1";
+
+/// A data reader that serves ranges from a local cache file, see the [module documentation](self).
+pub struct DataReaderCached {
+	inner: DataReader,
+	state: Arc<Mutex<CacheState>>,
+}
+
+/// Persists the ranges recorded by a [`DataReaderCached`].
+#[derive(Clone)]
+pub struct DataReaderCacheHandle {
+	state: Arc<Mutex<CacheState>>,
+}
+
+struct CacheState {
+	path: PathBuf,
+	fingerprint: Option<String>,
+	ranges: HashMap<ByteRange, Blob>,
+	recording: bool,
+	changed: bool,
+}
+
+impl DataReaderCached {
+	/// Wraps `inner` and loads the ranges cached in the file at `cache_path`, if it belongs to the current
+	/// version of the source. Returns the reader and a handle to persist the recorded ranges.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the fingerprint of the source can not be determined.
+	/// A missing, outdated or broken cache file is not an error.
+	#[context("while opening cached reader with cache file {cache_path:?}")]
+	pub async fn open(inner: DataReader, cache_path: &Path) -> Result<(DataReaderCached, DataReaderCacheHandle)> {
+		let fingerprint = inner.fingerprint().await?;
+
+		let mut ranges = HashMap::new();
+		if let Some(fingerprint) = &fingerprint
+			&& cache_path.exists()
+		{
+			match read_cache_file(cache_path, fingerprint) {
+				Ok(Some(cached)) => {
+					log::debug!("using {} cached ranges of '{}'", cached.len(), inner.get_name());
+					ranges = cached;
+				}
+				Ok(None) => log::debug!("cache file {cache_path:?} is outdated"),
+				Err(error) => log::warn!("ignoring broken cache file {cache_path:?}: {error:#}"),
+			}
+		}
+
+		let state = Arc::new(Mutex::new(CacheState {
+			path: cache_path.to_path_buf(),
+			recording: fingerprint.is_some(),
+			fingerprint,
+			ranges,
+			changed: false,
+		}));
+		let handle = DataReaderCacheHandle { state: state.clone() };
+		Ok((DataReaderCached { inner, state }, handle))
+	}
+}
+
+impl DataReaderCacheHandle {
+	/// Writes the recorded ranges to the cache file, if there are new ones, and stops recording.
+	///
+	/// Call it after the container has been opened, so that only its header and indexes are cached, not its tiles.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the cache file can not be written.
+	pub fn persist(&self) -> Result<()> {
+		let mut state = self.state.lock().unwrap();
+		state.recording = false;
+		if !state.changed {
+			return Ok(());
+		}
+		let Some(fingerprint) = &state.fingerprint else {
+			return Ok(());
+		};
+		write_cache_file(&state.path, fingerprint, &state.ranges)?;
+		state.changed = false;
+		Ok(())
+	}
+}
+
+#[async_trait]
+impl DataReaderTrait for DataReaderCached {
+	/// Reads a range from the cache, or from the wrapped reader and records it until the cache is persisted.
+	async fn read_range(&self, range: &ByteRange) -> Result<Blob> {
+		if let Some(blob) = self.state.lock().unwrap().ranges.get(range) {
+			return Ok(blob.clone());
+		}
+		let blob = self.inner.read_range(range).await?;
+		let mut state = self.state.lock().unwrap();
+		if state.recording {
+			state.ranges.insert(*range, blob.clone());
+			state.changed = true;
+		}
+		Ok(blob)
+	}
+
+	async fn read_all(&self) -> Result<Blob> {
+		self.inner.read_all().await
+	}
+
+	fn get_name(&self) -> &str {
+		self.inner.get_name()
+	}
+
+	async fn fingerprint(&self) -> Result<Option<String>> {
+		Ok(self.state.lock().unwrap().fingerprint.clone())
+	}
+
+	async fn get_size(&self) -> Result<Option<u64>> {
+		self.inner.get_size().await
+	}
+}
+
+impl Debug for DataReaderCached {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("DataReaderCached")
+			.field("inner", &self.inner)
+			.field("path", &self.state.lock().unwrap().path)
+			.finish()
+	}
+}
+
+/// Reads the cached ranges, or returns `None` if the file belongs to another version of the source.
+#[context("while reading cache file {path:?}")]
+fn read_cache_file(path: &Path, fingerprint: &str) -> Result<Option<HashMap<ByteRange, Blob>>> {
+	let data = fs::read(path)?;
+	ensure!(data.starts_with(MAGIC), "unknown file format");
+	let mut reader = ValueReaderSlice::new_le(&data[MAGIC.len()..]);
+
+	let length = reader.read_varint()?;
+	if reader.read_string(length)? != fingerprint {
+		return Ok(None);
+	}
+
+	let mut ranges = HashMap::new();
+	for _ in 0..reader.read_varint()? {
+		let range = reader.read_range()?;
+		ranges.insert(range, reader.read_blob(range.length)?);
+	}
+	if reader.has_remaining() {
+		bail!("unexpected data at the end of the file");
+	}
+	Ok(Some(ranges))
+}
+
+/// Writes the ranges to a temporary file first, so an interrupted write does not leave a broken cache file.
+#[context("while writing cache file {path:?}")]
+fn write_cache_file(path: &Path, fingerprint: &str, ranges: &HashMap<ByteRange, Blob>) -> Result<()> {
+	let mut writer = ValueWriterBlob::new_le();
+	writer.write_slice(MAGIC)?;
+	writer.write_varint(fingerprint.len() as u64)?;
+	writer.write_string(fingerprint)?;
+	writer.write_varint(ranges.len() as u64)?;
+	for (range, blob) in ranges {
+		writer.write_range(range)?;
+		writer.write_blob(blob)?;
+	}
+
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)?;
+	}
+	let temp_path = path.with_extension("tmp");
+	fs::write(&temp_path, writer.into_blob().as_slice())?;
+	fs::rename(&temp_path, path)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::io::DataReaderBlob;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	/// Counts the ranges read from the source and reports a fixed fingerprint.
+	#[derive(Debug)]
+	struct Source {
+		reader: DataReaderBlob,
+		fingerprint: Option<&'static str>,
+		reads: Arc<AtomicUsize>,
+	}
+
+	#[async_trait]
+	impl DataReaderTrait for Source {
+		async fn read_range(&self, range: &ByteRange) -> Result<Blob> {
+			self.reads.fetch_add(1, Ordering::SeqCst);
+			self.reader.read_range(range).await
+		}
+		async fn read_all(&self) -> Result<Blob> {
+			self.reader.read_all().await
+		}
+		fn get_name(&self) -> &str {
+			"source"
+		}
+		async fn fingerprint(&self) -> Result<Option<String>> {
+			Ok(self.fingerprint.map(String::from))
+		}
+	}
+
+	fn source(fingerprint: Option<&'static str>, reads: &Arc<AtomicUsize>) -> DataReader {
+		Box::new(Source {
+			reader: DataReaderBlob::from(b"0123456789abcdef".to_vec()),
+			fingerprint,
+			reads: reads.clone(),
+		})
+	}
+
+	#[tokio::test]
+	async fn caches_ranges_read_before_persisting() -> Result<()> {
+		let dir = assert_fs::TempDir::new()?;
+		let path = dir.path().join("cache/source.bin");
+		let reads = Arc::new(AtomicUsize::new(0));
+
+		let (reader, handle) = DataReaderCached::open(source(Some("v1"), &reads), &path).await?;
+		assert_eq!(reader.read_range(&ByteRange::new(2, 3)).await?.as_str(), "234");
+		handle.persist()?;
+		assert_eq!(reader.read_range(&ByteRange::new(10, 2)).await?.as_str(), "ab");
+		assert_eq!(reads.load(Ordering::SeqCst), 2);
+		assert!(path.exists());
+
+		// after a restart, only the range read before persisting comes from the cache
+		let (reader, handle) = DataReaderCached::open(source(Some("v1"), &reads), &path).await?;
+		assert_eq!(reader.read_range(&ByteRange::new(2, 3)).await?.as_str(), "234");
+		assert_eq!(reads.load(Ordering::SeqCst), 2);
+		assert_eq!(reader.read_range(&ByteRange::new(10, 2)).await?.as_str(), "ab");
+		assert_eq!(reads.load(Ordering::SeqCst), 3);
+		handle.persist()?;
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn invalidates_cache_of_changed_source() -> Result<()> {
+		let dir = assert_fs::TempDir::new()?;
+		let path = dir.path().join("source.bin");
+		let reads = Arc::new(AtomicUsize::new(0));
+
+		let (reader, handle) = DataReaderCached::open(source(Some("v1"), &reads), &path).await?;
+		reader.read_range(&ByteRange::new(2, 3)).await?;
+		handle.persist()?;
+
+		let (reader, _) = DataReaderCached::open(source(Some("v2"), &reads), &path).await?;
+		reader.read_range(&ByteRange::new(2, 3)).await?;
+		assert_eq!(reads.load(Ordering::SeqCst), 2);
+
+		// without fingerprint, nothing is cached
+		let (reader, handle) = DataReaderCached::open(source(None, &reads), &path).await?;
+		reader.read_range(&ByteRange::new(2, 3)).await?;
+		assert_eq!(reads.load(Ordering::SeqCst), 3);
+		fs::remove_file(&path)?;
+		handle.persist()?;
+		assert!(!path.exists());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn ignores_broken_cache_files() -> Result<()> {
+		let dir = assert_fs::TempDir::new()?;
+		let path = dir.path().join("source.bin");
+		fs::write(&path, b"broken")?;
+		let reads = Arc::new(AtomicUsize::new(0));
+
+		let (reader, handle) = DataReaderCached::open(source(Some("v1"), &reads), &path).await?;
+		assert_eq!(reader.read_range(&ByteRange::new(0, 4)).await?.as_str(), "0123");
+		handle.persist()?;
+		assert!(fs::read(&path)?.starts_with(MAGIC));
+		Ok(())
+	}
+}
@@ -35,6 +35,7 @@ use std::{
 	fs::File,
 	io::{Read, Seek, SeekFrom},
 	path::Path,
+	time::UNIX_EPOCH,
 };
 use versatiles_derive::context;
 
@@ -133,6 +134,27 @@ impl DataReaderTrait for DataReaderFile {
 	fn get_name(&self) -> &str {
 		&self.name
 	}
+
+	/// Identifies the current version of the file by its size and modification time.
+	///
+	/// # Returns
+	///
+	/// * A Result containing the fingerprint.
+	#[context("while reading the metadata of file '{}'", self.name)]
+	async fn fingerprint(&self) -> Result<Option<String>> {
+		let metadata = self.file.metadata()?;
+		let modified = metadata.modified()?.duration_since(UNIX_EPOCH)?.as_nanos();
+		Ok(Some(format!("size: {}, modified: {modified}", metadata.len())))
+	}
+
+	/// Gets the size of the file in bytes.
+	///
+	/// # Returns
+	///
+	/// * A Result containing the size.
+	async fn get_size(&self) -> Result<Option<u64>> {
+		Ok(Some(self.size))
+	}
 }
 
 impl Read for DataReaderFile {
@@ -224,6 +246,27 @@ mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn fingerprint() -> Result<()> {
+		let temp_file_path = NamedTempFile::new("testfile.txt")?;
+		File::create(&temp_file_path)?.write_all(b"Hello, world!")?;
+
+		let fingerprint = DataReaderFile::open(temp_file_path.path())?
+			.fingerprint()
+			.await?
+			.unwrap();
+		assert_wildcard!(&fingerprint, "size: 13, modified: *");
+		assert_eq!(DataReaderFile::open(temp_file_path.path())?.get_size().await?, Some(13));
+
+		File::create(&temp_file_path)?.write_all(b"Hello!")?;
+		let changed = DataReaderFile::open(temp_file_path.path())?
+			.fingerprint()
+			.await?
+			.unwrap();
+		assert_ne!(fingerprint, changed);
+		Ok(())
+	}
+
 	// Test the synchronous `Read` implementation
 	#[test]
 	fn read_sync_and_read_trait() -> Result<()> {
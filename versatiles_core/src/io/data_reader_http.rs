@@ -56,17 +56,37 @@ impl DataReaderHttp {
 	///
 	/// * A Result containing a boxed `DataReaderHttp` or an error.
 	pub fn from_url(url: Url) -> Result<Box<DataReaderHttp>> {
+		Self::from_url_with_timeout(url, None)
+	}
+
+	/// Creates a `DataReaderHttp` from a URL, failing every request that takes longer than `timeout`.
+	///
+	/// The timeout covers the whole request, from connecting until the response body has been read,
+	/// so a hung server produces an error instead of stalling the reader.
+	///
+	/// # Arguments
+	///
+	/// * `url` - The URL of the HTTP(S) endpoint.
+	/// * `timeout` - The maximum duration of a single request, or `None` for no limit.
+	///
+	/// # Returns
+	///
+	/// * A Result containing a boxed `DataReaderHttp` or an error.
+	pub fn from_url_with_timeout(url: Url, timeout: Option<Duration>) -> Result<Box<DataReaderHttp>> {
 		match url.scheme() {
 			"http" | "https" => (),
 			_ => bail!("url has wrong scheme {url}"),
 		}
 
-		let client = Client::builder()
+		let mut builder = Client::builder()
 			.tcp_keepalive(Duration::from_secs(600))
 			.connection_verbose(true)
 			.danger_accept_invalid_certs(true)
-			.use_rustls_tls()
-			.build()?;
+			.use_rustls_tls();
+		if let Some(timeout) = timeout {
+			builder = builder.timeout(timeout);
+		}
+		let client = builder.build()?;
 
 		Ok(Box::new(DataReaderHttp {
 			client,
@@ -144,7 +164,7 @@ impl DataReaderTrait for DataReaderHttp {
 
 		let bytes = response.bytes().await.with_context(ctx)?;
 
-		Ok(Blob::from(&*bytes))
+		Ok(Blob::from(bytes))
 
 		//.with_context(|| format!("while reading {} (range {range_val})", self.url))
 	}
@@ -163,7 +183,7 @@ impl DataReaderTrait for DataReaderHttp {
 			bail!("expected successful response, got {status}, {}", ctx());
 		}
 		let bytes = response.bytes().await.with_context(ctx)?;
-		Ok(Blob::from(&*bytes))
+		Ok(Blob::from(bytes))
 	}
 
 	/// Gets the name of the data source.
@@ -174,6 +194,44 @@ impl DataReaderTrait for DataReaderHttp {
 	fn get_name(&self) -> &str {
 		&self.name
 	}
+
+	/// Identifies the current version of the resource by the `ETag`, `Last-Modified` and
+	/// `Content-Length` headers of a `HEAD` request.
+	///
+	/// # Returns
+	///
+	/// * A Result containing the fingerprint, or `None` if the server sends none of these headers.
+	#[context("while requesting the fingerprint of url '{}'", self.url)]
+	async fn fingerprint(&self) -> Result<Option<String>> {
+		let response = self.client.head(self.url.clone()).send().await?;
+		if !response.status().is_success() {
+			bail!("expected successful response, got {}", response.status());
+		}
+		let headers = response.headers();
+		let parts = ["etag", "last-modified", "content-length"]
+			.into_iter()
+			.filter_map(|key| {
+				let value = headers.get(key)?.to_str().ok()?;
+				Some(format!("{key}: {value}"))
+			})
+			.collect::<Vec<_>>();
+		Ok(if parts.is_empty() { None } else { Some(parts.join(", ")) })
+	}
+
+	/// Gets the size of the resource from the `Content-Length` header of a `HEAD` request.
+	///
+	/// # Returns
+	///
+	/// * A Result containing the size, or `None` if the server does not send it.
+	#[context("while requesting the size of url '{}'", self.url)]
+	async fn get_size(&self) -> Result<Option<u64>> {
+		let response = self.client.head(self.url.clone()).send().await?;
+		if !response.status().is_success() {
+			bail!("expected successful response, got {}", response.status());
+		}
+		let size = response.headers().get("content-length");
+		Ok(size.and_then(|value| value.to_str().ok()?.parse().ok()))
+	}
 }
 
 #[cfg(test)]
@@ -193,6 +251,10 @@ mod tests {
 		// Test with an invalid URL
 		let data_reader_http = DataReaderHttp::from_url(invalid_url);
 		assert!(data_reader_http.is_err());
+
+		// Test with a timeout
+		let url = Url::parse("https://www.example.com").unwrap();
+		assert!(DataReaderHttp::from_url_with_timeout(url, Some(Duration::from_secs(5))).is_ok());
 	}
 
 	async fn read_range_helper(url: &str, offset: u64, length: u64, expected: &str) -> Result<()> {
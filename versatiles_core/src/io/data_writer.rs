@@ -58,6 +58,7 @@
 
 use crate::{Blob, ByteRange};
 use anyhow::Result;
+use std::io::Write;
 
 /// A trait for writing data to various destinations.
 ///
@@ -66,6 +67,10 @@ use anyhow::Result;
 /// - `write_start`: Writes data from the start of the writer.
 /// - `get_position`: Gets the current write position.
 /// - `set_position`: Sets the write position.
+///
+/// # Provided Methods
+/// - `append_with`: Appends data that is streamed into the writer.
+/// - `flush`: Writes buffered data to the destination.
 pub trait DataWriterTrait: Send + Sync {
 	/// Appends data to the writer.
 	///
@@ -78,6 +83,24 @@ pub trait DataWriterTrait: Send + Sync {
 	/// * A Result containing a `ByteRange` indicating the position and length of the appended data, or an error.
 	fn append(&mut self, blob: &Blob) -> Result<ByteRange>;
 
+	/// Appends data that `write` streams into the writer, e.g. while compressing a large tile.
+	///
+	/// The default implementation collects the data in memory and calls [`append`](Self::append).
+	/// Writers should override it if they can stream directly into their destination.
+	///
+	/// # Arguments
+	///
+	/// * `write` - A function that writes the data to the given `Write`.
+	///
+	/// # Returns
+	///
+	/// * A Result containing a `ByteRange` indicating the position and length of the appended data, or an error.
+	fn append_with(&mut self, write: &mut dyn FnMut(&mut dyn Write) -> Result<()>) -> Result<ByteRange> {
+		let mut buffer = Vec::new();
+		write(&mut buffer)?;
+		self.append(&Blob::from(buffer))
+	}
+
 	/// Writes data from the start of the writer.
 	///
 	/// # Arguments
@@ -106,4 +129,16 @@ pub trait DataWriterTrait: Send + Sync {
 	///
 	/// * A Result indicating success or an error.
 	fn set_position(&mut self, position: u64) -> Result<()>;
+
+	/// Writes buffered data to the destination.
+	///
+	/// Writers buffering their output also flush it when they are dropped, but errors are lost then.
+	/// The default implementation does nothing.
+	///
+	/// # Returns
+	///
+	/// * A Result indicating success or an error.
+	fn flush(&mut self) -> Result<()> {
+		Ok(())
+	}
 }
@@ -138,6 +138,24 @@ impl DataWriterTrait for DataWriterBlob {
 		Ok(ByteRange::new(pos, len as u64))
 	}
 
+	/// Streams data directly into the writer, without buffering it in memory first.
+	///
+	/// # Arguments
+	///
+	/// * `write` - A function that writes the data to the given `Write`.
+	///
+	/// # Returns
+	///
+	/// * A Result containing a `ByteRange` indicating the position and length of the appended data, or an error.
+	#[context("while appending streamed data to DataWriterBlob")]
+	fn append_with(&mut self, write: &mut dyn FnMut(&mut dyn Write) -> Result<()>) -> Result<ByteRange> {
+		let pos = self.writer.stream_position()?;
+		write(&mut self.writer)?;
+		let len = self.writer.stream_position()? - pos;
+
+		Ok(ByteRange::new(pos, len))
+	}
+
 	/// Writes data from the start of the writer.
 	///
 	/// # Arguments
@@ -194,6 +212,21 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_append_with() -> Result<()> {
+		let mut writer = DataWriterBlob::new()?;
+		writer.append(&Blob::from(vec![1, 2]))?;
+
+		let range = writer.append_with(&mut |w| {
+			w.write_all(&[3, 4])?;
+			w.write_all(&[5])?;
+			Ok(())
+		})?;
+		assert_eq!(range, ByteRange::new(2, 3));
+		assert_eq!(writer.as_slice(), &[1, 2, 3, 4, 5]);
+		Ok(())
+	}
+
 	#[test]
 	fn test_append() -> Result<()> {
 		let mut writer = DataWriterBlob::new()?;
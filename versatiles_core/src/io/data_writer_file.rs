@@ -33,7 +33,7 @@
 //! ```
 
 use super::DataWriterTrait;
-use crate::{Blob, ByteRange};
+use crate::{Blob, ByteRange, progress::ConversionMetrics};
 use anyhow::{Result, ensure};
 use async_trait::async_trait;
 use std::{
@@ -46,6 +46,7 @@ use versatiles_derive::context;
 /// A struct that provides writing capabilities to a file.
 pub struct DataWriterFile {
 	writer: BufWriter<File>,
+	metrics: ConversionMetrics,
 }
 
 impl DataWriterFile {
@@ -64,8 +65,14 @@ impl DataWriterFile {
 
 		Ok(DataWriterFile {
 			writer: BufWriter::new(File::create(path)?),
+			metrics: ConversionMetrics::new(),
 		})
 	}
+
+	/// Sets the metrics that count the written bytes, e.g. those of the running conversion.
+	pub fn set_metrics(&mut self, metrics: ConversionMetrics) {
+		self.metrics = metrics;
+	}
 }
 
 #[async_trait]
@@ -83,10 +90,30 @@ impl DataWriterTrait for DataWriterFile {
 	fn append(&mut self, blob: &Blob) -> Result<ByteRange> {
 		let pos = self.writer.stream_position()?;
 		let len = self.writer.write(blob.as_slice())?;
+		self.metrics.add_bytes_written(len as u64);
 
 		Ok(ByteRange::new(pos, len as u64))
 	}
 
+	/// Streams data directly into the file, without buffering it in memory first.
+	///
+	/// # Arguments
+	///
+	/// * `write` - A function that writes the data to the given `Write`.
+	///
+	/// # Returns
+	///
+	/// * A Result containing a `ByteRange` indicating the position and length of the appended data, or an error.
+	#[context("while appending streamed data to file")]
+	fn append_with(&mut self, write: &mut dyn FnMut(&mut dyn Write) -> Result<()>) -> Result<ByteRange> {
+		let pos = self.writer.stream_position()?;
+		write(&mut self.writer)?;
+		let len = self.writer.stream_position()? - pos;
+		self.metrics.add_bytes_written(len);
+
+		Ok(ByteRange::new(pos, len))
+	}
+
 	/// Writes data from the start of the file.
 	///
 	/// # Arguments
@@ -129,6 +156,12 @@ impl DataWriterTrait for DataWriterFile {
 		self.writer.seek(SeekFrom::Start(position))?;
 		Ok(())
 	}
+
+	/// Writes buffered data to the file.
+	#[context("while flushing file")]
+	fn flush(&mut self) -> Result<()> {
+		Ok(self.writer.flush()?)
+	}
 }
 
 #[cfg(test)]
@@ -164,6 +197,25 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_append_with() -> Result<()> {
+		let temp = NamedTempFile::new("test3")?;
+		let path = temp.path();
+		let mut writer = DataWriterFile::from_path(path)?;
+
+		writer.append(&Blob::from(vec![1, 2]))?;
+		let range = writer.append_with(&mut |w| {
+			w.write_all(&[3, 4, 5])?;
+			Ok(())
+		})?;
+		assert_eq!(range, ByteRange::new(2, 3));
+		assert_eq!(writer.get_position()?, 5);
+		drop(writer);
+
+		assert_eq!(std::fs::read(path)?, vec![1, 2, 3, 4, 5]);
+		Ok(())
+	}
+
 	#[test]
 	fn test_write_start_and_append() -> Result<()> {
 		let temp = NamedTempFile::new("test2")?;
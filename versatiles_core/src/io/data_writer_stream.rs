@@ -0,0 +1,227 @@
+//! This module provides functionality for writing data to non-seekable streams, like stdout or a pipe.
+//!
+//! # Overview
+//!
+//! The `DataWriterStream` struct wraps any [`Write`] and implements the `DataWriterTrait`.
+//! Data can only be appended: the stream keeps track of the number of bytes written, so appended
+//! data still gets its `ByteRange`, but `write_start` and moving the write position fail.
+//! Writers that need to seek, e.g. to update a header at the end, can not write to a stream.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use versatiles_core::{io::{DataWriterStream, DataWriterTrait}, Blob, ByteRange};
+//! use anyhow::Result;
+//!
+//! fn main() -> Result<()> {
+//!     let mut writer = DataWriterStream::new(Vec::new());
+//!
+//!     // Appending data
+//!     assert_eq!(writer.append(&Blob::from(vec![1, 2, 3, 4]))?, ByteRange::new(0, 4));
+//!     assert_eq!(writer.append(&Blob::from(vec![5, 6]))?, ByteRange::new(4, 2));
+//!
+//!     // Writing data from the start is not possible
+//!     assert!(writer.write_start(&Blob::from(vec![7])).is_err());
+//!
+//!     assert_eq!(writer.into_inner()?, vec![1, 2, 3, 4, 5, 6]);
+//!     Ok(())
+//! }
+//! ```
+
+use super::DataWriterTrait;
+use crate::{Blob, ByteRange, progress::ConversionMetrics};
+use anyhow::{Result, bail};
+use std::io::{self, BufWriter, Stdout, Write};
+use versatiles_derive::context;
+
+/// A struct that provides append-only writing capabilities to a non-seekable stream.
+pub struct DataWriterStream<W: Write> {
+	writer: BufWriter<W>,
+	position: u64,
+	metrics: ConversionMetrics,
+}
+
+impl<W: Write> DataWriterStream<W> {
+	/// Creates a `DataWriterStream` writing to `writer`.
+	///
+	/// # Arguments
+	///
+	/// * `writer` - The stream to write to.
+	pub fn new(writer: W) -> DataWriterStream<W> {
+		DataWriterStream {
+			writer: BufWriter::new(writer),
+			position: 0,
+			metrics: ConversionMetrics::new(),
+		}
+	}
+
+	/// Sets the metrics that count the written bytes, e.g. those of the running conversion.
+	pub fn set_metrics(&mut self, metrics: ConversionMetrics) {
+		self.metrics = metrics;
+	}
+
+	/// Flushes all buffered data and returns the underlying stream.
+	///
+	/// # Returns
+	///
+	/// * A Result containing the stream or an error.
+	#[context("while flushing stream")]
+	pub fn into_inner(self) -> Result<W> {
+		self.writer.into_inner().map_err(|error| error.into_error().into())
+	}
+}
+
+impl DataWriterStream<Stdout> {
+	/// Creates a `DataWriterStream` writing to stdout.
+	#[must_use]
+	pub fn stdout() -> DataWriterStream<Stdout> {
+		DataWriterStream::new(io::stdout())
+	}
+}
+
+impl<W: Write + Send + Sync> DataWriterTrait for DataWriterStream<W> {
+	/// Appends data to the stream.
+	///
+	/// # Arguments
+	///
+	/// * `blob` - A reference to the `Blob` to append.
+	///
+	/// # Returns
+	///
+	/// * A Result containing a `ByteRange` indicating the position and length of the appended data, or an error.
+	#[context("while appending {} bytes to stream", blob.len())]
+	fn append(&mut self, blob: &Blob) -> Result<ByteRange> {
+		let pos = self.position;
+		self.writer.write_all(blob.as_slice())?;
+		self.position += blob.len();
+		self.metrics.add_bytes_written(blob.len());
+
+		Ok(ByteRange::new(pos, blob.len()))
+	}
+
+	/// Streams data directly into the stream, without buffering it in memory first.
+	///
+	/// # Arguments
+	///
+	/// * `write` - A function that writes the data to the given `Write`.
+	///
+	/// # Returns
+	///
+	/// * A Result containing a `ByteRange` indicating the position and length of the appended data, or an error.
+	#[context("while appending streamed data to stream")]
+	fn append_with(&mut self, write: &mut dyn FnMut(&mut dyn Write) -> Result<()>) -> Result<ByteRange> {
+		let pos = self.position;
+		let mut counter = CountingWriter {
+			writer: &mut self.writer,
+			count: 0,
+		};
+		write(&mut counter)?;
+		let len = counter.count;
+		self.position += len;
+		self.metrics.add_bytes_written(len);
+
+		Ok(ByteRange::new(pos, len))
+	}
+
+	/// Fails, because a stream can not be rewound.
+	fn write_start(&mut self, _blob: &Blob) -> Result<()> {
+		bail!("can not write to the start of a stream, because it is not seekable")
+	}
+
+	/// Gets the number of bytes written so far.
+	///
+	/// # Returns
+	///
+	/// * A Result containing the current write position in bytes.
+	fn get_position(&mut self) -> Result<u64> {
+		Ok(self.position)
+	}
+
+	/// Fails, unless `position` is the current write position, because a stream is not seekable.
+	///
+	/// # Arguments
+	///
+	/// * `position` - The position to set in bytes.
+	///
+	/// # Returns
+	///
+	/// * A Result indicating success or an error.
+	fn set_position(&mut self, position: u64) -> Result<()> {
+		if position != self.position {
+			bail!(
+				"can not move the write position of a stream from {} to {position}, because it is not seekable",
+				self.position
+			);
+		}
+		Ok(())
+	}
+
+	/// Flushes the buffered data into the stream.
+	#[context("while flushing stream")]
+	fn flush(&mut self) -> Result<()> {
+		Ok(self.writer.flush()?)
+	}
+}
+
+/// Counts the bytes written through it.
+struct CountingWriter<'a, W: Write> {
+	writer: &'a mut W,
+	count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<'_, W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let len = self.writer.write(buf)?;
+		self.count += len as u64;
+		Ok(len)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.writer.flush()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_append() -> Result<()> {
+		let mut writer = DataWriterStream::new(Vec::new());
+		assert_eq!(writer.append(&Blob::from(vec![1, 2, 3]))?, ByteRange::new(0, 3));
+		assert_eq!(writer.append(&Blob::from(vec![4]))?, ByteRange::new(3, 1));
+		assert_eq!(writer.get_position()?, 4);
+		assert_eq!(writer.into_inner()?, vec![1, 2, 3, 4]);
+		Ok(())
+	}
+
+	#[test]
+	fn test_append_with() -> Result<()> {
+		let mut writer = DataWriterStream::new(Vec::new());
+		writer.append(&Blob::from(vec![1, 2]))?;
+
+		let range = writer.append_with(&mut |w| {
+			w.write_all(&[3, 4])?;
+			w.write_all(&[5])?;
+			Ok(())
+		})?;
+		assert_eq!(range, ByteRange::new(2, 3));
+		assert_eq!(writer.get_position()?, 5);
+		assert_eq!(writer.into_inner()?, vec![1, 2, 3, 4, 5]);
+		Ok(())
+	}
+
+	#[test]
+	fn test_not_seekable() -> Result<()> {
+		let mut writer = DataWriterStream::new(Vec::new());
+		writer.append(&Blob::from(vec![1, 2, 3]))?;
+
+		assert!(writer.write_start(&Blob::from(vec![4])).is_err());
+		assert!(writer.set_position(0).is_err());
+		writer.set_position(3)?;
+
+		writer.flush()?;
+		assert_eq!(writer.into_inner()?, vec![1, 2, 3]);
+		Ok(())
+	}
+}
@@ -4,7 +4,8 @@
 //!
 //! The module provides a unified interface for importing all the necessary components for reading and writing data
 //! in various formats and from various sources. It includes readers and writers for blobs, files, HTTP sources (if enabled),
-//! and more. The value readers and writers support different byte orders and offer functionality for handling various data types.
+//! non-seekable streams like stdout, and more. The value readers and writers support different byte orders and offer functionality for handling various data types.
+//! The [`pbf`] module implements the Protocol Buffers wire format used by vector tiles and other formats.
 //!
 //! # Examples
 //!
@@ -19,11 +20,14 @@
 
 mod data_reader;
 mod data_reader_blob;
+mod data_reader_cached;
 mod data_reader_file;
 mod data_reader_http;
 mod data_writer;
 mod data_writer_blob;
 mod data_writer_file;
+mod data_writer_stream;
+pub mod pbf;
 mod value_reader;
 mod value_reader_blob;
 mod value_reader_file;
@@ -34,11 +38,13 @@ mod value_writer_file;
 
 pub use data_reader::*;
 pub use data_reader_blob::*;
+pub use data_reader_cached::*;
 pub use data_reader_file::*;
 pub use data_reader_http::*;
 pub use data_writer::*;
 pub use data_writer_blob::*;
 pub use data_writer_file::*;
+pub use data_writer_stream::*;
 pub use value_reader::*;
 pub use value_reader_blob::*;
 pub use value_reader_file::*;
@@ -0,0 +1,530 @@
+//! A minimal implementation of the Protocol Buffers wire format.
+//!
+//! # Overview
+//!
+//! This module contains the building blocks for reading and writing protobuf messages without
+//! generated code: varints, zigzag encoding, field keys, length-delimited fields and packed
+//! repeated fields. It is used by the Mapbox Vector Tile code and the [`ValueReader`](super::ValueReader)
+//! and [`ValueWriter`](super::ValueWriter) traits, and can also be used directly.
+//!
+//! [`PbfReader`] reads from a byte slice without copying, [`PbfWriter`] writes into a growing buffer.
+//! Malformed input, e.g. truncated fields, overlong varints or unknown wire types, results in an error
+//! and never in a panic.
+//!
+//! See <https://protobuf.dev/programming-guides/encoding/> for the specification.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use versatiles_core::io::pbf::{PbfReader, PbfWriter, WireType};
+//! use anyhow::Result;
+//!
+//! fn main() -> Result<()> {
+//!     let mut writer = PbfWriter::new();
+//!     writer.write_key(1, WireType::Len);
+//!     writer.write_string("hello");
+//!     writer.write_key(2, WireType::Len);
+//!     writer.write_packed_varints([3, 270, 86942]);
+//!     let blob = writer.into_blob();
+//!
+//!     let mut reader = PbfReader::new(blob.as_slice());
+//!     while reader.has_remaining() {
+//!         match reader.read_key()? {
+//!             (1, WireType::Len) => assert_eq!(reader.read_string()?, "hello"),
+//!             (2, WireType::Len) => assert_eq!(reader.read_packed_varints()?, vec![3, 270, 86942]),
+//!             (_, wire_type) => reader.skip(wire_type)?,
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::Blob;
+use anyhow::{Context, Result, bail};
+use std::io::{Read, Write};
+
+/// The maximum number of bytes of an encoded 64-bit varint.
+const MAX_VARINT_LENGTH: usize = 10;
+
+/// The wire type of a protobuf field, stored in the lowest three bits of the field key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WireType {
+	/// `int32`, `int64`, `uint32`, `uint64`, `sint32`, `sint64`, `bool`, `enum`
+	Varint = 0,
+	/// `fixed64`, `sfixed64`, `double`
+	I64 = 1,
+	/// `string`, `bytes`, embedded messages, packed repeated fields
+	Len = 2,
+	/// `fixed32`, `sfixed32`, `float`
+	I32 = 5,
+}
+
+impl WireType {
+	/// Returns the numeric value used in field keys.
+	#[must_use]
+	pub fn as_u8(self) -> u8 {
+		self as u8
+	}
+}
+
+impl TryFrom<u8> for WireType {
+	type Error = anyhow::Error;
+
+	fn try_from(value: u8) -> Result<Self> {
+		Ok(match value {
+			0 => WireType::Varint,
+			1 => WireType::I64,
+			2 => WireType::Len,
+			5 => WireType::I32,
+			3 | 4 => bail!("deprecated protobuf group wire type ({value}) is not supported"),
+			_ => bail!("unknown protobuf wire type ({value})"),
+		})
+	}
+}
+
+/// Encodes a signed integer with zigzag encoding, so that small negative values result in short varints.
+#[must_use]
+pub fn zigzag_encode(value: i64) -> u64 {
+	((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Decodes a zigzag-encoded integer.
+#[must_use]
+pub fn zigzag_decode(value: u64) -> i64 {
+	((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// Combines a field number and a wire type into a field key.
+#[must_use]
+pub fn encode_key(field_number: u32, wire_type: u8) -> u64 {
+	(u64::from(field_number) << 3) | u64::from(wire_type & 0x07)
+}
+
+/// Splits a field key into field number and wire type.
+///
+/// # Errors
+/// Returns an error if the field number does not fit into a `u32`.
+pub fn decode_key(key: u64) -> Result<(u32, u8)> {
+	let field_number = u32::try_from(key >> 3).context("protobuf field number is too large")?;
+	Ok((field_number, (key & 0x07) as u8))
+}
+
+/// Reads a varint from `reader`.
+///
+/// # Errors
+/// Returns an error if reading fails or if the varint is longer than 10 bytes or exceeds 64 bits.
+pub fn read_varint<R: Read + ?Sized>(reader: &mut R) -> Result<u64> {
+	let mut value = 0;
+	for index in 0..MAX_VARINT_LENGTH {
+		let mut byte = [0u8];
+		reader.read_exact(&mut byte)?;
+		let byte = byte[0];
+		if index == MAX_VARINT_LENGTH - 1 && byte > 1 {
+			bail!("Varint too long");
+		}
+		value |= u64::from(byte & 0x7F) << (7 * index);
+		if byte & 0x80 == 0 {
+			return Ok(value);
+		}
+	}
+	unreachable!()
+}
+
+/// Writes `value` as a varint to `writer`.
+///
+/// # Errors
+/// Returns an error if writing fails.
+pub fn write_varint<W: Write + ?Sized>(writer: &mut W, mut value: u64) -> Result<()> {
+	let mut buffer = [0u8; MAX_VARINT_LENGTH];
+	let mut length = 0;
+	while value >= 0x80 {
+		buffer[length] = ((value as u8) & 0x7F) | 0x80;
+		value >>= 7;
+		length += 1;
+	}
+	buffer[length] = value as u8;
+	writer.write_all(&buffer[..=length])?;
+	Ok(())
+}
+
+/// Reads protobuf fields from a byte slice without copying.
+#[derive(Clone, Debug)]
+pub struct PbfReader<'a> {
+	data: &'a [u8],
+	position: usize,
+}
+
+impl<'a> PbfReader<'a> {
+	/// Creates a reader for a protobuf message.
+	#[must_use]
+	pub fn new(data: &'a [u8]) -> Self {
+		Self { data, position: 0 }
+	}
+
+	/// Returns the current read position in bytes.
+	#[must_use]
+	pub fn position(&self) -> usize {
+		self.position
+	}
+
+	/// Returns `true` if there are bytes left to read.
+	#[must_use]
+	pub fn has_remaining(&self) -> bool {
+		self.position < self.data.len()
+	}
+
+	/// Reads the next `length` bytes.
+	fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+		let end = self.position.checked_add(length).filter(|end| *end <= self.data.len());
+		let Some(end) = end else {
+			bail!(
+				"protobuf field of {length} bytes at position {} exceeds the message length of {} bytes",
+				self.position,
+				self.data.len()
+			);
+		};
+		let slice = &self.data[self.position..end];
+		self.position = end;
+		Ok(slice)
+	}
+
+	/// Reads a field key and returns field number and wire type.
+	///
+	/// # Errors
+	/// Returns an error if the key is truncated, or the wire type is unknown.
+	pub fn read_key(&mut self) -> Result<(u32, WireType)> {
+		let (field_number, wire_type) = decode_key(self.read_varint()?)?;
+		Ok((field_number, WireType::try_from(wire_type)?))
+	}
+
+	/// Reads an unsigned varint.
+	///
+	/// # Errors
+	/// Returns an error if the varint is truncated or too long.
+	pub fn read_varint(&mut self) -> Result<u64> {
+		let mut slice = &self.data[self.position..];
+		let value = read_varint(&mut slice).context("Failed to read protobuf varint")?;
+		self.position = self.data.len() - slice.len();
+		Ok(value)
+	}
+
+	/// Reads a zigzag-encoded signed varint.
+	///
+	/// # Errors
+	/// Returns an error if the varint is truncated or too long.
+	pub fn read_svarint(&mut self) -> Result<i64> {
+		Ok(zigzag_decode(self.read_varint()?))
+	}
+
+	/// Reads a little-endian `fixed32` value.
+	///
+	/// # Errors
+	/// Returns an error if the value is truncated.
+	pub fn read_fixed32(&mut self) -> Result<u32> {
+		Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+	}
+
+	/// Reads a little-endian `fixed64` value.
+	///
+	/// # Errors
+	/// Returns an error if the value is truncated.
+	pub fn read_fixed64(&mut self) -> Result<u64> {
+		Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+	}
+
+	/// Reads a length-delimited field and returns its content.
+	///
+	/// # Errors
+	/// Returns an error if the length is invalid or the content is truncated.
+	pub fn read_bytes(&mut self) -> Result<&'a [u8]> {
+		let length = usize::try_from(self.read_varint()?)?;
+		self.take(length)
+	}
+
+	/// Reads a length-delimited UTF-8 string.
+	///
+	/// # Errors
+	/// Returns an error if the field is truncated or not valid UTF-8.
+	pub fn read_string(&mut self) -> Result<&'a str> {
+		Ok(std::str::from_utf8(self.read_bytes()?)?)
+	}
+
+	/// Reads an embedded message and returns a reader for it.
+	///
+	/// # Errors
+	/// Returns an error if the field is truncated.
+	pub fn read_message(&mut self) -> Result<PbfReader<'a>> {
+		Ok(PbfReader::new(self.read_bytes()?))
+	}
+
+	/// Reads a packed repeated field of varints.
+	///
+	/// # Errors
+	/// Returns an error if the field or one of its values is truncated.
+	pub fn read_packed_varints(&mut self) -> Result<Vec<u64>> {
+		let mut reader = self.read_message()?;
+		let mut values = Vec::new();
+		while reader.has_remaining() {
+			values.push(reader.read_varint()?);
+		}
+		Ok(values)
+	}
+
+	/// Skips the value of a field with the given wire type.
+	///
+	/// # Errors
+	/// Returns an error if the value is truncated.
+	pub fn skip(&mut self, wire_type: WireType) -> Result<()> {
+		match wire_type {
+			WireType::Varint => {
+				self.read_varint()?;
+			}
+			WireType::I64 => {
+				self.take(8)?;
+			}
+			WireType::Len => {
+				self.read_bytes()?;
+			}
+			WireType::I32 => {
+				self.take(4)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Writes protobuf fields into a growing buffer.
+#[derive(Clone, Debug, Default)]
+pub struct PbfWriter {
+	data: Vec<u8>,
+}
+
+impl PbfWriter {
+	/// Creates an empty writer.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the number of bytes written so far.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.data.len()
+	}
+
+	/// Returns `true` if nothing has been written yet.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.data.is_empty()
+	}
+
+	/// Writes a field key.
+	pub fn write_key(&mut self, field_number: u32, wire_type: WireType) {
+		self.write_varint(encode_key(field_number, wire_type.as_u8()));
+	}
+
+	/// Writes an unsigned varint.
+	pub fn write_varint(&mut self, value: u64) {
+		write_varint(&mut self.data, value).expect("writing into a Vec never fails");
+	}
+
+	/// Writes a zigzag-encoded signed varint.
+	pub fn write_svarint(&mut self, value: i64) {
+		self.write_varint(zigzag_encode(value));
+	}
+
+	/// Writes a little-endian `fixed32` value.
+	pub fn write_fixed32(&mut self, value: u32) {
+		self.data.extend_from_slice(&value.to_le_bytes());
+	}
+
+	/// Writes a little-endian `fixed64` value.
+	pub fn write_fixed64(&mut self, value: u64) {
+		self.data.extend_from_slice(&value.to_le_bytes());
+	}
+
+	/// Writes a length-delimited field, e.g. bytes or an embedded message.
+	pub fn write_bytes(&mut self, bytes: &[u8]) {
+		self.write_varint(bytes.len() as u64);
+		self.data.extend_from_slice(bytes);
+	}
+
+	/// Writes a length-delimited UTF-8 string.
+	pub fn write_string(&mut self, text: &str) {
+		self.write_bytes(text.as_bytes());
+	}
+
+	/// Writes a packed repeated field of varints.
+	pub fn write_packed_varints(&mut self, values: impl IntoIterator<Item = u64>) {
+		let mut packed = PbfWriter::new();
+		for value in values {
+			packed.write_varint(value);
+		}
+		self.write_bytes(&packed.data);
+	}
+
+	/// Returns the written bytes.
+	#[must_use]
+	pub fn as_slice(&self) -> &[u8] {
+		&self.data
+	}
+
+	/// Converts the written bytes into a [`Blob`].
+	#[must_use]
+	pub fn into_blob(self) -> Blob {
+		Blob::from(self.data)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn encode(value: u64) -> Vec<u8> {
+		let mut buffer = Vec::new();
+		write_varint(&mut buffer, value).unwrap();
+		buffer
+	}
+
+	#[test]
+	fn varint_roundtrip() {
+		for (value, bytes) in [
+			(0, vec![0x00]),
+			(1, vec![0x01]),
+			(127, vec![0x7F]),
+			(128, vec![0x80, 0x01]),
+			(300, vec![0xAC, 0x02]),
+			(
+				u64::MAX,
+				vec![0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01],
+			),
+		] {
+			assert_eq!(encode(value), bytes);
+			assert_eq!(read_varint(&mut bytes.as_slice()).unwrap(), value);
+		}
+	}
+
+	#[test]
+	fn varint_errors() {
+		// truncated
+		assert!(read_varint(&mut [0x80, 0x80].as_slice()).is_err());
+		// more than 64 bits
+		assert!(read_varint(&mut [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02].as_slice()).is_err());
+		// more than 10 bytes
+		assert!(read_varint(&mut [0x80; 11].as_slice()).is_err());
+	}
+
+	#[test]
+	fn zigzag() {
+		for (value, encoded) in [
+			(0, 0),
+			(-1, 1),
+			(1, 2),
+			(-75, 149),
+			(i64::MAX, u64::MAX - 1),
+			(i64::MIN, u64::MAX),
+		] {
+			assert_eq!(zigzag_encode(value), encoded);
+			assert_eq!(zigzag_decode(encoded), value);
+		}
+	}
+
+	#[test]
+	fn keys() {
+		assert_eq!(encode_key(1, 0), 0x08);
+		assert_eq!(encode_key(15, 2), 0x7A);
+		assert_eq!(decode_key(0x7A).unwrap(), (15, 2));
+		assert!(decode_key(u64::MAX).is_err());
+		assert!(WireType::try_from(3).is_err());
+		assert!(WireType::try_from(7).is_err());
+		assert_eq!(WireType::try_from(5).unwrap(), WireType::I32);
+	}
+
+	#[test]
+	fn message_roundtrip() -> Result<()> {
+		let mut inner = PbfWriter::new();
+		inner.write_key(1, WireType::Varint);
+		inner.write_svarint(-3);
+
+		let mut writer = PbfWriter::new();
+		writer.write_key(1, WireType::Varint);
+		writer.write_varint(150);
+		writer.write_key(2, WireType::Len);
+		writer.write_string("hello");
+		writer.write_key(3, WireType::I32);
+		writer.write_fixed32(7);
+		writer.write_key(4, WireType::I64);
+		writer.write_fixed64(8);
+		writer.write_key(5, WireType::Len);
+		writer.write_packed_varints([1, 300, u64::MAX]);
+		writer.write_key(6, WireType::Len);
+		writer.write_bytes(inner.as_slice());
+		assert_eq!(&writer.as_slice()[0..3], &[0x08, 0x96, 0x01]);
+
+		let mut reader = PbfReader::new(writer.as_slice());
+		assert_eq!(reader.read_key()?, (1, WireType::Varint));
+		assert_eq!(reader.read_varint()?, 150);
+		assert_eq!(reader.read_key()?, (2, WireType::Len));
+		assert_eq!(reader.read_string()?, "hello");
+		assert_eq!(reader.read_key()?, (3, WireType::I32));
+		assert_eq!(reader.read_fixed32()?, 7);
+		assert_eq!(reader.read_key()?, (4, WireType::I64));
+		assert_eq!(reader.read_fixed64()?, 8);
+		assert_eq!(reader.read_key()?, (5, WireType::Len));
+		assert_eq!(reader.read_packed_varints()?, vec![1, 300, u64::MAX]);
+		assert_eq!(reader.read_key()?, (6, WireType::Len));
+		let mut message = reader.read_message()?;
+		assert_eq!(message.read_key()?, (1, WireType::Varint));
+		assert_eq!(message.read_svarint()?, -3);
+		assert!(!message.has_remaining());
+		assert!(!reader.has_remaining());
+		assert_eq!(reader.position(), writer.len());
+		Ok(())
+	}
+
+	#[test]
+	fn skip_fields() -> Result<()> {
+		let mut writer = PbfWriter::new();
+		for wire_type in [WireType::Varint, WireType::I64, WireType::Len, WireType::I32] {
+			writer.write_key(9, wire_type);
+			match wire_type {
+				WireType::Varint => writer.write_varint(1 << 40),
+				WireType::I64 => writer.write_fixed64(1),
+				WireType::Len => writer.write_string("skipped"),
+				WireType::I32 => writer.write_fixed32(1),
+			}
+		}
+		writer.write_key(1, WireType::Varint);
+		writer.write_varint(42);
+
+		let mut reader = PbfReader::new(writer.as_slice());
+		loop {
+			match reader.read_key()? {
+				(1, WireType::Varint) => break,
+				(_, wire_type) => reader.skip(wire_type)?,
+			}
+		}
+		assert_eq!(reader.read_varint()?, 42);
+		Ok(())
+	}
+
+	#[test]
+	fn truncated_input() {
+		// length-delimited field claims 5 bytes, but only 2 are present
+		let mut reader = PbfReader::new(&[0x05, b'h', b'i']);
+		assert!(reader.read_bytes().is_err());
+
+		// length that overflows usize arithmetic
+		let mut reader = PbfReader::new(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]);
+		assert!(reader.read_bytes().is_err());
+
+		let mut reader = PbfReader::new(&[0x01, 0x02]);
+		assert!(reader.read_fixed32().is_err());
+
+		let mut reader = PbfReader::new(&[0x02, 0x80, 0x80]);
+		assert!(reader.read_packed_varints().is_err());
+
+		let mut reader = PbfReader::new(&[0x02, 0xFF, 0xFE]);
+		assert!(reader.read_string().is_err());
+	}
+}
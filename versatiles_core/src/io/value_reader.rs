@@ -31,8 +31,9 @@
 // }
 // ```
 
+use super::pbf;
 use crate::{Blob, ByteRange};
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result};
 use byteorder::{ByteOrder, ReadBytesExt};
 use std::io::{Read, Seek};
 
@@ -99,22 +100,9 @@ pub trait ValueReader<'a, E: ByteOrder + 'a> {
 	/// The decoded `u64` value.
 	///
 	/// # Errors
-	/// Returns an error if reading fails or the varint is too long (more than 70 bits).
+	/// Returns an error if reading fails or the varint is too long (more than 64 bits).
 	fn read_varint(&mut self) -> Result<u64> {
-		let mut value = 0;
-		let mut shift = 0;
-		loop {
-			let byte = self.get_reader().read_u8()?;
-			value |= (u64::from(byte) & 0x7F) << shift;
-			if byte & 0x80 == 0 {
-				break;
-			}
-			shift += 7;
-			if shift >= 70 {
-				bail!("Varint too long");
-			}
-		}
-		Ok(value)
+		pbf::read_varint(self.get_reader())
 	}
 
 	/// Reads a variable-length signed integer (zigzag-encoded varint) from the data.
@@ -125,8 +113,7 @@ pub trait ValueReader<'a, E: ByteOrder + 'a> {
 	/// # Errors
 	/// Returns an error if reading the underlying varint fails.
 	fn read_svarint(&mut self) -> Result<i64> {
-		let sint_value = self.read_varint()? as i64;
-		Ok((sint_value >> 1) ^ -(sint_value & 1))
+		Ok(pbf::zigzag_decode(self.read_varint()?))
 	}
 
 	/// Reads a 32-bit floating point number from the data.
@@ -239,9 +226,9 @@ pub trait ValueReader<'a, E: ByteOrder + 'a> {
 	/// # Errors
 	/// Returns an error if reading fails.
 	fn read_blob(&mut self, length: u64) -> Result<Blob> {
-		let mut blob = Blob::new_sized(length as usize);
-		self.get_reader().read_exact(blob.as_mut_slice())?;
-		Ok(blob)
+		let mut buffer = vec![0u8; length as usize];
+		self.get_reader().read_exact(&mut buffer)?;
+		Ok(Blob::from(buffer))
 	}
 
 	/// Reads a UTF-8 encoded string of the specified length.
@@ -280,10 +267,10 @@ pub trait ValueReader<'a, E: ByteOrder + 'a> {
 	/// A tuple `(field_number, wire_type)` where `field_number` is a `u32` and `wire_type` is a `u8`.
 	///
 	/// # Errors
-	/// Returns an error if reading the varint fails.
+	/// Returns an error if reading the varint fails or the field number does not fit into a `u32`.
 	fn read_pbf_key(&mut self) -> Result<(u32, u8)> {
 		let value = self.read_varint().context("Failed to read varint for PBF key")?;
-		Ok(((value >> 3) as u32, (value & 0x07) as u8))
+		pbf::decode_key(value)
 	}
 
 	/// Returns a sub-reader limited to the given length.
@@ -327,18 +314,17 @@ pub trait ValueReader<'a, E: ByteOrder + 'a> {
 	/// A vector of `u32` values.
 	///
 	/// # Errors
-	/// Returns an error if reading the sub-reader or any varint fails.
+	/// Returns an error if reading the sub-reader or any varint fails, or a value does not fit into a `u32`.
 	fn read_pbf_packed_uint32(&mut self) -> Result<Vec<u32>> {
 		let mut reader = self
 			.get_pbf_sub_reader()
 			.context("Failed to get PBF sub-reader for packed uint32")?;
 		let mut values = Vec::new();
 		while reader.has_remaining() {
-			values.push(
-				reader
-					.read_varint()
-					.context("Failed to read varint for packed uint32")? as u32,
-			);
+			let value = reader
+				.read_varint()
+				.context("Failed to read varint for packed uint32")?;
+			values.push(u32::try_from(value).context("Packed uint32 value is too large")?);
 		}
 		drop(reader);
 		Ok(values)
@@ -52,7 +52,7 @@
 //! }
 //! ```
 
-use super::ValueWriterBlob;
+use super::{ValueWriterBlob, pbf};
 use crate::{Blob, ByteRange};
 use anyhow::{Context, Result};
 use byteorder::{ByteOrder, WriteBytesExt};
@@ -101,13 +101,8 @@ pub trait ValueWriter<E: ByteOrder> {
 	/// # Errors
 	///
 	/// Returns an error if writing to the underlying writer fails.
-	fn write_varint(&mut self, mut value: u64) -> Result<()> {
-		while value >= 0x80 {
-			self.get_writer().write_all(&[((value as u8) & 0x7F) | 0x80])?;
-			value >>= 7;
-		}
-		self.get_writer().write_all(&[value as u8])?;
-		Ok(())
+	fn write_varint(&mut self, value: u64) -> Result<()> {
+		pbf::write_varint(self.get_writer(), value)
 	}
 
 	/// Writes a signed variable-length integer (zigzag-encoded) to the writer.
@@ -120,7 +115,7 @@ pub trait ValueWriter<E: ByteOrder> {
 	///
 	/// Returns an error if writing to the underlying writer fails.
 	fn write_svarint(&mut self, value: i64) -> Result<()> {
-		self.write_varint(((value << 1) ^ (value >> 63)) as u64)
+		self.write_varint(pbf::zigzag_encode(value))
 	}
 
 	/// Writes an 8-bit unsigned integer to the writer.
@@ -270,7 +265,7 @@ pub trait ValueWriter<E: ByteOrder> {
 	/// Returns an error if writing to the underlying writer fails.
 	fn write_pbf_key(&mut self, field_number: u32, wire_type: u8) -> Result<()> {
 		self
-			.write_varint((u64::from(field_number) << 3) | u64::from(wire_type))
+			.write_varint(pbf::encode_key(field_number, wire_type))
 			.context("Failed to write PBF key")
 	}
 
@@ -7,7 +7,12 @@
 //! - percentage
 //! - speed (items/sec)
 //! - ETA
+//! - stage and weighted total progress, if stages are defined
+//!
+//! Alternatively, every update is written as a JSON line, see [`ConversionMetrics::set_progress_json`].
 
+use super::{ConversionMetrics, ProgressStages, StageHandle, StageInfo};
+use crate::json::{JsonObject, JsonValue};
 use std::time::{Duration, Instant};
 
 pub struct Inner {
@@ -17,6 +22,7 @@ pub struct Inner {
 	pub start: Instant,
 	pub finished: bool,
 	pub last_draw: Instant,
+	pub stage: Option<StageHandle>,
 }
 
 impl Inner {
@@ -26,9 +32,26 @@ impl Inner {
 		}
 		self.last_draw = Instant::now();
 
+		let stage = self
+			.stage
+			.and_then(|handle| ProgressStages::update(handle, self.pos, self.len));
+
+		if ConversionMetrics::is_progress_json() {
+			let line = format_json(&self.message, self.pos, self.len, self.finished, stage.as_ref());
+			self.output(&format!("{line}\n"));
+			return;
+		}
+
 		let len = self.len.max(1); // avoid div by zero
 		let pos = self.pos.min(len);
-		let msg = &self.message;
+		let msg = match &stage {
+			Some(stage) => format!("[{}/{}] {}", stage.index + 1, stage.count, self.message),
+			None => self.message.clone(),
+		};
+		let total_str = match &stage {
+			Some(stage) => format!(" total {:>3}%", (stage.total_fraction * 100.0).floor() as u64),
+			None => String::new(),
+		};
 		let elapsed = self.start.elapsed();
 		let per_sec = if elapsed.as_secs_f64() > 0.0 {
 			pos as f64 / elapsed.as_secs_f64()
@@ -45,9 +68,10 @@ impl Inner {
 		let per_sec_str = format_rate(per_sec);
 		let eta_str = format_eta(Duration::from_secs_f64(eta_secs));
 
-		let get_line = |bar_str| format!("{msg}▕{bar_str}▏{pos}/{len} ({percent:>3}%) {per_sec_str:>5} {eta_str:>5}");
+		let get_line =
+			|bar_str| format!("{msg}▕{bar_str}▏{pos}/{len} ({percent:>3}%) {per_sec_str:>5} {eta_str:>5}{total_str}");
 
-		let available_bar_width = terminal_width() - get_line("").chars().count();
+		let available_bar_width = terminal_width().saturating_sub(get_line("").chars().count());
 		let bar_str = make_bar(pos, len, available_bar_width);
 		let line = get_line(&bar_str);
 
@@ -55,10 +79,18 @@ impl Inner {
 		self.write(&format!("\r\x1b[2K{line}"));
 	}
 
-	#[allow(unused_variables)]
+	/// Writes terminal output of the bar. Skipped if progress is reported as JSON.
 	pub fn write(&mut self, line: &str) {
+		if !ConversionMetrics::is_progress_json() {
+			self.output(line);
+		}
+	}
+
+	#[allow(unused_variables)]
+	fn output(&self, line: &str) {
+		let hidden = ConversionMetrics::is_progress_bar_hidden();
 		#[cfg(not(any(test, feature = "test")))]
-		{
+		if !hidden {
 			use std::io::Write;
 			let mut output = std::io::stderr();
 			write!(output, "{line}").unwrap();
@@ -76,10 +108,28 @@ impl Default for Inner {
 			start: Instant::now(),
 			finished: false,
 			last_draw: Instant::now(),
+			stage: None,
 		}
 	}
 }
 
+fn format_json(message: &str, pos: u64, len: u64, finished: bool, stage: Option<&StageInfo>) -> String {
+	let percent = |fraction: f64| JsonValue::Number((fraction * 1000.0).floor() / 10.0);
+	let mut object = JsonObject::new();
+	object.set("message", message);
+	object.set("position", JsonValue::Number(pos as f64));
+	object.set("length", JsonValue::Number(len as f64));
+	object.set("percent", percent(pos as f64 / len.max(1) as f64));
+	object.set("finished", finished);
+	if let Some(stage) = stage {
+		object.set("stage", stage.name.as_str());
+		object.set("stage_index", JsonValue::Number(stage.index as f64));
+		object.set("stage_count", JsonValue::Number(stage.count as f64));
+		object.set("total_percent", percent(stage.total_fraction));
+	}
+	object.stringify()
+}
+
 // Determine terminal width (rough heuristic: prefer $COLUMNS; fallback 80)
 fn terminal_width() -> usize {
 	if let Some((width, _)) = terminal_size::terminal_size() {
@@ -189,6 +239,24 @@ mod tests {
 		assert_eq!(inner.message, "Test");
 	}
 
+	#[test]
+	fn test_format_json() {
+		assert_eq!(
+			format_json("scanning", 25, 200, false, None),
+			r#"{"finished":false,"length":200,"message":"scanning","percent":12.5,"position":25}"#
+		);
+		let stage = StageInfo {
+			name: "convert".to_string(),
+			index: 1,
+			count: 3,
+			total_fraction: 0.4,
+		};
+		assert_eq!(
+			format_json("converting tiles", 10, 10, true, Some(&stage)),
+			r#"{"finished":true,"length":10,"message":"converting tiles","percent":100,"position":10,"stage":"convert","stage_count":3,"stage_index":1,"total_percent":40}"#
+		);
+	}
+
 	#[rstest]
 	#[case(0.0, "0/s")]
 	#[case(1.0, "1/s")]
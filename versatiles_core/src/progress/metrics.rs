@@ -0,0 +1,506 @@
+//! Metrics of running tile conversions.
+//!
+//! Readers, streams and writers record what they are doing in a [`ConversionMetrics`]:
+//! tiles processed per zoom level, bytes written, cache hits and transcoded tiles.
+//! Every conversion gets its own instance through its `ProcessingConfig`, so concurrent conversions
+//! don't mix their counts. Each instance also counts into its parent, up to the process-wide instance
+//! returned by [`ConversionMetrics::process`], which e.g. a metrics exporter can observe.
+//!
+//! A user interface can poll [`ConversionMetrics::snapshot`] or receive snapshots at a fixed
+//! interval through the channel returned by [`ConversionMetrics::subscribe`].
+//!
+//! Recording is cheap (a relaxed atomic add per instance), so the counters are always active.
+//!
+//! ```rust
+//! use versatiles_core::progress::*;
+//!
+//! let metrics = ConversionMetrics::new();
+//! metrics.set_level_total(5, 100);
+//! metrics.add_tiles(5, 25);
+//!
+//! let snapshot = metrics.snapshot();
+//! let level = snapshot.levels.iter().find(|l| l.level == 5).unwrap();
+//! assert_eq!(level.done, 25);
+//! ```
+
+use super::{ProgressStages, StagesSnapshot};
+use std::{
+	fmt::{Debug, Display},
+	sync::{
+		Arc, LazyLock,
+		atomic::{AtomicBool, AtomicU64, Ordering},
+	},
+	time::{Duration, Instant},
+};
+use tokio::sync::watch;
+
+/// Number of zoom levels that can be tracked.
+pub const MAX_LEVELS: usize = 32;
+
+static PROCESS: LazyLock<ConversionMetrics> = LazyLock::new(|| ConversionMetrics {
+	counters: Arc::new(Counters::default()),
+	parent: None,
+});
+static WORKERS_BUSY: AtomicU64 = AtomicU64::new(0);
+static PROGRESS_BAR_HIDDEN: AtomicBool = AtomicBool::new(false);
+static PROGRESS_JSON: AtomicBool = AtomicBool::new(false);
+
+/// Progress of a single zoom level.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LevelProgress {
+	pub level: u8,
+	pub done: u64,
+	pub total: u64,
+}
+
+/// Number of format and compression changes that were skipped or performed.
+///
+/// Format and compression changes are skipped when the tile already has the requested format or
+/// compression, so these counts show how much decoding and encoding a conversion avoided.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TileTranscodeStats {
+	/// Format changes skipped, because the tile already had the requested format.
+	pub format_passed: u64,
+	/// Format changes that decoded and re-encoded the tile.
+	pub format_converted: u64,
+	/// Compression changes skipped, because the blob already had the requested compression.
+	pub compression_passed: u64,
+	/// Compression changes that recompressed the blob.
+	pub compression_converted: u64,
+}
+
+impl TileTranscodeStats {
+	/// Returns the changes counted between `earlier` and `self`.
+	#[must_use]
+	pub fn since(&self, earlier: &TileTranscodeStats) -> TileTranscodeStats {
+		TileTranscodeStats {
+			format_passed: self.format_passed.saturating_sub(earlier.format_passed),
+			format_converted: self.format_converted.saturating_sub(earlier.format_converted),
+			compression_passed: self.compression_passed.saturating_sub(earlier.compression_passed),
+			compression_converted: self.compression_converted.saturating_sub(earlier.compression_converted),
+		}
+	}
+
+	/// Returns `true` if no format or compression change was requested.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		*self == TileTranscodeStats::default()
+	}
+}
+
+impl Display for TileTranscodeStats {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"format: {} passed through, {} converted; compression: {} passed through, {} recompressed",
+			self.format_passed, self.format_converted, self.compression_passed, self.compression_converted
+		)
+	}
+}
+
+/// State of all counters at one point in time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+	/// When the snapshot was taken.
+	pub time: Instant,
+	/// Zoom levels that have tiles to process, in ascending order.
+	pub levels: Vec<LevelProgress>,
+	/// Bytes written to the output so far.
+	pub bytes_written: u64,
+	/// Number of workers of the process currently processing a tile.
+	pub workers_busy: u64,
+	/// Number of workers available for parallel processing.
+	pub workers_total: u64,
+	/// Number of tiles served from a cache instead of being processed again.
+	pub cache_hits: u64,
+	/// Format and compression changes that were skipped or performed.
+	pub transcode: TileTranscodeStats,
+	/// Stages of the conversion, see [`ProgressStages`].
+	pub stages: StagesSnapshot,
+}
+
+impl MetricsSnapshot {
+	/// Tiles processed over all levels.
+	#[must_use]
+	pub fn tiles_done(&self) -> u64 {
+		self.levels.iter().map(|l| l.done).sum()
+	}
+
+	/// Tiles to process over all levels.
+	#[must_use]
+	pub fn tiles_total(&self) -> u64 {
+		self.levels.iter().map(|l| l.total).sum()
+	}
+
+	/// Tiles per second and bytes per second between `earlier` and `self`.
+	#[must_use]
+	pub fn rates_since(&self, earlier: &MetricsSnapshot) -> (f64, f64) {
+		let seconds = self.time.saturating_duration_since(earlier.time).as_secs_f64();
+		if seconds <= 0.0 {
+			return (0.0, 0.0);
+		}
+		let tiles = self.tiles_done().saturating_sub(earlier.tiles_done()) as f64;
+		let bytes = self.bytes_written.saturating_sub(earlier.bytes_written) as f64;
+		(tiles / seconds, bytes / seconds)
+	}
+}
+
+/// Counters of a single [`ConversionMetrics`] instance.
+#[derive(Default)]
+struct Counters {
+	tiles_total: [AtomicU64; MAX_LEVELS],
+	tiles_done: [AtomicU64; MAX_LEVELS],
+	bytes_written: AtomicU64,
+	cache_hits: AtomicU64,
+	format_passed: AtomicU64,
+	format_converted: AtomicU64,
+	compression_passed: AtomicU64,
+	compression_converted: AtomicU64,
+}
+
+/// Conversion metrics, shared by cloning. Every recorded value is also counted into the parents.
+#[derive(Clone)]
+pub struct ConversionMetrics {
+	counters: Arc<Counters>,
+	parent: Option<Box<ConversionMetrics>>,
+}
+
+impl ConversionMetrics {
+	/// Creates empty metrics for a conversion, counting into [`ConversionMetrics::process`].
+	#[must_use]
+	pub fn new() -> ConversionMetrics {
+		ConversionMetrics::process().child()
+	}
+
+	/// Returns the metrics of the whole process, which include the counts of all other instances.
+	#[must_use]
+	pub fn process() -> &'static ConversionMetrics {
+		&PROCESS
+	}
+
+	/// Creates empty metrics that also count into `self`, e.g. for one of several outputs of a conversion.
+	#[must_use]
+	pub fn child(&self) -> ConversionMetrics {
+		ConversionMetrics {
+			counters: Arc::new(Counters::default()),
+			parent: Some(Box::new(self.clone())),
+		}
+	}
+
+	/// Calls `record` with the counters of `self` and of all parents.
+	fn record(&self, record: impl Fn(&Counters)) {
+		let mut metrics = Some(self);
+		while let Some(m) = metrics {
+			record(&m.counters);
+			metrics = m.parent.as_deref();
+		}
+	}
+
+	/// Sets the number of tiles that will be processed at `level`.
+	///
+	/// Parents add the difference to the previous total, so they sum up the totals of their children.
+	pub fn set_level_total(&self, level: u8, count: u64) {
+		let Some(counter) = self.counters.tiles_total.get(level as usize) else {
+			return;
+		};
+		let difference = count.wrapping_sub(counter.swap(count, Ordering::Relaxed));
+		let mut parent = self.parent.as_deref();
+		while let Some(p) = parent {
+			p.counters.tiles_total[level as usize].fetch_add(difference, Ordering::Relaxed);
+			parent = p.parent.as_deref();
+		}
+	}
+
+	/// Counts `count` processed tiles at `level`.
+	pub fn add_tiles(&self, level: u8, count: u64) {
+		if (level as usize) < MAX_LEVELS {
+			self.record(|c| {
+				c.tiles_done[level as usize].fetch_add(count, Ordering::Relaxed);
+			});
+		}
+	}
+
+	/// Counts `count` bytes written to the output.
+	pub fn add_bytes_written(&self, count: u64) {
+		self.record(|c| {
+			c.bytes_written.fetch_add(count, Ordering::Relaxed);
+		});
+	}
+
+	/// Counts a tile that was served from a cache.
+	pub fn add_cache_hit(&self) {
+		self.record(|c| {
+			c.cache_hits.fetch_add(1, Ordering::Relaxed);
+		});
+	}
+
+	/// Counts a format change, that was skipped if `passed` is `true`.
+	pub fn count_format(&self, passed: bool) {
+		self.record(|c| {
+			let counter = if passed { &c.format_passed } else { &c.format_converted };
+			counter.fetch_add(1, Ordering::Relaxed);
+		});
+	}
+
+	/// Counts a compression change, that was skipped if `passed` is `true`.
+	pub fn count_compression(&self, passed: bool) {
+		self.record(|c| {
+			let counter = if passed {
+				&c.compression_passed
+			} else {
+				&c.compression_converted
+			};
+			counter.fetch_add(1, Ordering::Relaxed);
+		});
+	}
+
+	/// Marks a worker as busy until the returned guard is dropped.
+	///
+	/// Workers are shared by all conversions, so they are counted for the whole process.
+	#[must_use]
+	pub fn worker_busy() -> WorkerGuard {
+		WORKERS_BUSY.fetch_add(1, Ordering::Relaxed);
+		WorkerGuard(())
+	}
+
+	/// Returns the current values of all counters.
+	#[must_use]
+	pub fn snapshot(&self) -> MetricsSnapshot {
+		let c = &self.counters;
+		let levels = (0..MAX_LEVELS)
+			.filter_map(|level| {
+				let total = c.tiles_total[level].load(Ordering::Relaxed);
+				let done = c.tiles_done[level].load(Ordering::Relaxed);
+				(total > 0 || done > 0).then_some(LevelProgress {
+					level: level as u8,
+					done,
+					total,
+				})
+			})
+			.collect();
+
+		MetricsSnapshot {
+			time: Instant::now(),
+			levels,
+			bytes_written: c.bytes_written.load(Ordering::Relaxed),
+			workers_busy: WORKERS_BUSY.load(Ordering::Relaxed),
+			workers_total: num_cpus::get() as u64,
+			cache_hits: c.cache_hits.load(Ordering::Relaxed),
+			transcode: TileTranscodeStats {
+				format_passed: c.format_passed.load(Ordering::Relaxed),
+				format_converted: c.format_converted.load(Ordering::Relaxed),
+				compression_passed: c.compression_passed.load(Ordering::Relaxed),
+				compression_converted: c.compression_converted.load(Ordering::Relaxed),
+			},
+			stages: ProgressStages::snapshot(),
+		}
+	}
+
+	/// Returns a channel that receives a new snapshot every `interval`.
+	///
+	/// Sampling stops when the receiver is dropped. Must be called within a Tokio runtime.
+	#[must_use]
+	pub fn subscribe(&self, interval: Duration) -> watch::Receiver<MetricsSnapshot> {
+		let (sender, receiver) = watch::channel(self.snapshot());
+		let metrics = self.clone();
+		tokio::spawn(async move {
+			let mut ticker = tokio::time::interval(interval);
+			loop {
+				ticker.tick().await;
+				if sender.send(metrics.snapshot()).is_err() {
+					break;
+				}
+			}
+		});
+		receiver
+	}
+
+	/// Hides the terminal progress bar, e.g. while a different user interface shows the metrics.
+	pub fn set_progress_bar_hidden(hidden: bool) {
+		PROGRESS_BAR_HIDDEN.store(hidden, Ordering::Relaxed);
+	}
+
+	pub(crate) fn is_progress_bar_hidden() -> bool {
+		PROGRESS_BAR_HIDDEN.load(Ordering::Relaxed)
+	}
+
+	/// Reports progress as JSON lines on stderr instead of drawing a progress bar,
+	/// e.g. when the output is parsed by another program.
+	///
+	/// Every line is an object with the fields `message`, `position`, `length`, `percent`
+	/// and `finished`. While stages are defined, `stage`, `stage_index`, `stage_count`
+	/// and the weighted `total_percent` are added.
+	pub fn set_progress_json(json: bool) {
+		PROGRESS_JSON.store(json, Ordering::Relaxed);
+	}
+
+	pub(crate) fn is_progress_json() -> bool {
+		PROGRESS_JSON.load(Ordering::Relaxed)
+	}
+}
+
+impl Default for ConversionMetrics {
+	fn default() -> Self {
+		ConversionMetrics::new()
+	}
+}
+
+impl Debug for ConversionMetrics {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ConversionMetrics").finish_non_exhaustive()
+	}
+}
+
+/// Marks a worker as busy while it exists. Created by [`ConversionMetrics::worker_busy`].
+pub struct WorkerGuard(());
+
+impl Drop for WorkerGuard {
+	fn drop(&mut self) {
+		WORKERS_BUSY.fetch_sub(1, Ordering::Relaxed);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn at(levels: &[(u8, u64)], bytes: u64, time: Instant) -> MetricsSnapshot {
+		MetricsSnapshot {
+			time,
+			levels: levels
+				.iter()
+				.map(|&(level, done)| LevelProgress {
+					level,
+					done,
+					total: 100,
+				})
+				.collect(),
+			bytes_written: bytes,
+			workers_busy: 0,
+			workers_total: 1,
+			cache_hits: 0,
+			transcode: TileTranscodeStats::default(),
+			stages: StagesSnapshot::default(),
+		}
+	}
+
+	#[test]
+	fn snapshot_totals_and_rates() {
+		let start = Instant::now();
+		let earlier = at(&[(3, 10), (4, 0)], 1000, start);
+		let later = at(&[(3, 30), (4, 20)], 5000, start + Duration::from_secs(2));
+
+		assert_eq!(later.tiles_done(), 50);
+		assert_eq!(later.tiles_total(), 200);
+		assert_eq!(later.rates_since(&earlier), (20.0, 2000.0));
+		assert_eq!(earlier.rates_since(&later), (0.0, 0.0));
+	}
+
+	#[test]
+	fn counters_increase() {
+		let metrics = ConversionMetrics::new();
+		metrics.set_level_total(31, 10);
+		metrics.add_tiles(31, 3);
+		metrics.add_tiles(200, 3); // out of range, ignored
+		metrics.add_bytes_written(7);
+		metrics.add_cache_hit();
+		metrics.count_format(true);
+		metrics.count_compression(false);
+
+		let snapshot = metrics.snapshot();
+		assert_eq!(
+			snapshot.levels,
+			[LevelProgress {
+				level: 31,
+				done: 3,
+				total: 10
+			}]
+		);
+		assert_eq!(snapshot.bytes_written, 7);
+		assert_eq!(snapshot.cache_hits, 1);
+		assert_eq!(
+			snapshot.transcode.to_string(),
+			"format: 1 passed through, 0 converted; compression: 0 passed through, 1 recompressed"
+		);
+
+		// other tests may run in parallel, so only lower bounds can be checked
+		let guard = ConversionMetrics::worker_busy();
+		assert!(metrics.snapshot().workers_busy >= 1);
+		drop(guard);
+	}
+
+	#[test]
+	fn children_count_into_parent_only() {
+		let parent = ConversionMetrics::new();
+		let first = parent.child();
+		let second = parent.child();
+
+		first.set_level_total(2, 10);
+		second.set_level_total(2, 6);
+		first.set_level_total(2, 8);
+		first.add_tiles(2, 4);
+		second.add_bytes_written(100);
+
+		assert_eq!(
+			first.snapshot().levels,
+			[LevelProgress {
+				level: 2,
+				done: 4,
+				total: 8
+			}]
+		);
+		assert_eq!(
+			second.snapshot().levels,
+			[LevelProgress {
+				level: 2,
+				done: 0,
+				total: 6
+			}]
+		);
+		assert_eq!(first.snapshot().bytes_written, 0);
+
+		let snapshot = parent.snapshot();
+		assert_eq!(
+			snapshot.levels,
+			[LevelProgress {
+				level: 2,
+				done: 4,
+				total: 14
+			}]
+		);
+		assert_eq!(snapshot.bytes_written, 100);
+		assert!(ConversionMetrics::process().snapshot().bytes_written >= 100);
+	}
+
+	#[test]
+	fn transcode_since_and_display() {
+		let earlier = TileTranscodeStats {
+			format_passed: 1,
+			format_converted: 2,
+			compression_passed: 3,
+			compression_converted: 4,
+		};
+		let later = TileTranscodeStats {
+			format_passed: 11,
+			format_converted: 2,
+			compression_passed: 5,
+			compression_converted: 4,
+		};
+		let diff = later.since(&earlier);
+		assert_eq!(
+			diff.to_string(),
+			"format: 10 passed through, 0 converted; compression: 2 passed through, 0 recompressed"
+		);
+		assert!(!diff.is_empty());
+		assert!(later.since(&later).is_empty());
+	}
+
+	#[tokio::test]
+	async fn subscribe_receives_snapshots() {
+		let metrics = ConversionMetrics::new();
+		let mut receiver = metrics.subscribe(Duration::from_millis(5));
+		metrics.add_cache_hit();
+		receiver.changed().await.unwrap();
+		assert!(receiver.borrow().workers_total >= 1);
+	}
+}
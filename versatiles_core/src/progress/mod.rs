@@ -21,7 +21,12 @@
 //! ```
 
 mod inner;
+mod metrics;
 mod progress_bar;
+mod stages;
+
+pub use metrics::*;
+pub use stages::*;
 
 use progress_bar::ProgressBar;
 
@@ -8,7 +8,7 @@
 //! - speed (items/sec)
 //! - ETA
 
-use super::inner::Inner;
+use super::{ProgressStages, inner::Inner};
 use std::cmp::min;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
@@ -36,6 +36,8 @@ impl Clone for ProgressBar {
 
 impl ProgressBar {
 	/// Initialize the bar with a message and maximum value.
+	///
+	/// If a stage is active (see [`ProgressStages::begin`]), the bar reports into this stage.
 	pub fn new(message: &str, max_value: u64) -> ProgressBar {
 		let progress = ProgressBar {
 			inner: Arc::new(Mutex::new(Inner {
@@ -45,6 +47,7 @@ impl ProgressBar {
 				start: Instant::now(),
 				finished: false,
 				last_draw: Instant::now(),
+				stage: ProgressStages::attach(),
 			})),
 		};
 		progress.inner.try_lock().unwrap().redraw();
@@ -0,0 +1,250 @@
+//! Weighted stages of a long-running operation, e.g. the phases of a tile conversion.
+//!
+//! A conversion does more than converting tiles: it scans the input, converts the tiles,
+//! writes directories and finalizes the output. [`ProgressStages::define`] declares these
+//! stages together with a weight, i.e. their expected share of the total work.
+//! [`ProgressStages::begin`] switches to the next stage.
+//!
+//! Every progress bar created while a stage is active reports into that stage, so the bar
+//! can show the stage (`[2/4] converting tiles`) and the weighted progress of all stages.
+//! If no stages are defined, progress bars behave as before.
+//!
+//! ```rust
+//! use versatiles_core::progress::*;
+//!
+//! ProgressStages::define(&[("scan", 1), ("convert", 8), ("finalize", 1)]);
+//! ProgressStages::begin("scan");
+//! ProgressStages::begin("convert");
+//!
+//! let snapshot = ProgressStages::snapshot();
+//! assert_eq!(snapshot.current_stage().unwrap().name, "convert");
+//! assert_eq!(snapshot.fraction(), 0.1);
+//!
+//! ProgressStages::clear();
+//! ```
+
+use std::sync::{
+	Mutex,
+	atomic::{AtomicU64, Ordering},
+};
+
+static STAGES: Mutex<StagesSnapshot> = Mutex::new(StagesSnapshot {
+	stages: Vec::new(),
+	current: None,
+});
+// incremented by every `define` and `clear`, so progress bars of earlier stages are ignored
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Progress of a single stage.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StageProgress {
+	pub name: String,
+	/// Expected share of the total work, relative to the weights of the other stages.
+	pub weight: u32,
+	pub done: u64,
+	pub total: u64,
+	pub finished: bool,
+}
+
+impl StageProgress {
+	/// Progress of this stage between `0.0` and `1.0`.
+	#[must_use]
+	pub fn fraction(&self) -> f64 {
+		if self.finished {
+			1.0
+		} else if self.total == 0 {
+			0.0
+		} else {
+			(self.done as f64 / self.total as f64).min(1.0)
+		}
+	}
+}
+
+/// State of all stages at one point in time.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StagesSnapshot {
+	/// All defined stages, in order.
+	pub stages: Vec<StageProgress>,
+	/// Index of the active stage.
+	pub current: Option<usize>,
+}
+
+impl StagesSnapshot {
+	/// The active stage, if any.
+	#[must_use]
+	pub fn current_stage(&self) -> Option<&StageProgress> {
+		self.stages.get(self.current?)
+	}
+
+	/// Weighted progress over all stages between `0.0` and `1.0`.
+	#[must_use]
+	pub fn fraction(&self) -> f64 {
+		let weights: u64 = self.stages.iter().map(|s| u64::from(s.weight)).sum();
+		if weights == 0 {
+			return 0.0;
+		}
+		let done: f64 = self.stages.iter().map(|s| f64::from(s.weight) * s.fraction()).sum();
+		done / weights as f64
+	}
+
+	fn begin(&mut self, name: &str) -> bool {
+		let Some(index) = self.stages.iter().position(|s| s.name == name) else {
+			return false;
+		};
+		for stage in &mut self.stages[..index] {
+			stage.finished = true;
+		}
+		self.current = Some(index);
+		true
+	}
+
+	fn update(&mut self, index: usize, done: u64, total: u64) {
+		if let Some(stage) = self.stages.get_mut(index) {
+			stage.done = done;
+			stage.total = total;
+		}
+	}
+}
+
+/// A stage that a progress bar reports into. Created by [`ProgressStages::attach`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct StageHandle {
+	generation: u64,
+	index: usize,
+}
+
+/// The stage of a progress bar, as shown next to the bar.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct StageInfo {
+	pub name: String,
+	pub index: usize,
+	pub count: usize,
+	/// Weighted progress over all stages.
+	pub total_fraction: f64,
+}
+
+/// Global conversion stages. All methods are associated functions on the shared state.
+pub struct ProgressStages;
+
+impl ProgressStages {
+	/// Declares the stages of an operation as `(name, weight)` pairs, replacing earlier stages.
+	///
+	/// No stage is active until [`ProgressStages::begin`] is called.
+	pub fn define(stages: &[(&str, u32)]) {
+		let mut state = STAGES.lock().unwrap();
+		GENERATION.fetch_add(1, Ordering::Relaxed);
+		state.stages = stages
+			.iter()
+			.map(|&(name, weight)| StageProgress {
+				name: name.to_string(),
+				weight,
+				done: 0,
+				total: 0,
+				finished: false,
+			})
+			.collect();
+		state.current = None;
+	}
+
+	/// Activates the stage `name` and marks all previous stages as finished.
+	///
+	/// Returns `false` if no stage with this name is defined, e.g. because the operation
+	/// does not use stages. In this case nothing changes.
+	pub fn begin(name: &str) -> bool {
+		STAGES.lock().unwrap().begin(name)
+	}
+
+	/// Removes all stages, e.g. after the operation has finished.
+	pub fn clear() {
+		let mut state = STAGES.lock().unwrap();
+		GENERATION.fetch_add(1, Ordering::Relaxed);
+		*state = StagesSnapshot::default();
+	}
+
+	/// Returns the current state of all stages.
+	#[must_use]
+	pub fn snapshot() -> StagesSnapshot {
+		STAGES.lock().unwrap().clone()
+	}
+
+	/// Returns the active stage, so that a new progress bar can report into it.
+	pub(crate) fn attach() -> Option<StageHandle> {
+		let state = STAGES.lock().unwrap();
+		state.current.map(|index| StageHandle {
+			generation: GENERATION.load(Ordering::Relaxed),
+			index,
+		})
+	}
+
+	/// Reports the progress of a stage and returns its [`StageInfo`],
+	/// or `None` if the stages have been redefined since the stage was attached.
+	pub(crate) fn update(handle: StageHandle, done: u64, total: u64) -> Option<StageInfo> {
+		let mut state = STAGES.lock().unwrap();
+		if GENERATION.load(Ordering::Relaxed) != handle.generation {
+			return None;
+		}
+		state.update(handle.index, done, total);
+		Some(StageInfo {
+			name: state.stages[handle.index].name.clone(),
+			index: handle.index,
+			count: state.stages.len(),
+			total_fraction: state.fraction(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn stages(list: &[(&str, u32)]) -> StagesSnapshot {
+		StagesSnapshot {
+			stages: list
+				.iter()
+				.map(|&(name, weight)| StageProgress {
+					name: name.to_string(),
+					weight,
+					done: 0,
+					total: 0,
+					finished: false,
+				})
+				.collect(),
+			current: None,
+		}
+	}
+
+	#[test]
+	fn weighted_fraction() {
+		let mut snapshot = stages(&[("scan", 1), ("convert", 8), ("finalize", 1)]);
+		assert_eq!(snapshot.fraction(), 0.0);
+		assert!(snapshot.current_stage().is_none());
+
+		assert!(snapshot.begin("convert"));
+		assert_eq!(snapshot.current_stage().unwrap().name, "convert");
+		assert_eq!(snapshot.fraction(), 0.1);
+
+		snapshot.update(1, 50, 100);
+		assert_eq!(snapshot.fraction(), 0.5);
+
+		assert!(snapshot.begin("finalize"));
+		assert_eq!(snapshot.fraction(), 0.9);
+		assert!(!snapshot.begin("unknown"));
+		assert_eq!(snapshot.current, Some(2));
+
+		assert_eq!(StagesSnapshot::default().fraction(), 0.0);
+	}
+
+	#[test]
+	fn stage_fraction() {
+		let mut stage = stages(&[("scan", 1)]).stages.remove(0);
+		assert_eq!(stage.fraction(), 0.0);
+		stage.total = 4;
+		stage.done = 1;
+		assert_eq!(stage.fraction(), 0.25);
+		stage.done = 10;
+		assert_eq!(stage.fraction(), 1.0);
+		stage.done = 0;
+		stage.finished = true;
+		assert_eq!(stage.fraction(), 1.0);
+	}
+}
@@ -7,7 +7,8 @@ use crate::{
 	TileBBox, TileBBoxPyramid, TraversalOrder, TraversalSize,
 	traversal::processing::{TraversalTranslationStep, translate_traversals},
 };
-use anyhow::Result;
+use anyhow::{Result, bail};
+use std::fmt::Display;
 use versatiles_derive::context;
 
 #[derive(Clone, PartialEq)]
@@ -28,11 +29,11 @@ impl Traversal {
 	///
 	/// # Parameters
 	/// - `order`: the `TraversalOrder` (e.g., depth-first, Hilbert).
-	/// - `min_size`: minimum block size in tiles (power of two).
-	/// - `max_size`: maximum block size in tiles (power of two).
+	/// - `min_size`: minimum block size in tiles, usually a power of two.
+	/// - `max_size`: maximum block size in tiles, usually a power of two.
 	///
 	/// # Errors
-	/// Returns an error if size parameters are invalid (not powers of two or out of range).
+	/// Returns an error if size parameters are invalid (zero or out of range).
 	#[context("while creating Traversal with order {order:?}, min_size {min_size}, max_size {max_size}")]
 	pub fn new(order: TraversalOrder, min_size: u32, max_size: u32) -> Result<Traversal> {
 		Ok(Traversal {
@@ -88,21 +89,61 @@ impl Traversal {
 	/// Modify this `Traversal` to be the intersection with another.
 	///
 	/// Combines size and order; errors if the order or sizes cannot intersect.
-	#[context("while intersecting Traversal {:?} with {:?}", self.order, other.order)]
+	/// On error, this `Traversal` is left unchanged.
+	#[context("while intersecting {self:?} with {other:?}")]
 	pub fn intersect(&mut self, other: &Traversal) -> Result<()> {
-		self.order.intersect(&other.order)?;
-		self.size.intersect(&other.size)?;
+		let order = self.order.get_intersected(&other.order)?;
+		let size = self.size.get_intersected(&other.size)?;
+		self.order = order;
+		self.size = size;
 		Ok(())
 	}
 
 	/// Return a new `Traversal` that is the intersection of this and another, without modifying either.
-	#[context("while computing intersected Traversal between {:?} and {:?}", self.order, other.order)]
+	#[context("while computing intersected Traversal between {self:?} and {other:?}")]
 	pub fn get_intersected(&self, other: &Traversal) -> Result<Traversal> {
 		let mut result = self.clone();
 		result.intersect(other)?;
 		Ok(result)
 	}
 
+	/// Intersect the traversals required by several named sources, e.g. the inputs of an operation.
+	///
+	/// Unlike repeated calls of [`Traversal::intersect`], this checks all sources before failing:
+	/// the error lists every source whose traversal conflicts with the sources before it,
+	/// together with the traversals and names of those sources.
+	///
+	/// # Errors
+	/// Returns an error if any traversals cannot intersect.
+	pub fn intersect_all<'a, N: Display>(sources: impl IntoIterator<Item = (N, &'a Traversal)>) -> Result<Traversal> {
+		let mut result = Traversal::new_any();
+		let mut required_by: Vec<String> = Vec::new();
+		let mut conflicts: Vec<String> = Vec::new();
+
+		for (name, traversal) in sources {
+			if let Ok(intersected) = result.get_intersected(traversal) {
+				result = intersected;
+				if *traversal != Traversal::ANY {
+					required_by.push(name.to_string());
+				}
+			} else if required_by.is_empty() {
+				conflicts.push(format!(
+					"{name} requires {traversal:?}, which conflicts with {result:?}"
+				));
+			} else {
+				conflicts.push(format!(
+					"{name} requires {traversal:?}, which conflicts with {result:?} required by {}",
+					required_by.join(", ")
+				));
+			}
+		}
+
+		if !conflicts.is_empty() {
+			bail!("incompatible traversals: {}", conflicts.join("; "));
+		}
+		Ok(result)
+	}
+
 	/// Traverse the tile pyramid, returning all `TileBBox` in traversal order.
 	///
 	/// Generates bounding boxes at each level, groups them by block size,
@@ -348,4 +389,47 @@ mod tests {
 		assert_eq!(got.order(), &TraversalOrder::PMTiles);
 		assert_eq!(got.max_size().unwrap(), 16);
 	}
+
+	#[test]
+	fn intersect_error_keeps_traversal_unchanged() {
+		let mut t1 = Traversal::new(TraversalOrder::AnyOrder, 1, 4).unwrap();
+		let t2 = Traversal::new(TraversalOrder::DepthFirst, 16, 64).unwrap();
+		let error = t1.intersect(&t2).unwrap_err();
+		assert_eq!(
+			error.chain().map(ToString::to_string).collect::<Vec<_>>(),
+			[
+				"while intersecting Traversal(AnyOrder, min-size: 1, max-size: 4) with Traversal(DepthFirst, min-size: 16, max-size: 64)",
+				"Non-overlapping traversal sizes: TraversalSize (1..4) and TraversalSize (16..64)"
+			]
+		);
+		assert_eq!(t1, Traversal::new(TraversalOrder::AnyOrder, 1, 4).unwrap());
+	}
+
+	#[test]
+	fn intersect_all_merges_named_traversals() {
+		let t1 = Traversal::new_any_size(1, 16).unwrap();
+		let t2 = Traversal::new(TraversalOrder::PMTiles, 4, 256).unwrap();
+		let result = Traversal::intersect_all([("a", &Traversal::ANY), ("b", &t1), ("c", &t2)]).unwrap();
+		assert_eq!(result, Traversal::new(TraversalOrder::PMTiles, 4, 16).unwrap());
+	}
+
+	#[test]
+	fn intersect_all_reports_all_conflicts() {
+		let depth_first = Traversal::new(TraversalOrder::DepthFirst, 1, 256).unwrap();
+		let pmtiles = Traversal::new(TraversalOrder::PMTiles, 1, 64).unwrap();
+		let large = Traversal::new_any_size(512, 512).unwrap();
+		let error = Traversal::intersect_all([
+			("'a'", &Traversal::ANY),
+			("'b'", &depth_first),
+			("'c'", &pmtiles),
+			("'d'", &large),
+		])
+		.unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"incompatible traversals: \
+			'c' requires Traversal(PMTiles, min-size: 1, max-size: 64), which conflicts with Traversal(DepthFirst, min-size: 1, max-size: 256) required by 'b'; \
+			'd' requires Traversal(AnyOrder, min-size: 512, max-size: 512), which conflicts with Traversal(DepthFirst, min-size: 1, max-size: 256) required by 'b'"
+		);
+	}
 }
@@ -28,6 +28,7 @@ impl TraversalOrder {
 	///
 	/// * `bboxes` – mutable slice of tile bounding boxes to sort.
 	/// * `size` – block size used to compute quadtree coordinates for `DepthFirst`.
+	///   Blocks of other sizes than powers of two are ordered by their block index.
 	pub fn sort_bboxes(&self, bboxes: &mut Vec<TileBBox>, size: u32) {
 		use TraversalOrder::*;
 		match self {
@@ -57,9 +57,10 @@ pub fn translate_traversals(
 	}
 
 	if traversal_write.order() == &TraversalOrder::AnyOrder {
-		#[allow(clippy::collapsible_if)]
-		if traversal_read.size.max_size()? <= traversal_write.size.min_size()? {
-			let write_size = traversal_write.size.min_size()?;
+		let read_size = traversal_read.size.max_size()?;
+		let write_size = traversal_write.size.min_size()?;
+		// read blocks must nest into write blocks, which is always true for powers of two
+		if read_size <= write_size && write_size.is_multiple_of(read_size) {
 			let read_bboxes = traversal_read.traverse_pyramid(pyramid)?;
 
 			use TraversalTranslationStep::*;
@@ -81,7 +82,7 @@ pub fn translate_traversals(
 			verify_steps(
 				&steps,
 				*traversal_read.order(),
-				traversal_read.size.max_size()?,
+				read_size,
 				*traversal_write.order(),
 				write_size,
 				pyramid,
@@ -403,4 +404,21 @@ mod tests {
 			]
 		);
 	}
+
+	#[test]
+	fn translate_other_sizes() {
+		let pyramid = TileBBoxPyramid::from_geo_bbox(13, 13, &GeoBBox::new(12.0, 13.0, 14.0, 15.0).unwrap());
+		let read = Traversal::new_any_size(10, 10).unwrap();
+
+		// blocks of 10×10 tiles are read as they are
+		let steps = translate_traversals(&pyramid, &read, &Traversal::ANY).unwrap();
+		assert_eq!(steps.len(), 5 * 6);
+
+		// and nest into blocks of 40×40 tiles
+		let steps = translate_traversals(&pyramid, &read, &Traversal::new_any_size(40, 40).unwrap()).unwrap();
+		assert!(matches!(steps.last(), Some(TraversalTranslationStep::Pop(_, bbox)) if bbox.width() == 40));
+
+		// but not into blocks of 256×256 tiles
+		assert!(translate_traversals(&pyramid, &read, &Traversal::new_any_size(256, 256).unwrap()).is_err());
+	}
 }
@@ -1,40 +1,47 @@
 //! Module for representing and manipulating ranges of block sizes for tile traversal.
 //!
-//! A `TraversalSize` encodes a range of block sizes as powers of two (log2 values),
-//! controlling how many tiles are grouped per traversal block.
+//! A `TraversalSize` encodes a range of block sizes, controlling how many tiles are grouped per traversal block.
+//! A block of size `n` covers `n`×`n` tiles and is aligned to multiples of `n`.
+//!
+//! Block sizes are usually powers of two, so that blocks nest into each other and into the tile pyramid.
+//! Sources that chunk their data differently, e.g. in blocks of 10×10 tiles, can use any size.
+//! Traversals with such sizes can only be translated into traversals with multiples of that size,
+//! see [`translate_traversals`](crate::translate_traversals).
 
 use anyhow::{Result, ensure};
 use versatiles_derive::context;
 
-/// Represents allowed sizes of a block of tiles
-/// The size is represented as log2(size).
-/// For example, `TraversalSize` { max: 6 } represents block of sizes up to 64 tiles.
+/// The largest supported block size.
+const MAX_SIZE: u32 = 1 << 20;
+
+/// Represents allowed sizes of a block of tiles.
+/// For example, `TraversalSize` { min: 1, max: 64 } represents blocks of sizes up to 64×64 tiles.
 #[derive(Clone, PartialEq)]
 pub struct TraversalSize {
-	min: u8,
-	max: u8,
+	min: u32,
+	max: u32,
 }
 
 impl TraversalSize {
 	/// Create a new `TraversalSize` covering sizes from `min_size` up to `max_size`.
 	///
-	/// Both `min_size` and `max_size` must be positive powers of two, and `min_size <= max_size`.
+	/// Both sizes must be positive and `min_size <= max_size`. They do not have to be powers of two.
 	///
 	/// # Errors
-	/// Returns an error if sizes are zero, not powers of two, out of order, or too large.
+	/// Returns an error if sizes are zero, out of order, or too large.
 	#[context("Failed to create TraversalSize")]
 	pub fn new(min_size: u32, max_size: u32) -> Result<TraversalSize> {
 		ensure!(min_size <= max_size, "min size must be less than or equal to max size");
 		Ok(TraversalSize {
-			min: size_to_bits(min_size)?,
-			max: size_to_bits(max_size)?,
+			min: check_size(min_size)?,
+			max: check_size(max_size)?,
 		})
 	}
 
-	/// Return a default `TraversalSize` covering the full range of valid sizes (1 to 2^31).
+	/// Return a default `TraversalSize` covering the full range of valid sizes (1 to 2^20).
 	#[must_use]
 	pub const fn new_default() -> Self {
-		TraversalSize { min: 0, max: 20 }
+		TraversalSize { min: 1, max: MAX_SIZE }
 	}
 
 	/// Shortcut to create a `TraversalSize` with minimum size 1 and maximum size `size`.
@@ -54,8 +61,8 @@ impl TraversalSize {
 	/// Returns an error if the range is empty or `max` is out of bounds.
 	pub fn max_size(&self) -> Result<u32> {
 		ensure!(!self.is_empty(), "TraversalSize is empty: {self:?}");
-		ensure!(self.max <= 20, "TraversalSize max is too large: {self:?}");
-		Ok(1 << self.max)
+		ensure!(self.max <= MAX_SIZE, "TraversalSize max is too large: {self:?}");
+		Ok(self.max)
 	}
 
 	/// Return the minimum allowed block size.
@@ -64,7 +71,7 @@ impl TraversalSize {
 	/// Returns an error if the range is empty.
 	pub fn min_size(&self) -> Result<u32> {
 		ensure!(!self.is_empty(), "TraversalSize is empty: {self:?}");
-		Ok(1 << self.min)
+		Ok(self.min)
 	}
 
 	/// Restrict this range to the intersection with another `TraversalSize`.
@@ -98,21 +105,19 @@ impl std::fmt::Debug for TraversalSize {
 		if self.is_empty() {
 			write!(f, "TraversalSize (empty)")
 		} else {
-			write!(f, "TraversalSize ({}..{})", 1 << self.min, 1 << self.max)
+			write!(f, "TraversalSize ({}..{})", self.min, self.max)
 		}
 	}
 }
 
-/// Convert a block size (power of two) into its log2 (bit) representation.
+/// Check that a block size is positive and not too large.
 ///
 /// # Errors
-/// Returns an error if `size` is zero, not a power of two, or too large.
-fn size_to_bits(size: u32) -> Result<u8> {
+/// Returns an error if `size` is zero or larger than 2^20.
+fn check_size(size: u32) -> Result<u32> {
 	ensure!(size > 0, "Size must be greater than zero");
-	ensure!(size.is_power_of_two(), "Size must be a power of two, but is {size}");
-	let bits = f64::from(size).log2().floor() as u8;
-	ensure!(bits < 32, "Size {size} is too large");
-	Ok(bits)
+	ensure!(size <= MAX_SIZE, "Size {size} is too large");
+	Ok(size)
 }
 
 #[cfg(test)]
@@ -191,13 +196,19 @@ mod tests {
 	}
 
 	#[test]
-	fn test_non_power_of_two_error() {
+	fn test_non_power_of_two_sizes() -> Result<()> {
+		let mut ts = TraversalSize::new(3, 10)?;
+		assert_eq!(ts.max_size()?, 10);
+		ts.intersect(&TraversalSize::new_max(8)?)?;
+		assert_eq!(format!("{ts:?}"), "TraversalSize (3..8)");
+		Ok(())
+	}
+
+	#[test]
+	fn test_too_large_error() {
 		assert_eq!(
-			extract_error_lines(TraversalSize::new(3, 8)),
-			[
-				"Failed to create TraversalSize",
-				"Size must be a power of two, but is 3"
-			]
+			extract_error_lines(TraversalSize::new(1, (1 << 20) + 1)),
+			["Failed to create TraversalSize", "Size 1048577 is too large"]
 		);
 	}
 
@@ -0,0 +1,154 @@
+//! [`Arbitrary`] implementations for the core types, used by property tests and fuzz targets.
+//!
+//! Every generated value satisfies the invariants of its type, e.g. a [`TileCoord`] always lies
+//! inside its zoom level and a [`ByteRange`] never ends beyond `u64::MAX`. This module is only
+//! available with the `arbitrary` feature.
+
+use crate::{ByteRange, GeoBBox, GeoCenter, TileBBox, TileCoord, TileJSON, TileSize};
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+/// Picks a zoom level in `0..=31`.
+fn level(u: &mut Unstructured) -> Result<u8> {
+	u.int_in_range(0..=31)
+}
+
+/// Picks a tile index that is valid at `level`.
+fn tile_index(u: &mut Unstructured, level: u8) -> Result<u32> {
+	u.int_in_range(0..=((1u32 << level) - 1))
+}
+
+/// Picks a coordinate in `-limit..=limit`, quantized to micro degrees like in serialized `TileJSON`.
+fn degrees(u: &mut Unstructured, limit: f64) -> Result<f64> {
+	let limit = (limit * 1e6) as i64;
+	Ok(u.int_in_range(-limit..=limit)? as f64 / 1e6)
+}
+
+impl<'a> Arbitrary<'a> for TileCoord {
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		let level = level(u)?;
+		let x = tile_index(u, level)?;
+		let y = tile_index(u, level)?;
+		Ok(TileCoord::new(level, x, y).unwrap())
+	}
+}
+
+impl<'a> Arbitrary<'a> for TileBBox {
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		let level = level(u)?;
+		if u.ratio(1, 16)? {
+			return Ok(TileBBox::new_empty(level).unwrap());
+		}
+		let [x0, x1, y0, y1] = [
+			tile_index(u, level)?,
+			tile_index(u, level)?,
+			tile_index(u, level)?,
+			tile_index(u, level)?,
+		];
+		Ok(TileBBox::from_min_and_max(level, x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)).unwrap())
+	}
+}
+
+impl<'a> Arbitrary<'a> for ByteRange {
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		let offset = u64::arbitrary(u)?;
+		let length = u.int_in_range(0..=(u64::MAX - offset))?;
+		Ok(ByteRange::new(offset, length))
+	}
+}
+
+impl<'a> Arbitrary<'a> for GeoBBox {
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		Ok(GeoBBox::new_normalized(
+			degrees(u, 180.0)?,
+			degrees(u, 90.0)?,
+			degrees(u, 180.0)?,
+			degrees(u, 90.0)?,
+		))
+	}
+}
+
+impl<'a> Arbitrary<'a> for GeoCenter {
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		Ok(GeoCenter(
+			degrees(u, 180.0)?,
+			degrees(u, 90.0)?,
+			u.int_in_range(0..=30)?,
+		))
+	}
+}
+
+impl<'a> Arbitrary<'a> for TileJSON {
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		let mut tilejson = TileJSON {
+			bounds: Option::arbitrary(u)?,
+			center: Option::arbitrary(u)?,
+			..TileJSON::default()
+		};
+		if u.arbitrary()? {
+			let min_zoom = u.int_in_range(0..=30)?;
+			tilejson.set_min_zoom(min_zoom);
+			tilejson.set_max_zoom(u.int_in_range(min_zoom..=30)?);
+		}
+		if u.arbitrary()? {
+			tilejson.set_string("name", u.arbitrary()?).unwrap();
+		}
+		if u.arbitrary()? {
+			tilejson.tile_size = Some(TileSize::new(*u.choose(&[256, 512])?).unwrap());
+		}
+		Ok(tilejson)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ::arbitrary::Arbitrary;
+	use proptest::prelude::*;
+
+	/// Builds a value of type `T` from random bytes, skipping inputs that are too short.
+	fn build<'a, T: Arbitrary<'a>>(bytes: &'a [u8]) -> Option<T> {
+		T::arbitrary(&mut Unstructured::new(bytes)).ok()
+	}
+
+	fn bytes() -> impl Strategy<Value = Vec<u8>> {
+		prop::collection::vec(any::<u8>(), 0..256)
+	}
+
+	proptest! {
+		#[test]
+		fn tile_coord_quadkey_roundtrip(bytes in bytes()) {
+			if let Some(coord) = build::<TileCoord>(&bytes) {
+				prop_assert_eq!(TileCoord::from_quadkey(&coord.as_quadkey()).unwrap(), coord);
+			}
+		}
+
+		#[test]
+		fn tile_bbox_index_roundtrip(bytes in bytes(), index: u64) {
+			if let Some(bbox) = build::<TileBBox>(&bytes) {
+				if bbox.is_empty() {
+					prop_assert!(bbox.coord_at_index(0).is_err());
+				} else {
+					let index = index % bbox.count_tiles();
+					let coord = bbox.coord_at_index(index).unwrap();
+					prop_assert!(bbox.contains(&coord));
+					prop_assert_eq!(bbox.index_of(&coord).unwrap(), index);
+				}
+			}
+		}
+
+		#[test]
+		fn byte_range_stays_in_bounds(bytes in bytes()) {
+			if let Some(range) = build::<ByteRange>(&bytes) {
+				prop_assert!(range.offset.checked_add(range.length).is_some());
+			}
+		}
+
+		#[test]
+		fn tilejson_string_roundtrip(bytes in bytes()) {
+			if let Some(tilejson) = build::<TileJSON>(&bytes) {
+				let text = tilejson.as_string();
+				prop_assert_eq!(TileJSON::try_from(text.as_str()).unwrap().as_string(), text);
+			}
+		}
+	}
+}
@@ -1,11 +1,14 @@
-//! This module provides the [`Blob`] struct, a wrapper around [`Vec<u8>`] that provides additional methods
+//! This module provides the [`Blob`] struct, a wrapper around [`Bytes`] that provides additional methods
 //! for working with byte data.
 //!
 //! # Overview
 //!
-//! The [`Blob`] struct is a simple wrapper around a `Vec<u8>` that provides methods for creating, accessing,
-//! and manipulating byte data. It includes various utility methods for common operations on byte slices,
-//! such as creating slices, reading ranges, and converting to and from different types.
+//! The [`Blob`] struct is a cheaply cloneable, reference-counted byte buffer. It includes various utility
+//! methods for common operations on byte slices, such as creating slices, reading ranges, and converting to
+//! and from different types.
+//!
+//! Cloning a `Blob`, [`Blob::slice`] and [`Blob::read_range`] do not copy any data; they share the
+//! underlying buffer. Converting from `Vec<u8>`, `String` or [`Bytes`] takes ownership without copying.
 //!
 //! # Examples
 //!
@@ -27,11 +30,12 @@
 
 use super::ByteRange;
 use anyhow::{Result, bail};
+use bytes::Bytes;
 use std::fmt::Debug;
 use std::ops::Range;
 use std::path::Path;
 
-/// A simple wrapper around [`Vec<u8>`] that provides additional methods for working with byte data.
+/// A cheaply cloneable wrapper around [`Bytes`] that provides additional methods for working with byte data.
 ///
 /// # Examples
 ///
@@ -49,7 +53,7 @@ use std::path::Path;
 /// assert_eq!(blob2.as_str(), "ABC");
 /// ```
 #[derive(Clone, PartialEq, Eq)]
-pub struct Blob(Vec<u8>);
+pub struct Blob(Bytes);
 
 #[allow(dead_code)]
 impl Blob {
@@ -66,7 +70,7 @@ impl Blob {
 	/// ```
 	#[must_use]
 	pub fn new_empty() -> Blob {
-		Blob(Vec::new())
+		Blob(Bytes::new())
 	}
 
 	/// Creates a `Blob` with the specified size, filled with zeros.
@@ -90,7 +94,7 @@ impl Blob {
 	/// ```
 	#[must_use]
 	pub fn new_sized(length: usize) -> Blob {
-		Blob(vec![0u8; length])
+		Blob(Bytes::from(vec![0u8; length]))
 	}
 
 	/// Returns a byte slice from the specified `range`.
@@ -121,7 +125,26 @@ impl Blob {
 		&self.0[range]
 	}
 
-	/// Returns a new [`Blob`] containing the bytes in the specified [`ByteRange`].
+	/// Returns a new [`Blob`] sharing the bytes in the specified `range`, without copying.
+	///
+	/// # Panics
+	///
+	/// Panics if the specified range is out of bounds.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use versatiles_core::Blob;
+	///
+	/// let blob = Blob::from("abcdef");
+	/// assert_eq!(blob.slice(1..4).as_str(), "bcd");
+	/// ```
+	#[must_use]
+	pub fn slice(&self, range: Range<usize>) -> Blob {
+		Blob(self.0.slice(range))
+	}
+
+	/// Returns a new [`Blob`] sharing the bytes in the specified [`ByteRange`], without copying.
 	///
 	/// # Arguments
 	///
@@ -146,10 +169,14 @@ impl Blob {
 	/// }
 	/// ```
 	pub fn read_range(&self, range: &ByteRange) -> Result<Blob> {
-		if range.offset + range.length > self.0.len() as u64 {
+		if range
+			.offset
+			.checked_add(range.length)
+			.is_none_or(|end| end > self.0.len() as u64)
+		{
 			bail!("read outside range")
 		}
-		Ok(Blob::from(&self.0[range.as_range_usize()]))
+		Ok(Blob(self.0.slice(range.as_range_usize())))
 	}
 
 	/// Returns a reference to the underlying byte slice.
@@ -167,35 +194,42 @@ impl Blob {
 		self.0.as_ref()
 	}
 
-	/// Returns a mutable reference to the underlying byte slice.
+	/// Consumes this [`Blob`] and returns the bytes as a `Vec<u8>`.
+	///
+	/// The buffer is reused if this `Blob` is its only owner, otherwise the bytes are copied.
 	///
 	/// # Examples
 	///
 	/// ```rust
 	/// use versatiles_core::Blob;
 	///
-	/// let mut blob = Blob::from("abc");
-	/// let slice = blob.as_mut_slice();
-	/// slice[0] = b'z';
-	/// assert_eq!(blob.as_str(), "zbc");
+	/// let blob = Blob::from(&[1, 2, 3]);
+	/// let vec = blob.into_vec();
+	/// assert_eq!(vec, vec![1, 2, 3]);
 	/// ```
-	pub fn as_mut_slice(&mut self) -> &mut [u8] {
-		self.0.as_mut()
+	#[must_use]
+	pub fn into_vec(self) -> Vec<u8> {
+		Vec::from(self.0)
+	}
+
+	/// Returns a reference to the underlying [`Bytes`].
+	#[must_use]
+	pub fn as_bytes(&self) -> &Bytes {
+		&self.0
 	}
 
-	/// Consumes this [`Blob`] and returns the underlying `Vec<u8>`.
+	/// Consumes this [`Blob`] and returns the underlying [`Bytes`], without copying.
 	///
 	/// # Examples
 	///
 	/// ```rust
 	/// use versatiles_core::Blob;
 	///
-	/// let blob = Blob::from(&[1, 2, 3]);
-	/// let vec = blob.into_vec();
-	/// assert_eq!(vec, vec![1, 2, 3]);
+	/// let bytes = Blob::from("abc").into_bytes();
+	/// assert_eq!(&bytes[..], b"abc");
 	/// ```
 	#[must_use]
-	pub fn into_vec(self) -> Vec<u8> {
+	pub fn into_bytes(self) -> Bytes {
 		self.0
 	}
 
@@ -235,7 +269,7 @@ impl Blob {
 	/// ```
 	#[must_use]
 	pub fn into_string(self) -> String {
-		String::from_utf8(self.0).expect("Blob content was not valid UTF-8")
+		String::from_utf8(self.into_vec()).expect("Blob content was not valid UTF-8")
 	}
 
 	/// Returns a hexadecimal string representation of the underlying bytes, with each byte separated by a space.
@@ -366,10 +400,34 @@ impl From<Vec<u8>> for Blob {
 	/// assert_eq!(blob.len(), 3);
 	/// ```
 	fn from(item: Vec<u8>) -> Self {
+		Blob(Bytes::from(item))
+	}
+}
+
+impl From<Bytes> for Blob {
+	/// Converts [`Bytes`] into a [`Blob`], without copying.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use bytes::Bytes;
+	/// use versatiles_core::Blob;
+	///
+	/// let blob = Blob::from(Bytes::from_static(b"abc"));
+	/// assert_eq!(blob.as_str(), "abc");
+	/// ```
+	fn from(item: Bytes) -> Self {
 		Blob(item)
 	}
 }
 
+impl From<Blob> for Bytes {
+	/// Converts a [`Blob`] into [`Bytes`], without copying.
+	fn from(item: Blob) -> Self {
+		item.0
+	}
+}
+
 impl From<&Vec<u8>> for Blob {
 	/// Converts a reference to a `Vec<u8>` into a [`Blob`].
 	///
@@ -383,7 +441,7 @@ impl From<&Vec<u8>> for Blob {
 	/// assert_eq!(blob.len(), 3);
 	/// ```
 	fn from(item: &Vec<u8>) -> Self {
-		Blob(item.clone())
+		Blob(Bytes::copy_from_slice(item))
 	}
 }
 
@@ -400,7 +458,7 @@ impl From<&[u8]> for Blob {
 	/// assert_eq!(blob.len(), 3);
 	/// ```
 	fn from(item: &[u8]) -> Self {
-		Blob(item.to_vec())
+		Blob(Bytes::copy_from_slice(item))
 	}
 }
 
@@ -416,7 +474,7 @@ impl<const N: usize> From<&[u8; N]> for Blob {
 	/// assert_eq!(blob.len(), 3);
 	/// ```
 	fn from(item: &[u8; N]) -> Self {
-		Blob(item.to_vec())
+		Blob(Bytes::copy_from_slice(item))
 	}
 }
 
@@ -432,7 +490,7 @@ impl From<&str> for Blob {
 	/// assert_eq!(blob.len(), 13);
 	/// ```
 	fn from(item: &str) -> Self {
-		Blob(item.as_bytes().to_vec())
+		Blob(Bytes::copy_from_slice(item.as_bytes()))
 	}
 }
 
@@ -449,7 +507,7 @@ impl From<&String> for Blob {
 	/// assert_eq!(blob.as_str(), "Example");
 	/// ```
 	fn from(item: &String) -> Self {
-		Blob(item.as_bytes().to_vec())
+		Blob(Bytes::copy_from_slice(item.as_bytes()))
 	}
 }
 
@@ -466,7 +524,7 @@ impl From<String> for Blob {
 	/// assert_eq!(blob.as_str(), "Data");
 	/// ```
 	fn from(item: String) -> Self {
-		Blob(item.into_bytes())
+		Blob(Bytes::from(item.into_bytes()))
 	}
 }
 
@@ -485,6 +543,12 @@ impl std::fmt::Display for Blob {
 	}
 }
 
+impl AsRef<[u8]> for Blob {
+	fn as_ref(&self) -> &[u8] {
+		&self.0
+	}
+}
+
 impl Default for Blob {
 	/// Returns an empty [`Blob`] by default.
 	fn default() -> Self {
@@ -492,10 +556,6 @@ impl Default for Blob {
 	}
 }
 
-// Allow `Blob` to be used in multithreaded scenarios
-unsafe impl Send for Blob {}
-unsafe impl Sync for Blob {}
-
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -720,10 +780,26 @@ mod tests {
 	}
 
 	#[test]
-	fn test_as_mut_slice() {
-		let mut blob = Blob::from("abc");
-		let slice = blob.as_mut_slice();
-		slice[0] = b'z';
-		assert_eq!(blob.as_str(), "zbc");
+	fn test_slices_share_buffer() -> Result<()> {
+		let blob = Blob::from(vec![0, 1, 2, 3, 4, 5, 6, 7]);
+		let slice = blob.slice(2..6);
+		assert_eq!(slice.as_slice(), &[2, 3, 4, 5]);
+		assert_eq!(slice.as_slice().as_ptr(), blob.get_range(2..6).as_ptr());
+
+		let range = blob.read_range(&ByteRange::new(1, 3))?;
+		assert_eq!(range.as_slice(), &[1, 2, 3]);
+		assert_eq!(range.as_slice().as_ptr(), blob.get_range(1..4).as_ptr());
+
+		let bytes = blob.clone().into_bytes();
+		assert_eq!(bytes.as_ptr(), blob.as_slice().as_ptr());
+		Ok(())
+	}
+
+	#[test]
+	fn test_into_vec_reuses_unique_buffer() {
+		let vec = vec![1, 2, 3];
+		let ptr = vec.as_ptr();
+		let vec = Blob::from(vec).into_vec();
+		assert_eq!(vec.as_ptr(), ptr);
 	}
 }
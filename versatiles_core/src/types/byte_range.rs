@@ -203,7 +203,9 @@ impl fmt::Debug for ByteRange {
 
 impl fmt::Display for ByteRange {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		write!(f, "[{}..={}]", self.offset, self.offset + self.length - 1)
+		// computed in i128, so that empty and overflowing ranges can be displayed, e.g. in error messages
+		let end = i128::from(self.offset) + i128::from(self.length) - 1;
+		write!(f, "[{}..={end}]", self.offset)
 	}
 }
 
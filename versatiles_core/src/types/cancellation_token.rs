@@ -0,0 +1,155 @@
+//! Defines the `CancellationToken` type used for cooperative cancellation of long-running tile streams.
+//!
+//! A token is cheap to clone; all clones share the same state. Once [`CancellationToken::cancel`]
+//! has been called, every clone reports [`CancellationToken::is_cancelled`] and all pending
+//! [`CancellationToken::cancelled`] futures resolve. A [`CancellationDropGuard`] cancels its token when
+//! dropped, e.g. together with the future of an HTTP request whose client has disconnected.
+
+use std::{
+	sync::{
+		Arc,
+		atomic::{AtomicBool, Ordering},
+	},
+	time::Duration,
+};
+use tokio::sync::Notify;
+
+#[derive(Debug, Default)]
+struct Inner {
+	cancelled: AtomicBool,
+	notify: Notify,
+}
+
+/// A shareable flag that signals that running work should stop as soon as possible.
+///
+/// Streams observe the token via [`TileStream::take_until_cancelled`](crate::TileStream::take_until_cancelled),
+/// so producers end gracefully instead of being aborted mid-tile.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+	inner: Arc<Inner>,
+}
+
+impl CancellationToken {
+	/// Creates a new token that is not cancelled.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Marks the token as cancelled and wakes all tasks waiting on [`CancellationToken::cancelled`].
+	///
+	/// Calling this more than once has no further effect.
+	pub fn cancel(&self) {
+		if !self.inner.cancelled.swap(true, Ordering::SeqCst) {
+			self.inner.notify.notify_waiters();
+		}
+	}
+
+	/// Returns `true` if [`CancellationToken::cancel`] has been called on this token or any clone.
+	#[must_use]
+	pub fn is_cancelled(&self) -> bool {
+		self.inner.cancelled.load(Ordering::SeqCst)
+	}
+
+	/// Resolves once the token is cancelled. Returns immediately if it already is.
+	pub async fn cancelled(&self) {
+		loop {
+			// Register interest before checking the flag, so a concurrent `cancel` cannot be missed.
+			let notified = self.inner.notify.notified();
+			if self.is_cancelled() {
+				return;
+			}
+			notified.await;
+		}
+	}
+
+	/// Cancels the token automatically once `duration` has elapsed.
+	///
+	/// Must be called from within a tokio runtime. Useful to put a time limit on a stream.
+	pub fn cancel_after(&self, duration: Duration) {
+		let token = self.clone();
+		tokio::spawn(async move {
+			tokio::time::sleep(duration).await;
+			token.cancel();
+		});
+	}
+
+	/// Returns a guard that cancels this token when it is dropped.
+	///
+	/// Keep the guard alive inside a future to stop the work started by that future once it is dropped.
+	#[must_use]
+	pub fn drop_guard(self) -> CancellationDropGuard {
+		CancellationDropGuard { token: self }
+	}
+}
+
+/// Cancels its [`CancellationToken`] when dropped. Created by [`CancellationToken::drop_guard`].
+#[derive(Debug)]
+pub struct CancellationDropGuard {
+	token: CancellationToken,
+}
+
+impl Drop for CancellationDropGuard {
+	fn drop(&mut self) {
+		self.token.cancel();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn new_token_is_not_cancelled() {
+		let token = CancellationToken::new();
+		assert!(!token.is_cancelled());
+	}
+
+	#[test]
+	fn cancel_is_shared_between_clones() {
+		let token = CancellationToken::new();
+		let clone = token.clone();
+		clone.cancel();
+		assert!(token.is_cancelled());
+		assert!(clone.is_cancelled());
+
+		// cancelling twice is a no-op
+		token.cancel();
+		assert!(token.is_cancelled());
+	}
+
+	#[tokio::test]
+	async fn cancelled_resolves_after_cancel() {
+		let token = CancellationToken::new();
+		let waiter = {
+			let token = token.clone();
+			tokio::spawn(async move { token.cancelled().await })
+		};
+		token.cancel();
+		waiter.await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn cancelled_resolves_immediately_if_already_cancelled() {
+		let token = CancellationToken::new();
+		token.cancel();
+		token.cancelled().await;
+	}
+
+	#[test]
+	fn drop_guard_cancels_on_drop() {
+		let token = CancellationToken::new();
+		let guard = token.clone().drop_guard();
+		assert!(!token.is_cancelled());
+		drop(guard);
+		assert!(token.is_cancelled());
+	}
+
+	#[tokio::test]
+	async fn cancel_after_cancels_token() {
+		let token = CancellationToken::new();
+		token.cancel_after(Duration::from_millis(5));
+		token.cancelled().await;
+		assert!(token.is_cancelled());
+	}
+}
@@ -8,15 +8,19 @@
 //!
 //! ## Supported operations
 //! * Strict constructor [`GeoBBox::new`], plus [`GeoBBox::new_normalized`] for unordered input.
+//! * Parsing of user input like `"[-180,-90,180,90]"` or `"13.08,52.33,13.77,52.68"` via [`str::parse`].
 //! * In‑place clamp to the valid Web‑Mercator domain via [`GeoBBox::limit_to_mercator`].
 //! * Set/return as tuple/array/vec/strings; extend & intersect (mutating and non‑mutating).
 //! * Conversion to EPSG:3857 using the spherical Web‑Mercator formulas.
 //!
 //! ## Antimeridian & empties
-//! * Bounding boxes are **not** wrapped across the antimeridian; all input coordinates must lie within `[-180, 180]` longitude and `[-90, 90]` latitude.
+//! * All input coordinates must lie within `[-180, 180]` longitude and `[-90, 90]` latitude.
+//! * [`GeoBBox::new`] requires `west <= east`. A bounding box that crosses the antimeridian, e.g. around Fiji, is
+//!   created with [`GeoBBox::new_wrapping`] and has `west > east`. [`GeoBBox::split_antimeridian`] splits it into
+//!   two regular boxes, one on each side of the antimeridian.
 //! * Some operations (such as [`intersect`]) may yield an "empty" box with `x_min > x_max` or `y_min > y_max` to signal that there is no overlap.
-use anyhow::{Result, ensure};
-use std::fmt::Debug;
+use anyhow::{Result, bail, ensure};
+use std::{fmt::Debug, str::FromStr};
 use versatiles_derive::context;
 
 /// Maximum latitude permitted by Web‑Mercator (in degrees).
@@ -25,7 +29,7 @@ static MAX_MERC_LAT: f64 = 85.051_128_779_806_59;
 /// Maximum longitude permitted by Web‑Mercator (in degrees).
 static MAX_MERC_LNG: f64 = 180.0;
 /// Spherical Web‑Mercator radius (WGS84 semi‑major axis), in meters.
-static RADIUS: f64 = 6_378_137.0; // meters
+pub const EARTH_RADIUS: f64 = 6_378_137.0;
 
 /// A geographical bounding box (`GeoBBox`) represents a rectangular area on a map
 /// defined by its minimum and maximum longitude (x) and latitude (y) coordinates.
@@ -104,6 +108,34 @@ impl GeoBBox {
 		.checked()
 	}
 
+	/// Like [`GeoBBox::new`], but also accepts `x_min > x_max` for a bounding box that crosses the antimeridian.
+	///
+	/// # Examples
+	/// ```
+	/// use versatiles_core::GeoBBox;
+	///
+	/// let fiji = GeoBBox::new_wrapping(176.0, -21.0, -178.0, -12.0).unwrap();
+	/// assert!(fiji.crosses_antimeridian());
+	/// assert!(GeoBBox::new(176.0, -21.0, -178.0, -12.0).is_err());
+	/// ```
+	pub fn new_wrapping(x_min: f64, y_min: f64, x_max: f64, y_max: f64) -> Result<GeoBBox> {
+		let bbox = GeoBBox {
+			x_min,
+			y_min,
+			x_max,
+			y_max,
+			phantom: (),
+		};
+		if bbox.crosses_antimeridian() {
+			for part in bbox.split_antimeridian() {
+				part.checked()?;
+			}
+			Ok(bbox)
+		} else {
+			bbox.checked()
+		}
+	}
+
 	/// Build a `GeoBBox` from two unconstrained corners `(x0, y0)` and `(x1, y1)`.
 	///
 	/// The inputs may be **unordered**; longitudes and latitudes are sorted into
@@ -188,6 +220,32 @@ impl GeoBBox {
 		self.y_max = self.y_max.max(-MAX_MERC_LAT).min(MAX_MERC_LAT); // north
 	}
 
+	/// Returns `true` if the bounding box crosses the antimeridian, i.e. `x_min > x_max`.
+	#[must_use]
+	pub fn crosses_antimeridian(&self) -> bool {
+		self.x_min > self.x_max
+	}
+
+	/// Splits a bounding box that crosses the antimeridian into its eastern part, ending at 180°,
+	/// and its western part, starting at -180°. Any other bounding box is returned as it is.
+	///
+	/// # Examples
+	/// ```
+	/// use versatiles_core::GeoBBox;
+	///
+	/// let parts = GeoBBox::new_wrapping(176.0, -21.0, -178.0, -12.0).unwrap().split_antimeridian();
+	/// assert_eq!(parts[0].as_tuple(), (176.0, -21.0, 180.0, -12.0));
+	/// assert_eq!(parts[1].as_tuple(), (-180.0, -21.0, -178.0, -12.0));
+	/// ```
+	#[must_use]
+	pub fn split_antimeridian(&self) -> Vec<GeoBBox> {
+		if self.crosses_antimeridian() {
+			vec![GeoBBox { x_max: 180.0, ..*self }, GeoBBox { x_min: -180.0, ..*self }]
+		} else {
+			vec![*self]
+		}
+	}
+
 	/// Returns the bounding box as a `Vec<f64>` in the form `[west, south, east, north]`.
 	///
 	/// # Examples
@@ -271,7 +329,14 @@ impl GeoBBox {
 	/// // west = -12, south = -5, east = 10, north = 6
 	/// assert_eq!(bbox1.as_tuple(), (-12.0, -5.0, 10.0, 6.0));
 	/// ```
+	///
+	/// If one of the boxes crosses the antimeridian, the result is the narrowest range of longitudes covering both,
+	/// which may cross the antimeridian as well.
 	pub fn extend(&mut self, other: &GeoBBox) {
+		if self.crosses_antimeridian() || other.crosses_antimeridian() {
+			self.extend_wrapping(other);
+			return;
+		}
 		self.x_min = self.x_min.min(other.x_min); // min_x
 		self.y_min = self.y_min.min(other.y_min); // min_y
 		self.x_max = self.x_max.max(other.x_max); // max_x
@@ -309,7 +374,15 @@ impl GeoBBox {
 	/// ```
 	///
 	/// **Note:** If there is no overlap between the boxes, the resulting bounding box will be "empty" (with inverted bounds, e.g., `x_min > x_max` or `y_min > y_max`), as per this module's convention.
+	///
+	/// If one of the boxes crosses the antimeridian, their parts on both sides of the antimeridian are intersected.
+	/// If the overlap consists of two separate areas that do not touch the antimeridian, the result covers both.
+	/// If there is no overlap, the result has `y_min > y_max`.
 	pub fn intersect(&mut self, other: &GeoBBox) {
+		if self.crosses_antimeridian() || other.crosses_antimeridian() {
+			self.intersect_wrapping(other);
+			return;
+		}
 		self.x_min = self.x_min.max(other.x_min); // min_x
 		self.y_min = self.y_min.max(other.y_min); // min_y
 		self.x_max = self.x_max.min(other.x_max); // max_x
@@ -339,6 +412,61 @@ impl GeoBBox {
 		self
 	}
 
+	/// Extends a box by another, if at least one of them crosses the antimeridian.
+	/// Longitudes are treated as arcs from west to east, and the shortest arc covering both boxes is used.
+	fn extend_wrapping(&mut self, other: &GeoBBox) {
+		fn width(west: f64, east: f64) -> f64 {
+			if west <= east { east - west } else { east - west + 360.0 }
+		}
+		fn covers(west: f64, east: f64, bbox: &GeoBBox) -> bool {
+			width(west, east) >= 360.0 || width(west, bbox.x_min) + width(bbox.x_min, bbox.x_max) <= width(west, east)
+		}
+
+		let candidates = [
+			(self.x_min, self.x_max),
+			(other.x_min, other.x_max),
+			(self.x_min, other.x_max),
+			(other.x_min, self.x_max),
+		];
+		(self.x_min, self.x_max) = candidates
+			.into_iter()
+			.filter(|&(west, east)| covers(west, east, self) && covers(west, east, other))
+			.min_by(|a, b| width(a.0, a.1).total_cmp(&width(b.0, b.1)))
+			.unwrap_or((-180.0, 180.0));
+		self.y_min = self.y_min.min(other.y_min);
+		self.y_max = self.y_max.max(other.y_max);
+	}
+
+	/// Intersects a box with another, if at least one of them crosses the antimeridian.
+	fn intersect_wrapping(&mut self, other: &GeoBBox) {
+		let halves_self = self.split_antimeridian();
+		let halves_other = other.split_antimeridian();
+		let parts: Vec<GeoBBox> = halves_self
+			.iter()
+			.flat_map(|a| halves_other.iter().map(|b| a.intersected(b)))
+			.filter(|part| part.x_min <= part.x_max && part.y_min <= part.y_max)
+			.collect();
+
+		let east = parts.iter().find(|part| part.x_max == 180.0);
+		let west = parts.iter().find(|part| part.x_min == -180.0);
+		*self = match (parts.as_slice(), east, west) {
+			([], _, _) => {
+				// `x_min > x_max` would look like a box crossing the antimeridian, so signal "empty" with the latitudes
+				let mut empty = halves_self[0].intersected(&halves_other[0]);
+				if empty.y_min <= empty.y_max {
+					std::mem::swap(&mut empty.y_min, &mut empty.y_max);
+				}
+				empty
+			}
+			([part], _, _) => *part,
+			([_, _], Some(east), Some(west)) if east != west => GeoBBox {
+				x_max: west.x_max,
+				..*east
+			},
+			([first, rest @ ..], _, _) => rest.iter().fold(*first, |bbox, part| bbox.extended(part)),
+		};
+	}
+
 	/// Validate coordinate ranges and ordering.
 	/// Ensures `x_min ≥ -180`, `x_max ≤ 180`, `y_min ≥ -90`, `y_max ≤ 90`, and
 	/// `x_min ≤ x_max`, `y_min ≤ y_max`.
@@ -349,7 +477,7 @@ impl GeoBBox {
 		ensure!(self.y_max <= 90., "y_max ({}) must be <= 90", self.y_max);
 		ensure!(
 			self.x_min <= self.x_max,
-			"x_min ({}) must be <= x_max ({})",
+			"x_min ({}) must be <= x_max ({}), bounding boxes crossing the antimeridian are not supported",
 			self.x_min,
 			self.x_max
 		);
@@ -383,12 +511,12 @@ impl GeoBBox {
 		// Spherical Mercator radius (WGS84 semi-major axis)
 		fn x_from_lon(lon_deg: f64) -> f64 {
 			let lon = lon_deg.max(-MAX_MERC_LNG).min(MAX_MERC_LNG);
-			RADIUS * lon.to_radians()
+			EARTH_RADIUS * lon.to_radians()
 		}
 		fn y_from_lat(lat_deg: f64) -> f64 {
 			let lat = lat_deg.max(-MAX_MERC_LAT).min(MAX_MERC_LAT);
 			let phi = lat.to_radians();
-			RADIUS * ((std::f64::consts::FRAC_PI_4 + phi / 2.0).tan()).ln()
+			EARTH_RADIUS * ((std::f64::consts::FRAC_PI_4 + phi / 2.0).tan()).ln()
 		}
 
 		[
@@ -411,6 +539,64 @@ impl Debug for GeoBBox {
 	}
 }
 
+impl FromStr for GeoBBox {
+	type Err = anyhow::Error;
+
+	/// Parses a bounding box given by a user, e.g. as a command line argument.
+	///
+	/// The four values `west, south, east, north` are in **degrees** and separated by commas, spaces or semicolons.
+	/// The list may be enclosed in square brackets like a JSON array.
+	/// If `west` is greater than `east`, the bounding box crosses the antimeridian, see [`GeoBBox::new_wrapping`].
+	///
+	/// # Errors
+	///
+	/// Returns an error if there are not exactly four numbers or if a value is out of range.
+	/// Values that look like Web‑Mercator meters are reported as such.
+	///
+	/// # Examples
+	/// ```
+	/// use versatiles_core::GeoBBox;
+	///
+	/// let bbox: GeoBBox = "[-180,-90,180,90]".parse().unwrap();
+	/// assert_eq!(bbox.as_tuple(), (-180.0, -90.0, 180.0, 90.0));
+	///
+	/// let bbox: GeoBBox = "13.08, 52.33, 13.77, 52.68".parse().unwrap();
+	/// assert_eq!(bbox.as_tuple(), (13.08, 52.33, 13.77, 52.68));
+	///
+	/// let bbox: GeoBBox = "176,-21,-178,-12".parse().unwrap();
+	/// assert!(bbox.crosses_antimeridian());
+	/// ```
+	#[context("Failed to parse bbox {input:?}")]
+	fn from_str(input: &str) -> Result<Self> {
+		let mut list = input.trim();
+		if let Some(inner) = list.strip_prefix('[') {
+			list = inner.strip_suffix(']').context("missing closing bracket")?;
+		}
+
+		let values = list
+			.split([',', ' ', ';'])
+			.filter(|s| !s.is_empty())
+			.map(|s| {
+				let value = s
+					.parse::<f64>()
+					.with_context(|| format!("value {s:?} is not a number"))?;
+				ensure!(value.is_finite(), "value {s:?} is not a finite number");
+				Ok(value)
+			})
+			.collect::<Result<Vec<f64>>>()?;
+		ensure!(
+			values.len() == 4,
+			"expected 4 values (west, south, east, north), but got {}",
+			values.len()
+		);
+
+		if values.iter().any(|v| v.abs() > 1000.0) {
+			bail!("values must be longitudes and latitudes in degrees (EPSG:4326), not Web Mercator meters");
+		}
+		GeoBBox::new_wrapping(values[0], values[1], values[2], values[3])
+	}
+}
+
 impl TryFrom<Vec<f64>> for GeoBBox {
 	type Error = anyhow::Error;
 
@@ -747,6 +933,92 @@ mod tests {
 		assert_eq!(mercator_bbox, [-point_mm.0, -point_mm.1, point_mm.0, point_mm.1]);
 	}
 
+	#[rstest]
+	#[case("[-180,-90,180,90]", [-180.0, -90.0, 180.0, 90.0])]
+	#[case("13.08,52.33,13.77,52.68", [13.08, 52.33, 13.77, 52.68])]
+	#[case(" [ -10, -5, 10, 5 ] ", [-10.0, -5.0, 10.0, 5.0])]
+	#[case("-10 -5 10 5", [-10.0, -5.0, 10.0, 5.0])]
+	#[case("-10;-5;10;5", [-10.0, -5.0, 10.0, 5.0])]
+	#[case("170,-20,-170,-10", [170.0, -20.0, -170.0, -10.0])]
+	fn test_from_str(#[case] input: &str, #[case] expected: [f64; 4]) {
+		assert_eq!(input.parse::<GeoBBox>().unwrap().as_array(), expected);
+	}
+
+	#[rstest]
+	#[case("", "expected 4 values (west, south, east, north), but got 0")]
+	#[case("[1,2,3,4", "missing closing bracket")]
+	#[case("1,2,3", "expected 4 values (west, south, east, north), but got 3")]
+	#[case("1,2,3,4,5", "expected 4 values (west, south, east, north), but got 5")]
+	#[case("1,2,3,north", "value \"north\" is not a number")]
+	#[case("1,2,3,NaN", "value \"NaN\" is not a finite number")]
+	#[case("-190,-5,10,5", "x_min (-190) must be >= -180")]
+	#[case("-10,-5,10,95", "y_max (95) must be <= 90")]
+	#[case("-10,5,10,-5", "y_min (5) must be <= y_max (-5)")]
+	#[case("170,-20,190,-10", "x_max (190) must be <= 180")]
+	#[case(
+		"1447153,6887893,1532366,6948093",
+		"values must be longitudes and latitudes in degrees (EPSG:4326), not Web Mercator meters"
+	)]
+	fn test_from_str_errors(#[case] input: &str, #[case] error: &str) {
+		let err = input.parse::<GeoBBox>().unwrap_err();
+		let message = format!("{err:#}");
+		assert!(message.starts_with("Failed to parse bbox"), "{message}");
+		assert!(message.contains(error), "{message}");
+	}
+
+	#[test]
+	fn test_new_wrapping() {
+		let bbox = GeoBBox::new_wrapping(170.0, -20.0, -170.0, -10.0).unwrap();
+		assert!(bbox.crosses_antimeridian());
+		assert_eq!(
+			bbox.split_antimeridian(),
+			[
+				GeoBBox::new(170.0, -20.0, 180.0, -10.0).unwrap(),
+				GeoBBox::new(-180.0, -20.0, -170.0, -10.0).unwrap()
+			]
+		);
+
+		let bbox = GeoBBox::new_wrapping(-10.0, -5.0, 10.0, 5.0).unwrap();
+		assert!(!bbox.crosses_antimeridian());
+		assert_eq!(bbox.split_antimeridian(), [bbox]);
+
+		assert!(GeoBBox::new_wrapping(190.0, -20.0, -170.0, -10.0).is_err());
+		assert!(GeoBBox::new_wrapping(170.0, -20.0, -190.0, -10.0).is_err());
+		assert!(GeoBBox::new_wrapping(170.0, -10.0, -170.0, -20.0).is_err());
+	}
+
+	#[rstest]
+	#[case([170, -20, -170, -10], [-180, -90, 180, 90], [170, -20, -170, -10])]
+	#[case([170, -20, -170, -10], [175, -30, 180, 0], [175, -20, 180, -10])]
+	#[case([170, -20, -170, -10], [-175, -30, 0, 0], [-175, -20, -170, -10])]
+	#[case([170, -20, -170, -10], [175, -30, -175, 0], [175, -20, -175, -10])]
+	#[case([10, -20, -10, -10], [-20, -30, 20, 0], [-20, -20, 20, -10])]
+	fn test_intersect_wrapping(#[case] a: [i16; 4], #[case] b: [i16; 4], #[case] expected: [i16; 4]) {
+		let new = |v: [i16; 4]| GeoBBox::new_wrapping(v[0].into(), v[1].into(), v[2].into(), v[3].into()).unwrap();
+		assert_eq!(new(a).intersected(&new(b)).as_array(), expected.map(f64::from));
+		assert_eq!(new(b).intersected(&new(a)).as_array(), expected.map(f64::from));
+	}
+
+	#[test]
+	fn test_intersect_wrapping_no_overlap() {
+		let bbox = GeoBBox::new_wrapping(170.0, -20.0, -170.0, -10.0).unwrap();
+		let empty = bbox.intersected(&GeoBBox::new(-20.0, -30.0, 20.0, 0.0).unwrap());
+		assert!(empty.y_min > empty.y_max, "{empty:?}");
+	}
+
+	#[rstest]
+	#[case([170, -20, -170, -10], [175, -30, 180, 0], [170, -30, -170, 0])]
+	#[case([170, -20, -170, -10], [-175, -30, -160, 0], [170, -30, -160, 0])]
+	#[case([170, -20, -170, -10], [150, -30, 160, 0], [150, -30, -170, 0])]
+	#[case([170, -20, -170, -10], [-160, -30, -150, 0], [170, -30, -150, 0])]
+	#[case([170, -20, -170, -10], [160, -30, -160, 0], [160, -30, -160, 0])]
+	#[case([170, -20, -170, -10], [-175, -30, 175, 0], [-180, -30, 180, 0])]
+	fn test_extend_wrapping(#[case] a: [i16; 4], #[case] b: [i16; 4], #[case] expected: [i16; 4]) {
+		let new = |v: [i16; 4]| GeoBBox::new_wrapping(v[0].into(), v[1].into(), v[2].into(), v[3].into()).unwrap();
+		assert_eq!(new(a).extended(&new(b)).as_array(), expected.map(f64::from));
+		assert_eq!(new(b).extended(&new(a)).as_array(), expected.map(f64::from));
+	}
+
 	/// Extensive normalization tests: sorting and clamping behavior
 	#[rstest]
 	#[case((0, 0, 1, 1),           (0, 0, 1, 1))] // already ordered
@@ -1,5 +1,8 @@
-use anyhow::{Result, ensure};
-use std::fmt::Debug;
+use anyhow::{Context, Result, ensure};
+use std::{
+	fmt::{Debug, Display},
+	str::FromStr,
+};
 
 /// A center point in geographic space, represented by:
 /// - `f64` longitude, in the range `[-180, 180]`
@@ -9,6 +12,25 @@ use std::fmt::Debug;
 pub struct GeoCenter(pub f64, pub f64, pub u8);
 
 impl GeoCenter {
+	/// Creates a `GeoCenter` and checks that it is valid, see [`GeoCenter::check`].
+	///
+	/// # Errors
+	///
+	/// Returns an error if longitude, latitude or zoom are out of range.
+	///
+	/// # Examples
+	/// ```
+	/// use versatiles_core::GeoCenter;
+	///
+	/// assert!(GeoCenter::new(13.4, 52.5, 10).is_ok());
+	/// assert!(GeoCenter::new(13.4, 95.0, 10).is_err());
+	/// ```
+	pub fn new(lon: f64, lat: f64, zoom: u8) -> Result<Self> {
+		let center = GeoCenter(lon, lat, zoom);
+		center.check()?;
+		Ok(center)
+	}
+
 	/// Tries to construct an optional `GeoCenter` from an `Option<Vec<f64>>`.
 	///
 	/// - If the input is `Some(vec)`, attempts a conversion via [`GeoCenter::try_from`].
@@ -99,6 +121,8 @@ impl GeoCenter {
 	/// }
 	/// ```
 	pub fn check(&self) -> Result<()> {
+		ensure!(self.0.is_finite(), "center[0] (longitude) must be a finite number");
+		ensure!(self.1.is_finite(), "center[1] (latitude) must be a finite number");
 		ensure!(-180.0 <= self.0, "center[0] (longitude) must be >= -180");
 		ensure!(-90.0 <= self.1, "center[1] (latitude) must be >= -90");
 		ensure!(self.0 <= 180.0, "center[0] (longitude) must be <= 180");
@@ -106,6 +130,20 @@ impl GeoCenter {
 		ensure!(self.2 <= 30, "center[2] (zoom) must be <= 30");
 		Ok(())
 	}
+
+	/// Moves the zoom level into the range `min..=max`, e.g. to keep the center within the zoom levels of a tileset.
+	///
+	/// # Examples
+	/// ```
+	/// use versatiles_core::GeoCenter;
+	///
+	/// let mut center = GeoCenter(13.4, 52.5, 3);
+	/// center.clamp_zoom(5, 14);
+	/// assert_eq!(center, GeoCenter(13.4, 52.5, 5));
+	/// ```
+	pub fn clamp_zoom(&mut self, min: u8, max: u8) {
+		self.2 = self.2.clamp(min, max.max(min));
+	}
 }
 
 impl Debug for GeoCenter {
@@ -115,6 +153,39 @@ impl Debug for GeoCenter {
 	}
 }
 
+impl Display for GeoCenter {
+	/// Formats the `GeoCenter` as `"longitude,latitude,zoom"`, the format of the MBTiles `center` metadata.
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{},{},{}", self.0, self.1, self.2)
+	}
+}
+
+impl FromStr for GeoCenter {
+	type Err = anyhow::Error;
+
+	/// Parses a `GeoCenter` from `"longitude,latitude,zoom"`, e.g. the MBTiles `center` metadata,
+	/// and checks that it is valid.
+	///
+	/// # Examples
+	/// ```
+	/// use versatiles_core::GeoCenter;
+	///
+	/// let center: GeoCenter = "13.422538,52.50638,7".parse().unwrap();
+	/// assert_eq!(center, GeoCenter(13.422538, 52.50638, 7));
+	/// assert!("13.4,52.5".parse::<GeoCenter>().is_err());
+	/// ```
+	fn from_str(s: &str) -> Result<Self> {
+		let values = s
+			.split(',')
+			.map(|v| v.trim().parse::<f64>())
+			.collect::<Result<Vec<f64>, _>>()
+			.with_context(|| format!("center '{s}' must consist of numbers"))?;
+		let center = GeoCenter::try_from(values).with_context(|| format!("parsing center '{s}'"))?;
+		center.check().with_context(|| format!("parsing center '{s}'"))?;
+		Ok(center)
+	}
+}
+
 impl TryFrom<Vec<f64>> for GeoCenter {
 	type Error = anyhow::Error;
 
@@ -123,9 +194,8 @@ impl TryFrom<Vec<f64>> for GeoCenter {
 	///
 	/// # Errors
 	///
-	/// Returns an error if the length is not exactly three.
-	/// If desired, you could also validate numeric bounds here before casting
-	/// `zoom` to `u8`.
+	/// Returns an error if the length is not exactly three or if the zoom is negative or not a number.
+	/// A fractional zoom is rounded. Longitude and latitude are not checked, see [`GeoCenter::check`].
 	///
 	/// # Examples
 	/// ```
@@ -144,7 +214,12 @@ impl TryFrom<Vec<f64>> for GeoCenter {
 			input.len() == 3,
 			"center must have 3 elements: [longitude, latitude, zoom]"
 		);
-		Ok(GeoCenter(input[0], input[1], input[2] as u8))
+		let zoom = input[2].round();
+		ensure!(
+			(0.0..=f64::from(u8::MAX)).contains(&zoom),
+			"center[2] (zoom) must be a number between 0 and 255"
+		);
+		Ok(GeoCenter(input[0], input[1], zoom as u8))
 	}
 }
 
@@ -186,6 +261,52 @@ mod tests {
 		assert!(result.is_err(), "Expected error for < 3 elements");
 	}
 
+	#[test]
+	fn test_try_from_zoom() -> Result<()> {
+		assert_eq!(GeoCenter::try_from(vec![1.0, 2.0, 3.6])?, GeoCenter(1.0, 2.0, 4));
+		assert!(GeoCenter::try_from(vec![1.0, 2.0, -1.0]).is_err());
+		assert!(GeoCenter::try_from(vec![1.0, 2.0, f64::NAN]).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_new() {
+		assert_eq!(GeoCenter::new(1.0, 2.0, 3).unwrap(), GeoCenter(1.0, 2.0, 3));
+		assert!(GeoCenter::new(181.0, 2.0, 3).is_err());
+		assert!(GeoCenter::new(1.0, f64::NAN, 3).is_err());
+		assert!(GeoCenter::new(1.0, 2.0, 31).is_err());
+	}
+
+	#[test]
+	fn test_parse_and_display() -> Result<()> {
+		let center: GeoCenter = " 13.422538, 52.50638 ,7".parse()?;
+		assert_eq!(center, GeoCenter(13.422538, 52.50638, 7));
+		assert_eq!(center.to_string(), "13.422538,52.50638,7");
+		assert_eq!(center.to_string().parse::<GeoCenter>()?, center);
+
+		assert_eq!(
+			"13.4,52.5".parse::<GeoCenter>().unwrap_err().to_string(),
+			"parsing center '13.4,52.5'"
+		);
+		assert_eq!(
+			"13.4,north,5".parse::<GeoCenter>().unwrap_err().to_string(),
+			"center '13.4,north,5' must consist of numbers"
+		);
+		assert!("13.4,95,5".parse::<GeoCenter>().is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_clamp_zoom() {
+		let mut center = GeoCenter(0.0, 0.0, 3);
+		center.clamp_zoom(5, 14);
+		assert_eq!(center.2, 5);
+		center.clamp_zoom(0, 4);
+		assert_eq!(center.2, 4);
+		center.clamp_zoom(6, 2);
+		assert_eq!(center.2, 6);
+	}
+
 	#[test]
 	fn test_as_vec_and_array() {
 		let gc = GeoCenter(-75.0, 40.0, 5);
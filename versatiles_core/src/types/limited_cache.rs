@@ -2,8 +2,10 @@
 //!
 //! The `LimitedCache` manages entries in a manner resembling an LRU cache, ensuring it does not exceed
 //! a predefined number of elements (derived from the byte size limit). Once the limit is reached,
-//! least-recently accessed items are removed using a custom cleanup method.
+//! least-recently accessed items are removed using a custom cleanup method. Items are also removed early
+//! while the global [`MemoryBudget`](crate::MemoryBudget) is under pressure.
 
+use crate::MemoryBudget;
 use anyhow::Result;
 use std::{collections::HashMap, fmt::Debug, hash::Hash, mem::size_of, ops::Div};
 use versatiles_derive::context;
@@ -160,7 +162,8 @@ where
 	///
 	/// - Increments `last_index`.
 	/// - Stores `(value, last_index)` in the internal map.  
-	/// - If adding triggers the capacity limit, it runs `cleanup()` to evict items.
+	/// - If adding triggers the capacity limit, or the global [`MemoryBudget`] is under pressure,
+	///   it runs `cleanup()` to evict items.
 	///
 	/// # Examples
 	///
@@ -172,7 +175,7 @@ where
 	/// assert_eq!(inserted, 123);
 	/// ```
 	pub fn add(&mut self, key: K, value: V) -> V {
-		if self.cache.len() >= self.max_length {
+		if self.cache.len() >= self.max_length || (!self.cache.is_empty() && MemoryBudget::global().is_under_pressure()) {
 			self.cleanup();
 		}
 
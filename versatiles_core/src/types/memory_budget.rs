@@ -0,0 +1,238 @@
+//! A process-wide memory budget for large conversions.
+//!
+//! Components that hold a lot of data (buffered tiles, caches) report their usage with a
+//! [`MemoryReservation`]. The budget does not block allocations. Instead, parallel streams reduce their
+//! concurrency and caches evict entries earlier when the usage approaches the limit.
+//!
+//! Without a limit the budget only counts.
+//!
+//! ```rust
+//! use versatiles_core::MemoryBudget;
+//!
+//! let budget = MemoryBudget::new();
+//! budget.set_limit(Some(1000));
+//!
+//! let mut reservation = budget.reserve(500);
+//! assert_eq!(budget.concurrency(8), 8);
+//!
+//! reservation.resize(900);
+//! assert!(budget.is_under_pressure());
+//! assert_eq!(budget.concurrency(8), 4);
+//!
+//! drop(reservation);
+//! assert_eq!(budget.used(), 0);
+//! ```
+
+use anyhow::{Context, Result, bail};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Usage (in percent of the limit) above which concurrency is reduced and caches evict early.
+const PRESSURE_PERCENT: u128 = 80;
+
+static GLOBAL: MemoryBudget = MemoryBudget::new();
+
+/// Counts the memory used by big buffers and compares it to an optional limit.
+#[derive(Debug, Default)]
+pub struct MemoryBudget {
+	/// Limit in bytes, 0 means unlimited.
+	limit: AtomicU64,
+	used: AtomicU64,
+}
+
+impl MemoryBudget {
+	/// Creates an unlimited budget.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			limit: AtomicU64::new(0),
+			used: AtomicU64::new(0),
+		}
+	}
+
+	/// Returns the budget shared by the whole process, e.g. set with `--max-memory`.
+	#[must_use]
+	pub fn global() -> &'static MemoryBudget {
+		&GLOBAL
+	}
+
+	/// Sets the limit in bytes. `None` removes the limit.
+	pub fn set_limit(&self, limit: Option<u64>) {
+		self.limit.store(limit.unwrap_or(0), Ordering::Relaxed);
+	}
+
+	/// Returns the limit in bytes, if any.
+	#[must_use]
+	pub fn limit(&self) -> Option<u64> {
+		match self.limit.load(Ordering::Relaxed) {
+			0 => None,
+			limit => Some(limit),
+		}
+	}
+
+	/// Returns the number of reserved bytes.
+	#[must_use]
+	pub fn used(&self) -> u64 {
+		self.used.load(Ordering::Relaxed)
+	}
+
+	/// Reserves `bytes`. They are released when the reservation is dropped.
+	#[must_use]
+	pub fn reserve(&self, bytes: usize) -> MemoryReservation<'_> {
+		self.used.fetch_add(bytes as u64, Ordering::Relaxed);
+		MemoryReservation {
+			budget: self,
+			bytes: bytes as u64,
+		}
+	}
+
+	/// Returns the used fraction of the limit, or 0 without a limit.
+	#[must_use]
+	pub fn pressure(&self) -> f64 {
+		match self.limit() {
+			Some(limit) => self.used() as f64 / limit as f64,
+			None => 0.0,
+		}
+	}
+
+	/// Returns `true` if the usage is close to or above the limit.
+	#[must_use]
+	pub fn is_under_pressure(&self) -> bool {
+		self
+			.limit()
+			.is_some_and(|limit| u128::from(self.used()) >= threshold(limit))
+	}
+
+	/// Scales the concurrency `max` down when the budget is under pressure, reaching 1 at the limit.
+	#[must_use]
+	pub fn concurrency(&self, max: usize) -> usize {
+		let Some(limit) = self.limit() else {
+			return max;
+		};
+		let (limit, used, threshold) = (u128::from(limit), u128::from(self.used()), threshold(limit));
+		if used < threshold {
+			return max;
+		}
+		let scaled = (max as u128 * limit.saturating_sub(used)).div_ceil(limit - threshold);
+		(scaled as usize).clamp(1, max.max(1))
+	}
+}
+
+/// Usage in bytes above which a budget with `limit` is under pressure.
+fn threshold(limit: u64) -> u128 {
+	u128::from(limit) * PRESSURE_PERCENT / 100
+}
+
+/// Memory reserved in a [`MemoryBudget`], released on drop.
+#[derive(Debug)]
+pub struct MemoryReservation<'a> {
+	budget: &'a MemoryBudget,
+	bytes: u64,
+}
+
+impl MemoryReservation<'_> {
+	/// Returns the number of reserved bytes.
+	#[must_use]
+	pub fn bytes(&self) -> u64 {
+		self.bytes
+	}
+
+	/// Changes the number of reserved bytes.
+	pub fn resize(&mut self, bytes: usize) {
+		let bytes = bytes as u64;
+		if bytes > self.bytes {
+			self.budget.used.fetch_add(bytes - self.bytes, Ordering::Relaxed);
+		} else {
+			self.budget.used.fetch_sub(self.bytes - bytes, Ordering::Relaxed);
+		}
+		self.bytes = bytes;
+	}
+}
+
+impl Drop for MemoryReservation<'_> {
+	fn drop(&mut self) {
+		self.budget.used.fetch_sub(self.bytes, Ordering::Relaxed);
+	}
+}
+
+/// Parses a memory size like `"8G"`, `"512MB"`, `"1.5GiB"` or `"1000000"`. Units are binary (1K = 1024 bytes).
+///
+/// # Errors
+/// Returns an error if the value is not a positive number with an optional unit.
+pub fn parse_memory_size(value: &str) -> Result<u64> {
+	let value = value.trim();
+	let split = value
+		.find(|c: char| !(c.is_ascii_digit() || c == '.'))
+		.unwrap_or(value.len());
+	let (number, unit) = value.split_at(split);
+	let number: f64 = number
+		.parse()
+		.with_context(|| format!("invalid memory size {value:?}"))?;
+	let factor: u64 = match unit.trim().to_uppercase().trim_end_matches("IB").trim_end_matches('B') {
+		"" => 1,
+		"K" => 1 << 10,
+		"M" => 1 << 20,
+		"G" => 1 << 30,
+		"T" => 1 << 40,
+		_ => bail!("invalid memory size {value:?}, expected a unit like K, M, G or T"),
+	};
+	let bytes = (number * factor as f64).round();
+	if bytes < 1.0 {
+		bail!("memory size {value:?} must be positive");
+	}
+	Ok(bytes as u64)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reservations() {
+		let budget = MemoryBudget::new();
+		let a = budget.reserve(100);
+		let mut b = budget.reserve(50);
+		assert_eq!(budget.used(), 150);
+		b.resize(20);
+		assert_eq!(b.bytes(), 20);
+		assert_eq!(budget.used(), 120);
+		drop(a);
+		assert_eq!(budget.used(), 20);
+		drop(b);
+		assert_eq!(budget.used(), 0);
+	}
+
+	#[test]
+	fn concurrency_follows_pressure() {
+		let budget = MemoryBudget::new();
+		let mut reservation = budget.reserve(10_000);
+		assert_eq!(budget.pressure(), 0.0);
+		assert_eq!(budget.concurrency(16), 16);
+
+		budget.set_limit(Some(1000));
+		assert_eq!(budget.limit(), Some(1000));
+		assert!(budget.is_under_pressure());
+		assert_eq!(budget.concurrency(16), 1);
+
+		reservation.resize(700);
+		assert!(!budget.is_under_pressure());
+		assert_eq!(budget.concurrency(16), 16);
+
+		reservation.resize(850);
+		assert_eq!(budget.concurrency(16), 12);
+
+		budget.set_limit(None);
+		assert_eq!(budget.concurrency(16), 16);
+	}
+
+	#[test]
+	fn parse_sizes() {
+		assert_eq!(parse_memory_size("1000").unwrap(), 1000);
+		assert_eq!(parse_memory_size("8G").unwrap(), 8 << 30);
+		assert_eq!(parse_memory_size("512 MB").unwrap(), 512 << 20);
+		assert_eq!(parse_memory_size("1.5GiB").unwrap(), 3 << 29);
+		assert_eq!(parse_memory_size("2k").unwrap(), 2048);
+		assert!(parse_memory_size("").is_err());
+		assert!(parse_memory_size("0").is_err());
+		assert!(parse_memory_size("8X").is_err());
+	}
+}
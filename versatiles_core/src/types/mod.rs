@@ -1,11 +1,17 @@
 //! Contains types like coordinates, bounding boxes (bboxes), format types, and more.
 
+#[cfg(any(test, feature = "arbitrary"))]
+mod arbitrary;
+
 mod blob;
 pub use blob::*;
 
 mod byte_range;
 pub use byte_range::*;
 
+mod cancellation_token;
+pub use cancellation_token::*;
+
 mod geo_bbox;
 pub use geo_bbox::*;
 
@@ -15,6 +21,9 @@ pub use geo_center::*;
 mod limited_cache;
 pub use limited_cache::*;
 
+mod memory_budget;
+pub use memory_budget::*;
+
 mod probe_depth;
 pub use probe_depth::*;
 
@@ -33,6 +42,9 @@ pub use tile_compression::*;
 mod tile_coord;
 pub use tile_coord::*;
 
+mod tile_coord_list;
+pub use tile_coord_list::*;
+
 mod tile_format;
 pub use tile_format::*;
 
@@ -245,6 +245,7 @@ impl TileBBox {
 	/// # Errors
 	///
 	/// - If the geographical coordinates are invalid.
+	/// - If the bounding box crosses the antimeridian.
 	/// - If the converted tile coordinates are out of bounds.
 	///
 	/// # Example
@@ -257,6 +258,10 @@ impl TileBBox {
 	#[context("Failed to create TileBBox from GeoBBox {bbox:?} at level {level}")]
 	pub fn from_geo(level: u8, bbox: &GeoBBox) -> Result<TileBBox> {
 		ensure!(level <= 31, "level ({level}) must be <= 31");
+		ensure!(
+			!bbox.crosses_antimeridian(),
+			"bounding box crosses the antimeridian, split it with GeoBBox::split_antimeridian first"
+		);
 
 		// Convert geographical coordinates to tile coordinates
 		let p_min = TileCoord::from_geo(bbox.x_min + 1e-10, bbox.y_max - 1e-10, level)?;
@@ -50,8 +50,8 @@ impl TileBBox {
 	/// Splits the bounding box into a grid of smaller bounding boxes of a specified size.
 	///
 	/// Each sub-bounding box will have dimensions at most `size x size` tiles.
-	/// The last sub-bounding boxes in each row or column may be smaller if the original
-	/// dimensions are not exact multiples of `size`.
+	/// The grid is aligned to multiples of `size`, so the sub-bounding boxes at the edges
+	/// may be smaller. `size` does not have to be a power of two.
 	///
 	/// # Arguments
 	///
@@ -86,6 +86,29 @@ impl TileBBox {
 
 		Box::new(iter)
 	}
+
+	/// Splits the bounding box into a grid like [`Self::iter_bbox_grid`], and adds a buffer around every cell.
+	///
+	/// Yields pairs of a grid cell and the same cell expanded by `buffer` tiles on every side,
+	/// so neighbouring buffered cells overlap by `2 * buffer` tiles. Operations that need the tiles around a cell,
+	/// e.g. to avoid seams at tile borders, can read the buffered cell and write the cell.
+	/// The buffered cells are clipped to the bounds of the zoom level, but not to this bounding box.
+	///
+	/// ```
+	/// # use versatiles_core::TileBBox;
+	/// let bbox = TileBBox::from_min_and_max(4, 0, 0, 7, 3).unwrap();
+	/// let cells: Vec<_> = bbox.iter_bbox_grid_buffered(4, 1).collect();
+	/// assert_eq!(cells[0].0.as_array().unwrap(), [0, 0, 3, 3]);
+	/// assert_eq!(cells[0].1.as_array().unwrap(), [0, 0, 4, 4]);
+	/// assert_eq!(cells[1].1.as_array().unwrap(), [3, 0, 8, 4]);
+	/// ```
+	pub fn iter_bbox_grid_buffered(&self, size: u32, buffer: u32) -> impl Iterator<Item = (TileBBox, TileBBox)> + '_ {
+		self.iter_bbox_grid(size).map(move |bbox| {
+			let mut buffered = bbox;
+			buffered.expand_by(buffer, buffer, buffer, buffer);
+			(bbox, buffered)
+		})
+	}
 }
 
 #[cfg(test)]
@@ -141,6 +164,8 @@ mod tests {
 	#[case::size2((5, 10, 20, 15, 25), 2, (3,3))]
 	#[case::size4((6,  5,  6, 11,  9), 4, (2,2))]
 	#[case::size8((7,  2,  3,  4,  4), 8, (1,1))]
+	#[case::size3((5, 10, 20, 15, 25), 3, (3,3))]
+	#[case::size10((7, 5,  6, 11,  9), 10, (2,1))]
 	fn grid_cells_and_cover(
 		#[case] minmax: (u8, u32, u32, u32, u32),
 		#[case] size: u32,
@@ -184,6 +209,27 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn grid_buffered_cells_overlap() -> Result<()> {
+		let bb = TileBBox::from_min_and_max(4, 2, 5, 9, 6)?;
+		let cells: Vec<_> = bb.iter_bbox_grid_buffered(4, 2).collect();
+		let grid: Vec<_> = bb.iter_bbox_grid(4).collect();
+		assert_eq!(cells.iter().map(|(cell, _)| *cell).collect::<Vec<_>>(), grid);
+
+		let buffered: Vec<_> = cells
+			.iter()
+			.map(|(_, buffered)| buffered.as_array())
+			.collect::<Result<_>>()?;
+		// clipped to the level bounds (0..=15), but not to the bbox
+		assert_eq!(buffered, [[0, 3, 5, 8], [2, 3, 9, 8], [6, 3, 11, 8]]);
+
+		// without buffer, the cells are returned unchanged
+		for (cell, buffered) in bb.iter_bbox_grid_buffered(4, 0) {
+			assert_eq!(cell, buffered);
+		}
+		Ok(())
+	}
+
 	#[test]
 	#[should_panic(expected = "size must be greater than 0")]
 	fn grid_panics_on_zero_size() {
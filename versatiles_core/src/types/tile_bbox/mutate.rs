@@ -259,6 +259,8 @@ impl TileBBox {
 	/// Scales down the bounding box by a specified factor.
 	///
 	/// Divides all coordinates by `scale`, effectively reducing the resolution.
+	/// For factors that are not powers of two, e.g. 10, the result is the bbox of the 10×10 blocks
+	/// touched by this bbox.
 	///
 	/// # Arguments
 	///
@@ -272,7 +274,6 @@ impl TileBBox {
 			return; // No-op for empty bboxes
 		}
 		assert!(scale > 0, "scale must be greater than 0");
-		assert!(scale.is_power_of_two(), "scale must be a power of two");
 
 		self
 			.set_min_and_max(
@@ -284,7 +285,7 @@ impl TileBBox {
 			.unwrap();
 	}
 
-	/// Return a downscaled **copy** of this bbox by an integer power-of-two factor.
+	/// Return a downscaled **copy** of this bbox by an integer factor.
 	pub fn scaled_down(&self, scale: u32) -> TileBBox {
 		if self.is_empty() {
 			return TileBBox::new_empty(self.level).unwrap();
@@ -533,10 +534,13 @@ mod tests {
 	}
 
 	#[test]
-	#[should_panic(expected = "scale must be a power of two")]
-	fn scale_down_non_power_of_two_panics() {
-		let mut b = bb(4, 4, 4, 7, 7);
+	fn scale_down_by_other_factors() -> Result<()> {
+		let mut b = bb(5, 8, 10, 15, 17);
 		b.scale_down(3);
+		assert_eq!(b.as_array()?, [2, 3, 5, 5]);
+		b.scale_down(10);
+		assert_eq!(b.as_array()?, [0, 0, 0, 0]);
+		Ok(())
 	}
 
 	#[test]
@@ -1,42 +1,141 @@
-//! A dense, level-aware container for values laid out on a rectangular tile grid.
+//! A level-aware container for values laid out on a rectangular tile grid.
 //!
 //! `TileBBoxMap<I>` stores exactly one value per tile inside a [`TileBBox`],
-//! keeping items in a compact `Vec` in **row-major** order (x fastest, then y).
+//! in **row-major** order (x fastest, then y).
 //! It provides O(1) indexed access via [`TileCoord`] and utility methods to
 //! transform or regroup values across levels.
+//!
+//! Large maps that are created with a fill value (e.g. [`TileBBoxMap::new_default`])
+//! start out **sparse**: only the slots that differ from the fill value are stored in a
+//! `HashMap`. Once more than a quarter of the slots has been set, the map switches to a
+//! dense `Vec`. This keeps e.g. 256×256 blocks of mostly empty ocean tiles cheap, while
+//! densely filled maps keep the speed of plain indexing.
+//!
+//! This is the pattern used by operations that need all tiles of a bbox at once,
+//! e.g. to stack several sources: create a map of `Option<T>`, fill it from one or
+//! more [`TileStream`]s, combine the values and turn the result into a stream again.
+//!
+//! # Example
+//! ```
+//! use versatiles_core::{TileBBox, TileBBoxMap, TileCoord};
+//!
+//! let bbox = TileBBox::from_min_and_max(3, 0, 0, 3, 1).unwrap();
+//!
+//! // one value per tile
+//! let map = TileBBoxMap::from_fn(bbox, |coord| coord.x + coord.y);
+//! assert_eq!(*map.get(&TileCoord::new(3, 2, 1).unwrap()).unwrap(), 3);
+//!
+//! // keep only some of them
+//! let map = map.filter_map(|_coord, sum| (sum % 2 == 0).then_some(sum));
+//! assert_eq!(map.count_present(), 4);
+//!
+//! let tiles: Vec<(TileCoord, u32)> = map.into_present().collect();
+//! assert_eq!(tiles[1], (TileCoord::new(3, 2, 0).unwrap(), 2));
+//! ```
 
 use crate::{TileBBox, TileCoord, TileStream};
 use anyhow::Result;
-use std::fmt::Debug;
+use futures::{StreamExt, stream};
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
 use versatiles_derive::context;
 
-/// A dense map of tiles inside a bounding box.
+/// Maps with at least this many slots start out sparse.
+const SPARSE_MIN_TILES: u64 = 1024;
+
+/// A sparse map becomes dense once more than `1 / DENSE_FILL_DIVISOR` of its slots are set.
+const DENSE_FILL_DIVISOR: u64 = 4;
+
+enum Storage<I> {
+	/// One value per slot, in row-major order.
+	Dense(Vec<I>),
+	/// Only the values that have been set, by slot index. All other slots hold `fill`.
+	Sparse {
+		items: HashMap<u64, I>,
+		fill: I,
+		clone: fn(&I) -> I,
+	},
+}
+
+/// A map of tiles inside a bounding box.
 ///
-/// Values are stored in a flat `Vec` and addressed by [`TileCoord`]. The
-/// container remembers its source [`TileBBox`] to translate coordinates into
-/// indices. This keeps memory compact and lookups very fast.
+/// Values are addressed by [`TileCoord`]. The container remembers its source
+/// [`TileBBox`] to translate coordinates into indices. Depending on how many
+/// slots are set, values are stored densely in a flat `Vec` or sparsely in a
+/// `HashMap`; see the [module documentation](self).
 ///
 /// The generic `I` is the per-tile value. Use [`Option<I>`] to represent
 /// missing values and the convenience constructors [`TileBBoxMap::from_iter`] / [`TileBBoxMap::from_stream`]
 /// (provided on `TileBBoxMap<Option<I>>`).
 pub struct TileBBoxMap<I> {
 	bbox: TileBBox,
-	vec: Vec<I>,
+	storage: Storage<I>,
 }
 
 impl<I> TileBBoxMap<I> {
 	/// Create a new container for `bbox` with every slot initialized to `item`.
 	///
-	/// The vector is allocated to `bbox.count_tiles()` and filled by cloning
-	/// `item`.
+	/// Small maps allocate a vector of `bbox.count_tiles()` clones of `item`,
+	/// large maps start sparse and only store the slots that are set later.
 	pub fn new_prefilled_with(bbox: TileBBox, item: I) -> Self
 	where
 		I: Clone,
 	{
-		let n = bbox.count_tiles() as usize;
-		let mut vec = Vec::with_capacity(n);
-		vec.resize(n, item);
-		Self { bbox, vec }
+		let n = bbox.count_tiles();
+		let storage = if n >= SPARSE_MIN_TILES {
+			Storage::Sparse {
+				items: HashMap::new(),
+				fill: item,
+				clone: I::clone,
+			}
+		} else {
+			Storage::Dense(vec![item; n as usize])
+		};
+		Self { bbox, storage }
+	}
+
+	/// Create a new container with every slot initialized to `f(coord)`.
+	///
+	/// `f` is called once per tile in **row-major** order.
+	pub fn from_fn(bbox: TileBBox, mut f: impl FnMut(TileCoord) -> I) -> Self {
+		let vec = bbox.iter_coords().map(&mut f).collect();
+		Self {
+			bbox,
+			storage: Storage::Dense(vec),
+		}
+	}
+
+	/// Like [`TileBBoxMap::from_fn`], but calls `f` on blocking worker threads, one row at a time,
+	/// with up to `num_cpus::get()` rows in parallel.
+	///
+	/// Use this when computing a value is expensive, e.g. rendering or decoding images.
+	///
+	/// # Errors
+	/// Returns an error if a worker task panics.
+	#[context("Failed to fill TileBBoxMap {:?} in parallel", bbox)]
+	pub async fn from_fn_parallel<F>(bbox: TileBBox, f: F) -> Result<Self>
+	where
+		F: Fn(TileCoord) -> I + Send + Sync + 'static,
+		I: Send + 'static,
+	{
+		let f = Arc::new(f);
+		let coords = bbox.iter_coords().collect::<Vec<_>>();
+		let rows = stream::iter(coords.chunks(bbox.width().max(1) as usize).map(<[TileCoord]>::to_vec))
+			.map(|row| {
+				let f = Arc::clone(&f);
+				tokio::task::spawn_blocking(move || row.into_iter().map(&*f).collect::<Vec<I>>())
+			})
+			.buffered(num_cpus::get())
+			.collect::<Vec<_>>()
+			.await;
+
+		let mut vec = Vec::with_capacity(bbox.count_tiles() as usize);
+		for row in rows {
+			vec.extend(row?);
+		}
+		Ok(Self {
+			bbox,
+			storage: Storage::Dense(vec),
+		})
 	}
 
 	/// Create a new container with all slots initialized using `Default`.
@@ -51,14 +150,50 @@ impl<I> TileBBoxMap<I> {
 	/// Total number of tiles (slots) in the container.
 	#[must_use]
 	pub fn len(&self) -> usize {
-		self.vec.len()
+		self.bbox.count_tiles() as usize
 	}
 
 	/// Whether the container has zero slots. Note: this is equivalent to
 	/// `bbox.count_tiles() == 0`.
 	#[must_use]
 	pub fn is_empty(&self) -> bool {
-		self.vec.is_empty()
+		self.len() == 0
+	}
+
+	/// Whether the values are currently stored sparsely.
+	#[must_use]
+	pub fn is_sparse(&self) -> bool {
+		matches!(self.storage, Storage::Sparse { .. })
+	}
+
+	/// Switch to dense storage, e.g. before many values are written.
+	pub fn make_dense(&mut self) {
+		let storage = std::mem::replace(&mut self.storage, Storage::Dense(Vec::new()));
+		self.storage = match storage {
+			Storage::Sparse { mut items, fill, clone } => Storage::Dense(
+				(0..self.bbox.count_tiles())
+					.map(|index| items.remove(&index).unwrap_or_else(|| clone(&fill)))
+					.collect(),
+			),
+			dense => dense,
+		};
+	}
+
+	/// Switches to dense storage if a sparse map is filled too much.
+	fn check_fill(&mut self) {
+		if let Storage::Sparse { items, .. } = &self.storage
+			&& items.len() as u64 * DENSE_FILL_DIVISOR > self.bbox.count_tiles()
+		{
+			self.make_dense();
+		}
+	}
+
+	/// The value at slot `index`.
+	fn item_at(&self, index: u64) -> &I {
+		match &self.storage {
+			Storage::Dense(vec) => &vec[index as usize],
+			Storage::Sparse { items, fill, .. } => items.get(&index).unwrap_or(fill),
+		}
 	}
 
 	/// The bounding box that defines the grid covered by this container.
@@ -74,7 +209,13 @@ impl<I> TileBBoxMap<I> {
 	#[context("Failed to insert into TileBBoxMap at coord: {:?}", coord)]
 	pub fn insert(&mut self, coord: TileCoord, item: I) -> Result<()> {
 		let index = self.bbox.index_of(&coord)?;
-		self.vec[index as usize] = item;
+		match &mut self.storage {
+			Storage::Dense(vec) => vec[index as usize] = item,
+			Storage::Sparse { items, .. } => {
+				items.insert(index, item);
+				self.check_fill();
+			}
+		}
 		Ok(())
 	}
 
@@ -85,7 +226,7 @@ impl<I> TileBBoxMap<I> {
 	#[context("Failed to get from TileBBoxMap at coord: {:?}", coord)]
 	pub fn get(&self, coord: &TileCoord) -> Result<&I> {
 		let index = self.bbox.index_of(coord)?;
-		Ok(&self.vec[index as usize])
+		Ok(self.item_at(index))
 	}
 
 	/// Get a mutable reference to the value at `coord`.
@@ -95,18 +236,37 @@ impl<I> TileBBoxMap<I> {
 	#[context("Failed to get mutably from TileBBoxMap at coord: {:?}", coord)]
 	pub fn get_mut(&mut self, coord: &TileCoord) -> Result<&mut I> {
 		let index = self.bbox.index_of(coord)?;
-		Ok(&mut self.vec[index as usize])
+		if let Storage::Sparse { items, fill, clone } = &mut self.storage
+			&& !items.contains_key(&index)
+		{
+			items.insert(index, clone(fill));
+			self.check_fill();
+		}
+		Ok(match &mut self.storage {
+			Storage::Dense(vec) => &mut vec[index as usize],
+			Storage::Sparse { items, .. } => items.get_mut(&index).unwrap(),
+		})
 	}
 
 	/// Iterate over `(coord, &value)` pairs in **row-major** order.
 	///
 	/// Coordinates are yielded with `x` increasing fastest, then `y`.
 	pub fn iter(&self) -> impl Iterator<Item = (TileCoord, &I)> {
-		self
-			.vec
-			.iter()
+		(0..self.bbox.count_tiles()).map(move |i| (self.bbox.coord_at_index(i).unwrap(), self.item_at(i)))
+	}
+
+	/// Iterate over `(coord, &mut value)` pairs in **row-major** order.
+	///
+	/// Sparse maps are made dense first.
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = (TileCoord, &mut I)> {
+		self.make_dense();
+		let bbox = self.bbox;
+		let Storage::Dense(vec) = &mut self.storage else {
+			unreachable!()
+		};
+		vec.iter_mut()
 			.enumerate()
-			.map(move |(i, item)| (self.bbox.coord_at_index(i as u64).unwrap(), item))
+			.map(move |(i, item)| (bbox.coord_at_index(i as u64).unwrap(), item))
 	}
 
 	/// Group tiles by their parent tile one level above.
@@ -120,11 +280,11 @@ impl<I> TileBBoxMap<I> {
 		I: Clone,
 	{
 		let bbox1 = self.bbox.leveled_down();
-		self.vec.into_iter().enumerate().fold(
+		let level1 = self.bbox.level - 1;
+		self.into_iter().fold(
 			TileBBoxMap::<Vec<(TileCoord, I)>>::new_default(bbox1),
-			|mut container1, (i, item)| {
-				let coord0 = self.bbox.coord_at_index(i as u64).unwrap();
-				let coord1 = coord0.as_level(self.bbox.level - 1);
+			|mut container1, (coord0, item)| {
+				let coord1 = coord0.as_level(level1);
 				container1.get_mut(&coord1).unwrap().push((coord0, item));
 				container1
 			},
@@ -132,12 +292,27 @@ impl<I> TileBBoxMap<I> {
 	}
 
 	/// Transform all stored values with `f`, keeping the same bbox and order.
-	pub fn map<O: Clone>(self, f: impl FnMut(I) -> O) -> TileBBoxMap<O> {
+	///
+	/// `f` is called once per slot and the result is stored densely.
+	pub fn map<O>(self, mut f: impl FnMut(I) -> O) -> TileBBoxMap<O> {
+		self.map_with_coord(|_, item| f(item))
+	}
+
+	/// Transform all stored values with `f`, which also receives the coordinate of each tile.
+	///
+	/// `f` is called once per slot and the result is stored densely.
+	pub fn map_with_coord<O>(self, mut f: impl FnMut(TileCoord, I) -> O) -> TileBBoxMap<O> {
+		let bbox = self.bbox;
 		TileBBoxMap {
-			bbox: self.bbox,
-			vec: self.vec.into_iter().map(f).collect(),
+			bbox,
+			storage: Storage::Dense(self.into_iter().map(|(coord, item)| f(coord, item)).collect()),
 		}
 	}
+
+	/// Transform all stored values with `f`, marking the slots where `f` returns `None` as missing.
+	pub fn filter_map<O>(self, f: impl FnMut(TileCoord, I) -> Option<O>) -> TileBBoxMap<Option<O>> {
+		self.map_with_coord(f)
+	}
 }
 
 /// Constructors for `TileBBoxMap<Option<I>>` that populate the map from
@@ -176,6 +351,39 @@ impl<I> TileBBoxMap<Option<I>> {
 	}
 }
 
+/// Helpers for maps of optional values, where `None` marks a missing tile.
+impl<I> TileBBoxMap<Option<I>> {
+	/// Number of slots that contain a value.
+	#[must_use]
+	pub fn count_present(&self) -> usize {
+		match &self.storage {
+			Storage::Dense(vec) => vec.iter().filter(|item| item.is_some()).count(),
+			Storage::Sparse { items, fill, .. } => {
+				let set = items.values().filter(|item| item.is_some()).count();
+				if fill.is_some() {
+					set + self.len() - items.len()
+				} else {
+					set
+				}
+			}
+		}
+	}
+
+	/// Move out all present values, yielding `(coord, value)` in row-major order.
+	pub fn into_present(self) -> impl Iterator<Item = (TileCoord, I)> {
+		self.into_iter().filter_map(|(coord, item)| Some((coord, item?)))
+	}
+
+	/// Convert all present values into a [`TileStream`], e.g. to return them from an operation.
+	#[must_use]
+	pub fn into_stream<'a>(self) -> TileStream<'a, I>
+	where
+		I: Send + 'a,
+	{
+		TileStream::from_vec(self.into_present().collect())
+	}
+}
+
 /// Debug prints only the bbox to keep logs compact.
 impl<I: Debug> Debug for TileBBoxMap<I> {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -186,22 +394,63 @@ impl<I: Debug> Debug for TileBBoxMap<I> {
 /// Move out all values, yielding `(coord, value)` in row-major order.
 impl<I> std::iter::IntoIterator for TileBBoxMap<I> {
 	type Item = (TileCoord, I);
-	type IntoIter =
-		std::iter::Map<std::iter::Enumerate<std::vec::IntoIter<I>>, Box<dyn Fn((usize, I)) -> (TileCoord, I) + Send>>;
+	type IntoIter = TileBBoxMapIntoIter<I>;
 
 	fn into_iter(self) -> Self::IntoIter {
-		let bbox = self.bbox;
-		let f = Box::new(move |(i, item)| (bbox.coord_at_index(i as u64).unwrap(), item));
-		self.vec.into_iter().enumerate().map(f)
+		TileBBoxMapIntoIter {
+			bbox: self.bbox,
+			index: 0,
+			storage: match self.storage {
+				Storage::Dense(vec) => Storage::Dense(vec.into_iter().rev().collect()),
+				sparse => sparse,
+			},
+		}
+	}
+}
+
+/// Iterator returned by [`TileBBoxMap::into_iter`].
+pub struct TileBBoxMapIntoIter<I> {
+	bbox: TileBBox,
+	index: u64,
+	/// Dense values are stored in reverse order, so they can be popped.
+	storage: Storage<I>,
+}
+
+impl<I> Iterator for TileBBoxMapIntoIter<I> {
+	type Item = (TileCoord, I);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.index >= self.bbox.count_tiles() {
+			return None;
+		}
+		let item = match &mut self.storage {
+			Storage::Dense(vec) => vec.pop()?,
+			Storage::Sparse { items, fill, clone } => items.remove(&self.index).unwrap_or_else(|| clone(fill)),
+		};
+		let coord = self.bbox.coord_at_index(self.index).unwrap();
+		self.index += 1;
+		Some((coord, item))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let n = (self.bbox.count_tiles() - self.index) as usize;
+		(n, Some(n))
 	}
 }
 
-/// Clone by cloning the underlying value vector. The bbox is `Copy`.
+/// Clone by cloning the stored values. The bbox is `Copy`.
 impl<I: Clone> Clone for TileBBoxMap<I> {
 	fn clone(&self) -> Self {
 		TileBBoxMap {
 			bbox: self.bbox,
-			vec: self.vec.clone(),
+			storage: match &self.storage {
+				Storage::Dense(vec) => Storage::Dense(vec.clone()),
+				Storage::Sparse { items, fill, clone } => Storage::Sparse {
+					items: items.clone(),
+					fill: fill.clone(),
+					clone: *clone,
+				},
+			},
 		}
 	}
 }
@@ -303,6 +552,80 @@ mod tests {
 		);
 	}
 
+	#[tokio::test]
+	async fn from_fn_and_from_fn_parallel() -> Result<()> {
+		let bbox = bb(4, 3, 5, 9, 8);
+		let m1 = TileBBoxMap::from_fn(bbox, |coord| (coord.x, coord.y));
+		let m2 = TileBBoxMap::from_fn_parallel(bbox, |coord| (coord.x, coord.y)).await?;
+		assert_eq!(m1.len(), 28);
+		assert_eq!(
+			m1.into_iter().collect::<Vec<_>>(),
+			m2.clone().into_iter().collect::<Vec<_>>()
+		);
+		assert_eq!(*m2.get(&c(4, 9, 5))?, (9, 5));
+
+		let empty = TileBBoxMap::from_fn_parallel(TileBBox::new_empty(4)?, |coord| coord.x).await?;
+		assert!(empty.is_empty());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn filter_map_and_present_values() -> Result<()> {
+		let bbox = bb(3, 0, 0, 2, 1);
+		let mut m = TileBBoxMap::from_fn(bbox, |coord| coord.x);
+		for (coord, v) in m.iter_mut() {
+			*v += coord.y * 10;
+		}
+		let m = m.filter_map(|_, v| (v != 1 && v != 11).then_some(v));
+		assert_eq!(m.count_present(), 4);
+		assert_eq!(*m.get(&c(3, 1, 0))?, None);
+		assert_eq!(*m.get(&c(3, 2, 1))?, Some(12));
+
+		let values = m.clone().into_present().map(|(_, v)| v).collect::<Vec<_>>();
+		assert_eq!(values, vec![0, 2, 10, 12]);
+
+		let stream = m.into_stream().to_vec().await;
+		assert_eq!(stream.len(), 4);
+		assert_eq!(stream[3], (c(3, 2, 1), 12));
+		Ok(())
+	}
+
+	#[test]
+	fn sparse_storage() -> Result<()> {
+		let bbox = bb(8, 0, 0, 63, 63); // 4096 tiles
+		let mut m = TileBBoxMap::<Option<u32>>::new_default(bbox);
+		assert!(m.is_sparse());
+		assert_eq!(m.len(), 4096);
+
+		m.insert(c(8, 1, 2), Some(12))?;
+		*m.get_mut(&c(8, 63, 63))? = Some(99);
+		assert_eq!(*m.get(&c(8, 1, 2))?, Some(12));
+		assert_eq!(*m.get(&c(8, 2, 1))?, None);
+		assert_eq!(m.count_present(), 2);
+		assert!(m.get(&c(8, 64, 0)).is_err());
+
+		let present = m.clone().into_present().collect::<Vec<_>>();
+		assert_eq!(present, vec![(c(8, 1, 2), 12), (c(8, 63, 63), 99)]);
+		let all = m.iter().map(|(coord, v)| (coord, *v)).collect::<Vec<_>>();
+		assert_eq!(all, m.clone().into_iter().collect::<Vec<_>>());
+		assert_eq!(all.len(), 4096);
+
+		// becomes dense once more than a quarter of the slots is set
+		for i in 0..1023 {
+			m.insert(c(8, i % 64, i / 64), Some(i))?;
+		}
+		assert!(m.is_sparse());
+		m.insert(c(8, 5, 40), Some(1))?;
+		assert!(!m.is_sparse());
+		assert_eq!(m.count_present(), 1025);
+		assert_eq!(*m.get(&c(8, 63, 63))?, Some(99));
+		assert_eq!(*m.get(&c(8, 63, 62))?, None);
+
+		// small maps are dense right away
+		assert!(!TileBBoxMap::<Option<u32>>::new_default(bb(8, 0, 0, 15, 15)).is_sparse());
+		Ok(())
+	}
+
 	#[test]
 	fn map_transforms_inner_items() {
 		let bbox = bb(3, 0, 0, 1, 1);
@@ -83,11 +83,14 @@ impl TileBBoxPyramid {
 	///
 	/// A new `TileBBoxPyramid` populated with bounding boxes derived from `bbox`.
 	/// Levels outside the given range remain empty.
+	/// If `bbox` crosses the antimeridian, each level covers the tiles of both of its parts.
 	#[must_use]
 	pub fn from_geo_bbox(zoom_level_min: u8, zoom_level_max: u8, bbox: &GeoBBox) -> TileBBoxPyramid {
 		let mut pyramid = TileBBoxPyramid::new_empty();
 		for z in zoom_level_min..=zoom_level_max {
-			pyramid.set_level_bbox(TileBBox::from_geo(z, bbox).unwrap());
+			for part in bbox.split_antimeridian() {
+				pyramid.include_bbox(&TileBBox::from_geo(z, &part).unwrap());
+			}
 		}
 		pyramid
 	}
@@ -97,14 +100,40 @@ impl TileBBoxPyramid {
 	/// # Arguments
 	///
 	/// * `geo_bbox` - The geographical bounding box to intersect with.
+	///
+	/// A bounding box that crosses the antimeridian is split into its two parts. Each level is intersected with
+	/// both parts and then covers both intersections, i.e. also the tiles between them.
 	#[context("Failed to intersect {self} with {geo_bbox:?}")]
 	pub fn intersect_geo_bbox(&mut self, geo_bbox: &GeoBBox) -> Result<()> {
 		for (z, tile_bbox) in self.level_bbox.iter_mut().enumerate() {
-			tile_bbox.intersect_with(&TileBBox::from_geo(z as u8, geo_bbox)?)?;
+			let mut result = TileBBox::new_empty(z as u8)?;
+			for part in geo_bbox.split_antimeridian() {
+				let mut bbox = *tile_bbox;
+				bbox.intersect_with(&TileBBox::from_geo(z as u8, &part)?)?;
+				result.include_bbox(&bbox)?;
+			}
+			*tile_bbox = result;
 		}
 		Ok(())
 	}
 
+	/// Returns one pyramid per part of `geo_bbox`, see [`GeoBBox::split_antimeridian`], each intersected with its part.
+	///
+	/// Unlike [`intersect_geo_bbox`](Self::intersect_geo_bbox), the tiles between the two parts of a bounding box
+	/// that crosses the antimeridian are not covered.
+	#[context("Failed to split {self} by {geo_bbox:?}")]
+	pub fn split_geo_bbox(&self, geo_bbox: &GeoBBox) -> Result<Vec<TileBBoxPyramid>> {
+		geo_bbox
+			.split_antimeridian()
+			.iter()
+			.map(|part| {
+				let mut pyramid = self.clone();
+				pyramid.intersect_geo_bbox(part)?;
+				Ok(pyramid)
+			})
+			.collect()
+	}
+
 	/// Expands each bounding box in the pyramid by the specified border offsets.
 	///
 	/// This effectively shifts each bounding box outward by `(x_min, y_min, x_max, y_max)`.
@@ -564,6 +593,31 @@ mod tests {
 		assert!(pyramid.get_level_bbox(6).is_empty());
 	}
 
+	#[test]
+	fn test_intersect_geo_bbox_across_antimeridian() -> Result<()> {
+		let geo_bbox = GeoBBox::new_wrapping(170.0, -20.0, -170.0, -10.0)?;
+
+		let mut pyramid = TileBBoxPyramid::new_full(4);
+		pyramid.intersect_geo_bbox(&geo_bbox)?;
+		assert_eq!(pyramid.get_level_bbox(4).as_array()?, [0, 8, 15, 8]);
+
+		// only the western part overlaps
+		let mut pyramid = TileBBoxPyramid::new_empty();
+		pyramid.set_level_bbox(TileBBox::from_min_and_max(4, 0, 0, 7, 15)?);
+		pyramid.intersect_geo_bbox(&geo_bbox)?;
+		assert_eq!(pyramid.get_level_bbox(4).as_array()?, [0, 8, 0, 8]);
+
+		let pyramid = TileBBoxPyramid::from_geo_bbox(4, 4, &geo_bbox);
+		assert_eq!(pyramid.get_level_bbox(4).as_array()?, [0, 8, 15, 8]);
+
+		// split pyramids don't cover the tiles between the parts
+		let parts = TileBBoxPyramid::new_full(4).split_geo_bbox(&geo_bbox)?;
+		assert_eq!(parts.len(), 2);
+		assert_eq!(parts[0].get_level_bbox(4).as_array()?, [15, 8, 15, 8]);
+		assert_eq!(parts[1].get_level_bbox(4).as_array()?, [0, 8, 0, 8]);
+		Ok(())
+	}
+
 	#[test]
 	fn test_add_border2() {
 		let mut pyramid = TileBBoxPyramid::new_empty();
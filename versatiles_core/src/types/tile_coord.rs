@@ -20,8 +20,8 @@
 //! let geo = coord.as_geo();
 //! ```
 
-use crate::{GeoBBox, TileBBox};
-use anyhow::{Result, ensure};
+use crate::{GeoBBox, TileBBox, TileSize};
+use anyhow::{Result, bail, ensure};
 use std::{
 	f64::consts::PI as PI32,
 	fmt::{self, Debug},
@@ -99,12 +99,80 @@ impl TileCoord {
 		self.as_tile_bbox().to_geo_bbox().unwrap()
 	}
 
+	/// Return the bounding box of this tile in Web Mercator meters (EPSG:3857) as `[x_min, y_min, x_max, y_max]`.
+	#[must_use]
+	pub fn to_mercator_bbox(&self) -> [f64; 4] {
+		self.to_geo_bbox().to_mercator()
+	}
+
+	/// Return the latitude of the tile center in degrees.
+	#[must_use]
+	pub fn center_latitude(&self) -> f64 {
+		let zoom: f64 = 2.0f64.powi(i32::from(self.level));
+		(PI32 * (1.0 - 2.0 * (f64::from(self.y) + 0.5) / zoom))
+			.sinh()
+			.atan()
+			.to_degrees()
+	}
+
+	/// Return the ground resolution in meters per pixel at the center of this tile.
+	#[must_use]
+	pub fn ground_resolution(&self, tile_size: TileSize) -> f64 {
+		tile_size.ground_resolution(self.level, self.center_latitude())
+	}
+
+	/// Return the WMTS scale denominator of this tile's zoom level, see [`TileSize::scale_denominator`].
+	#[must_use]
+	pub fn scale_denominator(&self, tile_size: TileSize) -> f64 {
+		tile_size.scale_denominator(self.level)
+	}
+
 	/// Serialize this coordinate to a compact JSON-like string `{x:…,y:…,z:…}`.
 	#[must_use]
 	pub fn as_json(&self) -> String {
 		format!("{{x:{},y:{},z:{}}}", self.x, self.y, self.level)
 	}
 
+	/// Convert this coordinate to a Bing Maps style quadkey, e.g. `"0231"`.
+	///
+	/// Each character encodes one zoom level, so level 0 results in an empty string.
+	#[must_use]
+	pub fn as_quadkey(&self) -> String {
+		(1..=self.level)
+			.rev()
+			.map(|i| {
+				let mask = 1u32 << (i - 1);
+				let digit = u8::from(self.x & mask != 0) + 2 * u8::from(self.y & mask != 0);
+				char::from(b'0' + digit)
+			})
+			.collect()
+	}
+
+	/// Parse a Bing Maps style quadkey into a tile coordinate. The zoom level equals the length of the quadkey.
+	///
+	/// # Errors
+	/// Returns an error if the quadkey is longer than 31 characters or contains characters other than `0`–`3`.
+	#[context("Failed to parse quadkey '{quadkey}'")]
+	pub fn from_quadkey(quadkey: &str) -> Result<TileCoord> {
+		ensure!(quadkey.len() <= 31, "quadkey must not be longer than 31 characters");
+		let (mut x, mut y) = (0u32, 0u32);
+		for c in quadkey.chars() {
+			x <<= 1;
+			y <<= 1;
+			match c {
+				'0' => {}
+				'1' => x |= 1,
+				'2' => y |= 1,
+				'3' => {
+					x |= 1;
+					y |= 1;
+				}
+				_ => bail!("invalid character '{c}'"),
+			}
+		}
+		TileCoord::new(quadkey.len() as u8, x, y)
+	}
+
 	/// Compute a linear sort index combining zoom and x/y for total ordering.
 	#[must_use]
 	pub fn get_sort_index(&self) -> u64 {
@@ -248,6 +316,38 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn tilecoord_to_mercator_bbox() {
+		let half = 20_037_508.342_789_244;
+		let check = |coord: TileCoord, expected: [f64; 4]| {
+			let mercator = coord.to_mercator_bbox();
+			for (a, b) in mercator.iter().zip(expected) {
+				assert!((a - b).abs() < 1e-6, "{mercator:?} != {expected:?}");
+			}
+		};
+		check(TileCoord::new(0, 0, 0).unwrap(), [-half, -half, half, half]);
+		check(TileCoord::new(1, 1, 0).unwrap(), [0.0, 0.0, half, half]);
+		check(
+			TileCoord::new(5, 3, 4).unwrap(),
+			[
+				-half * 13.0 / 16.0,
+				half * 11.0 / 16.0,
+				-half * 12.0 / 16.0,
+				half * 12.0 / 16.0,
+			],
+		);
+	}
+
+	#[test]
+	fn tilecoord_resolution() {
+		let coord = TileCoord::new(1, 0, 0).unwrap();
+		assert!((coord.center_latitude() - 66.51326044311186).abs() < 1e-9);
+		assert!((coord.ground_resolution(TileSize::Size256) - 31_194.081_103).abs() < 1e-3);
+		assert!((coord.scale_denominator(TileSize::Size512) - 139_770_566.007_179).abs() < 1e-3);
+
+		assert!((TileCoord::new(1, 0, 1).unwrap().center_latitude() + 66.51326044311186).abs() < 1e-9);
+	}
+
 	#[test]
 	fn tilecoord_get_sort_index() {
 		let coord = TileCoord::new(5, 3, 4).unwrap();
@@ -336,6 +436,24 @@ mod tests {
 		assert_eq!(c, TileCoord::new(3, 1, 5).unwrap());
 	}
 
+	#[rstest]
+	#[case(0, 0, 0, "")]
+	#[case(1, 1, 0, "1")]
+	#[case(1, 0, 1, "2")]
+	#[case(3, 3, 5, "213")]
+	#[case(5, 3, 4, "00211")]
+	fn tilecoord_quadkey(#[case] level: u8, #[case] x: u32, #[case] y: u32, #[case] quadkey: &str) {
+		let coord = TileCoord::new(level, x, y).unwrap();
+		assert_eq!(coord.as_quadkey(), quadkey);
+		assert_eq!(TileCoord::from_quadkey(quadkey).unwrap(), coord);
+	}
+
+	#[test]
+	fn tilecoord_quadkey_errors() {
+		assert!(TileCoord::from_quadkey("0124").is_err());
+		assert!(TileCoord::from_quadkey(&"0".repeat(32)).is_err());
+	}
+
 	#[test]
 	fn tilecoord_swap_xy() {
 		let mut coord = TileCoord::new(5, 3, 4).unwrap();
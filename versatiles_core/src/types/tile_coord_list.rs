@@ -0,0 +1,260 @@
+//! An explicit set of tile coordinates, e.g. the tiles to rebuild after an update of the source data.
+//!
+//! A [`TileCoordList`] keeps the coordinates together with the [`TileBBoxPyramid`] covering them,
+//! so readers can limit their traversal to the pyramid and then skip the tiles that are not listed.
+//!
+//! # CSV format
+//!
+//! Lists are read from CSV files. All lines of a file have the same format, one of:
+//! - `z,x,y`: a single tile coordinate,
+//! - `id`: a single tile id, as used by PMTiles (position along the Hilbert curve, see [`HilbertIndex`]),
+//! - `first-last`: an inclusive range of tile ids.
+//!
+//! A header line is skipped, if its first field is not a number.
+//! [`TileCoordList::write_csv`] writes the `z,x,y` format with a header line.
+//!
+//! # Example
+//! ```
+//! use versatiles_core::{TileCoord, TileCoordList};
+//!
+//! let list = TileCoordList::from_csv_reader("z,x,y\n3,1,2\n3,2,2\n".as_bytes()).unwrap();
+//! assert_eq!(list.len(), 2);
+//! assert!(list.contains(&TileCoord::new(3, 2, 2).unwrap()));
+//! assert_eq!(list.bbox_pyramid().get_level_bbox(3).as_array().unwrap(), [1, 2, 2, 2]);
+//!
+//! // tile ids 1 to 4 are the tiles of zoom level 1
+//! let list = TileCoordList::from_csv_reader("1-4\n".as_bytes()).unwrap();
+//! assert_eq!(list.bbox_pyramid().count_tiles(), 4);
+//! ```
+
+use crate::{TileBBoxPyramid, TileCoord, utils::HilbertIndex, utils::read_csv_iter};
+use anyhow::{Context, Result, bail, ensure};
+use std::{
+	collections::HashSet,
+	fs::File,
+	io::{BufRead, BufReader, Write},
+	path::Path,
+};
+use versatiles_derive::context;
+
+/// Lists read from CSV must not contain more tiles than this, so that a single range can't exhaust the memory.
+const MAX_LENGTH: u64 = 20_000_000;
+
+/// A set of tile coordinates and the pyramid covering them, see the [module documentation](self).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TileCoordList {
+	coords: HashSet<TileCoord>,
+	pyramid: TileBBoxPyramid,
+}
+
+impl TileCoordList {
+	/// Creates an empty list.
+	#[must_use]
+	pub fn new() -> TileCoordList {
+		TileCoordList {
+			coords: HashSet::new(),
+			pyramid: TileBBoxPyramid::new_empty(),
+		}
+	}
+
+	/// Reads a list from a CSV file, see the [module documentation](self) for the format.
+	#[context("Failed to read tile coordinate list from {path:?}")]
+	pub fn from_csv_path(path: &Path) -> Result<TileCoordList> {
+		TileCoordList::from_csv_reader(BufReader::new(File::open(path)?))
+	}
+
+	/// Reads a list from CSV data, see the [module documentation](self) for the format.
+	pub fn from_csv_reader(reader: impl BufRead + Send) -> Result<TileCoordList> {
+		let mut list = TileCoordList::new();
+		for entry in read_csv_iter(reader, b',')? {
+			let (fields, line_pos, _byte_pos) = entry?;
+			let first = fields[0].trim();
+			if line_pos == 1 && !first.starts_with(|c: char| c.is_ascii_digit()) {
+				// header line
+				continue;
+			}
+			list
+				.insert_fields(&fields)
+				.with_context(|| format!("line {line_pos}: invalid entry {fields:?}"))?;
+		}
+		Ok(list)
+	}
+
+	fn insert_fields(&mut self, fields: &[String]) -> Result<()> {
+		let parse = |index: usize| fields[index].trim().parse::<u64>();
+		match fields.len() {
+			3 => self.insert_checked(TileCoord::new(
+				u8::try_from(parse(0)?)?,
+				parse(1)?.try_into()?,
+				parse(2)?.try_into()?,
+			)?)?,
+			1 => match fields[0].trim().split_once('-') {
+				Some((first, last)) => {
+					let (first, last) = (first.trim().parse::<u64>()?, last.trim().parse::<u64>()?);
+					ensure!(
+						first <= last,
+						"first tile id ({first}) must be <= last tile id ({last})"
+					);
+					self.ensure_capacity(last - first + 1)?;
+					for id in first..=last {
+						self.insert(TileCoord::from_hilbert_index(id)?);
+					}
+				}
+				None => self.insert_checked(TileCoord::from_hilbert_index(parse(0)?)?)?,
+			},
+			n => bail!("expected 3 fields (z,x,y) or 1 field (tile id or range of tile ids), found {n}"),
+		}
+		Ok(())
+	}
+
+	/// Fails if adding `count` more tiles could exceed [`MAX_LENGTH`].
+	fn ensure_capacity(&self, count: u64) -> Result<()> {
+		ensure!(
+			self.coords.len() as u64 + count <= MAX_LENGTH,
+			"tile coordinate list must not contain more than {MAX_LENGTH} tiles"
+		);
+		Ok(())
+	}
+
+	fn insert_checked(&mut self, coord: TileCoord) -> Result<()> {
+		self.ensure_capacity(1)?;
+		self.insert(coord);
+		Ok(())
+	}
+
+	/// Adds a tile coordinate.
+	pub fn insert(&mut self, coord: TileCoord) {
+		if self.coords.insert(coord) {
+			self.pyramid.include_coord(&coord);
+		}
+	}
+
+	/// Keeps only the coordinates for which `f` returns `true`.
+	pub fn retain(&mut self, mut f: impl FnMut(&TileCoord) -> bool) {
+		self.coords.retain(|coord| f(coord));
+		self.pyramid = TileBBoxPyramid::new_empty();
+		for coord in &self.coords {
+			self.pyramid.include_coord(coord);
+		}
+	}
+
+	/// Whether `coord` is in the list.
+	#[must_use]
+	pub fn contains(&self, coord: &TileCoord) -> bool {
+		self.coords.contains(coord)
+	}
+
+	/// The number of tile coordinates.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.coords.len()
+	}
+
+	/// Whether the list contains no tile coordinates.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.coords.is_empty()
+	}
+
+	/// The smallest pyramid covering all tile coordinates.
+	#[must_use]
+	pub fn bbox_pyramid(&self) -> &TileBBoxPyramid {
+		&self.pyramid
+	}
+
+	/// Writes the list as CSV with a header line and the columns `z,x,y`, ordered by zoom level, row and column.
+	pub fn write_csv(&self, mut writer: impl Write) -> Result<()> {
+		let mut coords: Vec<&TileCoord> = self.coords.iter().collect();
+		coords.sort_by_key(|coord| coord.get_sort_index());
+		writeln!(writer, "z,x,y")?;
+		for coord in coords {
+			writeln!(writer, "{},{},{}", coord.level, coord.x, coord.y)?;
+		}
+		writer.flush()?;
+		Ok(())
+	}
+
+	/// Iterates over the tile coordinates in no particular order.
+	pub fn iter(&self) -> impl Iterator<Item = &TileCoord> {
+		self.coords.iter()
+	}
+}
+
+impl FromIterator<TileCoord> for TileCoordList {
+	fn from_iter<T: IntoIterator<Item = TileCoord>>(iter: T) -> Self {
+		let mut list = TileCoordList::new();
+		for coord in iter {
+			list.insert(coord);
+		}
+		list
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	fn coord(z: u8, x: u32, y: u32) -> TileCoord {
+		TileCoord::new(z, x, y).unwrap()
+	}
+
+	#[test]
+	fn reads_coordinates() -> Result<()> {
+		let list = TileCoordList::from_csv_reader("z,x,y\n3,1,2\n 5 , 10 , 20 \n3,1,2\n".as_bytes())?;
+		assert_eq!(list.len(), 2);
+		assert!(list.contains(&coord(3, 1, 2)));
+		assert!(list.contains(&coord(5, 10, 20)));
+		assert_eq!(list.bbox_pyramid().get_level_min(), Some(3));
+		assert_eq!(list.bbox_pyramid().get_level_max(), Some(5));
+
+		let list = TileCoordList::from_csv_reader("3,1,2\n4,1,2\n".as_bytes())?;
+		assert_eq!(list.len(), 2);
+		Ok(())
+	}
+
+	#[test]
+	fn reads_tile_ids() -> Result<()> {
+		let list = TileCoordList::from_csv_reader("id\n0\n5\n".as_bytes())?;
+		assert_eq!(list, TileCoordList::from_iter([coord(0, 0, 0), coord(2, 0, 0)]));
+
+		let list = TileCoordList::from_csv_reader("0-4\n".as_bytes())?;
+		assert_eq!(list.len(), 5);
+		assert_eq!(list.bbox_pyramid().get_level_max(), Some(1));
+		for id in 0..=4 {
+			assert!(list.contains(&TileCoord::from_hilbert_index(id)?));
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn writes_csv() -> Result<()> {
+		let list = TileCoordList::from_iter([coord(3, 1, 2), coord(0, 0, 0), coord(3, 2, 1)]);
+		let mut csv = Vec::new();
+		list.write_csv(&mut csv)?;
+		assert_eq!(String::from_utf8(csv.clone())?, "z,x,y\n0,0,0\n3,2,1\n3,1,2\n");
+		assert_eq!(TileCoordList::from_csv_reader(csv.as_slice())?, list);
+		Ok(())
+	}
+
+	#[rstest]
+	#[case("3,1,2\n2,9,0\n")]
+	#[case("3,1\n2,0\n")]
+	#[case("3,1,-2\n")]
+	#[case("4-2\n")]
+	#[case("1-x\n")]
+	#[case("0-5000000000\n")]
+	#[case("0-20000000\n")]
+	fn rejects_invalid_lines(#[case] csv: &str) {
+		assert!(TileCoordList::from_csv_reader(csv.as_bytes()).is_err(), "{csv:?}");
+	}
+
+	#[test]
+	fn retain_updates_pyramid() {
+		let mut list = TileCoordList::from_iter([coord(3, 1, 2), coord(3, 5, 6), coord(4, 0, 0)]);
+		list.retain(|c| c.level == 3 && c.x < 4);
+		assert_eq!(list.len(), 1);
+		assert_eq!(list.bbox_pyramid().get_level_bbox(3).as_array().unwrap(), [1, 2, 1, 2]);
+		assert!(list.bbox_pyramid().get_level_bbox(4).is_empty());
+	}
+}
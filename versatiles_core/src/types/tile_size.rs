@@ -1,8 +1,11 @@
 //! Defines the `TileSize` enum representing supported raster or vector tile sizes.
 
-use std::fmt::Debug;
-
+use super::EARTH_RADIUS;
 use anyhow::{Result, bail};
+use std::{f64::consts::PI, fmt::Debug};
+
+/// Standardized rendering pixel size of 0.28 mm, as defined by OGC WMTS.
+const WMTS_PIXEL_SIZE: f64 = 0.000_28;
 
 /// Represents the pixel dimensions of a map tile.
 /// Currently supports 256×256 and 512×512 tiles.
@@ -32,6 +35,22 @@ impl TileSize {
 			TileSize::Size512 => 512,
 		}
 	}
+
+	/// Returns the ground resolution in meters per pixel at the given zoom `level` and `latitude` (in degrees).
+	///
+	/// At the equator and zoom level 0, a 256 px tile has a resolution of about 156543 m/px.
+	#[must_use]
+	pub fn ground_resolution(&self, level: u8, latitude: f64) -> f64 {
+		let resolution = 2.0 * PI * EARTH_RADIUS / (f64::from(self.size()) * 2.0f64.powi(i32::from(level)));
+		resolution * latitude.to_radians().cos()
+	}
+
+	/// Returns the WMTS scale denominator at the given zoom `level`, based on the resolution at the equator
+	/// and the standardized pixel size of 0.28 mm, as used in WMTS capabilities documents.
+	#[must_use]
+	pub fn scale_denominator(&self, level: u8) -> f64 {
+		self.ground_resolution(level, 0.0) / WMTS_PIXEL_SIZE
+	}
 }
 
 impl Debug for TileSize {
@@ -68,6 +87,23 @@ mod tests {
 		assert!(msg.contains("Invalid tile size"));
 	}
 
+	#[rstest]
+	#[case(TileSize::Size256, 0, 0.0, 156_543.033_928)]
+	#[case(TileSize::Size512, 0, 0.0, 78_271.516_964)]
+	#[case(TileSize::Size256, 10, 0.0, 152.874_057)]
+	#[case(TileSize::Size256, 10, 60.0, 76.437_028)]
+	fn ground_resolution(#[case] size: TileSize, #[case] level: u8, #[case] lat: f64, #[case] expected: f64) {
+		assert!((size.ground_resolution(level, lat) - expected).abs() < 1e-6);
+	}
+
+	#[rstest]
+	#[case(TileSize::Size256, 0, 559_082_264.028_718)]
+	#[case(TileSize::Size512, 0, 279_541_132.014_359)]
+	#[case(TileSize::Size256, 14, 34_123.673_342)]
+	fn scale_denominator(#[case] size: TileSize, #[case] level: u8, #[case] expected: f64) {
+		assert!((size.scale_denominator(level) - expected).abs() < 1e-5);
+	}
+
 	#[test]
 	fn clone_copy_and_eq_work() {
 		let a = TileSize::Size256;
@@ -18,6 +18,7 @@
 /// - `from_vec`: Constructs a `TileStream` from a vector of `(TileCoord, T)` items.
 /// - `from_iter_coord_parallel`: Creates a `TileStream` from an iterator of coordinates, processing them in parallel.
 /// - `from_coord_vec_async`: Creates a `TileStream` from a vector of coordinates, applying an async closure.
+/// - `from_coord_vec_async_parallel`: Like `from_coord_vec_async`, but runs a fallible async closure in parallel.
 ///
 /// ## Stream Flattening
 /// - `from_iter_stream`: Flattens multiple `TileStream`s from an iterator of `Future`s into a single `TileStream`.
@@ -33,22 +34,31 @@
 /// - `map_blob_parallel`: Transforms the value of type `T` for each tile in parallel.
 /// - `filter_map_blob_parallel`: Filters and transforms the value of type `T` for each tile in parallel.
 ///
+/// Parallel methods use `num_cpus::get()` tasks, reduced when the global [`MemoryBudget`] is under pressure.
+///
 /// ## Coordinate Transformations
 /// - `map_coord`: Applies a synchronous coordinate transformation to each item.
 ///
+/// ## Cancellation
+/// - `take_until_cancelled`: Ends the stream once a `CancellationToken` is cancelled.
+/// - `take_until_timeout`: Ends the stream after a time limit.
+///
+/// ## Tracing
+/// - `in_span`: Enters a `tracing` span whenever the stream is polled.
+///
 /// ## Utility
 /// - `drain_and_count`: Drains the stream and returns the total count of items.
 ///
 /// # Utility Functions
 /// - `unwrap_result`: Unwraps a `Result`, printing detailed error information and terminating the program on failure.
-use crate::{Blob, TileCoord};
+use crate::{Blob, CancellationToken, MemoryBudget, TileCoord, progress::ConversionMetrics};
 use anyhow::Result;
 use futures::{
 	Future, Stream, StreamExt,
 	future::ready,
 	stream::{self, BoxStream},
 };
-use std::{collections::HashMap, io::Write, pin::Pin, sync::Arc};
+use std::{collections::HashMap, io::Write, pin::Pin, sync::Arc, time::Duration};
 
 /// A stream of tiles represented by `(TileCoord, T)` pairs.
 ///
@@ -155,9 +165,12 @@ where
 			.map(move |coord| {
 				let cb = Arc::clone(&callback);
 				// Spawn a task for each coordinate
-				tokio::task::spawn_blocking(move || (coord, cb(coord)))
+				tokio::task::spawn_blocking(move || {
+					let _busy = ConversionMetrics::worker_busy();
+					(coord, cb(coord))
+				})
 			})
-			.buffer_unordered(num_cpus::get()) // concurrency
+			.buffer_unordered(parallelism()) // concurrency
 			.filter_map(|result| async {
 				match result {
 					Ok((coord, Some(item))) => Some((coord, item)),
@@ -210,6 +223,41 @@ where
 		TileStream { inner: s.boxed() }
 	}
 
+	/// Creates a `TileStream` by calling an async, fallible closure for every coordinate, in parallel.
+	///
+	/// The closure returns `Ok(Some(item))` to emit an item and `Ok(None)` to skip the coordinate.
+	/// Items are emitted in the order they are completed. Like [`TileStream::filter_map_item_parallel`],
+	/// an error aborts the process, so callers should handle recoverable errors in the closure.
+	///
+	/// # Examples
+	/// ```
+	/// # use versatiles_core::{TileCoord, Blob, TileStream};
+	/// # async fn example() {
+	/// let coords = vec![TileCoord::new(0,0,0).unwrap(), TileCoord::new(1,1,1).unwrap()];
+	/// let tile_stream = TileStream::from_coord_vec_async_parallel(coords, |coord| async move {
+	///     Ok((coord.level == 0).then(|| Blob::from("data")))
+	/// });
+	/// assert_eq!(tile_stream.to_vec().await.len(), 1);
+	/// # }
+	/// ```
+	pub fn from_coord_vec_async_parallel<F, Fut>(vec: Vec<TileCoord>, callback: F) -> Self
+	where
+		F: Fn(TileCoord) -> Fut + Send + 'a,
+		Fut: Future<Output = Result<Option<T>>> + Send + 'a,
+	{
+		let s = stream::iter(vec)
+			.map(move |coord| {
+				let future = callback(coord);
+				async move { (coord, future.await) }
+			})
+			.buffer_unordered(parallelism())
+			.filter_map(|(coord, result)| async move {
+				let maybe_item = unwrap_result(result, || format!("Failed to get tile at {coord:?}"));
+				maybe_item.map(|item| (coord, item))
+			});
+		TileStream { inner: s.boxed() }
+	}
+
 	// -------------------------------------------------------------------------
 	// Stream Flattening
 	// -------------------------------------------------------------------------
@@ -238,7 +286,36 @@ where
 		FutureStream: Future<Output = TileStream<'a, T>> + Send + 'a,
 	{
 		TileStream {
-			inner: Box::pin(streams.buffer_unordered(num_cpus::get()).map(|s| s.inner).flatten()),
+			inner: Box::pin(streams.buffer_unordered(parallelism()).map(|s| s.inner).flatten()),
+		}
+	}
+
+	/// Like [`TileStream::from_streams`], but the futures return a `Result`.
+	///
+	/// Errors are reported like errors of the `*_parallel` callbacks, so they terminate the program.
+	///
+	/// # Examples
+	/// ```
+	/// # use versatiles_core::{TileCoord, Blob, TileStream};
+	/// # use futures::{StreamExt, stream};
+	/// # async fn test() {
+	/// let merged = TileStream::from_try_streams(stream::iter(0..2u32).map(|x| async move {
+	///     Ok(TileStream::from_vec(vec![(TileCoord::new(1, x, 0)?, Blob::from("data"))]))
+	/// }));
+	/// assert_eq!(merged.to_vec().await.len(), 2);
+	/// # }
+	/// ```
+	pub fn from_try_streams<FutureStream>(streams: impl Stream<Item = FutureStream> + Send + 'a) -> TileStream<'a, T>
+	where
+		FutureStream: Future<Output = Result<TileStream<'a, T>>> + Send + 'a,
+	{
+		TileStream {
+			inner: Box::pin(
+				streams
+					.buffer_unordered(parallelism())
+					.map(|s| unwrap_result(s, || "Failed to get tile stream".to_string()).inner)
+					.flatten(),
+			),
 		}
 	}
 
@@ -327,7 +404,7 @@ where
 		F: FnMut((TileCoord, T)) -> Fut,
 		Fut: Future<Output = ()>,
 	{
-		self.inner.for_each_concurrent(num_cpus::get(), callback).await;
+		self.inner.for_each_concurrent(parallelism(), callback).await;
 	}
 
 	/// Applies a synchronous callback `callback` to each `(TileCoord, T)` item.
@@ -439,9 +516,12 @@ where
 			.inner
 			.map(move |(coord, item)| {
 				let cb = Arc::clone(&arc_cb);
-				tokio::task::spawn_blocking(move || (coord, cb(item)))
+				tokio::task::spawn_blocking(move || {
+					let _busy = ConversionMetrics::worker_busy();
+					(coord, cb(item))
+				})
 			})
-			.buffer_unordered(num_cpus::get())
+			.buffer_unordered(parallelism())
 			.map(|e| {
 				let (coord, item) = e.unwrap();
 				(
@@ -464,11 +544,12 @@ where
 			.map(move |(coord, item)| {
 				let cb = Arc::clone(&arc_cb);
 				tokio::task::spawn_blocking(move || {
+					let _busy = ConversionMetrics::worker_busy();
 					let s = unwrap_result(cb(coord, item), || format!("Failed to process tile at {coord:?}"));
 					unsafe { std::mem::transmute::<_, TileStream<O>>(s) }
 				})
 			})
-			.buffer_unordered(num_cpus::get())
+			.buffer_unordered(parallelism())
 			.flat_map_unordered(None, |e| e.unwrap().inner);
 		TileStream { inner: s.boxed() }
 	}
@@ -510,9 +591,56 @@ where
 			.inner
 			.map(move |(coord, item)| {
 				let cb = Arc::clone(&arc_cb);
-				tokio::task::spawn_blocking(move || (coord, cb(item)))
+				tokio::task::spawn_blocking(move || {
+					let _busy = ConversionMetrics::worker_busy();
+					(coord, cb(item))
+				})
 			})
-			.buffer_unordered(num_cpus::get())
+			.buffer_unordered(parallelism())
+			.filter_map(|res| async move {
+				let (coord, maybe_item) = res.unwrap();
+				let maybe_item = unwrap_result(maybe_item, || format!("Failed to process tile at {coord:?}"));
+				maybe_item.map(|item| (coord, item))
+			});
+		TileStream { inner: s.boxed() }
+	}
+
+	/// Like [`TileStream::filter_map_item_parallel`], but `callback` also receives the coordinate of the tile.
+	///
+	/// # Examples
+	/// ```
+	/// # use versatiles_core::{TileCoord, Blob, TileStream};
+	/// # async fn test() {
+	/// let stream = TileStream::from_vec(vec![
+	///     (TileCoord::new(0,0,0).unwrap(), Blob::from("data0")),
+	///     (TileCoord::new(1,1,1).unwrap(), Blob::from("data1")),
+	/// ]);
+	///
+	/// let filtered = stream.filter_map_parallel(|coord, value| {
+	///     Ok((coord.level > 0).then(|| Blob::from(format!("{} at z{}", value.as_str(), coord.level))))
+	/// });
+	///
+	/// let items = filtered.to_vec().await;
+	/// assert_eq!(items[0].1.as_str(), "data1 at z1");
+	/// # }
+	/// ```
+	pub fn filter_map_parallel<F, O>(self, callback: F) -> TileStream<'a, O>
+	where
+		F: Fn(TileCoord, T) -> Result<Option<O>> + Send + Sync + 'static,
+		T: 'static,
+		O: Send + Sync + 'static,
+	{
+		let arc_cb = Arc::new(callback);
+		let s = self
+			.inner
+			.map(move |(coord, item)| {
+				let cb = Arc::clone(&arc_cb);
+				tokio::task::spawn_blocking(move || {
+					let _busy = ConversionMetrics::worker_busy();
+					(coord, cb(coord, item))
+				})
+			})
+			.buffer_unordered(parallelism())
 			.filter_map(|res| async move {
 				let (coord, maybe_item) = res.unwrap();
 				let maybe_item = unwrap_result(maybe_item, || format!("Failed to process tile at {coord:?}"));
@@ -607,6 +735,55 @@ where
 		}
 	}
 
+	// -------------------------------------------------------------------------
+	// Cancellation
+	// -------------------------------------------------------------------------
+
+	/// Ends the stream as soon as `token` is cancelled.
+	///
+	/// Items already emitted are kept; no further items are pulled from the inner stream,
+	/// so consumers (e.g. writers) can finish gracefully with the tiles received so far.
+	///
+	/// # Examples
+	/// ```
+	/// # use versatiles_core::{TileCoord, Blob, TileStream, CancellationToken};
+	/// # async fn test() {
+	/// let token = CancellationToken::new();
+	/// token.cancel();
+	/// let stream = TileStream::from_vec(vec![
+	///     (TileCoord::new(0,0,0).unwrap(), Blob::from("data0")),
+	/// ]);
+	/// assert_eq!(stream.take_until_cancelled(token).drain_and_count().await, 0);
+	/// # }
+	/// ```
+	pub fn take_until_cancelled(self, token: CancellationToken) -> Self {
+		TileStream {
+			inner: self.inner.take_until(async move { token.cancelled().await }).boxed(),
+		}
+	}
+
+	/// Enters `span` whenever the stream is polled, so the time spent producing tiles is attributed to it.
+	#[must_use]
+	pub fn in_span(self, span: tracing::Span) -> Self {
+		let mut inner = self.inner;
+		TileStream {
+			inner: stream::poll_fn(move |cx| {
+				let _entered = span.enter();
+				inner.poll_next_unpin(cx)
+			})
+			.boxed(),
+		}
+	}
+
+	/// Ends the stream once `duration` has elapsed, measured from the first poll.
+	///
+	/// Must be polled from within a tokio runtime.
+	pub fn take_until_timeout(self, duration: Duration) -> Self {
+		TileStream {
+			inner: self.inner.take_until(tokio::time::sleep(duration)).boxed(),
+		}
+	}
+
 	// -------------------------------------------------------------------------
 	// Utility
 	// -------------------------------------------------------------------------
@@ -639,6 +816,11 @@ where
 	}
 }
 
+/// Number of parallel tasks, reduced when the memory budget is under pressure.
+fn parallelism() -> usize {
+	MemoryBudget::global().concurrency(num_cpus::get())
+}
+
 /// Unwraps a `Result`, printing a detailed error report and terminating the program on failure.
 ///
 /// * Every layer of context is written on its own line.
@@ -827,6 +1009,24 @@ mod tests {
 		assert_eq!(texts, ["kept-keep0", "kept-keep2"]);
 	}
 
+	#[tokio::test]
+	async fn should_parallel_filter_map_with_coords() {
+		let tile_data = vec![
+			(tc(0, 0, 0), Blob::from("a")),
+			(tc(1, 1, 0), Blob::from("b")),
+			(tc(2, 2, 3), Blob::from("c")),
+		];
+
+		let filtered = TileStream::from_vec(tile_data).filter_map_parallel(|coord, blob| {
+			Ok((coord.level > 0).then(|| Blob::from(format!("{}@{}/{}/{}", blob.as_str(), coord.level, coord.x, coord.y))))
+		});
+
+		let mut items = filtered.to_vec().await;
+		items.sort_by_key(|(coord, _)| coord.level);
+		let texts = items.iter().map(|(_, b)| b.as_str()).collect::<Vec<_>>();
+		assert_eq!(texts, ["b@1/1/0", "c@2/2/3"]);
+	}
+
 	#[tokio::test]
 	async fn should_construct_empty_stream() {
 		let empty = TileStream::<Blob>::empty();
@@ -850,6 +1050,15 @@ mod tests {
 		assert_eq!(items.len(), 2);
 	}
 
+	#[tokio::test]
+	async fn should_construct_from_try_streams() {
+		let merged = TileStream::<Blob>::from_try_streams(
+			stream::iter(0..3).map(|x| async move { Ok(TileStream::from_vec(vec![(tc(2, x, 0), Blob::from("sub"))])) }),
+		);
+		let items = merged.to_vec().await;
+		assert_eq!(items.len(), 3);
+	}
+
 	#[tokio::test]
 	async fn should_return_none_if_stream_is_empty() {
 		let mut empty = TileStream::<Blob>::empty();
@@ -925,6 +1134,21 @@ mod tests {
 		assert_eq!(items[0].1.as_str(), "keep");
 	}
 
+	#[tokio::test]
+	async fn should_create_from_coord_vec_async_parallel() {
+		let coords = vec![tc(0, 0, 0), tc(1, 1, 1), tc(2, 2, 2)];
+
+		let stream = TileStream::from_coord_vec_async_parallel(coords, |coord| async move {
+			Ok((coord.level > 0).then(|| Blob::from(format!("z{}", coord.level))))
+		});
+
+		let mut items = stream.to_vec().await;
+		items.sort_by_key(|(coord, _)| coord.level);
+		assert_eq!(items.len(), 2);
+		assert_eq!(items[0].1.as_str(), "z1");
+		assert_eq!(items[1].1.as_str(), "z2");
+	}
+
 	#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 	async fn test_map_item_parallel_parallelism() {
 		let stream = TileStream::from_vec((1..=6).map(|i| (tc(12, i, 0), i)).collect::<Vec<_>>());
@@ -1043,6 +1267,45 @@ mod tests {
 		assert!(max_parallel.load(Ordering::SeqCst) > 1);
 	}
 
+	#[tokio::test]
+	async fn should_stop_when_cancelled() {
+		let token = CancellationToken::new();
+		let items = (0..10).map(|i| (tc(4, i, 0), i)).collect::<Vec<_>>();
+
+		let mut stream = TileStream::from_vec(items).take_until_cancelled(token.clone());
+		assert_eq!(stream.next().await.unwrap().1, 0);
+		assert_eq!(stream.next().await.unwrap().1, 1);
+		token.cancel();
+		assert!(stream.next().await.is_none());
+	}
+
+	#[tokio::test]
+	async fn should_pass_everything_if_not_cancelled() {
+		let token = CancellationToken::new();
+		let items = (0..10).map(|i| (tc(4, i, 0), i)).collect::<Vec<_>>();
+		let stream = TileStream::from_vec(items).take_until_cancelled(token);
+		assert_eq!(stream.drain_and_count().await, 10);
+	}
+
+	#[tokio::test]
+	async fn should_stop_after_timeout() {
+		let s = stream::iter(0..u32::MAX).then(|i| async move {
+			tokio::time::sleep(Duration::from_millis(1)).await;
+			(tc(20, i, 0), i)
+		});
+		let stream = TileStream::from_stream(s.boxed()).take_until_timeout(Duration::from_millis(30));
+		let count = stream.drain_and_count().await;
+		assert!(count > 0);
+		assert!(count < 1000);
+	}
+
+	#[tokio::test]
+	async fn should_keep_items_in_span() {
+		let stream = TileStream::from_vec(vec![(tc(1, 0, 0), 1), (tc(1, 1, 0), 2)]);
+		let items = stream.in_span(tracing::info_span!("test")).to_vec().await;
+		assert_eq!(items, vec![(tc(1, 0, 0), 1), (tc(1, 1, 0), 2)]);
+	}
+
 	#[tokio::test]
 	async fn should_merge_streams_with_large_cores_per_task() {
 		// cores_per_task larger than CPU count should still work (limit clamped to 1)
@@ -31,7 +31,7 @@
 //! # }
 //! ```
 
-use super::{TileJsonValues, VectorLayers};
+use super::{STANDARD_KEYS, TileJsonValues, VectorLayers};
 use crate::{
 	Blob, GeoBBox, GeoCenter, TileBBoxPyramid, TileFormat, TileSchema, TileSize, TileType, TilesReaderParameters,
 	json::*,
@@ -93,8 +93,10 @@ impl TileJSON {
 		for (k, v) in object.iter() {
 			match k.as_str() {
 				"bounds" => {
-					// Parse `[west, south, east, north]`
-					r.bounds = Some(GeoBBox::try_from(v.as_array()?.as_number_vec()?)?);
+					// Parse `[west, south, east, north]`, west > east crosses the antimeridian
+					let b = v.as_array()?.as_number_vec()?;
+					ensure!(b.len() == 4, "'bounds' must have 4 elements, but has {}", b.len());
+					r.bounds = Some(GeoBBox::new_wrapping(b[0], b[1], b[2], b[3])?);
 				}
 				"center" => {
 					// Parse `[lon, lat, zoom]`
@@ -208,6 +210,7 @@ impl TileJSON {
 	/// - If the pyramid includes a `GeoBBox`, intersects or sets `self.bounds` via [`TileJSON::limit_bbox`].
 	/// - If the pyramid includes `zoom_min`, calls [`TileJSON::set_min_zoom`].
 	/// - If the pyramid includes `zoom_max`, calls [`TileJSON::set_max_zoom`].
+	/// - Moves the zoom level of `self.center` into the new zoom range.
 	pub fn update_from_pyramid(&mut self, pyramid: &TileBBoxPyramid) {
 		if let Some(bbox) = pyramid.get_geo_bbox() {
 			self.limit_bbox(bbox);
@@ -218,6 +221,16 @@ impl TileJSON {
 		if let Some(z) = pyramid.get_level_max() {
 			self.set_max_zoom(z);
 		}
+		self.limit_center_zoom();
+	}
+
+	/// Moves the zoom level of `self.center` into the range of `minzoom` and `maxzoom`, as required by TileJSON.
+	fn limit_center_zoom(&mut self) {
+		if let Some(center) = &mut self.center {
+			let min = self.values.get_byte("minzoom").unwrap_or(0);
+			let max = self.values.get_byte("maxzoom").unwrap_or(30);
+			center.clamp_zoom(min, max);
+		}
 	}
 
 	// -------------------------------------------------------------------------
@@ -236,6 +249,20 @@ impl TileJSON {
 		self.values.get_str(key)
 	}
 
+	/// Returns all keys that are not part of the `TileJSON` 3.0.0 specification, with their values.
+	///
+	/// These are kept as they are, e.g. provenance metadata like `generator`, `planetiler:*`
+	/// or `tippecanoe_decisions`, so they survive conversions between containers.
+	pub fn iter_extras(&self) -> impl Iterator<Item = (String, JsonValue)> + '_ {
+		self.values.iter_extras()
+	}
+
+	/// Returns `true` if `key` is part of the `TileJSON` 3.0.0 specification.
+	#[must_use]
+	pub fn is_standard_key(key: &str) -> bool {
+		STANDARD_KEYS.contains(&key) || matches!(key, "bounds" | "center" | "vector_layers")
+	}
+
 	/// Inserts or updates a byte (`u8`) value in `self.values`.
 	pub fn set_byte(&mut self, key: &str, value: u8) -> Result<()> {
 		self.values.insert(key, &JsonValue::from(value))
@@ -313,7 +340,8 @@ impl TileJSON {
 
 	/// Merges `other` into this `TileJSON` with specific rules:
 	/// 1. **Bounds**: extends or sets `self.bounds` if `other.bounds` is present.
-	/// 2. **Center**: overwrites `self.center` if `other.center` is `Some`.
+	/// 2. **Center**: overwrites `self.center` if `other.center` is `Some`, then moves its zoom level
+	///    into the merged zoom range.
 	/// 3. **minzoom** / **maxzoom**: uses the min or max across the two.
 	/// 4. **Other values**: overwrites conflicts from `other.values`.
 	/// 5. **Vector layers**: merges layers from `other`, overwriting existing layer IDs if needed.
@@ -353,6 +381,8 @@ impl TileJSON {
 
 		// 5. Merge vector_layers
 		self.vector_layers.merge(&other.vector_layers)?;
+
+		self.limit_center_zoom();
 		Ok(())
 	}
 
@@ -491,16 +521,21 @@ impl TileJSON {
 	/// Parses `TileJSON` from a blob or returns `TileJSON::default()` on failure.
 	///
 	/// Logs a warning with the parse error and falls back to a minimal default.
+	/// Outdated `TileJSON` 1.x and 2.x documents are up-converted to 3.0.0, see [`Self::upgrade`].
 	///
 	/// # Returns
 	/// A valid `TileJSON` even if the input is invalid.
 	#[must_use]
 	pub fn try_from_blob_or_default(blob: &Blob) -> TileJSON {
-		TileJSON::try_from(blob.as_str()).unwrap_or_else(|e| {
+		let mut tilejson = TileJSON::try_from(blob.as_str()).unwrap_or_else(|e| {
 			log::warn!("Failed to parse TileJSON: {e}");
 			log::warn!("Use default TileJSON instead");
 			TileJSON::default()
-		})
+		});
+		if tilejson.upgrade() {
+			log::debug!("upgraded TileJSON to version 3.0.0");
+		}
+		tilejson
 	}
 }
 
@@ -577,6 +612,7 @@ impl Debug for TileJSON {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::TileBBox;
 
 	/// Creates a minimal valid `TileJSON` object in the form of `JsonObject`.
 	fn make_test_json_object() -> JsonObject {
@@ -746,6 +782,27 @@ mod tests {
 		assert_eq!(tj, TileJSON::default());
 	}
 
+	#[test]
+	fn should_try_from_blob_or_default_upgrade_outdated_version() {
+		let blob = Blob::from(r#"{"tilejson":"2.2.0","name":"old"}"#);
+		let tj = TileJSON::try_from_blob_or_default(&blob);
+		assert_eq!(tj.get_str("tilejson"), Some("3.0.0"));
+		assert_eq!(tj.get_str("name"), Some("old"));
+	}
+
+	#[test]
+	fn should_keep_extras() -> Result<()> {
+		let text = r#"{"generator":"tippecanoe v2.0","name":"test","tilejson":"3.0.0","tippecanoe_decisions":{"basezoom":0,"drop_rate":2.5}}"#;
+		let tj = TileJSON::try_from(text)?;
+		assert_eq!(tj.as_string(), text);
+
+		let keys = tj.iter_extras().map(|(k, _)| k).collect::<Vec<_>>();
+		assert_eq!(keys, ["generator", "tippecanoe_decisions"]);
+		assert!(TileJSON::is_standard_key("vector_layers"));
+		assert!(!TileJSON::is_standard_key("generator"));
+		Ok(())
+	}
+
 	#[test]
 	fn should_set_and_get_string_and_str() -> Result<()> {
 		let mut tj = TileJSON::default();
@@ -808,6 +865,37 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn should_limit_center_zoom_to_zoom_range() -> Result<()> {
+		let mut tj1 = TileJSON {
+			center: Some(GeoCenter(1.0, 1.0, 2)),
+			..Default::default()
+		};
+		tj1.set_byte("minzoom", 5)?;
+		tj1.set_byte("maxzoom", 8)?;
+
+		// the center is moved into the zoom range
+		let mut tj2 = TileJSON::default();
+		tj2.merge(&tj1)?;
+		assert_eq!(tj2.center, Some(GeoCenter(1.0, 1.0, 5)));
+
+		// the merged zoom range includes the zoom level of the new center
+		let mut tj3 = TileJSON {
+			center: Some(GeoCenter(2.0, 2.0, 14)),
+			..Default::default()
+		};
+		tj3.set_byte("maxzoom", 14)?;
+		tj1.merge(&tj3)?;
+		assert_eq!(tj1.center, Some(GeoCenter(2.0, 2.0, 14)));
+
+		// the pyramid limits the zoom range
+		let mut pyramid = TileBBoxPyramid::new_empty();
+		pyramid.set_level_bbox(TileBBox::new_full(10)?);
+		tj1.update_from_pyramid(&pyramid);
+		assert_eq!(tj1.center, Some(GeoCenter(2.0, 2.0, 10)));
+		Ok(())
+	}
+
 	#[test]
 	fn should_return_none_for_missing_getters() {
 		let tj = TileJSON::default();
@@ -867,6 +955,16 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn should_round_trip_bounds_across_antimeridian() -> Result<()> {
+		let tj = TileJSON::try_from(r#"{"tilejson":"3.0.0","bounds":[170,-20,-170,-10]}"#)?;
+		assert_eq!(tj.bounds.unwrap().as_array(), [170.0, -20.0, -170.0, -10.0]);
+		assert!(tj.as_string().contains(r#""bounds":[170,-20,-170,-10]"#));
+
+		assert!(TileJSON::try_from(r#"{"tilejson":"3.0.0","bounds":[170,-20,-170]}"#).is_err());
+		Ok(())
+	}
+
 	#[test]
 	fn should_convert_into_string_and_blob() {
 		let tj = TileJSON::default();
@@ -1,10 +1,13 @@
 mod lib;
 mod tilejson_value;
 mod tilejson_values;
+mod validation;
 mod vector_layer;
 
 use tilejson_value::TileJsonValue;
-use tilejson_values::TileJsonValues;
+use tilejson_values::{STANDARD_KEYS, TileJsonValues};
 use vector_layer::VectorLayers;
 
 pub use lib::TileJSON;
+pub use validation::TileJsonReport;
+pub use vector_layer::VectorLayer;
@@ -10,6 +10,8 @@ pub enum TileJsonValue {
 	String(String),
 	/// A single byte (stored as `u8`). Must be in `[0, 255]`.
 	Byte(u8),
+	/// Any other JSON value, e.g. an object. Only used for non-standard keys.
+	Json(JsonValue),
 }
 
 impl TileJsonValue {
@@ -35,15 +37,33 @@ impl TileJsonValue {
 			TileJsonValue::Byte(b) => JsonValue::from(*b),
 			TileJsonValue::List(l) => JsonValue::from(l),
 			TileJsonValue::String(s) => JsonValue::from(s),
+			TileJsonValue::Json(j) => j.clone(),
 		}
 	}
 
-	/// Returns a string describing which variant this `TileJsonValue` is (`"List"`, `"String"`, or `"Byte"`).
+	/// Returns a string describing which variant this `TileJsonValue` is (`"List"`, `"String"`, `"Byte"` or `"JSON"`).
 	pub fn get_type(&self) -> &str {
 		match self {
 			TileJsonValue::Byte(_) => "Byte",
 			TileJsonValue::List(_) => "List",
 			TileJsonValue::String(_) => "String",
+			TileJsonValue::Json(_) => "JSON",
+		}
+	}
+
+	/// Converts a [`JsonValue`] without losing information, as used for non-standard keys.
+	///
+	/// Strings, lists of strings and integers in `[0, 255]` use their typed variant,
+	/// everything else is kept as [`TileJsonValue::Json`].
+	pub fn from_json_lossless(value: &JsonValue) -> Self {
+		match value {
+			JsonValue::String(s) => TileJsonValue::String(s.to_owned()),
+			JsonValue::Number(n) if (0.0..=255.0).contains(n) && n.fract() == 0.0 => TileJsonValue::Byte(*n as u8),
+			JsonValue::Array(a) => match a.as_string_vec() {
+				Ok(list) => TileJsonValue::List(list),
+				Err(_) => TileJsonValue::Json(value.clone()),
+			},
+			_ => TileJsonValue::Json(value.clone()),
 		}
 	}
 
@@ -69,6 +89,12 @@ impl From<u8> for TileJsonValue {
 	}
 }
 
+impl From<&str> for TileJsonValue {
+	fn from(value: &str) -> Self {
+		TileJsonValue::String(value.to_owned())
+	}
+}
+
 impl TryFrom<&JsonValue> for TileJsonValue {
 	type Error = anyhow::Error;
 
@@ -93,4 +119,23 @@ impl TryFrom<&JsonValue> for TileJsonValue {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+	use super::*;
+	use crate::json::parse_json_str;
+
+	#[test]
+	fn from_json_lossless() {
+		let convert = |json: &str| TileJsonValue::from_json_lossless(&parse_json_str(json).unwrap());
+		assert_eq!(convert(r#""abc""#), TileJsonValue::String("abc".to_string()));
+		assert_eq!(convert("12"), TileJsonValue::Byte(12));
+		assert_eq!(
+			convert(r#"["a","b"]"#),
+			TileJsonValue::List(vec!["a".to_string(), "b".to_string()])
+		);
+		for json in ["12.5", "1000", "-1", "true", "null", "[1,2]", r#"{"a":1}"#] {
+			let value = convert(json);
+			assert_eq!(value.get_type(), "JSON", "{json}");
+			assert_eq!(value.as_json_value(), parse_json_str(json).unwrap());
+		}
+	}
+}
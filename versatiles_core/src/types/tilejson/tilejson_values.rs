@@ -3,6 +3,26 @@ use crate::json::JsonValue;
 use anyhow::{Result, bail};
 use std::collections::BTreeMap;
 
+/// Keys of the `TileJSON` 3.0.0 specification that are stored in [`TileJsonValues`].
+///
+/// `bounds`, `center` and `vector_layers` are stored as separate fields of [`TileJSON`](super::TileJSON).
+pub const STANDARD_KEYS: [&str; 14] = [
+	"attribution",
+	"data",
+	"description",
+	"fillzoom",
+	"grids",
+	"legend",
+	"maxzoom",
+	"minzoom",
+	"name",
+	"scheme",
+	"template",
+	"tilejson",
+	"tiles",
+	"version",
+];
+
 /// A map storing string keys and their associated typed JSON values.
 ///
 /// By default, this map includes the key `"tilejson"` with a default value of
@@ -14,12 +34,20 @@ impl TileJsonValues {
 	/// Inserts a key-value pair into the internal `BTreeMap`,
 	/// converting the [`JsonValue`] into a [`TileJsonValue`].
 	///
+	/// Values of non-standard keys (see [`STANDARD_KEYS`]) are kept as they are,
+	/// so metadata like `generator` or `tippecanoe_decisions` survives conversions.
+	///
 	/// # Errors
 	///
-	/// Returns an error if the provided `JsonValue` cannot be converted into
+	/// Returns an error if the value of a standard key cannot be converted into
 	/// a `TileJsonValue` (e.g., out-of-range numeric value).
 	pub fn insert(&mut self, key: &str, value: &JsonValue) -> Result<()> {
-		self.0.insert(key.to_owned(), TileJsonValue::try_from(value)?);
+		let value = if STANDARD_KEYS.contains(&key) {
+			TileJsonValue::try_from(value)?
+		} else {
+			TileJsonValue::from_json_lossless(value)
+		};
+		self.0.insert(key.to_owned(), value);
 		Ok(())
 	}
 
@@ -85,6 +113,15 @@ impl TileJsonValues {
 		self.0.iter().map(|(k, v)| (k.clone(), v.as_json_value()))
 	}
 
+	/// Returns an iterator over all non-standard keys and their values, see [`STANDARD_KEYS`].
+	pub fn iter_extras(&self) -> impl Iterator<Item = (String, JsonValue)> + '_ {
+		self
+			.0
+			.iter()
+			.filter(|(k, _)| !STANDARD_KEYS.contains(&k.as_str()))
+			.map(|(k, v)| (k.clone(), v.as_json_value()))
+	}
+
 	/// Updates or inserts a byte (`u8`) for the given `key`.
 	/// The provided `update` closure receives the current value (if any)
 	/// and returns the new byte value to be stored.
@@ -144,10 +181,33 @@ mod tests {
 	fn insert_out_of_range_byte() {
 		let mut tv = TileJsonValues::default();
 		// 999.0 is out of byte range
-		let result = tv.insert("zoom", &JsonValue::from(999_f64));
+		let result = tv.insert("maxzoom", &JsonValue::from(999_f64));
 		assert!(result.is_err());
 	}
 
+	#[test]
+	fn insert_and_iterate_extras() -> Result<()> {
+		let mut tv = TileJsonValues::default();
+		tv.insert("name", &JsonValue::from("Test"))?;
+		tv.insert("generator", &JsonValue::from("planetiler"))?;
+		tv.insert("planetiler:osm:sequence", &JsonValue::from(3_456_789_f64))?;
+		tv.insert("tippecanoe_decisions", &JsonValue::from(vec![("basezoom", 0.0)]))?;
+
+		let extras = tv.iter_extras().collect::<Vec<_>>();
+		assert_eq!(
+			extras,
+			[
+				("generator".to_string(), JsonValue::from("planetiler")),
+				("planetiler:osm:sequence".to_string(), JsonValue::from(3_456_789_f64)),
+				(
+					"tippecanoe_decisions".to_string(),
+					JsonValue::from(vec![("basezoom", 0.0)])
+				),
+			]
+		);
+		Ok(())
+	}
+
 	#[test]
 	fn insert_and_retrieve_list() -> Result<()> {
 		let mut tv = TileJsonValues::default();
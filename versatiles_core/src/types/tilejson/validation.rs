@@ -0,0 +1,309 @@
+//! Validation of `TileJSON` documents against the `TileJSON` 2.x and 3.0 specifications,
+//! and up-conversion of outdated documents to 3.0.0.
+//!
+//! In contrast to [`TileJSON::check_raster`] and [`TileJSON::check_vector`], which stop at the first problem,
+//! [`TileJSON::validate`] collects every problem into a [`TileJsonReport`]:
+//! - **errors** violate the specification, e.g. a missing version or a raster tileset with `vector_layers`,
+//! - **warnings** are accepted but should be fixed, e.g. an outdated 2.x version.
+//!
+//! # Example
+//! ```rust
+//! # use versatiles_core::TileJSON;
+//! let mut tilejson = TileJSON::try_from(r#"{"tilejson":"2.2.0","minzoom":0,"maxzoom":14}"#).unwrap();
+//!
+//! let report = tilejson.validate();
+//! assert!(report.is_valid());
+//! assert_eq!(report.warnings.len(), 1);
+//!
+//! assert!(tilejson.upgrade());
+//! assert_eq!(tilejson.get_str("tilejson"), Some("3.0.0"));
+//! ```
+
+use super::TileJSON;
+use crate::TileType;
+use anyhow::{Result, bail};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// The `TileJSON` version that outdated documents are up-converted to.
+const CURRENT_VERSION: &str = "3.0.0";
+
+static VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^([123])\.[012]\.[01]$").unwrap());
+
+/// The result of [`TileJSON::validate`]: all errors and warnings found in a `TileJSON`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TileJsonReport {
+	/// Violations of the specification.
+	pub errors: Vec<String>,
+	/// Problems that are accepted, but should be fixed.
+	pub warnings: Vec<String>,
+}
+
+impl TileJsonReport {
+	/// Returns `true` if no errors were found. Warnings are ignored.
+	#[must_use]
+	pub fn is_valid(&self) -> bool {
+		self.errors.is_empty()
+	}
+
+	/// Returns `true` if neither errors nor warnings were found.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.errors.is_empty() && self.warnings.is_empty()
+	}
+
+	/// Applies the report, using `name` to describe the validated `TileJSON` in messages.
+	///
+	/// - `strict`: errors fail, warnings are logged as warnings.
+	/// - otherwise: errors are logged as warnings, warnings are logged as debug messages.
+	///
+	/// # Errors
+	/// Returns an error listing all errors, if `strict` is set and the report is not valid.
+	pub fn check(&self, name: &str, strict: bool) -> Result<()> {
+		if strict {
+			if !self.is_valid() {
+				bail!("invalid TileJSON in {name}: {}", self.errors.join("; "));
+			}
+			for warning in &self.warnings {
+				log::warn!("TileJSON in {name}: {warning}");
+			}
+		} else {
+			for error in &self.errors {
+				log::warn!("invalid TileJSON in {name}: {error}");
+			}
+			for warning in &self.warnings {
+				log::debug!("TileJSON in {name}: {warning}");
+			}
+		}
+		Ok(())
+	}
+}
+
+impl TileJSON {
+	/// Validates this `TileJSON` against the `TileJSON` 2.x and 3.0 specifications.
+	///
+	/// Checks the version, the types of all known fields, the zoom range, and the `vector_layers`,
+	/// which 3.0 requires for vector tilesets and forbids for raster tilesets.
+	/// The `tiles` URLs are not required, as they are added when the tiles are served.
+	#[must_use]
+	pub fn validate(&self) -> TileJsonReport {
+		let mut report = TileJsonReport::default();
+		let errors = &mut report.errors;
+		let warnings = &mut report.warnings;
+
+		// 3.1 tilejson - required
+		let major = match self.values.get_str("tilejson") {
+			None => {
+				errors.push("missing 'tilejson' version".to_string());
+				None
+			}
+			Some(version) => match VERSION_REGEX.captures(version) {
+				None => {
+					errors.push(format!("invalid 'tilejson' version '{version}'"));
+					None
+				}
+				Some(captures) => {
+					let major = captures[1].parse::<u8>().unwrap();
+					if major < 3 {
+						warnings.push(format!(
+							"outdated 'tilejson' version '{version}', should be '{CURRENT_VERSION}'"
+						));
+					}
+					Some(major)
+				}
+			},
+		};
+
+		for key in ["tiles", "data", "grids"] {
+			if let Err(e) = self.values.check_optional_list(key) {
+				errors.push(e.to_string());
+			}
+		}
+		for key in ["attribution", "description", "legend", "name", "scheme", "template"] {
+			if let Err(e) = self.values.check_optional_string(key) {
+				errors.push(e.to_string());
+			}
+		}
+		for key in ["fillzoom", "minzoom", "maxzoom"] {
+			if let Err(e) = self.values.check_optional_byte(key) {
+				errors.push(e.to_string());
+			}
+		}
+
+		if let Some(scheme) = self.values.get_str("scheme")
+			&& scheme != "xyz"
+			&& scheme != "tms"
+		{
+			errors.push(format!("invalid 'scheme' '{scheme}', expected 'xyz' or 'tms'"));
+		}
+
+		// MBTiles containers often use versions like "3.0", so this is only a warning
+		if let Some(version) = self.values.get_str("version")
+			&& !Regex::new(r"^\d+\.\d+\.\d+$").unwrap().is_match(version)
+		{
+			warnings.push(format!("'version' '{version}' is not a semantic version"));
+		}
+
+		let minzoom = self.values.get_byte("minzoom");
+		let maxzoom = self.values.get_byte("maxzoom");
+		if let Some(z) = maxzoom.filter(|z| *z > 30) {
+			errors.push(format!("'maxzoom' ({z}) must be <= 30"));
+		}
+		if let (Some(min), Some(max)) = (minzoom, maxzoom)
+			&& min > max
+		{
+			errors.push(format!("'minzoom' ({min}) must be <= 'maxzoom' ({max})"));
+		}
+
+		if let Some(center) = &self.center {
+			if let Err(e) = center.check() {
+				errors.push(e.to_string());
+			}
+			let zoom = center.2;
+			if minzoom.is_some_and(|min| zoom < min) || maxzoom.is_some_and(|max| zoom > max) {
+				warnings.push(format!("zoom level of 'center' ({zoom}) is outside of the zoom range"));
+			}
+		}
+
+		// 3.3 vector_layers - required for vector tilesets since 3.0.0
+		let tile_type = self.tile_type.or(self.tile_format.map(|f| f.to_type()));
+		match tile_type {
+			Some(TileType::Vector) if self.vector_layers.0.is_empty() => {
+				if major.is_some_and(|m| m < 3) {
+					warnings.push("vector tileset has no 'vector_layers'".to_string());
+				} else {
+					errors.push("vector tilesets must have 'vector_layers'".to_string());
+				}
+			}
+			Some(TileType::Raster) if !self.vector_layers.0.is_empty() => {
+				errors.push("raster tilesets must not have 'vector_layers'".to_string());
+			}
+			_ => {}
+		}
+		if let Err(e) = self.vector_layers.check() {
+			errors.push(format!("invalid 'vector_layers': {e:#}"));
+		}
+
+		report
+	}
+
+	/// Up-converts a `TileJSON` 1.x or 2.x document to 3.0.0.
+	///
+	/// All fields of 2.x are still valid in 3.0.0, so only the version is changed.
+	/// Returns `true` if the document was changed.
+	pub fn upgrade(&mut self) -> bool {
+		let Some(version) = self.values.get_str("tilejson") else {
+			return false;
+		};
+		let outdated = VERSION_REGEX
+			.captures(version)
+			.is_some_and(|captures| &captures[1] < "3");
+		if outdated {
+			self.values.set("tilejson", CURRENT_VERSION);
+		}
+		outdated
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::TileFormat;
+
+	fn parse(json: &str) -> TileJSON {
+		TileJSON::try_from(json).unwrap()
+	}
+
+	#[test]
+	fn valid_documents() {
+		assert!(parse(r#"{"tilejson":"3.0.0"}"#).validate().is_empty());
+		assert!(
+			parse(r#"{"tilejson":"3.0.0","tile_type":"raster","minzoom":0,"maxzoom":14}"#)
+				.validate()
+				.is_empty()
+		);
+		assert!(
+			parse(r#"{"tilejson":"3.0.0","tile_type":"vector","vector_layers":[{"id":"water","fields":{}}]}"#)
+				.validate()
+				.is_empty()
+		);
+	}
+
+	#[test]
+	fn collects_all_errors() {
+		let report = parse(
+			r#"{"tilejson":"4.0.0","name":3,"minzoom":10,"maxzoom":5,"scheme":"abc","tile_format":"image/png","vector_layers":[{"id":"water","fields":{}}]}"#,
+		)
+		.validate();
+		assert_eq!(
+			report.errors,
+			[
+				"invalid 'tilejson' version '4.0.0'",
+				"Item 'name' is 'Byte' and not a 'String'",
+				"invalid 'scheme' 'abc', expected 'xyz' or 'tms'",
+				"'minzoom' (10) must be <= 'maxzoom' (5)",
+				"raster tilesets must not have 'vector_layers'",
+			]
+		);
+		assert!(!report.is_valid());
+	}
+
+	#[test]
+	fn invalid_version() {
+		let mut tilejson = parse(r#"{"tilejson":"latest"}"#);
+		assert_eq!(tilejson.validate().errors, ["invalid 'tilejson' version 'latest'"]);
+		assert!(!tilejson.upgrade());
+	}
+
+	#[test]
+	fn vector_layers_by_version() {
+		let mut tilejson = parse(r#"{"tilejson":"2.2.0","tile_type":"vector"}"#);
+		let report = tilejson.validate();
+		assert!(report.is_valid());
+		assert_eq!(
+			report.warnings,
+			[
+				"outdated 'tilejson' version '2.2.0', should be '3.0.0'",
+				"vector tileset has no 'vector_layers'"
+			]
+		);
+
+		assert!(tilejson.upgrade());
+		assert!(!tilejson.upgrade());
+		assert_eq!(
+			tilejson.validate().errors,
+			["vector tilesets must have 'vector_layers'"]
+		);
+	}
+
+	#[test]
+	fn center_outside_zoom_range() {
+		let mut tilejson = parse(r#"{"tilejson":"3.0.0","minzoom":5,"maxzoom":10}"#);
+		tilejson.center = Some(crate::GeoCenter(13.4, 52.5, 3));
+		assert_eq!(
+			tilejson.validate().warnings,
+			["zoom level of 'center' (3) is outside of the zoom range"]
+		);
+	}
+
+	#[test]
+	fn tile_type_from_format() {
+		let mut tilejson = parse(r#"{"tilejson":"3.0.0"}"#);
+		tilejson.tile_format = Some(TileFormat::MVT);
+		assert!(!tilejson.validate().is_valid());
+	}
+
+	#[test]
+	fn check_strict() {
+		let report = TileJsonReport {
+			errors: vec!["a".to_string(), "b".to_string()],
+			warnings: vec!["c".to_string()],
+		};
+		assert!(report.check("test", false).is_ok());
+		assert_eq!(
+			report.check("test", true).unwrap_err().to_string(),
+			"invalid TileJSON in test: a; b"
+		);
+		assert!(TileJsonReport::default().check("test", true).is_ok());
+	}
+}
@@ -0,0 +1,267 @@
+//! Pluggable codec backends for Gzip and Brotli.
+//!
+//! Tile conversions spend most of their time compressing and decompressing tiles, so the
+//! implementation behind [`compress_gzip`](super::compress_gzip) and friends can be swapped
+//! at compile time via feature flags of `versatiles_core`:
+//!
+//! | feature      | Gzip backend                                            |
+//! |--------------|---------------------------------------------------------|
+//! | *(default)*  | `flate2` with the pure Rust `miniz_oxide` backend        |
+//! | `zlib-ng`    | `flate2` with the SIMD-accelerated `zlib-ng` backend     |
+//! | `libdeflate` | `libdeflate`, fastest for whole-buffer (de)compression  |
+//!
+//! Brotli always uses the `brotli` crate.
+//!
+//! All backends produce valid Gzip streams, so containers written with one backend
+//! can be read with any other.
+//!
+//! ## Usage
+//! ```rust
+//! use versatiles_core::{utils::*, *};
+//!
+//! let codec = gzip_codec();
+//! let data = Blob::from("Hello, world!");
+//! let compressed = codec.compress(&data)?;
+//! assert_eq!(codec.decompress(&compressed)?, data);
+//! println!("gzip backend: {}", codec.name());
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use crate::Blob;
+use anyhow::{Context, Result};
+use brotli::{BrotliCompress, BrotliDecompress, enc::BrotliEncoderParams};
+use flate2::bufread::{GzDecoder, GzEncoder};
+use std::io::{Cursor, Read};
+
+/// A compression algorithm implementation that works on whole blobs.
+pub trait Codec: Send + Sync {
+	/// Name of the backend, e.g. for logging or benchmarks.
+	fn name(&self) -> &'static str;
+
+	/// Compresses `blob`.
+	///
+	/// # Errors
+	/// Returns an error if compression fails.
+	fn compress(&self, blob: &Blob) -> Result<Blob>;
+
+	/// Decompresses `blob`.
+	///
+	/// # Errors
+	/// Returns an error if `blob` is not valid compressed data.
+	fn decompress(&self, blob: &Blob) -> Result<Blob>;
+}
+
+/// Gzip codec based on `flate2`. Uses `zlib-ng` when the `zlib-ng` feature is enabled.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Flate2GzipCodec;
+
+impl Codec for Flate2GzipCodec {
+	fn name(&self) -> &'static str {
+		if cfg!(feature = "zlib-ng") {
+			"flate2 (zlib-ng)"
+		} else {
+			"flate2 (miniz_oxide)"
+		}
+	}
+
+	fn compress(&self, blob: &Blob) -> Result<Blob> {
+		let mut encoder = GzEncoder::new(blob.as_slice(), flate2::Compression::best());
+		let mut compressed_data = Vec::new();
+		encoder
+			.read_to_end(&mut compressed_data)
+			.context("Failed to compress data using Gzip")?;
+		Ok(Blob::from(compressed_data))
+	}
+
+	fn decompress(&self, blob: &Blob) -> Result<Blob> {
+		let mut decoder = GzDecoder::new(blob.as_slice());
+		let mut decompressed_data = Vec::new();
+		decoder
+			.read_to_end(&mut decompressed_data)
+			.context("Failed to decompress data using Gzip")?;
+		Ok(Blob::from(decompressed_data))
+	}
+}
+
+/// Upper bound for the initial output buffer of [`LibdeflateGzipCodec::decompress`].
+/// Larger outputs are handled by growing the buffer.
+#[cfg(feature = "libdeflate")]
+const LIBDEFLATE_MAX_SIZE_HINT: usize = 64 * 1024 * 1024;
+
+/// Gzip codec based on `libdeflate`. Requires the `libdeflate` feature.
+#[cfg(feature = "libdeflate")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LibdeflateGzipCodec;
+
+#[cfg(feature = "libdeflate")]
+impl Codec for LibdeflateGzipCodec {
+	fn name(&self) -> &'static str {
+		"libdeflate"
+	}
+
+	fn compress(&self, blob: &Blob) -> Result<Blob> {
+		use libdeflater::{CompressionLvl, Compressor};
+
+		let level = CompressionLvl::new(9).map_err(|e| anyhow::anyhow!("invalid compression level: {e:?}"))?;
+		let mut compressor = Compressor::new(level);
+		let mut output = vec![0; compressor.gzip_compress_bound(blob.len() as usize)];
+		let length = compressor
+			.gzip_compress(blob.as_slice(), &mut output)
+			.context("Failed to compress data using Gzip")?;
+		output.truncate(length);
+		Ok(Blob::from(output))
+	}
+
+	fn decompress(&self, blob: &Blob) -> Result<Blob> {
+		use libdeflater::{DecompressionError, Decompressor};
+
+		let input = blob.as_slice();
+		anyhow::ensure!(input.len() >= 18, "Gzip data is too short");
+
+		// The last 4 bytes of a gzip stream contain the uncompressed size modulo 2^32.
+		// They are not trustworthy, so they are only used as a capped initial buffer size.
+		let size_hint = u32::from_le_bytes(input[input.len() - 4..].try_into()?) as usize;
+		let mut output = vec![0; size_hint.clamp(input.len(), LIBDEFLATE_MAX_SIZE_HINT)];
+		let mut decompressor = Decompressor::new();
+		loop {
+			match decompressor.gzip_decompress(input, &mut output) {
+				Ok(length) => {
+					output.truncate(length);
+					return Ok(Blob::from(output));
+				}
+				Err(DecompressionError::InsufficientSpace) => output.resize(output.len() * 2, 0),
+				Err(e) => return Err(e).context("Failed to decompress data using Gzip"),
+			}
+		}
+	}
+}
+
+/// Brotli codec with configurable quality and window size.
+#[derive(Clone, Copy, Debug)]
+pub struct BrotliCodec {
+	/// Compression quality, 0 (fastest) to 11 (smallest).
+	pub quality: i32,
+	/// Base-2 logarithm of the sliding window size.
+	pub lgwin: i32,
+}
+
+impl BrotliCodec {
+	/// Settings for the best compression ratio, used for final output.
+	pub const BEST: BrotliCodec = BrotliCodec { quality: 10, lgwin: 19 };
+	/// Settings for fast compression, e.g. for temporary data or serving on the fly.
+	pub const FAST: BrotliCodec = BrotliCodec { quality: 3, lgwin: 16 };
+}
+
+impl Codec for BrotliCodec {
+	fn name(&self) -> &'static str {
+		"brotli"
+	}
+
+	fn compress(&self, blob: &Blob) -> Result<Blob> {
+		let params = BrotliEncoderParams {
+			quality: self.quality,
+			lgwin: self.lgwin,
+			size_hint: blob.len() as usize,
+			..Default::default()
+		};
+		let mut input = Cursor::new(blob.as_slice());
+		let mut output = Vec::new();
+		BrotliCompress(&mut input, &mut output, &params).context("Failed to compress data using Brotli")?;
+		Ok(Blob::from(output))
+	}
+
+	fn decompress(&self, blob: &Blob) -> Result<Blob> {
+		let mut cursor = Cursor::new(blob.as_slice());
+		let mut decompressed_data = Vec::new();
+		BrotliDecompress(&mut cursor, &mut decompressed_data).context("Failed to decompress data using Brotli")?;
+		Ok(Blob::from(decompressed_data))
+	}
+}
+
+/// Returns the Gzip codec selected by the enabled feature flags.
+///
+/// `libdeflate` takes precedence over `zlib-ng`; without either feature `flate2` with `miniz_oxide` is used.
+#[must_use]
+pub fn gzip_codec() -> &'static dyn Codec {
+	#[cfg(feature = "libdeflate")]
+	{
+		&LibdeflateGzipCodec
+	}
+	#[cfg(not(feature = "libdeflate"))]
+	{
+		&Flate2GzipCodec
+	}
+}
+
+/// Returns all Gzip codecs compiled into this build, e.g. for comparing them in benchmarks.
+#[must_use]
+pub fn available_gzip_codecs() -> Vec<&'static dyn Codec> {
+	vec![
+		&Flate2GzipCodec,
+		#[cfg(feature = "libdeflate")]
+		&LibdeflateGzipCodec,
+	]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::tests::generate_test_data;
+	use super::*;
+
+	#[test]
+	fn gzip_codecs_are_interchangeable() -> Result<()> {
+		let data = generate_test_data(100_000);
+		for encoder in available_gzip_codecs() {
+			let compressed = encoder.compress(&data)?;
+			for decoder in available_gzip_codecs() {
+				assert_eq!(
+					decoder.decompress(&compressed)?,
+					data,
+					"{} -> {}",
+					encoder.name(),
+					decoder.name()
+				);
+			}
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn gzip_codec_rejects_invalid_data() {
+		for codec in available_gzip_codecs() {
+			assert!(codec.decompress(&Blob::from("not gzip data at all")).is_err());
+		}
+	}
+
+	#[test]
+	fn gzip_codec_does_not_trust_size_trailer() -> Result<()> {
+		let data = generate_test_data(100_000);
+		for codec in available_gzip_codecs() {
+			// the trailer of the last member is smaller than the output of the first one
+			let mut compressed = codec.compress(&data)?.into_vec();
+			compressed.extend_from_slice(codec.compress(&Blob::from("tail"))?.as_slice());
+			assert_eq!(
+				codec.decompress(&Blob::from(compressed.clone()))?,
+				data,
+				"{}",
+				codec.name()
+			);
+
+			// a size trailer of 4 GiB must not be allocated up front
+			let length = compressed.len();
+			compressed[length - 4..].copy_from_slice(&u32::MAX.to_le_bytes());
+			assert_eq!(codec.decompress(&Blob::from(compressed))?, data, "{}", codec.name());
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn brotli_codecs() -> Result<()> {
+		let data = generate_test_data(10_000);
+		for codec in [BrotliCodec::BEST, BrotliCodec::FAST] {
+			let compressed = codec.compress(&data)?;
+			assert_eq!(BrotliCodec::BEST.decompress(&compressed)?, data);
+		}
+		Ok(())
+	}
+}
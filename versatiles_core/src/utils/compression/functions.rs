@@ -20,6 +20,7 @@
 //! # Ok::<(), anyhow::Error>(())
 //! ```
 use super::{
+	codec::BrotliCodec,
 	compression_goal::CompressionGoal,
 	method_brotli::{compress_brotli, decompress_brotli},
 	method_gzip::{compress_gzip, decompress_gzip},
@@ -27,6 +28,10 @@ use super::{
 };
 use crate::{Blob, TileCompression};
 use anyhow::{Result, bail};
+use std::{
+	fmt,
+	io::{self, Read, Write},
+};
 use versatiles_derive::context;
 
 /// Optimizes the compression of a data blob based on the target compression settings.
@@ -205,6 +210,112 @@ pub fn decompress_ref(blob: &Blob, compression: TileCompression) -> Result<Blob>
 	}
 }
 
+/// Compresses data from `input` and streams the result into `output`.
+///
+/// Unlike [`compress`], neither the uncompressed nor the compressed data has to be held in memory
+/// as a whole, which matters for very large tiles.
+///
+/// # Errors
+///
+/// * If reading, compressing or writing fails.
+#[context("Compressing stream with algorithm: {compression:?}")]
+pub fn compress_to_writer(input: &mut dyn Read, compression: TileCompression, output: &mut dyn Write) -> Result<()> {
+	match compression {
+		TileCompression::Uncompressed => {
+			io::copy(input, output).context("Failed to copy data")?;
+		}
+		TileCompression::Gzip => {
+			let mut encoder = flate2::write::GzEncoder::new(output, flate2::Compression::best());
+			io::copy(input, &mut encoder).context("Failed to compress data using Gzip")?;
+			encoder.finish().context("Failed to compress data using Gzip")?;
+		}
+		TileCompression::Brotli => {
+			let params = brotli::enc::BrotliEncoderParams {
+				quality: BrotliCodec::BEST.quality,
+				lgwin: BrotliCodec::BEST.lgwin,
+				..Default::default()
+			};
+			brotli::BrotliCompress(&mut { input }, &mut { output }, &params)
+				.context("Failed to compress data using Brotli")?;
+		}
+	}
+	Ok(())
+}
+
+/// Recompresses `data` like [`recompress`], but streams the result into `output`.
+///
+/// The data is decompressed and compressed again on the fly, so no intermediate copy is created.
+///
+/// # Errors
+///
+/// * If decompression, compression or writing fails.
+#[context("Recompressing stream from {input_compression:?} to {output_compression:?}")]
+pub fn recompress_to_writer(
+	data: &[u8],
+	input_compression: TileCompression,
+	output_compression: TileCompression,
+	output: &mut dyn Write,
+) -> Result<()> {
+	if input_compression == output_compression {
+		output.write_all(data).context("Failed to write data")?;
+		return Ok(());
+	}
+	let mut decoder: Box<dyn Read + '_> = match input_compression {
+		TileCompression::Uncompressed => Box::new(data),
+		TileCompression::Gzip => Box::new(flate2::bufread::GzDecoder::new(data)),
+		TileCompression::Brotli => Box::new(brotli::Decompressor::new(data, 4096)),
+	};
+	compress_to_writer(&mut decoder, output_compression, output)
+}
+
+/// Error returned by [`decompress_limited`] if the decompressed data would exceed the size limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecompressionLimitExceeded {
+	/// The maximum allowed size in bytes.
+	pub limit: u64,
+}
+
+impl fmt::Display for DecompressionLimitExceeded {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "decompressed data exceeds the limit of {} bytes", self.limit)
+	}
+}
+
+impl std::error::Error for DecompressionLimitExceeded {}
+
+/// Decompresses data like [`decompress`], but stops as soon as the output exceeds `max_size` bytes.
+///
+/// Use this for data from untrusted sources, where a few kilobytes of crafted Gzip or Brotli data
+/// could otherwise expand to gigabytes.
+///
+/// # Errors
+///
+/// * [`DecompressionLimitExceeded`] if the decompressed data is larger than `max_size`.
+/// * If the data is not valid compressed data.
+#[context("Decompressing blob with algorithm {compression:?} and a limit of {max_size} bytes")]
+pub fn decompress_limited(blob: Blob, compression: TileCompression, max_size: u64) -> Result<Blob> {
+	let decoder: Box<dyn Read + '_> = match compression {
+		TileCompression::Uncompressed => {
+			if blob.len() > max_size {
+				bail!(DecompressionLimitExceeded { limit: max_size });
+			}
+			return Ok(blob);
+		}
+		TileCompression::Gzip => Box::new(flate2::bufread::GzDecoder::new(blob.as_slice())),
+		TileCompression::Brotli => Box::new(brotli::Decompressor::new(blob.as_slice(), 4096)),
+	};
+
+	let mut data = Vec::new();
+	decoder
+		.take(max_size.saturating_add(1))
+		.read_to_end(&mut data)
+		.context("Failed to decompress data")?;
+	if data.len() as u64 > max_size {
+		bail!(DecompressionLimitExceeded { limit: max_size });
+	}
+	Ok(Blob::from(data))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::super::tests::generate_test_data;
@@ -399,4 +510,55 @@ mod tests {
 		assert_eq!(out_blob2, original);
 		Ok(())
 	}
+
+	#[test]
+	fn should_compress_to_writer() -> Result<()> {
+		use TileCompression::*;
+		let data = generate_test_data(100_000);
+		for compression in [Uncompressed, Gzip, Brotli] {
+			let mut output = Vec::new();
+			compress_to_writer(&mut data.as_slice(), compression, &mut output)?;
+			assert_eq!(decompress(Blob::from(output), compression)?, data, "{compression:?}");
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn should_recompress_to_writer() -> Result<()> {
+		use TileCompression::*;
+		let data = generate_test_data(100_000);
+		for from in [Uncompressed, Gzip, Brotli] {
+			let input = compress(data.clone(), from)?;
+			for to in [Uncompressed, Gzip, Brotli] {
+				let mut output = Vec::new();
+				recompress_to_writer(input.as_slice(), from, to, &mut output)?;
+				assert_eq!(decompress(Blob::from(output), to)?, data, "{from:?} -> {to:?}");
+			}
+		}
+		assert!(recompress_to_writer(b"no gzip", Gzip, Brotli, &mut Vec::new()).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn should_limit_decompressed_size() -> Result<()> {
+		let data = generate_test_data(10_000);
+		for compression in [
+			TileCompression::Uncompressed,
+			TileCompression::Gzip,
+			TileCompression::Brotli,
+		] {
+			let compressed = compress(data.clone(), compression)?;
+			assert_eq!(decompress_limited(compressed.clone(), compression, 10_000)?, data);
+
+			let error = decompress_limited(compressed, compression, 9_999).unwrap_err();
+			assert_eq!(
+				error.downcast_ref::<DecompressionLimitExceeded>(),
+				Some(&DecompressionLimitExceeded { limit: 9_999 })
+			);
+		}
+
+		let error = decompress_limited(Blob::from("no gzip"), TileCompression::Gzip, 100).unwrap_err();
+		assert!(error.downcast_ref::<DecompressionLimitExceeded>().is_none());
+		Ok(())
+	}
 }
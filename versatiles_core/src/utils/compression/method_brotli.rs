@@ -1,7 +1,6 @@
+use super::codec::{BrotliCodec, Codec};
 use crate::Blob;
 use anyhow::Result;
-use brotli::{BrotliCompress, BrotliDecompress, enc::BrotliEncoderParams};
-use std::io::Cursor;
 use versatiles_derive::context;
 
 /// Compresses data using Brotli.
@@ -20,16 +19,7 @@ use versatiles_derive::context;
 /// * If the Brotli compression process fails.
 #[context("Compressing data using Brotli with highest quality settings")]
 pub fn compress_brotli(blob: &Blob) -> Result<Blob> {
-	let params = BrotliEncoderParams {
-		quality: 10, // Highest quality
-		lgwin: 19,   // Window size
-		size_hint: blob.len() as usize,
-		..Default::default()
-	};
-	let mut input = Cursor::new(blob.as_slice());
-	let mut output = Vec::new();
-	BrotliCompress(&mut input, &mut output, &params).context("Failed to compress data using Brotli")?;
-	Ok(Blob::from(output))
+	BrotliCodec::BEST.compress(blob)
 }
 
 /// Compresses data using Brotli with faster settings.
@@ -50,16 +40,7 @@ pub fn compress_brotli(blob: &Blob) -> Result<Blob> {
 /// * If the Brotli compression process fails.
 #[context("Compressing data using Brotli with fast compression settings")]
 pub fn compress_brotli_fast(blob: &Blob) -> Result<Blob> {
-	let params = BrotliEncoderParams {
-		quality: 3, // Lower quality for faster compression
-		lgwin: 16,  // Smaller window size
-		size_hint: blob.len() as usize,
-		..Default::default()
-	};
-	let mut input = Cursor::new(blob.as_slice());
-	let mut output = Vec::new();
-	BrotliCompress(&mut input, &mut output, &params).context("Failed to compress data using Brotli (fast)")?;
-	Ok(Blob::from(output))
+	BrotliCodec::FAST.compress(blob)
 }
 
 /// Decompresses data that was compressed using Brotli.
@@ -78,10 +59,7 @@ pub fn compress_brotli_fast(blob: &Blob) -> Result<Blob> {
 /// * If the Brotli decompression process fails.
 #[context("Decompressing data using Brotli")]
 pub fn decompress_brotli(blob: &Blob) -> Result<Blob> {
-	let mut cursor = Cursor::new(blob.as_slice());
-	let mut decompressed_data = Vec::new();
-	BrotliDecompress(&mut cursor, &mut decompressed_data).context("Failed to decompress data using Brotli")?;
-	Ok(Blob::from(decompressed_data))
+	BrotliCodec::BEST.decompress(blob)
 }
 
 #[cfg(test)]
@@ -1,10 +1,9 @@
+use super::codec::gzip_codec;
 use crate::Blob;
 use anyhow::Result;
-use flate2::bufread::{GzDecoder, GzEncoder};
-use std::io::Read;
 use versatiles_derive::context;
 
-/// Compresses data using Gzip.
+/// Compresses data using Gzip, with the backend selected by [`gzip_codec`].
 ///
 /// # Arguments
 ///
@@ -20,15 +19,10 @@ use versatiles_derive::context;
 /// * If the Gzip compression process fails.
 #[context("Compressing blob with algorithm: Gzip")]
 pub fn compress_gzip(blob: &Blob) -> Result<Blob> {
-	let mut encoder = GzEncoder::new(blob.as_slice(), flate2::Compression::best());
-	let mut compressed_data = Vec::new();
-	encoder
-		.read_to_end(&mut compressed_data)
-		.context("Failed to compress data using Gzip")?;
-	Ok(Blob::from(compressed_data))
+	gzip_codec().compress(blob)
 }
 
-/// Decompresses data that was compressed using Gzip.
+/// Decompresses data that was compressed using Gzip, with the backend selected by [`gzip_codec`].
 ///
 /// # Arguments
 ///
@@ -44,12 +38,7 @@ pub fn compress_gzip(blob: &Blob) -> Result<Blob> {
 /// * If the Gzip decompression process fails.
 #[context("Decompressing data using Gzip")]
 pub fn decompress_gzip(blob: &Blob) -> Result<Blob> {
-	let mut decoder = GzDecoder::new(blob.as_slice());
-	let mut decompressed_data = Vec::new();
-	decoder
-		.read_to_end(&mut decompressed_data)
-		.context("Failed to decompress data using Gzip")?;
-	Ok(Blob::from(decompressed_data))
+	gzip_codec().decompress(blob)
 }
 
 #[cfg(test)]
@@ -1,3 +1,4 @@
+mod codec;
 mod compression_goal;
 mod functions;
 mod method_brotli;
@@ -6,6 +7,7 @@ mod target_compression;
 #[cfg(test)]
 pub mod tests;
 
+pub use codec::*;
 pub use functions::*;
 pub use method_brotli::*;
 pub use method_gzip::*;
@@ -3,16 +3,19 @@
 //! - `compression`: for handling tile compression and decompression.
 //! - `csv`: for lightweight CSV parsing utilities.
 //! - `pretty_print` (enabled with the `cli` feature): for formatted command-line output.
+//! - `stable_hash`: for hashes that don't change across platforms and Rust versions.
 //! - `tile_hilbert_index`: for Hilbert index calculations and spatial ordering of tiles.
 
 mod compression;
 mod csv;
 #[cfg(feature = "cli")]
 mod pretty_print;
+mod stable_hash;
 mod tile_hilbert_index;
 
 pub use compression::*;
 pub use csv::*;
 #[cfg(feature = "cli")]
 pub use pretty_print::*;
+pub use stable_hash::*;
 pub use tile_hilbert_index::*;
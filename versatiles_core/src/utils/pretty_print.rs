@@ -55,6 +55,8 @@ impl PrettyPrinter {
 
 /// High-level interface for structured output with color and indentation.
 /// Supports categories, lists, key/value pairs, warnings, and JSON output.
+/// Clones write to the same output with the same indentation.
+#[derive(Clone)]
 pub struct PrettyPrint {
 	prefix: String,
 	suffix: String,
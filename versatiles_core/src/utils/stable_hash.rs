@@ -0,0 +1,101 @@
+//! Hash functions with a stable output.
+//!
+//! The output of [`std::hash::DefaultHasher`] may change with every Rust release, so it must not be used for
+//! anything that is persisted or compared across runs, like file names of caches or generated ids.
+//! The functions in this module are fully specified and produce the same values on all platforms and Rust versions:
+//! [`Fnv1aHasher`] hashes byte sequences, [`splitmix64`] mixes single integers, e.g. for pseudo-random selections.
+//!
+//! # Example
+//! ```
+//! use versatiles_core::utils::{Fnv1aHasher, fnv1a64};
+//!
+//! let mut hasher = Fnv1aHasher::new();
+//! hasher.write(b"hello");
+//! assert_eq!(hasher.finish(), fnv1a64(b"hello"));
+//! ```
+
+/// 64-bit FNV-1a hasher.
+///
+/// Integers are written in little-endian byte order, so the result does not depend on the platform.
+#[derive(Clone, Copy, Debug)]
+pub struct Fnv1aHasher(u64);
+
+impl Fnv1aHasher {
+	/// Creates a hasher with the FNV-1a offset basis.
+	#[must_use]
+	pub fn new() -> Self {
+		Self(0xcbf2_9ce4_8422_2325)
+	}
+
+	/// Adds `bytes` to the hash.
+	pub fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.0 ^= u64::from(*byte);
+			self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+
+	/// Adds `value` to the hash as 8 little-endian bytes.
+	pub fn write_u64(&mut self, value: u64) {
+		self.write(&value.to_le_bytes());
+	}
+
+	/// Returns the hash of everything written so far.
+	#[must_use]
+	pub fn finish(&self) -> u64 {
+		self.0
+	}
+}
+
+impl Default for Fnv1aHasher {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Returns the 64-bit FNV-1a hash of `bytes`.
+#[must_use]
+pub fn fnv1a64(bytes: &[u8]) -> u64 {
+	let mut hasher = Fnv1aHasher::new();
+	hasher.write(bytes);
+	hasher.finish()
+}
+
+/// The SplitMix64 mixing function: maps `value` to a well distributed, pseudo-random 64-bit value.
+#[must_use]
+pub fn splitmix64(value: u64) -> u64 {
+	let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+	z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+	z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fnv1a64_reference_values() {
+		assert_eq!(fnv1a64(b""), 0xcbf2_9ce4_8422_2325);
+		assert_eq!(fnv1a64(b"a"), 0xaf63_dc4c_8601_ec8c);
+		assert_eq!(fnv1a64(b"foobar"), 0x8594_4171_f739_67e8);
+	}
+
+	#[test]
+	fn splitmix64_reference_values() {
+		assert_eq!(splitmix64(0), 0xE220_A839_7B1D_CDAF);
+		assert_eq!(splitmix64(1), 0x910A_2DEC_8902_5CC1);
+	}
+
+	#[test]
+	fn write_in_parts() {
+		let mut hasher = Fnv1aHasher::default();
+		hasher.write(b"foo");
+		hasher.write(b"bar");
+		assert_eq!(hasher.finish(), fnv1a64(b"foobar"));
+
+		let mut hasher = Fnv1aHasher::new();
+		hasher.write_u64(1);
+		assert_eq!(hasher.finish(), fnv1a64(&[1, 0, 0, 0, 0, 0, 0, 0]));
+	}
+}
@@ -1,6 +1,8 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, TokenStream};
 use quote::quote;
-use syn::{Attribute, DataStruct, DeriveInput, Fields, Meta};
+use syn::{
+	Attribute, DataEnum, DataStruct, DeriveInput, Expr, Fields, GenericArgument, Lit, LitStr, Meta, PathArguments, Type,
+};
 
 pub fn extract_comment(attr: &Attribute) -> Option<String> {
 	if attr.path().is_ident("doc")
@@ -14,6 +16,186 @@ pub fn extract_comment(attr: &Attribute) -> Option<String> {
 	None
 }
 
+/// Encodes `text` as a JSON string literal.
+fn json_string(text: &str) -> String {
+	let mut result = String::from("\"");
+	for c in text.chars() {
+		match c {
+			'"' => result.push_str("\\\""),
+			'\\' => result.push_str("\\\\"),
+			'\n' => result.push_str("\\n"),
+			'\t' => result.push_str("\\t"),
+			'\r' => result.push_str("\\r"),
+			c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+			c => result.push(c),
+		}
+	}
+	result.push('"');
+	result
+}
+
+/// Options given with `#[vpl(...)]` on a field or an enum variant.
+#[derive(Default)]
+struct VplAttributes {
+	/// `default = EXPR`: value of a missing parameter.
+	default: Option<Expr>,
+	/// `min = EXPR`: smallest allowed value of a number.
+	min: Option<Expr>,
+	/// `max = EXPR`: largest allowed value of a number.
+	max: Option<Expr>,
+	/// `flatten`: the field is a nested argument struct, whose parameters are added to this one.
+	flatten: bool,
+	/// `rename = "..."`: the name of an enum variant in VPL.
+	rename: Option<String>,
+}
+
+fn parse_vpl_attributes(attrs: &[Attribute]) -> VplAttributes {
+	let mut result = VplAttributes::default();
+	for attr in attrs.iter().filter(|attr| attr.path().is_ident("vpl")) {
+		attr
+			.parse_nested_meta(|meta| {
+				if meta.path.is_ident("default") {
+					result.default = Some(meta.value()?.parse()?);
+				} else if meta.path.is_ident("min") {
+					result.min = Some(meta.value()?.parse()?);
+				} else if meta.path.is_ident("max") {
+					result.max = Some(meta.value()?.parse()?);
+				} else if meta.path.is_ident("flatten") {
+					result.flatten = true;
+				} else if meta.path.is_ident("rename") {
+					result.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+				} else {
+					return Err(meta.error("unknown vpl attribute, expected one of: default, min, max, flatten, rename"));
+				}
+				Ok(())
+			})
+			.unwrap_or_else(|e| panic!("{e}"));
+	}
+	result
+}
+
+/// Returns `expr` as it is shown in the docs: string literals without quotes, everything else as written.
+fn expr_text(expr: &Expr) -> String {
+	if let Expr::Lit(lit) = expr
+		&& let Lit::Str(lit_str) = &lit.lit
+	{
+		return lit_str.value();
+	}
+	quote!(#expr).to_string().replace(' ', "")
+}
+
+/// Returns `expr` as a JSON value: numbers and booleans as they are, everything else as a string.
+fn expr_json(expr: &Expr) -> String {
+	let text = expr_text(expr);
+	let is_string = matches!(expr, Expr::Lit(lit) if matches!(lit.lit, Lit::Str(_)));
+	if !is_string && (text.parse::<f64>().is_ok() || text == "true" || text == "false") {
+		text
+	} else {
+		json_string(&text)
+	}
+}
+
+/// Returns `T` if `ty` is `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+	if let Type::Path(type_path) = ty
+		&& let Some(segment) = type_path.path.segments.last()
+		&& segment.ident == "Option"
+		&& let PathArguments::AngleBracketed(arguments) = &segment.arguments
+		&& let Some(GenericArgument::Type(inner)) = arguments.args.first()
+	{
+		return Some(inner);
+	}
+	None
+}
+
+/// How a parameter of a given type is read from a `VPLNode`.
+struct ValueType {
+	/// Type name in the docs, `None` for enums that derive `VPLDecode`.
+	doc: Option<String>,
+	/// Getter for an optional parameter.
+	getter_option: TokenStream,
+	/// Getter for a required parameter.
+	getter_required: TokenStream,
+	/// Whether `min` and `max` can be used.
+	is_number: bool,
+}
+
+impl ValueType {
+	fn new(ty: &Type) -> Self {
+		let type_str = quote!(#ty).to_string().replace(' ', "");
+		let (doc, getter_option, getter_required, is_number) = match type_str.as_str() {
+			"String" => (
+				Some(String::from("String")),
+				quote!(get_property_string_option),
+				quote!(get_property_string_required),
+				false,
+			),
+			"bool" => (
+				Some(String::from("bool")),
+				quote!(get_property_bool_option),
+				quote!(get_property_bool_required),
+				false,
+			),
+			"u8" | "u16" | "u32" | "u64" | "i32" | "i64" | "f32" | "f64" => (
+				Some(type_str.clone()),
+				quote!(get_property_number_option::<#ty>),
+				quote!(get_property_number_required::<#ty>),
+				true,
+			),
+			"[f64;4]" => (
+				Some(String::from("[f64,f64,f64,f64]")),
+				quote!(get_property_number_array_option::<f64, 4>),
+				quote!(get_property_number_array_required::<f64, 4>),
+				false,
+			),
+			"[u8;3]" => (
+				Some(String::from("[u8,u8,u8]")),
+				quote!(get_property_number_array_option::<u8, 3>),
+				quote!(get_property_number_array_required::<u8, 3>),
+				false,
+			),
+			"VPLPipeline" => (
+				Some(String::from("VPL pipeline")),
+				quote!(get_named_source_option),
+				quote!(get_named_source_required),
+				false,
+			),
+			"TileCompression" | "TileSchema" | "TileFormat" => (
+				Some(type_str.clone()),
+				quote!(get_property_enum_option::<#ty>),
+				quote!(get_property_enum_required::<#ty>),
+				false,
+			),
+			// any other type must be an enum deriving `VPLDecode`
+			_ => (
+				None,
+				quote!(get_property_enum_option::<#ty>),
+				quote!(get_property_enum_required::<#ty>),
+				false,
+			),
+		};
+		ValueType {
+			doc,
+			getter_option,
+			getter_required,
+			is_number,
+		}
+	}
+}
+
+/// Builds a `Vec<String>` from statements that push to or extend `list`.
+fn collect_parts(parts: &[TokenStream]) -> TokenStream {
+	if parts.is_empty() {
+		quote! { Vec::new() }
+	} else {
+		quote! {
+			let mut list: Vec<String> = Vec::new();
+			#(#parts)*
+			list
+		}
+	}
+}
+
 pub fn decode_struct(input: DeriveInput, data_struct: DataStruct) -> TokenStream {
 	let name = input.ident;
 
@@ -34,18 +216,31 @@ pub fn decode_struct(input: DeriveInput, data_struct: DataStruct) -> TokenStream
 	};
 
 	let mut parser_fields: Vec<TokenStream> = Vec::new();
-	let mut doc_fields: Vec<String> = Vec::new();
+	let mut validations: Vec<TokenStream> = Vec::new();
+	let mut name_parts: Vec<TokenStream> = Vec::new();
+	let mut doc_parts: Vec<TokenStream> = Vec::new();
+	let mut schema_parts: Vec<TokenStream> = Vec::new();
 	let mut doc_sources: Option<String> = None;
-	let mut field_names: Vec<String> = Vec::new();
+	let mut schema_sources: Option<String> = None;
 
 	for field in fields {
-		let field_name = &field.ident;
+		let field_name = field.ident.as_ref().expect("could not get field_name");
 		let field_type = &field.ty;
-		let field_str = field_name.as_ref().expect("could not get field_name").to_string();
+		let field_str = field_name.to_string();
 		let field_type_str = quote!(#field_type).to_string().replace(' ', "");
+		let attributes = parse_vpl_attributes(&field.attrs);
+
+		if attributes.flatten {
+			// nested argument struct: its parameters are read from the same node
+			parser_fields.push(quote! { #field_name: <#field_type>::decode_vpl_node(node)? });
+			name_parts.push(quote! { list.extend(<#field_type>::get_argument_names()); });
+			doc_parts.push(quote! { list.extend(<#field_type>::get_parameter_docs()); });
+			schema_parts.push(quote! { list.extend(<#field_type>::get_parameter_schemas()); });
+			continue;
+		}
 
-		field_names.push(field_str.clone());
-		let mut comment = field
+		name_parts.push(quote! { list.push(#field_str.to_string()); });
+		let comment = field
 			.attrs
 			.iter()
 			.filter_map(extract_comment)
@@ -61,98 +256,149 @@ pub fn decode_struct(input: DeriveInput, data_struct: DataStruct) -> TokenStream
 				"type of 'sources' must be 'Vec<VPLPipeline>', but is '{field_type_str}'"
 			);
 			doc_sources = Some(format!("### Sources:\n{comment}"));
+			schema_sources = Some(json_string(&comment));
 			parser_fields.push(quote! { sources: node.sources.clone() });
+			continue;
+		}
+
+		let inner_type = option_inner(field_type);
+		let value_type = ValueType::new(inner_type.unwrap_or(field_type));
+		let (getter_option, getter_required) = (&value_type.getter_option, &value_type.getter_required);
+		let required = inner_type.is_none() && attributes.default.is_none();
+
+		parser_fields.push(match (inner_type, &attributes.default) {
+			(Some(_), Some(_)) => panic!("'{field_str}' is optional and can not have a default value"),
+			(Some(_), None) => quote! { #field_name: node.#getter_option(#field_str)? },
+			(None, Some(default)) if field_type_str == "String" => {
+				quote! { #field_name: node.#getter_option(#field_str)?.unwrap_or_else(|| String::from(#default)) }
+			}
+			(None, Some(default)) => quote! { #field_name: node.#getter_option(#field_str)?.unwrap_or_else(|| #default) },
+			(None, None) => quote! { #field_name: node.#getter_required(#field_str)? },
+		});
+
+		let mut notes: Vec<String> = Vec::new();
+		let mut schema_fields: Vec<(&str, String)> = vec![("description", json_string(&comment))];
+		if let Some(default) = &attributes.default {
+			notes.push(format!("default: {}", expr_text(default)));
+			schema_fields.push(("default", expr_json(default)));
+		}
+
+		let mut checks: Vec<TokenStream> = Vec::new();
+		for (key, bound, check) in [
+			("minimum", &attributes.min, quote!(>=)),
+			("maximum", &attributes.max, quote!(<=)),
+		] {
+			let Some(bound) = bound else { continue };
+			assert!(
+				value_type.is_number,
+				"'min' and 'max' can only be used for numbers, but '{field_str}' is '{field_type_str}'"
+			);
+			let text = expr_text(bound);
+			let limit = if key == "minimum" { "at least" } else { "at most" };
+			let message = format!("In operation '{{}}' the parameter '{field_str}' must be {limit} {text}, but is {{}}.");
+			notes.push(format!("{key}: {text}"));
+			schema_fields.push((key, expr_json(bound)));
+			checks.push(quote! {
+				anyhow::ensure!(*value #check #bound, #message, node.name, value);
+			});
+		}
+		if !checks.is_empty() {
+			validations.push(if inner_type.is_some() {
+				quote! { if let Some(value) = args.#field_name.as_ref() { #(#checks)* } }
+			} else {
+				quote! { { let value = &args.#field_name; #(#checks)* } }
+			});
+		}
+
+		let comment = if notes.is_empty() {
+			comment
+		} else {
+			format!("{comment} ({})", notes.join(", ")).trim().to_string()
+		};
+		let comment_suffix = if comment.is_empty() {
+			String::new()
 		} else {
-			if !comment.is_empty() {
-				comment = format!(" - {comment}");
+			format!(" - {comment}")
+		};
+		let (doc_prefix, doc_suffix) = if required {
+			(format!("- **`{field_str}`: "), format!(" (required)**{comment_suffix}"))
+		} else {
+			(format!("- *`{field_str}`: "), format!(" (optional)*{comment_suffix}"))
+		};
+		schema_fields.push(("name", json_string(&field_str)));
+		schema_fields.push(("required", required.to_string()));
+		schema_fields.sort_by_key(|(key, _)| *key);
+		let schema_fields: Vec<String> = schema_fields
+			.iter()
+			.map(|(key, value)| format!("\"{key}\":{value}"))
+			.collect();
+		let schema_prefix = format!("{{{},\"type\":", schema_fields.join(","));
+
+		match value_type.doc {
+			Some(doc) => {
+				let doc = if required && doc == "bool" { "Boolean" } else { &doc };
+				let doc_field = format!("{doc_prefix}{doc}{doc_suffix}");
+				let schema_field = format!("{schema_prefix}{}}}", json_string(doc));
+				doc_parts.push(quote! { list.push(#doc_field.to_string()); });
+				schema_parts.push(quote! { list.push(#schema_field.to_string()); });
+			}
+			None => {
+				// the values of an enum deriving `VPLDecode` are only known at runtime
+				let enum_type = inner_type.unwrap_or(field_type);
+				doc_parts.push(quote! {
+					list.push(
+						[
+							#doc_prefix,
+							&<#enum_type>::get_variant_names()
+								.iter()
+								.map(|v| format!("\"{v}\""))
+								.collect::<Vec<String>>()
+								.join(" | "),
+							#doc_suffix,
+						]
+						.concat(),
+					);
+				});
+				schema_parts.push(quote! {
+					list.push(
+						[
+							#schema_prefix,
+							"\"enum\",\"values\":[",
+							&<#enum_type>::get_variant_names()
+								.iter()
+								.map(|v| format!("\"{v}\""))
+								.collect::<Vec<String>>()
+								.join(","),
+							"]}",
+						]
+						.concat(),
+					);
+				});
 			}
-			let (doc_field, parser_field) = match field_type_str.as_str() {
-				"String" => (
-					format!("- **`{field_str}`: String (required)**{comment}"),
-					quote! { #field_name: node.get_property_string_required(#field_str)? },
-				),
-				"bool" => (
-					format!("- **`{field_str}`: Boolean (required)**{comment}"),
-					quote! { #field_name: node.get_property_bool_required(#field_str)? },
-				),
-				"u8" => (
-					format!("- **`{field_str}`: u8 (required)**{comment}"),
-					quote! { #field_name: node.get_property_number_required::<u8>(#field_str)? },
-				),
-				"[f64;4]" => (
-					format!("- **`{field_str}`: [f64,f64,f64,f64] (required)**{comment}"),
-					quote! { #field_name: node.get_property_number_array_required::<f64>(#field_str)? },
-				),
-				"Option<bool>" => (
-					format!("- *`{field_str}`: bool (optional)*{comment}"),
-					quote! { #field_name: node.get_property_bool_option(#field_str)? },
-				),
-				"Option<String>" => (
-					format!("- *`{field_str}`: String (optional)*{comment}"),
-					quote! { #field_name: node.get_property_string_option(#field_str)? },
-				),
-				"Option<f32>" => (
-					format!("- *`{field_str}`: f32 (optional)*{comment}"),
-					quote! { #field_name: node.get_property_number_option::<f32>(#field_str)? },
-				),
-				"Option<u8>" => (
-					format!("- *`{field_str}`: u8 (optional)*{comment}"),
-					quote! { #field_name: node.get_property_number_option::<u8>(#field_str)? },
-				),
-				"Option<u16>" => (
-					format!("- *`{field_str}`: u16 (optional)*{comment}"),
-					quote! { #field_name: node.get_property_number_option::<u16>(#field_str)? },
-				),
-				"Option<u32>" => (
-					format!("- *`{field_str}`: u32 (optional)*{comment}"),
-					quote! { #field_name: node.get_property_number_option::<u32>(#field_str)? },
-				),
-				"Option<[f64;4]>" => (
-					format!("- *`{field_str}`: [f64,f64,f64,f64] (optional)*{comment}"),
-					quote! { #field_name: node.get_property_number_array_option::<f64, 4>(#field_str)? },
-				),
-				"Option<[u8;3]>" => (
-					format!("- *`{field_str}`: [u8,u8,u8] (optional)*{comment}"),
-					quote! { #field_name: node.get_property_number_array_option::<u8, 3>(#field_str)? },
-				),
-				"Option<TileCompression>" => (
-					format!("- *`{field_str}`: TileCompression (optional)*{comment}"),
-					quote! { #field_name: node.get_property_enum_option::<TileCompression>(#field_str)? },
-				),
-				"Option<TileSchema>" => (
-					format!("- *`{field_str}`: TileSchema (optional)*{comment}"),
-					quote! { #field_name: node.get_property_enum_option::<TileSchema>(#field_str)? },
-				),
-				"Option<TileFormat>" => (
-					format!("- *`{field_str}`: TileFormat (optional)*{comment}"),
-					quote! { #field_name: node.get_property_enum_option::<TileFormat>(#field_str)? },
-				),
-				_ => panic!("unknown type field: {field_type_str}"),
-			};
-			doc_fields.push(doc_field.trim().to_string());
-			parser_fields.push(parser_field);
 		}
 	}
 
-	let doc_fields = if doc_fields.is_empty() {
-		String::new()
-	} else {
-		format!("### Parameters:\n{}", doc_fields.join("\n"))
-	};
-
-	let doc = vec![doc_struct, doc_sources.unwrap_or_default(), doc_fields]
+	let doc_head: Vec<String> = [doc_struct.clone(), doc_sources.unwrap_or_default()]
 		.into_iter()
 		.filter(|s| !s.is_empty())
-		.collect::<Vec<String>>()
-		.join("\n")
-		.trim()
-		.to_string();
+		.collect();
+
+	let schema_head = format!("{{\"description\":{},\"parameters\":[", json_string(&doc_struct));
+	let schema_tail = format!(
+		"]{}}}",
+		schema_sources.map(|s| format!(",\"sources\":{s}")).unwrap_or_default()
+	);
+
+	let argument_names = collect_parts(&name_parts);
+	let parameter_docs = collect_parts(&doc_parts);
+	let parameter_schemas = collect_parts(&schema_parts);
 
 	quote! {
+		#[allow(dead_code)]
 		impl #name {
 			pub fn from_vpl_node(node: &VPLNode) -> Result<Self> {
-				// scan node.get_property_names to ensure, that all properties are also defined in field_names
-				let argument_names: Vec<String> = vec![#(#field_names.to_string()),*];
+				// scan node.get_property_names to ensure, that all properties are also defined in argument_names
+				let argument_names = Self::get_argument_names();
 				let property_names = node.get_property_names();
 				for property_name in property_names {
 					if !argument_names.contains(&property_name) {
@@ -164,14 +410,106 @@ pub fn decode_struct(input: DeriveInput, data_struct: DataStruct) -> TokenStream
 						);
 					}
 				}
+				Self::decode_vpl_node(node)
+			}
 
-				Ok(Self {
+			pub fn decode_vpl_node(node: &VPLNode) -> Result<Self> {
+				let args = Self {
 					#(#parser_fields),*
-				})
+				};
+				#(#validations)*
+				Ok(args)
+			}
+
+			pub fn get_argument_names() -> Vec<String> {
+				#argument_names
+			}
+
+			pub fn get_parameter_docs() -> Vec<String> {
+				#parameter_docs
+			}
+
+			pub fn get_parameter_schemas() -> Vec<String> {
+				#parameter_schemas
 			}
 
 			pub fn get_docs() -> String {
-				#doc.to_string()
+				let mut docs: Vec<String> = vec![#(#doc_head.to_string()),*];
+				let parameters = Self::get_parameter_docs();
+				if !parameters.is_empty() {
+					docs.push(format!("### Parameters:\n{}", parameters.join("\n")));
+				}
+				docs.join("\n")
+			}
+
+			pub fn get_schema() -> String {
+				[#schema_head, &Self::get_parameter_schemas().join(","), #schema_tail].concat()
+			}
+		}
+	}
+}
+
+/// Converts a variant name like `RankAscending` to `rank_ascending`.
+fn snake_case(name: &str) -> String {
+	let mut result = String::new();
+	let mut previous_lowercase = false;
+	for c in name.chars() {
+		if c.is_uppercase() && previous_lowercase {
+			result.push('_');
+		}
+		previous_lowercase = c.is_lowercase() || c.is_ascii_digit();
+		result.extend(c.to_lowercase());
+	}
+	result
+}
+
+pub fn decode_enum(input: DeriveInput, data_enum: DataEnum) -> TokenStream {
+	let name = input.ident;
+
+	let mut variant_idents: Vec<Ident> = Vec::new();
+	let mut variant_names: Vec<String> = Vec::new();
+	for variant in data_enum.variants {
+		assert!(
+			matches!(variant.fields, Fields::Unit),
+			"VPLDecode can only be derived for enums with unit variants, but '{}' has fields",
+			variant.ident
+		);
+		let attributes = parse_vpl_attributes(&variant.attrs);
+		variant_names.push(
+			attributes
+				.rename
+				.unwrap_or_else(|| snake_case(&variant.ident.to_string())),
+		);
+		variant_idents.push(variant.ident);
+	}
+	let match_names: Vec<String> = variant_names.iter().map(|n| n.to_lowercase()).collect();
+
+	quote! {
+		#[allow(dead_code)]
+		impl #name {
+			pub fn get_variant_names() -> Vec<&'static str> {
+				vec![#(#variant_names),*]
+			}
+
+			pub fn as_str(&self) -> &'static str {
+				match self {
+					#(Self::#variant_idents => #variant_names),*
+				}
+			}
+		}
+
+		impl TryFrom<&str> for #name {
+			type Error = anyhow::Error;
+
+			fn try_from(value: &str) -> Result<Self, Self::Error> {
+				Ok(match value.trim().to_lowercase().as_str() {
+					#(#match_names => Self::#variant_idents,)*
+					_ => anyhow::bail!(
+						"unknown value '{}', expected one of: {}",
+						value,
+						Self::get_variant_names().join(", ")
+					),
+				})
 			}
 		}
 	}
@@ -179,7 +517,7 @@ pub fn decode_struct(input: DeriveInput, data_struct: DataStruct) -> TokenStream
 
 #[cfg(test)]
 mod tests {
-	use super::decode_struct;
+	use super::{decode_enum, decode_struct, json_string, snake_case};
 	use pretty_assertions::assert_eq;
 	use syn::{DeriveInput, parse_quote};
 
@@ -208,9 +546,10 @@ mod tests {
 		assert_eq!(
 			pretty_tokens(ts),
 			[
+				"#[allow(dead_code)]",
 				"impl Test {",
 				"    pub fn from_vpl_node(node: &VPLNode) -> Result<Self> {",
-				"        let argument_names: Vec<String> = vec![\"field1\".to_string()];",
+				"        let argument_names = Self::get_argument_names();",
 				"        let property_names = node.get_property_names();",
 				"        for property_name in property_names {",
 				"            if !argument_names.contains(&property_name) {",
@@ -220,16 +559,50 @@ mod tests {
 				"                );",
 				"            }",
 				"        }",
-				"        Ok(Self {",
+				"        Self::decode_vpl_node(node)",
+				"    }",
+				"    pub fn decode_vpl_node(node: &VPLNode) -> Result<Self> {",
+				"        let args = Self {",
 				"            field1: node.get_property_string_required(\"field1\")?,",
-				"        })",
+				"        };",
+				"        Ok(args)",
+				"    }",
+				"    pub fn get_argument_names() -> Vec<String> {",
+				"        let mut list: Vec<String> = Vec::new();",
+				"        list.push(\"field1\".to_string());",
+				"        list",
+				"    }",
+				"    pub fn get_parameter_docs() -> Vec<String> {",
+				"        let mut list: Vec<String> = Vec::new();",
+				"        list.push(\"- **`field1`: String (required)** - Field documentation\".to_string());",
+				"        list",
+				"    }",
+				"    pub fn get_parameter_schemas() -> Vec<String> {",
+				"        let mut list: Vec<String> = Vec::new();",
+				"        list.push(",
+				"            \"{\\\"description\\\":\\\"Field documentation\\\",\\\"name\\\":\\\"field1\\\",\\\"required\\\":true,\\\"type\\\":\\\"String\\\"}\"",
+				"                .to_string(),",
+				"        );",
+				"        list",
 				"    }",
 				"    pub fn get_docs() -> String {",
-				"        \"Struct documentation\\n### Parameters:\\n- **`field1`: String (required)** - Field documentation\"",
-				"            .to_string()",
+				"        let mut docs: Vec<String> = vec![\"Struct documentation\".to_string()];",
+				"        let parameters = Self::get_parameter_docs();",
+				"        if !parameters.is_empty() {",
+				"            docs.push(format!(\"### Parameters:\\n{}\", parameters.join(\"\\n\")));",
+				"        }",
+				"        docs.join(\"\\n\")",
+				"    }",
+				"    pub fn get_schema() -> String {",
+				"        [",
+				"            \"{\\\"description\\\":\\\"Struct documentation\\\",\\\"parameters\\\":[\",",
+				"            &Self::get_parameter_schemas().join(\",\"),",
+				"            \"]}\",",
+				"        ]",
+				"            .concat()",
 				"    }",
 				"}",
-				""
+				"",
 			]
 		);
 	}
@@ -272,7 +645,7 @@ mod tests {
 						v: [f64; 4],
 					}
 				),
-				"get_property_number_array_required::<f64>",
+				"get_property_number_array_required::<f64, 4>",
 				"**`v`: [f64,f64,f64,f64] (required)**",
 			),
 			(
@@ -338,40 +711,47 @@ mod tests {
 				"get_property_enum_option::<TileFormat>",
 				"*`v`: TileFormat (optional)*",
 			),
+			(
+				parse_quote!(
+					struct T {
+						v: VPLPipeline,
+					}
+				),
+				"get_named_source_required",
+				"**`v`: VPL pipeline (required)**",
+			),
+			(
+				parse_quote!(
+					struct T {
+						v: Option<VPLPipeline>,
+					}
+				),
+				"get_named_source_option",
+				"*`v`: VPL pipeline (optional)*",
+			),
 		];
 
 		for (input, getter, comment) in cases {
+			let required = comment.ends_with("(required)**");
+			let field_type = comment.split_once(": ").unwrap().1.rsplit_once(" (").unwrap().0;
 			let data_struct = match &input.data {
 				syn::Data::Struct(ds) => ds.clone(),
 				_ => panic!("Expected struct data"),
 			};
 			let ts = decode_struct(input.clone(), data_struct);
-			assert_eq!(
-				pretty_tokens(ts),
-				[
-					"impl T {",
-					"    pub fn from_vpl_node(node: &VPLNode) -> Result<Self> {",
-					"        let argument_names: Vec<String> = vec![\"v\".to_string()];",
-					"        let property_names = node.get_property_names();",
-					"        for property_name in property_names {",
-					"            if !argument_names.contains(&property_name) {",
-					"                anyhow::bail!(",
-					"                    \"The '{}' operation does not support the argument '{}'.\\nOnly the following arguments are supported:\\n'{}'\",",
-					"                    node.name, property_name, argument_names.join(\"', '\")",
-					"                );",
-					"            }",
-					"        }",
-					"        Ok(Self {",
-					&format!("            v: node.{getter}(\"v\")?,"),
-					"        })",
-					"    }",
-					"    pub fn get_docs() -> String {",
-					&format!("        \"### Parameters:\\n- {comment}\".to_string()"),
-					"    }",
-					"}",
-					""
-				]
+			let code = ts.to_string();
+			let lines = pretty_tokens(ts);
+			assert!(
+				lines.contains(&format!("            v: node.{getter}(\"v\")?,")),
+				"{lines:#?}"
 			);
+			assert!(
+				lines.contains(&format!("        list.push(\"- {comment}\".to_string());")),
+				"{lines:#?}"
+			);
+			let schema =
+				format!("{{\"description\":\"\",\"name\":\"v\",\"required\":{required},\"type\":\"{field_type}\"}}");
+			assert!(code.contains(&format!("{schema:?}")), "{code}");
 		}
 	}
 
@@ -394,5 +774,131 @@ mod tests {
 		// Ensure get_docs includes Sources section
 		assert!(code.contains("### Sources:"));
 		assert!(code.contains("List of sources"));
+		// Ensure get_schema includes the sources
+		assert!(code.contains(r#"\"sources\":\"List of sources\""#));
+	}
+
+	fn derive_struct(input: &DeriveInput) -> proc_macro2::TokenStream {
+		match &input.data {
+			syn::Data::Struct(ds) => decode_struct(input.clone(), ds.clone()),
+			_ => panic!("Expected struct data"),
+		}
+	}
+
+	#[test]
+	fn test_decode_struct_default_and_range() {
+		let input: DeriveInput = parse_quote!(
+			struct T {
+				/// Attempts.
+				#[vpl(default = 3, min = 1, max = 10)]
+				attempts: u8,
+				#[vpl(default = "viridis")]
+				name: String,
+				#[vpl(min = 0.5)]
+				scale: Option<f32>,
+			}
+		);
+		let ts = derive_struct(&input);
+		let code = ts.to_string();
+		let lines = pretty_tokens(ts);
+		for line in [
+			"            attempts: node",
+			"                .get_property_number_option::<u8>(\"attempts\")?",
+			"                .unwrap_or_else(|| 3),",
+			"                .unwrap_or_else(|| String::from(\"viridis\")),",
+			"            scale: node.get_property_number_option::<f32>(\"scale\")?,",
+			"            let value = &args.attempts;",
+			"                * value >= 1,",
+			"                \"In operation '{}' the parameter 'attempts' must be at least 1, but is {}.\",",
+			"                * value <= 10,",
+			"        if let Some(value) = args.scale.as_ref() {",
+		] {
+			assert!(lines.contains(&line.to_string()), "missing {line:?} in {lines:#?}");
+		}
+		for doc in [
+			"- *`attempts`: u8 (optional)* - Attempts. (default: 3, minimum: 1, maximum: 10)",
+			"- *`name`: String (optional)* - (default: viridis)",
+			"- *`scale`: f32 (optional)* - (minimum: 0.5)",
+			r#"{"default":3,"description":"Attempts.","maximum":10,"minimum":1,"name":"attempts","required":false,"type":"u8"}"#,
+			r#"{"default":"viridis","description":"","name":"name","required":false,"type":"String"}"#,
+		] {
+			assert!(code.contains(&format!("{doc:?}")), "missing {doc:?} in {code}");
+		}
+	}
+
+	#[test]
+	#[should_panic(expected = "can only be used for numbers")]
+	fn test_decode_struct_range_needs_number() {
+		derive_struct(&parse_quote!(
+			struct T {
+				#[vpl(min = 1)]
+				v: String,
+			}
+		));
+	}
+
+	#[test]
+	fn test_decode_struct_flatten_and_enum() {
+		let input: DeriveInput = parse_quote!(
+			struct T {
+				#[vpl(flatten)]
+				tiling: Tiling,
+				/// Mode.
+				mode: Option<Mode>,
+			}
+		);
+		let lines = pretty_tokens(derive_struct(&input));
+		for line in [
+			"            tiling: <Tiling>::decode_vpl_node(node)?,",
+			"            mode: node.get_property_enum_option::<Mode>(\"mode\")?,",
+			"        list.extend(<Tiling>::get_argument_names());",
+			"        list.push(\"mode\".to_string());",
+			"        list.extend(<Tiling>::get_parameter_docs());",
+			"        list.extend(<Tiling>::get_parameter_schemas());",
+			"                \"- *`mode`: \",",
+			"                \" (optional)* - Mode.\",",
+			"                \"\\\"enum\\\",\\\"values\\\":[\",",
+		] {
+			assert!(lines.contains(&line.to_string()), "missing {line:?} in {lines:#?}");
+		}
+	}
+
+	#[test]
+	fn test_decode_enum() {
+		let input: DeriveInput = parse_quote!(
+			enum Mode {
+				Property,
+				RankAscending,
+				#[vpl(rename = "PNG")]
+				Png,
+			}
+		);
+		let ts = match &input.data {
+			syn::Data::Enum(de) => decode_enum(input.clone(), de.clone()),
+			_ => panic!("Expected enum data"),
+		};
+		let lines = pretty_tokens(ts);
+		for line in [
+			"        vec![\"property\", \"rank_ascending\", \"PNG\"]",
+			"            Self::RankAscending => \"rank_ascending\",",
+			"impl TryFrom<&str> for Mode {",
+			"                \"rank_ascending\" => Self::RankAscending,",
+			"                \"png\" => Self::Png,",
+		] {
+			assert!(lines.contains(&line.to_string()), "missing {line:?} in {lines:#?}");
+		}
+	}
+
+	#[test]
+	fn test_snake_case() {
+		assert_eq!(snake_case("Property"), "property");
+		assert_eq!(snake_case("RankAscending"), "rank_ascending");
+		assert_eq!(snake_case("PNG"), "png");
+		assert_eq!(snake_case("Level2Tiles"), "level2_tiles");
+	}
+
+	#[test]
+	fn test_json_string() {
+		assert_eq!(json_string("a \"b\"\\\n\u{1}"), r#""a \"b\"\\\n\u0001""#);
 	}
 }
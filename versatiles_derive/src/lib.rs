@@ -4,7 +4,7 @@
 //! generating configuration documentation, and adding error context to functions.
 //!
 //! # Provided macros
-//! - `#[derive(VPLDecode)]`: Derive macro to decode VPL data into Rust structs.
+//! - `#[derive(VPLDecode)]`: Derive macro to decode VPL data into Rust structs and enums.
 //! - `#[derive(ConfigDoc)]`: Derive macro to generate YAML documentation for configuration structs.
 //! - `#[context("...")]`: Attribute macro to add error context to functions returning `Result`.
 
@@ -22,13 +22,23 @@ use syn::{Fields, parse_macro_input, spanned::Spanned};
 ///
 /// This macro can be applied to named-field structs to automatically generate decoding logic
 /// from VPL (VersaTiles Programming Language) data.
-#[proc_macro_derive(VPLDecode)]
+///
+/// Fields support the following attributes:
+///
+/// - `#[vpl(default = EXPR)]` makes a parameter optional and uses `EXPR` if it is missing.
+/// - `#[vpl(min = EXPR, max = EXPR)]` checks the range of a number.
+/// - `#[vpl(flatten)]` reads the parameters of a nested struct, that also derives `VPLDecode`.
+///
+/// Applied to an enum with unit variants, it implements `TryFrom<&str>`, so that the enum can be used
+/// as a field type. Variants are written in snake case, unless they are renamed with `#[vpl(rename = "...")]`.
+#[proc_macro_derive(VPLDecode, attributes(vpl))]
 pub fn decode_vpl(input: TokenStream) -> TokenStream {
 	let input = parse_macro_input!(input as syn::DeriveInput);
 
 	let expanded = match input.data.clone() {
 		syn::Data::Struct(data_struct) => decode_struct(input, data_struct),
-		_ => panic!("VPLDecode can only be derived for structs, but: {:?}", input.data),
+		syn::Data::Enum(data_enum) => decode_enum(input, data_enum),
+		syn::Data::Union(_) => panic!("VPLDecode can only be derived for structs and enums"),
 	};
 
 	TokenStream::from(expanded)
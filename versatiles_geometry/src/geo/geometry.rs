@@ -8,8 +8,8 @@
 use crate::geo::CompositeGeometryTrait;
 
 use super::{
-	GeometryTrait, LineStringGeometry, MultiLineStringGeometry, MultiPointGeometry, MultiPolygonGeometry, PointGeometry,
-	PolygonGeometry, SingleGeometryTrait,
+	Coordinates, GeometryTrait, LineStringGeometry, MultiLineStringGeometry, MultiPointGeometry, MultiPolygonGeometry,
+	PointGeometry, PolygonGeometry, SingleGeometryTrait,
 };
 use anyhow::Result;
 use std::fmt::Debug;
@@ -186,6 +186,38 @@ impl Geometry {
 		}
 	}
 
+	/// Replaces every coordinate of the geometry with the result of `f`, e.g. to project
+	/// tile-local coordinates to longitude/latitude.
+	///
+	/// ```rust
+	/// # use versatiles_geometry::geo::{Coordinates, Geometry};
+	/// let mut geometry = Geometry::new_line_string(vec![[0.0, 0.0], [2.0, 4.0]]);
+	/// geometry.map_coordinates(|c| Coordinates::new(c.x() / 2.0, c.y() / 2.0));
+	/// assert_eq!(geometry, Geometry::new_line_string(vec![[0.0, 0.0], [1.0, 2.0]]));
+	/// ```
+	pub fn map_coordinates<F>(&mut self, mut f: F)
+	where
+		F: FnMut(&Coordinates) -> Coordinates,
+	{
+		let mut map_all = |coordinates: &mut Vec<Coordinates>| {
+			for c in coordinates.iter_mut() {
+				*c = f(c);
+			}
+		};
+		match self {
+			Geometry::Point(g) => g.0 = f(&g.0),
+			Geometry::LineString(g) => map_all(&mut g.0),
+			Geometry::Polygon(g) => g.0.iter_mut().for_each(|ring| map_all(&mut ring.0)),
+			Geometry::MultiPoint(g) => g.0.iter_mut().for_each(|point| point.0 = f(&point.0)),
+			Geometry::MultiLineString(g) => g.0.iter_mut().for_each(|line| map_all(&mut line.0)),
+			Geometry::MultiPolygon(g) => {
+				g.0.iter_mut()
+					.flat_map(|polygon| polygon.0.iter_mut())
+					.for_each(|ring| map_all(&mut ring.0))
+			}
+		}
+	}
+
 	/// Serializes the geometry into a GeoJSON-compatible object with `type` and `coordinates`.
 	/// Coordinates may be rounded to `precision` fractional digits if provided.
 	pub fn to_json(&self, precision: Option<u8>) -> JsonObject {
@@ -0,0 +1,193 @@
+//! Boolean operations on polygons and clipping of lines, in tile coordinate space.
+//!
+//! All operations are delegated to the robust implementation of the `geo` crate. Resulting polygons
+//! are oriented like polygons decoded from vector tiles (outer rings with positive area, holes with
+//! negative area), so they can be encoded again directly. Empty results are empty geometries.
+//!
+//! ```
+//! use versatiles_geometry::geo::*;
+//!
+//! let a = MultiPolygonGeometry::from_bbox([0.0, 0.0, 4.0, 4.0]);
+//! let b = MultiPolygonGeometry::from_bbox([2.0, 0.0, 6.0, 4.0]);
+//! assert_eq!(a.union(&b).area(), 24.0);
+//! assert_eq!(a.intersection(&b).area(), 8.0);
+//! assert_eq!(a.difference(&b).area(), 8.0);
+//!
+//! let line = MultiLineStringGeometry::from(&[vec![[-2, 1], [8, 1]]]);
+//! assert_eq!(line.clip(&a).length(), 4.0);
+//! ```
+
+use super::{Coordinates, LineStringGeometry, MultiLineStringGeometry, MultiPolygonGeometry};
+use geo::{BooleanOps, Orient, orient::Direction};
+
+impl MultiPolygonGeometry {
+	/// Creates a rectangle from `[x_min, y_min, x_max, y_max]`, e.g. to clip geometries to a tile.
+	#[must_use]
+	pub fn from_bbox(bbox: [f64; 4]) -> MultiPolygonGeometry {
+		let [x_min, y_min, x_max, y_max] = bbox;
+		let rect = geo::Rect::new((x_min, y_min), (x_max, y_max));
+		from_geo(geo::MultiPolygon::new(vec![rect.to_polygon()]))
+	}
+
+	/// Returns the area covered by `self` or `other`.
+	#[must_use]
+	pub fn union(&self, other: &MultiPolygonGeometry) -> MultiPolygonGeometry {
+		from_geo(to_geo(self).union(&to_geo(other)))
+	}
+
+	/// Returns the area covered by both `self` and `other`.
+	#[must_use]
+	pub fn intersection(&self, other: &MultiPolygonGeometry) -> MultiPolygonGeometry {
+		from_geo(to_geo(self).intersection(&to_geo(other)))
+	}
+
+	/// Returns the area covered by `self` but not by `other`.
+	#[must_use]
+	pub fn difference(&self, other: &MultiPolygonGeometry) -> MultiPolygonGeometry {
+		from_geo(to_geo(self).difference(&to_geo(other)))
+	}
+
+	/// Returns the part of `self` inside the rectangle `[x_min, y_min, x_max, y_max]`.
+	#[must_use]
+	pub fn clip_to_bbox(&self, bbox: [f64; 4]) -> MultiPolygonGeometry {
+		self.intersection(&MultiPolygonGeometry::from_bbox(bbox))
+	}
+
+	/// Unions all polygons of `geometries`, so that overlapping and touching polygons become one polygon.
+	#[must_use]
+	pub fn union_all(geometries: &[MultiPolygonGeometry]) -> MultiPolygonGeometry {
+		let polygons = geometries
+			.iter()
+			.flat_map(|geometry| to_geo(geometry).0)
+			.collect::<Vec<_>>();
+		from_geo(geo::unary_union(&polygons))
+	}
+}
+
+impl MultiLineStringGeometry {
+	/// Returns the parts of the lines that are inside `polygons`.
+	#[must_use]
+	pub fn clip(&self, polygons: &MultiPolygonGeometry) -> MultiLineStringGeometry {
+		self.clip_with(polygons, false)
+	}
+
+	/// Returns the parts of the lines that are outside of `polygons`.
+	#[must_use]
+	pub fn clip_outside(&self, polygons: &MultiPolygonGeometry) -> MultiLineStringGeometry {
+		self.clip_with(polygons, true)
+	}
+
+	/// Returns the parts of the lines inside the rectangle `[x_min, y_min, x_max, y_max]`.
+	#[must_use]
+	pub fn clip_to_bbox(&self, bbox: [f64; 4]) -> MultiLineStringGeometry {
+		self.clip(&MultiPolygonGeometry::from_bbox(bbox))
+	}
+
+	fn clip_with(&self, polygons: &MultiPolygonGeometry, invert: bool) -> MultiLineStringGeometry {
+		let lines = geo::MultiLineString::new(
+			self
+				.0
+				.iter()
+				.map(|line| geo::LineString::from(line.0.iter().map(|c| (c.x(), c.y())).collect::<Vec<_>>()))
+				.collect(),
+		);
+		MultiLineStringGeometry(
+			to_geo(polygons)
+				.clip(&lines, invert)
+				.into_iter()
+				.filter(|line| line.0.len() >= 2)
+				.map(|line| LineStringGeometry(line.0.into_iter().map(Coordinates::from).collect()))
+				.collect(),
+		)
+	}
+}
+
+fn to_geo(geometry: &MultiPolygonGeometry) -> geo::MultiPolygon<f64> {
+	geo::MultiPolygon::from(geometry)
+}
+
+fn from_geo(geometry: geo::MultiPolygon<f64>) -> MultiPolygonGeometry {
+	MultiPolygonGeometry::from(geometry.orient(Direction::Default))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::geo::GeometryTrait;
+
+	fn square(x: i32, y: i32, size: i32) -> MultiPolygonGeometry {
+		MultiPolygonGeometry::from(&[[[[x, y], [x + size, y], [x + size, y + size], [x, y + size], [x, y]]]])
+	}
+
+	#[test]
+	fn from_bbox() {
+		let rect = MultiPolygonGeometry::from_bbox([0.0, 0.0, 4.0, 2.0]);
+		assert_eq!(rect.area(), 8.0);
+		assert!(rect.has_valid_winding());
+	}
+
+	#[test]
+	fn union_intersection_difference() {
+		let a = square(0, 0, 4);
+		let b = square(2, 2, 4);
+
+		let union = a.union(&b);
+		assert_eq!(union.0.len(), 1);
+		assert_eq!(union.area(), 28.0);
+		assert!(union.has_valid_winding());
+
+		let intersection = a.intersection(&b);
+		assert_eq!(intersection.area(), 4.0);
+		assert!(intersection.has_valid_winding());
+
+		let difference = a.difference(&b);
+		assert_eq!(difference.area(), 12.0);
+		assert!(difference.has_valid_winding());
+
+		assert!(a.intersection(&square(10, 10, 1)).0.is_empty());
+	}
+
+	#[test]
+	fn difference_creates_hole() {
+		let difference = square(0, 0, 10).difference(&square(4, 4, 2));
+		assert_eq!(difference.0.len(), 1);
+		assert_eq!(difference.0[0].0.len(), 2);
+		assert_eq!(difference.area(), 96.0);
+		assert!(difference.has_valid_winding());
+	}
+
+	#[test]
+	fn clip_to_bbox() {
+		assert_eq!(square(-2, -2, 4).clip_to_bbox([0.0, 0.0, 10.0, 10.0]).area(), 4.0);
+	}
+
+	#[test]
+	fn union_all_merges_touching_polygons() {
+		let geometries = [square(0, 0, 4), square(4, 0, 4), square(20, 0, 4)];
+		let union = MultiPolygonGeometry::union_all(&geometries);
+		assert_eq!(union.0.len(), 2);
+		assert_eq!(union.area(), 48.0);
+		assert!(union.has_valid_winding());
+	}
+
+	#[test]
+	fn clip_lines() {
+		let lines = MultiLineStringGeometry::from(&[vec![[-5, 2], [5, 2], [5, 20]], vec![[20, 20], [30, 30]]]);
+		let area = square(0, 0, 10);
+
+		let inside = lines.clip(&area);
+		assert!((inside.length() - 13.0).abs() < 1e-9);
+		assert!(
+			inside
+				.0
+				.iter()
+				.flat_map(|l| &l.0)
+				.all(|c| c.x() >= 0.0 && c.y() <= 10.0)
+		);
+
+		let outside = lines.clip_outside(&area);
+		assert!((outside.length() - (15.0 + 200f64.sqrt())).abs() < 1e-9);
+
+		assert_eq!(lines.clip_to_bbox([0.0, 0.0, 10.0, 10.0]).length(), inside.length());
+	}
+}
@@ -7,6 +7,18 @@ use versatiles_core::json::JsonValue;
 #[derive(Clone, PartialEq)]
 pub struct LineStringGeometry(pub Vec<Coordinates>);
 
+impl LineStringGeometry {
+	/// Returns the length of the line, i.e. the sum of the distances between consecutive coordinates.
+	#[must_use]
+	pub fn length(&self) -> f64 {
+		self
+			.0
+			.windows(2)
+			.map(|w| (w[1].x() - w[0].x()).hypot(w[1].y() - w[0].y()))
+			.sum()
+	}
+}
+
 impl GeometryTrait for LineStringGeometry {
 	/// Returns the area of the geometry.
 	///
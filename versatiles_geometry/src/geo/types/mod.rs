@@ -5,6 +5,7 @@
 // geometry types, including validation, area calculation, and JSON conversion.
 // The module re-exports all geometry types for convenient public access.
 
+mod boolean_ops;
 mod coordinates;
 mod linestring;
 mod macros;
@@ -1,6 +1,6 @@
-use super::{CompositeGeometryTrait, GeometryTrait, LineStringGeometry};
+use super::{CompositeGeometryTrait, Coordinates, GeometryTrait, LineStringGeometry};
 use anyhow::Result;
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug};
 use versatiles_core::json::JsonValue;
 
 /// Represents a collection of connected line strings, each being a sequence of coordinates.
@@ -8,6 +8,68 @@ use versatiles_core::json::JsonValue;
 #[derive(Clone, PartialEq)]
 pub struct MultiLineStringGeometry(pub Vec<LineStringGeometry>);
 
+impl MultiLineStringGeometry {
+	/// Returns the total length of all lines.
+	#[must_use]
+	pub fn length(&self) -> f64 {
+		self.0.iter().map(LineStringGeometry::length).sum()
+	}
+
+	/// Joins lines whose ends touch into longer lines.
+	///
+	/// Lines are only joined at points where exactly two line ends meet, so junctions of three or
+	/// more lines are kept. Lines may be reversed to be joined. Lines without coordinates are dropped.
+	#[must_use]
+	pub fn merge_lines(self) -> MultiLineStringGeometry {
+		fn key(c: &Coordinates) -> (u64, u64) {
+			(c.x().to_bits(), c.y().to_bits())
+		}
+
+		let mut lines = self
+			.0
+			.into_iter()
+			.filter(|line| !line.0.is_empty())
+			.map(|line| Some(line.0))
+			.collect::<Vec<_>>();
+
+		// all line ends, by position
+		let mut nodes: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+		for (index, line) in lines.iter().enumerate() {
+			let line = line.as_ref().unwrap();
+			nodes.entry(key(&line[0])).or_default().push(index);
+			nodes.entry(key(line.last().unwrap())).or_default().push(index);
+		}
+
+		let mut result = Vec::new();
+		for index in 0..lines.len() {
+			let Some(mut line) = lines[index].take() else {
+				continue;
+			};
+			// extend the end, then reverse and extend the other end
+			for _ in 0..2 {
+				loop {
+					let end = line.last().unwrap();
+					let ends = &nodes[&key(end)];
+					if ends.len() != 2 {
+						break;
+					}
+					let Some(next) = ends.iter().find_map(|i| lines[*i].take()) else {
+						break;
+					};
+					if key(&next[0]) == key(end) {
+						line.extend(next.into_iter().skip(1));
+					} else {
+						line.extend(next.into_iter().rev().skip(1));
+					}
+				}
+				line.reverse();
+			}
+			result.push(LineStringGeometry(line));
+		}
+		MultiLineStringGeometry(result)
+	}
+}
+
 /// Implementation of the `GeometryTrait` for `MultiLineStringGeometry`.
 impl GeometryTrait for MultiLineStringGeometry {
 	/// Returns the area of the geometry, which is always 0 for line strings since they have no area.
@@ -63,3 +125,36 @@ impl Debug for MultiLineStringGeometry {
 }
 
 crate::impl_from_array!(MultiLineStringGeometry, LineStringGeometry);
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn merge_lines_joins_touching_ends() {
+		let geometry = MultiLineStringGeometry::from(&[
+			vec![[0, 0], [1, 0]],
+			vec![[2, 0], [1, 0]],
+			vec![[2, 0], [3, 0], [3, 1]],
+			vec![[5, 5], [6, 6]],
+		]);
+		let merged = geometry.merge_lines();
+		assert_eq!(
+			merged,
+			MultiLineStringGeometry::from(&[vec![[0, 0], [1, 0], [2, 0], [3, 0], [3, 1]], vec![[5, 5], [6, 6]]])
+		);
+		assert_eq!(merged.0[0].length(), 4.0);
+	}
+
+	#[test]
+	fn merge_lines_keeps_junctions_and_loops() {
+		let geometry = MultiLineStringGeometry::from(&[
+			vec![[0, 0], [1, 0]],
+			vec![[1, 0], [2, 0]],
+			vec![[1, 0], [1, 1]],
+			vec![[5, 5], [6, 5], [6, 6], [5, 5]],
+		]);
+		let merged = geometry.clone().merge_lines();
+		assert_eq!(merged, geometry);
+	}
+}
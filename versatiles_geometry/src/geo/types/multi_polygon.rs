@@ -1,5 +1,6 @@
 use super::{CompositeGeometryTrait, GeometryTrait, PolygonGeometry};
 use anyhow::Result;
+use geo::{BooleanOps, Orient, Validation, orient::Direction};
 use std::fmt::Debug;
 use versatiles_core::json::JsonValue;
 
@@ -70,3 +71,90 @@ impl Debug for MultiPolygonGeometry {
 }
 
 crate::impl_from_array!(MultiPolygonGeometry, PolygonGeometry);
+
+impl MultiPolygonGeometry {
+	/// Returns `true` if every outer ring has a positive and every inner ring a negative area.
+	pub fn has_valid_winding(&self) -> bool {
+		self.0.iter().all(|polygon| {
+			polygon
+				.0
+				.iter()
+				.enumerate()
+				.all(|(i, ring)| if i == 0 { ring.area() > 0.0 } else { ring.area() < 0.0 })
+		})
+	}
+
+	/// Repairs self-intersections, overlapping polygons and wrong ring orientations.
+	///
+	/// Invalid geometries are rebuilt with a boolean union using the even-odd fill rule, so e.g. a
+	/// "bow tie" becomes two triangles. Returns `None` if the geometry is already valid.
+	pub fn make_valid(&self) -> Option<MultiPolygonGeometry> {
+		let geometry = geo::MultiPolygon::from(self);
+		let geometry = if geometry.is_valid() {
+			if self.has_valid_winding() {
+				return None;
+			}
+			geometry
+		} else {
+			geometry.union(&geo::MultiPolygon::new(vec![]))
+		};
+		Some(MultiPolygonGeometry::from(geometry.orient(Direction::Default)))
+	}
+}
+
+impl From<&MultiPolygonGeometry> for geo::MultiPolygon<f64> {
+	fn from(geometry: &MultiPolygonGeometry) -> Self {
+		let to_line_string =
+			|ring: &super::RingGeometry| geo::LineString::from(ring.0.iter().map(|c| (c.x(), c.y())).collect::<Vec<_>>());
+		geo::MultiPolygon::new(
+			geometry
+				.0
+				.iter()
+				.filter(|polygon| !polygon.0.is_empty())
+				.map(|polygon| {
+					geo::Polygon::new(
+						to_line_string(&polygon.0[0]),
+						polygon.0[1..].iter().map(to_line_string).collect(),
+					)
+				})
+				.collect(),
+		)
+	}
+}
+
+impl From<geo::MultiPolygon<f64>> for MultiPolygonGeometry {
+	fn from(geometry: geo::MultiPolygon<f64>) -> Self {
+		MultiPolygonGeometry(geometry.into_iter().map(PolygonGeometry::from).collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn make_valid_keeps_valid_geometry() {
+		let geometry = MultiPolygonGeometry::from(&[[[[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]]]]);
+		assert!(geometry.has_valid_winding());
+		assert_eq!(geometry.make_valid(), None);
+	}
+
+	#[test]
+	fn make_valid_fixes_winding() {
+		let geometry = MultiPolygonGeometry::from(&[[[[0, 0], [0, 4], [4, 4], [4, 0], [0, 0]]]]);
+		assert!(!geometry.has_valid_winding());
+		let fixed = geometry.make_valid().unwrap();
+		assert!(fixed.has_valid_winding());
+		assert_eq!(fixed.area(), -geometry.area());
+	}
+
+	#[test]
+	fn make_valid_splits_bow_tie() {
+		let geometry = MultiPolygonGeometry::from(&[[[[0, 0], [4, 4], [4, 0], [0, 4], [0, 0]]]]);
+		let fixed = geometry.make_valid().unwrap();
+		assert_eq!(fixed.0.len(), 2);
+		assert!(fixed.has_valid_winding());
+		assert!(geo::MultiPolygon::from(&fixed).is_valid());
+		assert_eq!(fixed.area(), 8.0);
+	}
+}
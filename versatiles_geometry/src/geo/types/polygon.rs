@@ -14,20 +14,10 @@ pub struct PolygonGeometry(pub Vec<RingGeometry>);
 impl GeometryTrait for PolygonGeometry {
 	/// Calculates the area of the polygon.
 	///
-	/// The area is computed by summing the area of the outer ring and subtracting
-	/// the areas of any inner rings (holes).
+	/// The area is computed by summing the signed areas of all rings. Inner rings (holes)
+	/// are oriented opposite to the outer ring, so their areas are subtracted.
 	fn area(&self) -> f64 {
-		let mut outer = true;
-		let mut sum = 0.0;
-		for ring in &self.0 {
-			if outer {
-				sum = ring.area();
-				outer = false;
-			} else {
-				sum -= ring.area();
-			}
-		}
-		sum
+		self.0.iter().map(GeometryTrait::area).sum()
 	}
 
 	/// Verifies the validity of the polygon.
@@ -112,6 +102,6 @@ mod tests {
 	fn test_area() {
 		let polygon = PolygonGeometry::from(&[[[0, 0], [5, 0], [5, 5], [0, 5], [0, 0]]]);
 		let area = polygon.area();
-		assert_eq!(area, 50.0);
+		assert_eq!(area, 25.0);
 	}
 }
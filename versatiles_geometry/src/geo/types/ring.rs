@@ -20,7 +20,7 @@ impl GeometryTrait for RingGeometry {
 			sum += (p2.x() - p1.x()) * (p1.y() + p2.y());
 			p2 = p1;
 		}
-		sum
+		sum / 2.0
 	}
 
 	/// Verifies that the ring is valid by checking:
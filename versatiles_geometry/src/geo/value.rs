@@ -254,6 +254,17 @@ impl GeoValue {
 		}
 	}
 
+	/// Returns the value as `f64` if it is a number; otherwise returns an error.
+	pub fn as_f64(&self) -> Result<f64> {
+		match self {
+			GeoValue::Double(v) => Ok(*v),
+			GeoValue::Float(v) => Ok(f64::from(*v)),
+			GeoValue::Int(v) => Ok(*v as f64),
+			GeoValue::UInt(v) => Ok(*v as f64),
+			_ => bail!("value is not a number"),
+		}
+	}
+
 	/// Converts the `GeoValue` to the crate’s `JsonValue` representation.
 	/// Note: integer types are converted to `Number` (as `f64`) to match JSON semantics.
 	#[must_use]
@@ -3,6 +3,8 @@
 //! It includes modules for:
 //! - `geo`: core geometry primitives and traits (e.g., `Point`, `Polygon`, etc.).
 //! - `geojson`: parsing and serialization for GeoJSON and NDGeoJSON.
+//! - `max_zoom`: heuristics to choose a maximum zoom level for tiling vector data.
+//! - `osm_change`: tiles affected by OSM change files, for incremental tile updates.
 //! - `tile_outline`: helper for generating polygonal outlines from tile bounding boxes.
 //! - `vector_tile`: support for reading and writing Mapbox Vector Tile (MVT) protobuf data.
 //!
@@ -10,5 +12,7 @@
 
 pub mod geo;
 pub mod geojson;
+pub mod max_zoom;
+pub mod osm_change;
 pub mod tile_outline;
 pub mod vector_tile;
@@ -0,0 +1,247 @@
+//! Heuristics to choose a maximum zoom level for tiling vector data, similar to tippecanoe's `-zg`.
+//!
+//! [`estimate_max_zoom`] measures three properties of the data in Web Mercator:
+//! - **detail**: the distance of every vertex of a line or ring from the straight line between its neighbours.
+//!   This is the error a Douglas-Peucker simplification introduces when it removes the vertex, so at the maximum
+//!   zoom level the typical vertex must be at least `tolerance` tile units away to survive the simplification.
+//! - **spacing**: the distance between neighbouring features, e.g. points of interest. At the maximum zoom level
+//!   typical neighbours must be at least `tolerance` tile units apart, so they don't end up at the same position.
+//! - **density**: at the maximum zoom level, no tile may contain more than `max_features_per_tile` features.
+//!
+//! The result is the lowest zoom level that satisfies all three, limited to `min_zoom..=max_zoom`.
+
+use crate::{
+	geo::{Coordinates, GeoCollection, Geometry},
+	vector_tile::TileProjection,
+};
+use std::collections::HashMap;
+
+/// Options for [`estimate_max_zoom`].
+#[derive(Clone, Debug)]
+pub struct MaxZoomOptions {
+	/// Size of a tile in vector tile units. Defaults to 4096.
+	pub extent: u32,
+	/// Acceptable error at the maximum zoom level, in vector tile units. Defaults to 1.
+	pub tolerance: f64,
+	/// Maximum number of features per tile at the maximum zoom level. Defaults to 200,000.
+	pub max_features_per_tile: usize,
+	/// Lowest zoom level that can be returned. Defaults to 0.
+	pub min_zoom: u8,
+	/// Highest zoom level that can be returned. Defaults to 14.
+	pub max_zoom: u8,
+}
+
+impl Default for MaxZoomOptions {
+	fn default() -> Self {
+		Self {
+			extent: 4096,
+			tolerance: 1.0,
+			max_features_per_tile: 200_000,
+			min_zoom: 0,
+			max_zoom: 14,
+		}
+	}
+}
+
+/// Estimates a sensible maximum zoom level for tiling `collection`, see the [module documentation](self).
+///
+/// Returns `options.min_zoom` for an empty collection.
+#[must_use]
+pub fn estimate_max_zoom(collection: &GeoCollection, options: &MaxZoomOptions) -> u8 {
+	let mut deviations: Vec<f64> = Vec::new();
+	let mut anchors: Vec<[f64; 2]> = Vec::new();
+	let world = TileProjection::world();
+
+	for feature in &collection.features {
+		let mut anchor = None;
+		for_each_line(&feature.geometry, &mut |line| {
+			let line: Vec<[f64; 2]> = line.iter().map(|c| world.from_lon_lat_clamped(c).into()).collect();
+			anchor = anchor.or(line.first().copied());
+			deviations.extend(line.windows(3).map(|w| distance_to_line(w[1], w[0], w[2])));
+		});
+		anchors.extend(anchor);
+	}
+
+	let zoom_for = |distance: f64| -> u8 {
+		let zoom = (options.tolerance / (distance * f64::from(options.extent)))
+			.log2()
+			.ceil();
+		zoom.clamp(0.0, 32.0) as u8
+	};
+
+	let zoom_detail = median(deviations).map_or(0, zoom_for);
+	let zoom_spacing = median(neighbour_distances(&mut anchors)).map_or(0, zoom_for);
+	let zoom_density = (options.min_zoom..=options.max_zoom)
+		.find(|&zoom| max_features_per_tile(&anchors, zoom) <= options.max_features_per_tile)
+		.unwrap_or(options.max_zoom);
+
+	log::debug!("estimated max zoom: detail={zoom_detail}, spacing={zoom_spacing}, density={zoom_density}");
+
+	zoom_detail
+		.max(zoom_spacing)
+		.max(zoom_density)
+		.clamp(options.min_zoom, options.max_zoom.max(options.min_zoom))
+}
+
+/// Calls `f` for every point, line and ring of `geometry`.
+fn for_each_line(geometry: &Geometry, f: &mut impl FnMut(&[Coordinates])) {
+	match geometry {
+		Geometry::Point(g) => f(std::slice::from_ref(&g.0)),
+		Geometry::LineString(g) => f(&g.0),
+		Geometry::Polygon(g) => g.0.iter().for_each(|ring| f(&ring.0)),
+		Geometry::MultiPoint(g) => g.0.iter().for_each(|point| f(std::slice::from_ref(&point.0))),
+		Geometry::MultiLineString(g) => g.0.iter().for_each(|line| f(&line.0)),
+		Geometry::MultiPolygon(g) => {
+			g.0.iter()
+				.flat_map(|polygon| polygon.0.iter())
+				.for_each(|ring| f(&ring.0))
+		}
+	}
+}
+
+/// Distance of `p` from the line through `a` and `b`.
+fn distance_to_line(p: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+	let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+	let length = dx.hypot(dy);
+	if length == 0.0 {
+		return (p[0] - a[0]).hypot(p[1] - a[1]);
+	}
+	((p[0] - a[0]) * dy - (p[1] - a[1]) * dx).abs() / length
+}
+
+/// Distances between neighbouring points, using the order along a Z-order curve.
+/// Identical points are ignored.
+fn neighbour_distances(points: &mut [[f64; 2]]) -> Vec<f64> {
+	points.sort_by_cached_key(|p| z_order(*p));
+	points
+		.windows(2)
+		.map(|w| (w[1][0] - w[0][0]).hypot(w[1][1] - w[0][1]))
+		.collect()
+}
+
+/// Position of a point on a Z-order curve with 2^32 × 2^32 cells.
+fn z_order(p: [f64; 2]) -> u64 {
+	let spread = |v: f64| -> u64 {
+		let mut v = (v.clamp(0.0, 1.0) * f64::from(u32::MAX)) as u64;
+		v = (v | (v << 16)) & 0x0000_FFFF_0000_FFFF;
+		v = (v | (v << 8)) & 0x00FF_00FF_00FF_00FF;
+		v = (v | (v << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+		v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+		(v | (v << 1)) & 0x5555_5555_5555_5555
+	};
+	spread(p[0]) | (spread(p[1]) << 1)
+}
+
+/// Largest number of points in one tile of the zoom level.
+fn max_features_per_tile(points: &[[f64; 2]], zoom: u8) -> usize {
+	let scale = 2f64.powi(i32::from(zoom));
+	let max = scale - 1.0;
+	let mut counts: HashMap<(u32, u32), usize> = HashMap::new();
+	for p in points {
+		let tile = ((p[0] * scale).min(max) as u32, (p[1] * scale).min(max) as u32);
+		*counts.entry(tile).or_default() += 1;
+	}
+	counts.into_values().max().unwrap_or(0)
+}
+
+/// Median of all positive values, as zero distances carry no information about the detail.
+fn median(mut values: Vec<f64>) -> Option<f64> {
+	values.retain(|v| *v > 0.0);
+	if values.is_empty() {
+		return None;
+	}
+	let middle = values.len() / 2;
+	Some(*values.select_nth_unstable_by(middle, f64::total_cmp).1)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::geo::GeoFeature;
+
+	fn collection(geometries: Vec<Geometry>) -> GeoCollection {
+		GeoCollection::from(geometries.into_iter().map(GeoFeature::new).collect())
+	}
+
+	fn estimate(geometries: Vec<Geometry>) -> u8 {
+		estimate_max_zoom(&collection(geometries), &MaxZoomOptions::default())
+	}
+
+	/// A zigzag line along the equator, whose vertices deviate `amplitude` degrees from a straight line.
+	fn zigzag(amplitude: f64) -> Geometry {
+		Geometry::new_line_string(
+			(0..100)
+				.map(|i| [f64::from(i) * amplitude, if i % 2 == 0 { 0.0 } else { amplitude }])
+				.collect::<Vec<_>>(),
+		)
+	}
+
+	#[test]
+	fn empty() {
+		assert_eq!(estimate(vec![]), 0);
+		let options = MaxZoomOptions {
+			min_zoom: 3,
+			..Default::default()
+		};
+		assert_eq!(estimate_max_zoom(&collection(vec![]), &options), 3);
+	}
+
+	#[test]
+	fn detail_of_lines() {
+		// 1 unit of a 4096 unit tile at zoom z is 360° / 2^(z+12), e.g. 0.011° at zoom 3 and 0.0055° at zoom 4
+		assert_eq!(estimate(vec![zigzag(0.01)]), 4);
+		assert_eq!(estimate(vec![zigzag(0.0001)]), 10);
+		assert_eq!(estimate(vec![zigzag(0.00002)]), 13);
+		// more detail than zoom 14 can show
+		assert_eq!(estimate(vec![zigzag(0.0000001)]), 14);
+
+		// straight lines and duplicate vertices carry no detail
+		let line = Geometry::new_line_string(vec![[0.0, 0.0], [0.0, 0.0], [1.0, 0.0], [2.0, 0.0]]);
+		assert_eq!(estimate(vec![line]), 0);
+	}
+
+	#[test]
+	fn spacing_of_points() {
+		let points = |spacing: f64| {
+			(0..50)
+				.flat_map(|x| (0..50).map(move |y| Geometry::new_point([f64::from(x) * spacing, f64::from(y) * spacing])))
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(estimate(points(1.0)), 0);
+		assert_eq!(estimate(points(0.001)), 7);
+		assert_eq!(estimate(points(0.00001)), 14);
+	}
+
+	#[test]
+	fn density() {
+		// 1000 points at the same position can only be split by the density limit
+		let points = vec![Geometry::new_point([13.4, 52.5]); 1000];
+		assert_eq!(estimate(points.clone()), 0);
+		let options = MaxZoomOptions {
+			max_features_per_tile: 999,
+			..Default::default()
+		};
+		assert_eq!(estimate_max_zoom(&collection(points), &options), 14);
+
+		// 4 clusters of 100 points in the 4 quadrants of the world
+		let points = [[-90.0, 45.0], [90.0, 45.0], [-90.0, -45.0], [90.0, -45.0]]
+			.iter()
+			.flat_map(|p| vec![Geometry::new_point(*p); 100])
+			.collect::<Vec<_>>();
+		let options = MaxZoomOptions {
+			max_features_per_tile: 100,
+			..Default::default()
+		};
+		assert_eq!(estimate_max_zoom(&collection(points), &options), 1);
+	}
+
+	#[test]
+	fn helpers() {
+		assert_eq!(distance_to_line([1.0, 1.0], [0.0, 0.0], [2.0, 0.0]), 1.0);
+		assert_eq!(distance_to_line([3.0, 4.0], [0.0, 0.0], [0.0, 0.0]), 5.0);
+		assert_eq!(z_order([0.0, 0.0]), 0);
+		assert!(z_order([0.5, 0.0]) < z_order([0.0, 0.5]));
+		assert_eq!(median(vec![0.0, 3.0, 1.0, 2.0]), Some(2.0));
+		assert_eq!(median(vec![0.0]), None);
+	}
+}
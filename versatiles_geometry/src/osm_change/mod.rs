@@ -0,0 +1,386 @@
+//! Tiles affected by OSM change files (`*.osc`), e.g. to update tiles incrementally with minutely or daily diffs.
+//!
+//! An OSM change file lists the created, modified and deleted nodes, ways and relations. [`OsmChange`] collects
+//! the areas they touch:
+//! - a node with coordinates touches its position,
+//! - a way touches the bounding box of its nodes,
+//! - a relation touches the bounding boxes of its member nodes and ways.
+//!
+//! Only the nodes contained in the change files have known coordinates. Members that are not part of the changes
+//! are ignored, and ways or relations without any known member can not be located at all. They are counted by
+//! [`OsmChange::count_unresolved`]. Change files do not contain the previous position of moved or deleted nodes.
+//!
+//! [`OsmChange::affected_tiles`] turns every bounding box into the tiles it touches on each zoom level. The box is
+//! expanded by a buffer first, because renderers also draw features into neighbouring tiles, e.g. wide lines, labels
+//! or the buffer of vector tiles. The result can be passed to `versatiles convert --coordinate-list`.
+//!
+//! # Example
+//! ```
+//! use versatiles_core::TileCoord;
+//! use versatiles_geometry::osm_change::OsmChange;
+//!
+//! let change = OsmChange::from_xml(r#"
+//!   <osmChange version="0.6">
+//!     <modify><node id="1" lat="52.52" lon="13.40"/></modify>
+//!   </osmChange>"#).unwrap();
+//! let tiles = change.affected_tiles(14, 14, 0.0).unwrap();
+//! assert_eq!(tiles.len(), 1);
+//! assert!(tiles.contains(&TileCoord::new(14, 8801, 5373).unwrap()));
+//! ```
+
+use crate::{geo::Coordinates, vector_tile::TileProjection};
+use anyhow::{Result, anyhow, bail, ensure};
+use quick_xml::{
+	Reader,
+	events::{BytesStart, Event},
+};
+use std::collections::HashMap;
+use versatiles_core::{TileBBox, TileCoordList};
+use versatiles_derive::context;
+
+/// The nodes, ways and relations of one or more OSM change files, see the [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct OsmChange {
+	/// Longitude and latitude of every node with coordinates.
+	nodes: HashMap<i64, [f64; 2]>,
+	/// Node ids of every way.
+	ways: HashMap<i64, Vec<i64>>,
+	/// Ids of the member nodes and member ways of every relation.
+	relations: Vec<(Vec<i64>, Vec<i64>)>,
+}
+
+impl OsmChange {
+	/// Parses a single OSM change file.
+	pub fn from_xml(xml: &str) -> Result<OsmChange> {
+		let mut change = OsmChange::default();
+		change.add_xml(xml)?;
+		Ok(change)
+	}
+
+	/// Adds the changes of another OSM change file, e.g. to combine a sequence of minutely diffs.
+	///
+	/// The file is read as a stream of XML events, so large daily diffs are never held as a document tree.
+	#[context("Failed to parse OSM change file")]
+	pub fn add_xml(&mut self, xml: &str) -> Result<()> {
+		let mut reader = Reader::from_str(xml);
+		// names of the currently open elements, starting with <osmChange>
+		let mut path: Vec<String> = Vec::new();
+		// the node, way or relation that is currently read
+		let mut element: Option<Element> = None;
+		let mut has_root = false;
+
+		loop {
+			let (start, is_empty) = match reader.read_event()? {
+				Event::Start(start) => (start, false),
+				Event::Empty(start) => (start, true),
+				Event::End(_) => {
+					path.pop();
+					if path.len() == 2 {
+						self.insert(element.take());
+					}
+					continue;
+				}
+				Event::Eof => break,
+				_ => continue,
+			};
+
+			let name = element_name(&start);
+			match path.len() {
+				0 => {
+					ensure!(name == "osmChange", "expected root element <osmChange>, found <{name}>");
+					has_root = true;
+				}
+				// the actions <create>, <modify> and <delete>
+				1 => {}
+				2 => element = Some(Element::parse(&start, &name, &path[1])?),
+				3 => match (&mut element, name.as_str()) {
+					(Some(Element::Way(_, refs)), "nd") => refs.push(parse_attribute(&start, "ref")?),
+					(Some(Element::Relation(nodes, ways)), "member") => {
+						match get_attribute(&start, "type")?.as_deref() {
+							Some("node") => nodes.push(parse_attribute(&start, "ref")?),
+							Some("way") => ways.push(parse_attribute(&start, "ref")?),
+							// nested relations are not resolved
+							_ => {}
+						}
+					}
+					_ => {}
+				},
+				_ => {}
+			}
+
+			if !is_empty {
+				path.push(name);
+			} else if path.len() == 2 {
+				self.insert(element.take());
+			}
+		}
+
+		ensure!(has_root, "missing root element <osmChange>");
+		if let Some(name) = path.last() {
+			bail!("unexpected end of file in <{name}>");
+		}
+		Ok(())
+	}
+
+	fn insert(&mut self, element: Option<Element>) {
+		match element {
+			Some(Element::Node(id, Some(coordinates))) => {
+				self.nodes.insert(id, coordinates);
+			}
+			Some(Element::Way(id, refs)) => {
+				self.ways.insert(id, refs);
+			}
+			Some(Element::Relation(nodes, ways)) => self.relations.push((nodes, ways)),
+			Some(Element::Node(_, None)) | None => {}
+		}
+	}
+
+	/// The number of nodes with coordinates, ways and relations.
+	#[must_use]
+	pub fn count_elements(&self) -> (usize, usize, usize) {
+		(self.nodes.len(), self.ways.len(), self.relations.len())
+	}
+
+	/// The number of ways and relations that can not be located, because none of their members is known.
+	#[must_use]
+	pub fn count_unresolved(&self) -> usize {
+		let ways = self.ways.values().filter(|refs| self.way_bbox(refs).is_none()).count();
+		let relations = self
+			.relations
+			.iter()
+			.filter(|relation| self.relation_bbox(relation).is_none())
+			.count();
+		ways + relations
+	}
+
+	/// The bounding boxes of all located nodes, ways and relations, as `[lon_min, lat_min, lon_max, lat_max]`.
+	#[must_use]
+	pub fn bboxes(&self) -> Vec<[f64; 4]> {
+		let nodes = self.nodes.values().map(|&[lon, lat]| [lon, lat, lon, lat]);
+		let ways = self.ways.values().filter_map(|refs| self.way_bbox(refs));
+		let relations = self
+			.relations
+			.iter()
+			.filter_map(|relation| self.relation_bbox(relation));
+		nodes.chain(ways).chain(relations).collect()
+	}
+
+	/// The tiles on the zoom levels `level_min..=level_max` touched by the changes.
+	///
+	/// Every bounding box is expanded by `buffer` on each side before, measured in tiles of the respective zoom level,
+	/// e.g. `64.0 / 4096.0` for the usual buffer of vector tiles.
+	#[context("Failed to compute the tiles affected by the changes")]
+	pub fn affected_tiles(&self, level_min: u8, level_max: u8, buffer: f64) -> Result<TileCoordList> {
+		ensure!(
+			level_min <= level_max,
+			"level_min ({level_min}) must be <= level_max ({level_max})"
+		);
+		ensure!(level_max <= 31, "level_max ({level_max}) must be <= 31");
+		ensure!(buffer >= 0.0, "buffer ({buffer}) must not be negative");
+
+		let world = TileProjection::world();
+		let mut tiles = TileCoordList::new();
+		for bbox in self.bboxes() {
+			let min = world.from_lon_lat_clamped(&Coordinates::new(bbox[0], bbox[3]));
+			let max = world.from_lon_lat_clamped(&Coordinates::new(bbox[2], bbox[1]));
+			for level in level_min..=level_max {
+				let scale = 2f64.powi(i32::from(level));
+				let to_tile = |v: f64| (v * scale).floor().clamp(0.0, scale - 1.0) as u32;
+				let tile_bbox = TileBBox::from_min_and_max(
+					level,
+					to_tile(min.x() - buffer / scale),
+					to_tile(min.y() - buffer / scale),
+					to_tile(max.x() + buffer / scale),
+					to_tile(max.y() + buffer / scale),
+				)?;
+				for coord in tile_bbox.iter_coords() {
+					tiles.insert(coord);
+				}
+			}
+		}
+		Ok(tiles)
+	}
+
+	fn way_bbox(&self, refs: &[i64]) -> Option<[f64; 4]> {
+		refs
+			.iter()
+			.filter_map(|id| self.nodes.get(id))
+			.map(|&[lon, lat]| [lon, lat, lon, lat])
+			.reduce(union)
+	}
+
+	fn relation_bbox(&self, (nodes, ways): &(Vec<i64>, Vec<i64>)) -> Option<[f64; 4]> {
+		let nodes = self.way_bbox(nodes);
+		let ways = ways
+			.iter()
+			.filter_map(|id| self.ways.get(id))
+			.filter_map(|refs| self.way_bbox(refs));
+		nodes.into_iter().chain(ways).reduce(union)
+	}
+}
+
+/// A node, way or relation while it is read.
+enum Element {
+	/// Id and, unless deleted, longitude and latitude of a node.
+	Node(i64, Option<[f64; 2]>),
+	/// Id and node ids of a way.
+	Way(i64, Vec<i64>),
+	/// Ids of the member nodes and member ways of a relation.
+	Relation(Vec<i64>, Vec<i64>),
+}
+
+impl Element {
+	/// Parses the start tag `<name>` of an element inside the action `<action>`.
+	fn parse(start: &BytesStart, name: &str, action: &str) -> Result<Element> {
+		let id: i64 = parse_attribute(start, "id")?;
+		Ok(match name {
+			"node" => {
+				let coordinates = if get_attribute(start, "lat")?.is_some() && get_attribute(start, "lon")?.is_some() {
+					let lon: f64 = parse_attribute(start, "lon")?;
+					let lat: f64 = parse_attribute(start, "lat")?;
+					ensure!(
+						(-180.0..=180.0).contains(&lon) && (-90.0..=90.0).contains(&lat),
+						"node {id} has invalid coordinates ({lon}, {lat})"
+					);
+					Some([lon, lat])
+				} else {
+					None
+				};
+				Element::Node(id, coordinates)
+			}
+			"way" => Element::Way(id, Vec::new()),
+			"relation" => Element::Relation(Vec::new(), Vec::new()),
+			name => bail!("unknown element <{name}> in <{action}>"),
+		})
+	}
+}
+
+fn element_name(start: &BytesStart) -> String {
+	String::from_utf8_lossy(start.name().as_ref()).into_owned()
+}
+
+/// Returns the unescaped attribute `name` of `start`, if present.
+fn get_attribute(start: &BytesStart, name: &str) -> Result<Option<String>> {
+	match start.try_get_attribute(name)? {
+		Some(attribute) => Ok(Some(attribute.unescape_value()?.into_owned())),
+		None => Ok(None),
+	}
+}
+
+/// Parses the attribute `name` of `start`.
+fn parse_attribute<T: std::str::FromStr>(start: &BytesStart, name: &str) -> Result<T> {
+	let element = element_name(start);
+	let value = get_attribute(start, name)?.ok_or_else(|| anyhow!("<{element}> has no attribute {name:?}"))?;
+	value
+		.parse()
+		.map_err(|_| anyhow!("<{element}> has an invalid {name:?}: {value:?}"))
+}
+
+fn union(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+	[a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_core::TileCoord;
+
+	const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osmChange version="0.6" generator="test">
+  <create>
+    <node id="1" version="1" lat="52.5" lon="13.3"/>
+    <node id="2" version="1" lat="52.6" lon="13.5"/>
+  </create>
+  <modify>
+    <way id="10" version="2">
+      <nd ref="1"/>
+      <nd ref="2"/>
+      <nd ref="99"/>
+      <tag k="highway" v="residential"/>
+    </way>
+    <way id="11" version="3">
+      <nd ref="98"/>
+    </way>
+  </modify>
+  <delete>
+    <node id="3" version="4"/>
+    <relation id="20" version="5">
+      <member type="way" ref="10" role="outer"/>
+      <member type="relation" ref="21" role=""/>
+    </relation>
+    <relation id="22" version="1">
+      <member type="node" ref="97" role=""/>
+    </relation>
+  </delete>
+</osmChange>"#;
+
+	#[test]
+	fn parses_changes() -> Result<()> {
+		let change = OsmChange::from_xml(XML)?;
+		assert_eq!(change.count_elements(), (2, 2, 2));
+		assert_eq!(change.count_unresolved(), 2);
+
+		let mut bboxes = change.bboxes();
+		bboxes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+		assert_eq!(
+			bboxes,
+			[
+				[13.3, 52.5, 13.3, 52.5],
+				[13.3, 52.5, 13.5, 52.6],
+				[13.3, 52.5, 13.5, 52.6],
+				[13.5, 52.6, 13.5, 52.6],
+			]
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn combines_files() -> Result<()> {
+		let mut change =
+			OsmChange::from_xml(r#"<osmChange><create><node id="1" lat="1" lon="2"/></create></osmChange>"#)?;
+		change.add_xml(r#"<osmChange><modify><way id="5"><nd ref="1"/></way></modify></osmChange>"#)?;
+		assert_eq!(change.count_unresolved(), 0);
+		assert_eq!(change.bboxes(), [[2.0, 1.0, 2.0, 1.0], [2.0, 1.0, 2.0, 1.0]]);
+		Ok(())
+	}
+
+	#[test]
+	fn computes_affected_tiles() -> Result<()> {
+		let change = OsmChange::from_xml(XML)?;
+
+		// one tile per zoom level up to 8, then the changes span two tiles
+		let tiles = change.affected_tiles(0, 10, 0.0)?;
+		assert_eq!(tiles.len(), 13);
+		assert!(tiles.contains(&TileCoord::new(0, 0, 0)?));
+		assert_eq!(
+			tiles.bbox_pyramid().get_level_bbox(10).as_array()?,
+			[549, 335, 550, 335]
+		);
+
+		// the buffer reaches into the neighbouring tiles
+		let tiles = change.affected_tiles(10, 10, 0.5)?;
+		assert_eq!(
+			tiles.bbox_pyramid().get_level_bbox(10).as_array()?,
+			[549, 334, 550, 336]
+		);
+
+		// but not beyond the edge of the world
+		let tiles = change.affected_tiles(1, 1, 5.0)?;
+		assert_eq!(tiles.len(), 4);
+		Ok(())
+	}
+
+	#[test]
+	fn rejects_invalid_files() {
+		for xml in [
+			"<osm/>",
+			"<osmChange><create><node lat=\"1\" lon=\"2\"/></create></osmChange>",
+			"<osmChange><create><node id=\"1\" lat=\"100\" lon=\"2\"/></create></osmChange>",
+			"<osmChange><create><changeset id=\"1\"/></create></osmChange>",
+			"<osmChange>",
+		] {
+			assert!(OsmChange::from_xml(xml).is_err(), "{xml}");
+		}
+		assert!(OsmChange::default().affected_tiles(5, 4, 0.0).is_err());
+	}
+}
@@ -1,9 +1,10 @@
 #![allow(dead_code)]
 
-use super::{geometry_type::GeomType, layer::VectorTileLayer};
+use super::{GeometryEncoder, geometry_type::GeomType, layer::VectorTileLayer};
 use crate::geo::{
 	CompositeGeometryTrait, Coordinates, GeoFeature, GeoProperties, GeoValue, Geometry, GeometryTrait,
-	MultiLineStringGeometry, MultiPointGeometry, MultiPolygonGeometry, RingGeometry, SingleGeometryTrait,
+	MultiLineStringGeometry, MultiPointGeometry, MultiPolygonGeometry, PolygonGeometry, RingGeometry,
+	SingleGeometryTrait,
 };
 use anyhow::{Context, Result, bail, ensure};
 use byteorder::LE;
@@ -216,84 +217,29 @@ impl VectorTileFeature {
 	}
 
 	pub fn from_geometry(id: Option<u64>, tag_ids: Vec<u32>, geometry: Geometry) -> Result<VectorTileFeature> {
-		fn write_coord(writer: &mut ValueWriterBlob<LE>, coord0: &mut (i64, i64), coord: &Coordinates) -> Result<()> {
-			let x = coord.x().round() as i64;
-			let y = coord.y().round() as i64;
-			writer.write_svarint(x - coord0.0)?;
-			writer.write_svarint(y - coord0.1)?;
-			coord0.0 = x;
-			coord0.1 = y;
-			Ok(())
-		}
-
 		fn write_points(points: MultiPointGeometry) -> Result<Blob> {
-			let mut writer = ValueWriterBlob::new_le();
-			let point0 = &mut (0i64, 0i64);
-			writer.write_varint(((points.len() as u64) << 3) | 0x1)?;
-			for point in points.into_iter() {
-				write_coord(&mut writer, point0, point.as_coord())?;
-			}
-			Ok(writer.into_blob())
+			let mut encoder = GeometryEncoder::new();
+			let coords = points.into_iter().map(|point| point.0).collect::<Vec<_>>();
+			encoder.add_points(&coords)?;
+			Ok(encoder.into_blob())
 		}
 
 		fn write_line_strings(line_strings: MultiLineStringGeometry) -> Result<Blob> {
-			let mut writer = ValueWriterBlob::new_le();
-			let point0 = &mut (0i64, 0i64);
-
+			let mut encoder = GeometryEncoder::new();
 			for line_string in line_strings.into_iter() {
-				if line_string.is_empty() {
-					continue;
-				}
-
-				let (first, rest) = line_string.into_first_and_rest().unwrap();
-
-				// Write the MoveTo command for the first point
-				writer.write_varint((1 << 3) | 0x1)?; // MoveTo command
-				write_coord(&mut writer, point0, &first)?;
-
-				// Write the LineTo command for the remaining points
-				if !rest.is_empty() {
-					writer.write_varint(((rest.len() as u64) << 3) | 0x2)?; // LineTo command
-					for point in rest {
-						write_coord(&mut writer, point0, &point)?;
-					}
-				}
+				encoder.add_line_string(line_string.as_vec())?;
 			}
-
-			Ok(writer.into_blob())
+			Ok(encoder.into_blob())
 		}
 
 		fn write_polygons(polygons: MultiPolygonGeometry) -> Result<Blob> {
-			let mut writer = ValueWriterBlob::new_le();
-			let point0 = &mut (0i64, 0i64);
-
+			let mut encoder = GeometryEncoder::new();
 			for polygon in polygons.into_iter() {
 				for ring in polygon.into_iter() {
-					if ring.len() < 4 {
-						continue;
-					}
-
-					let (first, mut rest) = ring.into_first_and_rest().unwrap();
-					rest.pop();
-
-					// Write the MoveTo command for the first point
-					writer.write_varint((1 << 3) | 0x1)?; // MoveTo command
-					write_coord(&mut writer, point0, &first)?;
-
-					// Write the LineTo command for the remaining points
-					if !rest.is_empty() {
-						writer.write_varint(((rest.len() as u64) << 3) | 0x2)?; // LineTo command
-						for point in rest {
-							write_coord(&mut writer, point0, &point)?;
-						}
-					}
-
-					// Write the ClosePath command
-					writer.write_varint(7)?; // ClosePath command
+					encoder.add_ring(ring.as_vec())?;
 				}
 			}
-
-			Ok(writer.into_blob())
+			Ok(encoder.into_blob())
 		}
 
 		fn m<T>(g: &[T]) -> Vec<&T> {
@@ -317,6 +263,76 @@ impl VectorTileFeature {
 		})
 	}
 
+	/// Scales all coordinates by `scale` and snaps them to multiples of `grid`, e.g. to reduce the extent
+	/// of a layer from 4096 to 1024 (`scale = 0.25`).
+	///
+	/// Consecutive duplicate points are removed. Line strings with less than two points, rings that collapse
+	/// or flip their orientation, and polygons that lose their outer ring are dropped.
+	/// Returns `None` if nothing of the geometry remains.
+	pub fn quantize(&self, scale: f64, grid: f64) -> Result<Option<VectorTileFeature>> {
+		let snap = |c: &Coordinates| {
+			Coordinates::new(
+				(c.x() * scale / grid).round() * grid,
+				(c.y() * scale / grid).round() * grid,
+			)
+		};
+		let snap_line = |line: &[Coordinates]| {
+			let mut result: Vec<Coordinates> = Vec::with_capacity(line.len());
+			for c in line.iter().map(snap) {
+				if result.last() != Some(&c) {
+					result.push(c);
+				}
+			}
+			result
+		};
+
+		let geometry = match self.to_geometry().context("Failed to decode geometry")? {
+			Geometry::MultiPoint(points) => {
+				let mut result: Vec<Coordinates> = Vec::with_capacity(points.len());
+				for c in points.into_iter().map(|p| snap(p.as_coord())) {
+					if !result.contains(&c) {
+						result.push(c);
+					}
+				}
+				(!result.is_empty()).then(|| Geometry::new_multi_point(result))
+			}
+			Geometry::MultiLineString(lines) => {
+				let lines = lines
+					.into_iter()
+					.map(|line| snap_line(line.as_vec()))
+					.filter(|line| line.len() >= 2)
+					.collect::<Vec<_>>();
+				(!lines.is_empty()).then(|| Geometry::new_multi_line_string(lines))
+			}
+			Geometry::MultiPolygon(polygons) => {
+				let mut result = Vec::new();
+				for polygon in polygons.into_iter() {
+					let mut rings = Vec::new();
+					for ring in polygon.into_iter() {
+						let area = ring.area();
+						let snapped = RingGeometry(snap_line(ring.as_vec()));
+						// keep only rings that are still valid and have the same orientation
+						if snapped.0.len() >= 4 && snapped.area() * area > 0.0 {
+							rings.push(snapped);
+						} else if rings.is_empty() {
+							// the outer ring has collapsed, so the holes are meaningless
+							break;
+						}
+					}
+					if !rings.is_empty() {
+						result.push(PolygonGeometry(rings));
+					}
+				}
+				(!result.is_empty()).then_some(Geometry::MultiPolygon(MultiPolygonGeometry(result)))
+			}
+			geometry => bail!("Unexpected geometry type {}", geometry.type_name()),
+		};
+
+		geometry
+			.map(|geometry| VectorTileFeature::from_geometry(self.id, self.tag_ids.clone(), geometry))
+			.transpose()
+	}
+
 	#[cfg(test)]
 	pub fn new_example() -> Self {
 		VectorTileFeature::from_geometry(Some(3), vec![1, 2], Geometry::new_example()).unwrap()
@@ -369,6 +385,53 @@ mod tests {
 		round_trip_feature(geometry)
 	}
 
+	#[test]
+	fn quantize_scales_and_removes_duplicates() -> Result<()> {
+		let feature = VectorTileFeature::from_geometry(
+			Some(7),
+			vec![1],
+			Geometry::new_line_string(&[[0, 0], [1, 1], [2, 2], [8, 8], [9, 8]]),
+		)?;
+		let result = feature.quantize(0.25, 1.0)?.unwrap();
+		assert_eq!(result.id, Some(7));
+		assert_eq!(result.tag_ids, vec![1]);
+		assert_eq!(
+			result.to_geometry()?,
+			Geometry::new_multi_line_string(&[vec![[0, 0], [1, 1], [2, 2]]])
+		);
+
+		// a short line collapses into a single point and is dropped
+		let feature = VectorTileFeature::from_geometry(None, vec![], Geometry::new_line_string(&[[0, 0], [1, 1]]))?;
+		assert!(feature.quantize(0.25, 1.0)?.is_none());
+		Ok(())
+	}
+
+	#[test]
+	fn quantize_snaps_to_grid_and_drops_collapsed_rings() -> Result<()> {
+		let feature = VectorTileFeature::from_geometry(
+			None,
+			vec![],
+			Geometry::new_multi_polygon(&[
+				vec![
+					vec![[0, 0], [30, 0], [30, 30], [0, 30], [0, 0]],
+					vec![[11, 11], [11, 12], [12, 12], [11, 11]],
+				],
+				vec![vec![[40, 0], [42, 0], [42, 2], [40, 0]]],
+			]),
+		)?;
+		let result = feature.quantize(1.0, 10.0)?.unwrap();
+		assert_eq!(
+			result.to_geometry()?,
+			Geometry::new_multi_polygon(&[vec![vec![[0, 0], [30, 0], [30, 30], [0, 30], [0, 0]]]])
+		);
+
+		let feature =
+			VectorTileFeature::from_geometry(None, vec![], Geometry::new_multi_point(&[[1, 1], [2, 2], [9, 9]]))?;
+		let result = feature.quantize(1.0, 10.0)?.unwrap();
+		assert_eq!(result.to_geometry()?, Geometry::new_multi_point(&[[0, 0], [10, 10]]));
+		Ok(())
+	}
+
 	#[test]
 	fn multi_polygon_geometry_round_trip() -> Result<()> {
 		let geometry = Geometry::new_multi_polygon(&[
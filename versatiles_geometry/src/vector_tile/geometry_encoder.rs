@@ -0,0 +1,235 @@
+//! Encoder for the geometry commands of Mapbox Vector Tile features.
+//!
+//! A feature geometry is a sequence of command integers (`MoveTo`, `LineTo`, `ClosePath`), each followed by
+//! delta- and zigzag-encoded coordinate pairs. [`GeometryEncoder`] keeps track of the cursor, rounds
+//! coordinates to the integer grid and produces the `geometry` field of a feature.
+//!
+//! Coordinates are given in tile pixels (`0..extent`) by default. With [`GeometryEncoder::with_extent`]
+//! they are given relative to the tile (`0.0..1.0`) and scaled to the extent instead.
+//!
+//! ```
+//! use versatiles_geometry::{geo::Coordinates, vector_tile::GeometryEncoder};
+//!
+//! let mut encoder = GeometryEncoder::with_extent(4096);
+//! encoder.add_line_string(&[Coordinates::new(0.0, 0.0), Coordinates::new(0.5, 0.25)]).unwrap();
+//! // MoveTo(1) [0, 0], LineTo(1) [+2048, +1024]
+//! assert_eq!(encoder.into_blob().as_slice(), &[9, 0, 0, 10, 128, 32, 128, 16]);
+//! ```
+//!
+//! See <https://github.com/mapbox/vector-tile-spec/blob/master/2.1/README.md#43-geometry-encoding>.
+
+use crate::geo::Coordinates;
+use anyhow::{Result, ensure};
+use versatiles_core::{Blob, io::pbf::PbfWriter};
+
+/// The largest repeat count of a command integer (29 bits).
+const MAX_COMMAND_COUNT: usize = (1 << 29) - 1;
+
+/// A geometry command of the vector tile specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeometryCommand {
+	MoveTo = 1,
+	LineTo = 2,
+	ClosePath = 7,
+}
+
+impl GeometryCommand {
+	/// Returns the command integer for `count` repetitions of this command.
+	///
+	/// # Errors
+	/// Returns an error if `count` exceeds 29 bits.
+	pub fn integer(self, count: usize) -> Result<u32> {
+		ensure!(count <= MAX_COMMAND_COUNT, "command count {count} is too large");
+		Ok(((count as u32) << 3) | self as u32)
+	}
+}
+
+/// Encodes points, line strings and rings into vector tile geometry commands.
+#[derive(Clone, Debug)]
+pub struct GeometryEncoder {
+	writer: PbfWriter,
+	cursor: (i64, i64),
+	scale: f64,
+}
+
+impl Default for GeometryEncoder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl GeometryEncoder {
+	/// Creates an encoder for coordinates in tile pixels.
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			writer: PbfWriter::new(),
+			cursor: (0, 0),
+			scale: 1.0,
+		}
+	}
+
+	/// Creates an encoder for tile-relative coordinates that are scaled by `extent`, e.g. 4096.
+	#[must_use]
+	pub fn with_extent(extent: u32) -> Self {
+		Self {
+			scale: f64::from(extent),
+			..Self::new()
+		}
+	}
+
+	/// Returns `true` if no command has been written yet.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.writer.is_empty()
+	}
+
+	/// Writes a command integer.
+	fn write_command(&mut self, command: GeometryCommand, count: usize) -> Result<()> {
+		self.writer.write_varint(u64::from(command.integer(count)?));
+		Ok(())
+	}
+
+	/// Moves the cursor to `coord` and writes the delta to the previous position.
+	fn write_coord(&mut self, coord: &Coordinates) {
+		let x = (coord.x() * self.scale).round() as i64;
+		let y = (coord.y() * self.scale).round() as i64;
+		self.writer.write_svarint(x - self.cursor.0);
+		self.writer.write_svarint(y - self.cursor.1);
+		self.cursor = (x, y);
+	}
+
+	/// Writes a `MoveTo` command followed by all `coords`.
+	///
+	/// # Errors
+	/// Returns an error if there are more coordinates than a command can hold.
+	pub fn move_to(&mut self, coords: &[Coordinates]) -> Result<()> {
+		self.write_command(GeometryCommand::MoveTo, coords.len())?;
+		coords.iter().for_each(|coord| self.write_coord(coord));
+		Ok(())
+	}
+
+	/// Writes a `LineTo` command followed by all `coords`.
+	///
+	/// # Errors
+	/// Returns an error if there are more coordinates than a command can hold.
+	pub fn line_to(&mut self, coords: &[Coordinates]) -> Result<()> {
+		self.write_command(GeometryCommand::LineTo, coords.len())?;
+		coords.iter().for_each(|coord| self.write_coord(coord));
+		Ok(())
+	}
+
+	/// Writes a `ClosePath` command.
+	pub fn close_path(&mut self) {
+		self
+			.writer
+			.write_varint(u64::from((1 << 3) | GeometryCommand::ClosePath as u32));
+	}
+
+	/// Adds the points of a (multi) point geometry. Empty input is ignored.
+	///
+	/// # Errors
+	/// Returns an error if there are more points than a command can hold.
+	pub fn add_points(&mut self, points: &[Coordinates]) -> Result<()> {
+		if points.is_empty() {
+			return Ok(());
+		}
+		self.move_to(points)
+	}
+
+	/// Adds a line string. Empty input is ignored.
+	///
+	/// # Errors
+	/// Returns an error if there are more points than a command can hold.
+	pub fn add_line_string(&mut self, line_string: &[Coordinates]) -> Result<()> {
+		let Some((first, rest)) = line_string.split_first() else {
+			return Ok(());
+		};
+		self.move_to(std::slice::from_ref(first))?;
+		if !rest.is_empty() {
+			self.line_to(rest)?;
+		}
+		Ok(())
+	}
+
+	/// Adds a closed ring, whose last point repeats the first one. Rings with less than four points are ignored.
+	///
+	/// Outer rings must be clockwise and inner rings counter-clockwise in tile coordinates (y pointing down).
+	///
+	/// # Errors
+	/// Returns an error if there are more points than a command can hold.
+	pub fn add_ring(&mut self, ring: &[Coordinates]) -> Result<()> {
+		if ring.len() < 4 {
+			return Ok(());
+		}
+		self.add_line_string(&ring[..ring.len() - 1])?;
+		self.close_path();
+		Ok(())
+	}
+
+	/// Returns the encoded geometry commands.
+	#[must_use]
+	pub fn into_blob(self) -> Blob {
+		self.writer.into_blob()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn coords(points: &[[f64; 2]]) -> Vec<Coordinates> {
+		points.iter().map(|p| Coordinates::new(p[0], p[1])).collect()
+	}
+
+	#[test]
+	fn command_integers() {
+		assert_eq!(GeometryCommand::MoveTo.integer(1).unwrap(), 9);
+		assert_eq!(GeometryCommand::LineTo.integer(3).unwrap(), 26);
+		assert_eq!(GeometryCommand::ClosePath.integer(1).unwrap(), 15);
+		assert!(GeometryCommand::LineTo.integer(MAX_COMMAND_COUNT + 1).is_err());
+	}
+
+	#[test]
+	fn spec_examples() -> Result<()> {
+		// examples from section 4.3.5 of the specification
+		let mut encoder = GeometryEncoder::new();
+		encoder.add_points(&coords(&[[25.0, 17.0]]))?;
+		assert_eq!(encoder.into_blob().as_slice(), &[9, 50, 34]);
+
+		let mut encoder = GeometryEncoder::new();
+		encoder.add_line_string(&coords(&[[2.0, 2.0], [2.0, 10.0], [10.0, 10.0]]))?;
+		assert_eq!(encoder.into_blob().as_slice(), &[9, 4, 4, 18, 0, 16, 16, 0]);
+
+		let mut encoder = GeometryEncoder::new();
+		encoder.add_ring(&coords(&[[3.0, 6.0], [8.0, 12.0], [20.0, 34.0], [3.0, 6.0]]))?;
+		assert_eq!(encoder.into_blob().as_slice(), &[9, 6, 12, 18, 10, 12, 24, 44, 15]);
+		Ok(())
+	}
+
+	#[test]
+	fn cursor_continues_across_parts() -> Result<()> {
+		let mut encoder = GeometryEncoder::new();
+		encoder.add_line_string(&coords(&[[2.0, 2.0], [2.0, 10.0], [10.0, 10.0]]))?;
+		encoder.add_line_string(&coords(&[[1.0, 1.0], [3.0, 5.0]]))?;
+		assert_eq!(
+			encoder.into_blob().as_slice(),
+			&[9, 4, 4, 18, 0, 16, 16, 0, 9, 17, 17, 10, 4, 8]
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn extent_and_degenerate_input() -> Result<()> {
+		let mut encoder = GeometryEncoder::with_extent(512);
+		assert!(encoder.is_empty());
+		encoder.add_points(&[])?;
+		encoder.add_line_string(&[])?;
+		encoder.add_ring(&coords(&[[0.0, 0.0], [1.0, 0.0], [0.0, 0.0]]))?;
+		assert!(encoder.is_empty());
+
+		encoder.add_points(&coords(&[[0.5, 0.25], [0.25, 0.5]]))?;
+		assert_eq!(encoder.into_blob().as_slice(), &[17, 128, 4, 128, 2, 255, 1, 128, 2]);
+		Ok(())
+	}
+}
@@ -15,12 +15,14 @@
 //!  * field 15: `version` (varint, default 1)
 
 use crate::{
-	geo::{GeoFeature, GeoProperties, GeoValue},
-	vector_tile::{feature::VectorTileFeature, property_manager::PropertyManager, value::GeoValuePBF},
+	geo::{GeoFeature, GeoProperties, GeoValue, Geometry, MultiLineStringGeometry, MultiPolygonGeometry},
+	vector_tile::{
+		feature::VectorTileFeature, geometry_type::GeomType, property_manager::PropertyManager, value::GeoValuePBF,
+	},
 };
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Context, Result, anyhow, bail, ensure};
 use byteorder::LE;
-use std::mem::swap;
+use std::{collections::HashMap, mem::swap};
 use versatiles_core::{
 	Blob,
 	io::{ValueReader, ValueWriter, ValueWriterBlob},
@@ -281,6 +283,251 @@ impl VectorTileLayer {
 		self.features.retain(filter_fn);
 	}
 
+	/// Reduces the coordinate precision: rescales all geometries to `extent` and snaps them to multiples of `grid`.
+	///
+	/// Features whose geometry collapses completely are removed. See [`VectorTileFeature::quantize`].
+	pub fn quantize(&mut self, extent: u32, grid: u32) -> Result<()> {
+		ensure!(extent > 0, "extent must be greater than 0");
+		ensure!(grid > 0, "grid must be greater than 0");
+		if extent == self.extent && grid == 1 {
+			return Ok(());
+		}
+
+		let scale = f64::from(extent) / f64::from(self.extent);
+		let mut features = Vec::with_capacity(self.features.len());
+		for feature in &self.features {
+			if let Some(feature) = feature
+				.quantize(scale, f64::from(grid))
+				.with_context(|| format!("Failed to quantize feature in layer '{}'", self.name))?
+			{
+				features.push(feature);
+			}
+		}
+		self.features = features;
+		self.extent = extent;
+		Ok(())
+	}
+
+	/// Repairs invalid polygon features (self-intersections, overlaps, wrong winding order).
+	///
+	/// Features that become empty are removed. Returns the number of repaired or removed features.
+	/// See [`MultiPolygonGeometry::make_valid`](crate::geo::MultiPolygonGeometry::make_valid).
+	pub fn fix_geometries(&mut self) -> Result<usize> {
+		let mut count = 0;
+		let mut features = Vec::with_capacity(self.features.len());
+		for feature in std::mem::take(&mut self.features) {
+			if feature.geom_type != GeomType::MultiPolygon {
+				features.push(feature);
+				continue;
+			}
+			let Geometry::MultiPolygon(geometry) = feature
+				.to_geometry()
+				.with_context(|| format!("Failed to decode feature in layer '{}'", self.name))?
+			else {
+				bail!("polygon feature decoded to a non-polygon geometry");
+			};
+			match geometry.make_valid() {
+				None => features.push(feature),
+				Some(fixed) => {
+					count += 1;
+					if !fixed.0.is_empty() {
+						features.push(VectorTileFeature::from_geometry(
+							feature.id,
+							feature.tag_ids,
+							Geometry::MultiPolygon(fixed),
+						)?);
+					}
+				}
+			}
+		}
+		self.features = features;
+		Ok(count)
+	}
+
+	/// Thins out point features, keeping at most `keep_top` points per square grid cell of `cell_size` tile units.
+	///
+	/// Within a cell, the points with the highest `rank` (computed from their properties) are kept;
+	/// on equal ranks the earlier feature wins. (Multi)points are placed by their first point.
+	/// Other geometry types are not touched. Returns the number of removed features.
+	pub fn declutter_points<F>(&mut self, cell_size: f64, keep_top: usize, rank: F) -> Result<usize>
+	where
+		F: Fn(&GeoProperties) -> f64,
+	{
+		ensure!(cell_size > 0.0, "cell size must be greater than 0");
+
+		let mut cells: HashMap<(i64, i64), Vec<(f64, usize)>> = HashMap::new();
+		for (index, feature) in self.features.iter().enumerate() {
+			if feature.geom_type != GeomType::MultiPoint {
+				continue;
+			}
+			let Geometry::MultiPoint(points) = feature
+				.to_geometry()
+				.with_context(|| format!("Failed to decode feature in layer '{}'", self.name))?
+			else {
+				bail!("point feature decoded to a non-point geometry");
+			};
+			let Some(point) = points.0.first() else {
+				continue;
+			};
+			let cell = (
+				(point.x() / cell_size).floor() as i64,
+				(point.y() / cell_size).floor() as i64,
+			);
+			let rank = rank(&self.decode_tag_ids(&feature.tag_ids)?);
+			cells.entry(cell).or_default().push((rank, index));
+		}
+
+		let mut remove = vec![false; self.features.len()];
+		let mut count = 0;
+		for mut entries in cells.into_values() {
+			if entries.len() <= keep_top {
+				continue;
+			}
+			entries.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+			for (_, index) in &entries[keep_top..] {
+				remove[*index] = true;
+				count += 1;
+			}
+		}
+
+		let mut remove = remove.into_iter();
+		self.features.retain(|_| !remove.next().unwrap());
+		Ok(count)
+	}
+
+	/// Joins line features with identical properties into one feature and merges their touching lines.
+	///
+	/// The merged feature replaces the first feature of its group and keeps its id only if all merged
+	/// features share it. Afterwards, lines shorter than `min_length` tile units are removed.
+	/// See [`MultiLineStringGeometry::merge_lines`]. Returns the number of removed features.
+	pub fn merge_lines(&mut self, min_length: f64) -> Result<usize> {
+		struct LineGroup {
+			slot: usize,
+			id: Option<u64>,
+			tag_ids: Vec<u32>,
+			lines: MultiLineStringGeometry,
+		}
+
+		let count = self.features.len();
+		let mut slots = Vec::with_capacity(count);
+		let mut groups: Vec<LineGroup> = Vec::new();
+		let mut group_index: HashMap<Vec<(u32, u32)>, usize> = HashMap::new();
+		for feature in std::mem::take(&mut self.features) {
+			if feature.geom_type != GeomType::MultiLineString {
+				slots.push(Some(feature));
+				continue;
+			}
+			let Geometry::MultiLineString(lines) = feature
+				.to_geometry()
+				.with_context(|| format!("Failed to decode feature in layer '{}'", self.name))?
+			else {
+				bail!("line feature decoded to a non-line geometry");
+			};
+
+			// identical properties may be encoded in a different order
+			let mut key = feature.tag_ids.chunks(2).map(|c| (c[0], c[1])).collect::<Vec<_>>();
+			key.sort_unstable();
+			if let Some(index) = group_index.get(&key) {
+				let group = &mut groups[*index];
+				if group.id != feature.id {
+					group.id = None;
+				}
+				group.lines.0.extend(lines.0);
+			} else {
+				group_index.insert(key, groups.len());
+				groups.push(LineGroup {
+					slot: slots.len(),
+					id: feature.id,
+					tag_ids: feature.tag_ids,
+					lines,
+				});
+				slots.push(None);
+			}
+		}
+
+		for group in groups {
+			let mut lines = group.lines.merge_lines();
+			lines.0.retain(|line| line.length() >= min_length);
+			if !lines.0.is_empty() {
+				slots[group.slot] = Some(VectorTileFeature::from_geometry(
+					group.id,
+					group.tag_ids,
+					Geometry::MultiLineString(lines),
+				)?);
+			}
+		}
+
+		self.features = slots.into_iter().flatten().collect();
+		Ok(count - self.features.len())
+	}
+
+	/// Unions all polygon features that share the same value of the property `field` into one feature.
+	///
+	/// The resulting features keep only `field` as property and have no id. Features without `field`
+	/// are dissolved together. Each feature replaces the first feature of its group; other geometry
+	/// types are not touched. Returns the number of removed features.
+	pub fn dissolve_polygons(&mut self, field: &str) -> Result<usize> {
+		struct PolygonGroup {
+			slot: usize,
+			value: Option<GeoValue>,
+			polygons: Vec<MultiPolygonGeometry>,
+		}
+
+		let count = self.features.len();
+		let mut slots = Vec::with_capacity(count);
+		let mut groups: Vec<PolygonGroup> = Vec::new();
+		let mut group_index: HashMap<Option<GeoValue>, usize> = HashMap::new();
+		for feature in std::mem::take(&mut self.features) {
+			if feature.geom_type != GeomType::MultiPolygon {
+				slots.push(Some(feature));
+				continue;
+			}
+			let Geometry::MultiPolygon(polygons) = feature
+				.to_geometry()
+				.with_context(|| format!("Failed to decode feature in layer '{}'", self.name))?
+			else {
+				bail!("polygon feature decoded to a non-polygon geometry");
+			};
+
+			let value = self.decode_tag_ids(&feature.tag_ids)?.get(field).cloned();
+			if let Some(index) = group_index.get(&value) {
+				groups[*index].polygons.push(polygons);
+			} else {
+				group_index.insert(value.clone(), groups.len());
+				groups.push(PolygonGroup {
+					slot: slots.len(),
+					value,
+					polygons: vec![polygons],
+				});
+				slots.push(None);
+			}
+		}
+
+		for group in groups {
+			let polygons = if group.polygons.len() == 1 {
+				group.polygons.into_iter().next().unwrap()
+			} else {
+				MultiPolygonGeometry::union_all(&group.polygons)
+			};
+			if polygons.0.is_empty() {
+				continue;
+			}
+			let mut properties = GeoProperties::new();
+			if let Some(value) = group.value {
+				properties.insert(field.to_string(), value);
+			}
+			let tag_ids = self.encode_tag_ids(properties);
+			slots[group.slot] = Some(VectorTileFeature::from_geometry(
+				None,
+				tag_ids,
+				Geometry::MultiPolygon(polygons),
+			)?);
+		}
+
+		self.features = slots.into_iter().flatten().collect();
+		Ok(count - self.features.len())
+	}
+
 	/// Encodes a property map to vector‑tile `tag_ids` using/expanding this layer's property tables.
 	pub fn encode_tag_ids(&mut self, properties: GeoProperties) -> Vec<u32> {
 		self.property_manager.encode_tag_ids(properties)
@@ -329,6 +576,7 @@ impl VectorTileLayer {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::geo::GeometryTrait;
 	use versatiles_core::io::ValueReaderSlice;
 
 	#[test]
@@ -336,8 +584,8 @@ mod tests {
 		// Example data for a vector tile layer
 		let data = vec![
 			0x0A, 0x05, b'h', b'e', b'l', b'l', b'o', // name: "hello"
-			18, 50, 8, 3, 18, 2, 1, 2, 24, 3, 34, 40, 9, 0, 0, 18, 10, 0, 3, 8, 7, 9, 1, 5, 18, 2, 2, 0, 1, 7, 9, 6, 1,
-			26, 6, 0, 0, 8, 5, 0, 7, 9, 2, 5, 26, 0, 4, 2, 0, 0, 3, 7, // feature
+			18, 50, 8, 3, 18, 2, 1, 2, 24, 3, 34, 40, 9, 0, 0, 18, 10, 0, 3, 8, 15, 9, 1, 5, 18, 2, 2, 0, 1, 15, 9, 6, 1,
+			26, 6, 0, 0, 8, 5, 0, 15, 9, 2, 5, 26, 0, 4, 2, 0, 0, 3, 15, // feature
 			0x1A, 0x03, b'k', b'e', b'y', // property key: "key"
 			0x22, 0x04, 0x0A, 0x02, b'v', b'l', // property value: "vl"
 		];
@@ -355,6 +603,154 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_quantize() -> Result<()> {
+		let features = vec![
+			GeoFeature::new(Geometry::new_line_string(&[[0, 0], [400, 400], [4096, 4096]])),
+			GeoFeature::new(Geometry::new_line_string(&[[0, 0], [1, 1]])),
+		];
+		let mut layer = VectorTileLayer::from_features(String::from("lines"), features, 4096, 1)?;
+		layer.quantize(1024, 1)?;
+		assert_eq!(layer.extent, 1024);
+		assert_eq!(layer.features.len(), 1);
+		assert_eq!(
+			layer.to_features()?[0].geometry,
+			Geometry::new_multi_line_string(&[vec![[0, 0], [100, 100], [1024, 1024]]])
+		);
+		assert!(layer.quantize(0, 1).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_fix_geometries() -> Result<()> {
+		let features = vec![
+			GeoFeature::new(Geometry::new_polygon(&[[[0, 0], [4, 4], [4, 0], [0, 6], [0, 0]]])),
+			GeoFeature::new(Geometry::new_polygon(&[[[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]]])),
+			GeoFeature::new(Geometry::new_line_string(&[[0, 0], [4, 4], [4, 0], [0, 4]])),
+		];
+		let mut layer = VectorTileLayer::from_features(String::from("areas"), features, 4096, 1)?;
+		assert_eq!(layer.fix_geometries()?, 1);
+		assert_eq!(layer.features.len(), 3);
+		let Geometry::MultiPolygon(fixed) = layer.features[0].to_geometry()? else {
+			panic!("expected a multi polygon");
+		};
+		assert_eq!(fixed.0.len(), 2);
+		assert_eq!(layer.fix_geometries()?, 0);
+		Ok(())
+	}
+
+	#[test]
+	fn test_declutter_points() -> Result<()> {
+		let point = |x: i32, y: i32, rank: i32| {
+			let mut feature = GeoFeature::new(Geometry::new_point(&[x, y]));
+			feature.set_property("rank".to_string(), rank);
+			feature
+		};
+		let features = vec![
+			point(10, 10, 1),
+			point(20, 20, 3),
+			point(30, 30, 2),
+			point(300, 10, 1),
+			GeoFeature::new(Geometry::new_line_string(&[[0, 0], [4, 4]])),
+		];
+		let mut layer = VectorTileLayer::from_features(String::from("pois"), features, 4096, 1)?;
+		let rank = |p: &GeoProperties| p.get("rank").unwrap().as_f64().unwrap();
+		assert_eq!(layer.declutter_points(256.0, 1, rank)?, 2);
+
+		let ranks = layer
+			.to_features()?
+			.iter()
+			.map(|f| f.properties.get("rank").map(|v| v.as_f64().unwrap()))
+			.collect::<Vec<_>>();
+		assert_eq!(ranks, vec![Some(3.0), Some(1.0), None]);
+
+		assert_eq!(layer.declutter_points(256.0, 1, rank)?, 0);
+		assert!(layer.declutter_points(0.0, 1, rank).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_declutter_empty_points() -> Result<()> {
+		let mut layer = VectorTileLayer::new_standard("pois");
+		let empty: Vec<[f64; 2]> = Vec::new();
+		layer.features.push(VectorTileFeature::from_geometry(
+			None,
+			vec![],
+			Geometry::new_multi_point(empty),
+		)?);
+		// empty (multi)points can't be decoded, but must not panic
+		assert!(layer.declutter_points(256.0, 0, |_| 0.0).is_err());
+		assert_eq!(layer.features.len(), 1);
+		Ok(())
+	}
+
+	#[test]
+	fn test_merge_lines() -> Result<()> {
+		let line = |coords: &[[i32; 2]], name: &str| {
+			let mut feature = GeoFeature::new(Geometry::new_line_string(coords));
+			feature.set_property("name".to_string(), name);
+			feature
+		};
+		let features = vec![
+			line(&[[0, 0], [10, 0]], "a"),
+			line(&[[10, 0], [20, 0]], "b"),
+			GeoFeature::new(Geometry::new_point(&[5, 5])),
+			line(&[[20, 0], [10, 0]], "a"),
+			line(&[[50, 50], [51, 50]], "a"),
+		];
+		let mut layer = VectorTileLayer::from_features(String::from("roads"), features, 4096, 1)?;
+		assert_eq!(layer.merge_lines(2.0)?, 2);
+
+		let features = layer.to_features()?;
+		assert_eq!(features.len(), 3);
+		assert_eq!(
+			features[0].geometry,
+			Geometry::new_multi_line_string(&[vec![[0, 0], [10, 0], [20, 0]]])
+		);
+		assert_eq!(features[0].properties.get("name"), Some(&GeoValue::from("a")));
+		assert_eq!(features[1].properties.get("name"), Some(&GeoValue::from("b")));
+		assert!(matches!(features[2].geometry, Geometry::MultiPoint(_)));
+		Ok(())
+	}
+
+	#[test]
+	fn test_dissolve_polygons() -> Result<()> {
+		let square = |x: i32, class: &str| {
+			let mut feature = GeoFeature::new(Geometry::new_polygon(&[[
+				[x, 0],
+				[x + 10, 0],
+				[x + 10, 10],
+				[x, 10],
+				[x, 0],
+			]]));
+			feature.set_property("class".to_string(), class);
+			feature.set_property("id".to_string(), x);
+			feature
+		};
+		let features = vec![
+			square(0, "forest"),
+			square(10, "water"),
+			square(20, "forest"),
+			square(10, "forest"),
+			GeoFeature::new(Geometry::new_point(&[5, 5])),
+		];
+		let mut layer = VectorTileLayer::from_features(String::from("landuse"), features, 4096, 1)?;
+		assert_eq!(layer.dissolve_polygons("class")?, 2);
+
+		let features = layer.to_features()?;
+		assert_eq!(features.len(), 3);
+		let Geometry::MultiPolygon(forest) = &features[0].geometry else {
+			panic!("expected a multi polygon");
+		};
+		assert_eq!(forest.0.len(), 1);
+		assert_eq!(forest.area(), 300.0);
+		assert_eq!(features[0].properties.len(), 1);
+		assert_eq!(features[0].properties.get("class"), Some(&GeoValue::from("forest")));
+		assert_eq!(features[1].properties.get("class"), Some(&GeoValue::from("water")));
+		assert!(matches!(features[2].geometry, Geometry::MultiPoint(_)));
+		Ok(())
+	}
+
 	#[test]
 	fn test_to_blob() -> Result<()> {
 		let layer = VectorTileLayer {
@@ -367,8 +763,8 @@ mod tests {
 		let blob = layer.to_blob()?;
 		let expected_data = vec![
 			0x0A, 0x05, b'h', b'e', b'l', b'l', b'o', // name: "hello"
-			18, 50, 8, 3, 18, 2, 1, 2, 24, 3, 34, 40, 9, 0, 0, 18, 10, 0, 3, 8, 7, 9, 1, 5, 18, 2, 2, 0, 1, 7, 9, 6, 1,
-			26, 6, 0, 0, 8, 5, 0, 7, 9, 2, 5, 26, 0, 4, 2, 0, 0, 3, 7, // feature
+			18, 50, 8, 3, 18, 2, 1, 2, 24, 3, 34, 40, 9, 0, 0, 18, 10, 0, 3, 8, 15, 9, 1, 5, 18, 2, 2, 0, 1, 15, 9, 6, 1,
+			26, 6, 0, 0, 8, 5, 0, 15, 9, 2, 5, 26, 0, 4, 2, 0, 0, 3, 15, // feature
 			0x1A, 0x03, b'k', b'e', b'y', // property key: "key"
 			0x22, 0x07, 0x0A, 0x05, b'v', b'a', b'l', b'u', b'e', // property value: "value"
 		];
@@ -8,14 +8,21 @@
 //! GeoJSON export.
 //!
 //! This module re‑exports the most commonly used types for convenience:
-//! [`VectorTileLayer`] and [`VectorTile`].
+//! [`VectorTileLayer`] and [`VectorTile`], as well as [`GeometryEncoder`] for
+//! writing feature geometries in new operations and [`TileProjection`] for converting
+//! tile-local coordinates to longitude/latitude and back.
 
 mod feature;
+mod geometry_encoder;
 mod geometry_type;
 mod layer;
+mod projection;
 mod property_manager;
 mod tile;
 mod value;
 
+pub use geometry_encoder::{GeometryCommand, GeometryEncoder};
+pub use geometry_type::GeomType;
 pub use layer::VectorTileLayer;
+pub use projection::TileProjection;
 pub use tile::VectorTile;
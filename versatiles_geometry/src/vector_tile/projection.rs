@@ -0,0 +1,187 @@
+//! Conversion between tile-local vector tile coordinates and longitude/latitude.
+//!
+//! Geometries in a vector tile are stored in the pixel space of their tile: `(0, 0)` is the top-left
+//! corner, `(extent, extent)` the bottom-right corner, and y points down. [`TileProjection`] converts
+//! such coordinates to WGS84 longitude/latitude in degrees (via Web Mercator) and back, e.g. to export
+//! features as GeoJSON or to write geographic data into a tile. It also converts them to Web Mercator
+//! meters (EPSG:3857) and back.
+//!
+//! ```rust
+//! use versatiles_core::TileCoord;
+//! use versatiles_geometry::{geo::Coordinates, vector_tile::TileProjection};
+//!
+//! let projection = TileProjection::new(TileCoord::new(1, 1, 0).unwrap(), 4096);
+//! let lon_lat = projection.to_lon_lat(&Coordinates::new(0.0, 4096.0));
+//! assert_eq!([lon_lat.x(), lon_lat.y()], [0.0, 0.0]);
+//!
+//! let pixel = projection.from_lon_lat(&Coordinates::new(90.0, 0.0));
+//! assert_eq!([pixel.x(), pixel.y()], [2048.0, 4096.0]);
+//! ```
+
+use crate::geo::{Coordinates, Geometry};
+use std::f64::consts::PI;
+use versatiles_core::{EARTH_RADIUS, TileCoord};
+
+/// Circumference of the Web Mercator world in meters.
+const WORLD_METERS: f64 = 2.0 * PI * EARTH_RADIUS;
+
+/// Latitude limit of Web Mercator.
+const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// Projects coordinates between the pixel space of a single vector tile and longitude/latitude.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TileProjection {
+	/// Number of pixels per world width at the zoom level of the tile.
+	world_size: f64,
+	/// Position of the tile's top-left corner in world pixels.
+	offset: (f64, f64),
+}
+
+impl TileProjection {
+	/// Creates a projection for the tile at `coord` with a layer extent of `extent` pixels, e.g. 4096.
+	#[must_use]
+	pub fn new(coord: TileCoord, extent: u32) -> Self {
+		let extent = f64::from(extent);
+		TileProjection {
+			world_size: 2f64.powi(i32::from(coord.level)) * extent,
+			offset: (f64::from(coord.x) * extent, f64::from(coord.y) * extent),
+		}
+	}
+
+	/// Creates a projection of the whole world onto the unit square, with the origin in the north-west,
+	/// e.g. to measure distances in Web Mercator independent of a zoom level.
+	#[must_use]
+	pub fn world() -> Self {
+		TileProjection {
+			world_size: 1.0,
+			offset: (0.0, 0.0),
+		}
+	}
+
+	/// Converts tile-local pixel coordinates into longitude/latitude in degrees.
+	///
+	/// Coordinates outside the tile (e.g. in the buffer) are converted as well.
+	#[must_use]
+	pub fn to_lon_lat(&self, pixel: &Coordinates) -> Coordinates {
+		let x = (self.offset.0 + pixel.x()) / self.world_size;
+		let y = (self.offset.1 + pixel.y()) / self.world_size;
+		let lon = x * 360.0 - 180.0;
+		let lat = (PI * (1.0 - 2.0 * y)).sinh().atan().to_degrees();
+		Coordinates::new(lon, lat)
+	}
+
+	/// Converts longitude/latitude in degrees into tile-local pixel coordinates.
+	///
+	/// The result is not rounded or clipped to the tile. Latitudes beyond ±85.05° map outside the world.
+	#[must_use]
+	pub fn from_lon_lat(&self, lon_lat: &Coordinates) -> Coordinates {
+		let x = (lon_lat.x() + 180.0) / 360.0;
+		let lat = lon_lat.y().to_radians();
+		let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0;
+		Coordinates::new(x * self.world_size - self.offset.0, y * self.world_size - self.offset.1)
+	}
+
+	/// Like [`from_lon_lat`](Self::from_lon_lat), but clamps the latitude to the limits of Web Mercator,
+	/// so the poles map to the top and bottom edge of the world.
+	#[must_use]
+	pub fn from_lon_lat_clamped(&self, lon_lat: &Coordinates) -> Coordinates {
+		let lat = lon_lat.y().clamp(-MAX_LATITUDE, MAX_LATITUDE);
+		self.from_lon_lat(&Coordinates::new(lon_lat.x(), lat))
+	}
+
+	/// Converts tile-local pixel coordinates into Web Mercator meters (EPSG:3857), with y pointing north.
+	#[must_use]
+	pub fn to_mercator(&self, pixel: &Coordinates) -> Coordinates {
+		let x = (self.offset.0 + pixel.x()) / self.world_size;
+		let y = (self.offset.1 + pixel.y()) / self.world_size;
+		Coordinates::new((x - 0.5) * WORLD_METERS, (0.5 - y) * WORLD_METERS)
+	}
+
+	/// Converts Web Mercator meters (EPSG:3857) into tile-local pixel coordinates.
+	#[must_use]
+	pub fn from_mercator(&self, meters: &Coordinates) -> Coordinates {
+		let x = meters.x() / WORLD_METERS + 0.5;
+		let y = 0.5 - meters.y() / WORLD_METERS;
+		Coordinates::new(x * self.world_size - self.offset.0, y * self.world_size - self.offset.1)
+	}
+
+	/// Converts all coordinates of `geometry` from tile-local pixels into longitude/latitude.
+	pub fn geometry_to_lon_lat(&self, geometry: &mut Geometry) {
+		geometry.map_coordinates(|c| self.to_lon_lat(c));
+	}
+
+	/// Converts all coordinates of `geometry` from longitude/latitude into tile-local pixels.
+	pub fn geometry_from_lon_lat(&self, geometry: &mut Geometry) {
+		geometry.map_coordinates(|c| self.from_lon_lat(c));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	fn assert_coords(actual: &Coordinates, expected: [f64; 2]) {
+		assert!(
+			(actual.x() - expected[0]).abs() < 1e-9 && (actual.y() - expected[1]).abs() < 1e-9,
+			"{actual:?} != {expected:?}"
+		);
+	}
+
+	#[rstest]
+	#[case(0, 0, 0, [0.0, 0.0], [-180.0, 85.051_128_779_806_59])]
+	#[case(0, 0, 0, [2048.0, 2048.0], [0.0, 0.0])]
+	#[case(1, 1, 1, [0.0, 0.0], [0.0, 0.0])]
+	#[case(1, 0, 1, [4096.0, 4096.0], [0.0, -85.051_128_779_806_59])]
+	fn converts_to_lon_lat(
+		#[case] z: u8,
+		#[case] x: u32,
+		#[case] y: u32,
+		#[case] pixel: [f64; 2],
+		#[case] expected: [f64; 2],
+	) {
+		let projection = TileProjection::new(TileCoord::new(z, x, y).unwrap(), 4096);
+		assert_coords(&projection.to_lon_lat(&Coordinates::from(pixel)), expected);
+	}
+
+	#[test]
+	fn round_trips() {
+		let projection = TileProjection::new(TileCoord::new(14, 8800, 5373).unwrap(), 4096);
+		for pixel in [[0.0, 0.0], [1234.5, 3210.0], [-64.0, 4160.0]] {
+			let lon_lat = projection.to_lon_lat(&Coordinates::from(pixel));
+			assert!(lon_lat.x() > 13.3 && lon_lat.x() < 13.4, "{lon_lat:?}");
+			assert!(lon_lat.y() > 52.5 && lon_lat.y() < 52.6, "{lon_lat:?}");
+			assert_coords(&projection.from_lon_lat(&lon_lat), pixel);
+		}
+	}
+
+	#[test]
+	fn projects_world_to_unit_square() {
+		let world = TileProjection::world();
+		assert_coords(&world.from_lon_lat(&Coordinates::new(0.0, 0.0)), [0.5, 0.5]);
+		assert_coords(&world.from_lon_lat_clamped(&Coordinates::new(-180.0, 90.0)), [0.0, 0.0]);
+		assert_coords(&world.from_lon_lat_clamped(&Coordinates::new(180.0, -90.0)), [1.0, 1.0]);
+	}
+
+	#[test]
+	fn converts_mercator_meters() {
+		let half = 20_037_508.342_789_244;
+		let world = TileProjection::world();
+		assert_coords(&world.to_mercator(&Coordinates::new(0.0, 0.0)), [-half, half]);
+		assert_coords(&world.to_mercator(&Coordinates::new(0.75, 0.5)), [half / 2.0, 0.0]);
+
+		let projection = TileProjection::new(TileCoord::new(1, 1, 0).unwrap(), 256);
+		assert_coords(&projection.to_mercator(&Coordinates::new(0.0, 256.0)), [0.0, 0.0]);
+		assert_coords(&projection.from_mercator(&Coordinates::new(half, half)), [256.0, 0.0]);
+	}
+
+	#[test]
+	fn projects_geometries() {
+		let projection = TileProjection::new(TileCoord::new(1, 1, 1).unwrap(), 512);
+		let mut geometry = Geometry::new_line_string(vec![[0.0, 0.0], [256.0, 0.0]]);
+		projection.geometry_to_lon_lat(&mut geometry);
+		assert_eq!(geometry, Geometry::new_line_string(vec![[0.0, 0.0], [90.0, 0.0]]));
+		projection.geometry_from_lon_lat(&mut geometry);
+		assert_eq!(geometry, Geometry::new_line_string(vec![[0.0, 0.0], [256.0, 0.0]]));
+	}
+}
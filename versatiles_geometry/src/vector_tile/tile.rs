@@ -9,6 +9,7 @@
 
 use super::layer::VectorTileLayer;
 use anyhow::{Result, bail};
+use byteorder::LE;
 use versatiles_core::{
 	Blob,
 	io::{ValueReader, ValueReaderSlice, ValueWriter, ValueWriterBlob},
@@ -62,6 +63,34 @@ impl VectorTile {
 		Ok(tile)
 	}
 
+	/// Returns the name and encoded size in bytes of every layer of a protobuf `Blob`, without decoding the features.
+	///
+	/// The sizes include the field key and length prefix of every layer, so they add up to the size of the blob.
+	/// This shows which layers contribute most to the size of a tile.
+	#[context("measuring layer sizes of VectorTile ({} bytes)", blob.len())]
+	pub fn layer_sizes(blob: &Blob) -> Result<Vec<(String, u64)>> {
+		let mut reader = ValueReaderSlice::new_le(blob.as_slice());
+
+		let mut sizes = Vec::new();
+		while reader.has_remaining() {
+			let start = reader.position();
+			match reader.read_pbf_key().context("Failed to read PBF key")? {
+				(3, 2) => {
+					let name = read_layer_name(
+						reader
+							.get_pbf_sub_reader()
+							.context("Failed to get PBF sub-reader")?
+							.as_mut(),
+					)?;
+					sizes.push((name, reader.position() - start));
+				}
+				(f, w) => bail!("Unexpected combination of field number ({f}) and wire type ({w})"),
+			}
+		}
+
+		Ok(sizes)
+	}
+
 	/// Serializes this tile and all of its layers to a protobuf `Blob` (MVT wire format).
 	#[context("serializing VectorTile to Blob")]
 	pub fn to_blob(&self) -> Result<Blob> {
@@ -89,6 +118,25 @@ impl VectorTile {
 	}
 }
 
+/// Reads only the name of an embedded MVT `layer` message and skips all other fields.
+#[context("reading name of VectorTileLayer")]
+fn read_layer_name(reader: &mut dyn ValueReader<'_, LE>) -> Result<String> {
+	let mut name = None;
+	while reader.has_remaining() {
+		match reader.read_pbf_key().context("Failed to read PBF key")? {
+			(1, 2) => name = Some(reader.read_pbf_string().context("Failed to read layer name")?),
+			(_, 0) => {
+				reader.read_varint()?;
+			}
+			(_, 2) => {
+				reader.get_pbf_sub_reader()?;
+			}
+			(f, w) => bail!("Unexpected combination of field number ({f}) and wire type ({w})"),
+		}
+	}
+	name.context("Layer name is required")
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -116,4 +164,18 @@ mod tests {
 		assert_eq!(tile1, tile2);
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn layer_sizes() -> Result<()> {
+		let blob = get_pbf().await?;
+		let tile = VectorTile::from_blob(&blob)?;
+		let sizes = VectorTile::layer_sizes(&blob)?;
+
+		let names: Vec<&str> = sizes.iter().map(|(name, _)| name.as_str()).collect();
+		let expected: Vec<&str> = tile.layers.iter().map(|layer| layer.name.as_str()).collect();
+		assert_eq!(names, expected);
+		assert_eq!(sizes.iter().map(|(_, size)| size).sum::<u64>(), blob.len());
+		assert!(sizes.iter().all(|(_, size)| *size > 0));
+		Ok(())
+	}
 }
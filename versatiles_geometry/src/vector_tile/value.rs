@@ -23,12 +23,7 @@ impl<'a> GeoValuePBF<'a> for GeoValue {
 		while reader.has_remaining() {
 			value = Some(match reader.read_pbf_key().context("Failed to read PBF key")? {
 				// https://protobuf.dev/programming-guides/encoding/#structure
-				(1, 2) => {
-					let len = reader
-						.read_varint()
-						.context("Failed to read varint for string length")?;
-					String(reader.read_string(len).context("Failed to read string value")?)
-				}
+				(1, 2) => String(reader.read_pbf_string().context("Failed to read string value")?),
 				(2, 5) => Float(reader.read_f32().context("Failed to read f32 value")?),
 				(3, 1) => Double(reader.read_f64().context("Failed to read f64 value")?),
 				(4, 0) => Int(reader.read_varint().context("Failed to read varint for int value")? as i64),
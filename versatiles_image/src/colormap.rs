@@ -0,0 +1,264 @@
+//! Color ramps that map scalar values to RGBA colors.
+//!
+//! A [`ColorMap`] is a list of color stops at positions between `0.0` and `1.0`.
+//! Values between two stops are interpolated linearly, values outside of `0..=1` are clamped.
+//! It is used to visualize single channel data, like elevation models or weather data.
+//!
+//! Color maps are either built in (see [`COLOR_MAP_NAMES`]) or defined as a JSON array of
+//! `[position, color]` stops, where a color is a hex string (`"#rrggbb"` or `"#rrggbbaa"`)
+//! or an array of 3 or 4 numbers.
+//!
+//! ```rust
+//! use versatiles_image::ColorMap;
+//!
+//! let ramp = ColorMap::parse("grayscale").unwrap();
+//! assert_eq!(ramp.get(0.5), [128, 128, 128, 255]);
+//!
+//! let ramp = ColorMap::parse(r##"[[0, "#0000ff"], [1, [255, 0, 0]]]"##).unwrap();
+//! assert_eq!(ramp.get(2.0), [255, 0, 0, 255]);
+//! ```
+
+use anyhow::{Context, Result, bail, ensure};
+use versatiles_core::json::JsonValue;
+
+/// A color ramp defined by color stops.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorMap {
+	/// Position (`0..=1`) and RGBA color of every stop, sorted by position.
+	stops: Vec<(f64, [u8; 4])>,
+}
+
+/// Names of the built-in color ramps.
+pub const COLOR_MAP_NAMES: [&str; 5] = ["grayscale", "temperature", "turbo", "viridis", "wind"];
+
+impl ColorMap {
+	/// Creates a color ramp from stops. The stops are sorted by position.
+	///
+	/// # Errors
+	/// Returns an error if there are less than two stops or if a position is not finite.
+	pub fn new(mut stops: Vec<(f64, [u8; 4])>) -> Result<ColorMap> {
+		ensure!(stops.len() >= 2, "a color map needs at least two stops");
+		ensure!(
+			stops.iter().all(|(position, _)| position.is_finite()),
+			"color map stops must have finite positions"
+		);
+		stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+		Ok(ColorMap { stops })
+	}
+
+	/// Creates a color ramp from opaque RGB colors, spaced evenly between `0` and `1`.
+	fn from_rgb(colors: &[[u8; 3]]) -> ColorMap {
+		let step = 1.0 / (colors.len() - 1) as f64;
+		ColorMap {
+			stops: colors
+				.iter()
+				.enumerate()
+				.map(|(i, [r, g, b])| (i as f64 * step, [*r, *g, *b, 255]))
+				.collect(),
+		}
+	}
+
+	/// Returns a built-in color ramp, see [`COLOR_MAP_NAMES`].
+	///
+	/// # Errors
+	/// Returns an error if the name is unknown.
+	pub fn from_name(name: &str) -> Result<ColorMap> {
+		Ok(match name {
+			"grayscale" => ColorMap::from_rgb(&[[0, 0, 0], [255, 255, 255]]),
+			"temperature" => ColorMap::from_rgb(&[
+				[49, 54, 149],
+				[69, 117, 180],
+				[171, 217, 233],
+				[255, 255, 191],
+				[253, 174, 97],
+				[215, 48, 39],
+				[165, 0, 38],
+			]),
+			"turbo" => ColorMap::from_rgb(&[
+				[48, 18, 59],
+				[65, 69, 171],
+				[70, 117, 237],
+				[57, 162, 252],
+				[27, 207, 212],
+				[36, 236, 166],
+				[97, 252, 108],
+				[164, 252, 59],
+				[209, 232, 52],
+				[243, 198, 58],
+				[254, 155, 45],
+				[243, 99, 21],
+				[217, 56, 6],
+				[177, 25, 1],
+				[122, 4, 3],
+			]),
+			"viridis" => ColorMap::from_rgb(&[
+				[68, 1, 84],
+				[71, 45, 123],
+				[59, 82, 139],
+				[44, 114, 142],
+				[33, 145, 140],
+				[40, 174, 128],
+				[94, 201, 98],
+				[173, 220, 48],
+				[253, 231, 37],
+			]),
+			"wind" => ColorMap::from_rgb(&[
+				[255, 255, 255],
+				[161, 218, 180],
+				[65, 182, 196],
+				[44, 127, 184],
+				[37, 52, 148],
+				[129, 15, 124],
+			]),
+			_ => bail!("unknown color map '{name}', expected one of {COLOR_MAP_NAMES:?}"),
+		})
+	}
+
+	/// Parses a color map from a JSON array of `[position, color]` stops.
+	///
+	/// # Errors
+	/// Returns an error if the JSON is invalid or if a stop or color can't be parsed.
+	pub fn from_json(json: &str) -> Result<ColorMap> {
+		let stops = JsonValue::parse_str(json)?
+			.as_array()?
+			.as_vec()
+			.iter()
+			.enumerate()
+			.map(|(index, stop)| {
+				let parse = || -> Result<(f64, [u8; 4])> {
+					match stop.as_array()?.as_vec().as_slice() {
+						[position, color] => Ok((position.as_number()?, parse_color(color)?)),
+						_ => bail!("a stop must be an array of position and color"),
+					}
+				};
+				parse().with_context(|| format!("invalid stop {index}: {}", stop.stringify()))
+			})
+			.collect::<Result<Vec<_>>>()?;
+		ColorMap::new(stops)
+	}
+
+	/// Parses a color map from a name (see [`COLOR_MAP_NAMES`]) or from JSON stops (see [`ColorMap::from_json`]).
+	///
+	/// # Errors
+	/// Returns an error if the name is unknown or the JSON is invalid.
+	pub fn parse(definition: &str) -> Result<ColorMap> {
+		let definition = definition.trim();
+		if definition.starts_with('[') {
+			ColorMap::from_json(definition)
+		} else {
+			ColorMap::from_name(definition)
+		}
+	}
+
+	/// Returns the color at `value`, interpolating linearly between the neighbouring stops.
+	/// Values outside of the stops are clamped to the first or last color.
+	#[must_use]
+	pub fn get(&self, value: f64) -> [u8; 4] {
+		let first = &self.stops[0];
+		if value.is_nan() || value <= first.0 {
+			return first.1;
+		}
+		for pair in self.stops.windows(2) {
+			let ((p0, c0), (p1, c1)) = (pair[0], pair[1]);
+			if value <= p1 {
+				let t = if p1 > p0 { (value - p0) / (p1 - p0) } else { 1.0 };
+				return std::array::from_fn(|i| {
+					(f64::from(c0[i]) + (f64::from(c1[i]) - f64::from(c0[i])) * t).round() as u8
+				});
+			}
+		}
+		self.stops[self.stops.len() - 1].1
+	}
+}
+
+/// Parses `"#rrggbb"`, `"#rrggbbaa"`, `[r, g, b]` or `[r, g, b, a]`.
+fn parse_color(value: &JsonValue) -> Result<[u8; 4]> {
+	let channels = match value {
+		JsonValue::String(text) => {
+			let hex = text.strip_prefix('#').unwrap_or(text);
+			ensure!(
+				(hex.len() == 6 || hex.len() == 8) && hex.is_ascii(),
+				"color '{text}' must have the format #rrggbb or #rrggbbaa"
+			);
+			(0..hex.len())
+				.step_by(2)
+				.map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map(f64::from))
+				.collect::<Result<Vec<_>, _>>()
+				.with_context(|| format!("invalid hex color '{text}'"))?
+		}
+		JsonValue::Array(array) => array.as_number_vec()?,
+		_ => bail!("a color must be a hex string or an array of numbers"),
+	};
+	ensure!(
+		channels.iter().all(|c| (0.0..=255.0).contains(c)),
+		"color channels must be between 0 and 255"
+	);
+	Ok(match channels.as_slice() {
+		[r, g, b] => [*r as u8, *g as u8, *b as u8, 255],
+		[r, g, b, a] => [*r as u8, *g as u8, *b as u8, *a as u8],
+		_ => bail!("a color must have 3 or 4 channels"),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn interpolation() -> Result<()> {
+		let ramp = ColorMap::new(vec![
+			(1.0, [255, 0, 0, 255]),
+			(0.0, [0, 0, 0, 0]),
+			(0.5, [0, 200, 0, 255]),
+		])?;
+		assert_eq!(ramp.get(-1.0), [0, 0, 0, 0]);
+		assert_eq!(ramp.get(0.25), [0, 100, 0, 128]);
+		assert_eq!(ramp.get(0.5), [0, 200, 0, 255]);
+		assert_eq!(ramp.get(0.75), [128, 100, 0, 255]);
+		assert_eq!(ramp.get(7.0), [255, 0, 0, 255]);
+		assert_eq!(ramp.get(f64::NAN), [0, 0, 0, 0]);
+		Ok(())
+	}
+
+	#[test]
+	fn named() -> Result<()> {
+		for name in COLOR_MAP_NAMES {
+			let ramp = ColorMap::from_name(name)?;
+			assert_eq!(ramp.get(0.0)[3], 255);
+			assert_eq!(ramp.get(1.0)[3], 255);
+		}
+		assert_eq!(ColorMap::from_name("grayscale")?.get(0.2), [51, 51, 51, 255]);
+		assert!(ColorMap::from_name("rainbow").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn json() -> Result<()> {
+		let ramp = ColorMap::parse(r##" [[1, "#FF000080"], [0, [0, 0, 255]], [0.5, "00ff00"]] "##)?;
+		assert_eq!(ramp.get(0.0), [0, 0, 255, 255]);
+		assert_eq!(ramp.get(0.5), [0, 255, 0, 255]);
+		assert_eq!(ramp.get(1.0), [255, 0, 0, 128]);
+		assert_eq!(ColorMap::parse("viridis")?, ColorMap::from_name("viridis")?);
+
+		for json in [
+			"[]",
+			"[[0, \"#000000\"]]",
+			"[[0, \"#000\"], [1, \"#ffffff\"]]",
+			"[[0, \"#gg0000\"], [1, \"#ffffff\"]]",
+			"[[0, [0, 0]], [1, [0, 0, 0]]]",
+			"[[0, [0, 0, 256]], [1, [0, 0, 0]]]",
+			"[[\"a\", [0, 0, 0]], [1, [0, 0, 0]]]",
+			"[0, 1]",
+			"{}",
+		] {
+			assert!(ColorMap::from_json(json).is_err(), "{json}");
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn invalid_stops() {
+		assert!(ColorMap::new(vec![(0.0, [0; 4])]).is_err());
+		assert!(ColorMap::new(vec![(0.0, [0; 4]), (f64::NAN, [0; 4])]).is_err());
+	}
+}
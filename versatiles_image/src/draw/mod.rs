@@ -0,0 +1,275 @@
+//! Antialiased drawing primitives for annotating RGBA images.
+//!
+//! Used to render debug tiles, tile borders, watermarks and labels. All functions draw in place
+//! on an [`RgbaImage`], clip at the image borders and take coordinates in pixels, where `(0, 0)`
+//! is the upper left corner of the upper left pixel.
+//!
+//! Edges are antialiased by the fraction of each pixel that is covered. The color is blended
+//! into the image by [`blend_pixel`]; the alpha channel of the color works as opacity.
+//!
+//! ```rust
+//! use versatiles_image::{Rgba, draw};
+//! use image::RgbaImage;
+//!
+//! let mut image = RgbaImage::from_pixel(256, 256, Rgba([255, 255, 255, 255]));
+//! draw::draw_rect(&mut image, 0.0, 0.0, 256.0, 256.0, 2.0, Rgba([255, 0, 0, 255]));
+//! draw::fill_circle(&mut image, (128.0, 128.0), 20.0, Rgba([0, 0, 255, 128]));
+//! draw::draw_text(&mut image, 10, 10, 24.0, Rgba([0, 0, 0, 255]), "z: 3");
+//! assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+//! ```
+
+use ab_glyph::{Font, FontArc, GlyphId, PxScale, ScaleFont, point};
+use image::{Rgba, RgbaImage};
+use lazy_static::lazy_static;
+
+lazy_static! {
+	/// The embedded font that is used by [`draw_text`].
+	pub static ref FONT: FontArc = FontArc::try_from_slice(include_bytes!("./trim.ttf")).unwrap();
+}
+
+/// Blends `color` into the pixel at `(x, y)`, weighted by `coverage` (`0..=1`) and the alpha of `color`.
+///
+/// Pixels outside of the image are ignored.
+pub fn blend_pixel(image: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>, coverage: f32) {
+	let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) else {
+		return;
+	};
+	if x >= image.width() || y >= image.height() {
+		return;
+	}
+	let weight = coverage.clamp(0.0, 1.0) * (f32::from(color[3]) / 255.0);
+	if weight <= 0.0 {
+		return;
+	}
+	let pixel = image.get_pixel_mut(x, y);
+	let mix = |a: u8, b: u8| (f32::from(a) * (1.0 - weight) + f32::from(b) * weight).clamp(0.0, 255.0) as u8;
+	pixel.0 = [
+		mix(pixel[0], color[0]),
+		mix(pixel[1], color[1]),
+		mix(pixel[2], color[2]),
+		mix(pixel[3], 255),
+	];
+}
+
+/// Calls `coverage` for every pixel center in the given area and blends `color` with the returned coverage.
+fn fill_by_coverage(image: &mut RgbaImage, area: [f32; 4], color: Rgba<u8>, coverage: impl Fn(f32, f32) -> f32) {
+	let [x_min, y_min, x_max, y_max] = area;
+	let x_range = (x_min.floor().max(0.0) as i32)..(x_max.ceil().min(image.width() as f32) as i32);
+	let y_range = (y_min.floor().max(0.0) as i32)..(y_max.ceil().min(image.height() as f32) as i32);
+	for y in y_range {
+		for x in x_range.clone() {
+			let value = coverage(x as f32 + 0.5, y as f32 + 0.5);
+			if value > 0.0 {
+				blend_pixel(image, x, y, color, value);
+			}
+		}
+	}
+}
+
+/// Draws a line from `start` to `end` with round caps.
+pub fn draw_line(image: &mut RgbaImage, start: (f32, f32), end: (f32, f32), width: f32, color: Rgba<u8>) {
+	let radius = width / 2.0;
+	let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+	let length2 = dx * dx + dy * dy;
+	let area = [
+		start.0.min(end.0) - radius - 1.0,
+		start.1.min(end.1) - radius - 1.0,
+		start.0.max(end.0) + radius + 1.0,
+		start.1.max(end.1) + radius + 1.0,
+	];
+	fill_by_coverage(image, area, color, |x, y| {
+		// distance of the pixel center to the segment
+		let t = if length2 > 0.0 {
+			(((x - start.0) * dx + (y - start.1) * dy) / length2).clamp(0.0, 1.0)
+		} else {
+			0.0
+		};
+		let distance = (x - start.0 - t * dx).hypot(y - start.1 - t * dy);
+		(radius + 0.5 - distance).clamp(0.0, 1.0)
+	});
+}
+
+/// Fills the rectangle with the upper left corner `(x, y)`.
+pub fn fill_rect(image: &mut RgbaImage, x: f32, y: f32, width: f32, height: f32, color: Rgba<u8>) {
+	let area = [x, y, x + width, y + height];
+	fill_by_coverage(image, area, color, |px, py| {
+		// overlap of the pixel with the rectangle in both directions
+		let overlap_x = ((px + 0.5).min(area[2]) - (px - 0.5).max(area[0])).clamp(0.0, 1.0);
+		let overlap_y = ((py + 0.5).min(area[3]) - (py - 0.5).max(area[1])).clamp(0.0, 1.0);
+		overlap_x * overlap_y
+	});
+}
+
+/// Draws the outline of a rectangle. The line lies inside of the rectangle.
+pub fn draw_rect(image: &mut RgbaImage, x: f32, y: f32, width: f32, height: f32, line_width: f32, color: Rgba<u8>) {
+	let line_width = line_width.min(width / 2.0).min(height / 2.0);
+	let inner_height = height - 2.0 * line_width;
+	fill_rect(image, x, y, width, line_width, color);
+	fill_rect(image, x, y + height - line_width, width, line_width, color);
+	fill_rect(image, x, y + line_width, line_width, inner_height, color);
+	fill_rect(
+		image,
+		x + width - line_width,
+		y + line_width,
+		line_width,
+		inner_height,
+		color,
+	);
+}
+
+/// Fills a circle.
+pub fn fill_circle(image: &mut RgbaImage, center: (f32, f32), radius: f32, color: Rgba<u8>) {
+	let area = [
+		center.0 - radius - 1.0,
+		center.1 - radius - 1.0,
+		center.0 + radius + 1.0,
+		center.1 + radius + 1.0,
+	];
+	fill_by_coverage(image, area, color, |x, y| {
+		(radius + 0.5 - (x - center.0).hypot(y - center.1)).clamp(0.0, 1.0)
+	});
+}
+
+/// Draws the outline of a circle. The line is centered on the radius.
+pub fn draw_circle(image: &mut RgbaImage, center: (f32, f32), radius: f32, line_width: f32, color: Rgba<u8>) {
+	let outer = radius + line_width / 2.0;
+	let area = [
+		center.0 - outer - 1.0,
+		center.1 - outer - 1.0,
+		center.0 + outer + 1.0,
+		center.1 + outer + 1.0,
+	];
+	fill_by_coverage(image, area, color, |x, y| {
+		let distance = ((x - center.0).hypot(y - center.1) - radius).abs();
+		(line_width / 2.0 + 0.5 - distance).clamp(0.0, 1.0)
+	});
+}
+
+/// Lays out a single line of text in [`FONT`] and calls `callback` for every glyph pixel
+/// with its offset to the upper left corner and its coverage. Returns the size of the text.
+fn layout_text(size: f32, text: &str, mut callback: impl FnMut(i32, i32, f32)) -> (u32, u32) {
+	let scale = PxScale::from(size);
+	let font = FONT.as_scaled(scale);
+	let (mut width, mut height) = (0f32, 0f32);
+	let mut last: Option<GlyphId> = None;
+
+	for c in text.chars() {
+		let glyph_id = font.glyph_id(c);
+		let glyph = glyph_id.with_scale_and_position(scale, point(width, font.ascent()));
+		width += font.h_advance(glyph_id);
+		if let Some(outlined) = font.outline_glyph(glyph) {
+			if let Some(last) = last {
+				width += font.kern(glyph_id, last);
+			}
+			last = Some(glyph_id);
+			let bounds = outlined.px_bounds();
+			height = height.max(bounds.height());
+			let (left, top) = (bounds.min.x.round() as i32, bounds.min.y.round() as i32);
+			outlined.draw(|x, y, coverage| callback(left + x as i32, top + y as i32, coverage));
+		}
+	}
+
+	(width as u32, height as u32)
+}
+
+/// Returns the width and height in pixels of a single line of `text` in [`FONT`] with the font size `size`.
+#[must_use]
+pub fn text_size(size: f32, text: &str) -> (u32, u32) {
+	layout_text(size, text, |_, _, _| {})
+}
+
+/// Draws a single line of `text` in [`FONT`] with the font size `size` and the upper left corner at `(x, y)`.
+pub fn draw_text(image: &mut RgbaImage, x: i32, y: i32, size: f32, color: Rgba<u8>, text: &str) {
+	layout_text(size, text, |dx, dy, coverage| {
+		blend_pixel(image, x + dx, y + dy, color, coverage);
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+	const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+	fn canvas() -> RgbaImage {
+		RgbaImage::from_pixel(32, 32, WHITE)
+	}
+
+	#[test]
+	fn blend_pixel_weights_by_coverage_and_alpha() {
+		let mut image = canvas();
+		blend_pixel(&mut image, 0, 0, RED, 1.0);
+		blend_pixel(&mut image, 1, 0, RED, 0.5);
+		blend_pixel(&mut image, 2, 0, Rgba([255, 0, 0, 51]), 1.0);
+		blend_pixel(&mut image, -1, 40, RED, 1.0);
+		assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+		assert_eq!(image.get_pixel(1, 0).0, [255, 127, 127, 255]);
+		assert_eq!(image.get_pixel(2, 0).0, [255, 204, 204, 255]);
+
+		let mut image = RgbaImage::new(1, 1);
+		blend_pixel(&mut image, 0, 0, RED, 0.5);
+		assert_eq!(image.get_pixel(0, 0).0, [127, 0, 0, 127]);
+	}
+
+	#[test]
+	fn rect_is_antialiased_at_fractional_edges() {
+		let mut image = canvas();
+		fill_rect(&mut image, 4.0, 4.0, 2.5, 2.0, RED);
+		assert_eq!(image.get_pixel(4, 4).0, [255, 0, 0, 255]);
+		assert_eq!(image.get_pixel(6, 4).0, [255, 127, 127, 255]);
+		assert_eq!(image.get_pixel(7, 4), &WHITE);
+		assert_eq!(image.get_pixel(4, 6), &WHITE);
+
+		let mut image = canvas();
+		draw_rect(&mut image, 0.0, 0.0, 32.0, 32.0, 1.0, RED);
+		assert_eq!(image.get_pixel(0, 10), &RED);
+		assert_eq!(image.get_pixel(31, 31), &RED);
+		assert_eq!(image.get_pixel(1, 1), &WHITE);
+	}
+
+	#[test]
+	fn line_covers_its_width() {
+		let mut image = canvas();
+		draw_line(&mut image, (2.0, 10.5), (30.0, 10.5), 1.0, RED);
+		assert_eq!(image.get_pixel(16, 10), &RED);
+		assert_eq!(image.get_pixel(16, 8), &WHITE);
+		assert_eq!(image.get_pixel(16, 12), &WHITE);
+		assert_eq!(image.get_pixel(31, 10), &WHITE);
+
+		let mut image = canvas();
+		draw_line(&mut image, (0.0, 0.0), (32.0, 32.0), 2.0, RED);
+		assert_eq!(image.get_pixel(16, 16), &RED);
+		assert_eq!(image.get_pixel(28, 4), &WHITE);
+	}
+
+	#[test]
+	fn circles() {
+		let mut image = canvas();
+		fill_circle(&mut image, (16.0, 16.0), 8.0, RED);
+		assert_eq!(image.get_pixel(16, 16), &RED);
+		assert_eq!(image.get_pixel(16, 9), &RED);
+		assert_eq!(image.get_pixel(2, 2), &WHITE);
+		let edge = image.get_pixel(21, 21).0; // distance ≈ 7.8
+		assert!(edge[1] > 0 && edge[1] < 255, "edge should be antialiased: {edge:?}");
+
+		let mut image = canvas();
+		draw_circle(&mut image, (16.0, 16.0), 10.0, 2.0, RED);
+		assert_eq!(image.get_pixel(16, 16), &WHITE);
+		assert_eq!(image.get_pixel(25, 15), &RED);
+		assert_eq!(image.get_pixel(15, 6), &RED);
+	}
+
+	#[test]
+	fn text() {
+		let (width, height) = text_size(20.0, "z: 12");
+		assert!(width > 30 && width < 80, "width {width}");
+		assert!(height > 8 && height <= 20, "height {height}");
+		assert_eq!(text_size(20.0, ""), (0, 0));
+
+		let mut image = canvas();
+		draw_text(&mut image, 2, 2, 20.0, RED, "12");
+		assert!(image.pixels().any(|p| p == &RED));
+		assert_eq!(image.get_pixel(31, 31), &WHITE);
+	}
+}
@@ -0,0 +1,266 @@
+//! Georeferenced export of single images: GeoTIFF and world files.
+//!
+//! All bounds are `[x_min, y_min, x_max, y_max]` in Web Mercator meters (EPSG:3857),
+//! e.g. from [`TileCoord::to_mercator_bbox`](versatiles_core::TileCoord::to_mercator_bbox).
+//!
+//! Highlights:
+//! - [`encode`] writes a minimal baseline TIFF (uncompressed, one strip, little-endian) with the
+//!   GeoTIFF tags `ModelPixelScale`, `ModelTiepoint` and `GeoKeyDirectory`.
+//! - Supports only **8‑bit** images with **1–4 channels** (L8, LA8, RGB8, RGBA8).
+//! - [`world_file`] and [`WEB_MERCATOR_PRJ`] georeference any other image file, e.g. a PNG
+//!   (`tile.png` + `tile.pgw` + `tile.prj`).
+
+use crate::traits::DynamicImageTraitInfo;
+use anyhow::{Result, bail, ensure};
+use image::DynamicImage;
+use versatiles_core::Blob;
+use versatiles_derive::context;
+
+/// Projection of the exported images in ESRI WKT, as expected in a `.prj` file next to a world file.
+pub const WEB_MERCATOR_PRJ: &str = r#"PROJCS["WGS_1984_Web_Mercator_Auxiliary_Sphere",GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]],PROJECTION["Mercator_Auxiliary_Sphere"],PARAMETER["False_Easting",0.0],PARAMETER["False_Northing",0.0],PARAMETER["Central_Meridian",0.0],PARAMETER["Standard_Parallel_1",0.0],PARAMETER["Auxiliary_Sphere_Type",0.0],UNIT["Meter",1.0]]"#;
+
+const TYPE_SHORT: u16 = 3;
+const TYPE_LONG: u16 = 4;
+const TYPE_DOUBLE: u16 = 12;
+
+const TAG_STRIP_OFFSETS: u16 = 273;
+
+/// A TIFF directory entry with its little-endian encoded values.
+struct Entry {
+	tag: u16,
+	field_type: u16,
+	count: u32,
+	data: Vec<u8>,
+}
+
+impl Entry {
+	fn shorts(tag: u16, values: &[u16]) -> Self {
+		let data = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+		Entry {
+			tag,
+			field_type: TYPE_SHORT,
+			count: values.len() as u32,
+			data,
+		}
+	}
+
+	fn longs(tag: u16, values: &[u32]) -> Self {
+		let data = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+		Entry {
+			tag,
+			field_type: TYPE_LONG,
+			count: values.len() as u32,
+			data,
+		}
+	}
+
+	fn doubles(tag: u16, values: &[f64]) -> Self {
+		let data = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+		Entry {
+			tag,
+			field_type: TYPE_DOUBLE,
+			count: values.len() as u32,
+			data,
+		}
+	}
+}
+
+#[context("encoding {}x{} {:?} as GeoTIFF", image.width(), image.height(), image.color())]
+/// Encode a `DynamicImage` as a GeoTIFF [`Blob`] covering `bounds` (EPSG:3857 meters).
+///
+/// * Errors if the image is not 8‑bit, the channel count is not in `1..=4` or the bounds are empty.
+pub fn encode(image: &DynamicImage, bounds: [f64; 4]) -> Result<Blob> {
+	if image.bits_per_value() != 8 {
+		bail!("GeoTIFF export only supports 8-bit images");
+	}
+	let channels = u16::from(image.channel_count());
+	if !(1..=4).contains(&channels) {
+		bail!("GeoTIFF export only supports Grey, GreyA, RGB or RGBA");
+	}
+	let [x_min, y_min, x_max, y_max] = bounds;
+	ensure!(x_min < x_max && y_min < y_max, "bounds {bounds:?} must not be empty");
+
+	let (width, height) = (image.width(), image.height());
+	let pixels = image.as_bytes();
+
+	let mut entries = vec![
+		Entry::longs(256, &[width]),                             // ImageWidth
+		Entry::longs(257, &[height]),                            // ImageLength
+		Entry::shorts(258, &vec![8; usize::from(channels)]),     // BitsPerSample
+		Entry::shorts(259, &[1]),                                // Compression: none
+		Entry::shorts(262, &[if channels < 3 { 1 } else { 2 }]), // Photometric: BlackIsZero or RGB
+		Entry::longs(TAG_STRIP_OFFSETS, &[0]),                   // set below
+		Entry::shorts(277, &[channels]),                         // SamplesPerPixel
+		Entry::longs(278, &[height]),                            // RowsPerStrip
+		Entry::longs(279, &[u32::try_from(pixels.len())?]),      // StripByteCounts
+		Entry::shorts(284, &[1]),                                // PlanarConfiguration: chunky
+	];
+	if image.has_alpha() {
+		entries.push(Entry::shorts(338, &[2])); // ExtraSamples: unassociated alpha
+	}
+	entries.push(Entry::doubles(
+		33550, // ModelPixelScale
+		&[
+			(x_max - x_min) / f64::from(width),
+			(y_max - y_min) / f64::from(height),
+			0.0,
+		],
+	));
+	entries.push(Entry::doubles(33922, &[0.0, 0.0, 0.0, x_min, y_max, 0.0])); // ModelTiepoint
+	entries.push(Entry::shorts(
+		34735, // GeoKeyDirectory
+		&[
+			1, 1, 0, 3, // version 1.1.0, 3 keys
+			1024, 0, 1, 1, // GTModelType: projected
+			1025, 0, 1, 1, // GTRasterType: pixel is area
+			3072, 0, 1, 3857, // ProjectedCSType: EPSG:3857
+		],
+	));
+
+	// layout: header, directory, values that don't fit into an entry, pixels
+	let directory_length = 2 + 12 * entries.len() + 4;
+	let values_length: usize = entries
+		.iter()
+		.filter(|entry| entry.data.len() > 4)
+		.map(|entry| entry.data.len().next_multiple_of(2))
+		.sum();
+	let pixel_offset = 8 + directory_length + values_length;
+	ensure!(
+		pixel_offset + pixels.len() <= u32::MAX as usize,
+		"image is too large for a TIFF file"
+	);
+	if let Some(entry) = entries.iter_mut().find(|entry| entry.tag == TAG_STRIP_OFFSETS) {
+		*entry = Entry::longs(TAG_STRIP_OFFSETS, &[pixel_offset as u32]);
+	}
+
+	let mut buffer = Vec::with_capacity(pixel_offset + pixels.len());
+	buffer.extend_from_slice(b"II*\0");
+	buffer.extend_from_slice(&8u32.to_le_bytes());
+	buffer.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+
+	let mut values = Vec::with_capacity(values_length);
+	for entry in &entries {
+		buffer.extend_from_slice(&entry.tag.to_le_bytes());
+		buffer.extend_from_slice(&entry.field_type.to_le_bytes());
+		buffer.extend_from_slice(&entry.count.to_le_bytes());
+		if entry.data.len() <= 4 {
+			let mut data = entry.data.clone();
+			data.resize(4, 0);
+			buffer.extend_from_slice(&data);
+		} else {
+			let offset = 8 + directory_length + values.len();
+			buffer.extend_from_slice(&(offset as u32).to_le_bytes());
+			values.extend_from_slice(&entry.data);
+			if values.len() % 2 == 1 {
+				values.push(0);
+			}
+		}
+	}
+	buffer.extend_from_slice(&0u32.to_le_bytes()); // no further directory
+	buffer.extend_from_slice(&values);
+	buffer.extend_from_slice(pixels);
+
+	Ok(Blob::from(buffer))
+}
+
+/// Returns the content of a world file (e.g. `.pgw` for PNG) for a `width`×`height` image covering `bounds`.
+///
+/// The six lines are: pixel width, two rotation terms, negative pixel height
+/// and the coordinates of the center of the upper left pixel.
+#[must_use]
+pub fn world_file(width: u32, height: u32, bounds: [f64; 4]) -> String {
+	let [x_min, y_min, x_max, y_max] = bounds;
+	let pixel_width = (x_max - x_min) / f64::from(width);
+	let pixel_height = (y_max - y_min) / f64::from(height);
+	format!(
+		"{pixel_width}\n0\n0\n{}\n{}\n{}\n",
+		-pixel_height,
+		x_min + pixel_width / 2.0,
+		y_max - pixel_height / 2.0
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::traits::DynamicImageTraitTest;
+	use std::collections::HashMap;
+
+	/// Reads the first directory of a little-endian TIFF: tag → (type, count, value or offset).
+	fn read_directory(data: &[u8]) -> HashMap<u16, (u16, u32, u32)> {
+		let u16_at = |pos: usize| u16::from_le_bytes([data[pos], data[pos + 1]]);
+		let u32_at = |pos: usize| u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap());
+		assert_eq!(&data[0..4], b"II*\0");
+		let start = u32_at(4) as usize;
+		(0..usize::from(u16_at(start)))
+			.map(|i| {
+				let pos = start + 2 + i * 12;
+				(u16_at(pos), (u16_at(pos + 2), u32_at(pos + 4), u32_at(pos + 8)))
+			})
+			.collect()
+	}
+
+	fn read_doubles(data: &[u8], offset: u32, count: u32) -> Vec<f64> {
+		(0..count as usize)
+			.map(|i| {
+				let pos = offset as usize + i * 8;
+				f64::from_le_bytes(data[pos..pos + 8].try_into().unwrap())
+			})
+			.collect()
+	}
+
+	#[test]
+	fn encode_rgba() -> Result<()> {
+		let image = DynamicImage::new_test_rgba();
+		let bounds = [1000.0, 2000.0, 1256.0, 2512.0];
+		let blob = encode(&image, bounds)?;
+		let data = blob.as_slice();
+		let directory = read_directory(data);
+
+		assert_eq!(directory[&256], (TYPE_LONG, 1, image.width()));
+		assert_eq!(directory[&277], (TYPE_SHORT, 1, 4));
+		assert_eq!(directory[&338], (TYPE_SHORT, 1, 2));
+
+		let (_, _, offset) = directory[&TAG_STRIP_OFFSETS];
+		let (_, _, length) = directory[&279];
+		assert_eq!(&data[offset as usize..(offset + length) as usize], image.as_bytes());
+		assert_eq!(data.len(), (offset + length) as usize);
+
+		let (_, count, offset) = directory[&33550];
+		assert_eq!(
+			read_doubles(data, offset, count),
+			[256.0 / f64::from(image.width()), 512.0 / f64::from(image.height()), 0.0]
+		);
+		let (_, count, offset) = directory[&33922];
+		assert_eq!(read_doubles(data, offset, count), [0.0, 0.0, 0.0, 1000.0, 2512.0, 0.0]);
+
+		let (field_type, count, offset) = directory[&34735];
+		assert_eq!((field_type, count), (TYPE_SHORT, 16));
+		let epsg = u16::from_le_bytes([data[offset as usize + 30], data[offset as usize + 31]]);
+		assert_eq!(epsg, 3857);
+		Ok(())
+	}
+
+	#[test]
+	fn encode_grey() -> Result<()> {
+		let image = DynamicImage::new_test_grey();
+		let directory = read_directory(encode(&image, [0.0, 0.0, 1.0, 1.0])?.as_slice());
+		assert_eq!(directory[&262], (TYPE_SHORT, 1, 1));
+		assert_eq!(directory[&258], (TYPE_SHORT, 1, 8));
+		assert!(!directory.contains_key(&338));
+		Ok(())
+	}
+
+	#[test]
+	fn encode_rejects_empty_bounds() {
+		assert!(encode(&DynamicImage::new_test_rgb(), [0.0, 0.0, 0.0, 1.0]).is_err());
+	}
+
+	#[test]
+	fn world_file_content() {
+		assert_eq!(
+			world_file(256, 256, [0.0, -512.0, 256.0, 0.0]),
+			"1\n0\n0\n-2\n0.5\n-1\n"
+		);
+	}
+}
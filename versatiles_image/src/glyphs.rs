@@ -0,0 +1,320 @@
+//! Glyph PBF generation for MapLibre/Mapbox text rendering.
+//!
+//! MapLibre loads fonts as "glyph ranges": protobuf files with 256 consecutive code points each,
+//! requested as `{fontstack}/{start}-{end}.pbf`. Every glyph is stored as a signed distance field
+//! (SDF) of the glyph rendered at 24 px, with a 3 px buffer around it, as produced by
+//! [node-fontnik](https://github.com/mapbox/node-fontnik) and
+//! [TinySDF](https://github.com/mapbox/tiny-sdf).
+//!
+//! ```rust
+//! use versatiles_image::glyphs::GlyphRenderer;
+//!
+//! let renderer = GlyphRenderer::from_vec(std::fs::read("src/draw/trim.ttf").unwrap(), "Trim").unwrap();
+//! assert_eq!(renderer.range_starts()[0], 0);
+//! let blob = renderer.render_range(0).unwrap();
+//! assert!(!blob.is_empty());
+//! ```
+
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont, point};
+use anyhow::{Result, ensure};
+use std::collections::BTreeSet;
+use versatiles_core::{
+	Blob,
+	io::pbf::{PbfWriter, WireType},
+};
+
+/// Font size in pixels at which glyphs are rendered.
+const FONT_SIZE: f32 = 24.0;
+/// Distance from the baseline to the top of a line, used to compute the glyph `top`.
+const ASCENDER: i32 = 24;
+/// Empty pixels around every glyph bitmap, so the distance field can fade out.
+const BUFFER: usize = 3;
+/// Maximum distance in pixels that is encoded in the distance field.
+const RADIUS: f64 = 8.0;
+/// Fraction of the value range used for the inside of a glyph.
+const CUTOFF: f64 = 0.25;
+/// Stands in for an infinite distance; large but finite, so the arithmetic never produces NaN.
+const INF: f64 = 1e20;
+/// Number of code points per glyph range.
+const RANGE_SIZE: u32 = 256;
+
+/// Renders the glyph ranges of a single TrueType/OpenType font.
+pub struct GlyphRenderer {
+	font: FontVec,
+	name: String,
+}
+
+impl GlyphRenderer {
+	/// Loads a TTF or OTF font from its bytes. `name` is the fontstack name stored in the PBFs.
+	pub fn from_vec(data: Vec<u8>, name: &str) -> Result<GlyphRenderer> {
+		let font = FontVec::try_from_vec(data).map_err(|e| anyhow::anyhow!("invalid font '{name}': {e}"))?;
+		Ok(GlyphRenderer {
+			font,
+			name: name.to_string(),
+		})
+	}
+
+	/// The fontstack name of this font.
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Returns the first code point of every range that contains at least one glyph, in ascending order.
+	#[must_use]
+	pub fn range_starts(&self) -> Vec<u32> {
+		let starts: BTreeSet<u32> = self
+			.font
+			.codepoint_ids()
+			.map(|(_, c)| u32::from(c) / RANGE_SIZE * RANGE_SIZE)
+			.collect();
+		starts.into_iter().collect()
+	}
+
+	/// Renders the range starting at code point `start` (a multiple of 256) into a glyph PBF.
+	pub fn render_range(&self, start: u32) -> Result<Blob> {
+		ensure!(
+			start.is_multiple_of(RANGE_SIZE),
+			"range start {start} must be a multiple of {RANGE_SIZE}"
+		);
+		let end = start + RANGE_SIZE - 1;
+
+		let mut stack = PbfWriter::new();
+		stack.write_key(1, WireType::Len);
+		stack.write_string(&self.name);
+		stack.write_key(2, WireType::Len);
+		stack.write_string(&format!("{start}-{end}"));
+		for code in start..=end {
+			let Some(c) = char::from_u32(code) else {
+				continue;
+			};
+			if self.font.glyph_id(c).0 == 0 {
+				continue;
+			}
+			stack.write_key(3, WireType::Len);
+			stack.write_bytes(self.render_glyph(c).as_slice());
+		}
+
+		let mut glyphs = PbfWriter::new();
+		glyphs.write_key(1, WireType::Len);
+		glyphs.write_bytes(stack.as_slice());
+		Ok(glyphs.into_blob())
+	}
+
+	/// Renders a single glyph message: id, SDF bitmap and metrics.
+	fn render_glyph(&self, c: char) -> PbfWriter {
+		let scale = PxScale::from(FONT_SIZE);
+		let font = self.font.as_scaled(scale);
+		let glyph_id = font.glyph_id(c);
+		let advance = font.h_advance(glyph_id).round().max(0.0) as u64;
+
+		let mut writer = PbfWriter::new();
+		writer.write_key(1, WireType::Varint);
+		writer.write_varint(u64::from(u32::from(c)));
+
+		let glyph = glyph_id.with_scale_and_position(scale, point(0.0, 0.0));
+		let (width, height, left, top) = match font.outline_glyph(glyph) {
+			Some(outlined) => {
+				let bounds = outlined.px_bounds();
+				let (width, height) = (bounds.width() as usize, bounds.height() as usize);
+				let mut coverage = vec![0.0; width * height];
+				outlined.draw(|x, y, value| {
+					if let Some(pixel) = coverage.get_mut(y as usize * width + x as usize) {
+						*pixel = value;
+					}
+				});
+				writer.write_key(2, WireType::Len);
+				writer.write_bytes(&sdf(&coverage, width, height));
+				(
+					width,
+					height,
+					bounds.min.x as i64,
+					-bounds.min.y as i64 - i64::from(ASCENDER),
+				)
+			}
+			None => (0, 0, 0, -i64::from(ASCENDER)),
+		};
+
+		writer.write_key(3, WireType::Varint);
+		writer.write_varint(width as u64);
+		writer.write_key(4, WireType::Varint);
+		writer.write_varint(height as u64);
+		writer.write_key(5, WireType::Varint);
+		writer.write_svarint(left);
+		writer.write_key(6, WireType::Varint);
+		writer.write_svarint(top);
+		writer.write_key(7, WireType::Varint);
+		writer.write_varint(advance);
+		writer
+	}
+}
+
+/// Computes the signed distance field of a glyph from its pixel `coverage` (`0..=1`).
+///
+/// The result has a [`BUFFER`] on every side. Like TinySDF, the squared distances to the
+/// outside and inside are computed with a Euclidean distance transform.
+fn sdf(coverage: &[f32], width: usize, height: usize) -> Vec<u8> {
+	let (w, h) = (width + 2 * BUFFER, height + 2 * BUFFER);
+	let mut outer = vec![INF; w * h];
+	let mut inner = vec![0.0; w * h];
+
+	for y in 0..height {
+		for x in 0..width {
+			let a = f64::from(coverage[y * width + x]).clamp(0.0, 1.0);
+			let i = (y + BUFFER) * w + x + BUFFER;
+			if a >= 1.0 {
+				outer[i] = 0.0;
+				inner[i] = INF;
+			} else if a > 0.0 {
+				let d = 0.5 - a;
+				outer[i] = if d > 0.0 { d * d } else { 0.0 };
+				inner[i] = if d < 0.0 { d * d } else { 0.0 };
+			}
+		}
+	}
+
+	edt(&mut outer, w, h);
+	edt(&mut inner, w, h);
+
+	outer
+		.iter()
+		.zip(inner.iter())
+		.map(|(o, i)| {
+			let d = o.sqrt() - i.sqrt();
+			(255.0 - 255.0 * (d / RADIUS + CUTOFF)).round().clamp(0.0, 255.0) as u8
+		})
+		.collect()
+}
+
+/// 2D squared Euclidean distance transform, applied to the columns and then to the rows.
+fn edt(data: &mut [f64], width: usize, height: usize) {
+	let mut line = vec![0.0; width.max(height)];
+	for x in 0..width {
+		for y in 0..height {
+			line[y] = data[y * width + x];
+		}
+		edt_1d(&mut line[..height]);
+		for y in 0..height {
+			data[y * width + x] = line[y];
+		}
+	}
+	for row in data.chunks_exact_mut(width) {
+		edt_1d(row);
+	}
+}
+
+/// 1D squared Euclidean distance transform (Felzenszwalb & Huttenlocher), in place.
+fn edt_1d(f: &mut [f64]) {
+	let n = f.len();
+	let input = f.to_vec();
+	let mut v = vec![0usize; n];
+	let mut z = vec![0.0; n + 1];
+	z[0] = -INF;
+	z[1] = INF;
+
+	let mut k = 0usize;
+	for q in 1..n {
+		let q2 = (q * q) as f64;
+		let mut s;
+		loop {
+			let r = v[k];
+			s = (input[q] - input[r] + q2 - (r * r) as f64) / (q - r) as f64 / 2.0;
+			if s <= z[k] && k > 0 {
+				k -= 1;
+			} else {
+				break;
+			}
+		}
+		if s <= z[k] {
+			// `q` dominates every parabola seen so far
+			v[0] = q;
+			z[0] = -INF;
+		} else {
+			k += 1;
+			v[k] = q;
+			z[k] = s;
+		}
+		z[k + 1] = INF;
+	}
+
+	k = 0;
+	for (q, value) in f.iter_mut().enumerate() {
+		while z[k + 1] < q as f64 {
+			k += 1;
+		}
+		let r = v[k];
+		let dq = q as f64 - r as f64;
+		*value = dq * dq + input[r];
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_core::io::pbf::PbfReader;
+
+	fn renderer() -> GlyphRenderer {
+		GlyphRenderer::from_vec(include_bytes!("./draw/trim.ttf").to_vec(), "Trim Regular").unwrap()
+	}
+
+	#[test]
+	fn edt_1d_measures_squared_distances() {
+		let mut line = [INF, INF, 0.0, INF, INF, INF];
+		edt_1d(&mut line);
+		assert_eq!(line, [4.0, 1.0, 0.0, 1.0, 4.0, 9.0]);
+	}
+
+	#[test]
+	fn sdf_is_bright_inside_and_dark_outside() {
+		let coverage = vec![1.0; 16];
+		let field = sdf(&coverage, 4, 4);
+		let w = 4 + 2 * BUFFER;
+		assert_eq!(field.len(), w * w);
+		assert!(field[(BUFFER + 2) * w + BUFFER + 2] > 191);
+		assert!(field[0] < 100);
+		// the pixels on both sides of the glyph edge
+		let edge = (BUFFER + 2) * w + BUFFER;
+		assert_eq!((field[edge - 1], field[edge]), (159, 223));
+	}
+
+	#[test]
+	fn range_starts_cover_ascii() {
+		let starts = renderer().range_starts();
+		assert_eq!(starts[0], 0);
+		assert!(starts.windows(2).all(|w| w[0] < w[1] && w[0] % 256 == 0));
+	}
+
+	#[test]
+	fn render_range_writes_fontstack() -> Result<()> {
+		let blob = renderer().render_range(0)?;
+		let mut reader = PbfReader::new(blob.as_slice());
+		assert_eq!(reader.read_key()?, (1, WireType::Len));
+		let stack = reader.read_bytes()?;
+		assert!(!reader.has_remaining());
+
+		let mut reader = PbfReader::new(stack);
+		let (mut name, mut range, mut ids) = (String::new(), String::new(), Vec::new());
+		while reader.has_remaining() {
+			match reader.read_key()? {
+				(1, WireType::Len) => name = reader.read_string()?.to_string(),
+				(2, WireType::Len) => range = reader.read_string()?.to_string(),
+				(3, WireType::Len) => {
+					let mut glyph = PbfReader::new(reader.read_bytes()?);
+					assert_eq!(glyph.read_key()?, (1, WireType::Varint));
+					ids.push(glyph.read_varint()?);
+				}
+				(_, wire_type) => reader.skip(wire_type)?,
+			}
+		}
+		assert_eq!(name, "Trim Regular");
+		assert_eq!(range, "0-255");
+		assert!(ids.contains(&u64::from(b'A')));
+		assert!(ids.contains(&u64::from(b' ')));
+		Ok(())
+	}
+
+	#[test]
+	fn render_range_rejects_unaligned_start() {
+		assert!(renderer().render_range(100).is_err());
+	}
+}
@@ -11,10 +11,22 @@
 //!   - Metadata and pixel introspection (`traits::info`)
 //!   - Common transformations (scaling, flattening, cropping; `traits::operation`)
 //!   - Deterministic test image generation (`traits::test`)
+//! - Color ramps for single channel data (`colormap`)
+//! - Antialiased drawing of lines, rectangles, circles and text (`draw`)
+//! - Stitching tiles into one image and splitting it back into tiles (`mosaic`)
+//! - Raw pixel buffers with row stride, e.g. for GPU texture uploads (`raw`)
 
+pub mod colormap;
+pub mod draw;
 pub mod format;
+pub mod glyphs;
+pub mod mosaic;
+pub mod raw;
+pub mod sprites;
 pub mod traits;
 
+pub use colormap::*;
 pub use format::*;
 pub use image::{DynamicImage, GenericImage, GenericImageView, ImageBuffer, Luma, LumaA, Rgb, Rgba};
+pub use raw::{RawImage, RawPixelFormat};
 pub use traits::*;
@@ -0,0 +1,162 @@
+//! Stitching raster tiles into one large image, and splitting it back into tiles.
+//!
+//! Both directions work on a [`TileBBox`]: the mosaic of a bbox that is `w × h` tiles wide
+//! is an image of `w·tile_size × h·tile_size` pixels, and the tile `(x, y)` starts at pixel
+//! `((x - x_min)·tile_size, (y - y_min)·tile_size)`.
+//!
+//! ```rust
+//! use versatiles_core::{TileBBox, TileCoord};
+//! use versatiles_image::{DynamicImage, Rgba, mosaic};
+//!
+//! let bbox = TileBBox::from_min_and_max(3, 2, 2, 3, 3).unwrap();
+//! let tile = DynamicImage::new_rgba8(256, 256);
+//! let tiles = vec![(TileCoord::new(3, 2, 2).unwrap(), tile)];
+//!
+//! let image = mosaic::stitch(&bbox, tiles, 256, Rgba([255, 255, 255, 255])).unwrap();
+//! assert_eq!((image.width(), image.height()), (512, 512));
+//!
+//! let tiles = mosaic::split(&image, &bbox, 256).unwrap();
+//! assert_eq!(tiles.len(), 4);
+//! ```
+
+use anyhow::{Result, bail, ensure};
+use image::{DynamicImage, GenericImageView, ImageBuffer, Rgba};
+use versatiles_core::{TileBBox, TileCoord};
+use versatiles_derive::context;
+
+#[context("stitching {} tiles of {}px", bbox.count_tiles(), tile_size)]
+/// Stitches the `tiles` of `bbox` into a single RGBA image.
+///
+/// Tiles that are missing in `tiles` are filled with `fill`, e.g. a transparent or a background color.
+///
+/// * Errors if a tile is outside of `bbox`, a tile is not `tile_size × tile_size` pixels,
+///   or the image would be larger than `u32::MAX` pixels in either direction.
+pub fn stitch(
+	bbox: &TileBBox,
+	tiles: impl IntoIterator<Item = (TileCoord, DynamicImage)>,
+	tile_size: u32,
+	fill: Rgba<u8>,
+) -> Result<DynamicImage> {
+	ensure!(!bbox.is_empty(), "bbox must not be empty");
+	let width = bbox.width().checked_mul(tile_size);
+	let height = bbox.height().checked_mul(tile_size);
+	let (Some(width), Some(height)) = (width, height) else {
+		bail!("mosaic of {bbox:?} is too large");
+	};
+	let (x_min, y_min) = (bbox.x_min()?, bbox.y_min()?);
+
+	let mut canvas = ImageBuffer::from_pixel(width, height, fill);
+	for (coord, tile) in tiles {
+		ensure!(bbox.contains(&coord), "tile {coord:?} is outside of {bbox:?}");
+		ensure!(
+			tile.dimensions() == (tile_size, tile_size),
+			"tile {coord:?} has a size of {}x{}, but expected {tile_size}x{tile_size}",
+			tile.width(),
+			tile.height()
+		);
+		let left = (coord.x - x_min) * tile_size;
+		let top = (coord.y - y_min) * tile_size;
+		for (x, y, pixel) in tile.to_rgba8().enumerate_pixels() {
+			canvas.put_pixel(left + x, top + y, *pixel);
+		}
+	}
+
+	Ok(DynamicImage::ImageRgba8(canvas))
+}
+
+#[context("splitting {}x{} image into tiles of {}px", image.width(), image.height(), tile_size)]
+/// Splits an image into the tiles of `bbox`, in the row-major order of [`TileBBox::iter_coords`].
+///
+/// The tiles keep the color type of `image`.
+///
+/// * Errors if the image is not exactly `bbox.width()·tile_size × bbox.height()·tile_size` pixels.
+pub fn split(image: &DynamicImage, bbox: &TileBBox, tile_size: u32) -> Result<Vec<(TileCoord, DynamicImage)>> {
+	ensure!(!bbox.is_empty(), "bbox must not be empty");
+	ensure!(
+		u64::from(image.width()) == u64::from(bbox.width()) * u64::from(tile_size)
+			&& u64::from(image.height()) == u64::from(bbox.height()) * u64::from(tile_size),
+		"image size {}x{} does not match {}x{} tiles of {tile_size}px",
+		image.width(),
+		image.height(),
+		bbox.width(),
+		bbox.height()
+	);
+	let (x_min, y_min) = (bbox.x_min()?, bbox.y_min()?);
+
+	Ok(bbox
+		.iter_coords()
+		.map(|coord| {
+			let tile = image.crop_imm(
+				(coord.x - x_min) * tile_size,
+				(coord.y - y_min) * tile_size,
+				tile_size,
+				tile_size,
+			);
+			(coord, tile)
+		})
+		.collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::traits::DynamicImageTraitTest;
+
+	fn solid(size: u32, color: [u8; 4]) -> DynamicImage {
+		DynamicImage::ImageRgba8(ImageBuffer::from_pixel(size, size, Rgba(color)))
+	}
+
+	#[test]
+	fn stitch_places_tiles_and_fills_gaps() -> Result<()> {
+		let bbox = TileBBox::from_min_and_max(4, 5, 6, 6, 7)?;
+		let tiles = vec![
+			(TileCoord::new(4, 5, 6)?, solid(8, [255, 0, 0, 255])),
+			(TileCoord::new(4, 6, 7)?, solid(8, [0, 0, 255, 255])),
+		];
+		let image = stitch(&bbox, tiles, 8, Rgba([1, 2, 3, 4]))?.to_rgba8();
+
+		assert_eq!(image.dimensions(), (16, 16));
+		assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+		assert_eq!(image.get_pixel(7, 7).0, [255, 0, 0, 255]);
+		assert_eq!(image.get_pixel(8, 0).0, [1, 2, 3, 4]);
+		assert_eq!(image.get_pixel(0, 8).0, [1, 2, 3, 4]);
+		assert_eq!(image.get_pixel(15, 15).0, [0, 0, 255, 255]);
+		Ok(())
+	}
+
+	#[test]
+	fn stitch_rejects_invalid_tiles() -> Result<()> {
+		let bbox = TileBBox::from_min_and_max(4, 5, 6, 6, 7)?;
+		let fill = Rgba([0, 0, 0, 0]);
+
+		let outside = vec![(TileCoord::new(4, 7, 7)?, solid(8, [0; 4]))];
+		assert!(stitch(&bbox, outside, 8, fill).is_err());
+
+		let wrong_size = vec![(TileCoord::new(4, 5, 6)?, solid(4, [0; 4]))];
+		assert!(stitch(&bbox, wrong_size, 8, fill).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn split_is_inverse_of_stitch() -> Result<()> {
+		let bbox = TileBBox::from_min_and_max(1, 0, 0, 1, 1)?;
+		let image = DynamicImage::new_test_rgba();
+		let tiles = split(&image, &bbox, 128)?;
+
+		let coords = tiles.iter().map(|(c, _)| (c.x, c.y)).collect::<Vec<_>>();
+		assert_eq!(coords, [(0, 0), (1, 0), (0, 1), (1, 1)]);
+		assert!(tiles.iter().all(|(_, tile)| tile.dimensions() == (128, 128)));
+		assert_eq!(tiles[1].1.get_pixel(0, 0), image.get_pixel(128, 0));
+
+		let stitched = stitch(&bbox, tiles, 128, Rgba([0, 0, 0, 0]))?;
+		assert_eq!(stitched.as_bytes(), image.as_bytes());
+		Ok(())
+	}
+
+	#[test]
+	fn split_rejects_wrong_size() -> Result<()> {
+		let bbox = TileBBox::from_min_and_max(1, 0, 0, 1, 1)?;
+		assert!(split(&DynamicImage::new_test_rgba(), &bbox, 256).is_err());
+		Ok(())
+	}
+}
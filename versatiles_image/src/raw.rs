@@ -0,0 +1,222 @@
+//! Raw pixel buffers with an explicit row stride, e.g. for uploading tiles to GPU textures.
+//!
+//! A [`RawImage`] describes uncompressed 8-bit pixels: `width × height` pixels in a
+//! [`RawPixelFormat`], where each row starts `stride` bytes after the previous one.
+//! Rows may be padded, because GPU APIs often require row alignments like 256 bytes.
+//!
+//! The conversions live in [`DynamicImageTraitConvert`](crate::DynamicImageTraitConvert):
+//! - `as_raw_image` borrows the pixels of an image without copying them,
+//! - `to_raw_image` copies them once into a buffer with a given format and row alignment,
+//! - `from_raw_image` builds an image, taking ownership of tightly packed buffers without copying.
+//!
+//! ```rust
+//! use versatiles_image::{DynamicImage, DynamicImageTraitConvert, RawPixelFormat};
+//!
+//! let image = DynamicImage::new_rgb8(3, 2);
+//!
+//! // borrow the pixels
+//! let raw = image.as_raw_image().unwrap();
+//! assert_eq!((raw.width, raw.height, raw.stride, raw.format), (3, 2, 9, RawPixelFormat::Rgb8));
+//!
+//! // copy them into RGBA rows, aligned to 256 bytes
+//! let raw = image.to_raw_image(RawPixelFormat::Rgba8, 256).unwrap();
+//! assert_eq!((raw.stride, raw.data.len()), (256, 512));
+//!
+//! let image = DynamicImage::from_raw_image(raw).unwrap();
+//! assert_eq!((image.width(), image.height()), (3, 2));
+//! ```
+
+use anyhow::{Result, ensure};
+use image::{ColorType, DynamicImage};
+use std::borrow::Cow;
+use versatiles_derive::context;
+
+/// The layout of a single pixel in a [`RawImage`], one byte per channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RawPixelFormat {
+	/// Grayscale
+	L8,
+	/// Grayscale with alpha
+	La8,
+	/// Red, green, blue
+	Rgb8,
+	/// Red, green, blue, alpha
+	Rgba8,
+}
+
+impl RawPixelFormat {
+	/// Returns the number of bytes per pixel.
+	#[must_use]
+	pub fn bytes_per_pixel(&self) -> usize {
+		match self {
+			RawPixelFormat::L8 => 1,
+			RawPixelFormat::La8 => 2,
+			RawPixelFormat::Rgb8 => 3,
+			RawPixelFormat::Rgba8 => 4,
+		}
+	}
+
+	/// Returns the pixel format of `color`, or `None` if it is not an 8-bit format.
+	#[must_use]
+	pub fn from_color_type(color: ColorType) -> Option<RawPixelFormat> {
+		match color {
+			ColorType::L8 => Some(RawPixelFormat::L8),
+			ColorType::La8 => Some(RawPixelFormat::La8),
+			ColorType::Rgb8 => Some(RawPixelFormat::Rgb8),
+			ColorType::Rgba8 => Some(RawPixelFormat::Rgba8),
+			_ => None,
+		}
+	}
+}
+
+/// Uncompressed pixels with an explicit row stride, either borrowed from an image or owned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawImage<'a> {
+	/// Width in pixels
+	pub width: u32,
+	/// Height in pixels
+	pub height: u32,
+	/// Distance between the starts of two rows in bytes
+	pub stride: usize,
+	/// Layout of the pixels
+	pub format: RawPixelFormat,
+	/// Pixel data, at least `stride · (height - 1) + width · bytes_per_pixel` bytes
+	pub data: Cow<'a, [u8]>,
+}
+
+impl<'a> RawImage<'a> {
+	/// Creates a `RawImage` and checks that `stride` and the length of `data` fit the dimensions.
+	#[context("creating raw image ({}x{}, stride {}, {:?})", width, height, stride, format)]
+	pub fn new(
+		width: u32,
+		height: u32,
+		stride: usize,
+		format: RawPixelFormat,
+		data: impl Into<Cow<'a, [u8]>>,
+	) -> Result<RawImage<'a>> {
+		let raw = RawImage {
+			width,
+			height,
+			stride,
+			format,
+			data: data.into(),
+		};
+		ensure!(
+			stride >= raw.row_length(),
+			"stride ({stride}) must be at least width ({width}) * bytes per pixel ({})",
+			format.bytes_per_pixel()
+		);
+		ensure!(
+			raw.data.len() >= raw.min_data_length(),
+			"data length ({}) must be at least {}",
+			raw.data.len(),
+			raw.min_data_length()
+		);
+		Ok(raw)
+	}
+
+	/// Returns the number of bytes in a row without padding.
+	#[must_use]
+	pub fn row_length(&self) -> usize {
+		self.width as usize * self.format.bytes_per_pixel()
+	}
+
+	/// Returns the pixels of row `y` without padding.
+	///
+	/// * Panics if `y` is outside of the image.
+	#[must_use]
+	pub fn row(&self, y: u32) -> &[u8] {
+		assert!(
+			y < self.height,
+			"row {y} is outside of the image height {}",
+			self.height
+		);
+		let start = y as usize * self.stride;
+		&self.data[start..start + self.row_length()]
+	}
+
+	/// Returns `true` if the rows are not padded.
+	#[must_use]
+	pub fn is_packed(&self) -> bool {
+		self.stride == self.row_length()
+	}
+
+	/// Returns the pixels without padding, copying them only if the rows are padded.
+	#[must_use]
+	pub fn into_packed_data(self) -> Vec<u8> {
+		let length = self.row_length() * self.height as usize;
+		if self.is_packed() {
+			let mut data = self.data.into_owned();
+			data.truncate(length);
+			data
+		} else {
+			let mut data = Vec::with_capacity(length);
+			for y in 0..self.height {
+				data.extend_from_slice(self.row(y));
+			}
+			data
+		}
+	}
+
+	fn min_data_length(&self) -> usize {
+		match self.height {
+			0 => 0,
+			h => self.stride * (h as usize - 1) + self.row_length(),
+		}
+	}
+}
+
+/// Converts `image` into a tightly packed buffer in `format`, borrowing it if the format already matches.
+pub(crate) fn packed_pixels(image: &DynamicImage, format: RawPixelFormat) -> Cow<'_, [u8]> {
+	if RawPixelFormat::from_color_type(image.color()) == Some(format) {
+		return Cow::Borrowed(image.as_bytes());
+	}
+	Cow::Owned(match format {
+		RawPixelFormat::L8 => image.to_luma8().into_raw(),
+		RawPixelFormat::La8 => image.to_luma_alpha8().into_raw(),
+		RawPixelFormat::Rgb8 => image.to_rgb8().into_raw(),
+		RawPixelFormat::Rgba8 => image.to_rgba8().into_raw(),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case::packed(2, 6, true)]
+	#[case::padded(2, 8, false)]
+	fn rows(#[case] height: u32, #[case] stride: usize, #[case] packed: bool) {
+		let data: Vec<u8> = (0..(stride * height as usize) as u8).collect();
+		let raw = RawImage::new(2, height, stride, RawPixelFormat::Rgb8, data).unwrap();
+		assert_eq!(raw.is_packed(), packed);
+		assert_eq!(raw.row(1), &raw.data[stride..stride + 6]);
+		let packed_data = raw.clone().into_packed_data();
+		assert_eq!(packed_data.len(), 12);
+		assert_eq!(&packed_data[6..], raw.row(1));
+	}
+
+	#[test]
+	fn last_row_needs_no_padding() {
+		let raw = RawImage::new(1, 2, 4, RawPixelFormat::L8, vec![1, 0, 0, 0, 2]).unwrap();
+		assert_eq!(raw.into_packed_data(), vec![1, 2]);
+	}
+
+	#[rstest]
+	#[case::stride(2, vec![0; 16], "stride (2) must be at least width (2) * bytes per pixel (2)")]
+	#[case::data(4, vec![0; 7], "data length (7) must be at least 8")]
+	fn new_rejects_invalid_layout(#[case] stride: usize, #[case] data: Vec<u8>, #[case] message: &str) {
+		let error = RawImage::new(2, 2, stride, RawPixelFormat::La8, data).unwrap_err();
+		assert_eq!(error.chain().last().unwrap().to_string(), message);
+	}
+
+	#[test]
+	fn packed_pixels_borrows_matching_format() {
+		let image = DynamicImage::new_rgba8(2, 2);
+		assert!(matches!(packed_pixels(&image, RawPixelFormat::Rgba8), Cow::Borrowed(_)));
+		let converted = packed_pixels(&image, RawPixelFormat::L8);
+		assert!(matches!(converted, Cow::Owned(_)));
+		assert_eq!(converted.len(), 4);
+	}
+}
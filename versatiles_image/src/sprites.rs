@@ -0,0 +1,209 @@
+//! Sprite sheet generation for MapLibre/Mapbox styles.
+//!
+//! A sprite is a single PNG containing all icons of a style, together with a JSON index that
+//! maps every icon name to its position: `{"name": {"x", "y", "width", "height", "pixelRatio"}}`.
+//! Styles reference sprites by a URL prefix; MapLibre loads `{prefix}.png`/`{prefix}.json` and,
+//! on high-DPI screens, `{prefix}@2x.png`/`{prefix}@2x.json`.
+//!
+//! [`SpriteBuilder`] rasterizes SVG icons at any pixel ratio and packs them into rows.
+//!
+//! ```rust
+//! use versatiles_image::sprites::SpriteBuilder;
+//!
+//! let mut builder = SpriteBuilder::default();
+//! builder.add_svg("dot", br#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8"><circle cx="4" cy="4" r="4"/></svg>"#).unwrap();
+//! let (image, index) = builder.render(2).unwrap();
+//! assert_eq!((image.width(), image.height()), (16, 16));
+//! assert_eq!(index.stringify(), r#"{"dot":{"height":16,"pixelRatio":2,"width":16,"x":0,"y":0}}"#);
+//! ```
+
+use anyhow::{Context, Result, ensure};
+use image::{DynamicImage, RgbaImage};
+use resvg::{tiny_skia, usvg};
+use std::collections::BTreeMap;
+use versatiles_core::json::JsonObject;
+
+/// Empty pixels between icons, so icons don't bleed into each other when scaled.
+const PADDING: u32 = 1;
+
+/// Collects SVG icons and renders them into sprite sheets.
+#[derive(Default)]
+pub struct SpriteBuilder {
+	icons: BTreeMap<String, usvg::Tree>,
+}
+
+impl SpriteBuilder {
+	/// Adds an SVG icon. Icons are sorted by name; adding a name twice replaces the icon.
+	pub fn add_svg(&mut self, name: &str, data: &[u8]) -> Result<()> {
+		let tree = usvg::Tree::from_data(data, &usvg::Options::default())
+			.with_context(|| format!("parsing SVG icon '{name}'"))?;
+		self.icons.insert(name.to_string(), tree);
+		Ok(())
+	}
+
+	/// Number of icons.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.icons.len()
+	}
+
+	/// Returns `true` if no icons were added.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.icons.is_empty()
+	}
+
+	/// Renders all icons at `pixel_ratio` into a sprite image and its JSON index.
+	pub fn render(&self, pixel_ratio: u32) -> Result<(DynamicImage, JsonObject)> {
+		ensure!(pixel_ratio > 0, "pixel ratio must be positive");
+		ensure!(!self.is_empty(), "sprite contains no icons");
+
+		let icons = self
+			.icons
+			.iter()
+			.map(|(name, tree)| Ok((name.as_str(), rasterize(tree, pixel_ratio as f32)?)))
+			.collect::<Result<Vec<_>>>()?;
+
+		let positions = pack(&icons.iter().map(|(_, icon)| icon.dimensions()).collect::<Vec<_>>());
+		let width = positions
+			.iter()
+			.zip(&icons)
+			.map(|((x, _), (_, icon))| x + icon.width())
+			.max();
+		let height = positions
+			.iter()
+			.zip(&icons)
+			.map(|((_, y), (_, icon))| y + icon.height())
+			.max();
+		let mut sprite = RgbaImage::new(width.unwrap_or(0), height.unwrap_or(0));
+
+		let mut index = JsonObject::new();
+		for ((x, y), (name, icon)) in positions.into_iter().zip(icons) {
+			image::imageops::replace(&mut sprite, &icon, i64::from(x), i64::from(y));
+			index.set(
+				name,
+				JsonObject::from(vec![
+					("height", icon.height()),
+					("pixelRatio", pixel_ratio),
+					("width", icon.width()),
+					("x", x),
+					("y", y),
+				]),
+			);
+		}
+
+		Ok((DynamicImage::ImageRgba8(sprite), index))
+	}
+}
+
+/// Renders an SVG at `scale` into an image with straight (not premultiplied) alpha.
+fn rasterize(tree: &usvg::Tree, scale: f32) -> Result<RgbaImage> {
+	let size = tree.size();
+	let width = (size.width() * scale).ceil().max(1.0) as u32;
+	let height = (size.height() * scale).ceil().max(1.0) as u32;
+	let mut pixmap = tiny_skia::Pixmap::new(width, height).context("creating icon pixmap")?;
+	resvg::render(
+		tree,
+		tiny_skia::Transform::from_scale(scale, scale),
+		&mut pixmap.as_mut(),
+	);
+
+	let data = pixmap
+		.pixels()
+		.iter()
+		.flat_map(|pixel| {
+			let color = pixel.demultiply();
+			[color.red(), color.green(), color.blue(), color.alpha()]
+		})
+		.collect();
+	RgbaImage::from_raw(width, height, data).context("converting icon pixmap")
+}
+
+/// Packs rectangles into rows ("shelves"), tallest first, and returns their upper left corners.
+///
+/// The rows are about as wide as the square root of the total area, so the sprite stays roughly square.
+fn pack(sizes: &[(u32, u32)]) -> Vec<(u32, u32)> {
+	let area: u64 = sizes
+		.iter()
+		.map(|(w, h)| u64::from(w + PADDING) * u64::from(h + PADDING))
+		.sum();
+	let max_width = sizes.iter().map(|(w, _)| *w).max().unwrap_or(0);
+	let row_width = max_width.max((area as f64).sqrt().ceil() as u32);
+
+	let mut order: Vec<usize> = (0..sizes.len()).collect();
+	order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+
+	let mut positions = vec![(0, 0); sizes.len()];
+	let (mut x, mut y, mut row_height) = (0, 0, 0);
+	for i in order {
+		let (w, h) = sizes[i];
+		if x > 0 && x + w > row_width {
+			x = 0;
+			y += row_height + PADDING;
+			row_height = 0;
+		}
+		positions[i] = (x, y);
+		x += w + PADDING;
+		row_height = row_height.max(h);
+	}
+	positions
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn square(size: u32, color: &str) -> String {
+		format!(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}"><rect width="{size}" height="{size}" fill="{color}"/></svg>"#
+		)
+	}
+
+	#[test]
+	fn pack_keeps_rectangles_apart() {
+		let sizes = [(10, 10), (4, 20), (10, 5), (3, 3)];
+		let positions = pack(&sizes);
+		for i in 0..sizes.len() {
+			for j in (i + 1)..sizes.len() {
+				let (a, b) = (positions[i], positions[j]);
+				let overlap_x = a.0 < b.0 + sizes[j].0 + PADDING && b.0 < a.0 + sizes[i].0 + PADDING;
+				let overlap_y = a.1 < b.1 + sizes[j].1 + PADDING && b.1 < a.1 + sizes[i].1 + PADDING;
+				assert!(!(overlap_x && overlap_y), "{i} and {j} overlap: {positions:?}");
+			}
+		}
+		assert_eq!(positions[1], (0, 0));
+	}
+
+	#[test]
+	fn render_places_icons() -> Result<()> {
+		let mut builder = SpriteBuilder::default();
+		builder.add_svg("red", square(4, "#f00").as_bytes())?;
+		builder.add_svg("blue", square(8, "#00f").as_bytes())?;
+		assert_eq!(builder.len(), 2);
+
+		for ratio in [1, 2] {
+			let (image, index) = builder.render(ratio)?;
+			let image = image.to_rgba8();
+			for (name, color) in [("red", [255, 0, 0, 255]), ("blue", [0, 0, 255, 255])] {
+				let icon = index.get_object(name)?.unwrap();
+				let x = icon.get_number("x")?.unwrap() as u32;
+				let y = icon.get_number("y")?.unwrap() as u32;
+				let width = icon.get_number("width")?.unwrap() as u32;
+				assert_eq!(icon.get_number("pixelRatio")?, Some(f64::from(ratio)));
+				assert_eq!(width, if name == "red" { 4 } else { 8 } * ratio);
+				assert_eq!(image.get_pixel(x, y).0, color);
+				assert_eq!(image.get_pixel(x + width - 1, y + width - 1).0, color);
+			}
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn rejects_invalid_input() {
+		let mut builder = SpriteBuilder::default();
+		assert!(builder.render(1).is_err());
+		assert!(builder.add_svg("broken", b"<svg").is_err());
+		builder.add_svg("ok", square(2, "#000").as_bytes()).unwrap();
+		assert!(builder.render(0).is_err());
+	}
+}
@@ -5,11 +5,15 @@
 //! - Convert between raw byte buffers and `DynamicImage` (`from_raw`)
 //! - Encode/decode to/from supported image formats (`to_blob`, `from_blob`)
 //! - Iterate over pixel data (`iter_pixels`)
+//! - Exchange pixels with strided buffers, e.g. GPU textures (`as_raw_image`, `to_raw_image`, `from_raw_image`)
 //!
 //! Supported formats include: PNG, JPEG, WEBP, and AVIF.
 //! These utilities are used in VersaTiles Pipeline.
 
-use crate::format::{avif, jpeg, png, webp};
+use crate::{
+	format::{avif, jpeg, png, webp},
+	raw::{RawImage, RawPixelFormat, packed_pixels},
+};
 use anyhow::{Result, anyhow, bail, ensure};
 use image::{DynamicImage, EncodableLayout, ImageBuffer};
 use versatiles_core::{Blob, TileFormat};
@@ -41,6 +45,18 @@ pub trait DynamicImageTraitConvert {
 
 	/// Returns a reference to the raw pixel data at the specified (x, y) coordinates.
 	fn get_raw_pixel(&self, x: u32, y: u32) -> &[u8];
+
+	/// Borrows the pixel data as a tightly packed [`RawImage`] without copying it.
+	/// Returns an error if the image is not L8, LA8, RGB8 or RGBA8.
+	fn as_raw_image(&self) -> Result<RawImage<'_>>;
+
+	/// Copies the pixel data into a [`RawImage`] in `format`, converting the pixels if needed.
+	/// Each row starts at a multiple of `row_alignment` bytes, e.g. 256 for GPU texture uploads.
+	fn to_raw_image(&self, format: RawPixelFormat, row_alignment: usize) -> Result<RawImage<'static>>;
+
+	/// Constructs a `DynamicImage` from a [`RawImage`].
+	/// Owned, tightly packed pixel data is moved into the image; otherwise it is copied once.
+	fn from_raw_image(raw: RawImage) -> Result<DynamicImage>;
 }
 
 impl DynamicImageTraitConvert for DynamicImage {
@@ -131,6 +147,57 @@ impl DynamicImageTraitConvert for DynamicImage {
 			_ => panic!("Unsupported image type for get_raw_pixel"),
 		}
 	}
+
+	#[context("borrowing {:?} image as raw image", self.color())]
+	fn as_raw_image(&self) -> Result<RawImage<'_>> {
+		let format = RawPixelFormat::from_color_type(self.color())
+			.ok_or_else(|| anyhow!("Unsupported color type for raw image: {:?}", self.color()))?;
+		RawImage::new(
+			self.width(),
+			self.height(),
+			self.width() as usize * format.bytes_per_pixel(),
+			format,
+			self.as_bytes(),
+		)
+	}
+
+	#[context("converting {:?} image to raw {:?} image (row alignment {})", self.color(), format, row_alignment)]
+	fn to_raw_image(&self, format: RawPixelFormat, row_alignment: usize) -> Result<RawImage<'static>> {
+		ensure!(row_alignment > 0, "row alignment must be greater than 0");
+		let row_length = self.width() as usize * format.bytes_per_pixel();
+		let stride = row_length.next_multiple_of(row_alignment);
+		let pixels = packed_pixels(self, format);
+
+		let data = if stride == row_length {
+			pixels.into_owned()
+		} else {
+			let height = self.height() as usize;
+			let mut data = vec![0u8; stride * height];
+			for (src, dst) in pixels.chunks_exact(row_length).zip(data.chunks_exact_mut(stride)) {
+				dst[..row_length].copy_from_slice(src);
+			}
+			data
+		};
+		RawImage::new(self.width(), self.height(), stride, format, data)
+	}
+
+	#[context("creating image from raw image ({}x{}, stride {}, {:?})", raw.width, raw.height, raw.stride, raw.format)]
+	fn from_raw_image(raw: RawImage) -> Result<DynamicImage> {
+		let raw = RawImage::new(raw.width, raw.height, raw.stride, raw.format, raw.data)?;
+		let (width, height, format) = (raw.width, raw.height, raw.format);
+		let data = raw.into_packed_data();
+		let error = || anyhow!("Failed to create {format:?} image buffer with provided data");
+		Ok(match format {
+			RawPixelFormat::L8 => DynamicImage::ImageLuma8(ImageBuffer::from_vec(width, height, data).ok_or_else(error)?),
+			RawPixelFormat::La8 => {
+				DynamicImage::ImageLumaA8(ImageBuffer::from_vec(width, height, data).ok_or_else(error)?)
+			}
+			RawPixelFormat::Rgb8 => DynamicImage::ImageRgb8(ImageBuffer::from_vec(width, height, data).ok_or_else(error)?),
+			RawPixelFormat::Rgba8 => {
+				DynamicImage::ImageRgba8(ImageBuffer::from_vec(width, height, data).ok_or_else(error)?)
+			}
+		})
+	}
 }
 
 /// Tests for the `DynamicImageTraitConvert` trait implementation.
@@ -246,4 +313,78 @@ mod tests {
 		let img = DynamicImage::from_fn(2, 2, |_, _| expected_pixel);
 		assert_eq!(img.average_color(), expected_pixel);
 	}
+
+	#[rstest]
+	#[case::l8(sample_l8(), RawPixelFormat::L8, 4)]
+	#[case::la8(sample_la8(), RawPixelFormat::La8, 8)]
+	#[case::rgb8(sample_rgb8(), RawPixelFormat::Rgb8, 12)]
+	#[case::rgba8(sample_rgba8(), RawPixelFormat::Rgba8, 16)]
+	fn as_raw_image_borrows_pixels(#[case] img: DynamicImage, #[case] format: RawPixelFormat, #[case] stride: usize) {
+		let raw = img.as_raw_image().unwrap();
+		assert_eq!((raw.width, raw.height, raw.stride, raw.format), (4, 3, stride, format));
+		assert_eq!(raw.data.as_ptr(), img.as_bytes().as_ptr());
+		assert_eq!(DynamicImage::from_raw_image(raw).unwrap(), img);
+	}
+
+	#[test]
+	fn as_raw_image_rejects_unsupported_color_types() {
+		let img = DynamicImage::new_rgba16(2, 2);
+		assert_eq!(
+			img.as_raw_image().unwrap_err().chain().last().unwrap().to_string(),
+			"Unsupported color type for raw image: Rgba16"
+		);
+	}
+
+	#[rstest]
+	#[case::packed(1, 16)]
+	#[case::aligned(256, 256)]
+	#[case::odd(5, 20)]
+	fn to_raw_image_pads_rows(#[case] row_alignment: usize, #[case] stride: usize) {
+		let img = sample_rgba8();
+		let raw = img.to_raw_image(RawPixelFormat::Rgba8, row_alignment).unwrap();
+		assert_eq!(raw.stride, stride);
+		assert_eq!(raw.data.len(), stride * 3);
+		assert_eq!(raw.row(2), &img.as_bytes()[32..48]);
+		assert_eq!(DynamicImage::from_raw_image(raw).unwrap(), img);
+	}
+
+	#[test]
+	fn to_raw_image_converts_format() {
+		let raw = sample_rgb8().to_raw_image(RawPixelFormat::Rgba8, 1).unwrap();
+		assert_eq!(raw.format, RawPixelFormat::Rgba8);
+		assert_eq!(raw.row(1)[4..8], [1, 1, 2, 255]);
+		assert_eq!(
+			DynamicImage::from_raw_image(raw).unwrap(),
+			sample_rgb8().to_rgba8().into()
+		);
+	}
+
+	#[test]
+	fn from_raw_image_moves_packed_data() {
+		let data = vec![7u8; 2 * 2 * 4];
+		let ptr = data.as_ptr();
+		let raw = RawImage::new(2, 2, 8, RawPixelFormat::Rgba8, data).unwrap();
+		let img = DynamicImage::from_raw_image(raw).unwrap();
+		assert_eq!(img.as_bytes().as_ptr(), ptr);
+	}
+
+	#[test]
+	fn from_raw_image_validates_layout() {
+		let raw = RawImage {
+			width: 2,
+			height: 2,
+			stride: 8,
+			format: RawPixelFormat::Rgba8,
+			data: vec![0u8; 10].into(),
+		};
+		assert_eq!(
+			DynamicImage::from_raw_image(raw)
+				.unwrap_err()
+				.chain()
+				.last()
+				.unwrap()
+				.to_string(),
+			"data length (10) must be at least 16"
+		);
+	}
 }
@@ -7,7 +7,7 @@
 //! - Computing a quick representative/average color
 //! - Cropping with resampling and downscaling with configurable filters
 //! - Alpha-aware flattening against a background color
-//! - In-place mutation of color channels (leaving alpha intact)
+//! - In-place mutation of color channels or pixel colors (leaving alpha intact)
 //! - In-place overlay compositing of two images with size validation
 //!
 //! Most operations use efficient backends (`fast_image_resize` where applicable) and avoid
@@ -78,6 +78,14 @@ pub trait DynamicImageTraitOperation: DynamicImageTraitInfo {
 	where
 		F: Fn(u8) -> u8;
 
+	/// Applies a mapping function `f` to the color of **every pixel**, leaving the alpha channel intact.
+	///
+	/// `f` gets and returns `[r, g, b]`. Grey images pass `[v, v, v]` and keep the first channel of the result,
+	/// so convert them to RGB first if `f` produces colors. Errors on unsupported color types.
+	fn mut_pixel_colors<F>(&mut self, f: F) -> Result<()>
+	where
+		F: Fn([u8; 3]) -> [u8; 3];
+
 	/// Draws `top` over `self` in place (composites at (0,0)).
 	///
 	/// Ensures the images have identical dimensions. Returns an error if sizes differ.
@@ -230,6 +238,37 @@ where
 		}
 	}
 
+	fn mut_pixel_colors<F>(&mut self, f: F) -> Result<()>
+	where
+		F: Fn([u8; 3]) -> [u8; 3],
+	{
+		match self {
+			DynamicImage::ImageLuma8(img) => {
+				for p in img.pixels_mut() {
+					p[0] = f([p[0], p[0], p[0]])[0];
+				}
+			}
+			DynamicImage::ImageLumaA8(img) => {
+				for p in img.pixels_mut() {
+					p[0] = f([p[0], p[0], p[0]])[0];
+				}
+			}
+			DynamicImage::ImageRgb8(img) => {
+				for p in img.pixels_mut() {
+					p.0 = f(p.0);
+				}
+			}
+			DynamicImage::ImageRgba8(img) => {
+				for p in img.pixels_mut() {
+					let [r, g, b] = f([p[0], p[1], p[2]]);
+					p.0 = [r, g, b, p[3]];
+				}
+			}
+			_ => bail!("Unsupported image type for mutating pixel colors: {:?}", self.color()),
+		}
+		Ok(())
+	}
+
 	#[context("overlaying top {}x{} {:?} onto base {}x{} {:?}", top.width(), top.height(), top.color(), self.width(), self.height(), self.color())]
 	fn overlay(&mut self, top: &DynamicImage) -> Result<()> {
 		self.ensure_same_size(top)?;
@@ -391,6 +430,21 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn mut_pixel_colors_maps_colors_and_keeps_alpha() -> Result<()> {
+		let mut img = DynamicImage::from_raw(2, 1, vec![10, 20, 30, 40, 50, 60, 70, 80])?;
+		img.mut_pixel_colors(|[r, g, b]| [b, g, r])?;
+		assert_eq!(img.as_bytes(), [30, 20, 10, 40, 70, 60, 50, 80]);
+
+		let mut img = DynamicImage::from_raw(2, 1, vec![10, 200, 30, 40])?;
+		img.mut_pixel_colors(|[r, g, b]| [r + g + b, 0, 0])?;
+		assert_eq!(img.as_bytes(), [30, 200, 90, 40]);
+
+		let mut img = DynamicImage::ImageRgb16(image::ImageBuffer::new(1, 1));
+		assert!(img.mut_pixel_colors(|c| c).is_err());
+		Ok(())
+	}
+
 	#[rstest]
 	#[case::black(Rgba([0, 0, 0, 255]))]
 	#[case::white(Rgba([255, 255, 255, 255]))]
@@ -26,6 +26,7 @@ pub struct PipelineReader {
 	name: String,
 	operation: Box<dyn OperationTrait>,
 	parameters: TilesReaderParameters,
+	cancellation_token: CancellationToken,
 }
 
 #[allow(dead_code)]
@@ -68,11 +69,12 @@ impl<'a> PipelineReader {
 		config: ProcessingConfig,
 	) -> BoxFuture<'a, Result<PipelineReader>> {
 		let registry = Arc::new(ContainerRegistry::default());
+		let cancellation_token = config.cancellation_token.clone();
 		Box::pin(async move {
 			let callback = Box::new(
 				move |filename: String| -> BoxFuture<Result<Box<dyn TilesReaderTrait>>> {
 					let registry = registry.clone();
-					Box::pin(async move { registry.get_reader_from_str(&filename).await })
+					Box::pin(async move { registry.open_reader(&filename).await })
 				},
 			);
 			let factory = PipelineFactory::new_default(dir, callback, config);
@@ -83,6 +85,7 @@ impl<'a> PipelineReader {
 				name: name.to_string(),
 				operation,
 				parameters,
+				cancellation_token,
 			})
 		})
 	}
@@ -128,22 +131,43 @@ impl TilesReaderTrait for PipelineReader {
 	/// are produced (pipelines must emit at most one tile per coordinate).
 	#[context("getting tile {:?} via pipeline '{}'", coord, self.name)]
 	async fn get_tile(&self, coord: &TileCoord) -> Result<Option<Tile>> {
-		let mut vec = self.operation.get_stream(coord.as_tile_bbox()).await?.to_vec().await;
-
-		ensure!(vec.len() <= 1, "PipelineReader should return at most one tile");
+		single_tile(self.operation.get_stream(coord.as_tile_bbox()).await?).await
+	}
 
-		if let Some((_, b)) = vec.pop() {
-			Ok(Some(b))
-		} else {
-			Ok(None)
-		}
+	/// Like [`get_tile`](Self::get_tile), but stops executing the pipeline once `token` is cancelled,
+	/// e.g. when the client of a tile server has disconnected.
+	#[context("getting cancellable tile {:?} via pipeline '{}'", coord, self.name)]
+	async fn get_tile_cancellable(&self, coord: &TileCoord, token: CancellationToken) -> Result<Option<Tile>> {
+		let stream = self
+			.operation
+			.get_stream_cancellable(coord.as_tile_bbox(), token)
+			.await?;
+		single_tile(stream).await
 	}
 
 	/// Streams all tiles intersecting `bbox` by executing the pipeline’s output operation.
+	///
+	/// The stream ends early once the cancellation token of the [`ProcessingConfig`] is cancelled.
 	#[context("streaming tiles for bbox {:?} via pipeline '{}'", bbox, self.name)]
 	async fn get_tile_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
 		log::debug!("get_tile_stream {:?}", bbox);
-		self.operation.get_stream(bbox).await
+		self
+			.operation
+			.get_stream_cancellable(bbox, self.cancellation_token.clone())
+			.await
+	}
+}
+
+/// Returns the only tile of `stream`, or `None` if it is empty.
+async fn single_tile(stream: TileStream<'_, Tile>) -> Result<Option<Tile>> {
+	let mut vec = stream.to_vec().await;
+
+	ensure!(vec.len() <= 1, "PipelineReader should return at most one tile");
+
+	if let Some((_, b)) = vec.pop() {
+		Ok(Some(b))
+	} else {
+		Ok(None)
 	}
 }
 
@@ -218,6 +242,32 @@ mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_tile_pipeline_reader_get_tile_stream_cancelled() -> Result<()> {
+		let config = ProcessingConfig::default();
+		config.cancellation_token.cancel();
+		let reader = PipelineReader::open_str(VPL, Path::new("../testdata/"), config).await?;
+		let bbox = TileBBox::from_min_and_max(1, 0, 0, 1, 1)?;
+		let result = reader.get_tile_stream(bbox).await?.to_vec().await;
+
+		assert!(result.is_empty());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_tile_pipeline_reader_get_tile_cancellable() -> Result<()> {
+		let reader = PipelineReader::open_str(VPL, Path::new("../testdata/"), ProcessingConfig::default()).await?;
+		let coord = TileCoord::new(14, 8800, 5377)?;
+
+		let token = CancellationToken::new();
+		assert!(reader.get_tile_cancellable(&coord, token.clone()).await?.is_some());
+
+		token.cancel();
+		assert!(reader.get_tile_cancellable(&coord, token).await?.is_none());
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_pipeline_reader_trait_and_debug() -> Result<()> {
 		let reader = PipelineReader::open_str(VPL, Path::new("../testdata/"), ProcessingConfig::default()).await?;
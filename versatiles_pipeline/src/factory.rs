@@ -11,21 +11,24 @@
 //! a "dummy" mode that resolves filenames to synthetic vector/raster sources.
 
 use crate::{
-	helpers::{dummy_image_source::DummyImageSource, dummy_vector_source::DummyVectorSource},
+	helpers::{TracedOperation, dummy_image_source::DummyImageSource, dummy_vector_source::DummyVectorSource},
 	operations::{get_read_operation_factories, get_transform_operation_factories},
 	traits::{OperationTrait, ReadOperationFactoryTrait, TransformOperationFactoryTrait},
 	vpl::{VPLNode, VPLPipeline, parse_vpl},
 };
 use anyhow::{Result, anyhow, bail};
-use futures::future::BoxFuture;
+use futures::future::{BoxFuture, join_all};
 use itertools::Itertools;
 use std::{
 	collections::HashMap,
 	path::{Path, PathBuf},
 	vec,
 };
-use versatiles_container::{ProcessingConfig, TilesReaderTrait};
-use versatiles_core::{TileFormat, TileType};
+use versatiles_container::{ProcessingConfig, TilesReaderTrait, uri_scheme};
+use versatiles_core::{
+	TileFormat, TileType,
+	json::{JsonObject, JsonValue},
+};
 use versatiles_derive::context;
 
 /// Callback used to resolve a filename/URL into a concrete [`TilesReaderTrait`].
@@ -128,9 +131,11 @@ impl PipelineFactory {
 	}
 
 	/// Resolves `filename` relative to `dir` and invokes `create_reader` to open a container.
+	///
+	/// URIs like `https://…` or `s3://…` are passed on unchanged.
 	#[context("Failed to get reader for file '{}'", filename)]
 	pub async fn get_reader(&self, filename: &str) -> Result<Box<dyn TilesReaderTrait>> {
-		(self.create_reader.as_ref())(self.dir.join(filename).to_string_lossy().to_string()).await
+		(self.create_reader.as_ref())(self.resolve_filename(filename)).await
 	}
 
 	/// Parses VPL text and builds the corresponding operation graph.
@@ -156,7 +161,25 @@ impl PipelineFactory {
 		Ok(vpl_operation)
 	}
 
+	/// Builds the source pipelines of an operation, e.g. the sources of `from_stacked`.
+	///
+	/// Each operation is returned with a name for error messages, e.g. `source 2 (from_container filename="b.pmtiles")`.
+	pub async fn build_sources(&self, pipelines: Vec<VPLPipeline>) -> Result<Vec<(String, Box<dyn OperationTrait>)>> {
+		let names = pipelines
+			.iter()
+			.enumerate()
+			.map(|(index, pipeline)| format!("source {} ({})", index + 1, pipeline.describe()))
+			.collect::<Vec<_>>();
+		let operations = join_all(pipelines.into_iter().map(|pipeline| self.build_pipeline(pipeline)))
+			.await
+			.into_iter()
+			.collect::<Result<Vec<_>>>()?;
+		Ok(names.into_iter().zip(operations).collect())
+	}
+
 	/// Instantiates a read operation from a VPL node using the registered factory.
+	///
+	/// The operation is wrapped in a [`TracedOperation`].
 	#[context("Failed to create read operation from VPL node")]
 	async fn read_operation_from_node(&self, node: VPLNode) -> Result<Box<dyn OperationTrait>> {
 		let factory = self
@@ -164,10 +187,13 @@ impl PipelineFactory {
 			.get(&node.name)
 			.ok_or_else(|| anyhow!("read operation '{}' unknown", node.name))?;
 
-		factory.build(node, self).await
+		let name = node.name.clone();
+		Ok(TracedOperation::wrap(&name, factory.build(node, self).await?))
 	}
 
 	/// Instantiates a transform operation from a VPL node using the registered factory.
+	///
+	/// The operation is wrapped in a [`TracedOperation`].
 	#[context("Failed to create transform operation from VPL node")]
 	async fn tran_operation_from_node(
 		&self,
@@ -179,12 +205,18 @@ impl PipelineFactory {
 			.get(&node.name)
 			.ok_or_else(|| anyhow!("transform operation '{}' unknown", node.name))?;
 
-		factory.build(node, source, self).await
+		let name = node.name.clone();
+		Ok(TracedOperation::wrap(&name, factory.build(node, source, self).await?))
 	}
 
 	/// Returns the absolute/normalized string path for a VPL-referenced `filename`.
+	///
+	/// URIs like `https://…` or `s3://…` are returned unchanged.
 	pub fn resolve_filename(&self, filename: &str) -> String {
-		String::from(self.resolve_path(filename).to_str().unwrap())
+		if uri_scheme(filename).is_some() {
+			return filename.to_string();
+		}
+		self.resolve_path(filename).to_string_lossy().to_string()
 	}
 
 	/// Resolves a VPL-referenced `filename` against `dir` and returns a `PathBuf`.
@@ -214,6 +246,33 @@ impl PipelineFactory {
 		.join("\n")
 	}
 
+	/// Returns the schemas of all registered operations as JSON, e.g. for building user interfaces.
+	///
+	/// Every operation is an object with `name`, `kind` (`"read"` or `"transform"`), `description`,
+	/// `parameters` (each with `name`, `type`, `required`, `description` and, if defined, `default`,
+	/// `minimum`, `maximum` or the enum `values`) and, if it takes a list of pipelines, `sources`.
+	pub fn help_json(&self) -> Result<JsonValue> {
+		let read_ops = self
+			.read_ops
+			.values()
+			.map(|f| (f.get_tag_name(), "read", f.get_schema()));
+		let tran_ops = self
+			.tran_ops
+			.values()
+			.map(|f| (f.get_tag_name(), "transform", f.get_schema()));
+		let operations = read_ops
+			.chain(tran_ops)
+			.sorted_by_key(|(name, kind, _)| (*kind, *name))
+			.map(|(name, kind, schema)| {
+				let mut operation = JsonObject::parse_str(&schema)?;
+				operation.set("name", name);
+				operation.set("kind", kind);
+				Ok(JsonValue::from(operation))
+			})
+			.collect::<Result<Vec<_>>>()?;
+		Ok(JsonValue::from(operations))
+	}
+
 	/// Returns the processing configuration associated with this factory.
 	pub fn config(&self) -> &ProcessingConfig {
 		&self.config
@@ -3,7 +3,11 @@ mod arrange_tiles;
 mod csv;
 pub mod dummy_image_source;
 pub mod dummy_vector_source;
+mod resolution;
+mod traced_operation;
 
 #[cfg(test)]
 pub use arrange_tiles::*;
 pub use csv::*;
+pub use resolution::*;
+pub use traced_operation::*;
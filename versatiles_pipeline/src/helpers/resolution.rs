@@ -0,0 +1,30 @@
+//! Matching the resolution of raster sources to WebMercator zoom levels.
+
+use std::f64::consts::PI;
+use versatiles_core::EARTH_RADIUS;
+
+/// Length of the equator in WebMercator meters.
+pub const EARTH_CIRCUMFERENCE: f64 = 2.0 * PI * EARTH_RADIUS;
+
+/// The lowest zoom level at which tiles of `tile_size` pixels have at least a resolution of
+/// `meters_per_pixel` at the equator.
+pub fn native_level(meters_per_pixel: f64, tile_size: u32) -> u8 {
+	let level = (EARTH_CIRCUMFERENCE / (f64::from(tile_size) * meters_per_pixel)).log2() - 1e-6;
+	level.ceil().clamp(0.0, 30.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn levels() {
+		let level0 = EARTH_CIRCUMFERENCE / 256.0;
+		assert_eq!(native_level(level0, 256), 0);
+		assert_eq!(native_level(level0 / 2.0, 256), 1);
+		assert_eq!(native_level(level0 / 2.0, 512), 0);
+		assert_eq!(native_level(level0 / 3.0, 256), 2);
+		assert_eq!(native_level(level0 * 2.0, 256), 0);
+		assert_eq!(native_level(1e-9, 256), 30);
+	}
+}
@@ -0,0 +1,78 @@
+//! Wraps pipeline operations in `tracing` spans.
+//!
+//! Every operation built by the [`PipelineFactory`](crate::PipelineFactory) is wrapped in a
+//! [`TracedOperation`]. Each call of `get_stream` opens an `operation` span with the VPL tag
+//! name and the bbox. The span is entered while the stream is created and whenever it is
+//! polled, so a subscriber (e.g. `versatiles --trace`) can show where the time of a pipeline is spent.
+
+use crate::traits::OperationTrait;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::fmt::Debug;
+use tracing::Instrument;
+use versatiles_container::Tile;
+use versatiles_core::{TileBBox, TileJSON, TileStream, TilesReaderParameters, Traversal};
+
+/// An operation that records a `tracing` span for every stream of its inner operation.
+pub struct TracedOperation {
+	name: String,
+	inner: Box<dyn OperationTrait>,
+}
+
+impl TracedOperation {
+	/// Wraps `inner`, using `name` (the VPL tag name) to label its spans.
+	pub fn wrap(name: &str, inner: Box<dyn OperationTrait>) -> Box<dyn OperationTrait> {
+		Box::new(TracedOperation {
+			name: name.to_string(),
+			inner,
+		})
+	}
+}
+
+/// Debug output is the one of the inner operation, so the wrapper stays invisible.
+impl Debug for TracedOperation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.inner.fmt(f)
+	}
+}
+
+#[async_trait]
+impl OperationTrait for TracedOperation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		self.inner.parameters()
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		self.inner.tilejson()
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.inner.traversal()
+	}
+
+	async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		let span = tracing::info_span!("operation", name = self.name.as_str(), bbox = ?bbox);
+		let stream = self.inner.get_stream(bbox).instrument(span.clone()).await?;
+		Ok(stream.in_span(span))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::PipelineFactory;
+
+	#[tokio::test]
+	async fn delegates_to_inner_operation() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let inner = factory.operation_from_vpl("from_debug format=png").await?;
+		let op = TracedOperation::wrap("from_debug", factory.operation_from_vpl("from_debug format=png").await?);
+
+		assert_eq!(op.parameters(), inner.parameters());
+		assert_eq!(format!("{op:?}"), format!("{inner:?}"));
+
+		let bbox = TileBBox::from_min_and_max(2, 0, 0, 1, 1)?;
+		assert_eq!(op.get_stream(bbox).await?.to_vec().await.len(), 4);
+		Ok(())
+	}
+}
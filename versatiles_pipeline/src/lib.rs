@@ -7,6 +7,10 @@
 //! The main entry points are [`PipelineFactory`] (for building operation graphs from VPL) and [`PipelineReader`] (for executing them via the container interface).
 //!
 //! This crate integrates tightly with [`versatiles_container`] and [`versatiles_core`] for tile I/O and metadata management.
+//!
+//! # Features
+//! - `gdal`: enables the `from_gdal_raster` read operation.
+//! - `test`: exposes synthetic tile sources in [`testing`] for integration tests in downstream crates.
 
 mod container_reader;
 mod factory;
@@ -17,5 +21,13 @@ mod vpl;
 
 pub use container_reader::*;
 pub use factory::PipelineFactory;
+pub use operations::context::{BLOCK_SIZE, ContextRunnerTrait, ContextTransformOp, TileContext, TileContextKind};
 pub use traits::OperationTrait;
 pub use vpl::VPLNode;
+
+/// Synthetic raster and vector tile sources, e.g. for writing fixtures with
+/// [`versatiles_container::ContainerRegistry::write_to_path`].
+#[cfg(any(test, feature = "test"))]
+pub mod testing {
+	pub use crate::helpers::{dummy_image_source::DummyImageSource, dummy_vector_source::DummyVectorSource};
+}
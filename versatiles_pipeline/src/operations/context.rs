@@ -0,0 +1,388 @@
+//! Transforms that see the tiles around each tile.
+//!
+//! Most transforms work on a single tile. Some can not: a blur or a hillshading needs the pixels on the other side
+//! of the tile edges to avoid visible seams, and label logic needs the features of the neighbouring tiles.
+//! Such a transform implements [`ContextRunnerTrait`]. It declares which [`TileContextKind`] it needs and is called
+//! with a [`TileContext`] for every tile: the tile itself together with its 8 neighbours or with its parent tile.
+//!
+//! [`ContextTransformOp`] handles the additional reads. It splits the requested bbox into blocks of
+//! [`BLOCK_SIZE`]×[`BLOCK_SIZE`] tiles and reads every block only once, together with a border of one tile or with
+//! the parent tiles of the block. The tiles of a block are decoded once and shared by all tiles of the block,
+//! so only the tiles in the border are read twice, by the blocks on both sides.
+
+use crate::traits::OperationTrait;
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use futures::{StreamExt, stream};
+use imageproc::image::{DynamicImage, GenericImage, GenericImageView};
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+use versatiles_container::{Tile, TileContent};
+use versatiles_core::{TileBBox, TileCoord, TileFormat, TileJSON, TileStream, TilesReaderParameters, Traversal};
+use versatiles_derive::context;
+
+/// Width and height of the blocks, in tiles, that are read at once.
+///
+/// All decoded tiles of a block are kept in memory until the block is done, so it is kept small.
+pub const BLOCK_SIZE: u32 = 16;
+
+/// The tiles that a [`ContextRunnerTrait`] needs besides the tile itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileContextKind {
+	/// The 8 neighbouring tiles on the same zoom level.
+	Neighbors,
+	/// The tile on the next lower zoom level that covers the tile.
+	Parent,
+}
+
+/// A tile together with the decoded tiles around it.
+pub struct TileContext {
+	coord: TileCoord,
+	tiles: Arc<HashMap<TileCoord, TileContent>>,
+}
+
+impl TileContext {
+	/// The tile itself.
+	#[must_use]
+	pub fn tile(&self) -> &TileContent {
+		&self.tiles[&self.coord]
+	}
+
+	/// The neighbouring tile at the offset `dx`, `dy`, each between -1 and 1, e.g. `(1, -1)` is the tile to
+	/// the top right. Returns `None` if the tile does not exist or is outside the world, or if the runner
+	/// asked for [`TileContextKind::Parent`].
+	#[must_use]
+	pub fn neighbor(&self, dx: i32, dy: i32) -> Option<&TileContent> {
+		if dx.abs() > 1 || dy.abs() > 1 {
+			return None;
+		}
+		let x = u32::try_from(i64::from(self.coord.x) + i64::from(dx)).ok()?;
+		let y = u32::try_from(i64::from(self.coord.y) + i64::from(dy)).ok()?;
+		let coord = TileCoord::new(self.coord.level, x, y).ok()?;
+		self.tiles.get(&coord)
+	}
+
+	/// The parent tile on the next lower zoom level. Returns `None` for tiles on zoom level 0, if the parent
+	/// does not exist, or if the runner asked for [`TileContextKind::Neighbors`].
+	#[must_use]
+	pub fn parent(&self) -> Option<&TileContent> {
+		let coord = self.coord.as_level_decreased().ok()?;
+		self.tiles.get(&coord)
+	}
+
+	/// Returns the raster image of the tile, extended by `border` pixels on every side with the pixels of the
+	/// neighbouring tiles. Where a neighbour is missing, the edge pixels of the tile are repeated instead.
+	///
+	/// # Errors
+	/// Returns an error if the tiles are not raster tiles, if their sizes differ,
+	/// or if `border` is larger than the tile.
+	#[context("Failed to extend the image of tile {:?} by a border of {border} pixels", self.coord)]
+	pub fn image_with_border(&self, border: u32) -> Result<DynamicImage> {
+		let center = self.tile().as_image()?;
+		let (width, height) = center.dimensions();
+		ensure!(
+			border <= width && border <= height,
+			"border is larger than the tile of {width}x{height} pixels"
+		);
+
+		let mut neighbors = [[None; 3]; 3];
+		for (row, dy) in neighbors.iter_mut().zip(-1..=1) {
+			for (slot, dx) in row.iter_mut().zip(-1..=1) {
+				if let Some(neighbor) = self.neighbor(dx, dy) {
+					let image = neighbor.as_image()?;
+					ensure!(
+						image.dimensions() == (width, height),
+						"neighbouring tile has {}x{} pixels instead of {width}x{height}",
+						image.width(),
+						image.height()
+					);
+					*slot = Some(image);
+				}
+			}
+		}
+
+		// Returns the index of the tile (0, 1 or 2) and the position in it, or the clamped position in the center.
+		let split = |v: u32, size: u32| -> (usize, u32, u32) {
+			if v < border {
+				(0, v + size - border, 0)
+			} else if v < border + size {
+				(1, v - border, v - border)
+			} else {
+				(2, v - border - size, size - 1)
+			}
+		};
+
+		let mut image = DynamicImage::new(width + 2 * border, height + 2 * border, center.color());
+		image.copy_from(center, border, border)?;
+		for y in 0..image.height() {
+			let (row, y_neighbor, y_center) = split(y, height);
+			for x in 0..image.width() {
+				let (column, x_neighbor, x_center) = split(x, width);
+				if row == 1 && column == 1 {
+					continue;
+				}
+				let pixel = match neighbors[row][column] {
+					Some(neighbor) => neighbor.get_pixel(x_neighbor, y_neighbor),
+					None => center.get_pixel(x_center, y_center),
+				};
+				image.put_pixel(x, y, pixel);
+			}
+		}
+		Ok(image)
+	}
+}
+
+/// A transform that needs the tiles around each tile, see the [module documentation](self).
+pub trait ContextRunnerTrait: Debug + Send + Sync + 'static {
+	/// The tiles that [`ContextRunnerTrait::run`] needs besides the tile itself.
+	fn kind(&self) -> TileContextKind;
+
+	/// Transforms the tile of `context`. Returning `None` removes the tile.
+	fn run(&self, context: &TileContext) -> Result<Option<TileContent>>;
+}
+
+/// An operation that calls a [`ContextRunnerTrait`] for every tile of its source.
+#[derive(Debug)]
+pub struct ContextTransformOp<R: ContextRunnerTrait> {
+	runner: Arc<R>,
+	source: Box<dyn OperationTrait>,
+}
+
+impl<R: ContextRunnerTrait> ContextTransformOp<R> {
+	pub fn new(source: Box<dyn OperationTrait>, runner: R) -> Self {
+		Self {
+			runner: Arc::new(runner),
+			source,
+		}
+	}
+
+	/// Reads and decodes the tiles of `bbox`, or nothing if `bbox` is empty.
+	async fn read_tiles(&self, bbox: TileBBox, tiles: &mut HashMap<TileCoord, TileContent>) -> Result<()> {
+		if bbox.is_empty() {
+			return Ok(());
+		}
+		let stream = self.source.get_stream(bbox).await?.map_item_parallel(into_content);
+		tiles.extend(stream.to_vec().await);
+		Ok(())
+	}
+
+	/// Reads the tiles of `block` together with the tiles around them.
+	#[context("Failed to read the tiles around block {:?}", block)]
+	async fn read_block(&self, block: TileBBox, buffered: TileBBox) -> Result<TileStream<'_, TileContext>> {
+		let mut tiles = HashMap::new();
+		match self.runner.kind() {
+			TileContextKind::Neighbors => self.read_tiles(buffered, &mut tiles).await?,
+			TileContextKind::Parent => {
+				self.read_tiles(block, &mut tiles).await?;
+				if block.level > 0 {
+					self.read_tiles(block.leveled_down(), &mut tiles).await?;
+				}
+			}
+		}
+
+		let coords: Vec<TileCoord> = block.iter_coords().filter(|coord| tiles.contains_key(coord)).collect();
+		let tiles = Arc::new(tiles);
+		Ok(TileStream::from_vec(
+			coords
+				.into_iter()
+				.map(|coord| {
+					let context = TileContext {
+						coord,
+						tiles: tiles.clone(),
+					};
+					(coord, context)
+				})
+				.collect(),
+		))
+	}
+}
+
+/// Decodes a tile, so it can be shared by all tiles of a block.
+fn into_content(tile: Tile) -> Result<TileContent> {
+	Ok(if tile.format().is_raster() {
+		TileContent::from_image(tile.into_image()?)
+	} else {
+		TileContent::from_vector(tile.into_vector()?)
+	})
+}
+
+fn into_tile(content: TileContent, format: TileFormat) -> Result<Tile> {
+	match content {
+		TileContent::Raster(image) => Tile::from_image(image, format),
+		TileContent::Vector(vector) => Tile::from_vector(vector, format),
+	}
+}
+
+#[async_trait]
+impl<R: ContextRunnerTrait> OperationTrait for ContextTransformOp<R> {
+	fn parameters(&self) -> &TilesReaderParameters {
+		self.source.parameters()
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		self.source.tilejson()
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.source.traversal()
+	}
+
+	#[context("Failed to get context transformed tile stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+
+		let blocks: Vec<(TileBBox, TileBBox)> = bbox.iter_bbox_grid_buffered(BLOCK_SIZE, 1).collect();
+		let runner = self.runner.clone();
+		let format = self.source.parameters().tile_format;
+
+		Ok(TileStream::from_try_streams(
+			stream::iter(blocks).map(move |(block, buffered)| self.read_block(block, buffered)),
+		)
+		.filter_map_parallel(move |_coord, context| {
+			runner
+				.run(&context)?
+				.map(|content| into_tile(content, format))
+				.transpose()
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::helpers::dummy_image_source::DummyImageSource;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use versatiles_core::{TileBBoxPyramid, TileFormat::PNG};
+	use versatiles_image::traits::*;
+
+	/// Counts the calls of `get_stream`.
+	#[derive(Debug)]
+	struct CountingSource {
+		source: DummyImageSource,
+		calls: Arc<AtomicUsize>,
+	}
+
+	#[async_trait]
+	impl OperationTrait for CountingSource {
+		fn parameters(&self) -> &TilesReaderParameters {
+			self.source.parameters()
+		}
+		fn tilejson(&self) -> &TileJSON {
+			self.source.tilejson()
+		}
+		async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+			self.calls.fetch_add(1, Ordering::SeqCst);
+			self.source.get_stream(bbox).await
+		}
+	}
+
+	/// Colors every tile with the number of neighbours, or with 1 if it has a parent.
+	#[derive(Debug)]
+	struct CountingRunner(TileContextKind);
+
+	impl ContextRunnerTrait for CountingRunner {
+		fn kind(&self) -> TileContextKind {
+			self.0
+		}
+		fn run(&self, context: &TileContext) -> Result<Option<TileContent>> {
+			let count = match self.0 {
+				TileContextKind::Neighbors => (-1..=1)
+					.flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+					.filter(|&(dx, dy)| (dx, dy) != (0, 0) && context.neighbor(dx, dy).is_some())
+					.count(),
+				TileContextKind::Parent => usize::from(context.parent().is_some()),
+			};
+			let mut image = context.tile().as_image()?.clone();
+			image.mut_pixel_colors(|_| [count as u8; 3])?;
+			Ok(Some(TileContent::from_image(image)))
+		}
+	}
+
+	fn operation(
+		kind: TileContextKind,
+		pyramid: TileBBoxPyramid,
+	) -> Result<(ContextTransformOp<CountingRunner>, Arc<AtomicUsize>)> {
+		let calls = Arc::new(AtomicUsize::new(0));
+		let source = CountingSource {
+			source: DummyImageSource::from_color(&[0, 0, 0], 4, PNG, Some(pyramid))?,
+			calls: calls.clone(),
+		};
+		Ok((ContextTransformOp::new(Box::new(source), CountingRunner(kind)), calls))
+	}
+
+	async fn counts(op: &ContextTransformOp<CountingRunner>, bbox: TileBBox) -> Result<HashMap<TileCoord, u8>> {
+		let mut colors = HashMap::new();
+		for (coord, tile) in op.get_stream(bbox).await?.to_vec().await {
+			colors.insert(coord, tile.into_image()?.average_color()[0]);
+		}
+		Ok(colors)
+	}
+
+	#[tokio::test]
+	async fn neighbors() -> Result<()> {
+		let mut pyramid = TileBBoxPyramid::new_empty();
+		pyramid.set_level_bbox(TileBBox::from_min_and_max(3, 2, 2, 4, 4)?);
+		let (op, calls) = operation(TileContextKind::Neighbors, pyramid)?;
+
+		let colors = counts(&op, TileBBox::from_min_and_max(3, 0, 0, 7, 7)?).await?;
+		assert_eq!(colors.len(), 9);
+		assert_eq!(colors[&TileCoord::new(3, 2, 2)?], 3);
+		assert_eq!(colors[&TileCoord::new(3, 3, 2)?], 5);
+		assert_eq!(colors[&TileCoord::new(3, 3, 3)?], 8);
+		assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+		// neighbours outside the requested bbox are read as well
+		let colors = counts(&op, TileBBox::from_min_and_max(3, 3, 3, 3, 3)?).await?;
+		assert_eq!(colors[&TileCoord::new(3, 3, 3)?], 8);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn reads_every_block_once() -> Result<()> {
+		let (op, calls) = operation(TileContextKind::Neighbors, TileBBoxPyramid::new_full(8))?;
+		let colors = counts(&op, TileBBox::from_min_and_max(8, 16, 16, 63, 63)?).await?;
+		assert_eq!(colors.len(), 2304);
+		assert!(colors.values().all(|&count| count == 8));
+		assert_eq!(calls.load(Ordering::SeqCst), 9);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn parent() -> Result<()> {
+		let mut pyramid = TileBBoxPyramid::new_empty();
+		pyramid.set_level_bbox(TileBBox::from_min_and_max(0, 0, 0, 0, 0)?);
+		pyramid.set_level_bbox(TileBBox::from_min_and_max(1, 0, 0, 1, 1)?);
+		pyramid.set_level_bbox(TileBBox::from_min_and_max(2, 0, 0, 1, 1)?);
+		let (op, _) = operation(TileContextKind::Parent, pyramid)?;
+
+		assert_eq!(counts(&op, TileBBox::new_full(0)?).await?[&TileCoord::new(0, 0, 0)?], 0);
+		assert!(counts(&op, TileBBox::new_full(1)?).await?.values().all(|&v| v == 1));
+		let colors = counts(&op, TileBBox::new_full(2)?).await?;
+		assert_eq!(colors.len(), 4);
+		assert!(colors.values().all(|&v| v == 1));
+		Ok(())
+	}
+
+	#[test]
+	fn image_with_border() -> Result<()> {
+		let tile = |value: u8| TileContent::from_image(DynamicImage::from_fn(2, 2, |x, y| [value + (x + 2 * y) as u8]));
+		let center = TileCoord::new(2, 1, 1)?;
+		let mut tiles = HashMap::new();
+		tiles.insert(center, tile(10));
+		tiles.insert(TileCoord::new(2, 2, 1)?, tile(20));
+		let context = TileContext {
+			coord: center,
+			tiles: Arc::new(tiles),
+		};
+
+		let image = context.image_with_border(1)?;
+		assert_eq!(image.dimensions(), (4, 4));
+		let rows: Vec<Vec<u8>> = image.to_luma8().rows().map(|row| row.map(|p| p[0]).collect()).collect();
+		assert_eq!(
+			rows,
+			[[10, 10, 11, 11], [10, 10, 11, 20], [12, 12, 13, 22], [12, 12, 13, 13]]
+		);
+
+		assert!(context.image_with_border(3).is_err());
+		Ok(())
+	}
+}
@@ -1,20 +1,25 @@
 use crate::{PipelineFactory, traits::*, vpl::VPLNode};
 use anyhow::{Result, bail};
 use async_trait::async_trait;
-use std::fmt::Debug;
+use futures::{StreamExt, future, stream};
+use std::{fmt::Debug, sync::Arc};
 use versatiles_container::Tile;
 use versatiles_core::*;
 use versatiles_derive::context;
 
 #[derive(versatiles_derive::VPLDecode, Clone, Debug)]
-/// Filter tiles by bounding box and/or zoom levels.
+/// Filter tiles by bounding box, zoom levels and/or a list of tiles.
+/// All given filters are combined, so only tiles matching all of them are kept.
 struct Args {
 	/// Bounding box in WGS84: [min lng, min lat, max lng, max lat].
+	/// If min lng is greater than max lng, the bounding box crosses the antimeridian.
 	bbox: Option<[f64; 4]>,
 	/// minimal zoom level
 	level_min: Option<u8>,
 	/// maximal zoom level
 	level_max: Option<u8>,
+	/// Path to a CSV file listing the tiles to keep, with the columns `z,x,y`, or with PMTiles tile ids or ranges of them (`first-last`) in a single column. A header line is optional.
+	tiles: Option<String>,
 }
 
 #[derive(Debug)]
@@ -22,11 +27,14 @@ struct Operation {
 	parameters: TilesReaderParameters,
 	source: Box<dyn OperationTrait>,
 	tilejson: TileJSON,
+	tile_list: Option<Arc<TileCoordList>>,
+	/// For a bounding box crossing the antimeridian: the pyramids of its western and eastern part.
+	bbox_parts: Option<Vec<TileBBoxPyramid>>,
 }
 
 impl Operation {
 	#[context("Building filter operation in VPL node {:?}", vpl_node.name)]
-	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, _factory: &PipelineFactory) -> Result<Operation>
+	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, factory: &PipelineFactory) -> Result<Operation>
 	where
 		Self: Sized + OperationTrait,
 	{
@@ -50,8 +58,29 @@ impl Operation {
 			parameters.bbox_pyramid.set_level_max(level_max);
 		}
 
-		if let Some(bbox) = args.bbox {
-			parameters.bbox_pyramid.intersect_geo_bbox(&GeoBBox::try_from(&bbox)?)?;
+		let mut geo_bbox = None;
+		let mut bbox_parts = None;
+		if let Some(b) = args.bbox {
+			let bbox = GeoBBox::new_wrapping(b[0], b[1], b[2], b[3])?;
+			if bbox.crosses_antimeridian() {
+				let mut parts = Vec::new();
+				for part in bbox.split_antimeridian() {
+					let mut pyramid = parameters.bbox_pyramid.clone();
+					pyramid.intersect_geo_bbox(&part)?;
+					parts.push(pyramid);
+				}
+				bbox_parts = Some(parts);
+				geo_bbox = Some(bbox);
+			}
+			parameters.bbox_pyramid.intersect_geo_bbox(&bbox)?;
+		}
+
+		let mut tile_list = None;
+		if let Some(filename) = args.tiles {
+			let mut list = TileCoordList::from_csv_path(&factory.resolve_path(&filename))?;
+			list.retain(|coord| parameters.bbox_pyramid.contains_coord(coord));
+			parameters.bbox_pyramid.intersect(list.bbox_pyramid());
+			tile_list = Some(Arc::new(list));
 		}
 
 		if parameters.bbox_pyramid.is_empty() {
@@ -63,11 +92,17 @@ impl Operation {
 
 		let mut tilejson = source.tilejson().clone();
 		tilejson.update_from_reader_parameters(&parameters);
+		if let Some(bbox) = geo_bbox {
+			// the pyramid spans the whole width, so limit the bounds to the crossing bounding box
+			tilejson.limit_bbox(bbox);
+		}
 
 		Ok(Self {
 			parameters,
 			source,
 			tilejson,
+			tile_list,
+			bbox_parts,
 		})
 	}
 }
@@ -92,7 +127,27 @@ impl OperationTrait for Operation {
 		if bbox.is_empty() {
 			return Ok(TileStream::empty());
 		}
-		Ok(self.source.get_stream(bbox).await?)
+		let stream = match &self.bbox_parts {
+			Some(parts) => {
+				let mut streams = Vec::new();
+				for part in parts {
+					let mut part_bbox = bbox;
+					part_bbox.intersect_with_pyramid(part);
+					if !part_bbox.is_empty() {
+						streams.push(self.source.get_stream(part_bbox).await?);
+					}
+				}
+				TileStream::from_streams(stream::iter(streams).map(future::ready))
+			}
+			None => self.source.get_stream(bbox).await?,
+		};
+		Ok(match &self.tile_list {
+			Some(list) => {
+				let list = list.clone();
+				stream.filter_coord(move |coord| std::future::ready(list.contains(&coord)))
+			}
+			None => stream,
+		})
 	}
 }
 
@@ -102,6 +157,9 @@ impl OperationFactoryTrait for Factory {
 	fn get_docs(&self) -> String {
 		Args::get_docs()
 	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
 	fn get_tag_name(&self) -> &str {
 		"filter"
 	}
@@ -243,4 +301,61 @@ mod tests {
 
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn test_filter_across_antimeridian() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let op = factory
+			.operation_from_vpl("from_debug format=mvt | filter bbox=[170,-20,-170,-10]")
+			.await?;
+
+		let o = op.tilejson().as_object();
+		assert_eq!(&o.get_number_array("bounds")?.unwrap(), &[170.0, -20.0, -170.0, -10.0]);
+
+		let mut coords = op
+			.get_stream(TileBBox::new_full(4)?)
+			.await?
+			.to_vec()
+			.await
+			.into_iter()
+			.map(|(coord, _)| (coord.x, coord.y))
+			.collect::<Vec<_>>();
+		coords.sort();
+		assert_eq!(coords, [(0, 8), (15, 8)]);
+
+		let middle = TileCoord::new(4, 8, 8)?.as_tile_bbox();
+		assert_eq!(op.get_stream(middle).await?.to_vec().await.len(), 0);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_filter_tile_list() -> Result<()> {
+		let dir = assert_fs::TempDir::new()?;
+		let path = dir.path().join("tiles.csv");
+		std::fs::write(&path, "z,x,y\n3,1,2\n3,2,2\n4,3,5\n6,0,0\n")?;
+
+		let op = PipelineFactory::new_dummy()
+			.operation_from_vpl(&format!(
+				"from_debug format=mvt | filter tiles=\"{}\" level_max=5",
+				path.display()
+			))
+			.await?;
+
+		let pyramid = &op.parameters().bbox_pyramid;
+		assert_eq!(pyramid.get_level_min(), Some(3));
+		assert_eq!(pyramid.get_level_max(), Some(4));
+		assert_eq!(op.tilejson().as_object().get_number("maxzoom")?.unwrap(), 4.0);
+
+		let mut coords = op
+			.get_stream(TileBBox::new_full(3)?)
+			.await?
+			.to_vec()
+			.await
+			.into_iter()
+			.map(|(coord, _)| (coord.x, coord.y))
+			.collect::<Vec<_>>();
+		coords.sort();
+		assert_eq!(coords, [(1, 2), (2, 2)]);
+		Ok(())
+	}
 }
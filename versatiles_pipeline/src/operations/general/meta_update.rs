@@ -87,6 +87,9 @@ impl OperationFactoryTrait for Factory {
 	fn get_docs(&self) -> String {
 		Args::get_docs()
 	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
 	fn get_tag_name(&self) -> &str {
 		"meta_update"
 	}
@@ -1,2 +1,4 @@
 pub mod filter;
 pub mod meta_update;
+pub mod retry;
+pub mod sample;
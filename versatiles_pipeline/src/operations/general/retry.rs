@@ -0,0 +1,303 @@
+use crate::{
+	PipelineFactory,
+	traits::*,
+	vpl::{VPLNode, VPLPipeline},
+};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use std::{fmt::Debug, time::Duration};
+use versatiles_container::Tile;
+use versatiles_core::{progress::ConversionMetrics, *};
+use versatiles_derive::context;
+
+/// Upper limit of the delay between two attempts, so that many attempts don't wait for ages.
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Retries failed requests to the source with an exponential backoff, e.g. for network based sources in long conversions.
+/// If all attempts fail, the tiles are requested from an optional fallback pipeline instead.
+struct Args {
+	/// Number of attempts, including the first one.
+	#[vpl(default = 3, min = 1)]
+	attempts: u8,
+	/// Delay in milliseconds before the first retry. It doubles with every further retry, up to one minute.
+	#[vpl(default = 500)]
+	delay: u32,
+	/// The pipeline that is used if all attempts fail, in parentheses, e.g. `fallback=( from_container filename="backup.versatiles" )`.
+	/// It must produce tiles of the same format as the source.
+	fallback: Option<VPLPipeline>,
+}
+
+#[derive(Debug)]
+struct Operation {
+	parameters: TilesReaderParameters,
+	source: Box<dyn OperationTrait>,
+	fallback: Option<Box<dyn OperationTrait>>,
+	tilejson: TileJSON,
+	attempts: u8,
+	delay: Duration,
+	metrics: ConversionMetrics,
+}
+
+impl Operation {
+	#[context("Building retry operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, factory: &PipelineFactory) -> Result<Operation>
+	where
+		Self: Sized + OperationTrait,
+	{
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		let parameters = source.parameters().clone();
+		let fallback = match args.fallback {
+			Some(pipeline) => {
+				let fallback = factory.build_pipeline(pipeline).await?;
+				ensure!(
+					fallback.parameters().tile_format == parameters.tile_format,
+					"fallback must produce {} tiles, but produces {}",
+					parameters.tile_format,
+					fallback.parameters().tile_format
+				);
+				Some(fallback)
+			}
+			None => None,
+		};
+
+		let tilejson = source.tilejson().clone();
+
+		Ok(Self {
+			parameters,
+			source,
+			fallback,
+			tilejson,
+			attempts: args.attempts,
+			delay: Duration::from_millis(u64::from(args.delay)),
+			metrics: factory.config().metrics.clone(),
+		})
+	}
+
+	/// Requests the tiles of `bbox` from the fallback and converts them to the compression of the source.
+	async fn get_fallback_stream<'a>(
+		&'a self,
+		fallback: &'a dyn OperationTrait,
+		mut bbox: TileBBox,
+	) -> Result<TileStream<'a, Tile>> {
+		bbox.intersect_with_pyramid(&fallback.parameters().bbox_pyramid);
+		if bbox.is_empty() {
+			return Ok(TileStream::empty());
+		}
+		let stream = fallback.get_stream(bbox).await?;
+		let compression = self.parameters.tile_compression;
+		if fallback.parameters().tile_compression == compression {
+			return Ok(stream);
+		}
+		let metrics = self.metrics.clone();
+		Ok(stream.map_item_parallel(move |mut tile| {
+			if tile.has_blob() {
+				metrics.count_compression(tile.compression() == compression);
+			}
+			tile.change_compression(compression)?;
+			Ok(tile)
+		}))
+	}
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		&self.parameters
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		&self.tilejson
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.source.traversal()
+	}
+
+	async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+		let mut delay = self.delay;
+		let mut attempt = 1;
+		let error = loop {
+			match self.source.get_stream(bbox).await {
+				Ok(stream) => return Ok(stream),
+				Err(error) if attempt >= self.attempts => break error,
+				Err(error) => {
+					log::warn!(
+						"attempt {attempt}/{} for {bbox:?} failed, retrying in {delay:?}: {error:#}",
+						self.attempts
+					);
+					tokio::time::sleep(delay).await;
+					delay = next_delay(delay);
+					attempt += 1;
+				}
+			}
+		};
+
+		match &self.fallback {
+			Some(fallback) => {
+				log::warn!(
+					"all {} attempts for {bbox:?} failed, using fallback: {error:#}",
+					self.attempts
+				);
+				self.get_fallback_stream(fallback.as_ref(), bbox).await
+			}
+			None => Err(error.context(format!("all {} attempts for {bbox:?} failed", self.attempts))),
+		}
+	}
+}
+
+/// Doubles `delay`, but not beyond [`MAX_DELAY`].
+fn next_delay(delay: Duration) -> Duration {
+	delay.saturating_mul(2).min(MAX_DELAY)
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"retry"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, source, factory)
+			.await
+			.map(|op| Box::new(op) as Box<dyn OperationTrait>)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::bail;
+	use std::sync::atomic::{AtomicU32, Ordering};
+
+	/// Fails the first `failures` requests, then forwards them to `inner`.
+	#[derive(Debug)]
+	struct FlakySource {
+		inner: Box<dyn OperationTrait>,
+		failures: AtomicU32,
+	}
+
+	#[async_trait]
+	impl OperationTrait for FlakySource {
+		fn parameters(&self) -> &TilesReaderParameters {
+			self.inner.parameters()
+		}
+
+		fn tilejson(&self) -> &TileJSON {
+			self.inner.tilejson()
+		}
+
+		fn traversal(&self) -> &Traversal {
+			self.inner.traversal()
+		}
+
+		async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+			if self
+				.failures
+				.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+				.is_ok()
+			{
+				bail!("connection reset");
+			}
+			self.inner.get_stream(bbox).await
+		}
+	}
+
+	async fn retry_operation(failures: u32, attempts: u8, fallback: Option<&str>) -> Result<Operation> {
+		let factory = PipelineFactory::new_dummy();
+		let source = Box::new(FlakySource {
+			inner: factory.operation_from_vpl("from_debug format=mvt").await?,
+			failures: AtomicU32::new(failures),
+		});
+		let fallback = match fallback {
+			Some(vpl) => Some(factory.operation_from_vpl(vpl).await?),
+			None => None,
+		};
+		Ok(Operation {
+			parameters: source.parameters().clone(),
+			tilejson: source.tilejson().clone(),
+			source,
+			fallback,
+			attempts,
+			delay: Duration::from_millis(1),
+			metrics: ConversionMetrics::new(),
+		})
+	}
+
+	async fn count_tiles(op: &Operation) -> Result<usize> {
+		Ok(op.get_stream(TileBBox::new_full(2)?).await?.to_vec().await.len())
+	}
+
+	#[tokio::test]
+	async fn recovers_after_retries() -> Result<()> {
+		let op = retry_operation(2, 3, None).await?;
+		assert_eq!(count_tiles(&op).await?, 16);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn fails_after_all_attempts() -> Result<()> {
+		let op = retry_operation(5, 3, None).await?;
+		let error = op.get_stream(TileBBox::new_full(2)?).await.err().unwrap();
+		assert!(format!("{error:#}").contains("all 3 attempts"));
+		assert!(format!("{error:#}").contains("connection reset"));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn uses_fallback() -> Result<()> {
+		let op = retry_operation(5, 2, Some("from_debug format=mvt | filter level_max=1")).await?;
+		assert_eq!(count_tiles(&op).await?, 0);
+		let tiles = op.get_stream(TileBBox::new_full(1)?).await?.to_vec().await;
+		assert_eq!(tiles.len(), 4);
+		Ok(())
+	}
+
+	#[test]
+	fn delay_is_capped() {
+		assert_eq!(next_delay(Duration::from_millis(500)), Duration::from_secs(1));
+		assert_eq!(next_delay(Duration::from_secs(40)), MAX_DELAY);
+		assert_eq!(next_delay(Duration::MAX), MAX_DELAY);
+	}
+
+	#[tokio::test]
+	async fn build_from_vpl() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let op = factory
+			.operation_from_vpl("from_debug format=mvt | retry attempts=2 delay=10 fallback=( from_debug format=mvt )")
+			.await?;
+		assert_eq!(op.parameters().tile_format, TileFormat::MVT);
+		assert_eq!(op.get_stream(TileBBox::new_full(1)?).await?.to_vec().await.len(), 4);
+
+		assert!(
+			factory
+				.operation_from_vpl("from_debug format=mvt | retry fallback=( from_debug format=png )")
+				.await
+				.is_err()
+		);
+		assert!(
+			factory
+				.operation_from_vpl("from_debug format=mvt | retry attempts=0")
+				.await
+				.is_err()
+		);
+		Ok(())
+	}
+}
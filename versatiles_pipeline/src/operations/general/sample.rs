@@ -0,0 +1,205 @@
+use crate::{PipelineFactory, traits::*, vpl::VPLNode};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use futures::{StreamExt, stream};
+use std::fmt::Debug;
+use versatiles_container::Tile;
+use versatiles_core::{utils::splitmix64, *};
+use versatiles_derive::context;
+
+/// Below this fraction, the selected tiles are requested one by one instead of filtering the whole stream,
+/// so the source doesn't have to produce the tiles that are dropped anyway.
+const SPARSE_FRACTION: f64 = 0.1;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Passes through only a pseudo-random fraction of the tiles, e.g. for a quick visual check or to estimate
+/// statistics of a huge tile set without processing all tiles.
+/// The selection is deterministic: it only depends on the tile coordinates and the seed.
+struct Args {
+	/// Fraction of tiles to keep, between 0 and 1, e.g. 0.01 for 1% of the tiles.
+	fraction: f64,
+	/// Seed of the selection. Different seeds select different tiles. Defaults to 0.
+	seed: Option<u64>,
+}
+
+/// Deterministic pseudo-random selection of tiles.
+#[derive(Clone, Copy, Debug)]
+struct Sampler {
+	fraction: f64,
+	seed: u64,
+}
+
+impl Sampler {
+	/// Returns `true` if the tile at `coord` is selected.
+	fn keeps(&self, coord: &TileCoord) -> bool {
+		let mut hash = splitmix64(self.seed);
+		for value in [u64::from(coord.level), u64::from(coord.x), u64::from(coord.y)] {
+			hash = splitmix64(hash ^ value);
+		}
+		// use the upper 53 bits as a uniformly distributed number in [0, 1)
+		((hash >> 11) as f64 / (1u64 << 53) as f64) < self.fraction
+	}
+}
+
+#[derive(Debug)]
+struct Operation {
+	parameters: TilesReaderParameters,
+	source: Box<dyn OperationTrait>,
+	tilejson: TileJSON,
+	sampler: Sampler,
+}
+
+impl Operation {
+	#[context("Building sample operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, _factory: &PipelineFactory) -> Result<Operation>
+	where
+		Self: Sized + OperationTrait,
+	{
+		let args = Args::from_vpl_node(&vpl_node)?;
+		ensure!(
+			(0.0..=1.0).contains(&args.fraction),
+			"fraction must be between 0 and 1, but is {}",
+			args.fraction
+		);
+
+		Ok(Self {
+			parameters: source.parameters().clone(),
+			tilejson: source.tilejson().clone(),
+			source,
+			sampler: Sampler {
+				fraction: args.fraction,
+				seed: args.seed.unwrap_or(0),
+			},
+		})
+	}
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		&self.parameters
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		&self.tilejson
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.source.traversal()
+	}
+
+	#[context("Failed to get stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, mut bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+		bbox.intersect_with_pyramid(&self.parameters.bbox_pyramid);
+		if bbox.is_empty() {
+			return Ok(TileStream::empty());
+		}
+
+		let sampler = self.sampler;
+		if sampler.fraction >= SPARSE_FRACTION {
+			return Ok(self
+				.source
+				.get_stream(bbox)
+				.await?
+				.filter_coord(move |coord| std::future::ready(sampler.keeps(&coord))));
+		}
+
+		let coords: Vec<TileCoord> = bbox.iter_coords().filter(|coord| sampler.keeps(coord)).collect();
+		Ok(TileStream::from_try_streams(
+			stream::iter(coords).map(move |coord| self.source.get_stream(coord.as_tile_bbox())),
+		))
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"sample"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, source, factory)
+			.await
+			.map(|op| Box::new(op) as Box<dyn OperationTrait>)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::helpers::dummy_image_source::DummyImageSource;
+
+	async fn sampled_coords(fraction: f64, seed: u64) -> Result<Vec<TileCoord>> {
+		let source = DummyImageSource::from_color(&[255, 0, 0], 1, TileFormat::PNG, None)?;
+		let op = Operation::build(
+			VPLNode::try_from_str(&format!("sample fraction={fraction} seed={seed}"))?,
+			Box::new(source),
+			&PipelineFactory::new_dummy(),
+		)
+		.await?;
+		let mut coords: Vec<TileCoord> = op
+			.get_stream(TileBBox::new_full(5)?)
+			.await?
+			.to_vec()
+			.await
+			.into_iter()
+			.map(|(coord, _)| coord)
+			.collect();
+		coords.sort_by_key(|coord| (coord.level, coord.y, coord.x));
+		Ok(coords)
+	}
+
+	#[test]
+	fn sampler_fraction() -> Result<()> {
+		for fraction in [0.001, 0.05, 0.3, 0.9] {
+			let sampler = Sampler { fraction, seed: 7 };
+			let bbox = TileBBox::new_full(9)?;
+			let count = bbox.iter_coords().filter(|coord| sampler.keeps(coord)).count();
+			let share = count as f64 / bbox.count_tiles() as f64;
+			assert!(
+				(share - fraction).abs() < 0.01 + fraction * 0.1,
+				"{share} != {fraction}"
+			);
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn deterministic() -> Result<()> {
+		let coords = sampled_coords(0.2, 1).await?;
+		assert!(!coords.is_empty());
+		assert_eq!(coords, sampled_coords(0.2, 1).await?);
+		assert_ne!(coords, sampled_coords(0.2, 2).await?);
+
+		// a smaller fraction selects a subset of the tiles
+		let subset = sampled_coords(0.05, 1).await?;
+		assert!(!subset.is_empty());
+		assert!(subset.iter().all(|coord| coords.contains(coord)));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn edge_cases() -> Result<()> {
+		assert!(sampled_coords(0.0, 0).await?.is_empty());
+		assert_eq!(sampled_coords(1.0, 0).await?.len(), 1024);
+		assert!(sampled_coords(1.5, 0).await.is_err());
+		assert!(sampled_coords(-0.1, 0).await.is_err());
+		Ok(())
+	}
+}
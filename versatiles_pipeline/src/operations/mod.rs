@@ -1,3 +1,4 @@
+pub mod context;
 mod general;
 mod raster;
 mod read;
@@ -9,24 +10,48 @@ pub fn get_transform_operation_factories() -> Vec<Box<dyn TransformOperationFact
 	vec![
 		Box::new(general::filter::Factory {}),
 		Box::new(general::meta_update::Factory {}),
+		Box::new(general::retry::Factory {}),
+		Box::new(general::sample::Factory {}),
+		Box::new(raster::raster_blur::Factory {}),
+		Box::new(raster::raster_channel_mix::Factory {}),
+		Box::new(raster::raster_colorize::Factory {}),
 		Box::new(raster::raster_flatten::Factory {}),
 		Box::new(raster::raster_format::Factory {}),
+		Box::new(raster::raster_grayscale::Factory {}),
+		Box::new(raster::raster_invert::Factory {}),
 		Box::new(raster::raster_levels::Factory {}),
+		Box::new(raster::raster_mask::Factory {}),
 		Box::new(raster::raster_overscale::Factory {}),
 		Box::new(raster::raster_overview::Factory {}),
+		Box::new(raster::raster_retile::Factory {}),
+		Box::new(raster::raster_watermark::Factory {}),
+		Box::new(vector::vector_add_tile_coords::Factory {}),
+		Box::new(vector::vector_declutter::Factory {}),
+		Box::new(vector::vector_dissolve::Factory {}),
+		Box::new(vector::vector_feature_ids::Factory {}),
 		Box::new(vector::vector_filter_layers::Factory {}),
 		Box::new(vector::vector_filter_properties::Factory {}),
+		Box::new(vector::vector_fix_geometries::Factory {}),
+		Box::new(vector::vector_merge_lines::Factory {}),
+		Box::new(vector::vector_migrate_schema::Factory {}),
+		Box::new(vector::vector_reduce_precision::Factory {}),
+		Box::new(vector::vector_reencode_properties::Factory {}),
+		Box::new(vector::vector_rename_layers::Factory {}),
+		Box::new(vector::vector_translate_properties::Factory {}),
 		Box::new(vector::vector_update_properties::Factory {}),
 	]
 }
 
 pub fn get_read_operation_factories() -> Vec<Box<dyn ReadOperationFactoryTrait>> {
 	vec![
+		Box::new(read::from_cog::Factory {}),
 		Box::new(read::from_container::Factory {}),
 		Box::new(read::from_debug::Factory {}),
+		Box::new(read::from_grid::Factory {}),
 		Box::new(read::from_stacked::Factory {}),
 		Box::new(read::from_stacked_raster::Factory {}),
 		Box::new(read::from_merged_vector::Factory {}),
+		Box::new(read::from_tile_url::Factory {}),
 		#[cfg(feature = "gdal")]
 		Box::new(read::from_gdal::raster::Factory {}),
 	]
@@ -1,5 +1,13 @@
+pub mod raster_blur;
+pub mod raster_channel_mix;
+pub mod raster_colorize;
 pub mod raster_flatten;
 pub mod raster_format;
+pub mod raster_grayscale;
+pub mod raster_invert;
 pub mod raster_levels;
+pub mod raster_mask;
 pub mod raster_overscale;
 pub mod raster_overview;
+pub mod raster_retile;
+pub mod raster_watermark;
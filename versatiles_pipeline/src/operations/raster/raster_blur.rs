@@ -0,0 +1,171 @@
+use crate::{
+	PipelineFactory,
+	operations::context::{ContextRunnerTrait, ContextTransformOp, TileContext, TileContextKind},
+	traits::*,
+	vpl::VPLNode,
+};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use std::fmt::Debug;
+use versatiles_container::TileContent;
+use versatiles_derive::context;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Blurs raster tiles with a gaussian blur. The pixels of the neighbouring tiles are included, so there are no seams at the tile edges.
+struct Args {
+	/// Standard deviation of the blur in pixels, between 0 and 32. Defaults to 2.
+	sigma: Option<f32>,
+}
+
+#[derive(Debug)]
+struct Runner {
+	sigma: f32,
+	border: u32,
+}
+
+impl Runner {
+	fn new(sigma: f32) -> Result<Runner> {
+		ensure!(
+			(0.0..=32.0).contains(&sigma),
+			"sigma must be between 0 and 32, but is {sigma}"
+		);
+		// the gaussian kernel covers about three standard deviations
+		Ok(Runner {
+			sigma,
+			border: (sigma * 3.0).ceil() as u32,
+		})
+	}
+}
+
+impl ContextRunnerTrait for Runner {
+	fn kind(&self) -> TileContextKind {
+		TileContextKind::Neighbors
+	}
+
+	fn run(&self, context: &TileContext) -> Result<Option<TileContent>> {
+		if self.sigma == 0.0 {
+			return Ok(Some(context.tile().clone()));
+		}
+		let image = context.image_with_border(self.border)?.blur(self.sigma);
+		let (width, height) = (image.width() - 2 * self.border, image.height() - 2 * self.border);
+		Ok(Some(TileContent::from_image(image.crop_imm(
+			self.border,
+			self.border,
+			width,
+			height,
+		))))
+	}
+}
+
+#[context("Building raster_blur operation in VPL node {:?}", vpl_node.name)]
+async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>) -> Result<ContextTransformOp<Runner>> {
+	let args = Args::from_vpl_node(&vpl_node)?;
+	ensure!(
+		source.parameters().tile_format.is_raster(),
+		"raster_blur needs raster tiles"
+	);
+	Ok(ContextTransformOp::new(source, Runner::new(args.sigma.unwrap_or(2.0))?))
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"raster_blur"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		_factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		build(vpl_node, source)
+			.await
+			.map(|op| Box::new(op) as Box<dyn OperationTrait>)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::helpers::dummy_image_source::DummyImageSource;
+	use imageproc::image::{DynamicImage, GenericImageView};
+	use versatiles_container::Tile;
+	use versatiles_core::*;
+	use versatiles_image::traits::*;
+
+	/// Tiles with a vertical stripe at their left edge.
+	fn source() -> Result<Box<dyn OperationTrait>> {
+		let image = DynamicImage::from_fn(16, 16, |x, _| if x < 2 { [0] } else { [200] });
+		Ok(Box::new(DummyImageSource::from_image(image, TileFormat::PNG, None)?))
+	}
+
+	async fn blurred(sigma: f32) -> Result<Vec<(TileCoord, Tile)>> {
+		let op = ContextTransformOp::new(source()?, Runner::new(sigma)?);
+		Ok(op
+			.get_stream(TileBBox::from_min_and_max(4, 3, 3, 4, 4)?)
+			.await?
+			.to_vec()
+			.await)
+	}
+
+	#[tokio::test]
+	async fn blurs_across_tile_edges() -> Result<()> {
+		let tiles = blurred(1.0).await?;
+		assert_eq!(tiles.len(), 4);
+		for (_, tile) in tiles {
+			let image = tile.into_image()?;
+			assert_eq!(image.dimensions(), (16, 16));
+			// the stripe of the next tile darkens the right edge
+			let right = image.get_pixel(15, 8)[0];
+			let middle = image.get_pixel(8, 8)[0];
+			assert_eq!(middle, 200);
+			assert!(right < 200, "{right}");
+			assert!(image.get_pixel(0, 8)[0] < 100);
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn zero_sigma_keeps_tiles() -> Result<()> {
+		for (_, tile) in blurred(0.0).await? {
+			let image = tile.into_image()?;
+			assert_eq!(image.get_pixel(1, 0)[0], 0);
+			assert_eq!(image.get_pixel(2, 0)[0], 200);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn invalid_sigma() {
+		assert!(Runner::new(-1.0).is_err());
+		assert!(Runner::new(33.0).is_err());
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let op = factory
+			.operation_from_vpl("from_debug format=png | raster_blur sigma=1.5")
+			.await?;
+		let bbox = TileCoord::new(3, 2, 1)?.as_tile_bbox();
+		assert_eq!(op.get_stream(bbox).await?.to_vec().await.len(), 1);
+		assert!(
+			factory
+				.operation_from_vpl("from_debug format=mvt | raster_blur")
+				.await
+				.is_err()
+		);
+		Ok(())
+	}
+}
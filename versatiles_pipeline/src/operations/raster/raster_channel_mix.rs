@@ -0,0 +1,173 @@
+use crate::{PipelineFactory, traits::*, vpl::VPLNode};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use std::fmt::Debug;
+use versatiles_container::Tile;
+use versatiles_core::*;
+use versatiles_derive::context;
+use versatiles_image::{DynamicImage, traits::*};
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Mixes the color channels of raster tiles linearly, e.g. to create false color composites.
+/// Every output channel is calculated as `r·R + g·G + b·B + offset` from the input channels R, G and B (0‑255)
+/// and the weights `[r, g, b, offset]`. Grayscale tiles are converted to RGB first. The alpha channel is kept.
+struct Args {
+	/// Weights `[r, g, b, offset]` of the red output channel. Defaults to [1,0,0,0] (no change).
+	red: Option<[f64; 4]>,
+	/// Weights `[r, g, b, offset]` of the green output channel. Defaults to [0,1,0,0] (no change).
+	green: Option<[f64; 4]>,
+	/// Weights `[r, g, b, offset]` of the blue output channel. Defaults to [0,0,1,0] (no change).
+	blue: Option<[f64; 4]>,
+}
+
+#[derive(Debug)]
+struct Operation {
+	source: Box<dyn OperationTrait>,
+	/// Weights of the red, green and blue output channel.
+	matrix: [[f64; 4]; 3],
+}
+
+/// Applies the weights `[r, g, b, offset]` of every output channel.
+fn mix(matrix: &[[f64; 4]; 3], [r, g, b]: [u8; 3]) -> [u8; 3] {
+	let (r, g, b) = (f64::from(r), f64::from(g), f64::from(b));
+	matrix.map(|[wr, wg, wb, offset]| (wr * r + wg * g + wb * b + offset).round().clamp(0.0, 255.0) as u8)
+}
+
+impl Operation {
+	#[context("Building raster_channel_mix operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, _factory: &PipelineFactory) -> Result<Operation>
+	where
+		Self: Sized + OperationTrait,
+	{
+		let args = Args::from_vpl_node(&vpl_node)?;
+		let matrix = [
+			args.red.unwrap_or([1.0, 0.0, 0.0, 0.0]),
+			args.green.unwrap_or([0.0, 1.0, 0.0, 0.0]),
+			args.blue.unwrap_or([0.0, 0.0, 1.0, 0.0]),
+		];
+		ensure!(
+			matrix.iter().flatten().all(|w| w.is_finite()),
+			"channel weights must be finite numbers"
+		);
+		Ok(Self { source, matrix })
+	}
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		self.source.parameters()
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		self.source.tilejson()
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.source.traversal()
+	}
+
+	#[context("Failed to get stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+
+		let matrix = self.matrix;
+		Ok(self.source.get_stream(bbox).await?.map_item_parallel(move |mut tile| {
+			let image = tile.as_image_mut()?;
+			if image.color().channel_count() <= 2 {
+				*image = if image.has_alpha() {
+					DynamicImage::ImageRgba8(image.to_rgba8())
+				} else {
+					DynamicImage::ImageRgb8(image.to_rgb8())
+				};
+			}
+			image.mut_pixel_colors(|color| mix(&matrix, color))?;
+			Ok(tile)
+		}))
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"raster_channel_mix"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, source, factory)
+			.await
+			.map(|op| Box::new(op) as Box<dyn OperationTrait>)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::helpers::dummy_image_source::DummyImageSource;
+	use rstest::rstest;
+
+	const IDENTITY: [[f64; 4]; 3] = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0]];
+	const SWAP: [[f64; 4]; 3] = [[0.0, 0.0, 1.0, 0.0], [0.0, 1.0, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0]];
+
+	#[test]
+	fn mix_channels() {
+		assert_eq!(mix(&IDENTITY, [1, 2, 3]), [1, 2, 3]);
+		assert_eq!(mix(&SWAP, [1, 2, 3]), [3, 2, 1]);
+		let matrix = [[0.5, 0.5, 0.0, 0.0], [0.0, 2.0, 0.0, 0.0], [0.0, 0.0, -1.0, 255.0]];
+		assert_eq!(mix(&matrix, [100, 200, 55]), [150, 255, 200]);
+	}
+
+	#[rstest]
+	#[case::grey(&[102], SWAP, &[102, 102, 102])]
+	#[case::grey_alpha(&[102, 119], IDENTITY, &[102, 102, 102, 119])]
+	#[case::rgb(&[0, 100, 255], SWAP, &[255, 100, 0])]
+	#[case::rgba(&[0, 100, 255, 10], SWAP, &[255, 100, 0, 10])]
+	#[tokio::test]
+	async fn channel_mix(
+		#[case] color_in: &[u8],
+		#[case] matrix: [[f64; 4]; 3],
+		#[case] color_out: &[u8],
+	) -> Result<()> {
+		let op = Operation {
+			source: Box::new(DummyImageSource::from_color(color_in, 4, TileFormat::PNG, None).unwrap()),
+			matrix,
+		};
+		let mut tiles = op
+			.get_stream(TileBBox::from_min_and_max(8, 56, 56, 56, 56)?)
+			.await?
+			.to_vec()
+			.await;
+		assert_eq!(tiles.len(), 1);
+		assert_eq!(tiles[0].1.as_image()?.average_color(), color_out);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let op = factory
+			.operation_from_vpl(
+				"from_debug format=png | raster_flatten color=[50,150,250] | raster_channel_mix red=[0,0,1,0] blue=[1,0,0,0]",
+			)
+			.await?;
+		let bbox = TileCoord::new(3, 2, 1)?.as_tile_bbox();
+		let image = op.get_stream(bbox).await?.next().await.unwrap().1.into_image()?;
+		assert_eq!(image.average_color(), [249, 157, 63]);
+		Ok(())
+	}
+}
@@ -0,0 +1,253 @@
+use crate::{PipelineFactory, traits::*, vpl::VPLNode};
+use anyhow::{Result, bail, ensure};
+use async_trait::async_trait;
+use std::{fmt::Debug, sync::Arc};
+use versatiles_container::Tile;
+use versatiles_core::*;
+use versatiles_derive::context;
+use versatiles_image::{ColorMap, DynamicImage};
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Maps single channel raster tiles through a color map, e.g. to visualize elevation models or gray scale data.
+/// Tiles with the tile schema "dem/mapbox" or "dem/terrarium" are decoded to elevations in meters,
+/// all other tiles use the value of their first channel (0‑255). Transparent pixels stay transparent.
+struct Args {
+	/// Name of a built-in color map ("grayscale", "temperature", "turbo", "viridis" or "wind")
+	/// or a JSON array of `[position, color]` stops, e.g. `colormap='[[0,"#000000"],[1,"#ff0000"]]'`. (default: "viridis")
+	colormap: Option<String>,
+	/// The value that is mapped to the start of the color map. (default: 0)
+	min: Option<f32>,
+	/// The value that is mapped to the end of the color map. (default: 255, or 4000 for elevation models)
+	max: Option<f32>,
+}
+
+/// How the value of a pixel is read.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Channel {
+	/// The first channel, 0‑255.
+	Gray,
+	/// Elevation in meters, encoded as in Mapbox Terrain-RGB.
+	DemMapbox,
+	/// Elevation in meters, encoded as in Terrarium.
+	DemTerrarium,
+}
+
+impl Channel {
+	fn from_schema(schema: Option<&TileSchema>) -> Result<Channel> {
+		Ok(match schema {
+			Some(TileSchema::RasterDEMMapbox) => Channel::DemMapbox,
+			Some(TileSchema::RasterDEMTerrarium) => Channel::DemTerrarium,
+			Some(TileSchema::RasterDEMVersatiles) => bail!("tile schema 'dem/versatiles' is not supported"),
+			_ => Channel::Gray,
+		})
+	}
+
+	fn is_dem(self) -> bool {
+		self != Channel::Gray
+	}
+
+	fn value(self, [r, g, b]: [u8; 3]) -> f64 {
+		let (r, g, b) = (f64::from(r), f64::from(g), f64::from(b));
+		match self {
+			Channel::Gray => r,
+			Channel::DemMapbox => (r * 65536.0 + g * 256.0 + b) * 0.1 - 10000.0,
+			Channel::DemTerrarium => r * 256.0 + g + b / 256.0 - 32768.0,
+		}
+	}
+}
+
+#[derive(Debug)]
+struct Colorizer {
+	colormap: ColorMap,
+	channel: Channel,
+	min: f64,
+	max: f64,
+}
+
+impl Colorizer {
+	fn colorize(&self, image: DynamicImage) -> DynamicImage {
+		let range = if self.max > self.min { self.max - self.min } else { 1.0 };
+		let mut image = image.into_rgba8();
+		for pixel in image.pixels_mut() {
+			let [r, g, b, a] = pixel.0;
+			if a == 0 {
+				pixel.0 = [0; 4];
+				continue;
+			}
+			let value = self.channel.value([r, g, b]);
+			let [r, g, b, alpha] = self.colormap.get((value - self.min) / range);
+			pixel.0 = [r, g, b, ((u16::from(alpha) * u16::from(a) + 127) / 255) as u8];
+		}
+		DynamicImage::ImageRgba8(image)
+	}
+}
+
+#[derive(Debug)]
+struct Operation {
+	source: Box<dyn OperationTrait>,
+	tilejson: TileJSON,
+	colorizer: Arc<Colorizer>,
+}
+
+impl Operation {
+	#[context("Building raster_colorize operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, _factory: &PipelineFactory) -> Result<Operation>
+	where
+		Self: Sized + OperationTrait,
+	{
+		let args = Args::from_vpl_node(&vpl_node)?;
+		ensure!(
+			source.parameters().tile_format.to_type() == TileType::Raster,
+			"raster_colorize needs raster tiles"
+		);
+
+		let mut tilejson = source.tilejson().clone();
+		let channel = Channel::from_schema(tilejson.tile_schema.as_ref())?;
+		if channel.is_dem() {
+			tilejson.tile_schema = Some(TileSchema::RasterRGBA);
+		}
+
+		let min = args.min.map_or(0.0, f64::from);
+		let max = args
+			.max
+			.map_or(if channel.is_dem() { 4000.0 } else { 255.0 }, f64::from);
+		ensure!(min < max, "min ({min}) must be < max ({max})");
+
+		Ok(Self {
+			colorizer: Arc::new(Colorizer {
+				colormap: ColorMap::parse(args.colormap.as_deref().unwrap_or("viridis"))?,
+				channel,
+				min,
+				max,
+			}),
+			tilejson,
+			source,
+		})
+	}
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		self.source.parameters()
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		&self.tilejson
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.source.traversal()
+	}
+
+	#[context("Failed to get stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+
+		let colorizer = Arc::clone(&self.colorizer);
+		Ok(self.source.get_stream(bbox).await?.map_item_parallel(move |tile| {
+			let format = tile.format();
+			let image = colorizer.colorize(tile.into_image()?);
+			// JPEG has no alpha channel
+			let image = if format == TileFormat::JPG {
+				DynamicImage::ImageRgb8(image.into_rgb8())
+			} else {
+				image
+			};
+			Tile::from_image(image, format)
+		}))
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"raster_colorize"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, source, factory)
+			.await
+			.map(|op| Box::new(op) as Box<dyn OperationTrait>)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_image::{GenericImageView, traits::*};
+
+	#[test]
+	fn channel_values() -> Result<()> {
+		assert_eq!(Channel::Gray.value([17, 1, 2]), 17.0);
+		assert_eq!(Channel::DemMapbox.value([1, 134, 160]), 0.0);
+		assert_eq!(Channel::DemTerrarium.value([128, 100, 128]), 100.5);
+		assert_eq!(
+			Channel::from_schema(Some(&TileSchema::RasterDEMMapbox))?,
+			Channel::DemMapbox
+		);
+		assert_eq!(Channel::from_schema(None)?, Channel::Gray);
+		assert!(Channel::from_schema(Some(&TileSchema::RasterDEMVersatiles)).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn colorize() -> Result<()> {
+		let colorizer = Colorizer {
+			colormap: ColorMap::parse(r##"[[0, "#0000ff"], [1, "#ff0000"]]"##)?,
+			channel: Channel::Gray,
+			min: 0.0,
+			max: 200.0,
+		};
+		let image = DynamicImage::from_raw(3, 1, vec![0, 0, 0, 255, 100, 0, 0, 128, 50, 50, 50, 0])?;
+		let image = colorizer.colorize(image);
+		assert_eq!(image.get_pixel(0, 0).0, [0, 0, 255, 255]);
+		assert_eq!(image.get_pixel(1, 0).0, [128, 0, 128, 128]);
+		assert_eq!(image.get_pixel(2, 0).0, [0, 0, 0, 0]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let op = factory
+			.operation_from_vpl(
+				r#"from_debug format=png | meta_update schema="dem/terrarium" | raster_colorize colormap="turbo" min=-11000 max=9000"#,
+			)
+			.await?;
+		assert_eq!(op.tilejson().tile_schema, Some(TileSchema::RasterRGBA));
+
+		let bbox = TileCoord::new(2, 1, 1)?.as_tile_bbox();
+		let image = op.get_stream(bbox).await?.next().await.unwrap().1.into_image()?;
+		assert_eq!(image.width(), 512);
+
+		assert!(
+			factory
+				.operation_from_vpl(r#"from_debug format=png | raster_colorize colormap="rainbow""#)
+				.await
+				.is_err()
+		);
+		assert!(
+			factory
+				.operation_from_vpl(r#"from_debug format=mvt | raster_colorize"#)
+				.await
+				.is_err()
+		);
+		Ok(())
+	}
+}
@@ -1,8 +1,8 @@
 use crate::{PipelineFactory, traits::*, vpl::VPLNode};
 use anyhow::{Result, bail, ensure};
 use async_trait::async_trait;
-use std::{fmt::Debug, str};
-use versatiles_container::Tile;
+use std::{fmt::Debug, str, sync::Arc};
+use versatiles_container::{Tile, TileEncodeCache};
 use versatiles_core::*;
 use versatiles_derive::context;
 
@@ -76,11 +76,16 @@ struct Operation {
 	format: RasterTileFormat,
 	quality: [Option<u8>; 32],
 	speed: Option<u8>,
+	/// Identical input tiles (e.g. empty ocean tiles) are encoded only once.
+	encode_cache: Arc<TileEncodeCache>,
 }
 
+/// Maximum number of encoded tiles kept in the encode cache.
+const ENCODE_CACHE_ENTRIES: usize = 4096;
+
 impl Operation {
 	#[context("Building raster_format operation in VPL node {:?}", vpl_node.name)]
-	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, _factory: &PipelineFactory) -> Result<Operation>
+	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, factory: &PipelineFactory) -> Result<Operation>
 	where
 		Self: Sized + OperationTrait,
 	{
@@ -104,6 +109,10 @@ impl Operation {
 			format,
 			quality: parse_quality(args.quality)?,
 			speed: args.speed,
+			encode_cache: Arc::new(TileEncodeCache::new(
+				ENCODE_CACHE_ENTRIES,
+				factory.config().metrics.clone(),
+			)),
 			parameters,
 			source,
 			tilejson,
@@ -159,9 +168,10 @@ impl OperationTrait for Operation {
 		let speed = self.speed;
 		let stream = self.source.get_stream(bbox).await?;
 		let format: TileFormat = self.format.into();
+		let encode_cache = self.encode_cache.clone();
 
 		Ok(stream.map_item_parallel(move |mut tile| {
-			tile.change_format(format, quality, speed)?;
+			encode_cache.change_format(&mut tile, format, quality, speed)?;
 			Ok(tile)
 		}))
 	}
@@ -173,6 +183,9 @@ impl OperationFactoryTrait for Factory {
 	fn get_docs(&self) -> String {
 		Args::get_docs()
 	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
 	fn get_tag_name(&self) -> &str {
 		"raster_format"
 	}
@@ -0,0 +1,126 @@
+use crate::{PipelineFactory, traits::*, vpl::VPLNode};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::fmt::Debug;
+use versatiles_container::Tile;
+use versatiles_core::*;
+use versatiles_derive::context;
+use versatiles_image::traits::*;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Converts raster tiles to grayscale, using the luma weights of ITU-R BT.601. The alpha channel is kept.
+struct Args {}
+
+#[derive(Debug)]
+struct Operation {
+	source: Box<dyn OperationTrait>,
+}
+
+impl Operation {
+	#[context("Building raster_grayscale operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, _factory: &PipelineFactory) -> Result<Operation>
+	where
+		Self: Sized + OperationTrait,
+	{
+		Args::from_vpl_node(&vpl_node)?;
+		Ok(Self { source })
+	}
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		self.source.parameters()
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		self.source.tilejson()
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.source.traversal()
+	}
+
+	#[context("Failed to get stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+
+		Ok(self.source.get_stream(bbox).await?.map_item_parallel(|mut tile| {
+			tile.as_image_mut()?.mut_pixel_colors(|[r, g, b]| {
+				let luma = (299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b) + 500) / 1000;
+				[luma as u8; 3]
+			})?;
+			Ok(tile)
+		}))
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"raster_grayscale"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, source, factory)
+			.await
+			.map(|op| Box::new(op) as Box<dyn OperationTrait>)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::helpers::dummy_image_source::DummyImageSource;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case::grey(&[102], &[102])]
+	#[case::grey_alpha(&[102, 119], &[102, 119])]
+	#[case::red(&[255, 0, 0], &[76, 76, 76])]
+	#[case::green_alpha(&[0, 255, 0, 153], &[150, 150, 150, 153])]
+	#[case::white(&[255, 255, 255], &[255, 255, 255])]
+	#[tokio::test]
+	async fn grayscale(#[case] color_in: &[u8], #[case] color_out: &[u8]) -> Result<()> {
+		let op = Operation {
+			source: Box::new(DummyImageSource::from_color(color_in, 4, TileFormat::PNG, None).unwrap()),
+		};
+		let mut tiles = op
+			.get_stream(TileBBox::from_min_and_max(8, 56, 56, 56, 56)?)
+			.await?
+			.to_vec()
+			.await;
+		assert_eq!(tiles.len(), 1);
+		assert_eq!(tiles[0].1.as_image()?.average_color(), color_out);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let op = factory
+			.operation_from_vpl("from_debug format=png | raster_flatten color=[50,150,250] | raster_grayscale")
+			.await?;
+		let bbox = TileCoord::new(3, 2, 1)?.as_tile_bbox();
+		let image = op.get_stream(bbox).await?.next().await.unwrap().1.into_image()?;
+		let color = image.average_color();
+		assert_eq!(color[0], color[1]);
+		assert_eq!(color[1], color[2]);
+		Ok(())
+	}
+}
@@ -0,0 +1,124 @@
+use crate::{PipelineFactory, traits::*, vpl::VPLNode};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::fmt::Debug;
+use versatiles_container::Tile;
+use versatiles_core::*;
+use versatiles_derive::context;
+use versatiles_image::traits::*;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Inverts the colors of raster tiles, e.g. to create a dark map from a light one. The alpha channel is kept.
+struct Args {}
+
+#[derive(Debug)]
+struct Operation {
+	source: Box<dyn OperationTrait>,
+}
+
+impl Operation {
+	#[context("Building raster_invert operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, _factory: &PipelineFactory) -> Result<Operation>
+	where
+		Self: Sized + OperationTrait,
+	{
+		Args::from_vpl_node(&vpl_node)?;
+		Ok(Self { source })
+	}
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		self.source.parameters()
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		self.source.tilejson()
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.source.traversal()
+	}
+
+	#[context("Failed to get stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+
+		Ok(self.source.get_stream(bbox).await?.map_item_parallel(|mut tile| {
+			tile
+				.as_image_mut()?
+				.mut_pixel_colors(|[r, g, b]| [255 - r, 255 - g, 255 - b])?;
+			Ok(tile)
+		}))
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"raster_invert"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, source, factory)
+			.await
+			.map(|op| Box::new(op) as Box<dyn OperationTrait>)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::helpers::dummy_image_source::DummyImageSource;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case::grey(&[102], &[153])]
+	#[case::grey_alpha(&[102, 119], &[153, 119])]
+	#[case::rgb(&[0, 100, 255], &[255, 155, 0])]
+	#[case::rgba(&[0, 100, 255, 10], &[255, 155, 0, 10])]
+	#[tokio::test]
+	async fn invert(#[case] color_in: &[u8], #[case] color_out: &[u8]) -> Result<()> {
+		let op = Operation {
+			source: Box::new(DummyImageSource::from_color(color_in, 4, TileFormat::PNG, None).unwrap()),
+		};
+		let mut tiles = op
+			.get_stream(TileBBox::from_min_and_max(8, 56, 56, 56, 56)?)
+			.await?
+			.to_vec()
+			.await;
+		assert_eq!(tiles.len(), 1);
+		assert_eq!(tiles[0].1.as_image()?.average_color(), color_out);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let op = factory
+			.operation_from_vpl(
+				"from_debug format=png | raster_flatten color=[50,150,250] | raster_invert | raster_invert",
+			)
+			.await?;
+		let bbox = TileCoord::new(3, 2, 1)?.as_tile_bbox();
+		let image = op.get_stream(bbox).await?.next().await.unwrap().1.into_image()?;
+		assert_eq!(image.average_color(), [63, 157, 249]);
+		Ok(())
+	}
+}
@@ -80,6 +80,9 @@ impl OperationFactoryTrait for Factory {
 	fn get_docs(&self) -> String {
 		Args::get_docs()
 	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
 	fn get_tag_name(&self) -> &str {
 		"raster_levels"
 	}
@@ -0,0 +1,238 @@
+use crate::{
+	PipelineFactory,
+	traits::*,
+	vpl::{VPLNode, VPLPipeline},
+};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use futures::{StreamExt, stream};
+use std::fmt::Debug;
+use versatiles_container::Tile;
+use versatiles_core::*;
+use versatiles_derive::context;
+use versatiles_image::{DynamicImage, GenericImageView, traits::*};
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Masks raster tiles with the tiles of a second pipeline, e.g. to cut out a region or to fade out water areas.
+/// The alpha channel of the mask is multiplied into the alpha channel of the tiles; masks without an alpha channel
+/// use their brightness instead. Tiles without a matching mask tile are removed.
+struct Args {
+	/// The pipeline that generates the mask tiles, in parentheses, e.g. `mask=( from_container filename="mask.versatiles" )`.
+	/// Mask tiles must have the same size as the tiles, or be larger by an integer factor.
+	mask: VPLPipeline,
+	/// Invert the mask, so that opaque/bright mask pixels hide the tiles. (default: false)
+	invert: Option<bool>,
+}
+
+/// Multiplies the mask into the alpha channel of `image`.
+fn apply_mask(image: DynamicImage, mask: &DynamicImage, invert: bool) -> Result<DynamicImage> {
+	let (width, height) = image.dimensions();
+	let scaled;
+	let mask = if mask.dimensions() == (width, height) {
+		mask
+	} else {
+		let factor = mask.width() / width;
+		ensure!(
+			factor > 1 && mask.width() == width * factor && mask.height() == height * factor,
+			"mask size {}x{} doesn't match tile size {width}x{height}",
+			mask.width(),
+			mask.height()
+		);
+		scaled = mask.get_scaled_down(factor)?;
+		&scaled
+	};
+
+	let use_alpha = mask.color().has_alpha();
+	let mask = mask.to_luma_alpha8();
+	let mut image = image.into_rgba8();
+	for (pixel, value) in image.pixels_mut().zip(mask.pixels()) {
+		let [luma, alpha] = value.0;
+		let value = if use_alpha { alpha } else { luma };
+		let value = if invert { 255 - value } else { value };
+		pixel.0[3] = ((u16::from(pixel.0[3]) * u16::from(value) + 127) / 255) as u8;
+	}
+	Ok(DynamicImage::ImageRgba8(image))
+}
+
+#[derive(Debug)]
+struct Operation {
+	parameters: TilesReaderParameters,
+	source: Box<dyn OperationTrait>,
+	mask: Box<dyn OperationTrait>,
+	tilejson: TileJSON,
+	invert: bool,
+}
+
+impl Operation {
+	#[context("Building raster_mask operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, factory: &PipelineFactory) -> Result<Operation>
+	where
+		Self: Sized + OperationTrait,
+	{
+		let args = Args::from_vpl_node(&vpl_node)?;
+		let mask = factory.build_pipeline(args.mask).await?;
+
+		let mut parameters = source.parameters().clone();
+		ensure!(
+			parameters.tile_format.to_type() == TileType::Raster,
+			"raster_mask needs raster tiles"
+		);
+		ensure!(
+			parameters.tile_format != TileFormat::JPG,
+			"raster_mask needs a tile format with alpha channel, convert JPG tiles with raster_format first"
+		);
+		ensure!(
+			mask.parameters().tile_format.to_type() == TileType::Raster,
+			"the mask of raster_mask must be raster tiles"
+		);
+		parameters.bbox_pyramid.intersect(&mask.parameters().bbox_pyramid);
+
+		let mut tilejson = source.tilejson().clone();
+		tilejson.update_from_reader_parameters(&parameters);
+
+		Ok(Self {
+			parameters,
+			source,
+			mask,
+			tilejson,
+			invert: args.invert.unwrap_or(false),
+		})
+	}
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		&self.parameters
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		&self.tilejson
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.source.traversal()
+	}
+
+	#[context("Failed to get stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, mut bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+		bbox.intersect_with_pyramid(&self.parameters.bbox_pyramid);
+		if bbox.is_empty() {
+			return Ok(TileStream::empty());
+		}
+
+		// pair tiles and masks block by block, so only one block per task is kept in memory
+		let bboxes: Vec<TileBBox> = bbox.iter_bbox_grid(16).collect();
+		let invert = self.invert;
+		Ok(
+			TileStream::from_try_streams(stream::iter(bboxes).map(move |bbox| async move {
+				let mut masks = self.mask.get_stream(bbox).await?.to_map().await;
+				let tiles = self
+					.source
+					.get_stream(bbox)
+					.await?
+					.to_vec()
+					.await
+					.into_iter()
+					.filter_map(|(coord, tile)| masks.remove(&coord).map(|mask| (coord, (tile, mask))))
+					.collect::<Vec<_>>();
+				Ok(TileStream::from_vec(tiles))
+			}))
+			.filter_map_item_parallel(move |(tile, mask)| {
+				let format = tile.format();
+				let image = apply_mask(tile.into_image()?, &mask.into_image()?, invert)?;
+				image
+					.into_optional()
+					.map(|image| Tile::from_image(image, format))
+					.transpose()
+			}),
+		)
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"raster_mask"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, source, factory)
+			.await
+			.map(|op| Box::new(op) as Box<dyn OperationTrait>)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn mask() -> Result<()> {
+		let image = DynamicImage::from_raw(2, 1, vec![10, 20, 30, 255, 10, 20, 30, 128])?;
+
+		let mask = DynamicImage::from_raw(2, 1, vec![255, 0, 255, 255])?;
+		let result = apply_mask(image.clone(), &mask, false)?;
+		assert_eq!(result.get_pixel(0, 0).0, [10, 20, 30, 0]);
+		assert_eq!(result.get_pixel(1, 0).0, [10, 20, 30, 128]);
+
+		let result = apply_mask(image.clone(), &mask, true)?;
+		assert_eq!(result.get_pixel(0, 0).0, [10, 20, 30, 255]);
+		assert_eq!(result.get_pixel(1, 0).0, [10, 20, 30, 0]);
+
+		// gray mask without alpha, twice the size
+		let mask = DynamicImage::from_raw(4, 2, vec![255, 255, 0, 0, 255, 255, 0, 0])?;
+		let result = apply_mask(image.clone(), &mask, false)?;
+		assert_eq!(result.get_pixel(0, 0).0, [10, 20, 30, 255]);
+		assert_eq!(result.get_pixel(1, 0).0, [10, 20, 30, 0]);
+
+		let mask = DynamicImage::from_raw(3, 1, vec![0, 0, 0])?;
+		assert!(apply_mask(image, &mask, false).is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let op = factory
+			.operation_from_vpl(
+				r#"from_debug format=png | raster_mask mask=( from_debug format=png | raster_flatten | filter level_max=3 )"#,
+			)
+			.await?;
+		assert_eq!(op.parameters().bbox_pyramid.get_level_max(), Some(3));
+
+		let bbox = TileCoord::new(3, 2, 1)?.as_tile_bbox();
+		let tiles = op.get_stream(bbox).await?.to_vec().await;
+		assert_eq!(tiles.len(), 1);
+		assert_eq!(tiles[0].1.clone().into_image()?.width(), 512);
+
+		let bbox = TileCoord::new(4, 2, 1)?.as_tile_bbox();
+		assert_eq!(op.get_stream(bbox).await?.to_vec().await.len(), 0);
+
+		for vpl in [
+			r#"from_debug format=png | raster_mask"#,
+			r#"from_debug format=png | raster_mask mask="from_debug""#,
+			r#"from_debug format=png | raster_mask mask=( from_debug format=mvt )"#,
+			r#"from_debug format=mvt | raster_mask mask=( from_debug format=png )"#,
+		] {
+			assert!(factory.operation_from_vpl(vpl).await.is_err(), "{vpl}");
+		}
+		Ok(())
+	}
+}
@@ -39,7 +39,12 @@ impl Operation {
 		Self: Sized + OperationTrait,
 	{
 		let args = Args::from_vpl_node(&vpl_node)?;
-		ensure!(source.traversal().is_any());
+		ensure!(
+			source.traversal().is_any(),
+			"raster_overview traverses its source in {:?}, so the source must not require an order, but it requires {:?}",
+			TraversalOrder::DepthFirst,
+			source.traversal()
+		);
 
 		let mut parameters = source.parameters().clone();
 
@@ -247,6 +252,9 @@ impl OperationFactoryTrait for Factory {
 	fn get_docs(&self) -> String {
 		Args::get_docs()
 	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
 	fn get_tag_name(&self) -> &str {
 		"raster_overview"
 	}
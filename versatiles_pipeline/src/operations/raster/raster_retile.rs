@@ -0,0 +1,382 @@
+use crate::{PipelineFactory, traits::*, vpl::VPLNode};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use imageproc::image::{DynamicImage, GenericImage, GenericImageView};
+use std::fmt::Debug;
+use versatiles_container::Tile;
+use versatiles_core::*;
+use versatiles_derive::context;
+use versatiles_image::traits::*;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Converts a pyramid of 256px raster tiles into a pyramid of 512px tiles, or vice versa.
+/// Unlike resizing, this regrids the pyramid: four 256px tiles of zoom level z are combined into one 512px tile
+/// of zoom level z-1, and a 512px tile of zoom level z is split into four 256px tiles of zoom level z+1.
+/// The zoom levels and the "tile_size" of the TileJSON are updated accordingly.
+struct Args {
+	/// Size of the output tiles in pixels: 256 or 512.
+	tile_size: u32,
+	/// Size of the source tiles in pixels: 256 or 512. Defaults to the "tile_size" of the source TileJSON, or 256.
+	source_tile_size: Option<u32>,
+}
+
+#[derive(Debug)]
+struct Operation {
+	parameters: TilesReaderParameters,
+	source: Box<dyn OperationTrait>,
+	tilejson: TileJSON,
+	source_size: u32,
+	tile_size: u32,
+}
+
+impl Operation {
+	#[context("Building raster_retile operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, _factory: &PipelineFactory) -> Result<Operation>
+	where
+		Self: Sized + OperationTrait,
+	{
+		let args = Args::from_vpl_node(&vpl_node)?;
+		ensure!(
+			source.parameters().tile_format.is_raster(),
+			"raster_retile needs raster tiles"
+		);
+
+		let tile_size = TileSize::new(u16::try_from(args.tile_size)?)?;
+		let source_size = match args.source_tile_size {
+			Some(size) => TileSize::new(u16::try_from(size)?)?,
+			None => source.tilejson().tile_size.unwrap_or(TileSize::Size256),
+		};
+
+		let mut parameters = source.parameters().clone();
+		parameters.bbox_pyramid = TileBBoxPyramid::new_empty();
+		for bbox in source.parameters().bbox_pyramid.iter_levels() {
+			match (source_size, tile_size) {
+				(TileSize::Size256, TileSize::Size512) if bbox.level > 0 => {
+					parameters.bbox_pyramid.set_level_bbox(bbox.leveled_down())
+				}
+				(TileSize::Size512, TileSize::Size256) if bbox.level < 31 => {
+					parameters.bbox_pyramid.set_level_bbox(bbox.leveled_up())
+				}
+				(TileSize::Size256, TileSize::Size256) | (TileSize::Size512, TileSize::Size512) => {
+					parameters.bbox_pyramid.set_level_bbox(*bbox)
+				}
+				_ => {}
+			}
+		}
+
+		let mut tilejson = source.tilejson().clone();
+		tilejson.update_from_reader_parameters(&parameters);
+		tilejson.tile_size = Some(tile_size);
+
+		Ok(Self {
+			parameters,
+			source,
+			tilejson,
+			source_size: u32::from(source_size.size()),
+			tile_size: u32::from(tile_size.size()),
+		})
+	}
+}
+
+/// Decodes a source tile and checks its size.
+fn decode(tile: Tile, size: u32) -> Result<DynamicImage> {
+	let image = tile.into_image()?;
+	ensure!(
+		image.dimensions() == (size, size),
+		"expected source tiles of {size}x{size} pixels, but got {}x{} pixels",
+		image.width(),
+		image.height()
+	);
+	Ok(image)
+}
+
+/// Combines up to four tiles of the next zoom level into one tile of twice the size.
+/// Missing tiles stay transparent; JPEG tiles, which have no alpha channel, are filled with black instead.
+fn combine(children: Vec<(TileCoord, DynamicImage)>, size: u32, format: TileFormat) -> Result<Option<Tile>> {
+	let Some((_, first)) = children.first() else {
+		return Ok(None);
+	};
+
+	let mut image = if children.len() == 4 {
+		DynamicImage::new(size * 2, size * 2, first.color())
+	} else {
+		DynamicImage::new_rgba8(size * 2, size * 2)
+	};
+	for (coord, child) in &children {
+		let child = if image.color() == child.color() {
+			child.clone()
+		} else {
+			DynamicImage::from(child.to_rgba8())
+		};
+		image.copy_from(&child, (coord.x % 2) * size, (coord.y % 2) * size)?;
+	}
+
+	if format == TileFormat::JPG && image.color().has_alpha() {
+		image = DynamicImage::from(image.to_rgb8());
+	}
+	image
+		.into_optional()
+		.map(|image| Tile::from_image(image, format))
+		.transpose()
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		&self.parameters
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		&self.tilejson
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.source.traversal()
+	}
+
+	#[context("Failed to get stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+
+		if !self.parameters.bbox_pyramid.overlaps_bbox(&bbox) {
+			return Ok(TileStream::empty());
+		}
+
+		let source_size = self.source_size;
+		let format = self.source.parameters().tile_format;
+
+		if self.tile_size == source_size {
+			return self.source.get_stream(bbox).await;
+		}
+
+		if self.tile_size > source_size {
+			// combine 2x2 source tiles of the next zoom level
+			let bbox_source = bbox.leveled_up();
+			let images = TileBBoxMap::<Option<DynamicImage>>::from_stream(
+				bbox_source,
+				self
+					.source
+					.get_stream(bbox_source)
+					.await?
+					.map_item_parallel(move |tile| decode(tile, source_size)),
+			)
+			.await?;
+
+			let groups = images
+				.into_decreased_level()
+				.into_iter()
+				.filter_map(|(coord, children)| {
+					let children: Vec<(TileCoord, DynamicImage)> = children
+						.into_iter()
+						.filter_map(|(child, image)| image.map(|image| (child, image)))
+						.collect();
+					(!children.is_empty()).then_some((coord, children))
+				})
+				.collect();
+
+			Ok(TileStream::from_vec(groups)
+				.filter_map_item_parallel(move |children| combine(children, source_size, format)))
+		} else {
+			// split each source tile of the previous zoom level into 2x2 tiles
+			let size = self.tile_size;
+			let bbox_source = bbox.leveled_down();
+			Ok(self
+				.source
+				.get_stream(bbox_source)
+				.await?
+				.flat_map_parallel(move |coord_source, tile| {
+					let mut bbox_children = coord_source.as_tile_bbox().leveled_up();
+					bbox_children.intersect_with(&bbox)?;
+
+					let image = decode(tile, source_size)?;
+
+					let tiles = bbox_children
+						.into_iter_coords()
+						.filter_map(|coord| {
+							image
+								.crop_imm((coord.x % 2) * size, (coord.y % 2) * size, size, size)
+								.into_optional()
+								.map(|image| Ok((coord, Tile::from_image(image, format)?)))
+						})
+						.collect::<Result<Vec<_>>>()?;
+					Ok(TileStream::from_vec(tiles))
+				}))
+		}
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"raster_retile"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, source, factory)
+			.await
+			.map(|op| Box::new(op) as Box<dyn OperationTrait>)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::helpers::dummy_image_source::DummyImageSource;
+
+	/// Source whose tiles are colored by their coordinates: `[x, y, level]`.
+	fn make_source(tile_size: u32, pyramid: TileBBoxPyramid) -> Box<dyn OperationTrait> {
+		Box::new(
+			DummyImageSource::new(
+				move |coord| {
+					let color = [coord.x as u8, coord.y as u8, coord.level];
+					let image = DynamicImage::from_fn(tile_size as usize, tile_size as usize, |_, _| color);
+					Some(Tile::from_image(image, TileFormat::PNG).unwrap())
+				},
+				TileFormat::PNG,
+				Some(pyramid),
+			)
+			.unwrap(),
+		)
+	}
+
+	async fn make_operation(vpl: &str, tile_size: u32, pyramid: TileBBoxPyramid) -> Result<Operation> {
+		Operation::build(
+			VPLNode::try_from_str(vpl)?,
+			make_source(tile_size, pyramid),
+			&PipelineFactory::new_dummy(),
+		)
+		.await
+	}
+
+	async fn get_tiles(op: &Operation, bbox: TileBBox) -> Result<Vec<(TileCoord, DynamicImage)>> {
+		let mut tiles = Vec::new();
+		for (coord, tile) in op.get_stream(bbox).await?.to_vec().await {
+			tiles.push((coord, tile.into_image()?));
+		}
+		tiles.sort_by_key(|(coord, _)| (coord.y, coord.x));
+		Ok(tiles)
+	}
+
+	#[tokio::test]
+	async fn combine_256_to_512() -> Result<()> {
+		let op = make_operation("raster_retile tile_size=512", 256, TileBBoxPyramid::new_full(3)).await?;
+		assert_eq!(op.parameters.bbox_pyramid.get_level_min(), Some(0));
+		assert_eq!(op.parameters.bbox_pyramid.get_level_max(), Some(2));
+		assert_eq!(op.tilejson.tile_size, Some(TileSize::Size512));
+
+		let tiles = get_tiles(&op, TileBBox::from_min_and_max(1, 0, 1, 1, 1)?).await?;
+		assert_eq!(tiles.len(), 2);
+		for (coord, image) in tiles {
+			assert_eq!(image.dimensions(), (512, 512));
+			// the quadrants come from the tiles of the next zoom level
+			for (qx, qy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+				let pixel = image.get_pixel(qx * 256 + 100, qy * 256 + 100).0;
+				assert_eq!(pixel, [(coord.x * 2 + qx) as u8, (coord.y * 2 + qy) as u8, 2, 255]);
+			}
+		}
+
+		// there are no tiles above the maximum zoom level
+		assert!(get_tiles(&op, TileBBox::new_full(3)?).await?.is_empty());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn combine_partial_coverage() -> Result<()> {
+		let mut pyramid = TileBBoxPyramid::new_empty();
+		pyramid.set_level_bbox(TileBBox::from_min_and_max(3, 1, 1, 2, 2)?);
+		let op = make_operation("raster_retile tile_size=512", 256, pyramid).await?;
+		assert_eq!(
+			*op.parameters.bbox_pyramid.get_level_bbox(2),
+			TileBBox::from_min_and_max(2, 0, 0, 1, 1)?
+		);
+
+		let tiles = get_tiles(&op, TileBBox::new_full(2)?).await?;
+		assert_eq!(tiles.len(), 4);
+		let (coord, image) = &tiles[0];
+		assert_eq!(*coord, TileCoord::new(2, 0, 0)?);
+		// only the lower right quadrant is covered by the source
+		assert_eq!(image.get_pixel(100, 100).0, [0, 0, 0, 0]);
+		assert_eq!(image.get_pixel(300, 300).0, [1, 1, 3, 255]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn split_512_to_256() -> Result<()> {
+		let op = make_operation(
+			"raster_retile tile_size=256 source_tile_size=512",
+			512,
+			TileBBoxPyramid::new_full(2),
+		)
+		.await?;
+		assert_eq!(op.parameters.bbox_pyramid.get_level_min(), Some(1));
+		assert_eq!(op.parameters.bbox_pyramid.get_level_max(), Some(3));
+		assert_eq!(op.tilejson.tile_size, Some(TileSize::Size256));
+
+		let tiles = get_tiles(&op, TileBBox::from_min_and_max(3, 2, 4, 3, 5)?).await?;
+		assert_eq!(tiles.len(), 4);
+		for (coord, image) in tiles {
+			assert_eq!(image.dimensions(), (256, 256));
+			assert_eq!(
+				image.get_pixel(0, 0).0,
+				[(coord.x / 2) as u8, (coord.y / 2) as u8, 2, 255]
+			);
+		}
+
+		assert!(get_tiles(&op, TileBBox::new_full(0)?).await?.is_empty());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn roundtrip() -> Result<()> {
+		let op = Operation::build(
+			VPLNode::try_from_str("raster_retile tile_size=256")?,
+			Box::new(make_operation("raster_retile tile_size=512", 256, TileBBoxPyramid::new_full(3)).await?),
+			&PipelineFactory::new_dummy(),
+		)
+		.await?;
+		assert_eq!(op.parameters.bbox_pyramid.get_level_min(), Some(1));
+		assert_eq!(op.parameters.bbox_pyramid.get_level_max(), Some(3));
+
+		let bbox = TileBBox::new_full(3)?;
+		let tiles = get_tiles(&op, bbox).await?;
+		assert_eq!(tiles.len(), 64);
+		for (coord, image) in tiles {
+			assert_eq!(image.get_pixel(128, 128).0, [coord.x as u8, coord.y as u8, 3, 255]);
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn wrong_sizes() -> Result<()> {
+		let pyramid = TileBBoxPyramid::new_full(2);
+		assert!(
+			make_operation("raster_retile tile_size=300", 256, pyramid.clone())
+				.await
+				.is_err()
+		);
+
+		// the source tiles are not as large as declared
+		let tile = Tile::from_image(DynamicImage::new_rgb8(256, 256), TileFormat::PNG)?;
+		assert!(decode(tile.clone(), 256).is_ok());
+		assert_eq!(
+			decode(tile, 512).unwrap_err().to_string(),
+			"expected source tiles of 512x512 pixels, but got 256x256 pixels"
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,330 @@
+use crate::{PipelineFactory, traits::*, vpl::VPLNode};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use imageproc::image::{DynamicImage, Rgba, RgbaImage};
+use std::{fmt::Debug, sync::Arc};
+use versatiles_container::Tile;
+use versatiles_core::*;
+use versatiles_derive::context;
+use versatiles_image::draw;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Burns a text or a logo into every raster tile, e.g. an attribution that is required by a license.
+/// Exactly one of `text` and `image` must be set.
+struct Args {
+	/// Text to draw, e.g. "© OpenStreetMap contributors".
+	text: Option<String>,
+	/// Filename of an image (PNG, JPEG, WebP or AVIF) to draw, e.g. a logo. It is drawn in its original size.
+	image: Option<String>,
+	/// Corner of the tile: "top-left", "top-right", "bottom-left", "bottom-right" or "center". Defaults to "bottom-right".
+	position: Option<Position>,
+	/// Opacity of the watermark. Defaults to 1.
+	#[vpl(min = 0.0, max = 1.0)]
+	opacity: Option<f32>,
+	/// Font size in pixels. Defaults to 1/24 of the tile width.
+	size: Option<f32>,
+	/// Text color, in RGB format. Defaults to black.
+	color: Option<[u8; 3]>,
+	/// If set, a box in this RGB color is drawn behind the text.
+	background: Option<[u8; 3]>,
+	/// Distance to the border of the tile in pixels. Defaults to 0.
+	margin: Option<u32>,
+}
+
+#[derive(versatiles_derive::VPLDecode, Clone, Copy, Debug, PartialEq)]
+enum Position {
+	#[vpl(rename = "top-left")]
+	TopLeft,
+	#[vpl(rename = "top-right")]
+	TopRight,
+	#[vpl(rename = "bottom-left")]
+	BottomLeft,
+	#[vpl(rename = "bottom-right")]
+	BottomRight,
+	Center,
+}
+
+impl Position {
+	/// Returns the upper left corner of a `width`×`height` box in a `tile_width`×`tile_height` tile.
+	fn place(self, tile_width: u32, tile_height: u32, width: u32, height: u32, margin: u32) -> (i32, i32) {
+		let left = margin as i32;
+		let top = margin as i32;
+		let right = tile_width as i32 - width as i32 - margin as i32;
+		let bottom = tile_height as i32 - height as i32 - margin as i32;
+		match self {
+			Position::TopLeft => (left, top),
+			Position::TopRight => (right, top),
+			Position::BottomLeft => (left, bottom),
+			Position::BottomRight => (right, bottom),
+			Position::Center => (
+				(tile_width as i32 - width as i32) / 2,
+				(tile_height as i32 - height as i32) / 2,
+			),
+		}
+	}
+}
+
+#[derive(Debug)]
+enum Mark {
+	Text {
+		text: String,
+		size: Option<f32>,
+		color: [u8; 3],
+		background: Option<[u8; 3]>,
+	},
+	Image(RgbaImage),
+}
+
+#[derive(Debug)]
+struct Watermark {
+	mark: Mark,
+	position: Position,
+	opacity: f32,
+	margin: u32,
+}
+
+impl Watermark {
+	/// Draws the watermark onto a copy of `image`. Images without alpha channel stay without alpha channel.
+	fn apply(&self, image: &DynamicImage) -> DynamicImage {
+		let mut canvas = image.to_rgba8();
+		let (width, height) = canvas.dimensions();
+		let alpha = (self.opacity * 255.0).round() as u8;
+
+		match &self.mark {
+			Mark::Text {
+				text,
+				size,
+				color,
+				background,
+			} => {
+				let size = size.unwrap_or(width as f32 / 24.0);
+				let padding = (size / 4.0).round() as u32;
+				let (text_width, _) = draw::text_size(size, text);
+				let box_width = text_width + 2 * padding;
+				let box_height = size.ceil() as u32 + 2 * padding;
+				let (x, y) = self.position.place(width, height, box_width, box_height, self.margin);
+				if let Some([r, g, b]) = background {
+					let color = Rgba([*r, *g, *b, alpha]);
+					draw::fill_rect(
+						&mut canvas,
+						x as f32,
+						y as f32,
+						box_width as f32,
+						box_height as f32,
+						color,
+					);
+				}
+				let [r, g, b] = *color;
+				let (x, y) = (x + padding as i32, y + padding as i32);
+				draw::draw_text(&mut canvas, x, y, size, Rgba([r, g, b, alpha]), text);
+			}
+			Mark::Image(logo) => {
+				let (x, y) = self
+					.position
+					.place(width, height, logo.width(), logo.height(), self.margin);
+				for (lx, ly, pixel) in logo.enumerate_pixels() {
+					draw::blend_pixel(&mut canvas, x + lx as i32, y + ly as i32, *pixel, self.opacity);
+				}
+			}
+		}
+
+		if image.has_alpha() {
+			DynamicImage::ImageRgba8(canvas)
+		} else {
+			DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(canvas).to_rgb8())
+		}
+	}
+}
+
+#[derive(Debug)]
+struct Operation {
+	source: Box<dyn OperationTrait>,
+	watermark: Arc<Watermark>,
+}
+
+impl Operation {
+	#[context("Building raster_watermark operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, source: Box<dyn OperationTrait>, factory: &PipelineFactory) -> Result<Operation>
+	where
+		Self: Sized + OperationTrait,
+	{
+		let args = Args::from_vpl_node(&vpl_node)?;
+		ensure!(
+			args.text.is_some() != args.image.is_some(),
+			"raster_watermark needs exactly one of the parameters 'text' and 'image'"
+		);
+		ensure!(
+			source.parameters().tile_format.is_raster(),
+			"raster_watermark needs raster tiles"
+		);
+
+		let mark = if let Some(filename) = args.image {
+			let path = factory.resolve_path(&filename);
+			let mut name = path.to_string_lossy().to_string();
+			let format = TileFormat::from_filename(&mut name)
+				.filter(TileFormat::is_raster)
+				.with_context(|| format!("unknown image format of {path:?}"))?;
+			let blob = Blob::from(std::fs::read(&path).with_context(|| format!("Failed to read image {path:?}"))?);
+			Mark::Image(versatiles_image::decode(&blob, format)?.to_rgba8())
+		} else {
+			Mark::Text {
+				text: args.text.unwrap_or_default(),
+				size: args.size,
+				color: args.color.unwrap_or([0, 0, 0]),
+				background: args.background,
+			}
+		};
+
+		Ok(Self {
+			watermark: Arc::new(Watermark {
+				mark,
+				position: args.position.unwrap_or(Position::BottomRight),
+				opacity: args.opacity.unwrap_or(1.0),
+				margin: args.margin.unwrap_or(0),
+			}),
+			source,
+		})
+	}
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		self.source.parameters()
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		self.source.tilejson()
+	}
+
+	fn traversal(&self) -> &Traversal {
+		self.source.traversal()
+	}
+
+	#[context("Failed to get stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+
+		let watermark = self.watermark.clone();
+		Ok(self.source.get_stream(bbox).await?.map_item_parallel(move |mut tile| {
+			let image = tile.as_image_mut()?;
+			*image = watermark.apply(image);
+			Ok(tile)
+		}))
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"raster_watermark"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, source, factory)
+			.await
+			.map(|op| Box::new(op) as Box<dyn OperationTrait>)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use imageproc::image::GenericImageView;
+
+	fn white() -> DynamicImage {
+		DynamicImage::ImageRgb8(imageproc::image::RgbImage::from_pixel(256, 256, [255, 255, 255].into()))
+	}
+
+	fn text_mark(position: Position) -> Watermark {
+		Watermark {
+			mark: Mark::Text {
+				text: String::from("© contributors"),
+				size: None,
+				color: [0, 0, 0],
+				background: Some([255, 0, 0]),
+			},
+			position,
+			opacity: 1.0,
+			margin: 0,
+		}
+	}
+
+	#[test]
+	fn text_in_corner() {
+		let image = text_mark(Position::BottomRight).apply(&white());
+		assert!(!image.has_alpha());
+		assert_eq!(image.get_pixel(0, 0).0, [255, 255, 255, 255]);
+		assert_eq!(image.get_pixel(255, 255).0, [255, 0, 0, 255]);
+		assert_eq!(image.get_pixel(255, 200).0, [255, 255, 255, 255]);
+
+		let image = text_mark(Position::TopLeft).apply(&white());
+		assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+		assert_eq!(image.get_pixel(255, 255).0, [255, 255, 255, 255]);
+	}
+
+	#[test]
+	fn logo_with_opacity() {
+		let logo = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 255, 255]));
+		let watermark = Watermark {
+			mark: Mark::Image(logo),
+			position: Position::TopRight,
+			opacity: 0.5,
+			margin: 2,
+		};
+		let image = watermark.apply(&white());
+		assert_eq!(image.get_pixel(250, 2).0, [127, 127, 255, 255]);
+		assert_eq!(image.get_pixel(253, 2).0, [127, 127, 255, 255]);
+		assert_eq!(image.get_pixel(254, 2).0, [255, 255, 255, 255]);
+		assert_eq!(image.get_pixel(250, 1).0, [255, 255, 255, 255]);
+	}
+
+	#[test]
+	fn place() {
+		assert_eq!(Position::TopLeft.place(256, 256, 10, 20, 3), (3, 3));
+		assert_eq!(Position::BottomRight.place(256, 256, 10, 20, 3), (243, 233));
+		assert_eq!(Position::Center.place(256, 256, 10, 20, 3), (123, 118));
+	}
+
+	#[tokio::test]
+	async fn pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let op = factory
+			.operation_from_vpl(
+				r#"from_debug format=png | raster_flatten | raster_watermark text="© test" position=top-left background=[0,255,0] opacity=0.6"#,
+			)
+			.await?;
+		let bbox = TileCoord::new(3, 2, 1)?.as_tile_bbox();
+		let image = op.get_stream(bbox).await?.next().await.unwrap().1.into_image()?;
+		let [r, g, b, _] = image.get_pixel(0, 0).0;
+		assert!((101..=102).contains(&r) && g == 255 && r == b, "{r},{g},{b}");
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn needs_text_or_image() {
+		let factory = PipelineFactory::new_dummy();
+		for vpl in [
+			"from_debug format=png | raster_watermark",
+			r#"from_debug format=png | raster_watermark text="a" image="b.png""#,
+			r#"from_debug format=mvt | raster_watermark text="a""#,
+		] {
+			assert!(factory.operation_from_vpl(vpl).await.is_err(), "{vpl}");
+		}
+	}
+}
@@ -0,0 +1,831 @@
+//! Minimal reader for Cloud Optimized GeoTIFFs (COG).
+//!
+//! Only the parts of (Big)TIFF that are needed for COGs are implemented: tiled images with
+//! 8 bit unsigned samples (gray, gray+alpha, RGB or RGBA) in chunky planar configuration,
+//! uncompressed, LZW or Deflate compressed, plus reduced-resolution overviews and the GeoTIFF
+//! keys for EPSG:3857 and EPSG:4326.
+//!
+//! Everything is read with range requests through a [`DataReader`], so the file can be local
+//! or on a web server. Only the directories are read when opening; tile data is fetched on demand.
+
+use super::decode::{Compression, undo_horizontal_predictor};
+use crate::helpers::{EARTH_CIRCUMFERENCE, native_level};
+use anyhow::{Result, anyhow, bail, ensure};
+use futures::future::try_join_all;
+use std::collections::{BTreeSet, HashMap};
+use versatiles_core::{ByteRange, GeoBBox, TileCoord, io::DataReader};
+use versatiles_derive::context;
+use versatiles_geometry::{geo::Coordinates, vector_tile::TileProjection};
+use versatiles_image::{DynamicImage, traits::*};
+
+const MAX_DIRECTORIES: usize = 64;
+/// Maximum size of a single TIFF tag value, e.g. the tile offsets of a level. Enough for millions of tiles.
+const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+
+/// TIFF tags used by the reader.
+mod tag {
+	pub const NEW_SUBFILE_TYPE: u16 = 254;
+	pub const IMAGE_WIDTH: u16 = 256;
+	pub const IMAGE_LENGTH: u16 = 257;
+	pub const BITS_PER_SAMPLE: u16 = 258;
+	pub const COMPRESSION: u16 = 259;
+	pub const PHOTOMETRIC: u16 = 262;
+	pub const SAMPLES_PER_PIXEL: u16 = 277;
+	pub const PLANAR_CONFIGURATION: u16 = 284;
+	pub const PREDICTOR: u16 = 317;
+	pub const TILE_WIDTH: u16 = 322;
+	pub const TILE_LENGTH: u16 = 323;
+	pub const TILE_OFFSETS: u16 = 324;
+	pub const TILE_BYTE_COUNTS: u16 = 325;
+	pub const SAMPLE_FORMAT: u16 = 339;
+	pub const MODEL_PIXEL_SCALE: u16 = 33550;
+	pub const MODEL_TIEPOINT: u16 = 33922;
+	pub const GEO_KEY_DIRECTORY: u16 = 34735;
+	pub const GDAL_NODATA: u16 = 42113;
+}
+
+/// Coordinate reference systems that can be reprojected to WebMercator tiles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Crs {
+	/// EPSG:3857, coordinates in meters
+	WebMercator,
+	/// EPSG:4326, coordinates in degrees
+	Wgs84,
+}
+
+impl Crs {
+	/// Converts tile-local pixel coordinates of `projection` into coordinates of this CRS.
+	fn pixel_to_crs(self, projection: &TileProjection, pixel: &Coordinates) -> Coordinates {
+		match self {
+			Crs::WebMercator => projection.to_mercator(pixel),
+			Crs::Wgs84 => projection.to_lon_lat(pixel),
+		}
+	}
+
+	/// Converts coordinates of this CRS into longitude/latitude.
+	fn crs_to_lon_lat(self, x: f64, y: f64) -> (f64, f64) {
+		match self {
+			Crs::WebMercator => {
+				let world = TileProjection::world();
+				let lon_lat = world.to_lon_lat(&world.from_mercator(&Coordinates::new(x, y)));
+				(lon_lat.x(), lon_lat.y())
+			}
+			Crs::Wgs84 => (x, y),
+		}
+	}
+
+	/// Length of a WebMercator meter in units of this CRS, measured along the equator.
+	fn units_per_meter(self) -> f64 {
+		match self {
+			Crs::WebMercator => 1.0,
+			Crs::Wgs84 => 360.0 / EARTH_CIRCUMFERENCE,
+		}
+	}
+}
+
+/// The GeoTIFF keys that are needed to determine the CRS.
+#[derive(Debug, Default, PartialEq)]
+struct GeoKeys {
+	model_type: Option<u64>,
+	projected: Option<u64>,
+	geographic: Option<u64>,
+	pixel_is_point: bool,
+}
+
+impl GeoKeys {
+	/// Parses the values of the `GeoKeyDirectory` tag.
+	fn parse(values: &[u64]) -> Result<GeoKeys> {
+		ensure!(values.len() >= 4, "GeoKeyDirectory is too short");
+		let count = values[3] as usize;
+		ensure!(
+			values.len() >= 4 + count * 4,
+			"GeoKeyDirectory announces {count} keys, but is too short"
+		);
+
+		let mut keys = GeoKeys::default();
+		for key in values[4..4 + count * 4].chunks(4) {
+			// values stored in other tags (e.g. citations) are not needed
+			if key[1] != 0 {
+				continue;
+			}
+			match key[0] {
+				1024 => keys.model_type = Some(key[3]),
+				1025 => keys.pixel_is_point = key[3] == 2,
+				2048 => keys.geographic = Some(key[3]),
+				3072 => keys.projected = Some(key[3]),
+				_ => {}
+			}
+		}
+		Ok(keys)
+	}
+
+	fn crs(&self) -> Result<Crs> {
+		match (self.model_type, self.projected, self.geographic) {
+			(_, Some(3857 | 3785 | 900913 | 102100 | 102113), _) => Ok(Crs::WebMercator),
+			(Some(2) | None, None, Some(4326)) => Ok(Crs::Wgs84),
+			_ => bail!("unsupported coordinate reference system {self:?}, only EPSG:3857 and EPSG:4326 are supported"),
+		}
+	}
+}
+
+/// A raw TIFF directory entry.
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+	field_type: u16,
+	count: u64,
+	/// The value itself if it fits, otherwise the offset of the value.
+	value: [u8; 8],
+}
+
+type Directory = HashMap<u16, Entry>;
+
+/// Low level access to the TIFF structure.
+struct TiffReader {
+	reader: DataReader,
+	little_endian: bool,
+	big_tiff: bool,
+}
+
+impl TiffReader {
+	async fn read(&self, offset: u64, length: u64) -> Result<Vec<u8>> {
+		Ok(self
+			.reader
+			.read_range(&ByteRange::new(offset, length))
+			.await?
+			.into_vec())
+	}
+
+	/// Decodes an unsigned integer of 1 to 8 bytes in the byte order of the file.
+	fn uint(&self, bytes: &[u8]) -> u64 {
+		let fold = |value: u64, byte: &u8| (value << 8) | u64::from(*byte);
+		if self.little_endian {
+			bytes.iter().rev().fold(0, fold)
+		} else {
+			bytes.iter().fold(0, fold)
+		}
+	}
+
+	/// Reads the directory at `offset` and returns it together with the offset of the next one.
+	async fn read_directory(&self, offset: u64) -> Result<(Directory, u64)> {
+		let (count_size, entry_size, offset_size) = if self.big_tiff { (8, 20, 8) } else { (2, 12, 4) };
+		let count = self.uint(&self.read(offset, count_size).await?);
+		ensure!(count < 4096, "invalid TIFF directory with {count} entries");
+
+		let data = self.read(offset + count_size, count * entry_size + offset_size).await?;
+		let (entries, next) = data.split_at((count * entry_size) as usize);
+
+		let mut directory = Directory::new();
+		for chunk in entries.chunks(entry_size as usize) {
+			let (count, value) = if self.big_tiff {
+				(self.uint(&chunk[4..12]), &chunk[12..20])
+			} else {
+				(self.uint(&chunk[4..8]), &chunk[8..12])
+			};
+			let mut entry = Entry {
+				field_type: self.uint(&chunk[2..4]) as u16,
+				count,
+				value: [0; 8],
+			};
+			entry.value[..value.len()].copy_from_slice(value);
+			directory.insert(self.uint(&chunk[0..2]) as u16, entry);
+		}
+		Ok((directory, self.uint(next)))
+	}
+
+	/// Returns the raw bytes of an entry value, reading them from the file if necessary.
+	async fn entry_bytes(&self, entry: &Entry) -> Result<Vec<u8>> {
+		let size = match entry.field_type {
+			1 | 2 | 6 | 7 => 1,
+			3 | 8 => 2,
+			4 | 9 | 11 => 4,
+			5 | 10 | 12 | 16 | 17 | 18 => 8,
+			t => bail!("unknown TIFF field type {t}"),
+		};
+		let length = entry
+			.count
+			.checked_mul(size)
+			.filter(|length| *length <= MAX_ENTRY_SIZE)
+			.ok_or_else(|| anyhow!("TIFF tag value with {} entries is too large", entry.count))?;
+		let inline_size = if self.big_tiff { 8 } else { 4 };
+		if length <= inline_size {
+			Ok(entry.value[..length as usize].to_vec())
+		} else {
+			let offset = self.uint(&entry.value[..inline_size as usize]);
+			self.read(offset, length).await
+		}
+	}
+
+	async fn integers(&self, directory: &Directory, tag: u16) -> Result<Option<Vec<u64>>> {
+		let Some(entry) = directory.get(&tag) else {
+			return Ok(None);
+		};
+		let size = match entry.field_type {
+			1 => 1,
+			3 => 2,
+			4 => 4,
+			16 => 8,
+			t => bail!("TIFF tag {tag} has type {t}, expected an unsigned integer"),
+		};
+		let bytes = self.entry_bytes(entry).await?;
+		Ok(Some(bytes.chunks(size).map(|c| self.uint(c)).collect()))
+	}
+
+	async fn integer(&self, directory: &Directory, tag: u16) -> Result<Option<u64>> {
+		Ok(self
+			.integers(directory, tag)
+			.await?
+			.and_then(|values| values.first().copied()))
+	}
+
+	async fn required_integer(&self, directory: &Directory, tag: u16, name: &str) -> Result<u64> {
+		self
+			.integer(directory, tag)
+			.await?
+			.ok_or_else(|| anyhow!("missing TIFF tag {name}"))
+	}
+
+	async fn doubles(&self, directory: &Directory, tag: u16) -> Result<Option<Vec<f64>>> {
+		let Some(entry) = directory.get(&tag) else {
+			return Ok(None);
+		};
+		ensure!(
+			entry.field_type == 12,
+			"TIFF tag {tag} has type {}, expected a double",
+			entry.field_type
+		);
+		let bytes = self.entry_bytes(entry).await?;
+		Ok(Some(bytes.chunks(8).map(|c| f64::from_bits(self.uint(c))).collect()))
+	}
+
+	async fn ascii(&self, directory: &Directory, tag: u16) -> Result<Option<String>> {
+		let Some(entry) = directory.get(&tag) else {
+			return Ok(None);
+		};
+		let bytes = self.entry_bytes(entry).await?;
+		Ok(Some(String::from_utf8_lossy(&bytes).trim_end_matches('\0').to_string()))
+	}
+
+	async fn read_level(&self, directory: &Directory) -> Result<CogLevel> {
+		let width = self.required_integer(directory, tag::IMAGE_WIDTH, "ImageWidth").await? as u32;
+		let height = self
+			.required_integer(directory, tag::IMAGE_LENGTH, "ImageLength")
+			.await? as u32;
+		let (Some(tile_width), Some(tile_height)) = (
+			self.integer(directory, tag::TILE_WIDTH).await?,
+			self.integer(directory, tag::TILE_LENGTH).await?,
+		) else {
+			bail!("GeoTIFF is not tiled, please convert it to a Cloud Optimized GeoTIFF")
+		};
+		ensure!(tile_width > 0 && tile_height > 0, "invalid tile size");
+
+		let tile_offsets = self
+			.integers(directory, tag::TILE_OFFSETS)
+			.await?
+			.ok_or_else(|| anyhow!("missing TIFF tag TileOffsets"))?;
+		let tile_byte_counts = self
+			.integers(directory, tag::TILE_BYTE_COUNTS)
+			.await?
+			.ok_or_else(|| anyhow!("missing TIFF tag TileByteCounts"))?;
+
+		let level = CogLevel {
+			width,
+			height,
+			tile_width: tile_width as u32,
+			tile_height: tile_height as u32,
+			tile_offsets,
+			tile_byte_counts,
+			pixel_size: [0.0; 2],
+			compression: Compression::from_tag(self.integer(directory, tag::COMPRESSION).await?.unwrap_or(1))?,
+			predictor: match self.integer(directory, tag::PREDICTOR).await?.unwrap_or(1) {
+				1 => false,
+				2 => true,
+				p => bail!("unsupported TIFF predictor {p}"),
+			},
+		};
+
+		let tile_count = (level.tiles_across() * height.div_ceil(level.tile_height)) as usize;
+		ensure!(
+			level.tile_offsets.len() >= tile_count && level.tile_byte_counts.len() >= tile_count,
+			"expected {tile_count} tiles, but found {} offsets and {} byte counts",
+			level.tile_offsets.len(),
+			level.tile_byte_counts.len()
+		);
+		Ok(level)
+	}
+}
+
+/// One resolution of the image: the full resolution image or an overview.
+#[derive(Clone, Debug)]
+pub struct CogLevel {
+	pub width: u32,
+	pub height: u32,
+	tile_width: u32,
+	tile_height: u32,
+	tile_offsets: Vec<u64>,
+	tile_byte_counts: Vec<u64>,
+	/// Width and height of a pixel in units of the CRS.
+	pub pixel_size: [f64; 2],
+	compression: Compression,
+	predictor: bool,
+}
+
+impl CogLevel {
+	fn tiles_across(&self) -> u32 {
+		self.width.div_ceil(self.tile_width)
+	}
+}
+
+/// An opened Cloud Optimized GeoTIFF.
+pub struct Cog {
+	tiff: TiffReader,
+	pub crs: Crs,
+	/// Upper left corner of the image in units of the CRS.
+	origin: [f64; 2],
+	samples: usize,
+	nodata: Option<u8>,
+	/// All resolutions, starting with the finest.
+	pub levels: Vec<CogLevel>,
+}
+
+impl Cog {
+	/// Reads the TIFF header and all image directories.
+	#[context("Failed to open COG")]
+	pub async fn open(reader: DataReader) -> Result<Cog> {
+		let header = reader.read_range(&ByteRange::new(0, 16)).await?.into_vec();
+		let little_endian = match &header[0..2] {
+			b"II" => true,
+			b"MM" => false,
+			_ => bail!("not a TIFF file"),
+		};
+		let mut tiff = TiffReader {
+			reader,
+			little_endian,
+			big_tiff: false,
+		};
+		let mut offset = match tiff.uint(&header[2..4]) {
+			42 => tiff.uint(&header[4..8]),
+			43 => {
+				tiff.big_tiff = true;
+				tiff.uint(&header[8..16])
+			}
+			version => bail!("unknown TIFF version {version}"),
+		};
+
+		let mut directories = Vec::new();
+		while offset != 0 {
+			ensure!(directories.len() < MAX_DIRECTORIES, "too many TIFF directories");
+			let (directory, next) = tiff.read_directory(offset).await?;
+			directories.push(directory);
+			offset = next;
+		}
+		let main = directories.first().ok_or_else(|| anyhow!("TIFF contains no image"))?;
+
+		let samples = tiff.integer(main, tag::SAMPLES_PER_PIXEL).await?.unwrap_or(1) as usize;
+		ensure!(
+			(1..=4).contains(&samples),
+			"only 1 to 4 samples per pixel are supported, found {samples}"
+		);
+		let bits = tiff
+			.integers(main, tag::BITS_PER_SAMPLE)
+			.await?
+			.unwrap_or_else(|| vec![1]);
+		ensure!(
+			bits.iter().all(|b| *b == 8),
+			"only 8 bit samples are supported, found {bits:?}"
+		);
+		ensure!(
+			tiff.integer(main, tag::SAMPLE_FORMAT).await?.unwrap_or(1) == 1,
+			"only unsigned integer samples are supported"
+		);
+		ensure!(
+			tiff.integer(main, tag::PLANAR_CONFIGURATION).await?.unwrap_or(1) == 1,
+			"only interleaved samples (PlanarConfiguration=1) are supported"
+		);
+		match tiff.integer(main, tag::PHOTOMETRIC).await? {
+			None | Some(1) | Some(2) => {}
+			Some(p) => bail!("unsupported photometric interpretation {p}, only gray and RGB are supported"),
+		}
+
+		let scale = tiff
+			.doubles(main, tag::MODEL_PIXEL_SCALE)
+			.await?
+			.ok_or_else(|| anyhow!("missing ModelPixelScale, only north-up GeoTIFFs are supported"))?;
+		let tiepoint = tiff
+			.doubles(main, tag::MODEL_TIEPOINT)
+			.await?
+			.ok_or_else(|| anyhow!("missing ModelTiepoint"))?;
+		ensure!(scale.len() >= 2 && tiepoint.len() >= 6, "invalid georeferencing");
+		let geo_keys = GeoKeys::parse(
+			&tiff
+				.integers(main, tag::GEO_KEY_DIRECTORY)
+				.await?
+				.ok_or_else(|| anyhow!("missing GeoKeyDirectory, file is not a GeoTIFF"))?,
+		)?;
+		let crs = geo_keys.crs()?;
+		let mut origin = [
+			tiepoint[3] - tiepoint[0] * scale[0],
+			tiepoint[4] + tiepoint[1] * scale[1],
+		];
+		if geo_keys.pixel_is_point {
+			origin[0] -= scale[0] / 2.0;
+			origin[1] += scale[1] / 2.0;
+		}
+
+		let nodata = tiff
+			.ascii(main, tag::GDAL_NODATA)
+			.await?
+			.and_then(|text| text.trim().parse::<f64>().ok())
+			.filter(|value| (0.0..=255.0).contains(value) && value.fract() == 0.0)
+			.map(|value| value as u8);
+
+		let mut levels = Vec::new();
+		for (index, directory) in directories.iter().enumerate() {
+			let subfile_type = tiff.integer(directory, tag::NEW_SUBFILE_TYPE).await?.unwrap_or(0);
+			// only use reduced resolution images (bit 0) that are no transparency masks (bit 2)
+			if index > 0 && (subfile_type & 1 == 0 || subfile_type & 4 != 0) {
+				continue;
+			}
+			levels.push(tiff.read_level(directory).await?);
+		}
+
+		let (full_width, full_height) = (f64::from(levels[0].width), f64::from(levels[0].height));
+		for level in levels.iter_mut() {
+			level.pixel_size = [
+				scale[0] * full_width / f64::from(level.width),
+				scale[1] * full_height / f64::from(level.height),
+			];
+		}
+		levels.sort_by(|a, b| a.pixel_size[0].total_cmp(&b.pixel_size[0]));
+
+		Ok(Cog {
+			tiff,
+			crs,
+			origin,
+			samples,
+			nodata,
+			levels,
+		})
+	}
+
+	/// Bounding box of the image in longitude/latitude.
+	pub fn geo_bbox(&self) -> GeoBBox {
+		let level = &self.levels[0];
+		let [x0, y0] = self.origin;
+		let x1 = x0 + f64::from(level.width) * level.pixel_size[0];
+		let y1 = y0 - f64::from(level.height) * level.pixel_size[1];
+		let (lon0, lat0) = self.crs.crs_to_lon_lat(x0, y0);
+		let (lon1, lat1) = self.crs.crs_to_lon_lat(x1, y1);
+		GeoBBox::new_normalized(lon0, lat0, lon1, lat1)
+	}
+
+	/// The lowest zoom level at which tiles of `tile_size` pixels have at least the native resolution.
+	pub fn native_level(&self, tile_size: u32) -> u8 {
+		native_level(self.levels[0].pixel_size[0] / self.crs.units_per_meter(), tile_size)
+	}
+
+	/// Returns the coarsest level that is at least as detailed as `resolution` (in CRS units per pixel).
+	fn select_level(&self, resolution: f64) -> &CogLevel {
+		self
+			.levels
+			.iter()
+			.rev()
+			.find(|level| level.pixel_size[0] <= resolution * 1.000_001)
+			.unwrap_or(&self.levels[0])
+	}
+
+	/// Reads and decodes one internal tile. Returns `None` for sparse tiles, which have no data.
+	async fn read_tile(&self, level: &CogLevel, col: u32, row: u32) -> Result<Option<Vec<u8>>> {
+		let index = (row * level.tiles_across() + col) as usize;
+		let length = level.tile_byte_counts[index];
+		if length == 0 {
+			return Ok(None);
+		}
+		let row_length = level.tile_width as usize * self.samples;
+		let size = row_length * level.tile_height as usize;
+
+		let data = self.tiff.read(level.tile_offsets[index], length).await?;
+		let mut data = level.compression.decompress(&data, size)?;
+		ensure!(
+			data.len() >= size,
+			"tile {col},{row} has {} bytes after decompression, expected {size}",
+			data.len()
+		);
+		data.truncate(size);
+		if level.predictor {
+			undo_horizontal_predictor(&mut data, row_length, self.samples);
+		}
+		Ok(Some(data))
+	}
+
+	fn is_nodata(&self, value: &[u8]) -> bool {
+		let color = if self.samples.is_multiple_of(2) {
+			&value[..self.samples - 1]
+		} else {
+			value
+		};
+		self.nodata.is_some_and(|nodata| color.iter().all(|v| *v == nodata))
+	}
+
+	/// Renders the WebMercator tile `coord` as an RGBA image of `tile_size`×`tile_size` pixels,
+	/// using nearest neighbour resampling of the best matching overview.
+	///
+	/// Returns `None` if the tile doesn't contain any data.
+	#[context("Failed to render tile {coord:?} from COG")]
+	pub async fn render_tile(&self, coord: &TileCoord, tile_size: u32) -> Result<Option<DynamicImage>> {
+		let size = tile_size as usize;
+		let resolution = EARTH_CIRCUMFERENCE / (f64::from(tile_size) * 2f64.powi(i32::from(coord.level)));
+		let level = self.select_level(resolution * self.crs.units_per_meter());
+		let [origin_x, origin_y] = self.origin;
+		let [pixel_width, pixel_height] = level.pixel_size;
+		let projection = TileProjection::new(*coord, tile_size);
+
+		// position of the source pixel for every target pixel
+		let mut positions = Vec::with_capacity(size * size);
+		for py in 0..size {
+			for px in 0..size {
+				let pixel = Coordinates::new(px as f64 + 0.5, py as f64 + 0.5);
+				let position = self.crs.pixel_to_crs(&projection, &pixel);
+				let (x, y) = (position.x(), position.y());
+				let col = ((x - origin_x) / pixel_width).floor();
+				let row = ((origin_y - y) / pixel_height).floor();
+				let inside = col >= 0.0 && row >= 0.0 && col < f64::from(level.width) && row < f64::from(level.height);
+				positions.push(inside.then_some((col as u32, row as u32)));
+			}
+		}
+
+		let (tile_width, tile_height) = (level.tile_width, level.tile_height);
+		let tiles = positions
+			.iter()
+			.flatten()
+			.map(|(col, row)| (col / tile_width, row / tile_height))
+			.collect::<BTreeSet<_>>();
+		if tiles.is_empty() {
+			return Ok(None);
+		}
+
+		let data = try_join_all(tiles.into_iter().map(|(col, row)| async move {
+			Ok::<_, anyhow::Error>(((col, row), self.read_tile(level, col, row).await?))
+		}))
+		.await?
+		.into_iter()
+		.filter_map(|(key, data)| data.map(|data| (key, data)))
+		.collect::<HashMap<_, _>>();
+
+		let mut pixels = vec![0u8; size * size * 4];
+		for (pixel, position) in pixels.chunks_exact_mut(4).zip(positions) {
+			let Some((col, row)) = position else { continue };
+			let Some(tile) = data.get(&(col / tile_width, row / tile_height)) else {
+				continue;
+			};
+			let offset = ((row % tile_height) * tile_width + col % tile_width) as usize * self.samples;
+			let value = &tile[offset..offset + self.samples];
+			if self.is_nodata(value) {
+				continue;
+			}
+			pixel.copy_from_slice(&match *value {
+				[v] => [v, v, v, 255],
+				[v, a] => [v, v, v, a],
+				[r, g, b] => [r, g, b, 255],
+				[r, g, b, a] => [r, g, b, a],
+				_ => unreachable!("samples are checked when opening"),
+			});
+		}
+
+		Ok(DynamicImage::from_raw(size, size, pixels)?.into_optional())
+	}
+}
+
+impl std::fmt::Debug for Cog {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Cog")
+			.field("name", &self.tiff.reader.get_name())
+			.field("crs", &self.crs)
+			.field("samples", &self.samples)
+			.field("levels", &self.levels.len())
+			.finish()
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+
+	const MERCATOR_HALF: f64 = EARTH_CIRCUMFERENCE / 2.0;
+	use versatiles_core::{Blob, io::DataReaderBlob};
+
+	pub const TEST_TILE_SIZE: u32 = 256;
+
+	/// Builds a little endian, uncompressed RGB COG with one uniformly colored image per level.
+	/// `levels` lists width, height and color, starting with the full resolution.
+	/// If `sparse_tile` is set, this tile of the full resolution is left empty.
+	pub fn build_test_cog(
+		epsg: u16,
+		origin: [f64; 2],
+		pixel_size: f64,
+		levels: &[(u32, u32, [u8; 3])],
+		sparse_tile: Option<usize>,
+	) -> Vec<u8> {
+		fn value(field_type: u16, count: usize, bytes: Vec<u8>) -> (u16, u32, Vec<u8>) {
+			(field_type, count as u32, bytes)
+		}
+		let short = |v: &[u16]| value(3, v.len(), v.iter().flat_map(|v| v.to_le_bytes()).collect());
+		let long = |v: &[u32]| value(4, v.len(), v.iter().flat_map(|v| v.to_le_bytes()).collect());
+		let double = |v: &[f64]| value(12, v.len(), v.iter().flat_map(|v| v.to_le_bytes()).collect());
+
+		let mut data = b"II\x2a\x00\x00\x00\x00\x00".to_vec();
+		let mut next_pointer = 4;
+		let tile_bytes = (TEST_TILE_SIZE * TEST_TILE_SIZE * 3) as usize;
+
+		for (index, (width, height, color)) in levels.iter().enumerate() {
+			let tile_count = (width.div_ceil(TEST_TILE_SIZE) * height.div_ceil(TEST_TILE_SIZE)) as usize;
+			let mut offsets = vec![];
+			let mut counts = vec![];
+			for tile in 0..tile_count {
+				if index == 0 && sparse_tile == Some(tile) {
+					offsets.push(0);
+					counts.push(0);
+				} else {
+					offsets.push(data.len() as u32);
+					counts.push(tile_bytes as u32);
+					data.extend(color.iter().cycle().take(tile_bytes));
+				}
+			}
+
+			let mut entries = vec![
+				(tag::NEW_SUBFILE_TYPE, long(&[u32::from(index > 0)])),
+				(tag::IMAGE_WIDTH, long(&[*width])),
+				(tag::IMAGE_LENGTH, long(&[*height])),
+				(tag::BITS_PER_SAMPLE, short(&[8, 8, 8])),
+				(tag::COMPRESSION, short(&[1])),
+				(tag::PHOTOMETRIC, short(&[2])),
+				(tag::SAMPLES_PER_PIXEL, short(&[3])),
+				(tag::PLANAR_CONFIGURATION, short(&[1])),
+				(tag::TILE_WIDTH, long(&[TEST_TILE_SIZE])),
+				(tag::TILE_LENGTH, long(&[TEST_TILE_SIZE])),
+				(tag::TILE_OFFSETS, long(&offsets)),
+				(tag::TILE_BYTE_COUNTS, long(&counts)),
+			];
+			if index == 0 {
+				let geo_keys: &[u16] = if epsg == 4326 {
+					&[1, 1, 0, 3, 1024, 0, 1, 2, 1025, 0, 1, 1, 2048, 0, 1, 4326]
+				} else {
+					&[1, 1, 0, 3, 1024, 0, 1, 1, 1025, 0, 1, 1, 3072, 0, 1, epsg]
+				};
+				entries.push((tag::MODEL_PIXEL_SCALE, double(&[pixel_size, pixel_size, 0.0])));
+				entries.push((tag::MODEL_TIEPOINT, double(&[0.0, 0.0, 0.0, origin[0], origin[1], 0.0])));
+				entries.push((tag::GEO_KEY_DIRECTORY, short(geo_keys)));
+			}
+
+			// out-of-line values first, then the directory
+			let mut values = vec![];
+			for (_, (_, _, bytes)) in entries.iter() {
+				if bytes.len() > 4 {
+					values.push(data.len() as u32);
+					data.extend(bytes);
+				} else {
+					values.push(0);
+				}
+			}
+			let directory_offset = data.len() as u32;
+			data[next_pointer..next_pointer + 4].copy_from_slice(&directory_offset.to_le_bytes());
+			data.extend((entries.len() as u16).to_le_bytes());
+			for ((tag, (field_type, count, bytes)), offset) in entries.iter().zip(values) {
+				data.extend(tag.to_le_bytes());
+				data.extend(field_type.to_le_bytes());
+				data.extend(count.to_le_bytes());
+				if bytes.len() > 4 {
+					data.extend(offset.to_le_bytes());
+				} else {
+					let mut inline = bytes.clone();
+					inline.resize(4, 0);
+					data.extend(inline);
+				}
+			}
+			next_pointer = data.len();
+			data.extend([0; 4]);
+		}
+		data
+	}
+
+	pub async fn open_test_cog(data: Vec<u8>) -> Result<Cog> {
+		Cog::open(Box::new(DataReaderBlob::from(Blob::from(data)))).await
+	}
+
+	/// A WebMercator COG covering exactly the tile 1/1/0, with an overview.
+	pub async fn mercator_cog() -> Result<Cog> {
+		let levels = [(512, 512, [255, 0, 0]), (256, 256, [0, 0, 255])];
+		open_test_cog(build_test_cog(
+			3857,
+			[0.0, MERCATOR_HALF],
+			MERCATOR_HALF / 512.0,
+			&levels,
+			Some(3),
+		))
+		.await
+	}
+
+	#[tokio::test]
+	async fn open_mercator() -> Result<()> {
+		let cog = mercator_cog().await?;
+		assert_eq!(cog.crs, Crs::WebMercator);
+		assert_eq!(cog.levels.len(), 2);
+		assert_eq!(cog.levels[1].pixel_size, [MERCATOR_HALF / 256.0; 2]);
+		assert_eq!(cog.native_level(256), 2);
+		assert_eq!(cog.native_level(512), 1);
+
+		let bbox = cog.geo_bbox().as_array().map(|v| (v * 100.0).round() / 100.0);
+		assert_eq!(bbox, [0.0, 0.0, 180.0, 85.05]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn render_mercator() -> Result<()> {
+		async fn render(cog: &Cog, level: u8, x: u32, y: u32) -> Result<Option<Vec<u8>>> {
+			let image = cog.render_tile(&TileCoord::new(level, x, y)?, 256).await?;
+			Ok(image.map(|image| image.average_color()))
+		}
+		let cog = mercator_cog().await?;
+
+		// overview
+		assert_eq!(render(&cog, 1, 1, 0).await?, Some(vec![0, 0, 255, 255]));
+		// full resolution
+		assert_eq!(render(&cog, 2, 2, 0).await?, Some(vec![255, 0, 0, 255]));
+		// sparse tile
+		assert_eq!(render(&cog, 2, 3, 1).await?, None);
+		// outside
+		assert_eq!(render(&cog, 1, 0, 0).await?, None);
+		assert_eq!(render(&cog, 2, 2, 2).await?, None);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn render_wgs84() -> Result<()> {
+		let data = build_test_cog(4326, [-180.0, 90.0], 360.0 / 512.0, &[(512, 256, [9, 99, 199])], None);
+		let cog = open_test_cog(data).await?;
+		assert_eq!(cog.crs, Crs::Wgs84);
+		assert_eq!(cog.geo_bbox().as_array(), [-180.0, -90.0, 180.0, 90.0]);
+
+		let image = cog.render_tile(&TileCoord::new(0, 0, 0)?, 256).await?.unwrap();
+		assert!(image.is_opaque());
+		assert_eq!(image.average_color(), [9, 99, 199, 255]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn open_errors() {
+		let error = |data: Vec<u8>| async move { format!("{:?}", open_test_cog(data).await.unwrap_err()) };
+		assert!(error(b"GIF89a-not-a-tiff".to_vec()).await.contains("not a TIFF file"));
+		assert!(
+			error(build_test_cog(32632, [0.0, 0.0], 1.0, &[(256, 256, [0; 3])], None))
+				.await
+				.contains("unsupported coordinate reference system")
+		);
+	}
+
+	#[tokio::test]
+	async fn entry_size_limit() {
+		let tiff = TiffReader {
+			reader: Box::new(DataReaderBlob::from(Blob::from(vec![0; 16]))),
+			little_endian: true,
+			big_tiff: true,
+		};
+		let entry = |count| Entry {
+			field_type: 16,
+			count,
+			value: [0; 8],
+		};
+		assert!(tiff.entry_bytes(&entry(u64::MAX / 4)).await.is_err());
+		assert!(tiff.entry_bytes(&entry(MAX_ENTRY_SIZE)).await.is_err());
+		assert_eq!(tiff.entry_bytes(&entry(2)).await.unwrap(), vec![0; 16]);
+	}
+
+	#[test]
+	fn geo_keys() -> Result<()> {
+		let keys = GeoKeys::parse(&[1, 1, 0, 2, 1024, 0, 1, 2, 2048, 0, 1, 4326])?;
+		assert_eq!(keys.crs()?, Crs::Wgs84);
+		let keys = GeoKeys::parse(&[1, 1, 0, 2, 1025, 0, 1, 2, 3072, 0, 1, 3857])?;
+		assert!(keys.pixel_is_point);
+		assert_eq!(keys.crs()?, Crs::WebMercator);
+		assert!(GeoKeys::parse(&[1, 1, 0, 2, 1024, 0, 1, 2]).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn crs_conversion() {
+		let (lon, lat) = Crs::WebMercator.crs_to_lon_lat(MERCATOR_HALF / 2.0, 0.0);
+		assert!((lon - 90.0).abs() < 1e-9 && lat.abs() < 1e-9, "{lon}, {lat}");
+		let (lon, lat) = Crs::WebMercator.crs_to_lon_lat(-MERCATOR_HALF, MERCATOR_HALF);
+		assert!((lon + 180.0).abs() < 1e-9);
+		assert!((lat - 85.051_128_78).abs() < 1e-6);
+
+		let projection = TileProjection::new(TileCoord::new(1, 1, 0).unwrap(), 256);
+		let pixel = Coordinates::new(0.0, 256.0);
+		let position = Crs::WebMercator.pixel_to_crs(&projection, &pixel);
+		assert!(position.x().abs() < 1e-6 && position.y().abs() < 1e-6, "{position:?}");
+		let position = Crs::Wgs84.pixel_to_crs(&projection, &Coordinates::new(128.0, 256.0));
+		assert!(
+			(position.x() - 90.0).abs() < 1e-9 && position.y().abs() < 1e-9,
+			"{position:?}"
+		);
+	}
+}
@@ -0,0 +1,233 @@
+//! Decompression of TIFF tile data: LZW, Deflate and the horizontal differencing predictor.
+
+use anyhow::{Result, bail, ensure};
+use std::io::Read;
+
+const LZW_CLEAR: u16 = 256;
+const LZW_END: u16 = 257;
+const LZW_MAX_BITS: u8 = 12;
+
+/// Compression schemes of TIFF tiles that can be decoded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+	None,
+	Lzw,
+	Deflate,
+}
+
+impl Compression {
+	/// Maps the value of the TIFF `Compression` tag.
+	pub fn from_tag(value: u64) -> Result<Self> {
+		Ok(match value {
+			1 => Compression::None,
+			5 => Compression::Lzw,
+			8 | 32946 => Compression::Deflate,
+			7 => bail!("JPEG compressed GeoTIFFs are not supported, use LZW or Deflate"),
+			_ => bail!("unsupported TIFF compression {value}"),
+		})
+	}
+
+	/// Decompresses the data of a tile that should have `size` bytes.
+	///
+	/// Deflate data is only inflated up to `size` bytes, so a crafted tile can't exhaust memory.
+	pub fn decompress(&self, data: &[u8], size: usize) -> Result<Vec<u8>> {
+		match self {
+			Compression::None => Ok(data.to_vec()),
+			Compression::Lzw => decode_lzw(data),
+			Compression::Deflate => {
+				let mut result = Vec::with_capacity(size);
+				flate2::read::ZlibDecoder::new(data)
+					.take(size as u64 + 1)
+					.read_to_end(&mut result)?;
+				ensure!(result.len() <= size, "deflated tile has more than {size} bytes");
+				Ok(result)
+			}
+		}
+	}
+}
+
+/// Decodes TIFF flavoured LZW: MSB-first codes of 9 to 12 bits, switching the code width
+/// one code early, as libtiff does.
+pub fn decode_lzw(data: &[u8]) -> Result<Vec<u8>> {
+	let mut result = Vec::with_capacity(data.len() * 2);
+	let mut table: Vec<Vec<u8>> = Vec::with_capacity(1 << LZW_MAX_BITS);
+	let reset = |table: &mut Vec<Vec<u8>>| {
+		table.clear();
+		table.extend((0..=255u8).map(|i| vec![i]));
+		table.push(vec![]); // clear code
+		table.push(vec![]); // end of information
+	};
+	reset(&mut table);
+
+	let mut bits: u8 = 9;
+	let mut position = 0usize;
+	let mut previous: Option<Vec<u8>> = None;
+
+	while let Some(code) = read_bits(data, position, bits) {
+		position += bits as usize;
+		match code {
+			LZW_CLEAR => {
+				reset(&mut table);
+				bits = 9;
+				previous = None;
+				continue;
+			}
+			LZW_END => break,
+			_ => {}
+		}
+
+		let entry = if (code as usize) < table.len() {
+			table[code as usize].clone()
+		} else if let Some(previous) = previous.as_ref().filter(|_| code as usize == table.len()) {
+			let mut entry = previous.clone();
+			entry.push(previous[0]);
+			entry
+		} else {
+			bail!("invalid LZW code {code} at bit {position}");
+		};
+		result.extend_from_slice(&entry);
+
+		if let Some(mut previous) = previous.take()
+			&& table.len() < (1 << LZW_MAX_BITS)
+		{
+			previous.push(entry[0]);
+			table.push(previous);
+		}
+		if table.len() >= (1 << bits) - 1 && bits < LZW_MAX_BITS {
+			bits += 1;
+		}
+		previous = Some(entry);
+	}
+
+	Ok(result)
+}
+
+/// Reads `bits` bits (MSB first) starting at bit `position`. Returns `None` at the end of `data`.
+fn read_bits(data: &[u8], position: usize, bits: u8) -> Option<u16> {
+	if position + bits as usize > data.len() * 8 {
+		return None;
+	}
+	let mut value = 0u32;
+	for i in position..position + bits as usize {
+		let bit = (data[i / 8] >> (7 - i % 8)) & 1;
+		value = (value << 1) | u32::from(bit);
+	}
+	Some(value as u16)
+}
+
+/// Reverts the horizontal differencing predictor (TIFF `Predictor` = 2) for 8 bit samples.
+pub fn undo_horizontal_predictor(data: &mut [u8], row_length: usize, samples: usize) {
+	for row in data.chunks_mut(row_length) {
+		for i in samples..row.len() {
+			row[i] = row[i].wrapping_add(row[i - samples]);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Minimal LZW encoder matching the code width switching of libtiff.
+	fn encode_lzw(data: &[u8]) -> Vec<u8> {
+		let mut output = Vec::new();
+		let (mut buffer, mut buffer_bits) = (0u32, 0u8);
+		let mut write = |code: u16, bits: u8| {
+			buffer = (buffer << bits) | u32::from(code);
+			buffer_bits += bits;
+			while buffer_bits >= 8 {
+				output.push((buffer >> (buffer_bits - 8)) as u8);
+				buffer_bits -= 8;
+			}
+		};
+
+		let mut table = std::collections::HashMap::<Vec<u8>, u16>::new();
+		let mut next_code = 258u16;
+		let mut bits = 9u8;
+		write(LZW_CLEAR, bits);
+
+		let mut current: Vec<u8> = Vec::new();
+		for &byte in data {
+			let mut candidate = current.clone();
+			candidate.push(byte);
+			if candidate.len() == 1 || table.contains_key(&candidate) {
+				current = candidate;
+				continue;
+			}
+			let code = if current.len() == 1 {
+				u16::from(current[0])
+			} else {
+				table[&current]
+			};
+			write(code, bits);
+			table.insert(candidate, next_code);
+			next_code += 1;
+			if next_code == 4094 {
+				write(LZW_CLEAR, bits);
+				table.clear();
+				next_code = 258;
+				bits = 9;
+			} else if next_code > (1 << bits) - 1 {
+				bits += 1;
+			}
+			current = vec![byte];
+		}
+		if !current.is_empty() {
+			let code = if current.len() == 1 {
+				u16::from(current[0])
+			} else {
+				table[&current]
+			};
+			write(code, bits);
+		}
+		write(LZW_END, bits);
+		write(0, 7);
+		output
+	}
+
+	#[test]
+	fn lzw_roundtrip() -> Result<()> {
+		let short = b"ABABABABBBABABAAAAAAAAAAAAAAAB".to_vec();
+		assert_eq!(decode_lzw(&encode_lzw(&short))?, short);
+
+		// long enough to use all code widths and at least one table reset
+		let long = (0..200_000u32)
+			.map(|i| (((i * 7919) % 251) ^ (i / 1000)) as u8)
+			.collect::<Vec<_>>();
+		assert_eq!(decode_lzw(&encode_lzw(&long))?, long);
+		Ok(())
+	}
+
+	#[test]
+	fn lzw_invalid_code() {
+		// clear code followed by an undefined code
+		assert!(decode_lzw(&[0x80, 0x7f, 0xf0]).is_err());
+	}
+
+	#[test]
+	fn deflate() -> Result<()> {
+		use std::io::Write;
+		let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+		encoder.write_all(b"hello hello hello")?;
+		let data = encoder.finish()?;
+		assert_eq!(Compression::Deflate.decompress(&data, 17)?, b"hello hello hello");
+		assert!(Compression::Deflate.decompress(&data, 16).is_err());
+		assert_eq!(Compression::None.decompress(b"raw", 3)?, b"raw");
+		Ok(())
+	}
+
+	#[test]
+	fn compression_from_tag() {
+		assert_eq!(Compression::from_tag(5).unwrap(), Compression::Lzw);
+		assert_eq!(Compression::from_tag(32946).unwrap(), Compression::Deflate);
+		assert!(Compression::from_tag(7).is_err());
+		assert!(Compression::from_tag(34887).is_err());
+	}
+
+	#[test]
+	fn horizontal_predictor() {
+		let mut data = vec![10, 20, 1, 1, 255, 2, /* row 2 */ 5, 5, 1, 0, 0, 0];
+		undo_horizontal_predictor(&mut data, 6, 2);
+		assert_eq!(data, [10, 20, 11, 21, 10, 23, 5, 5, 6, 5, 6, 5]);
+	}
+}
@@ -0,0 +1,220 @@
+//! # From‑COG read operation
+//!
+//! This module defines an [`Operation`] that renders raster tiles from a
+//! **Cloud Optimized GeoTIFF** (COG). The file may be local or on a web server;
+//! in both cases only the directories are read up front and tile data is
+//! fetched with range requests when a tile is requested.
+//!
+//! Every output tile is reprojected from EPSG:3857 or EPSG:4326 to the
+//! WebMercator grid with nearest neighbour resampling, using the overview that
+//! best matches the zoom level.
+
+mod cog;
+mod decode;
+
+use crate::{PipelineFactory, operations::read::traits::ReadOperationTrait, traits::*, vpl::VPLNode};
+use anyhow::{Result, bail, ensure};
+use async_trait::async_trait;
+use cog::Cog;
+use futures::{StreamExt, stream};
+use reqwest::Url;
+use std::{fmt::Debug, path::Path, sync::Arc};
+use versatiles_container::{Tile, uri_scheme};
+use versatiles_core::{io::*, *};
+use versatiles_derive::context;
+
+/// Number of tiles that are rendered concurrently.
+const CONCURRENCY: usize = 8;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Reads a Cloud Optimized GeoTIFF (COG) and renders it as raster tiles.
+/// Supported are tiled GeoTIFFs in EPSG:3857 or EPSG:4326 with 8 bit gray, gray+alpha, RGB or RGBA samples,
+/// compressed with LZW, Deflate or not at all.
+struct Args {
+	/// The filename of the COG, relative to the path of the VPL file, or an http(s) URL.
+	/// For example: `filename="satellite.tif"`.
+	filename: String,
+	/// The size of the generated tiles in pixels. (default: 512)
+	tile_size: Option<u32>,
+	/// The tile format to use for the output tiles. (default: `PNG`)
+	tile_format: Option<TileFormat>,
+	/// The maximum zoom level to generate tiles for. (default: the zoom level matching the native resolution)
+	level_max: Option<u8>,
+	/// The minimum zoom level to generate tiles for. (default: 0)
+	level_min: Option<u8>,
+}
+
+#[derive(Debug)]
+struct Operation {
+	cog: Arc<Cog>,
+	parameters: TilesReaderParameters,
+	tile_size: u32,
+	tilejson: TileJSON,
+}
+
+impl ReadOperationTrait for Operation {
+	#[context("Failed to build from_cog operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, factory: &PipelineFactory) -> Result<Box<dyn OperationTrait>>
+	where
+		Self: Sized + OperationTrait,
+	{
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		let filename = factory.resolve_filename(&args.filename);
+		let reader: DataReader = match uri_scheme(&filename).as_deref() {
+			Some("http" | "https") => DataReaderHttp::from_url(Url::parse(&filename)?)?,
+			Some(scheme) => bail!("URI scheme '{scheme}' is not supported by from_cog, use a file or an HTTP(S) URL"),
+			None => DataReaderFile::open(Path::new(&filename))?,
+		};
+		let cog = Cog::open(reader).await?;
+
+		let tile_size = args.tile_size.unwrap_or(512);
+		ensure!(tile_size > 0, "tile_size must be greater than zero");
+		let tile_format = args.tile_format.unwrap_or(TileFormat::PNG);
+		ensure!(
+			tile_format.to_type() == TileType::Raster,
+			"tile_format must be a raster format"
+		);
+
+		let level_max = args.level_max.unwrap_or_else(|| cog.native_level(tile_size));
+		let level_min = args.level_min.unwrap_or(0);
+		ensure!(
+			level_min <= level_max,
+			"level_min ({level_min}) must be ≤ level_max ({level_max})"
+		);
+
+		let bbox_pyramid = TileBBoxPyramid::from_geo_bbox(level_min, level_max, &cog.geo_bbox());
+		let parameters = TilesReaderParameters::new(tile_format, TileCompression::Uncompressed, bbox_pyramid);
+		let mut tilejson = TileJSON::default();
+		tilejson.update_from_reader_parameters(&parameters);
+
+		Ok(Box::new(Self {
+			cog: Arc::new(cog),
+			parameters,
+			tile_size,
+			tilejson,
+		}) as Box<dyn OperationTrait>)
+	}
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		&self.parameters
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		&self.tilejson
+	}
+
+	#[context("Failed to get tile stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, mut bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+		bbox.intersect_with_pyramid(&self.parameters.bbox_pyramid);
+
+		let cog = Arc::clone(&self.cog);
+		let tile_size = self.tile_size;
+		let format = self.parameters.tile_format;
+
+		let stream = stream::iter(bbox.into_iter_coords())
+			.map(move |coord| {
+				let cog = Arc::clone(&cog);
+				async move {
+					let image = match cog.render_tile(&coord, tile_size).await {
+						Ok(image) => image?,
+						Err(err) => {
+							log::warn!("failed to render tile {coord:?}: {err:?}");
+							return None;
+						}
+					};
+					match Tile::from_image(image, format) {
+						Ok(tile) => Some((coord, tile)),
+						Err(err) => {
+							log::warn!("failed to encode tile {coord:?}: {err:?}");
+							None
+						}
+					}
+				}
+			})
+			.buffer_unordered(CONCURRENCY)
+			.filter_map(futures::future::ready);
+
+		Ok(TileStream::from_stream(stream.boxed()))
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"from_cog"
+	}
+}
+
+#[async_trait]
+impl ReadOperationFactoryTrait for Factory {
+	async fn build<'a>(&self, vpl_node: VPLNode, factory: &'a PipelineFactory) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, factory).await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use assert_fs::TempDir;
+	use versatiles_image::traits::*;
+
+	/// Writes a WebMercator COG covering the tile 1/1/0 and builds `vpl`, with `{dir}` replaced by its directory.
+	async fn build(dir: &TempDir, vpl: &str) -> Result<Box<dyn OperationTrait>> {
+		let half = 20_037_508.342_789_244;
+		let levels = [(512, 512, [255, 0, 0]), (256, 256, [0, 0, 255])];
+		let data = cog::tests::build_test_cog(3857, [0.0, half], half / 512.0, &levels, None);
+		std::fs::write(dir.path().join("test.tif"), data)?;
+		let vpl = vpl.replace("{dir}", dir.path().to_str().unwrap());
+		PipelineFactory::new_dummy().operation_from_vpl(&vpl).await
+	}
+
+	#[tokio::test]
+	async fn test_build() -> Result<()> {
+		let dir = TempDir::new()?;
+		let operation = build(&dir, r#"from_cog filename="{dir}/test.tif" tile_size=256"#).await?;
+
+		let parameters = operation.parameters();
+		assert_eq!(parameters.tile_format, TileFormat::PNG);
+		assert_eq!(parameters.bbox_pyramid.get_level_min(), Some(0));
+		assert_eq!(parameters.bbox_pyramid.get_level_max(), Some(2));
+		assert_eq!(parameters.bbox_pyramid.get_level_bbox(1).count_tiles(), 1);
+
+		let tiles = operation.get_stream(TileBBox::new_full(2)?).await?.to_vec().await;
+		assert_eq!(tiles.len(), 4);
+		for (_coord, tile) in tiles {
+			let image = tile.into_image()?;
+			assert_eq!(image.width(), 256);
+			assert_eq!(image.average_color(), [255, 0, 0, 255]);
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_build_errors() -> Result<()> {
+		let dir = TempDir::new()?;
+		assert!(build(&dir, r#"from_cog filename="{dir}/missing.tif""#).await.is_err());
+		assert!(
+			build(&dir, r#"from_cog filename="{dir}/test.tif" tile_format="mvt""#)
+				.await
+				.is_err()
+		);
+		assert!(
+			build(&dir, r#"from_cog filename="{dir}/test.tif" level_min=5 level_max=3"#)
+				.await
+				.is_err()
+		);
+		Ok(())
+	}
+}
@@ -1,14 +1,6 @@
-use ab_glyph::{FontArc, PxScale};
-use imageproc::{
-	drawing::draw_text_mut,
-	image::{DynamicImage, Rgba, RgbaImage},
-};
-use lazy_static::lazy_static;
+use imageproc::image::{DynamicImage, Rgba, RgbaImage};
 use versatiles_core::TileCoord;
-
-lazy_static! {
-	static ref FONT: FontArc = FontArc::try_from_slice(include_bytes!("./trim.ttf")).unwrap();
-}
+use versatiles_image::draw::draw_text;
 
 pub fn create_debug_image(coord: &TileCoord, use_alpha: bool) -> DynamicImage {
 	let br = ((coord.x + coord.y) % 2) as u8 * 255;
@@ -16,9 +8,7 @@ pub fn create_debug_image(coord: &TileCoord, use_alpha: bool) -> DynamicImage {
 	// Build everything as RGBA; for RGB output we drop alpha at the end.
 	let mut img = RgbaImage::from_pixel(512, 512, Rgba([br, br, br, if use_alpha { 16 } else { 255 }]));
 
-	let font: &FontArc = &FONT;
-	let mut draw =
-		|y: i32, c: Rgba<u8>, text: String| draw_text_mut(&mut img, c, 220, y, PxScale::from(40f32), font, &text);
+	let mut draw = |y: i32, c: Rgba<u8>, text: String| draw_text(&mut img, 220, y, 40.0, c, &text);
 
 	draw(195, Rgba([127, 30, 16, 255]), format!("z: {}", coord.level));
 	draw(225, Rgba([0, 92, 45, 255]), format!("x: {}", coord.x));
@@ -1,6 +1,5 @@
 use ab_glyph::{Font, FontArc, Outline, OutlineCurve::*, Point};
 use anyhow::Result;
-use lazy_static::lazy_static;
 use std::{f64::consts::PI, ops::Div, vec};
 use versatiles_core::TileCoord;
 use versatiles_derive::context;
@@ -8,10 +7,7 @@ use versatiles_geometry::{
 	geo::*,
 	vector_tile::{VectorTile, VectorTileLayer},
 };
-
-lazy_static! {
-	static ref FONT: FontArc = FontArc::try_from_slice(include_bytes!("./trim.ttf")).unwrap();
-}
+use versatiles_image::draw::FONT;
 
 #[context("Creating debug vector tile for coord {:?}", coord)]
 pub fn create_debug_vector_tile(coord: &TileCoord) -> Result<VectorTile> {
@@ -0,0 +1,354 @@
+//! Minimal reader for GRIB2 files.
+//!
+//! Supported are regular latitude/longitude grids (grid definition template 3.0) with simple
+//! packing (data representation template 5.0) and optional bitmaps. Every data section of a file
+//! is one field; fields are numbered in the order they appear, across all messages.
+//! GRIB1 files and other packings (e.g. JPEG 2000 or PNG) are not supported.
+
+use super::grid::Grid;
+use anyhow::{Result, anyhow, bail, ensure};
+
+/// Value of a missing 32-bit number.
+const MISSING: u32 = u32::MAX;
+
+/// Converts a sign-magnitude number, as used by GRIB2, into an integer.
+fn signed(value: u32, bits: u32) -> i64 {
+	let sign = 1 << (bits - 1);
+	let magnitude = i64::from(value & (sign - 1));
+	if value & sign == 0 { magnitude } else { -magnitude }
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Result<u32> {
+	let bytes = data
+		.get(offset..offset + 2)
+		.ok_or_else(|| anyhow!("GRIB section is too short"))?;
+	Ok(u32::from(u16::from_be_bytes([bytes[0], bytes[1]])))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32> {
+	let bytes = data
+		.get(offset..offset + 4)
+		.ok_or_else(|| anyhow!("GRIB section is too short"))?;
+	Ok(u32::from_be_bytes(bytes.try_into()?))
+}
+
+/// Reads big-endian numbers of arbitrary bit width.
+struct BitReader<'a> {
+	data: &'a [u8],
+	pos: usize,
+}
+
+impl BitReader<'_> {
+	fn read(&mut self, bits: u32) -> Option<u32> {
+		let mut value = 0u64;
+		let mut remaining = bits;
+		while remaining > 0 {
+			let byte = *self.data.get(self.pos / 8)?;
+			let offset = (self.pos % 8) as u32;
+			let take = (8 - offset).min(remaining);
+			let chunk = (u32::from(byte) >> (8 - offset - take)) & ((1 << take) - 1);
+			value = (value << take) | u64::from(chunk);
+			self.pos += take as usize;
+			remaining -= take;
+		}
+		Some(value as u32)
+	}
+}
+
+/// Grid definition (section 3, template 3.0).
+#[derive(Clone, Debug, PartialEq)]
+struct GridDefinition {
+	width: usize,
+	height: usize,
+	lon0: f64,
+	lat0: f64,
+	dlon: f64,
+	dlat: f64,
+}
+
+impl GridDefinition {
+	fn parse(section: &[u8]) -> Result<GridDefinition> {
+		let template = u16_at(section, 12)?;
+		ensure!(
+			template == 0,
+			"unsupported grid definition template 3.{template}, only regular latitude/longitude grids (3.0) are supported"
+		);
+		ensure!(section.len() >= 72, "grid definition section is too short");
+
+		let width = u32_at(section, 30)? as usize;
+		let height = u32_at(section, 34)? as usize;
+		let basic_angle = u32_at(section, 38)?;
+		let subdivisions = u32_at(section, 42)?;
+		let unit = if basic_angle == 0 || basic_angle == MISSING || subdivisions == 0 || subdivisions == MISSING {
+			1e-6
+		} else {
+			f64::from(basic_angle) / f64::from(subdivisions)
+		};
+		let angle = |offset: usize| -> Result<f64> { Ok(signed(u32_at(section, offset)?, 32) as f64 * unit) };
+		let (lat0, lon0) = (angle(46)?, angle(50)?);
+		let (lat1, lon1) = (angle(55)?, angle(59)?);
+		let (di, dj) = (u32_at(section, 63)?, u32_at(section, 67)?);
+
+		let scan = section[71];
+		ensure!(
+			scan & 0x30 == 0,
+			"unsupported scanning mode {scan:#04x}, rows must be consecutive and scanned in the same direction"
+		);
+		let east_to_west = scan & 0x80 != 0;
+		let south_to_north = scan & 0x40 != 0;
+
+		let dlon = if di == MISSING {
+			let mut span = lon1 - lon0;
+			if !east_to_west && span < 0.0 {
+				span += 360.0;
+			} else if east_to_west && span > 0.0 {
+				span -= 360.0;
+			}
+			span / (width.max(2) - 1) as f64
+		} else {
+			f64::from(di) * unit * if east_to_west { -1.0 } else { 1.0 }
+		};
+		let dlat = if dj == MISSING {
+			(lat1 - lat0) / (height.max(2) - 1) as f64
+		} else {
+			f64::from(dj) * unit * if south_to_north { 1.0 } else { -1.0 }
+		};
+
+		Ok(GridDefinition {
+			width,
+			height,
+			lon0,
+			lat0,
+			dlon,
+			dlat,
+		})
+	}
+}
+
+/// Simple packing parameters (section 5, template 5.0).
+#[derive(Clone, Debug, PartialEq)]
+struct Packing {
+	reference: f64,
+	binary_scale: f64,
+	decimal_scale: f64,
+	bits: u32,
+}
+
+impl Packing {
+	fn parse(section: &[u8]) -> Result<Packing> {
+		let template = u16_at(section, 9)?;
+		ensure!(
+			template == 0,
+			"unsupported data representation template 5.{template}, only simple packing (5.0) is supported"
+		);
+		let bits = u32::from(*section.get(19).ok_or_else(|| anyhow!("packing section is too short"))?);
+		ensure!(bits <= 32, "unsupported number of bits per value: {bits}");
+		Ok(Packing {
+			reference: f64::from(f32::from_bits(u32_at(section, 11)?)),
+			binary_scale: 2f64.powi(signed(u16_at(section, 15)?, 16) as i32),
+			decimal_scale: 10f64.powi(-signed(u16_at(section, 17)?, 16) as i32),
+			bits,
+		})
+	}
+
+	/// Unpacks the values of a data section. Points without a bit in `bitmap` are `NaN`.
+	fn unpack(&self, data: &[u8], count: usize, bitmap: Option<&[u8]>) -> Result<Vec<f32>> {
+		let mut reader = BitReader { data, pos: 0 };
+		let mut values = Vec::with_capacity(count.min(1 << 24));
+		for index in 0..count {
+			if let Some(bitmap) = bitmap {
+				let byte = bitmap.get(index / 8).ok_or_else(|| anyhow!("bitmap is too short"))?;
+				if byte & (0x80 >> (index % 8)) == 0 {
+					values.push(f32::NAN);
+					continue;
+				}
+			}
+			let packed = reader
+				.read(self.bits)
+				.ok_or_else(|| anyhow!("data section is too short"))?;
+			let value = (self.reference + f64::from(packed) * self.binary_scale) * self.decimal_scale;
+			values.push(value as f32);
+		}
+		Ok(values)
+	}
+}
+
+/// Reads field number `index` (counting from 0) from a GRIB2 file.
+pub fn read_grib(data: &[u8], index: usize) -> Result<Grid> {
+	let mut field = 0;
+	let mut pos = 0;
+	while let Some(start) = data[pos..].windows(4).position(|w| w == b"GRIB") {
+		let message_start = pos + start;
+		let header = data
+			.get(message_start..message_start + 16)
+			.ok_or_else(|| anyhow!("GRIB message at byte {message_start} is truncated"))?;
+		let edition = header[7];
+		ensure!(
+			edition == 2,
+			"unsupported GRIB edition {edition}, only GRIB2 is supported"
+		);
+		let length = u64::from_be_bytes(header[8..16].try_into()?) as usize;
+		let message = data
+			.get(message_start..message_start.saturating_add(length))
+			.ok_or_else(|| anyhow!("GRIB message at byte {message_start} is truncated"))?;
+
+		let mut grid = None;
+		let mut packing = None;
+		let mut bitmap: Option<&[u8]> = None;
+		let mut offset = 16;
+		while offset + 4 <= message.len() && &message[offset..offset + 4] != b"7777" {
+			let section_length = u32_at(message, offset)? as usize;
+			ensure!(section_length >= 5, "invalid GRIB section length {section_length}");
+			let section = message
+				.get(offset..offset + section_length)
+				.ok_or_else(|| anyhow!("GRIB section at byte {} is truncated", message_start + offset))?;
+			match section[4] {
+				3 => grid = Some(GridDefinition::parse(section)?),
+				5 => packing = Some(Packing::parse(section)?),
+				6 => match section.get(5).copied().unwrap_or(255) {
+					0 => bitmap = Some(&section[6..]),
+					254 => {}
+					255 => bitmap = None,
+					indicator => bail!("unsupported bitmap indicator {indicator}"),
+				},
+				7 => {
+					if field == index {
+						let grid = grid
+							.as_ref()
+							.ok_or_else(|| anyhow!("data section without grid definition"))?;
+						let packing = packing
+							.as_ref()
+							.ok_or_else(|| anyhow!("data section without packing"))?;
+						let values = packing.unpack(&section[5..], grid.width * grid.height, bitmap)?;
+						return Grid::new(
+							grid.width,
+							grid.height,
+							grid.lon0,
+							grid.lat0,
+							grid.dlon,
+							grid.dlat,
+							values,
+						);
+					}
+					field += 1;
+				}
+				_ => {}
+			}
+			offset += section_length;
+		}
+		pos = message_start + length.max(16);
+	}
+	bail!("field {index} not found, the file contains {field} fields")
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+
+	fn section(number: u8, body: &[u8]) -> Vec<u8> {
+		let mut section = ((body.len() + 5) as u32).to_be_bytes().to_vec();
+		section.push(number);
+		section.extend_from_slice(body);
+		section
+	}
+
+	/// Grid definition of 3×2 cells of 1°, from 41°N to 40°N and 0°E to 2°E. Offsets passed to `put` are 1-based octet numbers.
+	fn grid_section(scan: u8) -> Vec<u8> {
+		let mut body = vec![0u8; 67];
+		let mut put = |offset: usize, value: u32| body[offset - 6..offset - 2].copy_from_slice(&value.to_be_bytes());
+		put(7, 6);
+		put(31, 3);
+		put(35, 2);
+		put(47, 41_000_000);
+		put(51, 0);
+		put(56, 40_000_000);
+		put(60, 2_000_000);
+		put(64, 1_000_000);
+		put(68, 1_000_000);
+		body[66] = scan;
+		section(3, &body)
+	}
+
+	fn packing_section(reference: f32, binary_scale: i16, bits: u8) -> Vec<u8> {
+		let mut body = vec![0u8; 16];
+		body[0..4].copy_from_slice(&6u32.to_be_bytes());
+		body[6..10].copy_from_slice(&reference.to_bits().to_be_bytes());
+		let magnitude = binary_scale.unsigned_abs() | if binary_scale < 0 { 0x8000 } else { 0 };
+		body[10..12].copy_from_slice(&magnitude.to_be_bytes());
+		body[14] = bits;
+		section(5, &body)
+	}
+
+	fn message(sections: &[Vec<u8>]) -> Vec<u8> {
+		let body = sections.concat();
+		let mut message = b"GRIB\0\0\0\x02".to_vec();
+		message.extend_from_slice(&((body.len() + 20) as u64).to_be_bytes());
+		message.extend_from_slice(&body);
+		message.extend_from_slice(b"7777");
+		message
+	}
+
+	/// Two messages: values 10‥15 packed with 8 bits, and 1,3,5,7,9 packed with 4 bits plus a missing point.
+	pub fn build_test_grib(scan: u8) -> Vec<u8> {
+		let mut data = message(&[
+			section(1, &[0; 16]),
+			grid_section(scan),
+			section(4, &[0; 29]),
+			packing_section(10.0, 0, 8),
+			section(6, &[255]),
+			section(7, &[0, 1, 2, 3, 4, 5]),
+		]);
+		data.extend(message(&[
+			section(1, &[0; 16]),
+			grid_section(scan),
+			section(4, &[0; 29]),
+			packing_section(1.0, 1, 4),
+			section(6, &[0, 0b1111_1000]),
+			section(7, &[0x01, 0x23, 0x40]),
+		]));
+		data
+	}
+
+	#[test]
+	fn read_fields() -> Result<()> {
+		let data = build_test_grib(0);
+
+		let grid = read_grib(&data, 0)?;
+		assert_eq!((grid.width, grid.height), (3, 2));
+		assert_eq!((grid.lon0, grid.dlon, grid.lat0, grid.dlat), (0.0, 1.0, 41.0, -1.0));
+		assert_eq!(grid.values, [10.0, 11.0, 12.0, 13.0, 14.0, 15.0]);
+
+		let grid = read_grib(&data, 1)?;
+		assert_eq!(&grid.values[..5], [1.0, 3.0, 5.0, 7.0, 9.0]);
+		assert!(grid.values[5].is_nan());
+		Ok(())
+	}
+
+	#[test]
+	fn errors() {
+		assert!(read_grib(&build_test_grib(0), 2).is_err());
+		assert!(read_grib(&build_test_grib(0x20), 0).is_err());
+		assert!(read_grib(b"GRIB\0\0\0\x01\0\0\0\0\0\0\0\x10", 0).is_err());
+		assert!(read_grib(b"no grib", 0).is_err());
+	}
+
+	#[test]
+	fn sign_magnitude() {
+		assert_eq!(signed(5, 16), 5);
+		assert_eq!(signed(0x8005, 16), -5);
+		assert_eq!(signed(0x8000_0001, 32), -1);
+	}
+
+	#[test]
+	fn bit_reader() {
+		let mut reader = BitReader {
+			data: &[0b1010_1100, 0b0101_0000],
+			pos: 0,
+		};
+		assert_eq!(reader.read(3), Some(0b101));
+		assert_eq!(reader.read(7), Some(0b011_0001));
+		assert_eq!(reader.read(0), Some(0));
+		assert_eq!(reader.read(6), Some(0b01_0000));
+		assert_eq!(reader.read(1), None);
+	}
+}
@@ -0,0 +1,215 @@
+//! Regular longitude/latitude grids and their rendering to WebMercator tiles.
+
+use crate::helpers::{EARTH_CIRCUMFERENCE, native_level};
+use anyhow::{Result, ensure};
+use std::f64::consts::PI;
+use versatiles_core::{GeoBBox, TileCoord};
+use versatiles_image::{ColorMap, DynamicImage, traits::*};
+
+/// A regular grid of values in longitude/latitude (EPSG:4326).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid {
+	pub width: usize,
+	pub height: usize,
+	/// Longitude of the center of the first column.
+	pub lon0: f64,
+	/// Latitude of the center of the first row.
+	pub lat0: f64,
+	/// Distance between two columns in degrees. Negative if the columns run from east to west.
+	pub dlon: f64,
+	/// Distance between two rows in degrees. Negative if the rows run from north to south.
+	pub dlat: f64,
+	/// Values in row-major order, `NaN` marks missing data.
+	pub values: Vec<f32>,
+}
+
+impl Grid {
+	/// Creates a grid and checks that the number of values matches its size.
+	pub fn new(
+		width: usize,
+		height: usize,
+		lon0: f64,
+		lat0: f64,
+		dlon: f64,
+		dlat: f64,
+		values: Vec<f32>,
+	) -> Result<Grid> {
+		ensure!(width > 0 && height > 0, "grid must not be empty");
+		ensure!(
+			values.len() == width * height,
+			"grid of {width}x{height} cells has {} values",
+			values.len()
+		);
+		ensure!(
+			dlon.is_finite() && dlat.is_finite() && dlon != 0.0 && dlat != 0.0,
+			"invalid grid spacing {dlon}/{dlat}"
+		);
+		Ok(Grid {
+			width,
+			height,
+			lon0: if lon0 > 180.0 { lon0 - 360.0 } else { lon0 },
+			lat0,
+			dlon,
+			dlat,
+			values,
+		})
+	}
+
+	/// Whether the columns span the whole globe, so that longitudes wrap around.
+	fn is_global(&self) -> bool {
+		self.width as f64 * self.dlon.abs() >= 359.999
+	}
+
+	/// Smallest and largest value, ignoring missing data. `None` if all values are missing.
+	pub fn value_range(&self) -> Option<(f64, f64)> {
+		self.values.iter().filter(|v| !v.is_nan()).fold(None, |range, v| {
+			let v = f64::from(*v);
+			Some(match range {
+				None => (v, v),
+				Some((min, max)) => (v.min(min), v.max(max)),
+			})
+		})
+	}
+
+	/// Bounding box of all cells.
+	pub fn geo_bbox(&self) -> GeoBBox {
+		let lat_a = self.lat0 - self.dlat / 2.0;
+		let lat_b = lat_a + self.height as f64 * self.dlat;
+		let (lon_west, lon_east) = if self.is_global() {
+			(-180.0, 180.0)
+		} else {
+			let lon_a = self.lon0 - self.dlon / 2.0;
+			let lon_b = lon_a + self.width as f64 * self.dlon;
+			let (west, east) = (lon_a.min(lon_b), lon_a.max(lon_b));
+			if east > 180.0 { (-180.0, 180.0) } else { (west, east) }
+		};
+		GeoBBox::new_normalized(lon_west, lat_a.min(lat_b), lon_east, lat_a.max(lat_b))
+	}
+
+	/// The lowest zoom level at which tiles of `tile_size` pixels have at least the resolution of the grid.
+	pub fn native_level(&self, tile_size: u32) -> u8 {
+		native_level(self.dlon.abs() / 360.0 * EARTH_CIRCUMFERENCE, tile_size)
+	}
+
+	/// Index of the column containing `lon`, if any.
+	fn column(&self, lon: f64) -> Option<usize> {
+		let col = ((lon - self.lon0) / self.dlon).round();
+		if self.is_global() {
+			Some((col as i64).rem_euclid(self.width as i64) as usize)
+		} else {
+			(col >= 0.0 && col < self.width as f64).then_some(col as usize)
+		}
+	}
+
+	/// Index of the row containing `lat`, if any.
+	fn row(&self, lat: f64) -> Option<usize> {
+		let row = ((lat - self.lat0) / self.dlat).round();
+		(row >= 0.0 && row < self.height as f64).then_some(row as usize)
+	}
+
+	/// Renders the WebMercator tile `coord` as an RGBA image of `tile_size`×`tile_size` pixels.
+	///
+	/// Values are mapped linearly from `min..=max` to the color map, missing values are transparent.
+	/// Returns `None` if the tile doesn't contain any data.
+	pub fn render_tile(
+		&self,
+		coord: &TileCoord,
+		tile_size: u32,
+		colormap: &ColorMap,
+		(min, max): (f64, f64),
+	) -> Result<Option<DynamicImage>> {
+		let size = tile_size as usize;
+		let scale = f64::from(tile_size) * 2f64.powi(i32::from(coord.level));
+		let x0 = f64::from(coord.x) * f64::from(tile_size);
+		let y0 = f64::from(coord.y) * f64::from(tile_size);
+
+		let columns = (0..size)
+			.map(|px| self.column((x0 + px as f64 + 0.5) / scale * 360.0 - 180.0))
+			.collect::<Vec<_>>();
+		let rows = (0..size)
+			.map(|py| {
+				let lat = (PI * (1.0 - 2.0 * (y0 + py as f64 + 0.5) / scale)).sinh().atan();
+				self.row(lat.to_degrees())
+			})
+			.collect::<Vec<_>>();
+
+		let range = if max > min { max - min } else { 1.0 };
+		let mut pixels = vec![0u8; size * size * 4];
+		for (row, line) in rows.iter().zip(pixels.chunks_exact_mut(size * 4)) {
+			let Some(row) = row else { continue };
+			for (col, pixel) in columns.iter().zip(line.chunks_exact_mut(4)) {
+				let Some(col) = col else { continue };
+				let value = self.values[row * self.width + col];
+				if !value.is_nan() {
+					pixel.copy_from_slice(&colormap.get((f64::from(value) - min) / range));
+				}
+			}
+		}
+
+		Ok(DynamicImage::from_raw(size, size, pixels)?.into_optional())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_image::GenericImageView;
+
+	impl Grid {
+		/// Value of the cell nearest to `lon`/`lat`, `NaN` if outside of the grid or missing.
+		fn get(&self, lon: f64, lat: f64) -> f32 {
+			match (self.column(lon), self.row(lat)) {
+				(Some(col), Some(row)) => self.values[row * self.width + col],
+				_ => f32::NAN,
+			}
+		}
+	}
+
+	fn global_grid() -> Grid {
+		// 4x2 cells of 90°, west to east, north to south, starting at 0°E
+		let values = vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, f32::NAN, 7.0];
+		Grid::new(4, 2, 45.0, 45.0, 90.0, -90.0, values).unwrap()
+	}
+
+	#[test]
+	fn get_and_wrap() {
+		let grid = global_grid();
+		assert_eq!(grid.get(10.0, 10.0), 0.0);
+		assert_eq!(grid.get(100.0, 10.0), 1.0);
+		assert_eq!(grid.get(-10.0, 10.0), 3.0);
+		assert_eq!(grid.get(-10.0, -10.0), 7.0);
+		assert!(grid.get(-100.0, -10.0).is_nan());
+		assert_eq!(grid.value_range(), Some((0.0, 7.0)));
+		assert_eq!(grid.geo_bbox().as_string_list(), "-180,-90,180,90");
+	}
+
+	#[test]
+	fn regional_grid() -> Result<()> {
+		let grid = Grid::new(2, 3, 10.5, 50.5, 1.0, 1.0, vec![1.0; 6])?;
+		assert!(grid.get(9.0, 51.0).is_nan());
+		assert!(grid.get(11.0, 53.5).is_nan());
+		assert_eq!(grid.get(11.0, 52.0), 1.0);
+		assert_eq!(grid.geo_bbox().as_string_list(), "10,50,12,53");
+		assert_eq!(grid.native_level(256), 1);
+		assert!(Grid::new(2, 3, 0.0, 0.0, 1.0, 1.0, vec![1.0; 5]).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn render() -> Result<()> {
+		let grid = global_grid();
+		let colormap = ColorMap::from_name("grayscale")?;
+		let image = grid
+			.render_tile(&TileCoord::new(1, 1, 0)?, 4, &colormap, (0.0, 7.0))?
+			.unwrap();
+		assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 255]);
+		assert_eq!(image.get_pixel(3, 3).0, [36, 36, 36, 255]);
+
+		let image = grid
+			.render_tile(&TileCoord::new(1, 0, 1)?, 4, &colormap, (0.0, 7.0))?
+			.unwrap();
+		assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 0]);
+		assert_eq!(image.get_pixel(3, 0).0, [255, 255, 255, 255]);
+		Ok(())
+	}
+}
@@ -0,0 +1,266 @@
+//! # From‑grid read operation
+//!
+//! This module defines an [`Operation`] that renders raster tiles from gridded
+//! data, like weather forecasts, stored as **NetCDF** (classic format) or
+//! **GRIB2**. One time slice of one variable is read into memory and every
+//! value is mapped to a color with a [`ColorMap`].
+//!
+//! The grid must be a regular longitude/latitude grid. Every output tile is
+//! reprojected to WebMercator with nearest neighbour resampling; global grids
+//! wrap around the antimeridian.
+
+mod grib;
+mod grid;
+mod netcdf;
+
+use crate::{PipelineFactory, operations::read::traits::ReadOperationTrait, traits::*, vpl::VPLNode};
+use anyhow::{Result, anyhow, bail, ensure};
+use async_trait::async_trait;
+use grid::Grid;
+use std::{fmt::Debug, sync::Arc};
+use versatiles_container::Tile;
+use versatiles_core::*;
+use versatiles_derive::context;
+use versatiles_image::ColorMap;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Reads gridded data from a NetCDF (classic format) or GRIB2 file and renders it as colored raster tiles.
+/// The data must be a regular longitude/latitude grid. GRIB2 files must use simple packing.
+struct Args {
+	/// The filename of the NetCDF or GRIB2 file, relative to the path of the VPL file.
+	/// The format is detected from the file content. For example: `filename="forecast.nc"`.
+	filename: String,
+	/// NetCDF only: name of the variable to render. (default: the first variable with at least two dimensions)
+	variable: Option<String>,
+	/// Index of the time slice, starting at 0. For NetCDF this is the index along the first dimension of the variable,
+	/// for GRIB2 the index of the field in the file. (default: 0)
+	time: Option<u32>,
+	/// Name of a built-in color map ("grayscale", "temperature", "turbo", "viridis" or "wind")
+	/// or a JSON array of `[position, color]` stops, e.g. `colormap='[[0,"#0000ff"],[1,"#ff0000"]]'`. (default: "grayscale")
+	colormap: Option<String>,
+	/// The value that is mapped to the start of the color map. (default: the smallest value)
+	min: Option<f32>,
+	/// The value that is mapped to the end of the color map. (default: the largest value)
+	max: Option<f32>,
+	/// The size of the generated tiles in pixels. (default: 512)
+	tile_size: Option<u32>,
+	/// The tile format to use for the output tiles. (default: `PNG`)
+	tile_format: Option<TileFormat>,
+	/// The maximum zoom level to generate tiles for. (default: the zoom level matching the grid resolution)
+	level_max: Option<u8>,
+	/// The minimum zoom level to generate tiles for. (default: 0)
+	level_min: Option<u8>,
+}
+
+/// Parses NetCDF or GRIB2 data, depending on the magic bytes.
+fn read_grid(data: &[u8], variable: Option<&str>, time: usize) -> Result<Grid> {
+	if data.starts_with(b"CDF") {
+		netcdf::read_netcdf(data, variable, time)
+	} else if data.starts_with(b"\x89HDF") {
+		bail!(
+			"NetCDF-4 (HDF5) files are not supported, convert them to the classic format, e.g. with `nccopy -k classic`"
+		)
+	} else if data.windows(4).take(1024).any(|w| w == b"GRIB") {
+		ensure!(variable.is_none(), "variable is only supported for NetCDF files");
+		grib::read_grib(data, time)
+	} else {
+		bail!("unknown file format, expected NetCDF or GRIB2")
+	}
+}
+
+#[derive(Debug)]
+struct Operation {
+	grid: Arc<Grid>,
+	colormap: Arc<ColorMap>,
+	range: (f64, f64),
+	parameters: TilesReaderParameters,
+	tile_size: u32,
+	tilejson: TileJSON,
+}
+
+impl ReadOperationTrait for Operation {
+	#[context("Failed to build from_grid operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, factory: &PipelineFactory) -> Result<Box<dyn OperationTrait>>
+	where
+		Self: Sized + OperationTrait,
+	{
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		let path = factory.resolve_path(&args.filename);
+		let data = std::fs::read(&path).map_err(|err| anyhow!("failed to read {path:?}: {err}"))?;
+		let grid = read_grid(&data, args.variable.as_deref(), args.time.unwrap_or(0) as usize)?;
+
+		let colormap = ColorMap::parse(args.colormap.as_deref().unwrap_or("grayscale"))?;
+		let (data_min, data_max) = grid
+			.value_range()
+			.ok_or_else(|| anyhow!("the grid does not contain any values"))?;
+		let min = args.min.map_or(data_min, f64::from);
+		let max = args.max.map_or(data_max, f64::from);
+		ensure!(min <= max, "min ({min}) must be ≤ max ({max})");
+
+		let tile_size = args.tile_size.unwrap_or(512);
+		ensure!(tile_size > 0, "tile_size must be greater than zero");
+		let tile_format = args.tile_format.unwrap_or(TileFormat::PNG);
+		ensure!(
+			tile_format.to_type() == TileType::Raster,
+			"tile_format must be a raster format"
+		);
+
+		let level_max = args.level_max.unwrap_or_else(|| grid.native_level(tile_size));
+		let level_min = args.level_min.unwrap_or(0);
+		ensure!(
+			level_min <= level_max,
+			"level_min ({level_min}) must be ≤ level_max ({level_max})"
+		);
+
+		let bbox_pyramid = TileBBoxPyramid::from_geo_bbox(level_min, level_max, &grid.geo_bbox());
+		let parameters = TilesReaderParameters::new(tile_format, TileCompression::Uncompressed, bbox_pyramid);
+		let mut tilejson = TileJSON::default();
+		tilejson.update_from_reader_parameters(&parameters);
+
+		Ok(Box::new(Self {
+			grid: Arc::new(grid),
+			colormap: Arc::new(colormap),
+			range: (min, max),
+			parameters,
+			tile_size,
+			tilejson,
+		}) as Box<dyn OperationTrait>)
+	}
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		&self.parameters
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		&self.tilejson
+	}
+
+	#[context("Failed to get tile stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, mut bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+		bbox.intersect_with_pyramid(&self.parameters.bbox_pyramid);
+
+		let grid = Arc::clone(&self.grid);
+		let colormap = Arc::clone(&self.colormap);
+		let (range, tile_size) = (self.range, self.tile_size);
+		let format = self.parameters.tile_format;
+
+		Ok(TileStream::from_iter_coord_parallel(
+			bbox.into_iter_coords(),
+			move |coord| {
+				let image = match grid.render_tile(&coord, tile_size, &colormap, range) {
+					Ok(image) => image?,
+					Err(err) => {
+						log::warn!("failed to render tile {coord:?}: {err:?}");
+						return None;
+					}
+				};
+				match Tile::from_image(image, format) {
+					Ok(tile) => Some(tile),
+					Err(err) => {
+						log::warn!("failed to encode tile {coord:?}: {err:?}");
+						None
+					}
+				}
+			},
+		))
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"from_grid"
+	}
+}
+
+#[async_trait]
+impl ReadOperationFactoryTrait for Factory {
+	async fn build<'a>(&self, vpl_node: VPLNode, factory: &'a PipelineFactory) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, factory).await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use assert_fs::TempDir;
+	use versatiles_image::traits::*;
+
+	/// Writes the test files and builds `vpl`, with `{dir}` replaced by their directory.
+	async fn build(dir: &TempDir, vpl: &str) -> Result<Box<dyn OperationTrait>> {
+		std::fs::write(dir.path().join("test.nc"), netcdf::tests::build_test_netcdf())?;
+		std::fs::write(dir.path().join("test.grib2"), grib::tests::build_test_grib(0))?;
+		let vpl = vpl.replace("{dir}", dir.path().to_str().unwrap());
+		PipelineFactory::new_dummy().operation_from_vpl(&vpl).await
+	}
+
+	#[tokio::test]
+	async fn test_netcdf() -> Result<()> {
+		let dir = TempDir::new()?;
+		let operation = build(
+			&dir,
+			r#"from_grid filename="{dir}/test.nc" time=1 colormap="temperature" min=0 max=40 tile_size=256"#,
+		)
+		.await?;
+
+		let parameters = operation.parameters();
+		assert_eq!(parameters.tile_format, TileFormat::PNG);
+		assert_eq!(parameters.bbox_pyramid.get_level_min(), Some(0));
+		assert_eq!(parameters.bbox_pyramid.get_level_max(), Some(1));
+
+		// the grid covers 0.5°W to 2.5°E, so it touches two tiles
+		let tiles = operation.get_stream(TileBBox::new_full(1)?).await?.to_vec().await;
+		assert_eq!(tiles.len(), 2);
+		for (_coord, tile) in tiles {
+			let image = tile.into_image()?;
+			assert_eq!(image.width(), 256);
+			assert!(!image.is_opaque());
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_grib() -> Result<()> {
+		let dir = TempDir::new()?;
+		let operation = build(&dir, r#"from_grid filename="{dir}/test.grib2" time=1 level_max=2"#).await?;
+		let mut tiles = operation.get_stream(TileBBox::new_full(2)?).await?.to_vec().await;
+		tiles.sort_by_key(|(coord, _)| coord.x);
+		let coords = tiles.iter().map(|(coord, _)| *coord).collect::<Vec<_>>();
+		assert_eq!(coords, [TileCoord::new(2, 1, 1)?, TileCoord::new(2, 2, 1)?]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_build_errors() -> Result<()> {
+		let dir = TempDir::new()?;
+		for vpl in [
+			r#"from_grid filename="{dir}/missing.nc""#,
+			r#"from_grid filename="{dir}/test.nc" time=2"#,
+			r#"from_grid filename="{dir}/test.nc" colormap="rainbow""#,
+			r#"from_grid filename="{dir}/test.nc" min=10 max=0"#,
+			r#"from_grid filename="{dir}/test.nc" tile_format="mvt""#,
+			r#"from_grid filename="{dir}/test.grib2" variable="temp""#,
+		] {
+			assert!(build(&dir, vpl).await.is_err(), "{vpl}");
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn detect_format() {
+		assert!(read_grid(b"\x89HDF\r\n\x1a\n", None, 0).is_err());
+		assert!(read_grid(b"PK\x03\x04", None, 0).is_err());
+	}
+}
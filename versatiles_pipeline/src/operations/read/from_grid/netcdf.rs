@@ -0,0 +1,517 @@
+//! Minimal reader for NetCDF classic files (CDF-1, CDF-2 and CDF-5).
+//!
+//! Only the header and a single two-dimensional slice of one variable are decoded. The variable
+//! must have latitude and longitude as its last two dimensions, both described by regularly spaced
+//! coordinate variables. Leading dimensions are treated as time; all but the first must have a
+//! length of one. NetCDF-4 files (which are HDF5 files) are not supported.
+
+use super::grid::Grid;
+use anyhow::{Result, anyhow, bail, ensure};
+
+const NC_DIMENSION: u32 = 0x0A;
+const NC_VARIABLE: u32 = 0x0B;
+const NC_ATTRIBUTE: u32 = 0x0C;
+
+/// Values with a larger magnitude are treated as the default fill value of floating point variables.
+const DEFAULT_FILL_THRESHOLD: f64 = 9.9e36;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum NcType {
+	Byte,
+	Char,
+	Short,
+	Int,
+	Float,
+	Double,
+	UByte,
+	UShort,
+	UInt,
+	Int64,
+	UInt64,
+}
+
+impl NcType {
+	fn from_code(code: u32) -> Result<NcType> {
+		Ok(match code {
+			1 => NcType::Byte,
+			2 => NcType::Char,
+			3 => NcType::Short,
+			4 => NcType::Int,
+			5 => NcType::Float,
+			6 => NcType::Double,
+			7 => NcType::UByte,
+			8 => NcType::UShort,
+			9 => NcType::UInt,
+			10 => NcType::Int64,
+			11 => NcType::UInt64,
+			_ => bail!("unknown NetCDF type {code}"),
+		})
+	}
+
+	fn size(self) -> usize {
+		match self {
+			NcType::Byte | NcType::Char | NcType::UByte => 1,
+			NcType::Short | NcType::UShort => 2,
+			NcType::Int | NcType::UInt | NcType::Float => 4,
+			NcType::Double | NcType::Int64 | NcType::UInt64 => 8,
+		}
+	}
+
+	/// Decodes big-endian values.
+	fn decode(self, data: &[u8]) -> Result<Vec<f64>> {
+		let chunks = data.chunks_exact(self.size());
+		Ok(match self {
+			NcType::Char => bail!("character data can not be used as grid values"),
+			NcType::Byte => chunks.map(|b| f64::from(b[0] as i8)).collect(),
+			NcType::UByte => chunks.map(|b| f64::from(b[0])).collect(),
+			NcType::Short => chunks.map(|b| f64::from(i16::from_be_bytes([b[0], b[1]]))).collect(),
+			NcType::UShort => chunks.map(|b| f64::from(u16::from_be_bytes([b[0], b[1]]))).collect(),
+			NcType::Int => chunks
+				.map(|b| f64::from(i32::from_be_bytes(b.try_into().unwrap())))
+				.collect(),
+			NcType::UInt => chunks
+				.map(|b| f64::from(u32::from_be_bytes(b.try_into().unwrap())))
+				.collect(),
+			NcType::Float => chunks
+				.map(|b| f64::from(f32::from_be_bytes(b.try_into().unwrap())))
+				.collect(),
+			NcType::Double => chunks.map(|b| f64::from_be_bytes(b.try_into().unwrap())).collect(),
+			NcType::Int64 => chunks
+				.map(|b| i64::from_be_bytes(b.try_into().unwrap()) as f64)
+				.collect(),
+			NcType::UInt64 => chunks
+				.map(|b| u64::from_be_bytes(b.try_into().unwrap()) as f64)
+				.collect(),
+		})
+	}
+}
+
+#[derive(Debug)]
+struct Dimension {
+	name: String,
+	/// `0` for the record (unlimited) dimension.
+	length: u64,
+}
+
+#[derive(Debug)]
+struct Variable {
+	name: String,
+	dim_ids: Vec<usize>,
+	/// Numeric attributes, text attributes are skipped.
+	attributes: Vec<(String, Vec<f64>)>,
+	nc_type: NcType,
+	vsize: u64,
+	begin: u64,
+}
+
+impl Variable {
+	/// First number of the attribute `name`, if present.
+	fn number(&self, name: &str) -> Option<f64> {
+		self
+			.attributes
+			.iter()
+			.find(|(key, _)| key == name)
+			.and_then(|(_, values)| values.first().copied())
+	}
+}
+
+#[derive(Debug)]
+struct Header {
+	numrecs: u64,
+	dimensions: Vec<Dimension>,
+	variables: Vec<Variable>,
+}
+
+/// Big-endian cursor over the header bytes.
+struct Cursor<'a> {
+	data: &'a [u8],
+	pos: usize,
+	version: u8,
+}
+
+impl<'a> Cursor<'a> {
+	fn bytes(&mut self, length: usize) -> Result<&'a [u8]> {
+		let end = self.pos.checked_add(length).filter(|end| *end <= self.data.len());
+		let end = end.ok_or_else(|| anyhow!("unexpected end of NetCDF header at byte {}", self.pos))?;
+		let bytes = &self.data[self.pos..end];
+		self.pos = end;
+		Ok(bytes)
+	}
+
+	/// Reads `length` bytes and skips the padding to the next multiple of 4.
+	fn padded(&mut self, length: usize) -> Result<&'a [u8]> {
+		let bytes = self.bytes(length)?;
+		self.bytes((4 - length % 4) % 4)?;
+		Ok(bytes)
+	}
+
+	fn u32(&mut self) -> Result<u32> {
+		Ok(u32::from_be_bytes(self.bytes(4)?.try_into()?))
+	}
+
+	fn u64(&mut self) -> Result<u64> {
+		Ok(u64::from_be_bytes(self.bytes(8)?.try_into()?))
+	}
+
+	/// Counts are 64 bit in CDF-5, 32 bit otherwise.
+	fn non_neg(&mut self) -> Result<u64> {
+		if self.version == 5 {
+			self.u64()
+		} else {
+			Ok(u64::from(self.u32()?))
+		}
+	}
+
+	/// File offsets are 32 bit in CDF-1, 64 bit otherwise.
+	fn offset(&mut self) -> Result<u64> {
+		if self.version == 1 {
+			Ok(u64::from(self.u32()?))
+		} else {
+			self.u64()
+		}
+	}
+
+	fn name(&mut self) -> Result<String> {
+		let length = self.non_neg()? as usize;
+		Ok(String::from_utf8_lossy(self.padded(length)?).into_owned())
+	}
+
+	/// Reads the tag and length of a list. An absent list has the tag `0`.
+	fn list(&mut self, tag: u32) -> Result<usize> {
+		let found = self.u32()?;
+		let length = self.non_neg()? as usize;
+		if found == 0 {
+			ensure!(length == 0, "absent NetCDF list must be empty");
+		} else {
+			ensure!(found == tag, "expected NetCDF list tag {tag:#x}, found {found:#x}");
+		}
+		Ok(length)
+	}
+
+	fn attributes(&mut self) -> Result<Vec<(String, Vec<f64>)>> {
+		let count = self.list(NC_ATTRIBUTE)?;
+		let mut attributes = Vec::with_capacity(count.min(1024));
+		for _ in 0..count {
+			let name = self.name()?;
+			let nc_type = NcType::from_code(self.u32()?)?;
+			let length = self.non_neg()? as usize;
+			let size = length
+				.checked_mul(nc_type.size())
+				.ok_or_else(|| anyhow!("attribute '{name}' is too large"))?;
+			let data = self.padded(size)?;
+			if nc_type != NcType::Char {
+				attributes.push((name, nc_type.decode(data)?));
+			}
+		}
+		Ok(attributes)
+	}
+}
+
+impl Header {
+	fn parse(data: &[u8]) -> Result<Header> {
+		ensure!(data.len() >= 4 && &data[0..3] == b"CDF", "not a NetCDF classic file");
+		let version = data[3];
+		ensure!(matches!(version, 1 | 2 | 5), "unsupported NetCDF version {version}");
+		let mut cursor = Cursor { data, pos: 4, version };
+		let numrecs = cursor.non_neg()?;
+
+		let count = cursor.list(NC_DIMENSION)?;
+		let mut dimensions = Vec::with_capacity(count.min(1024));
+		for _ in 0..count {
+			let name = cursor.name()?;
+			let length = cursor.non_neg()?;
+			dimensions.push(Dimension { name, length });
+		}
+
+		// global attributes are not needed
+		cursor.attributes()?;
+
+		let count = cursor.list(NC_VARIABLE)?;
+		let mut variables = Vec::with_capacity(count.min(1024));
+		for _ in 0..count {
+			let name = cursor.name()?;
+			let rank = cursor.non_neg()? as usize;
+			let dim_ids = (0..rank)
+				.map(|_| {
+					let id = cursor.non_neg()? as usize;
+					ensure!(id < dimensions.len(), "variable '{name}' uses unknown dimension {id}");
+					Ok(id)
+				})
+				.collect::<Result<Vec<_>>>()?;
+			let attributes = cursor.attributes()?;
+			let nc_type = NcType::from_code(cursor.u32()?)?;
+			let vsize = cursor.non_neg()?;
+			let begin = cursor.offset()?;
+			variables.push(Variable {
+				name,
+				dim_ids,
+				attributes,
+				nc_type,
+				vsize,
+				begin,
+			});
+		}
+
+		Ok(Header {
+			numrecs,
+			dimensions,
+			variables,
+		})
+	}
+
+	fn is_record_variable(&self, variable: &Variable) -> bool {
+		variable
+			.dim_ids
+			.first()
+			.is_some_and(|id| self.dimensions[*id].length == 0)
+	}
+
+	fn dimension_length(&self, id: usize) -> u64 {
+		match self.dimensions[id].length {
+			0 => self.numrecs,
+			length => length,
+		}
+	}
+
+	/// Number of bytes between two records.
+	fn record_size(&self) -> u64 {
+		let record_variables = self
+			.variables
+			.iter()
+			.filter(|variable| self.is_record_variable(variable))
+			.collect::<Vec<_>>();
+		match record_variables.as_slice() {
+			// a single record variable is not padded
+			[variable] => {
+				let count: u64 = variable.dim_ids[1..]
+					.iter()
+					.map(|id| self.dimension_length(*id))
+					.product();
+				count * variable.nc_type.size() as u64
+			}
+			variables => variables.iter().map(|variable| variable.vsize).sum(),
+		}
+	}
+
+	/// Finds the variable that should be rendered: `name` if given, otherwise the first variable
+	/// with at least two dimensions that isn't a coordinate variable.
+	fn select_variable(&self, name: Option<&str>) -> Result<&Variable> {
+		match name {
+			Some(name) => self
+				.variables
+				.iter()
+				.find(|variable| variable.name == name)
+				.ok_or_else(|| {
+					let names = self.variables.iter().map(|v| v.name.as_str()).collect::<Vec<_>>();
+					anyhow!("variable '{name}' not found, available are {names:?}")
+				}),
+			None => self
+				.variables
+				.iter()
+				.find(|variable| variable.dim_ids.len() >= 2)
+				.ok_or_else(|| anyhow!("no variable with at least two dimensions found")),
+		}
+	}
+
+	/// Reads `count` values of `variable`, starting at the byte `offset`.
+	fn read_values(&self, data: &[u8], variable: &Variable, offset: u64, count: usize) -> Result<Vec<f64>> {
+		let length = count * variable.nc_type.size();
+		let start = usize::try_from(offset)?;
+		let bytes = start
+			.checked_add(length)
+			.and_then(|end| data.get(start..end))
+			.ok_or_else(|| anyhow!("data of variable '{}' is outside of the file", variable.name))?;
+		variable.nc_type.decode(bytes)
+	}
+
+	/// Reads the coordinate variable of dimension `id` and returns its first value and the spacing.
+	fn coordinate_axis(&self, data: &[u8], id: usize) -> Result<(f64, f64)> {
+		let name = &self.dimensions[id].name;
+		let variable = self
+			.variables
+			.iter()
+			.find(|variable| &variable.name == name && variable.dim_ids == [id])
+			.ok_or_else(|| anyhow!("coordinate variable for dimension '{name}' not found"))?;
+		let count = self.dimension_length(id) as usize;
+		let values = self.read_values(data, variable, variable.begin, count)?;
+		regular_spacing(&values).ok_or_else(|| anyhow!("coordinates of dimension '{name}' are not regularly spaced"))
+	}
+}
+
+/// Returns the first value and the spacing of regularly spaced `values`.
+fn regular_spacing(values: &[f64]) -> Option<(f64, f64)> {
+	let first = *values.first()?;
+	if values.len() == 1 {
+		return Some((first, 1.0));
+	}
+	let step = (values[values.len() - 1] - first) / (values.len() - 1) as f64;
+	let tolerance = step.abs() * 1e-3;
+	let regular = step != 0.0
+		&& values
+			.windows(2)
+			.all(|pair| (pair[1] - pair[0] - step).abs() <= tolerance);
+	regular.then_some((first, step))
+}
+
+/// Reads the slice `time` of a variable from a NetCDF classic file.
+///
+/// `variable` selects the variable by name; if `None`, the first variable with at least two
+/// dimensions is used. Packed values are unpacked with `scale_factor` and `add_offset`, and
+/// `_FillValue` or `missing_value` become `NaN`.
+pub fn read_netcdf(data: &[u8], variable: Option<&str>, time: usize) -> Result<Grid> {
+	let header = Header::parse(data)?;
+	let variable = header.select_variable(variable)?;
+	let rank = variable.dim_ids.len();
+	ensure!(
+		rank >= 2,
+		"variable '{}' must have latitude and longitude dimensions",
+		variable.name
+	);
+
+	let (lat_id, lon_id) = (variable.dim_ids[rank - 2], variable.dim_ids[rank - 1]);
+	let (lat0, dlat) = header.coordinate_axis(data, lat_id)?;
+	let (lon0, dlon) = header.coordinate_axis(data, lon_id)?;
+	let height = header.dimension_length(lat_id) as usize;
+	let width = header.dimension_length(lon_id) as usize;
+
+	let leading = &variable.dim_ids[..rank - 2];
+	for id in leading.iter().skip(1) {
+		ensure!(
+			header.dimension_length(*id) == 1,
+			"dimension '{}' of variable '{}' must have a length of 1",
+			header.dimensions[*id].name,
+			variable.name
+		);
+	}
+	let steps = leading.first().map_or(1, |id| header.dimension_length(*id)) as usize;
+	ensure!(
+		time < steps,
+		"time index {time} is out of range, variable '{}' has {steps} time steps",
+		variable.name
+	);
+
+	let count = width * height;
+	let offset = if header.is_record_variable(variable) {
+		variable.begin + time as u64 * header.record_size()
+	} else {
+		variable.begin + (time * count * variable.nc_type.size()) as u64
+	};
+	let raw = header.read_values(data, variable, offset, count)?;
+
+	let fill = variable
+		.number("_FillValue")
+		.or_else(|| variable.number("missing_value"));
+	let is_float = matches!(variable.nc_type, NcType::Float | NcType::Double);
+	let scale = variable.number("scale_factor").unwrap_or(1.0);
+	let add = variable.number("add_offset").unwrap_or(0.0);
+	let values = raw
+		.into_iter()
+		.map(|value| {
+			let missing = fill == Some(value) || value.is_nan() || (is_float && value.abs() >= DEFAULT_FILL_THRESHOLD);
+			if missing {
+				f32::NAN
+			} else {
+				(value * scale + add) as f32
+			}
+		})
+		.collect();
+
+	Grid::new(width, height, lon0, lat0, dlon, dlat, values)
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+
+	/// Big-endian writer for CDF-1 files.
+	#[derive(Default)]
+	struct Writer(Vec<u8>);
+
+	impl Writer {
+		fn u32(&mut self, value: u32) -> &mut Self {
+			self.0.extend_from_slice(&value.to_be_bytes());
+			self
+		}
+		fn padded(&mut self, bytes: &[u8]) -> &mut Self {
+			self.0.extend_from_slice(bytes);
+			self.0.resize(self.0.len().div_ceil(4) * 4, 0);
+			self
+		}
+		fn name(&mut self, name: &str) -> &mut Self {
+			self.u32(name.len() as u32).padded(name.as_bytes())
+		}
+	}
+
+	fn header(begin: u32) -> Vec<u8> {
+		let mut w = Writer::default();
+		w.padded(b"CDF\x01").u32(2);
+
+		w.u32(NC_DIMENSION).u32(3);
+		w.name("time").u32(0);
+		w.name("lat").u32(2);
+		w.name("lon").u32(3);
+
+		w.u32(0).u32(0);
+
+		w.u32(NC_VARIABLE).u32(3);
+		w.name("lat").u32(1).u32(1).u32(0).u32(0).u32(5).u32(8).u32(begin);
+		w.name("lon").u32(1).u32(2).u32(0).u32(0).u32(5).u32(12).u32(begin + 8);
+		w.name("temp").u32(3).u32(0).u32(1).u32(2);
+		w.u32(NC_ATTRIBUTE).u32(4);
+		w.name("scale_factor").u32(6).u32(1);
+		w.0.extend_from_slice(&0.5f64.to_be_bytes());
+		w.name("add_offset").u32(5).u32(1).padded(&10f32.to_be_bytes());
+		w.name("_FillValue").u32(3).u32(1).padded(&(-1i16).to_be_bytes());
+		w.name("units").u32(2).u32(4).padded(b"degC");
+		w.u32(3).u32(12).u32(begin + 20);
+		w.0
+	}
+
+	/// A 3×2 grid at 0–2°E, 40–41°N with two time steps of a packed `short` variable.
+	pub fn build_test_netcdf() -> Vec<u8> {
+		let mut data = header(header(0).len() as u32);
+		for lat in [40f32, 41.0] {
+			data.extend_from_slice(&lat.to_be_bytes());
+		}
+		for lon in [0f32, 1.0, 2.0] {
+			data.extend_from_slice(&lon.to_be_bytes());
+		}
+		for value in [0i16, 2, 4, 6, 8, -1, 20, 20, 20, 20, 20, 20] {
+			data.extend_from_slice(&value.to_be_bytes());
+		}
+		data
+	}
+
+	#[test]
+	fn read_slices() -> Result<()> {
+		let data = build_test_netcdf();
+
+		let grid = read_netcdf(&data, None, 0)?;
+		assert_eq!((grid.width, grid.height), (3, 2));
+		assert_eq!((grid.lon0, grid.dlon, grid.lat0, grid.dlat), (0.0, 1.0, 40.0, 1.0));
+		assert_eq!(&grid.values[..5], [10.0, 11.0, 12.0, 13.0, 14.0]);
+		assert!(grid.values[5].is_nan());
+
+		let grid = read_netcdf(&data, Some("temp"), 1)?;
+		assert_eq!(grid.values, vec![20.0; 6]);
+		Ok(())
+	}
+
+	#[test]
+	fn errors() {
+		let data = build_test_netcdf();
+		assert!(read_netcdf(&data, None, 2).is_err());
+		assert!(read_netcdf(&data, Some("rain"), 0).is_err());
+		assert!(read_netcdf(&data, Some("lat"), 0).is_err());
+		assert!(read_netcdf(&data[..100], None, 0).is_err());
+		assert!(read_netcdf(b"\x89HDF\r\n", None, 0).is_err());
+	}
+
+	#[test]
+	fn spacing() {
+		assert_eq!(regular_spacing(&[1.0, 1.5, 2.0]), Some((1.0, 0.5)));
+		assert_eq!(regular_spacing(&[90.0, 0.0, -90.0]), Some((90.0, -90.0)));
+		assert_eq!(regular_spacing(&[0.0, 1.0, 3.0]), None);
+		assert_eq!(regular_spacing(&[]), None);
+	}
+}
@@ -22,7 +22,7 @@ use crate::{
 };
 use anyhow::{Result, ensure};
 use async_trait::async_trait;
-use futures::{StreamExt, future::join_all, stream};
+use futures::{StreamExt, stream};
 use std::collections::HashMap;
 use versatiles_container::Tile;
 use versatiles_core::*;
@@ -75,10 +75,9 @@ impl ReadOperationTrait for Operation {
 		Self: Sized + OperationTrait,
 	{
 		let args = Args::from_vpl_node(&vpl_node)?;
-		let sources = join_all(args.sources.into_iter().map(|c| factory.build_pipeline(c)))
-			.await
-			.into_iter()
-			.collect::<Result<Vec<_>>>()?;
+		let named_sources = factory.build_sources(args.sources).await?;
+		let traversal = Traversal::intersect_all(named_sources.iter().map(|(name, source)| (name, source.traversal())))?;
+		let sources: Vec<_> = named_sources.into_iter().map(|(_, source)| source).collect();
 
 		ensure!(sources.len() > 1, "must have at least two sources");
 
@@ -87,13 +86,10 @@ impl ReadOperationTrait for Operation {
 		let tile_format = first_parameters.tile_format;
 		let tile_compression = first_parameters.tile_compression;
 		let mut pyramid = TileBBoxPyramid::new_empty();
-		let mut traversal = Traversal::new_any();
 
 		for source in sources.iter() {
 			tilejson.merge(source.tilejson())?;
 
-			traversal.intersect(source.traversal())?;
-
 			let parameters = source.parameters();
 			pyramid.include_bbox_pyramid(&parameters.bbox_pyramid);
 
@@ -180,6 +176,9 @@ impl OperationFactoryTrait for Factory {
 	fn get_docs(&self) -> String {
 		Args::get_docs()
 	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
 	fn get_tag_name(&self) -> &str {
 		"from_merged_vector"
 	}
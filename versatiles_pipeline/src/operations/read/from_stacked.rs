@@ -23,9 +23,9 @@ use crate::{
 };
 use anyhow::{Result, ensure};
 use async_trait::async_trait;
-use futures::{StreamExt, future::join_all, stream};
+use futures::{StreamExt, stream};
 use versatiles_container::Tile;
-use versatiles_core::*;
+use versatiles_core::{progress::ConversionMetrics, *};
 use versatiles_derive::context;
 
 #[derive(versatiles_derive::VPLDecode, Clone, Debug)]
@@ -47,6 +47,8 @@ struct Operation {
 	sources: Vec<Box<dyn OperationTrait>>,
 	tilejson: TileJSON,
 	traversal: Traversal,
+	/// Counts the format changes of tiles whose source has another format.
+	metrics: ConversionMetrics,
 }
 
 impl ReadOperationTrait for Operation {
@@ -56,19 +58,19 @@ impl ReadOperationTrait for Operation {
 		Self: Sized + OperationTrait,
 	{
 		let args = Args::from_vpl_node(&vpl_node)?;
-		let sources = join_all(args.sources.into_iter().map(|c| factory.build_pipeline(c)))
-			.await
-			.into_iter()
-			.collect::<Result<Vec<_>>>()?;
+		let sources = factory.build_sources(args.sources).await?;
 
-		Ok(Box::new(Operation::new(sources)?) as Box<dyn OperationTrait>)
+		Ok(Box::new(Operation::new(sources, factory.config().metrics.clone())?) as Box<dyn OperationTrait>)
 	}
 }
 
 impl Operation {
 	#[context("Failed to create from_stacked operation")]
-	fn new(sources: Vec<Box<dyn OperationTrait>>) -> Result<Operation> {
-		ensure!(sources.len() > 1, "must have at least two sources");
+	fn new(named_sources: Vec<(String, Box<dyn OperationTrait>)>, metrics: ConversionMetrics) -> Result<Operation> {
+		ensure!(named_sources.len() > 1, "must have at least two sources");
+
+		let traversal = Traversal::intersect_all(named_sources.iter().map(|(name, source)| (name, source.traversal())))?;
+		let sources: Vec<_> = named_sources.into_iter().map(|(_, source)| source).collect();
 
 		let mut tilejson = TileJSON::default();
 		let parameters = sources.first().unwrap().parameters();
@@ -76,13 +78,10 @@ impl Operation {
 		let tile_compression = parameters.tile_compression;
 
 		let mut pyramid = TileBBoxPyramid::new_empty();
-		let mut traversal = Traversal::default();
 
 		for source in sources.iter() {
 			tilejson.merge(source.tilejson())?;
 
-			traversal.intersect(source.traversal())?;
-
 			let parameters = source.parameters();
 			pyramid.include_bbox_pyramid(&parameters.bbox_pyramid);
 
@@ -100,6 +99,7 @@ impl Operation {
 			parameters,
 			sources,
 			traversal,
+			metrics,
 		})
 	}
 }
@@ -149,6 +149,7 @@ impl OperationTrait for Operation {
 						.for_each_sync(|(coord, mut tile)| {
 							let entry = tiles.get_mut(&coord).unwrap();
 							if entry.is_none() {
+								self.metrics.count_format(tile.has_format(format, None, None));
 								tile.change_format(format, None, None).unwrap();
 								*entry = Some(tile);
 							}
@@ -171,6 +172,9 @@ impl OperationFactoryTrait for Factory {
 	fn get_docs(&self) -> String {
 		Args::get_docs()
 	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
 	fn get_tag_name(&self) -> &str {
 		"from_stacked"
 	}
@@ -338,12 +342,55 @@ mod tests {
 		src1.set_traversal(Traversal::new_any_size(1, 16).unwrap());
 		src2.set_traversal(Traversal::new(TraversalOrder::PMTiles, 4, 256).unwrap());
 
-		let op = Operation::new(vec![
-			operation_from_reader(Box::new(src1)),
-			operation_from_reader(Box::new(src2)),
-		])
+		let op = Operation::new(
+			vec![
+				("source 1".to_string(), operation_from_reader(Box::new(src1))),
+				("source 2".to_string(), operation_from_reader(Box::new(src2))),
+			],
+			ConversionMetrics::new(),
+		)
 		.unwrap();
 
 		assert_eq!(op.traversal(), &Traversal::new(TraversalOrder::PMTiles, 4, 16).unwrap());
 	}
+
+	#[test]
+	fn test_traversal_conflicts() {
+		use crate::operations::read::from_container::operation_from_reader;
+
+		let source = |traversal: Traversal| {
+			let mut source = DummyVectorSource::new(&[], Some(TileBBoxPyramid::new_full(8)));
+			source.set_traversal(traversal);
+			operation_from_reader(Box::new(source))
+		};
+
+		let error = Operation::new(
+			vec![
+				(
+					"source 1".to_string(),
+					source(Traversal::new(TraversalOrder::DepthFirst, 1, 256).unwrap()),
+				),
+				(
+					"source 2".to_string(),
+					source(Traversal::new(TraversalOrder::PMTiles, 1, 64).unwrap()),
+				),
+				(
+					"source 3".to_string(),
+					source(Traversal::new_any_size(512, 512).unwrap()),
+				),
+			],
+			ConversionMetrics::new(),
+		)
+		.unwrap_err();
+
+		assert_eq!(
+			error.chain().map(ToString::to_string).collect::<Vec<_>>(),
+			[
+				"Failed to create from_stacked operation",
+				"incompatible traversals: \
+				source 2 requires Traversal(PMTiles, min-size: 1, max-size: 64), which conflicts with Traversal(DepthFirst, min-size: 1, max-size: 256) required by source 1; \
+				source 3 requires Traversal(AnyOrder, min-size: 512, max-size: 512), which conflicts with Traversal(DepthFirst, min-size: 1, max-size: 256) required by source 1"
+			]
+		);
+	}
 }
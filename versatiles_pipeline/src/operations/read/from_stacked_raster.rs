@@ -18,10 +18,10 @@ use crate::{
 };
 use anyhow::{Result, ensure};
 use async_trait::async_trait;
-use futures::{StreamExt, future::join_all, stream};
+use futures::{StreamExt, stream};
 use std::vec;
 use versatiles_container::Tile;
-use versatiles_core::*;
+use versatiles_core::{progress::ConversionMetrics, *};
 use versatiles_derive::context;
 use versatiles_image::traits::*;
 
@@ -47,6 +47,8 @@ struct Operation {
 	sources: Vec<Box<dyn OperationTrait>>,
 	tilejson: TileJSON,
 	traversal: Traversal,
+	/// Counts the format changes of the blended tiles.
+	metrics: ConversionMetrics,
 }
 
 /// Blend a list of equally‑sized tiles using *source‑over* compositing.
@@ -80,10 +82,9 @@ impl ReadOperationTrait for Operation {
 		Self: Sized + OperationTrait,
 	{
 		let args = Args::from_vpl_node(&vpl_node)?;
-		let sources = join_all(args.sources.into_iter().map(|c| factory.build_pipeline(c)))
-			.await
-			.into_iter()
-			.collect::<Result<Vec<_>>>()?;
+		let named_sources = factory.build_sources(args.sources).await?;
+		let traversal = Traversal::intersect_all(named_sources.iter().map(|(name, source)| (name, source.traversal())))?;
+		let sources: Vec<_> = named_sources.into_iter().map(|(_, source)| source).collect();
 
 		ensure!(!sources.is_empty(), "must have at least one source");
 
@@ -98,13 +99,10 @@ impl ReadOperationTrait for Operation {
 		let tile_compression = first_parameters.tile_compression;
 
 		let mut pyramid = TileBBoxPyramid::new_empty();
-		let mut traversal = Traversal::new_any();
 
 		for source in sources.iter() {
 			tilejson.merge(source.tilejson())?;
 
-			traversal.intersect(source.traversal())?;
-
 			let parameters = source.parameters();
 			pyramid.include_bbox_pyramid(&parameters.bbox_pyramid);
 
@@ -122,6 +120,7 @@ impl ReadOperationTrait for Operation {
 			parameters,
 			sources,
 			traversal,
+			metrics: factory.config().metrics.clone(),
 		}) as Box<dyn OperationTrait>)
 	}
 }
@@ -174,6 +173,7 @@ impl OperationTrait for Operation {
 					.into_iter()
 					.filter_map(|(c, v)| match stack_tiles(v) {
 						Ok(Some(mut tile)) => {
+							self.metrics.count_format(tile.has_format(tile_format, None, None));
 							tile.change_format(tile_format, None, None).unwrap();
 							Some(Ok((c, tile)))
 						}
@@ -195,6 +195,9 @@ impl OperationFactoryTrait for Factory {
 	fn get_docs(&self) -> String {
 		Args::get_docs()
 	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
 	fn get_tag_name(&self) -> &str {
 		"from_stacked_raster"
 	}
@@ -0,0 +1,324 @@
+//! # From‑tile‑URL read operation
+//!
+//! This module defines an [`Operation`] that fetches tiles from an **XYZ‑style
+//! tile server** via HTTP(S). The URL template may contain the placeholders
+//! `{z}`, `{x}`, `{y}`, `{-y}` (TMS row numbering) and `{quadkey}` (Bing Maps
+//! style quadkeys), so most public tile services can be used as a source.
+//!
+//! Tiles that the server answers with `404 Not Found` or `204 No Content` are
+//! treated as missing. Tiles are emitted as soon as they arrive. If a request
+//! for a single tile fails, the error is returned, so that a `retry` operation
+//! wrapping this source can repeat it, or the error policy of a conversion can
+//! skip or replace the tile. In larger blocks a failed tile is logged and left
+//! out, so that one transient error doesn't discard the other tiles.
+
+use crate::{PipelineFactory, operations::read::traits::ReadOperationTrait, traits::*, vpl::VPLNode};
+use anyhow::{Result, anyhow, ensure};
+use async_trait::async_trait;
+use futures::{StreamExt, stream};
+use reqwest::{Client, StatusCode};
+use std::{fmt::Debug, time::Duration};
+use versatiles_container::{Tile, uri_scheme};
+use versatiles_core::*;
+use versatiles_derive::context;
+
+/// Maximum width and height of the blocks that are requested at once.
+const MAX_BLOCK_SIZE: u32 = 16;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Reads tiles from a tile server using a URL template.
+/// Wrap it in `retry` to repeat failed requests, e.g. `from_tile_url url="…" | retry attempts=5`.
+struct Args {
+	/// URL template of the tiles. Supported placeholders are `{z}`, `{x}`, `{y}`, `{-y}` (TMS) and `{quadkey}`,
+	/// e.g. `url="https://tiles.example.org/{z}/{x}/{y}.png"`.
+	url: String,
+	/// Tile format, e.g. "png" or "mvt". Defaults to the file extension in the URL.
+	format: Option<TileFormat>,
+	/// Compression of the tiles, as delivered by the server. Defaults to the file extension in the URL.
+	compression: Option<TileCompression>,
+	/// Bounding box in WGS84: [min lng, min lat, max lng, max lat].
+	bbox: Option<[f64; 4]>,
+	/// minimal zoom level. Defaults to 0.
+	level_min: Option<u8>,
+	/// maximal zoom level. Defaults to 18.
+	level_max: Option<u8>,
+	/// Maximum number of concurrent requests. Defaults to 8.
+	concurrency: Option<u8>,
+}
+
+/// A URL template with `{z}`, `{x}`, `{y}`, `{-y}` and `{quadkey}` placeholders.
+#[derive(Clone, Debug, PartialEq)]
+struct UrlTemplate(String);
+
+impl UrlTemplate {
+	#[context("Failed to parse URL template '{url}'")]
+	fn new(url: &str) -> Result<Self> {
+		let has_xyz = url.contains("{z}") && url.contains("{x}") && (url.contains("{y}") || url.contains("{-y}"));
+		ensure!(
+			has_xyz || url.contains("{quadkey}"),
+			"URL template must contain either {{z}}, {{x}} and {{y}} (or {{-y}}), or {{quadkey}}"
+		);
+		ensure!(
+			matches!(uri_scheme(url).as_deref(), Some("http" | "https")),
+			"URL template must start with http:// or https://"
+		);
+		Ok(Self(url.to_string()))
+	}
+
+	fn fill(&self, coord: &TileCoord) -> String {
+		let mut url = self.0.clone();
+		if url.contains("{quadkey}") {
+			url = url.replace("{quadkey}", &coord.as_quadkey());
+		}
+		url.replace("{z}", &coord.level.to_string())
+			.replace("{x}", &coord.x.to_string())
+			.replace("{-y}", &(coord.max_value() - coord.y).to_string())
+			.replace("{y}", &coord.y.to_string())
+	}
+
+	/// Guesses format and compression from the file extension of the URL path.
+	fn guess_format(&self) -> (Option<TileFormat>, TileCompression) {
+		let mut path = self.0.split(['?', '#']).next().unwrap_or_default().to_string();
+		let compression = TileCompression::from_filename(&mut path);
+		let format = TileFormat::from_filename(&mut path);
+		(format, compression)
+	}
+}
+
+#[derive(Debug)]
+struct Operation {
+	client: Client,
+	concurrency: usize,
+	parameters: TilesReaderParameters,
+	template: UrlTemplate,
+	tilejson: TileJSON,
+	traversal: Traversal,
+}
+
+impl ReadOperationTrait for Operation {
+	#[context("Failed to build from_tile_url operation in VPL node {:?}", vpl_node.name)]
+	async fn build(vpl_node: VPLNode, _factory: &PipelineFactory) -> Result<Box<dyn OperationTrait>>
+	where
+		Self: Sized + OperationTrait,
+	{
+		let args = Args::from_vpl_node(&vpl_node)?;
+		let template = UrlTemplate::new(&args.url)?;
+
+		let (guessed_format, guessed_compression) = template.guess_format();
+		let tile_format = args
+			.format
+			.or(guessed_format)
+			.ok_or_else(|| anyhow!("can not guess the tile format from the URL, please set 'format'"))?;
+		let tile_compression = args.compression.unwrap_or(guessed_compression);
+
+		let level_min = args.level_min.unwrap_or(0);
+		let level_max = args.level_max.unwrap_or(18);
+		ensure!(
+			level_min <= level_max,
+			"level_min ({level_min}) must be ≤ level_max ({level_max})"
+		);
+		let mut bbox_pyramid = TileBBoxPyramid::new_full(level_max);
+		bbox_pyramid.set_level_min(level_min);
+		if let Some(bbox) = args.bbox {
+			bbox_pyramid.intersect_geo_bbox(&GeoBBox::try_from(&bbox)?)?;
+		}
+
+		let parameters = TilesReaderParameters::new(tile_format, tile_compression, bbox_pyramid);
+		let mut tilejson = TileJSON::default();
+		tilejson.update_from_reader_parameters(&parameters);
+
+		let client = Client::builder()
+			.tcp_keepalive(Duration::from_secs(600))
+			.use_rustls_tls()
+			.build()?;
+
+		Ok(Box::new(Self {
+			client,
+			concurrency: usize::from(args.concurrency.unwrap_or(8).max(1)),
+			parameters,
+			template,
+			tilejson,
+			traversal: Traversal::new_any_size(1, MAX_BLOCK_SIZE)?,
+		}) as Box<dyn OperationTrait>)
+	}
+}
+
+/// Fetches a single tile. Returns `None` if the server has no tile at this coordinate.
+async fn fetch_tile(client: &Client, url: &str) -> Result<Option<Blob>> {
+	let response = client.get(url).send().await?;
+	match response.status() {
+		StatusCode::NOT_FOUND | StatusCode::NO_CONTENT => Ok(None),
+		status if status.is_success() => Ok(Some(Blob::from(response.bytes().await?))),
+		status => Err(anyhow!("server responded with {status}")),
+	}
+}
+
+#[async_trait]
+impl OperationTrait for Operation {
+	fn parameters(&self) -> &TilesReaderParameters {
+		&self.parameters
+	}
+
+	fn tilejson(&self) -> &TileJSON {
+		&self.tilejson
+	}
+
+	fn traversal(&self) -> &Traversal {
+		&self.traversal
+	}
+
+	#[context("Failed to get tile stream for bbox: {:?}", bbox)]
+	async fn get_stream(&self, mut bbox: TileBBox) -> Result<TileStream<Tile>> {
+		log::debug!("get_stream {:?}", bbox);
+		bbox.intersect_with_pyramid(&self.parameters.bbox_pyramid);
+
+		let client = self.client.clone();
+		let template = self.template.clone();
+		let format = self.parameters.tile_format;
+		let compression = self.parameters.tile_compression;
+		let fetch = move |coord: TileCoord| {
+			let client = client.clone();
+			let url = template.fill(&coord);
+			async move {
+				let blob = fetch_tile(&client, &url)
+					.await
+					.with_context(|| format!("Failed to fetch tile {coord:?} from '{url}'"))?;
+				Ok::<_, anyhow::Error>(blob.map(|blob| Tile::from_blob(blob, compression, format)))
+			}
+		};
+
+		let coords: Vec<TileCoord> = bbox.into_iter_coords().collect();
+		if let [coord] = coords[..] {
+			let tile = fetch(coord).await?;
+			return Ok(TileStream::from_vec(
+				tile.map(|tile| (coord, tile)).into_iter().collect(),
+			));
+		}
+
+		let stream = stream::iter(coords)
+			.map(move |coord| {
+				let tile = fetch(coord);
+				async move {
+					match tile.await {
+						Ok(tile) => tile.map(|tile| (coord, tile)),
+						Err(err) => {
+							log::warn!("skipping tile {coord:?}: {err:#}");
+							None
+						}
+					}
+				}
+			})
+			.buffer_unordered(self.concurrency)
+			.filter_map(futures::future::ready);
+
+		Ok(TileStream::from_stream(stream.boxed()))
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"from_tile_url"
+	}
+}
+
+#[async_trait]
+impl ReadOperationFactoryTrait for Factory {
+	async fn build<'a>(&self, vpl_node: VPLNode, factory: &'a PipelineFactory) -> Result<Box<dyn OperationTrait>> {
+		Operation::build(vpl_node, factory).await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+
+	#[test]
+	fn test_template_fill() -> Result<()> {
+		let coord = TileCoord::new(3, 1, 2)?;
+		let fill = |url: &str| UrlTemplate::new(url).unwrap().fill(&coord);
+
+		assert_eq!(fill("https://a.org/{z}/{x}/{y}.png"), "https://a.org/3/1/2.png");
+		assert_eq!(fill("https://a.org/{z}/{x}/{-y}.png"), "https://a.org/3/1/5.png");
+		assert_eq!(
+			fill("http://a.org/tiles/{quadkey}.jpeg?g=1"),
+			"http://a.org/tiles/021.jpeg?g=1"
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_template_errors() {
+		assert!(UrlTemplate::new("https://a.org/{z}/{x}.png").is_err());
+		assert!(UrlTemplate::new("file:///tiles/{z}/{x}/{y}.png").is_err());
+		assert!(UrlTemplate::new("https://a.org/{quadkey}").is_ok());
+	}
+
+	#[test]
+	fn test_guess_format() -> Result<()> {
+		let guess = |url: &str| UrlTemplate::new(url).unwrap().guess_format();
+
+		assert_eq!(
+			guess("https://a.org/{z}/{x}/{y}.png?key=abc"),
+			(Some(TileFormat::PNG), TileCompression::Uncompressed)
+		);
+		assert_eq!(
+			guess("https://a.org/{z}/{x}/{y}.pbf.gz"),
+			(Some(TileFormat::MVT), TileCompression::Gzip)
+		);
+		assert_eq!(guess("https://a.org/{quadkey}"), (None, TileCompression::Uncompressed));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_build() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let operation = factory
+			.operation_from_vpl(r#"from_tile_url url="https://a.org/{quadkey}.jpeg" level_min=2 level_max=5"#)
+			.await?;
+
+		let parameters = operation.parameters();
+		assert_eq!(parameters.tile_format, TileFormat::JPG);
+		assert_eq!(parameters.bbox_pyramid.get_level_min(), Some(2));
+		assert_eq!(parameters.bbox_pyramid.get_level_max(), Some(5));
+
+		assert!(
+			factory
+				.operation_from_vpl(r#"from_tile_url url="https://a.org/{quadkey}""#)
+				.await
+				.is_err()
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_fetch_errors() -> Result<()> {
+		// nothing listens on port 9 (discard), so every request fails
+		let factory = PipelineFactory::new_dummy();
+		let operation = factory
+			.operation_from_vpl(r#"from_tile_url url="http://127.0.0.1:9/{z}/{x}/{y}.png" level_max=2"#)
+			.await?;
+		assert_eq!(operation.traversal(), &Traversal::new_any_size(1, MAX_BLOCK_SIZE)?);
+
+		// a single tile fails, so that `retry` can repeat it
+		let error = operation
+			.get_stream(TileBBox::from_min_and_max(1, 0, 0, 0, 0)?)
+			.await
+			.err()
+			.unwrap();
+		assert!(format!("{error:#}").contains("Failed to fetch tile"), "{error:#}");
+
+		// failed tiles of a block are left out
+		let stream = operation.get_stream(TileBBox::from_min_and_max(1, 0, 0, 1, 1)?).await?;
+		assert_eq!(stream.drain_and_count().await, 0);
+		Ok(())
+	}
+}
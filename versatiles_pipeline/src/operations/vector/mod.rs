@@ -1,4 +1,15 @@
 mod traits;
+pub mod vector_add_tile_coords;
+pub mod vector_declutter;
+pub mod vector_dissolve;
+pub mod vector_feature_ids;
 pub mod vector_filter_layers;
 pub mod vector_filter_properties;
+pub mod vector_fix_geometries;
+pub mod vector_merge_lines;
+pub mod vector_migrate_schema;
+pub mod vector_reduce_precision;
+pub mod vector_reencode_properties;
+pub mod vector_rename_layers;
+pub mod vector_translate_properties;
 pub mod vector_update_properties;
@@ -1,15 +1,23 @@
 use crate::traits::OperationTrait;
 use anyhow::{Result, ensure};
 use async_trait::async_trait;
-use std::sync::Arc;
+use std::sync::{
+	Arc,
+	atomic::{AtomicU64, Ordering},
+};
 use versatiles_container::Tile;
-use versatiles_core::{TileBBox, TileJSON, TileStream, TileType, TilesReaderParameters, Traversal};
+use versatiles_core::{TileBBox, TileCoord, TileJSON, TileStream, TileType, TilesReaderParameters, Traversal};
 use versatiles_derive::context;
 use versatiles_geometry::vector_tile::VectorTile;
 
 pub trait RunnerTrait: std::fmt::Debug + Send + Sync + 'static {
 	fn update_tilejson(&self, tilejson: &mut TileJSON);
 	fn run(&self, tile: VectorTile) -> Result<Option<VectorTile>>;
+
+	/// Like [`RunnerTrait::run`], for runners that depend on the coordinate of the tile.
+	fn run_at(&self, _coord: TileCoord, tile: VectorTile) -> Result<Option<VectorTile>> {
+		self.run(tile)
+	}
 }
 
 /// Generic “transform” operation that delegates all real work to a `Runner`.
@@ -43,9 +51,9 @@ impl<R: RunnerTrait> OperationTrait for TransformOp<R> {
 			.source
 			.get_stream(bbox)
 			.await?
-			.filter_map_item_parallel(move |tile| {
+			.filter_map_parallel(move |coord, tile| {
 				let vector = tile.into_vector()?;
-				if let Some(transformed_vector) = runner.run(vector)? {
+				if let Some(transformed_vector) = runner.run_at(coord, vector)? {
 					Ok(Some(Tile::from_vector(transformed_vector, tile_format)?))
 				} else {
 					Ok(None)
@@ -54,6 +62,45 @@ impl<R: RunnerTrait> OperationTrait for TransformOp<R> {
 	}
 }
 
+/// Counts the features a runner changed and logs the total when the runner is dropped,
+/// i.e. once the pipeline is finished.
+#[derive(Debug)]
+pub struct FeatureCounter {
+	operation: &'static str,
+	action: &'static str,
+	noun: &'static str,
+	count: AtomicU64,
+}
+
+impl FeatureCounter {
+	/// Creates a counter that logs e.g. "vector_dissolve: removed 12 features".
+	pub fn new(operation: &'static str, action: &'static str, noun: &'static str) -> Self {
+		Self {
+			operation,
+			action,
+			noun,
+			count: AtomicU64::new(0),
+		}
+	}
+
+	pub fn add(&self, count: usize) {
+		self.count.fetch_add(count as u64, Ordering::Relaxed);
+	}
+
+	pub fn get(&self) -> u64 {
+		self.count.load(Ordering::Relaxed)
+	}
+}
+
+impl Drop for FeatureCounter {
+	fn drop(&mut self) {
+		let count = self.get();
+		if count > 0 {
+			log::info!("{}: {} {count} {}", self.operation, self.action, self.noun);
+		}
+	}
+}
+
 // transform_factory.rs
 #[context("Failed to build transform operation")]
 pub async fn build_transform<R>(source: Box<dyn OperationTrait>, runner: R) -> Result<Box<dyn OperationTrait>>
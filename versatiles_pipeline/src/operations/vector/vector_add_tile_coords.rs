@@ -0,0 +1,201 @@
+use crate::{
+	PipelineFactory,
+	operations::vector::traits::{RunnerTrait, build_transform},
+	traits::{OperationFactoryTrait, OperationTrait, TransformOperationFactoryTrait},
+	vpl::VPLNode,
+};
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use versatiles_core::{TileCoord, TileJSON};
+use versatiles_derive::context;
+use versatiles_geometry::{geo::GeoValue, vector_tile::VectorTile};
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Adds the coordinate of the tile to the properties of every feature as `_tile_z`, `_tile_x` and `_tile_y`,
+/// e.g. to debug styles in the browser or to join features with tile statistics.
+/// Existing properties with these names are overwritten.
+struct Args {
+	/// Only update features in this layer. Defaults to all layers.
+	layer: Option<String>,
+
+	/// If set, also adds the geographic bounding box of the tile as `_tile_west`, `_tile_south`, `_tile_east` and `_tile_north`. Defaults to false.
+	bbox: Option<bool>,
+}
+
+const COORD_KEYS: [&str; 3] = ["_tile_z", "_tile_x", "_tile_y"];
+const BBOX_KEYS: [&str; 4] = ["_tile_west", "_tile_south", "_tile_east", "_tile_north"];
+
+#[derive(Debug)]
+struct Runner {
+	layer: Option<String>,
+	bbox: bool,
+}
+
+impl Runner {
+	fn from_args(args: Args) -> Self {
+		Self {
+			layer: args.layer,
+			bbox: args.bbox.unwrap_or(false),
+		}
+	}
+
+	/// The properties added to every feature of the tile at `coord`.
+	fn properties(&self, coord: TileCoord) -> Vec<(&'static str, GeoValue)> {
+		let mut properties = vec![
+			(COORD_KEYS[0], GeoValue::from(coord.level)),
+			(COORD_KEYS[1], GeoValue::from(coord.x)),
+			(COORD_KEYS[2], GeoValue::from(coord.y)),
+		];
+		if self.bbox {
+			let bbox = coord.to_geo_bbox();
+			let values = [bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max];
+			properties.extend(BBOX_KEYS.into_iter().zip(values.map(GeoValue::from)));
+		}
+		properties
+	}
+
+	fn keys(&self) -> impl Iterator<Item = &'static str> {
+		let bbox_keys: &[&str] = if self.bbox { &BBOX_KEYS } else { &[] };
+		COORD_KEYS.into_iter().chain(bbox_keys.iter().copied())
+	}
+}
+
+impl RunnerTrait for Runner {
+	fn run(&self, _tile: VectorTile) -> Result<Option<VectorTile>> {
+		bail!("vector_add_tile_coords needs the tile coordinate")
+	}
+
+	#[context("Failed to add tile coordinates to tile {coord:?}")]
+	fn run_at(&self, coord: TileCoord, mut tile: VectorTile) -> Result<Option<VectorTile>> {
+		let added = self.properties(coord);
+		for layer in &mut tile.layers {
+			if self.layer.as_ref().is_none_or(|name| name == &layer.name) {
+				layer.map_properties(|mut properties| {
+					for (key, value) in &added {
+						properties.insert(key.to_string(), value.clone());
+					}
+					properties
+				})?;
+			}
+		}
+		Ok(Some(tile))
+	}
+
+	fn update_tilejson(&self, tilejson: &mut TileJSON) {
+		for (name, layer) in tilejson.vector_layers.iter_mut() {
+			if self.layer.as_ref().is_none_or(|l| l == name) {
+				for key in self.keys() {
+					layer.fields.insert(key.to_string(), "Number".to_string());
+				}
+			}
+		}
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"vector_add_tile_coords"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		_factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		let args = Args::from_vpl_node(&vpl_node)?;
+		build_transform::<Runner>(source, Runner::from_args(args)).await
+	}
+}
+
+// ───────────────────────── TESTS ─────────────────────────
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	use versatiles_core::TileBBox;
+	use versatiles_geometry::{geo::*, vector_tile::VectorTileLayer};
+
+	fn create_tile() -> VectorTile {
+		let mut feature = GeoFeature::new(Geometry::new_example());
+		feature.properties = GeoProperties::from(vec![("name", GeoValue::from("x")), ("_tile_z", GeoValue::from(99))]);
+		VectorTile::new(vec![
+			VectorTileLayer::from_features("a".to_string(), vec![feature.clone()], 4096, 1).unwrap(),
+			VectorTileLayer::from_features("b".to_string(), vec![feature], 4096, 1).unwrap(),
+		])
+	}
+
+	fn properties(tile: &VectorTile, layer: usize) -> GeoProperties {
+		let layer = &tile.layers[layer];
+		layer.decode_tag_ids(&layer.features[0].tag_ids).unwrap()
+	}
+
+	#[test]
+	fn adds_coordinates() -> Result<()> {
+		let runner = Runner::from_args(Args {
+			layer: Some("a".to_string()),
+			bbox: None,
+		});
+		let tile = runner.run_at(TileCoord::new(3, 4, 5)?, create_tile())?.unwrap();
+
+		let a = properties(&tile, 0);
+		assert_eq!(a.get("_tile_z"), Some(&GeoValue::from(3u8)));
+		assert_eq!(a.get("_tile_x"), Some(&GeoValue::from(4u32)));
+		assert_eq!(a.get("_tile_y"), Some(&GeoValue::from(5u32)));
+		assert_eq!(a.get("name"), Some(&GeoValue::from("x")));
+		assert_eq!(a.get("_tile_west"), None);
+
+		let b = properties(&tile, 1);
+		assert_eq!(b.get("_tile_z"), Some(&GeoValue::from(99)));
+		assert_eq!(b.get("_tile_x"), None);
+		Ok(())
+	}
+
+	#[test]
+	fn adds_bbox() -> Result<()> {
+		let runner = Runner::from_args(Args {
+			layer: None,
+			bbox: Some(true),
+		});
+		let tile = runner.run_at(TileCoord::new(1, 1, 0)?, create_tile())?.unwrap();
+		for layer in 0..2 {
+			let properties = properties(&tile, layer);
+			assert_eq!(properties.get("_tile_west"), Some(&GeoValue::from(0.0)));
+			assert_eq!(properties.get("_tile_east"), Some(&GeoValue::from(180.0)));
+			assert_eq!(properties.get("_tile_south"), Some(&GeoValue::from(0.0)));
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let operation = factory
+			.operation_from_vpl(r#"from_debug | vector_add_tile_coords layer="debug_x" bbox=true"#)
+			.await?;
+
+		let fields = &operation.tilejson().vector_layers.find("debug_x").unwrap().fields;
+		assert_eq!(fields.get("_tile_north").map(String::as_str), Some("Number"));
+
+		let coord = TileCoord::new(2, 3, 1)?;
+		let mut stream = operation.get_stream(TileBBox::from_min_and_max(2, 3, 1, 3, 1)?).await?;
+		let (stream_coord, tile) = stream.next().await.unwrap();
+		assert_eq!(stream_coord, coord);
+		let tile = tile.into_vector()?;
+		let layer = tile.find_layer("debug_x").unwrap();
+		let properties = layer.decode_tag_ids(&layer.features[0].tag_ids)?;
+		assert_eq!(properties.get("_tile_x"), Some(&GeoValue::from(3u32)));
+		Ok(())
+	}
+}
@@ -0,0 +1,193 @@
+use crate::{
+	PipelineFactory,
+	operations::vector::traits::{FeatureCounter, RunnerTrait, build_transform},
+	traits::{OperationFactoryTrait, OperationTrait, TransformOperationFactoryTrait},
+	vpl::VPLNode,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use versatiles_core::TileJSON;
+use versatiles_derive::context;
+use versatiles_geometry::{geo::GeoProperties, vector_tile::VectorTile};
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Thins out dense point features like POIs or place labels, similar to tippecanoe's `--drop-densest`.
+/// Each tile is divided into a grid and only the most important points of each grid cell are kept.
+/// Lines and polygons are not changed. The number of removed features is logged at the end.
+struct Args {
+	/// Only declutter features in this layer. Defaults to all layers.
+	layer: Option<String>,
+	/// Number of grid cells along each side of a tile, e.g. cells=32.
+	#[vpl(default = 16, min = 1)]
+	cells: u32,
+	/// Number of points to keep in each grid cell.
+	#[vpl(default = 1, min = 1)]
+	keep_top: u32,
+	/// Numeric property that ranks the points, e.g. rank_field="population". Points with higher values are kept first,
+	/// points without a numeric value are dropped first. Defaults to keeping the points that come first in the tile.
+	rank_field: Option<String>,
+	/// If set, lower values of `rank_field` are more important, e.g. for ranks where 1 is the most important.
+	#[vpl(default = false)]
+	rank_ascending: bool,
+}
+
+#[derive(Debug)]
+struct Runner {
+	layer: Option<String>,
+	cells: u32,
+	keep_top: usize,
+	rank_field: Option<String>,
+	rank_ascending: bool,
+	removed_features: FeatureCounter,
+}
+
+impl Runner {
+	#[context("Failed to parse declutter arguments")]
+	pub fn from_args(args: Args) -> Result<Self> {
+		Ok(Self {
+			layer: args.layer,
+			cells: args.cells,
+			keep_top: args.keep_top as usize,
+			rank_field: args.rank_field,
+			rank_ascending: args.rank_ascending,
+			removed_features: FeatureCounter::new("vector_declutter", "removed", "points"),
+		})
+	}
+
+	/// Importance of a feature, higher is more important.
+	fn rank(&self, properties: &GeoProperties) -> f64 {
+		let Some(field) = &self.rank_field else {
+			return 0.0;
+		};
+		match properties.get(field).and_then(|value| value.as_f64().ok()) {
+			Some(value) if self.rank_ascending => -value,
+			Some(value) => value,
+			None => f64::NEG_INFINITY,
+		}
+	}
+}
+
+impl RunnerTrait for Runner {
+	#[context("Failed to run vector declutter")]
+	fn run(&self, mut tile: VectorTile) -> Result<Option<VectorTile>> {
+		for layer in &mut tile.layers {
+			if self.layer.as_ref().is_some_and(|name| name != &layer.name) {
+				continue;
+			}
+			let cell_size = f64::from(layer.extent) / f64::from(self.cells);
+			let count = layer.declutter_points(cell_size, self.keep_top, |properties| self.rank(properties))?;
+			if count > 0 {
+				log::trace!("removed {count} points in layer '{}'", layer.name);
+				self.removed_features.add(count);
+			}
+		}
+		Ok(Some(tile))
+	}
+
+	fn update_tilejson(&self, _tilejson: &mut TileJSON) {}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"vector_declutter"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		_factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		build_transform::<Runner>(source, Runner::from_args(args)?).await
+	}
+}
+
+// ───────────────────────── TESTS ─────────────────────────
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_geometry::{geo::*, vector_tile::VectorTileLayer};
+
+	fn args(rank_field: Option<&str>, rank_ascending: bool) -> Args {
+		Args {
+			layer: None,
+			cells: 4,
+			keep_top: 2,
+			rank_field: rank_field.map(String::from),
+			rank_ascending,
+		}
+	}
+
+	fn populations(tile: &VectorTile) -> Vec<Option<i64>> {
+		tile.layers[0]
+			.to_features()
+			.unwrap()
+			.iter()
+			.map(|f| f.properties.get("population").map(|v| v.as_f64().unwrap() as i64))
+			.collect()
+	}
+
+	fn cities() -> VectorTile {
+		let features = [
+			(10, Some(100)),
+			(20, Some(300)),
+			(30, None),
+			(40, Some(200)),
+			(2000, Some(50)),
+		]
+		.into_iter()
+		.map(|(x, population)| {
+			let mut feature = GeoFeature::new(Geometry::new_point(&[x, x]));
+			if let Some(population) = population {
+				feature.set_property("population".to_string(), population);
+			}
+			feature
+		})
+		.collect();
+		VectorTile::new(vec![
+			VectorTileLayer::from_features("cities".to_string(), features, 4096, 1).unwrap(),
+		])
+	}
+
+	#[test]
+	fn test_runner() -> Result<()> {
+		let runner = Runner::from_args(args(Some("population"), false))?;
+		let tile = runner.run(cities())?.unwrap();
+		assert_eq!(populations(&tile), vec![Some(300), Some(200), Some(50)]);
+		assert_eq!(runner.removed_features.get(), 2);
+
+		let runner = Runner::from_args(args(Some("population"), true))?;
+		let tile = runner.run(cities())?.unwrap();
+		assert_eq!(populations(&tile), vec![Some(100), Some(200), Some(50)]);
+
+		let runner = Runner::from_args(args(None, false))?;
+		let tile = runner.run(cities())?.unwrap();
+		assert_eq!(populations(&tile), vec![Some(100), Some(300), Some(50)]);
+		Ok(())
+	}
+
+	#[test]
+	fn test_args() -> Result<()> {
+		let a = Args::from_vpl_node(&VPLNode::try_from_str("vector_declutter")?)?;
+		assert_eq!((a.cells, a.keep_top, a.rank_ascending), (16, 1, false));
+
+		for vpl in ["vector_declutter cells=0", "vector_declutter keep_top=0"] {
+			let error = Args::from_vpl_node(&VPLNode::try_from_str(vpl)?).unwrap_err();
+			assert!(error.to_string().contains("must be at least 1, but is 0"), "{error}");
+		}
+		Ok(())
+	}
+}
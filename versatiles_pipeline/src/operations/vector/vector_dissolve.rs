@@ -0,0 +1,159 @@
+use crate::{
+	PipelineFactory,
+	operations::vector::traits::{FeatureCounter, RunnerTrait, build_transform},
+	traits::{OperationFactoryTrait, OperationTrait, TransformOperationFactoryTrait},
+	vpl::VPLNode,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use versatiles_core::TileJSON;
+use versatiles_derive::context;
+use versatiles_geometry::vector_tile::VectorTile;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Unions all polygons of a tile that share the same value of a property, e.g. `vector_dissolve field="class"`.
+/// Touching and overlapping polygons become one polygon, which massively reduces the complexity of landuse
+/// or landcover tiles at low zoom levels. The dissolved features keep only this property and lose their ids.
+/// Lines and points are not changed. The number of removed features is logged at the end.
+struct Args {
+	/// Name of the property whose values define which polygons are dissolved together.
+	field: String,
+	/// Only dissolve features in this layer. Defaults to all layers.
+	layer: Option<String>,
+}
+
+#[derive(Debug)]
+struct Runner {
+	field: String,
+	layer: Option<String>,
+	removed_features: FeatureCounter,
+}
+
+impl Runner {
+	pub fn from_args(args: Args) -> Self {
+		Self {
+			field: args.field,
+			layer: args.layer,
+			removed_features: FeatureCounter::new("vector_dissolve", "removed", "features"),
+		}
+	}
+}
+
+impl RunnerTrait for Runner {
+	#[context("Failed to run vector dissolve")]
+	fn run(&self, mut tile: VectorTile) -> Result<Option<VectorTile>> {
+		for layer in &mut tile.layers {
+			if self.layer.as_ref().is_some_and(|name| name != &layer.name) {
+				continue;
+			}
+			let count = layer.dissolve_polygons(&self.field)?;
+			if count > 0 {
+				log::trace!("removed {count} features in layer '{}'", layer.name);
+				self.removed_features.add(count);
+			}
+		}
+		Ok(Some(tile))
+	}
+
+	fn update_tilejson(&self, _tilejson: &mut TileJSON) {}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"vector_dissolve"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		_factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		build_transform::<Runner>(source, Runner::from_args(args)).await
+	}
+}
+
+// ───────────────────────── TESTS ─────────────────────────
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_core::TileBBox;
+	use versatiles_geometry::{geo::*, vector_tile::VectorTileLayer};
+
+	fn landuse(name: &str) -> VectorTileLayer {
+		let features = [0, 10, 20]
+			.into_iter()
+			.map(|x| {
+				let mut feature = GeoFeature::new(Geometry::new_polygon(&[[
+					[x, 0],
+					[x + 10, 0],
+					[x + 10, 10],
+					[x, 10],
+					[x, 0],
+				]]));
+				feature.set_property("class".to_string(), "forest");
+				feature
+			})
+			.collect();
+		VectorTileLayer::from_features(name.to_string(), features, 4096, 1).unwrap()
+	}
+
+	#[test]
+	fn test_runner() -> Result<()> {
+		let runner = Runner::from_args(Args {
+			field: "class".to_string(),
+			layer: Some("a".to_string()),
+		});
+		let tile = runner.run(VectorTile::new(vec![landuse("a"), landuse("b")]))?.unwrap();
+		assert_eq!(tile.layers[0].features.len(), 1);
+		assert_eq!(tile.layers[1].features.len(), 3);
+		assert_eq!(runner.removed_features.get(), 2);
+
+		let Geometry::MultiPolygon(polygons) = tile.layers[0].features[0].to_geometry()? else {
+			panic!("expected a multi polygon");
+		};
+		assert_eq!(polygons.0.len(), 1);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		// the debug tile of x=1111 draws "x:1111" with one feature per character
+		let factory = PipelineFactory::new_dummy();
+		let operation = factory
+			.operation_from_vpl(r#"from_debug | vector_dissolve field="char""#)
+			.await?;
+
+		let mut stream = operation
+			.get_stream(TileBBox::from_min_and_max(11, 1111, 0, 1111, 0)?)
+			.await?;
+		let tile = stream.next().await.unwrap().1.into_vector()?;
+		let chars = tile.find_layer("debug_x").unwrap().to_features()?;
+		let chars = chars
+			.iter()
+			.map(|feature| feature.properties.get("char").unwrap().to_string())
+			.collect::<Vec<_>>();
+		assert_eq!(chars, ["x", ":", "1"]);
+
+		assert!(
+			factory
+				.operation_from_vpl("from_debug | vector_dissolve")
+				.await
+				.is_err()
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,396 @@
+use crate::{
+	PipelineFactory,
+	operations::vector::traits::{RunnerTrait, build_transform},
+	traits::{OperationFactoryTrait, OperationTrait, TransformOperationFactoryTrait},
+	vpl::VPLNode,
+};
+use anyhow::{Result, bail, ensure};
+use async_trait::async_trait;
+use versatiles_core::{TileJSON, utils::Fnv1aHasher};
+use versatiles_derive::context;
+use versatiles_geometry::{
+	geo::{GeoProperties, GeoValue},
+	vector_tile::{VectorTile, VectorTileLayer},
+};
+
+/// Hashed IDs are limited to 53 bits, so they can be represented exactly as JavaScript numbers.
+const MAX_SAFE_ID: u64 = (1 << 53) - 1;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Sets the IDs of vector tile features, e.g. to enable feature state in MapLibre.
+struct Args {
+	/// How to derive the IDs: "property" (use the value of a property), "increment" (count up from 0 per layer and tile) or "hash" (hash of all properties).
+	source: IdMethod,
+
+	/// Name of the property used as ID, required for source="property". Only non-negative integers (or strings containing them) are used.
+	property: Option<String>,
+
+	/// Only update features in this layer. Defaults to all layers.
+	layer: Option<String>,
+
+	/// If set, existing feature IDs are overwritten. Defaults to false.
+	overwrite: Option<bool>,
+
+	/// If set and source="property", the property is removed after it has been used as ID.
+	remove_property: Option<bool>,
+
+	/// If set and source="hash", the geometry is included in the hash. Note that the same feature can have different geometries in neighbouring tiles.
+	hash_geometry: Option<bool>,
+}
+
+#[derive(versatiles_derive::VPLDecode, Clone, Copy, Debug, PartialEq)]
+enum IdMethod {
+	Property,
+	Increment,
+	Hash,
+}
+
+#[derive(Debug, PartialEq)]
+enum IdSource {
+	Property(String),
+	Increment,
+	Hash,
+}
+
+#[derive(Debug)]
+struct Runner {
+	source: IdSource,
+	layer: Option<String>,
+	overwrite: bool,
+	remove_property: bool,
+	hash_geometry: bool,
+}
+
+impl Runner {
+	#[context("Failed to build vector feature ids runner")]
+	pub fn from_args(args: Args) -> Result<Self> {
+		let source = match args.source {
+			IdMethod::Property => {
+				let Some(property) = args.property else {
+					bail!("source=\"property\" requires the parameter 'property'")
+				};
+				IdSource::Property(property)
+			}
+			IdMethod::Increment => IdSource::Increment,
+			IdMethod::Hash => IdSource::Hash,
+		};
+
+		let remove_property = args.remove_property.unwrap_or(false);
+		ensure!(
+			!remove_property || matches!(source, IdSource::Property(_)),
+			"remove_property can only be used with source=\"property\""
+		);
+
+		Ok(Self {
+			source,
+			layer: args.layer,
+			overwrite: args.overwrite.unwrap_or(false),
+			remove_property,
+			hash_geometry: args.hash_geometry.unwrap_or(false),
+		})
+	}
+
+	fn update_layer(&self, layer: &mut VectorTileLayer) -> Result<()> {
+		let mut counter: u64 = 0;
+		let mut ids = Vec::with_capacity(layer.features.len());
+
+		for feature in &layer.features {
+			if feature.id.is_some() && !self.overwrite {
+				ids.push(feature.id);
+				continue;
+			}
+			ids.push(match &self.source {
+				IdSource::Property(key) => layer.decode_tag_ids(&feature.tag_ids)?.get(key).and_then(value_to_id),
+				IdSource::Increment => {
+					counter += 1;
+					Some(counter - 1)
+				}
+				IdSource::Hash => {
+					let mut hasher = Fnv1aHasher::new();
+					hash_properties(&mut hasher, &layer.decode_tag_ids(&feature.tag_ids)?);
+					if self.hash_geometry {
+						hasher.write(feature.geom_data.as_slice());
+					}
+					Some(hasher.finish() & MAX_SAFE_ID)
+				}
+			});
+		}
+
+		for (feature, id) in layer.features.iter_mut().zip(ids) {
+			feature.id = id;
+		}
+
+		if self.remove_property
+			&& let IdSource::Property(key) = &self.source
+		{
+			layer.map_properties(|mut properties| {
+				properties.remove(key);
+				properties
+			})?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Converts a property value into a feature ID, if it represents a non-negative integer.
+fn value_to_id(value: &GeoValue) -> Option<u64> {
+	match value {
+		GeoValue::UInt(v) => Some(*v),
+		GeoValue::Int(v) => u64::try_from(*v).ok(),
+		GeoValue::Double(v) if v.fract() == 0.0 && *v >= 0.0 && *v <= u64::MAX as f64 => Some(*v as u64),
+		GeoValue::Float(v) if v.fract() == 0.0 && *v >= 0.0 && *v <= u64::MAX as f32 => Some(*v as u64),
+		GeoValue::String(v) => v.trim().parse::<u64>().ok(),
+		_ => None,
+	}
+}
+
+/// Hashes all properties in key order. Strings are prefixed with their length and values with a type tag,
+/// so the hash only depends on the data and not on any formatting.
+fn hash_properties(hasher: &mut Fnv1aHasher, properties: &GeoProperties) {
+	for (key, value) in properties.iter() {
+		hash_str(hasher, key);
+		match value {
+			GeoValue::Bool(v) => hasher.write(&[1, u8::from(*v)]),
+			GeoValue::Double(v) => {
+				hasher.write(&[2]);
+				hasher.write(&v.to_le_bytes());
+			}
+			GeoValue::Float(v) => {
+				hasher.write(&[3]);
+				hasher.write(&v.to_le_bytes());
+			}
+			GeoValue::Int(v) => {
+				hasher.write(&[4]);
+				hasher.write(&v.to_le_bytes());
+			}
+			GeoValue::Null => hasher.write(&[5]),
+			GeoValue::String(v) => {
+				hasher.write(&[6]);
+				hash_str(hasher, v);
+			}
+			GeoValue::UInt(v) => {
+				hasher.write(&[7]);
+				hasher.write_u64(*v);
+			}
+		}
+	}
+}
+
+fn hash_str(hasher: &mut Fnv1aHasher, value: &str) {
+	hasher.write_u64(value.len() as u64);
+	hasher.write(value.as_bytes());
+}
+
+impl RunnerTrait for Runner {
+	#[context("Failed to run vector feature ids")]
+	fn run(&self, mut tile: VectorTile) -> Result<Option<VectorTile>> {
+		for layer in &mut tile.layers {
+			if self.layer.as_ref().is_none_or(|name| name == &layer.name) {
+				self.update_layer(layer)?;
+			}
+		}
+
+		Ok(Some(tile))
+	}
+
+	fn update_tilejson(&self, tilejson: &mut TileJSON) {
+		if self.remove_property
+			&& let IdSource::Property(key) = &self.source
+		{
+			for (name, layer) in tilejson.vector_layers.iter_mut() {
+				if self.layer.as_ref().is_none_or(|l| l == name) {
+					layer.fields.remove(key);
+				}
+			}
+		}
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"vector_feature_ids"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		_factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		build_transform::<Runner>(source, Runner::from_args(args)?).await
+	}
+}
+
+// ───────────────────────── TESTS ─────────────────────────
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	use versatiles_core::TileBBox;
+	use versatiles_geometry::geo::*;
+
+	fn args(source: IdMethod) -> Args {
+		Args {
+			source,
+			property: None,
+			layer: None,
+			overwrite: None,
+			remove_property: None,
+			hash_geometry: None,
+		}
+	}
+
+	fn create_tile() -> VectorTile {
+		let features = [
+			GeoValue::from(7),
+			GeoValue::from("12"),
+			GeoValue::from(-1),
+			GeoValue::from("abc"),
+		]
+		.into_iter()
+		.map(|v| {
+			let mut feature = GeoFeature::new(Geometry::new_example());
+			feature.properties = GeoProperties::from(vec![("osm_id", v), ("name", GeoValue::from("x"))]);
+			feature
+		})
+		.collect();
+		VectorTile::new(vec![
+			VectorTileLayer::from_features("layer".to_string(), features, 4096, 1).unwrap(),
+		])
+	}
+
+	fn ids(tile: &VectorTile) -> Vec<Option<u64>> {
+		tile.layers[0].features.iter().map(|f| f.id).collect()
+	}
+
+	#[test]
+	fn test_from_property() {
+		let runner = Runner::from_args(Args {
+			property: Some("osm_id".to_string()),
+			remove_property: Some(true),
+			..args(IdMethod::Property)
+		})
+		.unwrap();
+		let tile = runner.run(create_tile()).unwrap().unwrap();
+		assert_eq!(ids(&tile), [Some(7), Some(12), None, None]);
+		assert_eq!(tile.layers[0].property_manager.key.list, ["name"]);
+	}
+
+	#[test]
+	fn test_increment() {
+		let runner = Runner::from_args(args(IdMethod::Increment)).unwrap();
+		let tile = runner.run(create_tile()).unwrap().unwrap();
+		assert_eq!(ids(&tile), [Some(0), Some(1), Some(2), Some(3)]);
+	}
+
+	#[test]
+	fn test_keep_existing_ids() {
+		let mut tile = create_tile();
+		tile.layers[0].features[1].id = Some(99);
+
+		let runner = Runner::from_args(args(IdMethod::Increment)).unwrap();
+		let result = runner.run(tile.clone()).unwrap().unwrap();
+		assert_eq!(ids(&result), [Some(0), Some(99), Some(1), Some(2)]);
+
+		let runner = Runner::from_args(Args {
+			overwrite: Some(true),
+			..args(IdMethod::Increment)
+		})
+		.unwrap();
+		let result = runner.run(tile).unwrap().unwrap();
+		assert_eq!(ids(&result), [Some(0), Some(1), Some(2), Some(3)]);
+	}
+
+	#[test]
+	fn test_hash() {
+		let runner = Runner::from_args(args(IdMethod::Hash)).unwrap();
+		let tile1 = runner.run(create_tile()).unwrap().unwrap();
+		let tile2 = runner.run(create_tile()).unwrap().unwrap();
+
+		let ids1 = ids(&tile1);
+		assert_eq!(ids1, ids(&tile2));
+		assert!(ids1.iter().all(|id| id.unwrap() <= MAX_SAFE_ID));
+
+		let mut unique = ids1.clone();
+		unique.sort();
+		unique.dedup();
+		assert_eq!(unique.len(), 4);
+	}
+
+	#[test]
+	fn test_hash_properties() {
+		let hash = |properties: Vec<(&str, GeoValue)>| {
+			let mut hasher = Fnv1aHasher::new();
+			hash_properties(&mut hasher, &GeoProperties::from(properties));
+			hasher.finish()
+		};
+
+		// values of different types or with shifted boundaries don't collide
+		assert_ne!(
+			hash(vec![("a", GeoValue::from(12u64))]),
+			hash(vec![("a", GeoValue::from("12"))])
+		);
+		assert_ne!(
+			hash(vec![("a", GeoValue::from("bc"))]),
+			hash(vec![("ab", GeoValue::from("c"))])
+		);
+
+		// IDs are persisted in tiles, so the hash must never change
+		assert_eq!(hash(vec![("name", GeoValue::from("x"))]), 0xb213_ba02_0ba3_26f7);
+		assert_eq!(hash(vec![("id", GeoValue::from(42u64))]), 0x2ecf_f114_077e_cb9d);
+	}
+
+	#[test]
+	fn test_invalid_args() {
+		let node = VPLNode::try_from_str(r#"vector_feature_ids source="unknown""#).unwrap();
+		let error = Args::from_vpl_node(&node).unwrap_err();
+		assert!(format!("{error:#}").contains("expected one of: property, increment, hash"));
+		assert!(Runner::from_args(args(IdMethod::Property)).is_err());
+		assert!(
+			Runner::from_args(Args {
+				remove_property: Some(true),
+				..args(IdMethod::Hash)
+			})
+			.is_err()
+		);
+	}
+
+	#[test]
+	fn test_value_to_id() {
+		assert_eq!(value_to_id(&GeoValue::UInt(5)), Some(5));
+		assert_eq!(value_to_id(&GeoValue::Int(-5)), None);
+		assert_eq!(value_to_id(&GeoValue::Double(3.0)), Some(3));
+		assert_eq!(value_to_id(&GeoValue::Double(3.5)), None);
+		assert_eq!(value_to_id(&GeoValue::from(" 42 ")), Some(42));
+		assert_eq!(value_to_id(&GeoValue::Bool(true)), None);
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let operation = factory
+			.operation_from_vpl(r#"from_debug | vector_feature_ids source="increment" layer="debug_x""#)
+			.await?;
+
+		let mut stream = operation.get_stream(TileBBox::new_full(0)?).await?;
+		let tile = stream.next().await.unwrap().1.into_vector()?;
+		let layer = tile.find_layer("debug_x").unwrap();
+		assert!(!layer.features.is_empty());
+		assert!(layer.features.iter().all(|f| f.id.is_some()));
+		Ok(())
+	}
+}
@@ -0,0 +1,111 @@
+use crate::{
+	PipelineFactory,
+	operations::vector::traits::{FeatureCounter, RunnerTrait, build_transform},
+	traits::{OperationFactoryTrait, OperationTrait, TransformOperationFactoryTrait},
+	vpl::VPLNode,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use versatiles_core::TileJSON;
+use versatiles_derive::context;
+use versatiles_geometry::vector_tile::VectorTile;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Repairs invalid polygons, e.g. self-intersections, overlapping parts and wrong winding order.
+/// Polygons that collapse completely are removed. The number of fixed features is logged at the end.
+struct Args {
+	/// Only fix features in this layer. Defaults to all layers.
+	layer: Option<String>,
+}
+
+#[derive(Debug)]
+struct Runner {
+	layer: Option<String>,
+	fixed_features: FeatureCounter,
+}
+
+impl Runner {
+	pub fn from_args(args: Args) -> Self {
+		Self {
+			layer: args.layer,
+			fixed_features: FeatureCounter::new("vector_fix_geometries", "fixed", "features"),
+		}
+	}
+}
+
+impl RunnerTrait for Runner {
+	#[context("Failed to run vector fix geometries")]
+	fn run(&self, mut tile: VectorTile) -> Result<Option<VectorTile>> {
+		for layer in &mut tile.layers {
+			if self.layer.as_ref().is_some_and(|name| name != &layer.name) {
+				continue;
+			}
+			let count = layer.fix_geometries()?;
+			if count > 0 {
+				log::trace!("fixed {count} features in layer '{}'", layer.name);
+				self.fixed_features.add(count);
+			}
+		}
+		Ok(Some(tile))
+	}
+
+	fn update_tilejson(&self, _tilejson: &mut TileJSON) {}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"vector_fix_geometries"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		_factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		build_transform::<Runner>(source, Runner::from_args(args)).await
+	}
+}
+
+// ───────────────────────── TESTS ─────────────────────────
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_geometry::{geo::*, vector_tile::VectorTileLayer};
+
+	fn bow_tie_layer(name: &str) -> VectorTileLayer {
+		let feature = GeoFeature::new(Geometry::new_polygon(&[[[0, 0], [40, 40], [40, 0], [0, 60], [0, 0]]]));
+		VectorTileLayer::from_features(name.to_string(), vec![feature], 4096, 1).unwrap()
+	}
+
+	#[test]
+	fn test_runner() -> Result<()> {
+		let runner = Runner::from_args(Args {
+			layer: Some("a".to_string()),
+		});
+		let tile = VectorTile::new(vec![bow_tie_layer("a"), bow_tie_layer("b")]);
+		let tile = runner.run(tile)?.unwrap();
+		assert_eq!(runner.fixed_features.get(), 1);
+
+		let polygon_count = |layer: &VectorTileLayer| match layer.features[0].to_geometry().unwrap() {
+			Geometry::MultiPolygon(g) => g.0.len(),
+			g => panic!("unexpected geometry {g:?}"),
+		};
+		assert_eq!(polygon_count(&tile.layers[0]), 2);
+		assert_eq!(polygon_count(&tile.layers[1]), 1);
+		Ok(())
+	}
+}
@@ -0,0 +1,164 @@
+use crate::{
+	PipelineFactory,
+	operations::vector::traits::{FeatureCounter, RunnerTrait, build_transform},
+	traits::{OperationFactoryTrait, OperationTrait, TransformOperationFactoryTrait},
+	vpl::VPLNode,
+};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use versatiles_core::TileJSON;
+use versatiles_derive::context;
+use versatiles_geometry::vector_tile::VectorTile;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Merges line features with identical properties into one feature per tile and joins their touching segments,
+/// e.g. to reduce the number of features of road or rail networks that come from per-segment sources.
+/// Segments are only joined where exactly two of them meet. The number of removed features is logged at the end.
+struct Args {
+	/// Only merge features in this layer. Defaults to all layers.
+	layer: Option<String>,
+	/// Removes merged lines that are shorter than this length (in units of the layer extent, usually 4096 per tile side), e.g. min_length=16. (default: 0)
+	min_length: Option<f32>,
+}
+
+#[derive(Debug)]
+struct Runner {
+	layer: Option<String>,
+	min_length: f64,
+	removed_features: FeatureCounter,
+}
+
+impl Runner {
+	#[context("Failed to parse merge lines arguments")]
+	pub fn from_args(args: Args) -> Result<Self> {
+		let min_length = f64::from(args.min_length.unwrap_or(0.0));
+		ensure!(min_length >= 0.0, "'min_length' must not be negative");
+		Ok(Self {
+			layer: args.layer,
+			min_length,
+			removed_features: FeatureCounter::new("vector_merge_lines", "removed", "features"),
+		})
+	}
+}
+
+impl RunnerTrait for Runner {
+	#[context("Failed to run vector merge lines")]
+	fn run(&self, mut tile: VectorTile) -> Result<Option<VectorTile>> {
+		for layer in &mut tile.layers {
+			if self.layer.as_ref().is_some_and(|name| name != &layer.name) {
+				continue;
+			}
+			let count = layer.merge_lines(self.min_length)?;
+			if count > 0 {
+				log::trace!("removed {count} features in layer '{}'", layer.name);
+				self.removed_features.add(count);
+			}
+		}
+		Ok(Some(tile))
+	}
+
+	fn update_tilejson(&self, _tilejson: &mut TileJSON) {}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"vector_merge_lines"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		_factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		build_transform::<Runner>(source, Runner::from_args(args)?).await
+	}
+}
+
+// ───────────────────────── TESTS ─────────────────────────
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_core::TileBBox;
+	use versatiles_geometry::{geo::*, vector_tile::VectorTileLayer};
+
+	fn segments(name: &str) -> VectorTileLayer {
+		let features = [[[0, 0], [10, 0]], [[10, 0], [20, 0]], [[20, 0], [30, 0]]]
+			.iter()
+			.map(|line| {
+				let mut feature = GeoFeature::new(Geometry::new_line_string(line));
+				feature.set_property("class".to_string(), "rail");
+				feature
+			})
+			.collect();
+		VectorTileLayer::from_features(name.to_string(), features, 4096, 1).unwrap()
+	}
+
+	#[test]
+	fn test_runner() -> Result<()> {
+		let runner = Runner::from_args(Args {
+			layer: Some("a".to_string()),
+			min_length: None,
+		})?;
+		let tile = runner
+			.run(VectorTile::new(vec![segments("a"), segments("b")]))?
+			.unwrap();
+		assert_eq!(tile.layers[0].features.len(), 1);
+		assert_eq!(tile.layers[1].features.len(), 3);
+		assert_eq!(runner.removed_features.get(), 2);
+
+		let runner = Runner::from_args(Args {
+			layer: None,
+			min_length: Some(40.0),
+		})?;
+		let tile = runner.run(VectorTile::new(vec![segments("a")]))?.unwrap();
+		assert!(tile.layers[0].features.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn test_invalid_args() {
+		assert!(
+			Runner::from_args(Args {
+				layer: None,
+				min_length: Some(-1.0),
+			})
+			.is_err()
+		);
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		// the background layer of debug tiles is a circle with a length of about 12900
+		let count_background = async |vpl: &str| -> Result<usize> {
+			let factory = PipelineFactory::new_dummy();
+			let operation = factory.operation_from_vpl(vpl).await?;
+			let mut stream = operation.get_stream(TileBBox::new_full(0)?).await?;
+			let tile = stream.next().await.unwrap().1.into_vector()?;
+			Ok(tile.find_layer("background").unwrap().features.len())
+		};
+
+		assert_eq!(
+			count_background("from_debug | vector_merge_lines min_length=10000").await?,
+			1
+		);
+		assert_eq!(
+			count_background("from_debug | vector_merge_lines min_length=20000").await?,
+			0
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,580 @@
+use crate::{
+	PipelineFactory,
+	operations::vector::traits::{RunnerTrait, build_transform},
+	traits::{OperationFactoryTrait, OperationTrait, TransformOperationFactoryTrait},
+	vpl::VPLNode,
+};
+use anyhow::{Result, anyhow, bail, ensure};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use versatiles_core::{
+	TileJSON, VectorLayer,
+	json::{JsonObject, JsonValue},
+};
+use versatiles_derive::context;
+use versatiles_geometry::{
+	geo::{GeoProperties, GeoValue},
+	vector_tile::{GeomType, VectorTile, VectorTileLayer},
+};
+
+/// Built-in mappings, selectable with `schema=…`.
+const SCHEMAS: [(&str, &str); 1] = [(
+	"openmaptiles_to_shortbread",
+	include_str!("schemas/openmaptiles_to_shortbread.json"),
+)];
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Migrates vector tiles from one schema to another by renaming, splitting and merging layers and by renaming and mapping properties.
+/// Use either a built-in mapping (`schema`) or a JSON mapping file (`mapping`). See the built-in "openmaptiles_to_shortbread" mapping for the file format.
+/// Each rule maps the features of a `layer` (optionally filtered by `where` and `geometry`) `to` a target layer and can `rename`, `flags`, `values`, `set` and `keep` properties.
+struct Args {
+	/// Name of a built-in mapping, e.g. schema="openmaptiles_to_shortbread".
+	schema: Option<String>,
+	/// Path to a JSON mapping file, e.g. mapping="my_schema.json".
+	mapping: Option<String>,
+}
+
+/// Mapping of the features of one source layer (optionally filtered) to a target layer.
+///
+/// The changes are applied in this order: `rename`, `flags`, `values`, `set`, `keep`.
+#[derive(Debug, Default)]
+struct Rule {
+	/// Name of the source layer.
+	layer: String,
+	/// Name of the target layer. Defaults to the source layer.
+	to: String,
+	/// Only features with this geometry type.
+	geometry: Option<GeomType>,
+	/// Only features whose properties have one of the listed values.
+	filter: Vec<(String, Vec<String>)>,
+	/// Renamed properties: old key → new key.
+	rename: Vec<(String, String)>,
+	/// Properties whose values become boolean properties, e.g. `brunnel=bridge` → `bridge=true`.
+	flags: Vec<(String, Vec<String>)>,
+	/// Mapped values per property. `None` removes the property.
+	values: HashMap<String, HashMap<String, Option<GeoValue>>>,
+	/// Properties that are set on all features.
+	set: Vec<(String, GeoValue)>,
+	/// If set, all other properties are removed.
+	keep: Option<Vec<String>>,
+}
+
+impl Rule {
+	#[context("Failed to parse mapping rule")]
+	fn from_json(json: &JsonObject) -> Result<Self> {
+		let layer = json
+			.get_string("layer")?
+			.ok_or_else(|| anyhow!("rule must have a 'layer'"))?;
+		let to = json.get_string("to")?.unwrap_or_else(|| layer.clone());
+
+		let geometry = match json.get_string("geometry")?.as_deref() {
+			None => None,
+			Some("point") => Some(GeomType::MultiPoint),
+			Some("line") => Some(GeomType::MultiLineString),
+			Some("polygon") => Some(GeomType::MultiPolygon),
+			Some(other) => bail!("unknown geometry '{other}', expected 'point', 'line' or 'polygon'"),
+		};
+
+		let mut rule = Rule {
+			layer,
+			to,
+			geometry,
+			keep: json.get_string_vec("keep")?,
+			..Default::default()
+		};
+
+		if let Some(filter) = json.get_object("where")? {
+			for (key, value) in filter.iter() {
+				let values = match value {
+					JsonValue::Array(array) => array.as_vec().iter().map(json_to_string).collect::<Result<_>>()?,
+					value => vec![json_to_string(value)?],
+				};
+				rule.filter.push((key.clone(), values));
+			}
+		}
+		if let Some(rename) = json.get_object("rename")? {
+			for (old, new) in rename.iter() {
+				rule.rename.push((old.clone(), new.as_string()?));
+			}
+		}
+		if let Some(flags) = json.get_object("flags")? {
+			for (key, values) in flags.iter() {
+				rule.flags.push((key.clone(), values.as_array()?.as_string_vec()?));
+			}
+		}
+		if let Some(values) = json.get_object("values")? {
+			for (key, map) in values.iter() {
+				let map = map
+					.as_object()?
+					.iter()
+					.map(|(old, new)| Ok((old.clone(), json_to_geo_value(new)?)))
+					.collect::<Result<_>>()?;
+				rule.values.insert(key.clone(), map);
+			}
+		}
+		if let Some(set) = json.get_object("set")? {
+			for (key, value) in set.iter() {
+				let value = json_to_geo_value(value)?.ok_or_else(|| anyhow!("'set.{key}' must not be null"))?;
+				rule.set.push((key.clone(), value));
+			}
+		}
+
+		Ok(rule)
+	}
+
+	fn matches(&self, geom_type: GeomType, properties: &GeoProperties) -> bool {
+		if self.geometry.is_some_and(|geometry| geometry != geom_type) {
+			return false;
+		}
+		self.filter.iter().all(|(key, values)| {
+			properties
+				.get(key)
+				.is_some_and(|value| values.contains(&value.to_string()))
+		})
+	}
+
+	fn apply(&self, mut properties: GeoProperties) -> GeoProperties {
+		for (old, new) in &self.rename {
+			if let Some(value) = properties.0.remove(old) {
+				properties.insert(new.clone(), value);
+			}
+		}
+		for (key, flags) in &self.flags {
+			if let Some(value) = properties.0.remove(key) {
+				let value = value.to_string();
+				if flags.contains(&value) {
+					properties.insert(value, GeoValue::Bool(true));
+				}
+			}
+		}
+		for (key, map) in &self.values {
+			let Some(value) = properties.get(key) else { continue };
+			match map.get(&value.to_string()) {
+				Some(Some(new_value)) => properties.insert(key.clone(), new_value.clone()),
+				Some(None) => properties.remove(key),
+				None => {}
+			}
+		}
+		for (key, value) in &self.set {
+			properties.insert(key.clone(), value.clone());
+		}
+		if let Some(keep) = &self.keep {
+			properties.retain(|key, _| keep.contains(key));
+		}
+		properties
+	}
+
+	fn apply_to_fields(&self, fields: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+		let mut fields = fields.clone();
+		for (old, new) in &self.rename {
+			if let Some(value) = fields.remove(old) {
+				fields.insert(new.clone(), value);
+			}
+		}
+		for (key, flags) in &self.flags {
+			if fields.remove(key).is_some() {
+				for flag in flags {
+					fields.insert(flag.clone(), String::from("Boolean"));
+				}
+			}
+		}
+		for (key, value) in &self.set {
+			let field_type = match value {
+				GeoValue::String(_) => "String",
+				GeoValue::Bool(_) => "Boolean",
+				_ => "Number",
+			};
+			fields.insert(key.clone(), field_type.to_string());
+		}
+		if let Some(keep) = &self.keep {
+			fields.retain(|key, _| keep.contains(key));
+		}
+		fields
+	}
+}
+
+/// Converts a JSON scalar to the string that is compared with property values.
+fn json_to_string(json: &JsonValue) -> Result<String> {
+	Ok(json_to_geo_value(json)?.map_or_else(|| String::from("null"), |value| value.to_string()))
+}
+
+fn json_to_geo_value(json: &JsonValue) -> Result<Option<GeoValue>> {
+	Ok(match json {
+		JsonValue::Null => None,
+		JsonValue::Boolean(value) => Some(GeoValue::Bool(*value)),
+		JsonValue::String(value) => Some(GeoValue::String(value.clone())),
+		JsonValue::Number(value) if value.fract() == 0.0 && *value >= 0.0 => Some(GeoValue::UInt(*value as u64)),
+		JsonValue::Number(value) if value.fract() == 0.0 => Some(GeoValue::Int(*value as i64)),
+		JsonValue::Number(value) => Some(GeoValue::Double(*value)),
+		value => bail!(
+			"expected a string, number, boolean or null, but got {}",
+			value.type_as_str()
+		),
+	})
+}
+
+#[derive(Debug)]
+struct Runner {
+	/// Rules per source layer, in the order of the mapping.
+	rules: HashMap<String, Vec<Rule>>,
+	/// If set, layers and features without a matching rule are removed.
+	drop_unmapped: bool,
+}
+
+impl Runner {
+	#[context("Failed to parse schema mapping")]
+	pub fn from_json(json: &str) -> Result<Self> {
+		let json = JsonObject::parse_str(json)?;
+		let drop_unmapped = match json.get("drop_unmapped") {
+			None => false,
+			Some(JsonValue::Boolean(value)) => *value,
+			Some(value) => bail!("'drop_unmapped' must be a boolean, but is {}", value.type_as_str()),
+		};
+
+		let mut rules: HashMap<String, Vec<Rule>> = HashMap::new();
+		let layers = json
+			.get_array("layers")?
+			.ok_or_else(|| anyhow!("mapping must have a 'layers' array"))?;
+		for (index, rule) in layers.as_vec().iter().enumerate() {
+			let rule = Rule::from_json(rule.as_object()?).with_context(|| format!("in rule {}", index + 1))?;
+			rules.entry(rule.layer.clone()).or_default().push(rule);
+		}
+		ensure!(!rules.is_empty(), "mapping must contain at least one rule");
+
+		Ok(Self { rules, drop_unmapped })
+	}
+
+	#[context("Failed to load schema mapping")]
+	pub fn from_args(args: &Args, factory: &PipelineFactory) -> Result<Self> {
+		match (&args.schema, &args.mapping) {
+			(Some(schema), None) => {
+				let (_, json) = SCHEMAS.iter().find(|(name, _)| name == schema).ok_or_else(|| {
+					let names = SCHEMAS.map(|(name, _)| name).join(", ");
+					anyhow!("unknown schema '{schema}', known schemas are: {names}")
+				})?;
+				Self::from_json(json)
+			}
+			(None, Some(mapping)) => {
+				let path = factory.resolve_path(mapping);
+				let json =
+					std::fs::read_to_string(&path).with_context(|| format!("Failed to read mapping file {path:?}"))?;
+				Self::from_json(&json)
+			}
+			_ => bail!("either 'schema' or 'mapping' must be set"),
+		}
+	}
+}
+
+/// Returns the layer named `name` in `layers`, adding it if it doesn't exist yet.
+fn get_or_add_layer<'a>(
+	layers: &'a mut Vec<VectorTileLayer>,
+	name: &str,
+	source: &VectorTileLayer,
+) -> Result<&'a mut VectorTileLayer> {
+	let index = match layers.iter().position(|layer| layer.name == name) {
+		Some(index) => index,
+		None => {
+			layers.push(VectorTileLayer::new(name.to_string(), source.extent, source.version));
+			layers.len() - 1
+		}
+	};
+	let layer = &mut layers[index];
+	ensure!(
+		layer.extent == source.extent,
+		"can not merge layer '{}' into '{name}', because their extents differ",
+		source.name
+	);
+	Ok(layer)
+}
+
+impl RunnerTrait for Runner {
+	#[context("Failed to run vector migrate schema")]
+	fn run(&self, tile: VectorTile) -> Result<Option<VectorTile>> {
+		let mut layers: Vec<VectorTileLayer> = Vec::new();
+
+		for mut source in tile.layers {
+			let Some(rules) = self.rules.get(&source.name) else {
+				if !self.drop_unmapped {
+					let name = source.name.clone();
+					get_or_add_layer(&mut layers, &name, &source)?.add_from_layer(source)?;
+				}
+				continue;
+			};
+
+			for feature in std::mem::take(&mut source.features) {
+				let properties = source.decode_tag_ids(&feature.tag_ids)?;
+				let (name, properties) = match rules.iter().find(|rule| rule.matches(feature.geom_type, &properties)) {
+					Some(rule) => (&rule.to, rule.apply(properties)),
+					None if self.drop_unmapped => continue,
+					None => (&source.name, properties),
+				};
+				get_or_add_layer(&mut layers, name, &source)?.add_vector_tile_features(feature, properties);
+			}
+		}
+
+		layers.retain(|layer| !layer.features.is_empty());
+		Ok(Some(VectorTile::new(layers)))
+	}
+
+	fn update_tilejson(&self, tilejson: &mut TileJSON) {
+		let old_layers = std::mem::take(&mut tilejson.vector_layers.0);
+		let mut add = |id: &str, layer: VectorLayer| match tilejson.vector_layers.0.get_mut(id) {
+			Some(target) => target.merge(&layer),
+			None => {
+				tilejson.vector_layers.0.insert(id.to_string(), layer);
+			}
+		};
+
+		for (id, layer) in old_layers {
+			let Some(rules) = self.rules.get(&id) else {
+				if !self.drop_unmapped {
+					add(&id, layer);
+				}
+				continue;
+			};
+			for rule in rules {
+				add(
+					&rule.to,
+					VectorLayer {
+						fields: rule.apply_to_fields(&layer.fields),
+						..layer.clone()
+					},
+				);
+			}
+			if !self.drop_unmapped {
+				add(&id, layer);
+			}
+		}
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"vector_migrate_schema"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		build_transform::<Runner>(source, Runner::from_args(&args, factory)?).await
+	}
+}
+
+// ───────────────────────── TESTS ─────────────────────────
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Context;
+	use pretty_assertions::assert_eq;
+	use versatiles_core::TileBBox;
+	use versatiles_geometry::geo::*;
+
+	fn create_layer(name: &str, features: &[(Geometry, &[(&str, GeoValue)])]) -> VectorTileLayer {
+		let features = features
+			.iter()
+			.map(|(geometry, properties)| {
+				let mut feature = GeoFeature::new(geometry.clone());
+				feature.properties = GeoProperties::from(properties.to_vec());
+				feature
+			})
+			.collect();
+		VectorTileLayer::from_features(name.to_string(), features, 4096, 1).unwrap()
+	}
+
+	fn dump(tile: &VectorTile) -> Vec<String> {
+		tile
+			.layers
+			.iter()
+			.flat_map(|layer| {
+				layer.to_features().unwrap().into_iter().map(|feature| {
+					let properties = feature
+						.properties
+						.iter()
+						.map(|(k, v)| format!("{k}={v}"))
+						.collect::<Vec<_>>()
+						.join(",");
+					format!("{}: {properties}", layer.name)
+				})
+			})
+			.collect()
+	}
+
+	fn point() -> Geometry {
+		Geometry::new_multi_point(vec![[1.0, 2.0]])
+	}
+
+	fn line() -> Geometry {
+		Geometry::new_multi_line_string(vec![vec![[1.0, 2.0], [3.0, 4.0]]])
+	}
+
+	const MAPPING: &str = r#"{
+		"drop_unmapped": true,
+		"layers": [
+			{ "layer": "water", "to": "ocean", "where": { "class": "ocean" }, "keep": [] },
+			{ "layer": "water", "to": "water_polygons", "rename": { "class": "kind" }, "values": { "kind": { "lake": "water", "pond": null } } },
+			{ "layer": "roads", "to": "streets", "geometry": "line", "flags": { "brunnel": ["bridge", "tunnel"] }, "set": { "layer": 1 } },
+			{ "layer": "rivers", "to": "water_polygons", "keep": ["kind"] }
+		]
+	}"#;
+
+	#[test]
+	fn test_run() -> Result<()> {
+		let runner = Runner::from_json(MAPPING)?;
+		let tile = VectorTile::new(vec![
+			create_layer(
+				"water",
+				&[
+					(
+						point(),
+						&[("class", GeoValue::from("ocean")), ("id", GeoValue::from(1))],
+					),
+					(point(), &[("class", GeoValue::from("lake"))]),
+					(point(), &[("class", GeoValue::from("pond"))]),
+				],
+			),
+			create_layer(
+				"roads",
+				&[
+					(line(), &[("brunnel", GeoValue::from("bridge"))]),
+					(line(), &[("brunnel", GeoValue::from("ford"))]),
+					(point(), &[("brunnel", GeoValue::from("bridge"))]),
+				],
+			),
+			create_layer(
+				"rivers",
+				&[(point(), &[("kind", GeoValue::from("river")), ("x", GeoValue::from(2))])],
+			),
+			create_layer("poi", &[(point(), &[])]),
+		]);
+
+		let tile = runner.run(tile)?.unwrap();
+		assert_eq!(
+			dump(&tile),
+			[
+				"ocean: ",
+				"water_polygons: kind=water",
+				"water_polygons: ",
+				"water_polygons: kind=river",
+				"streets: bridge=true,layer=1",
+				"streets: layer=1",
+			]
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_keep_unmapped() -> Result<()> {
+		let runner =
+			Runner::from_json(r#"{"layers":[{"layer":"water","where":{"class":["lake","river"]},"to":"lakes"}]}"#)?;
+		let tile = VectorTile::new(vec![
+			create_layer(
+				"water",
+				&[
+					(point(), &[("class", GeoValue::from("lake"))]),
+					(point(), &[("class", GeoValue::from("ocean"))]),
+				],
+			),
+			create_layer("poi", &[(point(), &[("name", GeoValue::from("x"))])]),
+		]);
+
+		let tile = runner.run(tile)?.unwrap();
+		assert_eq!(dump(&tile), ["lakes: class=lake", "water: class=ocean", "poi: name=x"]);
+		Ok(())
+	}
+
+	#[test]
+	fn test_update_tilejson() -> Result<()> {
+		let runner = Runner::from_json(MAPPING)?;
+		let mut tilejson = TileJSON::default();
+		for (id, fields) in [
+			("water", vec!["class", "id"]),
+			("roads", vec!["brunnel", "name"]),
+			("poi", vec!["name"]),
+		] {
+			tilejson.vector_layers.0.insert(
+				id.to_string(),
+				VectorLayer {
+					fields: fields.iter().map(|f| (f.to_string(), "String".to_string())).collect(),
+					description: None,
+					minzoom: None,
+					maxzoom: None,
+				},
+			);
+		}
+
+		runner.update_tilejson(&mut tilejson);
+		let layers = tilejson
+			.vector_layers
+			.iter()
+			.map(|(id, layer)| format!("{id}: {}", layer.fields.keys().cloned().collect::<Vec<_>>().join(",")))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			layers,
+			[
+				"ocean: ",
+				"streets: bridge,layer,name,tunnel",
+				"water_polygons: id,kind"
+			]
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_invalid_mappings() {
+		for json in [
+			"{}",
+			r#"{"layers":[]}"#,
+			r#"{"layers":[{"to":"a"}]}"#,
+			r#"{"layers":[{"layer":"a","geometry":"circle"}]}"#,
+			r#"{"layers":[{"layer":"a","set":{"b":null}}]}"#,
+			r#"{"layers":[{"layer":"a","where":{"b":{}}}]}"#,
+			r#"{"drop_unmapped":1,"layers":[{"layer":"a"}]}"#,
+		] {
+			assert!(Runner::from_json(json).is_err(), "mapping '{json}' should be rejected");
+		}
+	}
+
+	#[test]
+	fn test_builtin_schemas() -> Result<()> {
+		for (name, json) in SCHEMAS {
+			Runner::from_json(json).with_context(|| format!("schema '{name}'"))?;
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		assert!(
+			factory
+				.operation_from_vpl(r#"from_debug | vector_migrate_schema schema="unknown""#)
+				.await
+				.is_err()
+		);
+
+		let operation = factory
+			.operation_from_vpl(r#"from_debug | vector_migrate_schema schema="openmaptiles_to_shortbread""#)
+			.await?;
+		let mut stream = operation.get_stream(TileBBox::new_full(0)?).await?;
+		let tile = stream.next().await.unwrap().1.into_vector()?;
+		assert!(tile.layers.is_empty());
+		assert!(operation.tilejson().vector_layers.0.is_empty());
+		Ok(())
+	}
+}
@@ -0,0 +1,126 @@
+use crate::{
+	PipelineFactory,
+	operations::vector::traits::{RunnerTrait, build_transform},
+	traits::{OperationFactoryTrait, OperationTrait, TransformOperationFactoryTrait},
+	vpl::VPLNode,
+};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use versatiles_core::TileJSON;
+use versatiles_derive::context;
+use versatiles_geometry::vector_tile::VectorTile;
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Reduces the coordinate precision of vector tiles by lowering the extent and/or snapping coordinates to a grid.
+/// Duplicated points are removed and geometries that collapse are dropped, which can significantly reduce the size of low-zoom tiles.
+struct Args {
+	/// New extent of all layers, e.g. extent=1024. Defaults to the current extent of each layer.
+	#[vpl(min = 1)]
+	extent: Option<u32>,
+	/// Snaps coordinates to multiples of this value (in units of the new extent), e.g. grid=4. Defaults to 1.
+	#[vpl(min = 1)]
+	grid: Option<u32>,
+}
+
+#[derive(Debug)]
+struct Runner {
+	extent: Option<u32>,
+	grid: u32,
+}
+
+impl Runner {
+	#[context("Failed to parse precision arguments")]
+	pub fn from_args(args: Args) -> Result<Self> {
+		ensure!(
+			args.extent.is_some() || args.grid.is_some(),
+			"either 'extent' or 'grid' must be set"
+		);
+		Ok(Self {
+			extent: args.extent,
+			grid: args.grid.unwrap_or(1),
+		})
+	}
+}
+
+impl RunnerTrait for Runner {
+	#[context("Failed to run vector reduce precision")]
+	fn run(&self, mut tile: VectorTile) -> Result<Option<VectorTile>> {
+		for layer in &mut tile.layers {
+			layer.quantize(self.extent.unwrap_or(layer.extent), self.grid)?;
+		}
+		Ok(Some(tile))
+	}
+
+	fn update_tilejson(&self, _tilejson: &mut TileJSON) {}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"vector_reduce_precision"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		_factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		build_transform::<Runner>(source, Runner::from_args(args)?).await
+	}
+}
+
+// ───────────────────────── TESTS ─────────────────────────
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use versatiles_core::{TileBBox, TileCompression};
+
+	#[test]
+	fn test_invalid_args() -> Result<()> {
+		assert!(
+			Runner::from_args(Args {
+				extent: None,
+				grid: None
+			})
+			.is_err()
+		);
+		for vpl in ["vector_reduce_precision extent=0", "vector_reduce_precision grid=0"] {
+			assert!(Args::from_vpl_node(&VPLNode::try_from_str(vpl)?).is_err());
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		async fn tile_size(vpl: &str) -> Result<(u64, Vec<u32>)> {
+			let factory = PipelineFactory::new_dummy();
+			let operation = factory.operation_from_vpl(vpl).await?;
+			let mut stream = operation.get_stream(TileBBox::new_full(0)?).await?;
+			let tile = stream.next().await.unwrap().1;
+			let size = tile.clone().into_blob(TileCompression::Uncompressed)?.len();
+			let extents = tile.into_vector()?.layers.iter().map(|l| l.extent).collect();
+			Ok((size, extents))
+		}
+
+		let (size0, extents0) = tile_size("from_debug").await?;
+		assert!(extents0.iter().all(|e| *e == 4096));
+
+		let (size1, extents1) = tile_size("from_debug | vector_reduce_precision extent=256 grid=2").await?;
+		assert!(extents1.iter().all(|e| *e == 256));
+		assert!(size1 < size0, "{size1} < {size0}");
+		Ok(())
+	}
+}
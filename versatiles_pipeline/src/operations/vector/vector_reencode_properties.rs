@@ -0,0 +1,186 @@
+use crate::{
+	PipelineFactory,
+	operations::vector::traits::{RunnerTrait, build_transform},
+	traits::{OperationFactoryTrait, OperationTrait, TransformOperationFactoryTrait},
+	vpl::VPLNode,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use versatiles_core::TileJSON;
+use versatiles_derive::context;
+use versatiles_geometry::{
+	geo::{GeoProperties, GeoValue},
+	vector_tile::VectorTile,
+};
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Rebuilds the key/value tables of all vector tile layers.
+/// Duplicated keys and values are merged, unused ones are removed and the tables are sorted, which often reduces the tile size.
+struct Args {
+	/// If set, string values that contain a number (e.g. "42" or "-1.5") are converted to numeric values.
+	parse_numbers: Option<bool>,
+}
+
+#[derive(Debug)]
+struct Runner {
+	parse_numbers: bool,
+}
+
+impl Runner {
+	pub fn from_args(args: Args) -> Self {
+		Self {
+			parse_numbers: args.parse_numbers.unwrap_or(false),
+		}
+	}
+
+	/// Converts numeric-looking strings, but only if the number formats back to the identical string,
+	/// so values like "007" or "1.50" are kept as they are.
+	fn parse_numbers(mut properties: GeoProperties) -> GeoProperties {
+		for value in properties.0.values_mut() {
+			if let GeoValue::String(text) = value {
+				let parsed = GeoValue::parse_str(text);
+				if matches!(parsed, GeoValue::Double(_) | GeoValue::Int(_) | GeoValue::UInt(_))
+					&& parsed.to_string() == *text
+				{
+					*value = parsed;
+				}
+			}
+		}
+		properties
+	}
+}
+
+impl RunnerTrait for Runner {
+	#[context("Failed to run vector reencode properties")]
+	fn run(&self, mut tile: VectorTile) -> Result<Option<VectorTile>> {
+		for layer in &mut tile.layers {
+			if self.parse_numbers {
+				layer.map_properties(Runner::parse_numbers)?;
+			} else {
+				layer.map_properties(|properties| properties)?;
+			}
+		}
+
+		Ok(Some(tile))
+	}
+
+	fn update_tilejson(&self, _tilejson: &mut TileJSON) {}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"vector_reencode_properties"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		_factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		build_transform::<Runner>(source, Runner::from_args(args)).await
+	}
+}
+
+// ───────────────────────── TESTS ─────────────────────────
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	use versatiles_core::TileBBox;
+	use versatiles_geometry::{geo::*, vector_tile::VectorTileLayer};
+
+	fn create_tile() -> VectorTile {
+		let features = ["12", "007", "1.50", "-3", "abc", "2.5"]
+			.iter()
+			.map(|v| {
+				let mut feature = GeoFeature::new(Geometry::new_example());
+				feature.properties = GeoProperties::from(vec![("value", GeoValue::from(*v))]);
+				feature
+			})
+			.collect();
+		let mut layer = VectorTileLayer::from_features("test".to_string(), features, 4096, 1).unwrap();
+
+		// add unused keys and values
+		layer.property_manager.add_key("unused".to_string());
+		layer.property_manager.add_val(GeoValue::from("unused"));
+		VectorTile::new(vec![layer])
+	}
+
+	fn values(tile: &VectorTile) -> Vec<String> {
+		tile.layers[0]
+			.to_features()
+			.unwrap()
+			.iter()
+			.map(|f| format!("{:?}", f.properties.get("value").unwrap()))
+			.collect()
+	}
+
+	#[test]
+	fn test_removes_unused_entries() {
+		let runner = Runner::from_args(Args { parse_numbers: None });
+		let tile = create_tile();
+		assert_eq!(tile.layers[0].property_manager.key.list.len(), 2);
+		assert_eq!(tile.layers[0].property_manager.val.list.len(), 7);
+
+		let tile = runner.run(tile).unwrap().unwrap();
+		assert_eq!(tile.layers[0].property_manager.key.list, ["value"]);
+		assert_eq!(tile.layers[0].property_manager.val.list.len(), 6);
+		assert_eq!(
+			values(&tile),
+			[
+				"String(\"12\")",
+				"String(\"007\")",
+				"String(\"1.50\")",
+				"String(\"-3\")",
+				"String(\"abc\")",
+				"String(\"2.5\")"
+			]
+		);
+	}
+
+	#[test]
+	fn test_parse_numbers() {
+		let runner = Runner::from_args(Args {
+			parse_numbers: Some(true),
+		});
+		let tile = runner.run(create_tile()).unwrap().unwrap();
+		assert_eq!(
+			values(&tile),
+			[
+				"UInt(12)",
+				"String(\"007\")",
+				"String(\"1.50\")",
+				"Int(-3)",
+				"String(\"abc\")",
+				"Double(2.5)"
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let operation = factory
+			.operation_from_vpl("from_debug | vector_reencode_properties parse_numbers=true")
+			.await?;
+
+		let mut stream = operation.get_stream(TileBBox::new_full(0)?).await?;
+		let tile = stream.next().await.unwrap().1.into_vector()?;
+		assert_eq!(tile.layers.len(), 4);
+		Ok(())
+	}
+}
@@ -0,0 +1,195 @@
+use crate::{
+	PipelineFactory,
+	operations::vector::traits::{RunnerTrait, build_transform},
+	traits::{OperationFactoryTrait, OperationTrait, TransformOperationFactoryTrait},
+	vpl::VPLNode,
+};
+use anyhow::{Result, anyhow, ensure};
+use async_trait::async_trait;
+use std::collections::{HashMap, btree_map::Entry};
+use versatiles_core::TileJSON;
+use versatiles_derive::context;
+use versatiles_geometry::vector_tile::{VectorTile, VectorTileLayer};
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Renames vector tile layers. Layers that are renamed to the same name are merged into one layer.
+struct Args {
+	/// Comma-separated list of `old_name:new_name` pairs, e.g.: map="landuse:landcover,water_polygons:water".
+	map: String,
+}
+
+#[derive(Debug)]
+struct Runner {
+	names: HashMap<String, String>,
+}
+
+impl Runner {
+	#[context("Failed to parse layer rename map")]
+	pub fn from_args(args: Args) -> Result<Self> {
+		let names = args
+			.map
+			.split(',')
+			.filter(|entry| !entry.trim().is_empty())
+			.map(|entry| {
+				let (old, new) = entry
+					.split_once(':')
+					.ok_or_else(|| anyhow!("entry '{entry}' must have the format 'old_name:new_name'"))?;
+				let (old, new) = (old.trim(), new.trim());
+				ensure!(
+					!old.is_empty() && !new.is_empty(),
+					"entry '{entry}' must have the format 'old_name:new_name'"
+				);
+				Ok((old.to_string(), new.to_string()))
+			})
+			.collect::<Result<HashMap<String, String>>>()?;
+
+		Ok(Self { names })
+	}
+
+	fn new_name<'a>(&'a self, name: &'a str) -> &'a str {
+		self.names.get(name).map_or(name, String::as_str)
+	}
+}
+
+impl RunnerTrait for Runner {
+	#[context("Failed to run vector rename layers")]
+	fn run(&self, tile: VectorTile) -> Result<Option<VectorTile>> {
+		let mut layers: Vec<VectorTileLayer> = Vec::with_capacity(tile.layers.len());
+
+		for mut layer in tile.layers {
+			let name = self.new_name(&layer.name).to_string();
+			if let Some(target) = layers.iter_mut().find(|l| l.name == name) {
+				ensure!(
+					target.extent == layer.extent,
+					"can not merge layer '{}' into '{name}', because their extents differ",
+					layer.name
+				);
+				target.add_from_layer(layer)?;
+			} else {
+				layer.name = name;
+				layers.push(layer);
+			}
+		}
+
+		Ok(Some(VectorTile::new(layers)))
+	}
+
+	fn update_tilejson(&self, tilejson: &mut TileJSON) {
+		let old_layers = std::mem::take(&mut tilejson.vector_layers.0);
+		for (id, layer) in old_layers {
+			match tilejson.vector_layers.0.entry(self.new_name(&id).to_string()) {
+				Entry::Vacant(entry) => {
+					entry.insert(layer);
+				}
+				Entry::Occupied(mut entry) => entry.get_mut().merge(&layer),
+			}
+		}
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"vector_rename_layers"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		_factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		build_transform::<Runner>(source, Runner::from_args(args)?).await
+	}
+}
+
+// ───────────────────────── TESTS ─────────────────────────
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	use versatiles_core::TileBBox;
+	use versatiles_geometry::geo::*;
+
+	fn create_layer(name: &str, id: &str) -> VectorTileLayer {
+		let mut feature = GeoFeature::new(Geometry::new_example());
+		feature.properties = GeoProperties::from(vec![("id", GeoValue::from(id))]);
+		VectorTileLayer::from_features(name.to_string(), vec![feature], 4096, 1).unwrap()
+	}
+
+	#[test]
+	fn test_runner_rename_and_merge() {
+		let runner = Runner::from_args(Args {
+			map: "a:x, b:x,c:y".to_string(),
+		})
+		.unwrap();
+
+		let tile = VectorTile::new(vec![
+			create_layer("a", "1"),
+			create_layer("b", "2"),
+			create_layer("c", "3"),
+			create_layer("d", "4"),
+		]);
+		let tile = runner.run(tile).unwrap().unwrap();
+
+		let names = tile.layers.iter().map(|l| l.name.as_str()).collect::<Vec<_>>();
+		assert_eq!(names, ["x", "y", "d"]);
+
+		let ids = tile.layers[0]
+			.to_features()
+			.unwrap()
+			.iter()
+			.map(|f| f.properties.get("id").unwrap().to_string())
+			.collect::<Vec<_>>();
+		assert_eq!(ids, ["1", "2"]);
+	}
+
+	#[test]
+	fn test_runner_rejects_different_extents() {
+		let runner = Runner::from_args(Args { map: "a:b".to_string() }).unwrap();
+
+		let mut layer = create_layer("a", "1");
+		layer.extent = 512;
+		let tile = VectorTile::new(vec![layer, create_layer("b", "2")]);
+		assert!(runner.run(tile).is_err());
+	}
+
+	#[test]
+	fn test_invalid_map() {
+		for map in ["a", "a:", ":b", "a:b,c"] {
+			assert!(
+				Runner::from_args(Args { map: map.to_string() }).is_err(),
+				"map '{map}' should be rejected"
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_pipeline() -> Result<()> {
+		let factory = PipelineFactory::new_dummy();
+		let operation = factory
+			.operation_from_vpl(r#"from_debug | vector_rename_layers map="debug_x:debug,debug_y:debug,background:bg""#)
+			.await?;
+
+		let mut stream = operation.get_stream(TileBBox::new_full(0)?).await?;
+		let tile = stream.next().await.unwrap().1.into_vector()?;
+		let names = tile.layers.iter().map(|l| l.name.as_str()).collect::<Vec<_>>();
+		assert_eq!(names, ["bg", "debug_z", "debug"]);
+
+		let layer_ids = operation.tilejson().vector_layers.layer_ids();
+		assert_eq!(layer_ids, ["bg", "debug", "debug_z"]);
+		Ok(())
+	}
+}
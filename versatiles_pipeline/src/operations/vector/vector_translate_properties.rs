@@ -0,0 +1,221 @@
+use crate::{
+	PipelineFactory,
+	operations::vector::traits::{RunnerTrait, build_transform},
+	traits::{OperationFactoryTrait, OperationTrait, TransformOperationFactoryTrait},
+	vpl::VPLNode,
+};
+use anyhow::{Result, ensure};
+use async_trait::async_trait;
+use versatiles_core::TileJSON;
+use versatiles_derive::context;
+use versatiles_geometry::{geo::GeoProperties, vector_tile::VectorTile};
+
+#[derive(versatiles_derive::VPLDecode, Clone, Debug)]
+/// Selects localized labels: sets a property (e.g. `name`) to the first available language variant (e.g. `name:de`, `name:en`).
+/// If no variant is available, the property is left unchanged.
+struct Args {
+	/// Comma-separated list of language codes in order of priority, e.g.: languages="de,en".
+	languages: String,
+	/// Name of the property that is translated. Defaults to "name".
+	key: Option<String>,
+	/// Separator between property name and language code. Defaults to ":", as in "name:de". Use "_" for "name_de".
+	separator: Option<String>,
+	/// If set, all language variants (e.g. "name:fr") are removed afterwards to shrink the tiles.
+	drop_variants: Option<bool>,
+}
+
+#[derive(Debug)]
+struct Runner {
+	key: String,
+	variant_prefix: String,
+	variant_keys: Vec<String>,
+	drop_variants: bool,
+}
+
+impl Runner {
+	#[context("Failed to parse language list")]
+	pub fn from_args(args: Args) -> Result<Self> {
+		let key = args.key.unwrap_or_else(|| String::from("name"));
+		let variant_prefix = format!("{key}{}", args.separator.as_deref().unwrap_or(":"));
+		let variant_keys = args
+			.languages
+			.split(',')
+			.map(str::trim)
+			.filter(|language| !language.is_empty())
+			.map(|language| format!("{variant_prefix}{language}"))
+			.collect::<Vec<_>>();
+		ensure!(
+			!variant_keys.is_empty(),
+			"languages must contain at least one language code"
+		);
+
+		Ok(Self {
+			key,
+			variant_prefix,
+			variant_keys,
+			drop_variants: args.drop_variants.unwrap_or(false),
+		})
+	}
+
+	fn is_variant(&self, key: &str) -> bool {
+		key.len() > self.variant_prefix.len() && key.starts_with(&self.variant_prefix)
+	}
+
+	fn translate(&self, mut properties: GeoProperties) -> GeoProperties {
+		if let Some(value) = self.variant_keys.iter().find_map(|key| properties.get(key)) {
+			properties.insert(self.key.clone(), value.clone());
+		}
+		if self.drop_variants {
+			properties.retain(|key, _| !self.is_variant(key));
+		}
+		properties
+	}
+}
+
+impl RunnerTrait for Runner {
+	#[context("Failed to run vector translate properties")]
+	fn run(&self, mut tile: VectorTile) -> Result<Option<VectorTile>> {
+		for layer in tile.layers.iter_mut() {
+			layer.map_properties(|properties| self.translate(properties))?;
+		}
+		Ok(Some(tile))
+	}
+
+	fn update_tilejson(&self, tilejson: &mut TileJSON) {
+		if self.drop_variants {
+			tilejson.vector_layers.iter_mut().for_each(|(_, layer)| {
+				layer.fields.retain(|key, _| !self.is_variant(key));
+			});
+		}
+	}
+}
+
+pub struct Factory {}
+
+impl OperationFactoryTrait for Factory {
+	fn get_docs(&self) -> String {
+		Args::get_docs()
+	}
+	fn get_schema(&self) -> String {
+		Args::get_schema()
+	}
+	fn get_tag_name(&self) -> &str {
+		"vector_translate_properties"
+	}
+}
+
+#[async_trait]
+impl TransformOperationFactoryTrait for Factory {
+	async fn build<'a>(
+		&self,
+		vpl_node: VPLNode,
+		source: Box<dyn OperationTrait>,
+		_factory: &'a PipelineFactory,
+	) -> Result<Box<dyn OperationTrait>> {
+		let args = Args::from_vpl_node(&vpl_node)?;
+
+		build_transform::<Runner>(source, Runner::from_args(args)?).await
+	}
+}
+
+// ───────────────────────── TESTS ─────────────────────────
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	use versatiles_core::VectorLayer;
+	use versatiles_geometry::{geo::*, vector_tile::VectorTileLayer};
+
+	fn runner(languages: &str, separator: Option<&str>, drop_variants: bool) -> Runner {
+		Runner::from_args(Args {
+			languages: languages.to_string(),
+			key: None,
+			separator: separator.map(String::from),
+			drop_variants: Some(drop_variants),
+		})
+		.unwrap()
+	}
+
+	fn properties(entries: &[(&str, &str)]) -> GeoProperties {
+		GeoProperties::from(entries.to_vec())
+	}
+
+	fn names(properties: &GeoProperties) -> String {
+		properties
+			.iter()
+			.map(|(k, v)| format!("{k}={v}"))
+			.collect::<Vec<_>>()
+			.join(",")
+	}
+
+	#[test]
+	fn test_translate() {
+		let input = properties(&[
+			("name", "München"),
+			("name:en", "Munich"),
+			("name:fr", "Munich"),
+			("kind", "city"),
+		]);
+
+		let result = runner("it,en,de", None, false).translate(input.clone());
+		assert_eq!(names(&result), "kind=city,name=Munich,name:en=Munich,name:fr=Munich");
+
+		let result = runner("de,en", None, true).translate(input.clone());
+		assert_eq!(names(&result), "kind=city,name=Munich");
+
+		let result = runner("it", None, false).translate(input);
+		assert_eq!(names(&result), "kind=city,name=München,name:en=Munich,name:fr=Munich");
+	}
+
+	#[test]
+	fn test_translate_with_separator() {
+		let input = properties(&[("name", "Köln"), ("name_en", "Cologne"), ("name_", "x")]);
+		let result = runner("en", Some("_"), true).translate(input);
+		assert_eq!(names(&result), "name=Cologne,name_=x");
+	}
+
+	#[test]
+	fn test_invalid_languages() {
+		for languages in ["", " , "] {
+			assert!(
+				Runner::from_args(Args {
+					languages: languages.to_string(),
+					key: None,
+					separator: None,
+					drop_variants: None,
+				})
+				.is_err()
+			);
+		}
+	}
+
+	#[test]
+	fn test_run_and_tilejson() -> Result<()> {
+		let mut feature = GeoFeature::new(Geometry::new_example());
+		feature.properties = properties(&[("name", "Wien"), ("name:en", "Vienna")]);
+		let layer = VectorTileLayer::from_features("place".to_string(), vec![feature], 4096, 1)?;
+
+		let runner = runner("en", None, true);
+		let tile = runner.run(VectorTile::new(vec![layer]))?.unwrap();
+		let features = tile.layers[0].to_features()?;
+		assert_eq!(names(&features[0].properties), "name=Vienna");
+
+		let mut tilejson = TileJSON::default();
+		tilejson.vector_layers.0.insert(
+			"place".to_string(),
+			VectorLayer {
+				fields: [("name", "String"), ("name:en", "String")]
+					.iter()
+					.map(|(k, v)| (k.to_string(), v.to_string()))
+					.collect(),
+				description: None,
+				minzoom: None,
+				maxzoom: None,
+			},
+		);
+		runner.update_tilejson(&mut tilejson);
+		let fields = tilejson.vector_layers.0["place"].fields.keys().collect::<Vec<_>>();
+		assert_eq!(fields, ["name"]);
+		Ok(())
+	}
+}
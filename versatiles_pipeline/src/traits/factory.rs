@@ -5,6 +5,7 @@ use async_trait::async_trait;
 pub trait OperationFactoryTrait: Send + Sync {
 	fn get_tag_name(&self) -> &str;
 	fn get_docs(&self) -> String;
+	fn get_schema(&self) -> String;
 }
 
 #[async_trait]
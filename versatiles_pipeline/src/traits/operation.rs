@@ -9,7 +9,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use std::fmt::Debug;
 use versatiles_container::Tile;
-use versatiles_core::{TileBBox, TileJSON, TileStream, TilesReaderParameters, Traversal};
+use versatiles_core::{CancellationToken, TileBBox, TileJSON, TileStream, TilesReaderParameters, Traversal};
 
 /// Core abstraction for all tile-producing operations in a VersaTiles pipeline.
 ///
@@ -42,4 +42,11 @@ pub trait OperationTrait: Debug + Send + Sync + Unpin {
 	/// Implementations should emit tiles matching the requested bbox, possibly applying
 	/// transformations, filtering, or aggregation. Errors indicate I/O or processing failures.
 	async fn get_stream(&self, bbox: TileBBox) -> Result<TileStream<Tile>>;
+
+	/// Returns the same stream as [`OperationTrait::get_stream`], but ends it early once `token` is cancelled.
+	///
+	/// Streams are lazy, so ending the outer stream also stops pulling tiles from all upstream operations.
+	async fn get_stream_cancellable(&self, bbox: TileBBox, token: CancellationToken) -> Result<TileStream<Tile>> {
+		Ok(self.get_stream(bbox).await?.take_until_cancelled(token))
+	}
 }
@@ -8,9 +8,9 @@ use nom::{
 	combinator::{all_consuming, cut, opt, recognize, value},
 	error::context,
 	multi::{many0, many1, separated_list0, separated_list1},
-	sequence::{delimited, pair, preceded, separated_pair},
+	sequence::{delimited, pair, preceded, separated_pair, terminated},
 };
-use nom_language::error::{VerboseError, convert_error};
+use nom_language::error::{VerboseError, VerboseErrorKind};
 use std::collections::BTreeMap;
 use versatiles_derive::context;
 
@@ -69,24 +69,39 @@ fn parse_double_quoted_string(input: &str) -> IResult<&str, String, VerboseError
 	.parse(input)
 }
 
+/// Single quoted strings are taken literally, except for `\'` and `\\`, which are an escaped quote and
+/// an escaped backslash. A value ending with a backslash must therefore be written as `'C:\tiles\\'`.
 fn parse_single_quoted_string(input: &str) -> IResult<&str, String, VerboseError<&str>> {
 	context(
 		"parsing single quoted string",
-		delimited(char('\''), is_not("'").map(|s: &str| s.to_string()), char('\'')),
+		delimited(
+			char('\''),
+			many0(alt((
+				value("'", tag("\\'")),
+				value("\\", tag("\\\\")),
+				is_not("'\\"),
+				tag("\\"),
+			)))
+			.map(|parts| parts.concat()),
+			char('\''),
+		),
 	)
 	.parse(input)
 }
 
-fn parse_array(input: &str) -> IResult<&str, Vec<String>, VerboseError<&str>> {
-	context(
-		"parsing array",
-		delimited(
-			(char('['), ws0),
-			separated_list0((ws0, char(','), ws0), parse_string),
-			(ws0, char(']')),
-		),
+/// A comma separated list of `element`s in brackets. A trailing comma is allowed.
+fn bracketed_list<'a, O>(
+	element: impl Parser<&'a str, Output = O, Error = VerboseError<&'a str>>,
+) -> impl Parser<&'a str, Output = Vec<O>, Error = VerboseError<&'a str>> {
+	delimited(
+		(char('['), ws0),
+		terminated(separated_list0((ws0, char(','), ws0), element), opt((ws0, char(',')))),
+		(ws0, cut(char(']'))),
 	)
-	.parse(input)
+}
+
+fn parse_array(input: &str) -> IResult<&str, Vec<String>, VerboseError<&str>> {
+	context("parsing array", bracketed_list(parse_string)).parse(input)
 }
 
 fn parse_string(input: &str) -> IResult<&str, String, VerboseError<&str>> {
@@ -99,11 +114,28 @@ fn parse_string(input: &str) -> IResult<&str, String, VerboseError<&str>> {
 	}
 }
 
-fn parse_value(input: &str) -> IResult<&str, Vec<String>, VerboseError<&str>> {
-	if input.starts_with('[') {
-		parse_array.parse(input)
+/// The value of a property: one or more strings, or a nested pipeline in parentheses.
+#[derive(Debug, PartialEq)]
+enum PropertyValue {
+	Strings(Vec<String>),
+	Pipeline(VPLPipeline),
+}
+
+fn parse_named_source(input: &str) -> IResult<&str, VPLPipeline, VerboseError<&str>> {
+	context(
+		"parsing named source",
+		delimited((char('('), ws0), parse_pipeline, (ws0, cut(char(')')))),
+	)
+	.parse(input)
+}
+
+fn parse_value(input: &str) -> IResult<&str, PropertyValue, VerboseError<&str>> {
+	if input.starts_with('(') {
+		parse_named_source.map(PropertyValue::Pipeline).parse(input)
+	} else if input.starts_with('[') {
+		parse_array.map(PropertyValue::Strings).parse(input)
 	} else {
-		parse_string.map(|a| vec![a]).parse(input)
+		parse_string.map(|a| PropertyValue::Strings(vec![a])).parse(input)
 	}
 }
 
@@ -111,7 +143,7 @@ fn parse_identifier(input: &str) -> IResult<&str, String, VerboseError<&str>> {
 	context("parsing node identifier", parse_bare_identifier).parse(input)
 }
 
-fn parse_property(input: &str) -> IResult<&str, (String, Vec<String>), VerboseError<&str>> {
+fn parse_property(input: &str) -> IResult<&str, (String, PropertyValue), VerboseError<&str>> {
 	context(
 		"parsing property",
 		separated_pair(parse_identifier, cut((ws0, char('='), ws0)), cut(parse_value)),
@@ -122,12 +154,7 @@ fn parse_property(input: &str) -> IResult<&str, (String, Vec<String>), VerboseEr
 fn parse_sources(input: &str) -> IResult<&str, Vec<VPLPipeline>, VerboseError<&str>> {
 	context(
 		"parsing sources",
-		opt(delimited(
-			(char('['), ws0),
-			separated_list0((ws0, char(','), ws0), parse_pipeline),
-			(ws0, cut(char(']'))),
-		))
-		.map(|r| r.unwrap_or_default()),
+		opt(bracketed_list(parse_pipeline)).map(|r| r.unwrap_or_default()),
 	)
 	.parse(input)
 }
@@ -137,17 +164,31 @@ fn parse_node<'a>(input: &'a str) -> IResult<&'a str, VPLNode, VerboseError<&'a
 		let (input, _) = ws0(input)?;
 		let (input, name) = parse_identifier(input)?;
 		let (input, _) = ws0(input)?;
-		let (input, property_list) = separated_list0(ws1, parse_property).parse(input)?;
+		// keep the position of each property for error messages
+		let (input, property_list) =
+			separated_list0(ws1, |i| parse_property(i).map(|(rest, property)| (rest, (i, property)))).parse(input)?;
 		let (input, _) = ws0(input)?;
 		let (input, children) = parse_sources(input)?;
 		let (input, _) = ws0(input)?;
 
 		let mut properties = BTreeMap::new();
-		for (key, mut values) in property_list {
-			properties
-				.entry(key)
-				.and_modify(|list: &mut Vec<String>| list.append(&mut values))
-				.or_insert(values);
+		let mut named_sources = BTreeMap::new();
+		for (position, (key, value)) in property_list {
+			match value {
+				PropertyValue::Strings(mut values) => {
+					properties
+						.entry(key)
+						.and_modify(|list: &mut Vec<String>| list.append(&mut values))
+						.or_insert(values);
+				}
+				PropertyValue::Pipeline(pipeline) => {
+					if named_sources.insert(key, pipeline).is_some() {
+						return Err(nom::Err::Failure(VerboseError {
+							errors: vec![(position, VerboseErrorKind::Context("duplicate named source"))],
+						}));
+					}
+				}
+			}
 		}
 
 		Ok((
@@ -156,6 +197,7 @@ fn parse_node<'a>(input: &'a str) -> IResult<&'a str, VPLNode, VerboseError<&'a
 				name,
 				properties,
 				sources: children,
+				named_sources,
 			},
 		))
 	})
@@ -174,6 +216,62 @@ fn parse_pipeline(input: &str) -> IResult<&str, VPLPipeline, VerboseError<&str>>
 	.parse(input)
 }
 
+/// Returns the line and column (both starting at 1) of `rest` in `input` and the text of that line.
+fn locate<'a>(input: &'a str, rest: &str) -> (usize, usize, &'a str) {
+	let offset = input.len() - rest.len();
+	let line_start = input[..offset].rfind('\n').map_or(0, |pos| pos + 1);
+	let line_end = input[offset..].find('\n').map_or(input.len(), |pos| offset + pos);
+	let line_number = input[..offset].matches('\n').count() + 1;
+	let column = input[line_start..offset].chars().count() + 1;
+	(line_number, column, input[line_start..line_end].trim_end())
+}
+
+/// Formats a parser error with the line and column of the problem and the contexts it occurred in, e.g.:
+///
+/// ```text
+/// line 1, column 12: expected '=', found 'k'
+/// node child key=value
+///            ^
+/// while parsing property at line 1, column 6
+/// while parsing node at line 1, column 1
+/// while parsing pipeline at line 1, column 1
+/// ```
+fn format_error(input: &str, error: &VerboseError<&str>) -> String {
+	let Some((rest, kind)) = error.errors.first() else {
+		return String::from("unknown VPL syntax error");
+	};
+	let (line_number, column, line) = locate(input, rest);
+	let found = match rest.chars().next() {
+		Some(c) => format!("found '{c}'"),
+		None => String::from("found end of input"),
+	};
+	let message = match kind {
+		VerboseErrorKind::Char(c) => format!("expected '{c}', {found}"),
+		VerboseErrorKind::Context(context) => (*context).to_string(),
+		VerboseErrorKind::Nom(nom::error::ErrorKind::Eof) => format!("expected end of pipeline, {found}"),
+		VerboseErrorKind::Nom(_) => format!("unexpected input, {found}"),
+	};
+	// keep tabs in the indentation, so that the caret is aligned
+	let indent: String = line
+		.chars()
+		.take(column - 1)
+		.map(|c| if c == '\t' { '\t' } else { ' ' })
+		.collect();
+
+	let mut lines = vec![
+		format!("line {line_number}, column {column}: {message}"),
+		line.to_string(),
+		format!("{indent}^"),
+	];
+	for (rest, kind) in &error.errors[1..] {
+		if let VerboseErrorKind::Context(context) = kind {
+			let (line_number, column, _) = locate(input, rest);
+			lines.push(format!("while {context} at line {line_number}, column {column}"));
+		}
+	}
+	lines.join("\n")
+}
+
 #[context("Failed to parse VPL input")]
 pub fn parse_vpl(input: &str) -> Result<VPLPipeline> {
 	let result = all_consuming(parse_pipeline).parse(input);
@@ -185,7 +283,7 @@ pub fn parse_vpl(input: &str) -> Result<VPLPipeline> {
 			);
 			Ok(pipeline)
 		}
-		Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(anyhow::anyhow!(convert_error(input, e))),
+		Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(anyhow::anyhow!(format_error(input, &e))),
 		Err(e) => Err(anyhow::anyhow!("Error parsing VPL: {:?}", e)).context("Failed to parse VPL input"),
 	}
 }
@@ -235,18 +333,28 @@ mod tests {
 	#[rstest]
 	#[case(r#"'foo'"#, "", r#"foo"#)]
 	#[case(r#"'foo bar'"#, "", r#"foo bar"#)]
-	#[case(r#"'foo\'bar'"#, "bar'", r#"foo\"#)]
-	#[case(r#"'foo\\bar'"#, "", r#"foo\\bar"#)]
+	#[case(r#"'foo\'bar'"#, "", r#"foo'bar"#)]
+	#[case(r#"'foo\\bar'"#, "", r#"foo\bar"#)]
+	#[case(r#"'C:\tiles'"#, "", r#"C:\tiles"#)]
+	#[case(r#"'C:\tiles\\' x"#, " x", r#"C:\tiles\"#)]
+	#[case(r#"''"#, "", "")]
 	fn parse_single_quoted_string_ok(#[case] input: &str, #[case] rest: &str, #[case] expected: &str) {
 		assert_eq!(parse_single_quoted_string(input).unwrap(), (rest, expected.to_string()));
 	}
 
+	#[rstest]
+	#[case(r#"'foo"#)]
+	#[case(r#"'C:\tiles\'"#)]
+	fn parse_single_quoted_string_error(#[case] input: &str) {
+		assert!(parse_single_quoted_string(input).is_err());
+	}
+
 	#[test]
 	fn test_parse_prop() {
 		let check = |a, b: &str, c: &str| {
 			assert_eq!(
 				parse_property(a),
-				Ok(("", (b.to_string(), vec![c.to_string()]))),
+				Ok(("", (b.to_string(), PropertyValue::Strings(vec![c.to_string()])))),
 				"error on: {a}"
 			)
 		};
@@ -327,6 +435,50 @@ mod tests {
 		assert_eq!(parse_vpl(INPUT).unwrap(), expected);
 	}
 
+	#[test]
+	fn test_trailing_commas() {
+		assert_eq!(
+			parse_array("[ a, 'b', ]"),
+			Ok(("", vec!["a".to_string(), "b".to_string()]))
+		);
+		assert_eq!(
+			parse_vpl("node [ child1, child2, ]").unwrap(),
+			parse_vpl("node [ child1, child2 ]").unwrap()
+		);
+		assert!(parse_vpl("node [ child1,, ]").is_err());
+	}
+
+	#[test]
+	fn test_comments_and_multiline() {
+		let input = "# read the data
+			from_container # comment after the name
+				filename = 'world\\'s.versatiles' # escaped quote
+			# a comment between operations
+			|
+			filter
+				level_min=3
+				bbox=[
+					-10, # west
+					40,
+					20,  # east
+					60,
+				]
+			";
+		let expected = VPLPipeline::from(vec![
+			VPLNode::from(("from_container", ("filename", "world's.versatiles"))),
+			VPLNode {
+				name: "filter".to_string(),
+				properties: BTreeMap::from([
+					("level_min".to_string(), vec!["3".to_string()]),
+					("bbox".to_string(), ["-10", "40", "20", "60"].map(String::from).to_vec()),
+				]),
+				sources: vec![],
+				named_sources: BTreeMap::new(),
+			},
+		]);
+		assert_eq!(parse_vpl(input).unwrap(), expected);
+	}
+
 	#[test]
 	fn test_parse_unquoted_value() {
 		let inputs = ["value1", "value.1", "value-1", "value_1"];
@@ -338,110 +490,106 @@ mod tests {
 
 	#[test]
 	fn test_parse_value() {
-		assert_eq!(parse_value("value1"), Ok(("", vec!["value1".to_string()])));
-		assert_eq!(parse_value("\"value1\""), Ok(("", vec!["value1".to_string()])));
-		assert_eq!(parse_value("value 1"), Ok((" 1", vec!["value".to_string()])));
-		assert_eq!(parse_value("value\""), Ok(("\"", vec!["value".to_string()])));
+		let strings = |v: &str| PropertyValue::Strings(vec![v.to_string()]);
+		assert_eq!(parse_value("value1"), Ok(("", strings("value1"))));
+		assert_eq!(parse_value("\"value1\""), Ok(("", strings("value1"))));
+		assert_eq!(parse_value("value 1"), Ok((" 1", strings("value"))));
+		assert_eq!(parse_value("value\""), Ok(("\"", strings("value"))));
 		assert!(parse_value("\"value").is_err());
 	}
 
+	#[test]
+	fn test_parse_named_sources() {
+		let input = "node key=value mask=( child1 key=1 | child2 ) other=(child3) [ child4 ]";
+		let mut expected = VPLNode::from((
+			"node",
+			vec![("key", "value")],
+			VPLPipeline::from(VPLNode::from("child4")),
+		));
+		expected.named_sources.insert(
+			"mask".to_string(),
+			VPLPipeline::from(vec![VPLNode::from(("child1", ("key", "1"))), VPLNode::from("child2")]),
+		);
+		expected
+			.named_sources
+			.insert("other".to_string(), VPLPipeline::from(VPLNode::from("child3")));
+		assert_eq!(parse_node(input), Ok(("", expected)));
+
+		assert!(parse_vpl("node mask=( child ").is_err());
+		assert!(parse_vpl("node mask=( ) ").is_err());
+		assert!(parse_vpl("node mask=(a) mask=(b)").is_err());
+	}
+
 	#[rstest]
 	#[case("node [ child key=value ] node", &[
-		"0: at line 1, in Eof:",
+		"line 1, column 26: expected end of pipeline, found 'n'",
 		"node [ child key=value ] node",
 		"                         ^"
 	])]
 	#[case("node child key=value ]", &[
-		"0: at line 1:",
+		"line 1, column 12: expected '=', found 'k'",
 		"node child key=value ]",
 		"           ^",
-		"expected '=', found k",
-		"",
-		"1: at line 1, in parsing property:",
-		"node child key=value ]",
-		"     ^",
-		"",
-		"2: at line 1, in parsing node:",
-		"node child key=value ]",
-		"^",
-		"",
-		"3: at line 1, in parsing pipeline:",
-		"node child key=value ]",
-		"^"
+		"while parsing property at line 1, column 6",
+		"while parsing node at line 1, column 1",
+		"while parsing pipeline at line 1, column 1"
 	])]
 	#[case("node key=\"2.1", &[
-		"0: at line 1:",
+		"line 1, column 14: expected '\"', found end of input",
 		"node key=\"2.1",
 		"             ^",
-		"expected '\"', got end of input",
-		"",
-		"1: at line 1, in parsing double quoted string:",
-		"node key=\"2.1",
-		"         ^",
-		"",
-		"2: at line 1, in parsing property:",
-		"node key=\"2.1",
-		"     ^",
-		"",
-		"3: at line 1, in parsing node:",
-		"node key=\"2.1",
-		"^",
-		"",
-		"4: at line 1, in parsing pipeline:",
-		"node key=\"2.1",
-		"^"
+		"while parsing double quoted string at line 1, column 10",
+		"while parsing property at line 1, column 6",
+		"while parsing node at line 1, column 1",
+		"while parsing pipeline at line 1, column 1"
 	])]
 	#[case("node [n key=2,1]", &[
-		"0: at line 1:",
-		"node [n key=2,1]",
-		"             ^",
-		"expected ']', found ,",
-		"",
-		"1: at line 1, in parsing sources:",
+		"line 1, column 15: expected ']', found '1'",
 		"node [n key=2,1]",
-		"     ^",
-		"",
-		"2: at line 1, in parsing node:",
-		"node [n key=2,1]",
-		"^",
-		"",
-		"3: at line 1, in parsing pipeline:",
-		"node [n key=2,1]",
-		"^"
+		"              ^",
+		"while parsing sources at line 1, column 6",
+		"while parsing node at line 1, column 1",
+		"while parsing pipeline at line 1, column 1"
 	])]
 	#[case("node [n key=2]]", &[
-		"0: at line 1, in Eof:",
+		"line 1, column 15: expected end of pipeline, found ']'",
 		"node [n key=2]]",
 		"              ^"
 	])]
 	#[case("node [ ] [ ]", &[
-		"0: at line 1, in Eof:",
+		"line 1, column 10: expected end of pipeline, found '['",
 		"node [ ] [ ]",
 		"         ^"
 	])]
 	#[case("node [ a; b ]", &[
-		"0: at line 1:",
+		"line 1, column 9: expected ']', found ';'",
 		"node [ a; b ]",
 		"        ^",
-		"expected ']', found ;",
-		"",
-		"1: at line 1, in parsing sources:",
-		"node [ a; b ]",
-		"     ^",
-		"",
-		"2: at line 1, in parsing node:",
-		"node [ a; b ]",
-		"^",
-		"",
-		"3: at line 1, in parsing pipeline:",
-		"node [ a; b ]",
-		"^"
+		"while parsing sources at line 1, column 6",
+		"while parsing node at line 1, column 1",
+		"while parsing pipeline at line 1, column 1"
 	])]
 	#[case("node | | node", &[
-		"0: at line 1, in Eof:",
+		"line 1, column 6: expected end of pipeline, found '|'",
 		"node | | node",
 		"     ^"
 	])]
+	#[case("node\n\tkey=[1,\n\t2 3]", &[
+		"line 3, column 4: expected ']', found '3'",
+		"\t2 3]",
+		"\t  ^",
+		"while parsing array at line 2, column 6",
+		"while parsing property at line 2, column 2",
+		"while parsing node at line 1, column 1",
+		"while parsing pipeline at line 1, column 1"
+	])]
+	#[case("node mask=(a) mask=(b)", &[
+		"line 1, column 15: duplicate named source",
+		"node mask=(a) mask=(b)",
+		"              ^",
+		"while parsing node at line 1, column 1",
+		"while parsing pipeline at line 1, column 1"
+	])]
 	fn test_error_messages(#[case] vpl: &str, #[case] message: &[&str]) {
 		let error = parse_vpl(vpl).unwrap_err().chain().last().unwrap().to_string();
 		let lines = error.trim().split("\n").collect::<Vec<&str>>();
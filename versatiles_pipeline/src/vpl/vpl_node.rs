@@ -2,7 +2,8 @@
 //!
 //! This module defines [`VPLNode`], the parsed building block of the VersaTiles
 //! Pipeline Language (VPL). A node has a `name`, a multi-valued parameter map
-//! (`properties`), and zero or more child pipelines (`sources`). Helpers convert
+//! (`properties`), zero or more child pipelines (`sources`) and named secondary
+//! pipelines (`named_sources`). Helpers convert
 //! stringly-typed values to typed parameters with clear, contextual errors.
 
 use super::VPLPipeline;
@@ -25,6 +26,8 @@ pub struct VPLNode {
 	pub properties: BTreeMap<String, Vec<String>>,
 	/// Zero or more child pipelines (nested VPL blocks) used as this node's inputs.
 	pub sources: Vec<VPLPipeline>,
+	/// Named secondary pipelines, given as `name=( pipeline )`, e.g. the mask of a transform operation.
+	pub named_sources: BTreeMap<String, VPLPipeline>,
 }
 
 #[allow(dead_code)]
@@ -57,9 +60,33 @@ impl VPLNode {
 		})
 	}
 
-	/// Returns all property names present on this node.
+	/// Returns all property names present on this node, including the names of named sources.
 	pub fn get_property_names(&self) -> Vec<String> {
-		self.properties.keys().cloned().collect()
+		self
+			.properties
+			.keys()
+			.chain(self.named_sources.keys())
+			.cloned()
+			.collect()
+	}
+
+	/// Optional named source accessor; clones the pipeline when present.
+	///
+	/// Errors if `field` was given as a plain value instead of a pipeline in parentheses.
+	#[context("Failed to get optional named source '{field}' from VPL node '{}'", self.name)]
+	pub fn get_named_source_option(&self, field: &str) -> Result<Option<VPLPipeline>> {
+		ensure!(
+			!self.properties.contains_key(field),
+			"In operation '{}' the parameter '{field}' must be a pipeline in parentheses, e.g. {field}=( from_container filename=\"...\" ).",
+			self.name
+		);
+		Ok(self.named_sources.get(field).cloned())
+	}
+
+	/// Required named source accessor; errors if the field is missing.
+	#[context("Failed to get required named source '{field}' from VPL node '{}'", self.name)]
+	pub fn get_named_source_required(&self, field: &str) -> Result<VPLPipeline> {
+		self.required(field, self.get_named_source_option(field))
 	}
 
 	/// Attempts to parse `field` as an enum (`T: TryFrom<&str>`), returning `Ok(None)` if absent.
@@ -82,6 +109,16 @@ impl VPLNode {
 		})
 	}
 
+	/// Required enum parameter accessor; errors if the field is missing or not a valid value.
+	#[context("Failed to get required property enum '{field}' from VPL node '{}'", self.name)]
+	pub fn get_property_enum_required<'a, T>(&'a self, field: &str) -> Result<T>
+	where
+		T: TryFrom<&'a str>,
+		<T as TryFrom<&'a str>>::Error: std::fmt::Display + Send + Sync + 'static,
+	{
+		self.required(field, self.get_property_enum_option::<T>(field))
+	}
+
 	/// Optional string parameter accessor; clones the stored value when present.
 	#[context("Failed to get optional property string '{field}' from VPL node '{}'", self.name)]
 	pub fn get_property_string_option(&self, field: &str) -> Result<Option<String>> {
@@ -178,6 +215,7 @@ impl From<&str> for VPLNode {
 			name: name.to_string(),
 			properties: BTreeMap::new(),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		}
 	}
 }
@@ -189,6 +227,7 @@ impl From<(&str, (&str, &str))> for VPLNode {
 			name: input.0.to_string(),
 			properties: make_property(vec![input.1]),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		}
 	}
 }
@@ -200,6 +239,7 @@ impl From<(&str, Vec<(&str, &str)>)> for VPLNode {
 			name: input.0.to_string(),
 			properties: make_property(input.1),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		}
 	}
 }
@@ -211,6 +251,7 @@ impl From<(&str, Vec<(&str, &str)>, VPLPipeline)> for VPLNode {
 			name: input.0.to_string(),
 			properties: make_property(input.1),
 			sources: vec![input.2],
+			named_sources: BTreeMap::new(),
 		}
 	}
 }
@@ -222,6 +263,7 @@ impl From<(&str, Vec<(&str, &str)>, Vec<VPLPipeline>)> for VPLNode {
 			name: input.0.to_string(),
 			properties: make_property(input.1),
 			sources: input.2,
+			named_sources: BTreeMap::new(),
 		}
 	}
 }
@@ -236,6 +278,9 @@ impl Debug for VPLNode {
 		if !self.sources.is_empty() {
 			s.field("sources", &self.sources);
 		}
+		if !self.named_sources.is_empty() {
+			s.field("named_sources", &self.named_sources);
+		}
 		s.finish()
 	}
 }
@@ -258,6 +303,7 @@ fn make_property(input: Vec<(&str, &str)>) -> BTreeMap<String, Vec<String>> {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use versatiles_core::TileFormat;
 
 	#[test]
 	fn test_vplnode_get_property() {
@@ -265,6 +311,7 @@ mod tests {
 			name: "node".to_string(),
 			properties: make_property(vec![("key1", "value1"), ("key2", "value2")]),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		};
 		assert_eq!(node.get_property_vec("key1").unwrap(), &vec!["value1".to_string()]);
 		assert_eq!(node.get_property_vec("key2").unwrap(), &vec!["value2".to_string()]);
@@ -277,6 +324,7 @@ mod tests {
 			name: "node".to_string(),
 			properties: make_property(vec![("key1", "value1")]),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		};
 		assert_eq!(
 			node.get_property_string_option("key1").unwrap().unwrap(),
@@ -291,6 +339,7 @@ mod tests {
 			name: "node".to_string(),
 			properties: make_property(vec![("key1", "value1")]),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		};
 		assert_eq!(node.get_property_string_required("key1").unwrap(), "value1".to_string());
 		assert!(node.get_property_string_required("key2").is_err());
@@ -302,17 +351,36 @@ mod tests {
 			name: "node".to_string(),
 			properties: make_property(vec![("key1", "true"), ("key2", "0")]),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		};
 		assert!(node.get_property_bool_required("key1").unwrap());
 		assert!(!node.get_property_bool_required("key2").unwrap());
 	}
 
+	#[test]
+	fn test_vplnode_get_property_enum() {
+		let node = VPLNode {
+			name: "node".to_string(),
+			properties: make_property(vec![("key1", "png"), ("key2", "invalid")]),
+			sources: vec![],
+			named_sources: BTreeMap::new(),
+		};
+		assert_eq!(
+			node.get_property_enum_required::<TileFormat>("key1").unwrap(),
+			TileFormat::PNG
+		);
+		assert!(node.get_property_enum_option::<TileFormat>("key2").is_err());
+		assert!(node.get_property_enum_option::<TileFormat>("key3").unwrap().is_none());
+		assert!(node.get_property_enum_required::<TileFormat>("key3").is_err());
+	}
+
 	#[test]
 	fn test_vplnode_get_property_number() {
 		let node = VPLNode {
 			name: "node".to_string(),
 			properties: make_property(vec![("key1", "42"), ("key2", "invalid")]),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		};
 		assert_eq!(node.get_property_number_option::<i32>("key1").unwrap().unwrap(), 42);
 		assert!(node.get_property_number_option::<i32>("key2").is_err());
@@ -325,6 +393,7 @@ mod tests {
 			name: "node".to_string(),
 			properties: make_property(vec![("key1", "42")]),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		};
 		assert_eq!(node.get_property_number_required::<i32>("key1").unwrap(), 42);
 		assert!(node.get_property_number_required::<i32>("key2").is_err());
@@ -336,6 +405,7 @@ mod tests {
 			name: "node".to_string(),
 			properties: make_properties(vec![("key1", vec!["1", "2", "3", "4"])]),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		};
 		assert_eq!(
 			node
@@ -358,6 +428,7 @@ mod tests {
 			name: "node".to_string(),
 			properties: make_properties(vec![("key1", vec!["1", "2", "3", "4"])]),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		};
 		assert_eq!(
 			node.get_property_number_array_required::<i32, 4>("key1").unwrap(),
@@ -372,6 +443,7 @@ mod tests {
 			name: "node".to_string(),
 			properties: make_property(vec![("key1", "value1")]),
 			sources: vec![],
+			named_sources: BTreeMap::new(),
 		};
 		assert_eq!(
 			node.required("key1", Ok(Some("value1".to_string())))?,
@@ -381,6 +453,17 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_vplnode_get_named_source() -> Result<()> {
+		let node = VPLNode::try_from_str("node key=value mask=( child | filter )")?;
+		assert_eq!(node.get_property_names(), ["key", "mask"]);
+		assert_eq!(node.get_named_source_required("mask")?.len(), 2);
+		assert!(node.get_named_source_option("other")?.is_none());
+		assert!(node.get_named_source_required("other").is_err());
+		assert!(node.get_named_source_option("key").is_err());
+		Ok(())
+	}
+
 	#[test]
 	fn test_vplnode_from_str() {
 		fn run(vpl: &str) {
@@ -412,6 +495,7 @@ mod tests {
 			name: "test_node".to_string(),
 			properties: make_properties(vec![("key1", vec!["value1", "value2"]), ("key2", vec!["value3"])]),
 			sources: vec![VPLPipeline::default()],
+			named_sources: BTreeMap::new(),
 		};
 		let debug_str = format!("{node:?}");
 		assert!(debug_str.contains("VPLNode"));
@@ -35,6 +35,29 @@ impl VPLPipeline {
 		let first_element = self.pipeline.remove(0);
 		Ok((first_element, self.pipeline))
 	}
+
+	/// Returns a short VPL-like description, e.g. `from_container filename="a.pmtiles" | filter level_max="8"`,
+	/// to name the pipeline in error messages. Child pipelines are abbreviated as `[...]`.
+	pub fn describe(&self) -> String {
+		self
+			.pipeline
+			.iter()
+			.map(|node| {
+				let mut parts = vec![node.name.clone()];
+				for (key, values) in &node.properties {
+					match values.as_slice() {
+						[value] => parts.push(format!("{key}={value:?}")),
+						values => parts.push(format!("{key}=[{}]", values.join(","))),
+					}
+				}
+				if !node.sources.is_empty() {
+					parts.push("[...]".to_string());
+				}
+				parts.join(" ")
+			})
+			.collect::<Vec<_>>()
+			.join(" | ")
+	}
 }
 
 impl From<Vec<VPLNode>> for VPLPipeline {
@@ -54,3 +77,19 @@ impl Debug for VPLPipeline {
 		f.debug_list().entries(&self.pipeline).finish()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn describe() {
+		let pipeline = VPLPipeline::from_str(
+			"from_stacked [ from_container filename=\"a.pmtiles\", from_debug ] | filter bbox=[-11,-12,3,4] level_max=8",
+		);
+		assert_eq!(
+			pipeline.describe(),
+			"from_stacked [...] | filter bbox=[-11,-12,3,4] level_max=\"8\""
+		);
+	}
+}